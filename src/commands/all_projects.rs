@@ -0,0 +1,108 @@
+use futures::future::join_all;
+use std::path::Path;
+
+use crate::commands::run::{finish_ci_run, run_project, RunOptions};
+use crate::core::ci::RunCounts;
+use crate::core::projects::{host_groups, Project, ProjectConcurrency, ProjectsFile};
+use crate::core::{load_config, RunSummary};
+use crate::error::WorkSplitError;
+use crate::models::JobStatus;
+
+/// How one project's run went
+pub struct ProjectOutcome {
+    pub project: Project,
+    pub result: Result<Option<RunSummary>, WorkSplitError>,
+}
+
+/// Run the queues of every project in `worksplit-workspace.toml` and print
+/// one combined summary. Fails if any project couldn't be run.
+pub async fn run_all_projects(workspace_root: &Path, options: RunOptions) -> Result<Vec<ProjectOutcome>, WorkSplitError> {
+    let file = ProjectsFile::load(workspace_root)?;
+    let projects = file.projects(workspace_root)?;
+
+    let groups = match file.concurrency {
+        ProjectConcurrency::Sequential => vec![(0..projects.len()).collect()],
+        ProjectConcurrency::PerHost => {
+            let mut hosts = Vec::new();
+            for project in &projects {
+                let config = load_config(&project.root, options.profile.as_deref(), options.model.clone(),
+                    options.url.clone(), options.timeout, options.no_stream)?;
+                hosts.push(config.ollama.url);
+            }
+            host_groups(&hosts)
+        }
+    };
+    let mut options = options;
+    if groups.len() > 1 {
+        // Streams of concurrent projects would interleave token by token
+        options.no_stream = true;
+    }
+
+    let runs = groups.into_iter().map(|group| {
+        let (projects, options) = (&projects, &options);
+        async move {
+            let mut outcomes = Vec::new();
+            for index in group {
+                let project = &projects[index];
+                println!("\n=== Project {} ({}) ===", project.name, project.root.display());
+                let result = run_project(&project.root, options.clone()).await;
+                outcomes.push((index, ProjectOutcome { project: project.clone(), result }));
+            }
+            outcomes
+        }
+    });
+    let mut outcomes: Vec<(usize, ProjectOutcome)> = join_all(runs).await.into_iter().flatten().collect();
+    outcomes.sort_by_key(|(index, _)| *index);
+    let outcomes: Vec<ProjectOutcome> = outcomes.into_iter().map(|(_, outcome)| outcome).collect();
+
+    print_workspace_summary(&outcomes);
+
+    let errors = outcomes.iter().filter(|o| o.result.is_err()).count();
+    if errors > 0 {
+        return Err(WorkSplitError::JobError(format!("{} of {} projects could not be run", errors, outcomes.len())));
+    }
+    if options.ci {
+        let mut counts = RunCounts::default();
+        for summary in outcomes.iter().filter_map(|o| o.result.as_ref().ok()?.as_ref()) {
+            counts.add(RunCounts::of(summary));
+        }
+        finish_ci_run(counts);
+    }
+    Ok(outcomes)
+}
+
+fn print_workspace_summary(outcomes: &[ProjectOutcome]) {
+    let width = outcomes.iter().map(|o| o.project.name.len()).max().unwrap_or(0).max("Total".len());
+    let (mut processed, mut passed, mut failed, mut skipped) = (0, 0, 0, 0);
+
+    println!("\n=== Workspace Summary ===");
+    println!("{:<width$}  {:>9}  {:>6}  {:>6}  {:>7}", "Project", "Processed", "Passed", "Failed", "Skipped");
+    for outcome in outcomes {
+        match &outcome.result {
+            Ok(Some(summary)) => {
+                println!("{:<width$}  {:>9}  {:>6}  {:>6}  {:>7}", outcome.project.name,
+                    summary.processed, summary.passed, summary.failed, summary.skipped);
+                processed += summary.processed;
+                passed += summary.passed;
+                failed += summary.failed;
+                skipped += summary.skipped;
+            }
+            Ok(None) => println!("{:<width$}  (nothing run)", outcome.project.name),
+            Err(e) => println!("{:<width$}  error: {}", outcome.project.name, e.to_string().lines().next().unwrap_or_default()),
+        }
+    }
+    println!("{:<width$}  {:>9}  {:>6}  {:>6}  {:>7}", "Total", processed, passed, failed, skipped);
+
+    let failures: Vec<String> = outcomes.iter()
+        .filter_map(|o| o.result.as_ref().ok()?.as_ref().map(|summary| (o, summary)))
+        .flat_map(|(o, summary)| summary.results.iter()
+            .filter(|r| matches!(r.status, JobStatus::Fail | JobStatus::Partial))
+            .map(move |r| format!("  {}/{} [{:?}]: {}", o.project.name, r.job_id, r.status, r.error.as_deref().unwrap_or("no details"))))
+        .collect();
+    if !failures.is_empty() {
+        println!("\nFailed jobs:");
+        for line in failures {
+            println!("{}", line);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::path::Path;
+
+use crate::core::staging::StagingArea;
+use crate::error::WorkSplitError;
+use crate::models::JobStatus;
+
+/// Copy outputs staged by a read-only run into the project tree
+pub fn apply_staged(
+    project_root: &Path,
+    job_id: Option<&str>,
+    dry_run: bool,
+) -> Result<(), WorkSplitError> {
+    let staging = StagingArea::new(&project_root.join("jobs"));
+    let applied = staging.apply(project_root, job_id, dry_run)?;
+
+    if applied.is_empty() {
+        println!("Nothing staged to apply.");
+        return Ok(());
+    }
+
+    let verb = if dry_run { "Would apply" } else { "Applied" };
+    for file in &applied {
+        println!("  {} {}", verb, file.display());
+    }
+    println!("\n{} {} file(s).", verb, applied.len());
+    Ok(())
+}
+
+/// Print the staged outputs left by a read-only run
+pub fn print_staging_report(project_root: &Path) -> Result<(), WorkSplitError> {
+    let staging = StagingArea::new(&project_root.join("jobs"));
+    let jobs = staging.load_manifest()?;
+    if jobs.is_empty() {
+        return Ok(());
+    }
+
+    println!("\n=== Staged Outputs (read-only mode) ===");
+    println!("Nothing was written to the project tree. Staged in {}", staging.root().display());
+    for job in &jobs {
+        let status = match job.status {
+            JobStatus::Pass => "PASS",
+            JobStatus::Fail => "FAIL",
+            JobStatus::Partial => "PARTIAL",
+            _ => "PENDING",
+        };
+        println!("  {} [{}]", job.job_id, status);
+        for file in &job.files {
+            println!("    {}", file.display());
+        }
+    }
+    println!("\nReview the files, then run 'worksplit apply' (or 'worksplit apply --job <id>') to copy them into the project.");
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::Path;
 use chrono::{Duration, Utc};
 use tracing::info;
 
@@ -9,6 +9,10 @@ use crate::core::status::StatusManager;
 use crate::models::JobStatus;
 use crate::commands::cleanup::run_auto_cleanup;
 
+/// Folder inside jobs/ holding archived job files, with their status
+/// entries in its own `_jobstatus.json`
+pub const ARCHIVE_DIR: &str = "archive";
+
 /// Result of archive operation
 #[derive(Debug)]
 pub struct ArchiveResult {
@@ -16,67 +20,106 @@ pub struct ArchiveResult {
     pub archived_jobs: Vec<String>,
 }
 
-/// Archive completed jobs older than X days to jobs/archive/
+/// Which jobs `worksplit archive` moves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArchiveSelection {
+    /// Passed jobs last updated more than this many days ago (`None` uses
+    /// `[archive] days`)
+    OlderThan(Option<u32>),
+    /// Every passed job
+    Passed,
+    /// These jobs, which must have passed
+    Jobs(Vec<String>),
+}
+
+/// Status of the archived jobs, kept apart from the active queue
+pub fn archive_status(jobs_dir: &Path) -> Result<StatusManager, WorkSplitError> {
+    Ok(StatusManager::new(&jobs_dir.join(ARCHIVE_DIR))?)
+}
+
+/// Move completed jobs to jobs/archive/, where discovery doesn't look. Their
+/// status entries move to jobs/archive/_jobstatus.json; run history stays in
+/// place.
 pub fn archive_jobs(
-    project_root: &PathBuf,
-    days: Option<u32>,
+    project_root: &Path,
+    selection: &ArchiveSelection,
     dry_run: bool,
 ) -> Result<ArchiveResult, WorkSplitError> {
     let config = Config::load_from_dir(project_root)?;
     
     let jobs_dir = project_root.join("jobs");
-    let archive_dir = jobs_dir.join("archive");
+    let archive_dir = jobs_dir.join(ARCHIVE_DIR);
     
-    let status_manager = StatusManager::new(&jobs_dir)?;
+    let mut status_manager = StatusManager::new(&jobs_dir)?;
     
-    let threshold_days = days.unwrap_or(config.archive.days);
-    let cutoff = Utc::now() - Duration::days(threshold_days as i64);
+    let selected: Vec<(String, String)> = match selection {
+        ArchiveSelection::Jobs(ids) => {
+            let mut selected = Vec::new();
+            for id in ids {
+                if !jobs_dir.join(format!("{}.md", id)).exists() {
+                    return Err(WorkSplitError::JobError(format!("No job named '{}'", id)));
+                }
+                let status = status_manager.get(id).map_or(JobStatus::Created, |entry| entry.status);
+                if status != JobStatus::Pass {
+                    return Err(WorkSplitError::JobError(format!(
+                        "Job '{}' hasn't passed ({:?}); only completed jobs are archived", id, status)));
+                }
+                selected.push((id.clone(), String::new()));
+            }
+            selected
+        }
+        ArchiveSelection::Passed | ArchiveSelection::OlderThan(_) => {
+            let cutoff = match selection {
+                ArchiveSelection::OlderThan(days) => {
+                    let threshold_days = days.unwrap_or(config.archive.days);
+                    Some(Utc::now() - Duration::days(threshold_days as i64))
+                }
+                _ => None,
+            };
+            let mut entries: Vec<_> = status_manager.all_entries().into_iter()
+                .filter(|entry| entry.status == JobStatus::Pass)
+                .filter(|entry| cutoff.is_none_or(|cutoff| entry.updated_at < cutoff))
+                .filter(|entry| jobs_dir.join(format!("{}.md", entry.id)).exists())
+                .map(|entry| (entry.id.clone(), format!(" ({} days old)", (Utc::now() - entry.updated_at).num_days())))
+                .collect();
+            entries.sort();
+            entries
+        }
+    };
     
-    let mut archived_count = 0;
+    let mut archived = if dry_run || selected.is_empty() { None } else { Some(archive_status(&jobs_dir)?) };
     let mut archived_jobs = Vec::new();
     
-    for entry in status_manager.all_entries() {
-        if entry.status != JobStatus::Pass {
-            continue;
-        }
-        
-        if entry.updated_at < cutoff {
-            let job_file = jobs_dir.join(format!("{}.md", entry.id));
-            if !job_file.exists() {
-                continue;
+    for (id, age) in selected {
+        if let Some(archived) = &mut archived {
+            fs::create_dir_all(&archive_dir)?;
+            fs::rename(jobs_dir.join(format!("{}.md", id)), archive_dir.join(format!("{}.md", id)))?;
+            if let Some(entry) = status_manager.get(&id).cloned() {
+                archived.import_entries(vec![entry])?;
             }
-            
-            let days_old = (Utc::now() - entry.updated_at).num_days();
-            
-            if dry_run {
-                info!("Would archive: {} ({} days old)", entry.id, days_old);
-            } else {
-                fs::create_dir_all(&archive_dir)?;
-                let archive_file = archive_dir.join(format!("{}.md", entry.id));
-                fs::rename(&job_file, &archive_file)?;
-                info!("Archived: {} ({} days old)", entry.id, days_old);
-            }
-            
-            archived_count += 1;
-            archived_jobs.push(entry.id.clone());
+            status_manager.remove_job(&id)?;
+            info!("Archived: {}{}", id, age);
+        } else {
+            info!("Would archive: {}{}", id, age);
         }
+        archived_jobs.push(id);
     }
     
     Ok(ArchiveResult {
-        archived_count,
+        archived_count: archived_jobs.len(),
         archived_jobs,
     })
 }
 
 /// Run automatic archive if enabled in config
-pub fn run_auto_archive(project_root: &PathBuf) -> Result<(), WorkSplitError> {
+pub fn run_auto_archive(project_root: &Path) -> Result<(), WorkSplitError> {
     let config = Config::load_from_dir(project_root)?;
     
     if !config.archive.enabled {
         return Ok(());
     }
     
-    let result = archive_jobs(project_root, None, false)?;
+    let result = archive_jobs(project_root, &ArchiveSelection::OlderThan(None), false)?;
     
     if result.archived_count > 0 {
         info!("Archived {} completed job(s)", result.archived_count);
@@ -85,4 +128,43 @@ pub fn run_auto_archive(project_root: &PathBuf) -> Result<(), WorkSplitError> {
     run_auto_cleanup(project_root)?;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_archive_keeps_status_apart() {
+        let temp = TempDir::new().unwrap();
+        let jobs_dir = temp.path().join("jobs");
+        fs::create_dir_all(&jobs_dir).unwrap();
+        for id in ["001_done", "002_open"] {
+            fs::write(jobs_dir.join(format!("{}.md", id)), "---\noutput_file: a.rs\n---\n").unwrap();
+        }
+        let mut status = StatusManager::new(&jobs_dir).unwrap();
+        status.sync_with_jobs(&["001_done".to_string(), "002_open".to_string()]).unwrap();
+        status.update_status("001_done", JobStatus::Pass).unwrap();
+
+        let refused = archive_jobs(temp.path(), &ArchiveSelection::Jobs(vec!["002_open".into()]), false).unwrap_err();
+        assert!(refused.to_string().contains("hasn't passed"), "{}", refused);
+
+        let preview = archive_jobs(temp.path(), &ArchiveSelection::Passed, true).unwrap();
+        assert_eq!(preview.archived_jobs, vec!["001_done"]);
+        assert!(jobs_dir.join("001_done.md").exists());
+
+        // A fresh job isn't old enough for the age threshold
+        assert_eq!(archive_jobs(temp.path(), &ArchiveSelection::OlderThan(None), false).unwrap().archived_count, 0);
+
+        archive_jobs(temp.path(), &ArchiveSelection::Passed, false).unwrap();
+        assert!(jobs_dir.join("archive/001_done.md").exists());
+        assert!(!jobs_dir.join("001_done.md").exists());
+
+        let status = StatusManager::new(&jobs_dir).unwrap();
+        assert!(status.get("001_done").is_none());
+        assert!(status.get("002_open").is_some());
+        let archived = archive_status(&jobs_dir).unwrap();
+        assert_eq!(archived.get("001_done").unwrap().status, JobStatus::Pass);
+    }
+}
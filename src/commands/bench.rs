@@ -0,0 +1,310 @@
+//! `worksplit bench`: run reference jobs against several models
+//!
+//! Like `worksplit experiment`, every run happens in its own copy of the
+//! project with a fresh status file, so the project is never touched. Each
+//! model is warmed up before its first run, so load time doesn't count
+//! against it. The comparison is printed and saved to `jobs/_bench/`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use tracing::info;
+
+use crate::commands::experiment::{seconds, status_label, ProjectCopy};
+use crate::core::mock_fixtures::MockFixtures;
+use crate::core::{load_config, JobsManager, Runner};
+use crate::error::WorkSplitError;
+use crate::models::{JobStatus, NotifyConfig, StatusWebhookConfig};
+
+/// Benchmark reports folder inside the jobs folder
+pub const BENCH_DIR: &str = "_bench";
+
+/// Bench options
+#[derive(Debug, Default)]
+pub struct BenchOptions {
+    /// Models to compare; a bare tag such as `14b` reuses the name of the
+    /// model before it (`qwen2.5-coder:7b,14b`)
+    pub models: Vec<String>,
+    /// Job selectors (`tag:<name>`, `bench_*`, job IDs)
+    pub jobs: Vec<String>,
+    /// Times each job runs per model
+    pub runs: usize,
+    /// URL override
+    pub url: Option<String>,
+    /// Timeout override
+    pub timeout: Option<u64>,
+    /// Serve model responses from this fixture directory instead of Ollama
+    pub mock: Option<PathBuf>,
+}
+
+/// One run of one job on one model
+#[derive(Debug, Clone)]
+pub struct BenchOutcome {
+    pub job_id: String,
+    pub status: JobStatus,
+    pub duration: Duration,
+    /// LLM tokens (prompt + generated) the job used
+    pub tokens: u64,
+    pub error: Option<String>,
+}
+
+/// Results of one model
+#[derive(Debug, Clone)]
+pub struct ModelReport {
+    pub model: String,
+    pub outcomes: Vec<BenchOutcome>,
+}
+
+impl ModelReport {
+    pub fn passed(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.status == JobStatus::Pass).count()
+    }
+
+    /// Share of runs that passed, 0.0 to 1.0
+    pub fn pass_rate(&self) -> f64 {
+        if self.outcomes.is_empty() {
+            return 0.0;
+        }
+        self.passed() as f64 / self.outcomes.len() as f64
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        self.outcomes.iter().map(|o| o.duration).sum()
+    }
+
+    pub fn tokens(&self) -> u64 {
+        self.outcomes.iter().map(|o| o.tokens).sum()
+    }
+
+    /// Tokens per second of wall time, over all runs
+    pub fn tokens_per_second(&self) -> f64 {
+        let secs = self.total_duration().as_secs_f64();
+        if secs == 0.0 { 0.0 } else { self.tokens() as f64 / secs }
+    }
+
+    fn job_outcomes<'a>(&'a self, job_id: &'a str) -> impl Iterator<Item = &'a BenchOutcome> + 'a {
+        self.outcomes.iter().filter(move |o| o.job_id == job_id)
+    }
+}
+
+/// Results of a benchmark, one entry per model in the order given
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub selectors: Vec<String>,
+    pub jobs: Vec<String>,
+    pub runs: usize,
+    pub models: Vec<ModelReport>,
+}
+
+impl BenchReport {
+    /// The comparison as a markdown document
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Model Benchmark\n\n");
+        out.push_str(&format!("- Jobs: {} ({})\n", self.jobs.len(), self.selectors.join(", ")));
+        out.push_str(&format!("- Runs per job: {}\n\n", self.runs));
+
+        out.push_str("| Model | Passed | Pass rate | Total time | Mean time | Tokens | Tokens/s |\n");
+        out.push_str("|-------|--------|-----------|------------|-----------|--------|----------|\n");
+        for model in &self.models {
+            let mean = model.total_duration().checked_div(model.outcomes.len() as u32).unwrap_or_default();
+            out.push_str(&format!(
+                "| {} | {}/{} | {:.0}% | {} | {} | {} | {:.1} |\n",
+                model.model,
+                model.passed(),
+                model.outcomes.len(),
+                model.pass_rate() * 100.0,
+                seconds(model.total_duration()),
+                seconds(mean),
+                model.tokens(),
+                model.tokens_per_second()
+            ));
+        }
+
+        out.push_str("\n## Per Job\n\n| Job |");
+        for model in &self.models {
+            out.push_str(&format!(" {} |", model.model));
+        }
+        out.push_str("\n|-----|");
+        out.push_str(&"---|".repeat(self.models.len()));
+        out.push('\n');
+        for job_id in &self.jobs {
+            out.push_str(&format!("| {} |", job_id));
+            for model in &self.models {
+                let outcomes: Vec<&BenchOutcome> = model.job_outcomes(job_id).collect();
+                let passed = outcomes.iter().filter(|o| o.status == JobStatus::Pass).count();
+                let total: Duration = outcomes.iter().map(|o| o.duration).sum();
+                let mean = total.checked_div(outcomes.len() as u32).unwrap_or_default();
+                if outcomes.len() == 1 {
+                    out.push_str(&format!(" {} ({}) |", status_label(outcomes[0].status), seconds(mean)));
+                } else {
+                    out.push_str(&format!(" {}/{} ({} mean) |", passed, outcomes.len(), seconds(mean)));
+                }
+            }
+            out.push('\n');
+        }
+
+        let errors: Vec<String> = self
+            .models
+            .iter()
+            .flat_map(|model| {
+                model.outcomes.iter().filter_map(move |o| {
+                    o.error.as_ref().map(|e| format!("- {} / {}: {}", model.model, o.job_id, e.lines().next().unwrap_or_default()))
+                })
+            })
+            .collect();
+        if !errors.is_empty() {
+            out.push_str("\n## Errors\n\n");
+            out.push_str(&errors.join("\n"));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Save the report to `jobs/_bench/bench-<timestamp>.md`
+    pub fn save(&self, project_root: &Path) -> Result<PathBuf, WorkSplitError> {
+        let dir = project_root.join("jobs").join(BENCH_DIR);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("bench-{}.md", chrono::Utc::now().format("%Y%m%d-%H%M%S")));
+        fs::write(&path, self.to_markdown())?;
+        Ok(path)
+    }
+}
+
+/// Full model names: a bare tag starting with a digit (`14b`) takes the
+/// name of the model before it
+pub fn expand_models(models: &[String]) -> Vec<String> {
+    let mut expanded: Vec<String> = Vec::new();
+    for model in models.iter().map(|m| m.trim()).filter(|m| !m.is_empty()) {
+        let base = expanded.last().and_then(|previous| previous.split_once(':')).map(|(name, _)| name);
+        match base {
+            Some(name) if !model.contains(':') && model.starts_with(|c: char| c.is_ascii_digit()) => {
+                expanded.push(format!("{}:{}", name, model));
+            }
+            _ => expanded.push(model.to_string()),
+        }
+    }
+    expanded
+}
+
+/// Run the selected jobs `runs` times on each model and compare the results
+pub async fn run_bench(project_root: &Path, options: BenchOptions) -> Result<BenchReport, WorkSplitError> {
+    let models = expand_models(&options.models);
+    if models.is_empty() {
+        return Err(WorkSplitError::ConfigError("No models given".to_string()));
+    }
+    if options.jobs.is_empty() {
+        return Err(WorkSplitError::ConfigError("No benchmark job given (--job)".to_string()));
+    }
+    let runs = options.runs.max(1);
+
+    let mut config = load_config(project_root, None, None, options.url, options.timeout, true)?;
+    // Failures and status changes in throwaway copies aren't worth a
+    // notification
+    config.notify = NotifyConfig::default();
+    config.status_webhook = StatusWebhookConfig::default();
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), config.limits.clone());
+    let jobs = jobs_manager.select_jobs(&options.jobs)?;
+    if jobs.is_empty() {
+        return Err(WorkSplitError::JobError(format!("No jobs match {}", options.jobs.join(", "))));
+    }
+
+    let mut reports = Vec::new();
+    for model in models {
+        info!("Benchmarking {}: {} job(s), {} run(s)", model, jobs.len(), runs);
+        let mut config = config.clone();
+        config.ollama.model = model.clone();
+
+        let mut outcomes = Vec::new();
+        for run in 0..runs {
+            // A fresh copy per run, so no run sees another's outputs
+            let workspace = ProjectCopy::new(project_root)?;
+            let mut runner = Runner::new(config.clone(), workspace.path().to_path_buf())?;
+            match &options.mock {
+                Some(dir) => runner.set_mock_fixtures(MockFixtures::new(project_root.join(dir))?),
+                None if run == 0 => runner.warm_up().await,
+                None => {}
+            }
+
+            for job_id in &jobs {
+                let (started, tokens_before) = (Instant::now(), runner.tokens_used());
+                let (status, error) = match runner.run_single(job_id).await {
+                    Ok(result) => (result.status, result.error),
+                    Err(e) => (JobStatus::Fail, Some(e.to_string())),
+                };
+                let outcome = BenchOutcome {
+                    job_id: job_id.clone(),
+                    status,
+                    duration: started.elapsed(),
+                    tokens: runner.tokens_used() - tokens_before,
+                    error,
+                };
+                println!(
+                    "  {} / {} (run {}): {} ({}, {} tokens)",
+                    model,
+                    job_id,
+                    run + 1,
+                    status_label(outcome.status),
+                    seconds(outcome.duration),
+                    outcome.tokens
+                );
+                outcomes.push(outcome);
+            }
+        }
+        reports.push(ModelReport { model, outcomes });
+    }
+
+    Ok(BenchReport { selectors: options.jobs, jobs, runs, models: reports })
+}
+
+/// `worksplit bench`: run, print the comparison and save it
+pub async fn bench(project_root: &Path, options: BenchOptions) -> Result<(), WorkSplitError> {
+    let report = run_bench(project_root, options).await?;
+    println!("\n{}", report.to_markdown());
+    let path = report.save(project_root)?;
+    println!("Report saved to {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(job_id: &str, status: JobStatus, secs: u64, tokens: u64) -> BenchOutcome {
+        BenchOutcome { job_id: job_id.to_string(), status, duration: Duration::from_secs(secs), tokens, error: None }
+    }
+
+    #[test]
+    fn test_expand_models() {
+        let models = ["qwen2.5-coder:7b", "14b", "llama3", "8b", "deepseek-coder:6.7b"].map(String::from);
+        assert_eq!(expand_models(&models), ["qwen2.5-coder:7b", "qwen2.5-coder:14b", "llama3", "8b", "deepseek-coder:6.7b"]);
+    }
+
+    #[test]
+    fn test_bench_report_markdown() {
+        let mut failed = outcome("bench_api", JobStatus::Fail, 6, 3000);
+        failed.error = Some("Verification failed: stub\nmore".to_string());
+        let report = BenchReport {
+            selectors: vec!["bench_api".to_string()],
+            jobs: vec!["bench_api".to_string()],
+            runs: 2,
+            models: vec![
+                ModelReport {
+                    model: "qwen2.5-coder:7b".to_string(),
+                    outcomes: vec![outcome("bench_api", JobStatus::Pass, 2, 1000), failed],
+                },
+                ModelReport {
+                    model: "qwen2.5-coder:14b".to_string(),
+                    outcomes: vec![outcome("bench_api", JobStatus::Pass, 4, 1000), outcome("bench_api", JobStatus::Pass, 6, 1000)],
+                },
+            ],
+        };
+        assert_eq!(report.models[0].pass_rate(), 0.5);
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("| qwen2.5-coder:7b | 1/2 | 50% | 8.0s | 4.0s | 4000 | 500.0 |"), "{}", markdown);
+        assert!(markdown.contains("| qwen2.5-coder:14b | 2/2 | 100% | 10.0s | 5.0s | 2000 | 200.0 |"), "{}", markdown);
+        assert!(markdown.contains("| bench_api | 1/2 (4.0s mean) | 2/2 (5.0s mean) |"), "{}", markdown);
+        assert!(markdown.contains("- qwen2.5-coder:7b / bench_api: Verification failed: stub\n"), "{}", markdown);
+    }
+}
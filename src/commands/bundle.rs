@@ -0,0 +1,506 @@
+//! `worksplit export` / `worksplit import`: move a workload between machines
+//!
+//! A bundle is a `.tar.gz` holding job files, system prompts, prompt
+//! templates, `worksplit.toml` and snapshots of the files the jobs read.
+//! For jobs that have already run it also holds their outputs and status
+//! entries, so the same commands carry results back:
+//!
+//! ```text
+//! laptop$ worksplit export tag:backend -o backend.tar.gz
+//! gpu$    worksplit import backend.tar.gz && worksplit run
+//! gpu$    worksplit export tag:backend -o results.tar.gz
+//! laptop$ worksplit import results.tar.gz
+//! ```
+
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::core::atomic_write::write_atomic;
+use crate::core::freeze::FrozenManifest;
+use crate::core::output_paths::{check_protected_path, check_write_path, normalize_relative};
+use crate::core::prompt_templates::TEMPLATES_DIR;
+use crate::core::{JobsManager, StatusManager, CONVENTIONS_FILE};
+use crate::error::WorkSplitError;
+use crate::models::{Config, Job, JobStatus, JobStatusEntry, OutputMode};
+
+/// Manifest file name inside a bundle
+const MANIFEST_FILE: &str = "worksplit-bundle.json";
+
+/// Bundle format version
+const BUNDLE_VERSION: u32 = 1;
+
+/// Why a file is in the bundle; decides how `import` treats it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleRole {
+    /// A job file (always imported)
+    Job,
+    /// A system prompt or prompt template (always imported)
+    Prompt,
+    /// `worksplit.toml` (imported when missing locally)
+    Config,
+    /// A file a job reads (imported when missing locally)
+    Context,
+    /// A file a job that already ran wrote or edited (always imported)
+    Output,
+}
+
+impl BundleRole {
+    /// Whether importing replaces a different local copy
+    fn replaces_local(self) -> bool {
+        matches!(self, BundleRole::Job | BundleRole::Prompt | BundleRole::Output)
+    }
+
+    /// Whether a file of this role can live at `path`: jobs and prompts in
+    /// jobs/, the config at the root, and everything else outside
+    /// WorkSplit's own `jobs/_*` files
+    fn allows(self, path: &Path) -> bool {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let in_jobs = path.parent() == Some(Path::new("jobs"));
+        match self {
+            BundleRole::Job => in_jobs && !name.starts_with('_') && name.ends_with(".md"),
+            BundleRole::Prompt => {
+                (in_jobs && ((name.starts_with("_systemprompt") && name.ends_with(".md")) || name == CONVENTIONS_FILE))
+                    || path.parent() == Some(&Path::new("jobs").join(TEMPLATES_DIR))
+            }
+            BundleRole::Config => path == Path::new("worksplit.toml"),
+            BundleRole::Context | BundleRole::Output => !(path.starts_with("jobs") && name.starts_with('_')),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleFile {
+    /// Path relative to the project root
+    pub path: PathBuf,
+    pub role: BundleRole,
+}
+
+/// `worksplit-bundle.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub jobs: Vec<String>,
+    pub files: Vec<BundleFile>,
+    /// Status entries of the jobs that have run
+    pub status: Vec<JobStatusEntry>,
+}
+
+/// What `import_bundle` did
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub jobs: Vec<String>,
+    pub written: Vec<PathBuf>,
+    /// Local files that differ from the bundle and were kept
+    pub kept: Vec<PathBuf>,
+    pub status_entries: usize,
+}
+
+/// Whether a job has produced outputs worth carrying back
+fn has_run(entry: Option<&JobStatusEntry>) -> bool {
+    entry.is_some_and(|e| e.ran || !matches!(e.status, JobStatus::Created))
+}
+
+/// Files `job` reads and files it writes or edits
+fn job_files(jobs_manager: &mut JobsManager, job: &Job) -> Result<(Vec<PathBuf>, Vec<PathBuf>), WorkSplitError> {
+    let metadata = &job.metadata;
+    let mut reads = metadata.context_files.clone();
+    reads.extend(jobs_manager.resolve_context_symbols(job)?);
+    reads.extend(metadata.target_file.clone());
+
+    let writes = match metadata.mode {
+        OutputMode::Edit | OutputMode::ReplacePattern | OutputMode::UpdateFixtures => jobs_manager.resolve_target_files(job)?,
+        _ => metadata.get_output_files().into_iter().chain(metadata.test_path()).collect(),
+    };
+    Ok((reads, writes))
+}
+
+/// Add `path` (relative to the project root) if it exists and isn't in yet
+fn add_file(files: &mut BTreeMap<PathBuf, BundleRole>, project_root: &Path, path: &Path, role: BundleRole) {
+    let Some(relative) = normalize_relative(path) else {
+        warn!("Not bundling {}: outside the project", path.display());
+        return;
+    };
+    if project_root.join(&relative).is_file() {
+        files.entry(relative).or_insert(role);
+    }
+}
+
+/// Write a bundle of the jobs matching `selectors` (all jobs when empty)
+pub fn export_bundle(project_root: &Path, selectors: &[String], output: &Path) -> Result<BundleManifest, WorkSplitError> {
+    let config = Config::load_from_dir(project_root).unwrap_or_default();
+    let mut jobs_manager = JobsManager::new(project_root.to_path_buf(), config.limits);
+    if !jobs_manager.jobs_folder_exists() {
+        return Err(WorkSplitError::JobsFolderNotFound(project_root.join("jobs")));
+    }
+    let job_ids = if selectors.is_empty() {
+        jobs_manager.discover_jobs()?
+    } else {
+        jobs_manager.select_jobs(selectors)?
+    };
+    if job_ids.is_empty() {
+        return Err(WorkSplitError::JobError("No jobs to export".to_string()));
+    }
+
+    let jobs_dir = jobs_manager.jobs_dir().to_path_buf();
+    let jobs_folder = jobs_dir.strip_prefix(project_root).unwrap_or(Path::new("jobs")).to_path_buf();
+    let mut status_manager = StatusManager::new(&jobs_dir)?;
+    status_manager.sync_with_jobs(&jobs_manager.discover_jobs()?)?;
+
+    let mut files: BTreeMap<PathBuf, BundleRole> = BTreeMap::new();
+    let mut status = Vec::new();
+    for id in &job_ids {
+        add_file(&mut files, project_root, &jobs_folder.join(format!("{}.md", id)), BundleRole::Job);
+        let job = match jobs_manager.parse_job(id) {
+            Ok(job) => job,
+            // A half-written draft travels as just its file
+            Err(_) if jobs_manager.is_draft(id) => continue,
+            Err(e) => return Err(WorkSplitError::JobError(format!("Can't export job '{}': {}", id, e))),
+        };
+
+        let entry = status_manager.get(id);
+        let (reads, writes) = job_files(&mut jobs_manager, &job)?;
+        let (outputs, inputs) = if has_run(entry) { (writes, reads) } else { (Vec::new(), [reads, writes].concat()) };
+        // Outputs first: a file one job wrote and another reads is a result
+        for path in outputs {
+            add_file(&mut files, project_root, &path, BundleRole::Output);
+        }
+        for path in inputs {
+            add_file(&mut files, project_root, &path, BundleRole::Context);
+        }
+        if has_run(entry) {
+            status.extend(entry.cloned());
+        }
+    }
+
+    // Prompts, conventions and templates apply to every job
+    for entry in fs::read_dir(&jobs_dir)? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        if (name.starts_with("_systemprompt") && name.ends_with(".md")) || name == CONVENTIONS_FILE {
+            add_file(&mut files, project_root, &jobs_folder.join(name), BundleRole::Prompt);
+        }
+    }
+    if let Ok(entries) = fs::read_dir(jobs_dir.join(TEMPLATES_DIR)) {
+        for entry in entries.flatten() {
+            let path = jobs_folder.join(TEMPLATES_DIR).join(entry.file_name());
+            add_file(&mut files, project_root, &path, BundleRole::Prompt);
+        }
+    }
+    add_file(&mut files, project_root, Path::new("worksplit.toml"), BundleRole::Config);
+
+    let manifest = BundleManifest {
+        version: BUNDLE_VERSION,
+        created_at: Utc::now(),
+        jobs: job_ids,
+        files: files.into_iter().map(|(path, role)| BundleFile { path, role }).collect(),
+        status,
+    };
+    write_bundle(project_root, &manifest, output)?;
+    Ok(manifest)
+}
+
+fn write_bundle(project_root: &Path, manifest: &BundleManifest, output: &Path) -> Result<(), WorkSplitError> {
+    let append = |builder: &mut tar::Builder<GzEncoder<File>>, path: &Path, content: &[u8]| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(manifest.created_at.timestamp().max(0) as u64);
+        header.set_cksum();
+        builder.append_data(&mut header, path, content)
+    };
+
+    let mut builder = tar::Builder::new(GzEncoder::new(File::create(output)?, Compression::default()));
+    let manifest_json = serde_json::to_vec_pretty(manifest).map_err(|e| WorkSplitError::IoError(e.to_string()))?;
+    append(&mut builder, Path::new(MANIFEST_FILE), &manifest_json)?;
+    for file in &manifest.files {
+        let content = fs::read(project_root.join(&file.path))?;
+        append(&mut builder, &file.path, &content)?;
+    }
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// Read every file of a bundle into memory, manifest first
+fn read_bundle(bundle: &Path) -> Result<(BundleManifest, BTreeMap<PathBuf, Vec<u8>>), WorkSplitError> {
+    let invalid = |message: String| WorkSplitError::InvalidBundle { path: bundle.to_path_buf(), message };
+
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(bundle)?));
+    let mut contents = BTreeMap::new();
+    for entry in archive.entries().map_err(|e| invalid(e.to_string()))? {
+        let mut entry = entry.map_err(|e| invalid(e.to_string()))?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path().map_err(|e| invalid(e.to_string()))?.to_path_buf();
+        let path = normalize_relative(&path).ok_or_else(|| invalid(format!("unsafe path {}", path.display())))?;
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content).map_err(|e| invalid(e.to_string()))?;
+        contents.insert(path, content);
+    }
+
+    let manifest = contents
+        .remove(Path::new(MANIFEST_FILE))
+        .ok_or_else(|| invalid(format!("no {}", MANIFEST_FILE)))?;
+    let manifest: BundleManifest = serde_json::from_slice(&manifest).map_err(|e| invalid(e.to_string()))?;
+    if manifest.version > BUNDLE_VERSION {
+        return Err(invalid(format!("bundle version {} is newer than this worksplit supports", manifest.version)));
+    }
+    Ok((manifest, contents))
+}
+
+/// Refuse to import a project file the runner wouldn't write: outside
+/// `[safety] allowed_output_dirs` (outputs only), under `protected_paths`,
+/// or frozen (unless `force`)
+fn check_import(project_root: &Path, config: &Config, frozen: &FrozenManifest, file: &BundleFile, force: bool) -> Result<(), WorkSplitError> {
+    let target = project_root.join(&file.path);
+    if file.role == BundleRole::Output {
+        check_write_path(project_root, &target, &config.safety.allowed_output_dirs)?;
+    }
+    check_protected_path(project_root, &target, &config.safety.protected_paths)?;
+    if let Some(frozen) = frozen.get(&file.path) {
+        if !force {
+            return Err(WorkSplitError::FrozenOutput { path: file.path.clone(), job_id: frozen.job_id.clone() });
+        }
+        warn!("Overwriting {}, frozen with job '{}' (--force)", file.path.display(), frozen.job_id);
+    }
+    Ok(())
+}
+
+/// Unpack a bundle into the project. Job files, prompts and outputs replace
+/// local copies; context files and `worksplit.toml` only do so with
+/// `overwrite`. Files are checked against the local `[safety]` settings and
+/// frozen files (`force` overwrites those) before anything is written.
+/// Status entries in the bundle replace local ones.
+pub fn import_bundle(project_root: &Path, bundle: &Path, overwrite: bool, force: bool) -> Result<ImportReport, WorkSplitError> {
+    let (manifest, mut contents) = read_bundle(bundle)?;
+    let mut report = ImportReport { jobs: manifest.jobs.clone(), ..Default::default() };
+    let config = Config::load_from_dir(project_root)?;
+    let frozen = FrozenManifest::load(&project_root.join("jobs"))?;
+
+    let mut writes = Vec::new();
+    for file in &manifest.files {
+        let invalid = |message: String| WorkSplitError::InvalidBundle { path: bundle.to_path_buf(), message };
+        let content = contents
+            .remove(&file.path)
+            .ok_or_else(|| invalid(format!("{} is listed but missing", file.path.display())))?;
+        if !file.role.allows(&file.path) {
+            return Err(invalid(format!("{} can't be imported as a {:?} file", file.path.display(), file.role)));
+        }
+        let target = project_root.join(&file.path);
+        match fs::read(&target) {
+            Ok(local) if local == content => continue,
+            Ok(_) if !overwrite && !file.role.replaces_local() => {
+                report.kept.push(file.path.clone());
+                continue;
+            }
+            _ => {}
+        }
+        if matches!(file.role, BundleRole::Output | BundleRole::Context) {
+            check_import(project_root, &config, &frozen, file, force)?;
+        }
+        writes.push((file.path.clone(), target, content));
+    }
+
+    for (path, target, content) in writes {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(&target, &content)?;
+        report.written.push(path);
+    }
+
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), Default::default());
+    let mut status_manager = StatusManager::new(jobs_manager.jobs_dir())?;
+    status_manager.sync_with_jobs(&jobs_manager.discover_jobs()?)?;
+    report.status_entries = manifest.status.len();
+    status_manager.import_entries(manifest.status)?;
+    Ok(report)
+}
+
+/// `worksplit export`
+pub fn export_jobs(project_root: &Path, selectors: &[String], output: &Path) -> Result<(), WorkSplitError> {
+    let manifest = export_bundle(project_root, selectors, output)?;
+    let count = |role: BundleRole| manifest.files.iter().filter(|f| f.role == role).count();
+    println!("Exported {} job(s) to {}", manifest.jobs.len(), output.display());
+    println!(
+        "  {} context file(s), {} output(s), {} status entr{}",
+        count(BundleRole::Context),
+        count(BundleRole::Output),
+        manifest.status.len(),
+        if manifest.status.len() == 1 { "y" } else { "ies" }
+    );
+    Ok(())
+}
+
+/// `worksplit import`
+pub fn import_jobs(project_root: &Path, bundle: &Path, overwrite: bool, force: bool) -> Result<(), WorkSplitError> {
+    let report = import_bundle(project_root, bundle, overwrite, force)?;
+    println!("Imported {} job(s) from {}", report.jobs.len(), bundle.display());
+    for path in &report.written {
+        println!("  wrote {}", path.display());
+    }
+    if report.status_entries > 0 {
+        println!("  {} status entr{} updated", report.status_entries, if report.status_entries == 1 { "y" } else { "ies" });
+    }
+    if !report.kept.is_empty() {
+        println!("\nKept {} local file(s) that differ from the bundle (use --overwrite to replace):", report.kept.len());
+        for path in &report.kept {
+            println!("  {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("jobs")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("jobs/_systemprompt_create.md"), "create").unwrap();
+        fs::write(root.join("jobs/_systemprompt_verify.md"), "verify").unwrap();
+        fs::write(root.join("worksplit.toml"), "[ollama]\nmodel = \"small\"\n").unwrap();
+        temp
+    }
+
+    #[test]
+    fn test_export_then_import_results_back() {
+        let laptop = project();
+        let root = laptop.path();
+        fs::write(root.join("src/models.rs"), "pub struct User;\n").unwrap();
+        fs::write(
+            root.join("jobs/001_service.md"),
+            "---\ntags: [backend]\ncontext_files: [src/models.rs]\noutput_dir: src/\noutput_file: service.rs\n---\n\nService\n",
+        )
+        .unwrap();
+        fs::write(root.join("jobs/002_other.md"), "---\noutput_dir: src/\noutput_file: other.rs\n---\n\nOther\n").unwrap();
+
+        // Laptop -> GPU machine: the tagged job, its context and the prompts
+        let bundle = laptop.path().join("backend.tar.gz");
+        let manifest = export_bundle(root, &["tag:backend".to_string()], &bundle).unwrap();
+        assert_eq!(manifest.jobs, vec!["001_service"]);
+        let roles: Vec<(String, BundleRole)> =
+            manifest.files.iter().map(|f| (f.path.display().to_string(), f.role)).collect();
+        assert_eq!(
+            roles,
+            vec![
+                ("jobs/001_service.md".to_string(), BundleRole::Job),
+                ("jobs/_systemprompt_create.md".to_string(), BundleRole::Prompt),
+                ("jobs/_systemprompt_verify.md".to_string(), BundleRole::Prompt),
+                ("src/models.rs".to_string(), BundleRole::Context),
+                ("worksplit.toml".to_string(), BundleRole::Config),
+            ]
+        );
+        assert!(manifest.status.is_empty());
+
+        let gpu = TempDir::new().unwrap();
+        fs::write(gpu.path().join("worksplit.toml"), "[ollama]\nmodel = \"big\"\n").unwrap();
+        let report = import_bundle(gpu.path(), &bundle, false, false).unwrap();
+        assert_eq!(report.kept, vec![PathBuf::from("worksplit.toml")]);
+        assert_eq!(fs::read_to_string(gpu.path().join("src/models.rs")).unwrap(), "pub struct User;\n");
+        assert!(gpu.path().join("jobs/001_service.md").is_file());
+
+        // The job runs on the GPU machine...
+        fs::write(gpu.path().join("src/service.rs"), "pub fn serve() {}\n").unwrap();
+        let mut status = StatusManager::new(&gpu.path().join("jobs")).unwrap();
+        status.update_status("001_service", JobStatus::Pass).unwrap();
+        status.mark_ran("001_service").unwrap();
+
+        // ...and the results come back
+        let results = gpu.path().join("results.tar.gz");
+        let manifest = export_bundle(gpu.path(), &[], &results).unwrap();
+        assert_eq!(manifest.status.len(), 1);
+        assert!(manifest.files.contains(&BundleFile { path: PathBuf::from("src/service.rs"), role: BundleRole::Output }));
+
+        let report = import_bundle(root, &results, false, false).unwrap();
+        assert_eq!(report.written, vec![PathBuf::from("src/service.rs")]);
+        assert_eq!(report.kept, vec![PathBuf::from("worksplit.toml")]);
+        assert_eq!(fs::read_to_string(root.join("src/service.rs")).unwrap(), "pub fn serve() {}\n");
+        let status = StatusManager::new(&root.join("jobs")).unwrap();
+        assert_eq!(status.get("001_service").unwrap().status, JobStatus::Pass);
+        assert_eq!(status.get("002_other").unwrap().status, JobStatus::Created);
+    }
+
+    #[test]
+    fn test_import_checks_files_like_the_runner() {
+        let gpu = project();
+        let root = gpu.path();
+        fs::write(root.join("jobs/001_service.md"), "---\noutput_dir: src/\noutput_file: service.rs\n---\n\nService\n").unwrap();
+        fs::write(root.join("src/service.rs"), "pub fn serve() {}\n").unwrap();
+        let mut status = StatusManager::new(&root.join("jobs")).unwrap();
+        status.sync_with_jobs(&["001_service".to_string()]).unwrap();
+        status.update_status("001_service", JobStatus::Pass).unwrap();
+        status.mark_ran("001_service").unwrap();
+        let results = root.join("results.tar.gz");
+        export_bundle(root, &[], &results).unwrap();
+
+        // A frozen file is only replaced with force, and nothing is written before that
+        let laptop = project();
+        let local = laptop.path();
+        fs::write(local.join("src/service.rs"), "// reviewed\n").unwrap();
+        let mut frozen = FrozenManifest::default();
+        frozen.freeze("001_service", Path::new("src/service.rs"), "// reviewed\n");
+        frozen.save(&local.join("jobs")).unwrap();
+        let error = import_bundle(local, &results, false, false).unwrap_err();
+        assert!(matches!(error, WorkSplitError::FrozenOutput { .. }), "{}", error);
+        assert_eq!(fs::read_to_string(local.join("src/service.rs")).unwrap(), "// reviewed\n");
+        assert!(!local.join("jobs/001_service.md").exists());
+        import_bundle(local, &results, false, true).unwrap();
+        assert_eq!(fs::read_to_string(local.join("src/service.rs")).unwrap(), "pub fn serve() {}\n");
+
+        // The local [safety] settings apply, not the bundle's
+        let laptop = project();
+        let local = laptop.path();
+        fs::write(local.join("worksplit.toml"), "[safety]\nprotected_paths = [\"src/service.rs\"]\n").unwrap();
+        let error = import_bundle(local, &results, true, false).unwrap_err();
+        assert!(matches!(error, WorkSplitError::ProtectedPathViolation { .. }), "{}", error);
+        fs::write(local.join("worksplit.toml"), "[safety]\nallowed_output_dirs = [\"gen/\"]\n").unwrap();
+        let error = import_bundle(local, &results, false, false).unwrap_err();
+        assert!(matches!(error, WorkSplitError::OutputNotAllowed { .. }), "{}", error);
+        assert!(!local.join("src/service.rs").exists());
+
+        // A bundle can't pass a project file off as a prompt
+        let manifest = BundleManifest {
+            version: BUNDLE_VERSION,
+            created_at: Utc::now(),
+            jobs: Vec::new(),
+            files: vec![BundleFile { path: PathBuf::from("src/service.rs"), role: BundleRole::Prompt }],
+            status: Vec::new(),
+        };
+        let crafted = root.join("crafted.tar.gz");
+        write_bundle(root, &manifest, &crafted).unwrap();
+        let error = import_bundle(local, &crafted, false, false).unwrap_err();
+        assert!(error.to_string().contains("can't be imported as a Prompt file"), "{}", error);
+    }
+
+    #[test]
+    fn test_import_rejects_invalid_bundles() {
+        let temp = project();
+        let bundle = temp.path().join("bad.tar.gz");
+        fs::write(&bundle, "not a bundle").unwrap();
+        assert!(import_bundle(temp.path(), &bundle, false, false).is_err());
+
+        // A tar without a manifest
+        let mut builder = tar::Builder::new(GzEncoder::new(File::create(&bundle).unwrap(), Compression::default()));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(2);
+        header.set_cksum();
+        builder.append_data(&mut header, "src/a.rs", &b"{}"[..]).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+        let error = import_bundle(temp.path(), &bundle, false, false).unwrap_err();
+        assert!(error.to_string().contains("no worksplit-bundle.json"), "{}", error);
+    }
+}
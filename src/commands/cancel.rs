@@ -1,16 +1,27 @@
-use std::path::PathBuf;
+use std::path::Path;
 use crate::error::WorkSplitError;
+use crate::commands::reset::JobSelection;
+use crate::commands::status::status_label;
 use crate::core::status::StatusManager;
+use crate::models::JobStatus;
 
 /// Cancel running jobs by marking them as failed.
 /// Note: This marks jobs as cancelled but cannot actually kill the Ollama process.
 /// The running Ollama request will complete but its output will be discarded.
+///
+/// Only running or stuck jobs among the selected ones are cancelled; `all`
+/// cancels every one of them. Returns the cancelled jobs.
 pub fn cancel_jobs(
-    project_root: &PathBuf,
-    job_id: &str,
-) -> Result<(), WorkSplitError> {
+    project_root: &Path,
+    selection: &JobSelection,
+) -> Result<Vec<String>, WorkSplitError> {
+    if selection.is_empty() {
+        return Err(WorkSplitError::JobError(
+            "Name the jobs to cancel: job IDs, globs (auth_*), tag:<name>, 'all', --status or --tag".to_string(),
+        ));
+    }
     let mut status_manager = StatusManager::new(&project_root.join("jobs"))?;
-    
+
     // Find jobs in running states (PendingWork or PendingVerification)
     let running_jobs: Vec<String> = status_manager
         .get_stuck_jobs()
@@ -20,28 +31,61 @@ pub fn cancel_jobs(
 
     if running_jobs.is_empty() {
         println!("No running or stuck jobs found.");
-        return Ok(());
+        return Ok(Vec::new());
     }
 
-    if job_id == "all" {
-        for id in &running_jobs {
-            status_manager.set_failed(id, "Cancelled by user".to_string())?;
-            println!("Cancelled: {}", id);
-        }
-        println!("\nCancelled {} job(s).", running_jobs.len());
-    } else {
-        if !running_jobs.contains(&job_id.to_string()) {
+    let selected = selection.select(project_root, &status_manager)?;
+    let to_cancel: Vec<(String, JobStatus)> = selected.iter()
+        .filter(|(id, _)| running_jobs.contains(id))
+        .cloned()
+        .collect();
+
+    if to_cancel.is_empty() {
+        match selection.selectors.as_slice() {
             // Check if the job exists at all
-            if status_manager.get(job_id).is_some() {
-                println!("Job '{}' is not running.", job_id);
-            } else {
-                println!("Job '{}' not found.", job_id);
-            }
-            return Ok(());
+            [job_id] if !selection.is_all() && selected.len() == 1 => println!("Job '{}' is not running.", job_id),
+            _ => println!("No running or stuck jobs match {}.", selection.describe()),
         }
-        status_manager.set_failed(job_id, "Cancelled by user".to_string())?;
-        println!("Cancelled: {}", job_id);
+        return Ok(Vec::new());
+    }
+
+    for (id, from) in &to_cancel {
+        status_manager.set_failed(id, "Cancelled by user".to_string())?;
+        println!("Cancelled: {} ({} -> {})", id, status_label(*from), status_label(JobStatus::Fail));
     }
+    println!("\nCancelled {} job(s).", to_cancel.len());
 
-    Ok(())
+    Ok(to_cancel.into_iter().map(|(id, _)| id).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cancel_only_running_selected_jobs() {
+        let temp = TempDir::new().unwrap();
+        let jobs_dir = temp.path().join("jobs");
+        fs::create_dir_all(&jobs_dir).unwrap();
+        let jobs = [("api_001", JobStatus::PendingWork), ("api_002", JobStatus::Pass), ("ui_001", JobStatus::PendingVerification)];
+        for (id, _) in jobs {
+            fs::write(jobs_dir.join(format!("{}.md", id)), "---\noutput_file: a.rs\n---\n").unwrap();
+        }
+        let mut status = StatusManager::new(&jobs_dir).unwrap();
+        status.sync_with_jobs(&jobs.map(|(id, _)| id.to_string())).unwrap();
+        for (id, job_status) in jobs {
+            status.update_status(id, job_status).unwrap();
+        }
+
+        let selection = JobSelection { selectors: vec!["api_*".into()], ..Default::default() };
+        assert_eq!(cancel_jobs(temp.path(), &selection).unwrap(), ["api_001"]);
+        assert!(cancel_jobs(temp.path(), &JobSelection::job("api_002")).unwrap().is_empty());
+        assert_eq!(cancel_jobs(temp.path(), &JobSelection::job("all")).unwrap(), ["ui_001"]);
+
+        let status = StatusManager::new(&jobs_dir).unwrap();
+        assert_eq!(status.get("api_001").unwrap().status, JobStatus::Fail);
+        assert_eq!(status.get("api_002").unwrap().status, JobStatus::Pass);
+    }
 }
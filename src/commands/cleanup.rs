@@ -1,9 +1,10 @@
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::time::SystemTime;
 use chrono::{DateTime, Duration, Utc};
 use tracing::info;
 
+use crate::commands::archive::archive_status;
 use crate::error::WorkSplitError;
 use crate::models::Config;
 
@@ -19,14 +20,14 @@ fn get_file_modified_time(path: &Path) -> Result<DateTime<Utc>, std::io::Error>
     let metadata = fs::metadata(path)?;
     let modified = metadata.modified()?;
     let duration = modified.duration_since(SystemTime::UNIX_EPOCH)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        .map_err(std::io::Error::other)?;
     Ok(DateTime::from_timestamp(duration.as_secs() as i64, 0)
         .unwrap_or_else(Utc::now))
 }
 
 /// Clean up archived jobs older than X days
 pub fn cleanup_archived_jobs(
-    project_root: &PathBuf,
+    project_root: &Path,
     days: Option<u32>,
     dry_run: bool,
 ) -> Result<CleanupResult, WorkSplitError> {
@@ -66,7 +67,7 @@ pub fn cleanup_archived_jobs(
         }
 
         // Skip if not .md extension
-        if path.extension().map_or(false, |ext| ext != "md") {
+        if path.extension().is_some_and(|ext| ext != "md") {
             continue;
         }
 
@@ -100,6 +101,14 @@ pub fn cleanup_archived_jobs(
         }
     }
 
+    // Their status goes with them
+    if !deleted_jobs.is_empty() {
+        let mut archived = archive_status(&project_root.join("jobs"))?;
+        for job_id in &deleted_jobs {
+            archived.remove_job(job_id)?;
+        }
+    }
+
     Ok(CleanupResult {
         deleted_count,
         deleted_jobs,
@@ -107,7 +116,7 @@ pub fn cleanup_archived_jobs(
 }
 
 /// Run automatic cleanup based on config
-pub fn run_auto_cleanup(project_root: &PathBuf) -> Result<(), WorkSplitError> {
+pub fn run_auto_cleanup(project_root: &Path) -> Result<(), WorkSplitError> {
     // Load config
     let config = Config::load_from_dir(project_root)?;
 
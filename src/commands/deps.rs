@@ -6,13 +6,13 @@ use crate::error::WorkSplitError;
 
 /// Show job dependency graph
 pub fn show_deps(
-    project_root: &PathBuf,
+    project_root: &Path,
     verbose: bool,
     json: bool,
     quiet: bool,
 ) -> Result<(), WorkSplitError> {
-    let config = load_config(project_root, None, None, None, false)?;
-    let jobs_manager = JobsManager::new(project_root.clone(), config.limits.clone());
+    let config = load_config(project_root, None, None, None, None, false)?;
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), config.limits.clone());
     
     let discovered = jobs_manager.discover_jobs()?;
     
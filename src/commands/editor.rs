@@ -0,0 +1,134 @@
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+use crate::error::WorkSplitError;
+
+/// Editors `worksplit init --editor` can set up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Editor {
+    /// `.vscode/tasks.json` with run and status tasks
+    Vscode,
+}
+
+/// Matches the result lines of failed jobs (`  001_x [FAIL] (jobs/001_x.md): error`)
+/// so failures show up in the Problems panel against the job file
+pub(crate) fn vscode_problem_matcher() -> Value {
+    json!({
+        "owner": "worksplit",
+        "fileLocation": ["relative", "${workspaceFolder}"],
+        "severity": "error",
+        "pattern": {
+            "regexp": r"^\s+\S+ \[(?:FAIL|PARTIAL)\].* \((jobs/[^)]+\.md)\): (.*)$",
+            "file": 1,
+            "message": 2
+        }
+    })
+}
+
+fn vscode_tasks() -> Vec<Value> {
+    let task = |label: &str, args: &[&str], matcher: bool| {
+        let mut task = json!({
+            "label": label,
+            "type": "shell",
+            "command": "worksplit",
+            "args": args,
+            "options": { "cwd": "${workspaceFolder}" },
+            "problemMatcher": []
+        });
+        if matcher {
+            task["problemMatcher"] = vscode_problem_matcher();
+        }
+        task
+    };
+    vec![
+        task("WorkSplit: run all", &["run"], true),
+        task("WorkSplit: run current job", &["run", "--job", "${fileBasenameNoExtension}"], true),
+        task("WorkSplit: status", &["status"], false),
+    ]
+}
+
+/// Add the WorkSplit tasks to a `tasks.json` document, keeping tasks that
+/// are already there (including WorkSplit tasks the user changed); returns
+/// the labels added
+pub(crate) fn merge_vscode_tasks(document: &mut Value) -> Result<Vec<String>, WorkSplitError> {
+    let object = document.as_object_mut()
+        .ok_or_else(|| WorkSplitError::InitError("tasks.json is not a JSON object".to_string()))?;
+    object.entry("version").or_insert_with(|| json!("2.0.0"));
+    let tasks = object.entry("tasks").or_insert_with(|| json!([])).as_array_mut()
+        .ok_or_else(|| WorkSplitError::InitError("\"tasks\" in tasks.json is not an array".to_string()))?;
+
+    let mut added = Vec::new();
+    for task in vscode_tasks() {
+        let label = task["label"].as_str().unwrap_or_default().to_string();
+        if !tasks.iter().any(|t| t["label"] == label.as_str()) {
+            tasks.push(task);
+            added.push(label);
+        }
+    }
+    Ok(added)
+}
+
+/// Write the editor's task configuration into the project, merging with an
+/// existing configuration
+pub fn write_editor_config(project_root: &Path, editor: Editor) -> Result<(), WorkSplitError> {
+    match editor {
+        Editor::Vscode => {
+            let path = project_root.join(".vscode").join("tasks.json");
+            let mut document = match fs::read_to_string(&path) {
+                Ok(content) => serde_json::from_str(&content).map_err(|e| WorkSplitError::InitError(format!(
+                    "Cannot update {} ({}); remove its comments or add the WorkSplit tasks by hand",
+                    path.display(), e)))?,
+                Err(_) => json!({}),
+            };
+            let added = merge_vscode_tasks(&mut document)?;
+            if added.is_empty() {
+                println!("{} already has the WorkSplit tasks", path.display());
+                return Ok(());
+            }
+            fs::create_dir_all(path.parent().unwrap())?;
+            let content = serde_json::to_string_pretty(&document).map_err(|e| WorkSplitError::IoError(e.to_string()))?;
+            fs::write(&path, content + "\n")?;
+            println!("Added VS Code tasks to {}: {}", path.display(), added.join(", "));
+            println!("Run them with Terminal > Run Task; failed jobs show up in the Problems panel.");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    #[test]
+    fn test_merge_vscode_tasks_keeps_existing_tasks() {
+        let mut document = json!({
+            "version": "2.0.0",
+            "tasks": [
+                { "label": "cargo build", "type": "shell", "command": "cargo build" },
+                { "label": "WorkSplit: status", "type": "shell", "command": "worksplit status --summary" }
+            ]
+        });
+        let added = merge_vscode_tasks(&mut document).unwrap();
+        assert_eq!(added, vec!["WorkSplit: run all", "WorkSplit: run current job"]);
+        let tasks = document["tasks"].as_array().unwrap();
+        assert_eq!(tasks.len(), 4);
+        assert_eq!(tasks[1]["command"], "worksplit status --summary");
+        assert!(merge_vscode_tasks(&mut document).unwrap().is_empty());
+
+        let mut empty = json!({});
+        assert_eq!(merge_vscode_tasks(&mut empty).unwrap().len(), 3);
+        assert_eq!(empty["version"], "2.0.0");
+    }
+
+    #[test]
+    fn test_problem_matcher_matches_failed_result_lines() {
+        let matcher = vscode_problem_matcher();
+        let regex = Regex::new(matcher["pattern"]["regexp"].as_str().unwrap()).unwrap();
+        let caps = regex.captures("  001_api [FAIL] (120 lines) (jobs/001_api.md): Verification failed: missing docs").unwrap();
+        assert_eq!(&caps[1], "jobs/001_api.md");
+        assert_eq!(&caps[2], "Verification failed: missing docs");
+        assert!(!regex.is_match("  002_ok [PASS] (40 lines)"));
+    }
+}
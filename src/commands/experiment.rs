@@ -0,0 +1,368 @@
+//! `worksplit experiment`: compare system prompt variants on the same jobs
+//!
+//! Every variant runs in its own copy of the project in a temporary
+//! directory, with the variant installed as `jobs/_systemprompt_<kind>.md`
+//! and a fresh status file. The project's own files and status are never
+//! touched, and `--mock` fixtures are served to each variant from the start.
+//! The comparison is printed and saved to `jobs/_experiments/`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use tracing::info;
+
+use crate::core::mock_fixtures::MockFixtures;
+use crate::core::prompt_templates::TEMPLATES_DIR;
+use crate::core::targets::walk_files;
+use crate::core::{load_config, JobsManager, Runner};
+use crate::error::WorkSplitError;
+use crate::models::{JobStatus, NotifyConfig, StatusWebhookConfig};
+
+/// Experiment reports folder inside the jobs folder
+pub const EXPERIMENTS_DIR: &str = "_experiments";
+
+/// System prompts a variant can replace (`jobs/_systemprompt_<kind>.md`)
+pub const PROMPT_KINDS: &[&str] = &["create", "verify", "edit", "verify_edit", "split", "test", "fix"];
+
+/// Experiment options
+#[derive(Debug, Default)]
+pub struct ExperimentOptions {
+    /// System prompt variants, relative to the project root
+    pub prompts: Vec<PathBuf>,
+    /// Job selectors (`tag:<name>`, `auth_*`, job IDs); empty runs every job
+    pub jobs: Vec<String>,
+    /// Which system prompt the variants replace (see [`PROMPT_KINDS`])
+    pub kind: String,
+    /// Model override
+    pub model: Option<String>,
+    /// URL override
+    pub url: Option<String>,
+    /// Timeout override
+    pub timeout: Option<u64>,
+    /// Serve model responses from this fixture directory instead of Ollama
+    pub mock: Option<PathBuf>,
+}
+
+/// How one job did under one variant
+#[derive(Debug, Clone)]
+pub struct JobOutcome {
+    pub job_id: String,
+    pub status: JobStatus,
+    pub duration: Duration,
+    pub error: Option<String>,
+}
+
+/// Results of one prompt variant
+#[derive(Debug, Clone)]
+pub struct VariantReport {
+    pub prompt: PathBuf,
+    pub outcomes: Vec<JobOutcome>,
+}
+
+impl VariantReport {
+    pub fn passed(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.status == JobStatus::Pass).count()
+    }
+
+    /// Share of jobs that passed, 0.0 to 1.0
+    pub fn pass_rate(&self) -> f64 {
+        if self.outcomes.is_empty() {
+            return 0.0;
+        }
+        self.passed() as f64 / self.outcomes.len() as f64
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        self.outcomes.iter().map(|o| o.duration).sum()
+    }
+}
+
+/// Results of an experiment, one entry per variant in the order given
+#[derive(Debug, Clone)]
+pub struct ExperimentReport {
+    pub kind: String,
+    pub model: String,
+    pub selectors: Vec<String>,
+    pub jobs: Vec<String>,
+    pub variants: Vec<VariantReport>,
+}
+
+pub(crate) fn status_label(status: JobStatus) -> &'static str {
+    match status {
+        JobStatus::Pass => "PASS",
+        JobStatus::Fail => "FAIL",
+        JobStatus::Partial => "PARTIAL",
+        _ => "PENDING",
+    }
+}
+
+pub(crate) fn seconds(duration: Duration) -> String {
+    format!("{:.1}s", duration.as_secs_f64())
+}
+
+impl ExperimentReport {
+    /// The comparison as a markdown document
+    pub fn to_markdown(&self) -> String {
+        let selection = if self.selectors.is_empty() { "all jobs".to_string() } else { self.selectors.join(", ") };
+        let mut out = String::from("# Prompt Experiment\n\n");
+        out.push_str(&format!("- Prompt: `_systemprompt_{}.md`\n", self.kind));
+        out.push_str(&format!("- Model: {}\n", self.model));
+        out.push_str(&format!("- Jobs: {} ({})\n\n", self.jobs.len(), selection));
+
+        out.push_str("| Variant | Passed | Pass rate | Total time | Mean time |\n");
+        out.push_str("|---------|--------|-----------|------------|-----------|\n");
+        for variant in &self.variants {
+            let mean = variant.total_duration().checked_div(variant.outcomes.len() as u32).unwrap_or_default();
+            out.push_str(&format!(
+                "| {} | {}/{} | {:.0}% | {} | {} |\n",
+                variant.prompt.display(),
+                variant.passed(),
+                variant.outcomes.len(),
+                variant.pass_rate() * 100.0,
+                seconds(variant.total_duration()),
+                seconds(mean)
+            ));
+        }
+
+        out.push_str("\n## Per Job\n\n| Job |");
+        for variant in &self.variants {
+            out.push_str(&format!(" {} |", variant.prompt.display()));
+        }
+        out.push_str("\n|-----|");
+        out.push_str(&"---|".repeat(self.variants.len()));
+        out.push('\n');
+        for (index, job_id) in self.jobs.iter().enumerate() {
+            out.push_str(&format!("| {} |", job_id));
+            for variant in &self.variants {
+                let outcome = &variant.outcomes[index];
+                out.push_str(&format!(" {} ({}) |", status_label(outcome.status), seconds(outcome.duration)));
+            }
+            out.push('\n');
+        }
+
+        let errors: Vec<String> = self
+            .variants
+            .iter()
+            .flat_map(|variant| {
+                variant.outcomes.iter().filter_map(move |o| {
+                    o.error.as_ref().map(|e| format!("- {} / {}: {}", variant.prompt.display(), o.job_id, e.lines().next().unwrap_or_default()))
+                })
+            })
+            .collect();
+        if !errors.is_empty() {
+            out.push_str("\n## Errors\n\n");
+            out.push_str(&errors.join("\n"));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Save the report to `jobs/_experiments/experiment-<timestamp>.md`
+    pub fn save(&self, project_root: &Path) -> Result<PathBuf, WorkSplitError> {
+        let dir = project_root.join("jobs").join(EXPERIMENTS_DIR);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("experiment-{}.md", chrono::Utc::now().format("%Y%m%d-%H%M%S")));
+        fs::write(&path, self.to_markdown())?;
+        Ok(path)
+    }
+}
+
+/// Copy what a run needs into `dest`: the project files (as the file picker
+/// sees them), the job files, system prompts and prompt templates. Status,
+/// recordings, staging and earlier experiments stay behind.
+fn copy_project(project_root: &Path, dest: &Path) -> Result<(), WorkSplitError> {
+    let mut files = Vec::new();
+    walk_files(project_root, project_root, &mut files)?;
+
+    let jobs_dir = project_root.join("jobs");
+    for entry in fs::read_dir(&jobs_dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|e| e == "md") {
+            files.push(path.strip_prefix(project_root).unwrap_or(&path).to_path_buf());
+        }
+    }
+    let templates_dir = jobs_dir.join(TEMPLATES_DIR);
+    if templates_dir.is_dir() {
+        for entry in fs::read_dir(&templates_dir)? {
+            let path = entry?.path();
+            if path.is_file() {
+                files.push(path.strip_prefix(project_root).unwrap_or(&path).to_path_buf());
+            }
+        }
+    }
+
+    for relative in files {
+        let target = dest.join(&relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(project_root.join(&relative), &target)?;
+    }
+    Ok(())
+}
+
+/// A copy of the project in the system temporary directory, removed when
+/// dropped
+pub(crate) struct ProjectCopy(PathBuf);
+
+impl ProjectCopy {
+    pub(crate) fn new(project_root: &Path) -> Result<Self, WorkSplitError> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let name = format!(
+            "worksplit-{}-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        );
+        let copy = Self(std::env::temp_dir().join(name));
+        fs::create_dir_all(copy.path())?;
+        copy_project(project_root, copy.path())?;
+        Ok(copy)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ProjectCopy {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Run the selected jobs once per prompt variant and compare the results
+pub async fn run_experiment(project_root: &Path, options: ExperimentOptions) -> Result<ExperimentReport, WorkSplitError> {
+    if !PROMPT_KINDS.contains(&options.kind.as_str()) {
+        return Err(WorkSplitError::ConfigError(format!(
+            "Unknown prompt kind '{}' (expected one of: {})",
+            options.kind,
+            PROMPT_KINDS.join(", ")
+        )));
+    }
+    if options.prompts.is_empty() {
+        return Err(WorkSplitError::ConfigError("No prompt variants given".to_string()));
+    }
+    let variants = options
+        .prompts
+        .iter()
+        .map(|path| {
+            fs::read_to_string(project_root.join(path))
+                .map(|content| (path.clone(), content))
+                .map_err(|e| WorkSplitError::IoError(format!("Cannot read prompt variant {}: {}", path.display(), e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut config = load_config(project_root, None, options.model, options.url, options.timeout, true)?;
+    // Failures and status changes in throwaway copies aren't worth a
+    // notification
+    config.notify = NotifyConfig::default();
+    config.status_webhook = StatusWebhookConfig::default();
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), config.limits.clone());
+    let jobs = if options.jobs.is_empty() {
+        jobs_manager.discover_jobs()?
+    } else {
+        jobs_manager.select_jobs(&options.jobs)?
+    };
+    if jobs.is_empty() {
+        return Err(WorkSplitError::JobError(format!("No jobs match {}", options.jobs.join(", "))));
+    }
+
+    let mut reports = Vec::new();
+    for (prompt, content) in variants {
+        info!("Experiment variant {}: {} job(s)", prompt.display(), jobs.len());
+        let workspace = ProjectCopy::new(project_root)?;
+        fs::write(workspace.path().join(format!("jobs/_systemprompt_{}.md", options.kind)), content)?;
+
+        let mut runner = Runner::new(config.clone(), workspace.path().to_path_buf())?;
+        if let Some(dir) = &options.mock {
+            runner.set_mock_fixtures(MockFixtures::new(project_root.join(dir))?);
+        }
+
+        let mut outcomes = Vec::new();
+        for job_id in &jobs {
+            let started = Instant::now();
+            let outcome = match runner.run_single(job_id).await {
+                Ok(result) => JobOutcome {
+                    job_id: job_id.clone(),
+                    status: result.status,
+                    duration: started.elapsed(),
+                    error: result.error,
+                },
+                Err(e) => JobOutcome {
+                    job_id: job_id.clone(),
+                    status: JobStatus::Fail,
+                    duration: started.elapsed(),
+                    error: Some(e.to_string()),
+                },
+            };
+            println!(
+                "  {} / {}: {} ({})",
+                prompt.display(),
+                job_id,
+                status_label(outcome.status),
+                seconds(outcome.duration)
+            );
+            outcomes.push(outcome);
+        }
+        reports.push(VariantReport { prompt, outcomes });
+    }
+
+    Ok(ExperimentReport {
+        kind: options.kind,
+        model: config.ollama.model.clone(),
+        selectors: options.jobs,
+        jobs,
+        variants: reports,
+    })
+}
+
+/// `worksplit experiment`: run, print the comparison and save it
+pub async fn experiment(project_root: &Path, options: ExperimentOptions) -> Result<(), WorkSplitError> {
+    let report = run_experiment(project_root, options).await?;
+    println!("\n{}", report.to_markdown());
+    let path = report.save(project_root)?;
+    println!("Report saved to {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(job_id: &str, status: JobStatus, secs: u64) -> JobOutcome {
+        JobOutcome { job_id: job_id.to_string(), status, duration: Duration::from_secs(secs), error: None }
+    }
+
+    #[test]
+    fn test_report_markdown() {
+        let mut failed = outcome("002_b", JobStatus::Fail, 3);
+        failed.error = Some("Verification failed: stub\nmore".to_string());
+        let report = ExperimentReport {
+            kind: "create".to_string(),
+            model: "qwen".to_string(),
+            selectors: vec!["tag:benchmark".to_string()],
+            jobs: vec!["001_a".to_string(), "002_b".to_string()],
+            variants: vec![
+                VariantReport {
+                    prompt: PathBuf::from("a.md"),
+                    outcomes: vec![outcome("001_a", JobStatus::Pass, 2), outcome("002_b", JobStatus::Pass, 4)],
+                },
+                VariantReport {
+                    prompt: PathBuf::from("b.md"),
+                    outcomes: vec![outcome("001_a", JobStatus::Pass, 1), failed],
+                },
+            ],
+        };
+        assert_eq!(report.variants[1].pass_rate(), 0.5);
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("- Jobs: 2 (tag:benchmark)"), "{}", markdown);
+        assert!(markdown.contains("| a.md | 2/2 | 100% | 6.0s | 3.0s |"), "{}", markdown);
+        assert!(markdown.contains("| b.md | 1/2 | 50% | 4.0s | 2.0s |"), "{}", markdown);
+        assert!(markdown.contains("| 002_b | PASS (4.0s) | FAIL (3.0s) |"), "{}", markdown);
+        assert!(markdown.contains("- b.md / 002_b: Verification failed: stub\n"), "{}", markdown);
+    }
+}
@@ -1,8 +1,12 @@
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use crate::core::{extract_code_files, load_config, JobsManager, OllamaClient, StatusManager};
+use crate::core::atomic_write::write_atomic;
+use crate::core::postprocess::postprocess;
+use crate::core::output_paths::check_protected_path;
+use crate::core::{extract_code_files, load_config, JobsManager, OllamaClient, Runner, StatusManager};
 use crate::error::WorkSplitError;
 use crate::models::{Config, ErrorType, JobStatus, LimitsConfig};
 
@@ -56,8 +60,8 @@ pub struct FixSummary {
 
 /// Fix errors in a file using LLM - core function that can be called with any error type
 pub async fn fix_with_error_context(
-    project_root: &PathBuf,
-    output_path: &PathBuf,
+    project_root: &Path,
+    output_path: &Path,
     error_output: &str,
     error_type: ErrorType,
     config: &Config,
@@ -72,7 +76,7 @@ pub async fn fix_with_error_context(
     }
 
     // Load fix system prompt (auto-recreates from template if missing)
-    let jobs_manager = JobsManager::new(project_root.clone(), LimitsConfig::default());
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), LimitsConfig::default());
     let system_prompt = jobs_manager.load_system_prompt("_systemprompt_fix.md")?;
 
     // Read the source file content
@@ -116,13 +120,16 @@ pub async fn fix_with_error_context(
             full_output_path.clone()
         };
 
+        check_protected_path(project_root, &target_path, &config.safety.protected_paths)?;
         if let Some(parent) = target_path.parent() {
             if !parent.exists() {
                 fs::create_dir_all(parent)?;
             }
         }
 
-        fs::write(&target_path, &file.content)?;
+        let relative = target_path.strip_prefix(project_root).unwrap_or(&target_path);
+        let content = postprocess(&config.postprocess, project_root, relative, &file.content);
+        write_atomic(&target_path, &content)?;
         println!("  Wrote fixed file: {}", target_path.display());
         files_written += 1;
     }
@@ -132,15 +139,17 @@ pub async fn fix_with_error_context(
 
 /// Run a verification command and return the output
 fn run_verification_command(
-    project_root: &PathBuf,
+    dir: &Path,
     command: &str,
-    file_path: &PathBuf,
+    file_path: &Path,
+    env: &BTreeMap<String, String>,
 ) -> Result<(bool, String), WorkSplitError> {
     let full_cmd = format!("{} {}", command, file_path.display());
     let output = Command::new("sh")
         .arg("-c")
         .arg(&full_cmd)
-        .current_dir(project_root)
+        .current_dir(dir)
+        .envs(env)
         .output()
         .map_err(|e| WorkSplitError::IoError(format!("Failed to run command: {}", e)))?;
 
@@ -153,20 +162,32 @@ fn run_verification_command(
     Ok((output.status.success(), combined_output))
 }
 
+/// `fix` edits files in place, which read-only mode forbids
+fn ensure_writable(config: &Config) -> Result<(), WorkSplitError> {
+    if config.safety.read_only {
+        return Err(WorkSplitError::ConfigError(
+            "fix is disabled because [safety] read_only = true".into(),
+        ));
+    }
+    Ok(())
+}
+
 /// Auto-fix linter errors for a specific job using LLM
-pub async fn fix_job(project_root: &PathBuf, job_id: &str) -> Result<(), WorkSplitError> {
-    let config = load_config(project_root, None, None, None, false)?;
+pub async fn fix_job(project_root: &Path, job_id: &str) -> Result<(), WorkSplitError> {
+    let config = load_config(project_root, None, None, None, None, false)?;
+    ensure_writable(&config)?;
 
-    // Get lint command
-    let lint_cmd = config
-        .build
+    // Get job output path
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), config.limits.clone());
+    let job = jobs_manager.parse_job(job_id)?;
+
+    // Get lint command (the job's workspace may have its own)
+    let target = config.build_target(job.metadata.workdir.as_deref());
+    let lint_cmd = target
         .lint_command
         .as_ref()
         .ok_or_else(|| WorkSplitError::ConfigError("No lint_command configured in worksplit.toml. Add [build] lint_command = \"your-linter\"".into()))?;
-
-    // Get job output path
-    let jobs_manager = JobsManager::new(project_root.clone(), config.limits.clone());
-    let job = jobs_manager.parse_job(job_id)?;
+    let lint_dir = project_root.join(&target.dir);
     let output_path = job.metadata.output_path();
     let full_output_path = project_root.join(&output_path);
 
@@ -180,7 +201,7 @@ pub async fn fix_job(project_root: &PathBuf, job_id: &str) -> Result<(), WorkSpl
     println!("Running linter on {}...", output_path.display());
 
     // Run linter and capture output
-    let (success, lint_output) = run_verification_command(project_root, lint_cmd, &full_output_path)?;
+    let (success, lint_output) = run_verification_command(&lint_dir, lint_cmd, &full_output_path, &job.metadata.env)?;
 
     if success && lint_output.trim().is_empty() {
         println!("No lint errors found!");
@@ -214,7 +235,7 @@ pub async fn fix_job(project_root: &PathBuf, job_id: &str) -> Result<(), WorkSpl
 
         // Verify the fix
         println!("\nVerifying fix...");
-        let (success, new_output) = run_verification_command(project_root, lint_cmd, &full_output_path)?;
+        let (success, new_output) = run_verification_command(&lint_dir, lint_cmd, &full_output_path, &job.metadata.env)?;
 
         if success {
             println!("All lint errors fixed!");
@@ -229,16 +250,55 @@ pub async fn fix_job(project_root: &PathBuf, job_id: &str) -> Result<(), WorkSpl
     Ok(())
 }
 
+/// Fix build errors without a job (`worksplit fix --files` / `--from-build`)
+///
+/// Runs the configured build and hands its errors to the model together with
+/// `files`, or with the files the build output names when `files` is empty,
+/// until the build passes or `[build] auto_fix_attempts` is used up.
+pub async fn fix_build_errors(project_root: &Path, files: &[PathBuf], workdir: Option<&Path>) -> Result<(), WorkSplitError> {
+    let config = load_config(project_root, None, None, None, None, false)?;
+    ensure_writable(&config)?;
+    for file in files {
+        if !project_root.join(file).is_file() {
+            return Err(WorkSplitError::JobError(format!("File does not exist: {}", file.display())));
+        }
+    }
+
+    let max_attempts = config.build.auto_fix_attempts;
+    let runner = Runner::new(config, project_root.to_path_buf())?;
+    let report = runner.fix_build(files, workdir, max_attempts).await?;
+
+    if report.passed && report.attempts == 0 {
+        println!("Build passes: {}", report.command);
+        return Ok(());
+    }
+    for file in &report.files {
+        println!("  sent {}", file.display());
+    }
+    if report.passed {
+        println!("Build fixed after {} attempt(s): {}", report.attempts, report.command);
+        return Ok(());
+    }
+    if report.files.is_empty() {
+        println!("The build output names no project files; pass them with --files.");
+    }
+    Err(WorkSplitError::BuildFailed {
+        command: report.command,
+        output: format!("Build still failing after {} auto-fix attempt(s):\n{}", report.attempts, report.output),
+    })
+}
+
 /// Fix all failed jobs
-pub async fn fix_all_jobs(project_root: &PathBuf) -> Result<FixSummary, WorkSplitError> {
-    let config = load_config(project_root, None, None, None, false)?;
+pub async fn fix_all_jobs(project_root: &Path) -> Result<FixSummary, WorkSplitError> {
+    let config = load_config(project_root, None, None, None, None, false)?;
+    ensure_writable(&config)?;
 
     // Load status manager to find failed jobs
     let jobs_dir = project_root.join("jobs");
     let status_manager = StatusManager::new(&jobs_dir)?;
     let failed_jobs = status_manager.get_by_status(JobStatus::Fail);
 
-    let jobs_manager = JobsManager::new(project_root.clone(), config.limits.clone());
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), config.limits.clone());
 
     let mut summary = FixSummary {
         fixed: 0,
@@ -246,14 +306,11 @@ pub async fn fix_all_jobs(project_root: &PathBuf) -> Result<FixSummary, WorkSpli
         skipped: 0,
     };
 
-    // Get lint command (required for fix-all)
-    let lint_cmd = match &config.build.lint_command {
-        Some(cmd) => cmd.clone(),
-        None => {
-            println!("No lint_command configured. Skipping lint-based fixes.");
-            return Ok(summary);
-        }
-    };
+    // A lint command is required for fix-all, globally or per workspace
+    if !config.has_lint_command() {
+        println!("No lint_command configured. Skipping lint-based fixes.");
+        return Ok(summary);
+    }
 
     // Collect job IDs to process (need to clone since we borrow from status_manager)
     let job_ids: Vec<String> = failed_jobs.iter().map(|e| e.id.clone()).collect();
@@ -272,6 +329,14 @@ pub async fn fix_all_jobs(project_root: &PathBuf) -> Result<FixSummary, WorkSpli
             }
         };
 
+        let target = config.build_target(job.metadata.workdir.as_deref());
+        let Some(lint_cmd) = target.lint_command else {
+            println!("No lint_command configured for {}. Skipping.", job_id);
+            summary.skipped += 1;
+            continue;
+        };
+        let lint_dir = project_root.join(&target.dir);
+
         let output_path = job.metadata.output_path();
         let full_output_path = project_root.join(&output_path);
 
@@ -282,7 +347,7 @@ pub async fn fix_all_jobs(project_root: &PathBuf) -> Result<FixSummary, WorkSpli
         }
 
         // Run linter to get current errors
-        let (success, lint_output) = match run_verification_command(project_root, &lint_cmd, &full_output_path) {
+        let (success, lint_output) = match run_verification_command(&lint_dir, &lint_cmd, &full_output_path, &job.metadata.env) {
             Ok(result) => result,
             Err(e) => {
                 println!("Failed to run linter: {}", e);
@@ -320,7 +385,7 @@ pub async fn fix_all_jobs(project_root: &PathBuf) -> Result<FixSummary, WorkSpli
                     }
 
                     // Verify the fix
-                    match run_verification_command(project_root, &lint_cmd, &full_output_path) {
+                    match run_verification_command(&lint_dir, &lint_cmd, &full_output_path, &job.metadata.env) {
                         Ok((success, new_output)) => {
                             if success {
                                 println!("Fixed: {}", job_id);
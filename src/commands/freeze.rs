@@ -0,0 +1,150 @@
+use chrono::Local;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::reset::JobSelection;
+use crate::commands::status::status_label;
+use crate::core::freeze::FrozenManifest;
+use crate::core::output_paths::touched_files;
+use crate::core::provenance::sha256;
+use crate::core::{JobsManager, StatusManager};
+use crate::error::WorkSplitError;
+use crate::models::{Config, JobStatus};
+
+/// Freeze the outputs of the selected passed jobs, so later runs refuse to
+/// overwrite them without `--force`. Returns the frozen files.
+pub fn freeze_jobs(project_root: &Path, selection: &JobSelection) -> Result<Vec<String>, WorkSplitError> {
+    let jobs_dir = project_root.join("jobs");
+    let status_manager = StatusManager::new(&jobs_dir)?;
+    let config = Config::load_from_dir(project_root).unwrap_or_default();
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), config.limits);
+    let mut manifest = FrozenManifest::load(&jobs_dir)?;
+
+    let selected = selection.select(project_root, &status_manager)?;
+    if selected.is_empty() {
+        println!("No jobs match {}.", selection.describe());
+        return Ok(Vec::new());
+    }
+
+    let mut frozen = Vec::new();
+    for (id, job_status) in &selected {
+        if *job_status != JobStatus::Pass {
+            println!("Skipped: {} ({}): only passed jobs can be frozen", id, status_label(*job_status));
+            continue;
+        }
+        let outputs = job_outputs(&jobs_manager, &status_manager, id);
+        let mut count = 0;
+        for path in outputs {
+            // A file the job no longer has on disk has nothing to protect
+            let Ok(content) = fs::read_to_string(project_root.join(&path)) else { continue };
+            manifest.freeze(id, &path, &content);
+            frozen.push(path.to_string_lossy().replace('\\', "/"));
+            count += 1;
+        }
+        println!("Frozen: {} ({} file(s))", id, count);
+    }
+
+    manifest.save(&jobs_dir)?;
+    println!("\nFroze {} file(s). Runs will not overwrite them without --force.", frozen.len());
+    Ok(frozen)
+}
+
+/// Unfreeze the outputs of the selected jobs. Returns the unfrozen files.
+pub fn unfreeze_jobs(project_root: &Path, selection: &JobSelection) -> Result<Vec<String>, WorkSplitError> {
+    if selection.is_empty() {
+        return Err(WorkSplitError::JobError(
+            "Name the jobs to unfreeze: job IDs, globs (auth_*), tag:<name>, 'all', --passed or --tag".to_string(),
+        ));
+    }
+    let jobs_dir = project_root.join("jobs");
+    let status_manager = StatusManager::new(&jobs_dir)?;
+    let mut manifest = FrozenManifest::load(&jobs_dir)?;
+
+    let mut unfrozen = Vec::new();
+    for (id, _) in selection.select(project_root, &status_manager)? {
+        let paths = manifest.unfreeze_job(&id);
+        if !paths.is_empty() {
+            println!("Unfrozen: {} ({} file(s))", id, paths.len());
+            unfrozen.extend(paths);
+        }
+    }
+    if unfrozen.is_empty() {
+        println!("No frozen files belong to {}.", selection.describe());
+        return Ok(unfrozen);
+    }
+
+    manifest.save(&jobs_dir)?;
+    println!("\nUnfroze {} file(s).", unfrozen.len());
+    Ok(unfrozen)
+}
+
+/// List the frozen files, marking the ones changed since they were frozen
+pub fn show_frozen(project_root: &Path) -> Result<(), WorkSplitError> {
+    let manifest = FrozenManifest::load(&project_root.join("jobs"))?;
+    if manifest.files.is_empty() {
+        println!("No frozen files. Freeze the outputs of passed jobs with 'worksplit freeze <jobs|--passed>'.");
+        return Ok(());
+    }
+
+    println!("=== Frozen Files ===");
+    for (path, file) in &manifest.files {
+        let note = match fs::read_to_string(project_root.join(path)) {
+            // No hash to compare without the "provenance" feature
+            Ok(content) if file.content_hash.is_empty() || sha256(&content).is_empty() => "",
+            Ok(content) if sha256(&content) == file.content_hash => "",
+            Ok(_) => "  [changed since frozen]",
+            Err(_) => "  [missing]",
+        };
+        println!(
+            "{}  ({}, {}){}",
+            path,
+            file.job_id,
+            file.frozen_at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            note
+        );
+    }
+    Ok(())
+}
+
+/// The files the job generated last, or the files it declares when the
+/// status predates output tracking
+fn job_outputs(jobs_manager: &JobsManager, status_manager: &StatusManager, job_id: &str) -> Vec<PathBuf> {
+    let recorded = status_manager.get(job_id).map(|entry| entry.outputs.clone()).unwrap_or_default();
+    if !recorded.is_empty() {
+        return recorded;
+    }
+    jobs_manager.parse_job(job_id).map(|job| touched_files(&job)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_freeze_only_passed_jobs() {
+        let temp = TempDir::new().unwrap();
+        let jobs_dir = temp.path().join("jobs");
+        fs::create_dir_all(&jobs_dir).unwrap();
+        fs::create_dir_all(temp.path().join("src")).unwrap();
+        let jobs = [("a_001", "a.rs", JobStatus::Pass), ("b_001", "b.rs", JobStatus::Fail)];
+        for (id, file, _) in jobs {
+            fs::write(jobs_dir.join(format!("{}.md", id)), format!("---\noutput_dir: src/\noutput_file: {}\n---\n\nWrite it.\n", file)).unwrap();
+            fs::write(temp.path().join("src").join(file), "fn main() {}\n").unwrap();
+        }
+        let mut status = StatusManager::new(&jobs_dir).unwrap();
+        status.sync_with_jobs(&jobs.map(|(id, _, _)| id.to_string())).unwrap();
+        for (id, _, job_status) in jobs {
+            status.update_status(id, job_status).unwrap();
+        }
+
+        assert_eq!(freeze_jobs(temp.path(), &JobSelection::job("all")).unwrap(), ["src/a.rs"]);
+        let manifest = FrozenManifest::load(&jobs_dir).unwrap();
+        assert_eq!(manifest.get(Path::new("src/a.rs")).unwrap().content_hash, sha256("fn main() {}\n"));
+        assert!(manifest.get(Path::new("src/b.rs")).is_none());
+
+        assert!(unfreeze_jobs(temp.path(), &JobSelection::default()).is_err());
+        assert_eq!(unfreeze_jobs(temp.path(), &JobSelection::job("a_001")).unwrap(), ["src/a.rs"]);
+        assert!(FrozenManifest::load(&jobs_dir).unwrap().files.is_empty());
+    }
+}
@@ -1,16 +1,26 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::Path;
 use std::process::Command;
 use tracing::info;
 
 use dialoguer::{Select, theme::ColorfulTheme};
 
+use crate::commands::editor::{write_editor_config, Editor};
 use crate::error::WorkSplitError;
-use crate::models::Language;
+use crate::models::{Language, StatusFile};
 use crate::templates::get_templates;
 
 /// Initialize a new WorkSplit project with the specified or selected language and model
-pub fn init_project(project_root: &PathBuf, lang: Option<Language>, model: Option<String>) -> Result<(), WorkSplitError> {
+///
+/// With `editor`, also set up editor tasks; in a project that is already
+/// initialized only the editor tasks are added.
+pub fn init_project(project_root: &Path, lang: Option<Language>, model: Option<String>, editor: Option<Editor>) -> Result<(), WorkSplitError> {
+    if let Some(editor) = editor {
+        if project_root.join("worksplit.toml").exists() {
+            return write_editor_config(project_root, editor);
+        }
+    }
+
     // Determine the language - use provided or prompt interactively
     let language = match lang {
         Some(l) => l,
@@ -84,9 +94,11 @@ pub fn init_project(project_root: &PathBuf, lang: Option<Language>, model: Optio
     )?;
 
     // Create empty job status file
+    let empty_status = serde_json::to_string_pretty(&StatusFile::default())
+        .map_err(|e| WorkSplitError::IoError(e.to_string()))?;
     create_file_if_not_exists(
         &jobs_dir.join("_jobstatus.json"),
-        "[]",
+        &empty_status,
     )?;
 
     // Create config file with language-specific settings and selected model
@@ -111,6 +123,9 @@ pub fn init_project(project_root: &PathBuf, lang: Option<Language>, model: Optio
     )?;
 
     info!("WorkSplit project initialized successfully!");
+    if let Some(editor) = editor {
+        write_editor_config(project_root, editor)?;
+    }
     print_next_steps(project_root, language, &selected_model);
 
     Ok(())
@@ -206,7 +221,7 @@ fn prompt_for_model(models: Vec<String>) -> Result<String, WorkSplitError> {
     Ok(sorted_models[selection].clone())
 }
 
-fn create_file_if_not_exists(path: &PathBuf, content: &str) -> Result<(), WorkSplitError> {
+fn create_file_if_not_exists(path: &Path, content: &str) -> Result<(), WorkSplitError> {
     if !path.exists() {
         fs::write(path, content)?;
         info!("Created file: {}", path.display());
@@ -216,7 +231,7 @@ fn create_file_if_not_exists(path: &PathBuf, content: &str) -> Result<(), WorkSp
     Ok(())
 }
 
-fn print_next_steps(project_root: &PathBuf, language: Language, model: &str) {
+fn print_next_steps(project_root: &Path, language: Language, model: &str) {
     println!("WorkSplit project initialized at {}", project_root.display());
     println!("\nLanguage: {}", language.display_name());
     println!("\nModel: {}", model);
@@ -280,6 +295,13 @@ fn print_next_steps(project_root: &PathBuf, language: Language, model: &str) {
             println!("- For React components: generate .tsx and .css in the SAME job");
             println!("  (ensures CSS class names match JSX classNames)");
         }
+        Language::CSharp => {
+            println!("\nC#/.NET-specific tips:");
+            println!("- Use .cs extension for output files, one public type per file");
+            println!("- Build command: dotnet build");
+            println!("- Test command: dotnet test");
+            println!("- Run commands from the folder with your .sln or .csproj");
+        }
     }
     
     println!("\nTip: Add 'test_file: <filename>' to job frontmatter to enable TDD workflow");
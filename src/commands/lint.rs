@@ -1,30 +1,30 @@
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use crate::core::{load_config, JobsManager, StatusManager};
 use crate::error::WorkSplitError;
-use crate::models::JobStatus;
+use crate::models::{BuildTarget, JobStatus};
 
 /// Run linter on generated files
-pub fn lint_jobs(project_root: &PathBuf, job_id: Option<&str>) -> Result<(), WorkSplitError> {
-    let config = load_config(project_root, None, None, None, false)?;
+pub fn lint_jobs(project_root: &Path, job_id: Option<&str>) -> Result<(), WorkSplitError> {
+    let config = load_config(project_root, None, None, None, None, false)?;
 
-    let lint_cmd = config
-        .build
-        .lint_command
-        .as_ref()
-        .ok_or_else(|| WorkSplitError::ConfigError("No lint_command configured in worksplit.toml. Add [build] lint_command = \"your-linter\"".into()))?;
+    if !config.has_lint_command() {
+        return Err(WorkSplitError::ConfigError("No lint_command configured in worksplit.toml. Add [build] lint_command = \"your-linter\"".into()));
+    }
 
-    let jobs_manager = JobsManager::new(project_root.clone(), config.limits.clone());
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), config.limits.clone());
 
-    // Get files to lint
-    let files: Vec<PathBuf> = if let Some(id) = job_id {
+    // Get files to lint, with the commands of the workspace they belong to
+    // and their job's env
+    let files: Vec<(PathBuf, BuildTarget, BTreeMap<String, String>)> = if let Some(id) = job_id {
         // Lint specific job's output
         let job = jobs_manager.parse_job(id)?;
         let output_path = job.metadata.output_path();
         let full_path = project_root.join(&output_path);
         if full_path.exists() {
-            vec![output_path]
+            vec![(output_path, config.build_target(job.metadata.workdir.as_deref()), job.metadata.env)]
         } else {
             return Err(WorkSplitError::JobError(format!(
                 "Output file does not exist: {}",
@@ -48,7 +48,7 @@ pub fn lint_jobs(project_root: &PathBuf, job_id: Option<&str>) -> Result<(), Wor
                     let output_path = job.metadata.output_path();
                     let full_path = project_root.join(&output_path);
                     if full_path.exists() {
-                        Some(output_path)
+                        Some((output_path, config.build_target(job.metadata.workdir.as_deref()), job.metadata.env))
                     } else {
                         None
                     }
@@ -65,15 +65,21 @@ pub fn lint_jobs(project_root: &PathBuf, job_id: Option<&str>) -> Result<(), Wor
     println!("Linting {} file(s)...\n", files.len());
 
     let mut has_errors = false;
-    for file in &files {
-        // Build command with file argument
-        let full_cmd = format!("{} {}", lint_cmd, file.display());
+    for (file, target, env) in &files {
+        let Some(ref lint_cmd) = target.lint_command else {
+            println!("No lint_command configured for {}, skipping", file.display());
+            continue;
+        };
+        // Build command with file argument, relative to where it runs
+        let relative = file.strip_prefix(&target.dir).unwrap_or(file);
+        let full_cmd = format!("{} {}", lint_cmd, relative.display());
         println!("$ {}", full_cmd);
 
         let output = Command::new("sh")
             .arg("-c")
             .arg(&full_cmd)
-            .current_dir(project_root)
+            .current_dir(project_root.join(&target.dir))
+            .envs(env)
             .output()
             .map_err(|e| WorkSplitError::IoError(format!("Failed to run lint command: {}", e)))?;
 
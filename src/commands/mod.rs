@@ -1,26 +1,66 @@
+pub mod all_projects;
+pub mod apply;
 pub mod archive;
+pub mod bench;
+#[cfg(feature = "bundle")]
+pub mod bundle;
 pub mod cancel;
 pub mod cleanup;
+pub mod editor;
+pub mod experiment;
 pub mod fix;
+pub mod freeze;
 pub mod init;
 pub mod lint;
+pub mod mv;
 pub mod new_job;
+pub mod new_job_wizard;
+pub mod oneshot;
 pub mod preview;
+pub mod prompt;
 pub mod reset;
 pub mod retry;
 pub mod run;
+pub mod scan_todos;
+#[cfg(feature = "schema")]
+pub mod schema;
+#[cfg(feature = "server")]
+pub mod serve;
 pub mod status;
+pub mod templates;
 pub mod validate;
+pub mod warmup;
+pub mod worker;
 
+pub use all_projects::*;
+pub use apply::*;
 pub use archive::*;
+pub use bench::*;
+#[cfg(feature = "bundle")]
+pub use bundle::*;
 pub use cancel::*;
 pub use cleanup::*;
+pub use editor::*;
+pub use experiment::*;
 pub use fix::*;
+pub use freeze::*;
 pub use init::*;
 pub use lint::*;
+pub use mv::*;
 pub use new_job::*;
+pub use new_job_wizard::*;
+pub use oneshot::*;
 pub use preview::*;
+pub use prompt::*;
 pub use retry::*;
 pub use run::*;
+pub use scan_todos::*;
+#[cfg(feature = "schema")]
+pub use schema::*;
+#[cfg(feature = "server")]
+pub use serve::*;
 pub use status::*;
+pub use templates::*;
 pub use validate::*;
+pub use warmup::*;
+pub use worker::*;
@@ -0,0 +1,225 @@
+//! `worksplit mv`: rename a job without losing its status
+//!
+//! Renaming the job file by hand makes the status file treat it as a
+//! deleted job plus a new one. This moves the file, its status entry,
+//! staged outputs and frozen files together, and points `depends_on` in
+//! other jobs at the new ID.
+
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+use crate::core::atomic_write::write_atomic;
+use crate::core::freeze::FrozenManifest;
+use crate::core::staging::StagingArea;
+use crate::core::{JobsManager, StatusManager};
+use crate::error::WorkSplitError;
+use crate::models::LimitsConfig;
+
+/// What `move_job` carried over to the new ID
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MoveReport {
+    pub status_moved: bool,
+    pub staged_moved: bool,
+    pub frozen_moved: bool,
+    /// Jobs whose `depends_on` now names the new ID
+    pub dependents: Vec<String>,
+}
+
+/// Job ID from a command-line argument (`auth_001` or `auth_001.md`)
+fn job_id(arg: &str) -> &str {
+    arg.strip_suffix(".md").unwrap_or(arg)
+}
+
+fn check_new_id(id: &str) -> Result<(), WorkSplitError> {
+    let valid = !id.is_empty()
+        && !id.starts_with(['_', '.'])
+        && id.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(WorkSplitError::JobError(format!(
+            "Invalid job ID '{}': use letters, digits, '_', '-' and '.', not starting with '_' or '.'",
+            id
+        )))
+    }
+}
+
+/// Replace `old_id` with `new_id` in the `depends_on` entry of a job file's
+/// frontmatter (flow or block list). Returns `None` if nothing changed.
+pub fn rename_dependency(content: &str, old_id: &str, new_id: &str) -> Option<String> {
+    let id = Regex::new(&format!(r"(^|[^\w.-]){}([^\w.-]|$)", regex::escape(old_id))).unwrap();
+    let replacement = format!("${{1}}{}${{2}}", new_id);
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut fences = 0;
+    let mut in_depends_on = false;
+    let mut changed = false;
+    for line in content.split_inclusive('\n') {
+        let mut line = line.to_string();
+        if line.trim_end() == "---" {
+            fences += 1;
+            in_depends_on = false;
+        } else if fences == 1 {
+            if line.starts_with("depends_on:") {
+                in_depends_on = true;
+            } else if !line.starts_with([' ', '\t', '-']) {
+                in_depends_on = false;
+            }
+            if in_depends_on {
+                let renamed = id.replace_all(&line, replacement.as_str()).to_string();
+                changed |= renamed != line;
+                line = renamed;
+            }
+        }
+        lines.push(line);
+    }
+    changed.then(|| lines.concat())
+}
+
+/// Rename job `old_id` to `new_id`
+pub fn move_job(project_root: &Path, old_id: &str, new_id: &str) -> Result<MoveReport, WorkSplitError> {
+    let (old_id, new_id) = (job_id(old_id), job_id(new_id));
+    check_new_id(new_id)?;
+
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), LimitsConfig::default());
+    if !jobs_manager.jobs_folder_exists() {
+        return Err(WorkSplitError::JobsFolderNotFound(project_root.join("jobs")));
+    }
+    let jobs_dir = jobs_manager.jobs_dir().to_path_buf();
+    let old_file = jobs_dir.join(format!("{}.md", old_id));
+    let new_file = jobs_dir.join(format!("{}.md", new_id));
+    if !old_file.is_file() {
+        return Err(WorkSplitError::JobError(format!("No job named '{}'", old_id)));
+    }
+    if new_file.exists() {
+        return Err(WorkSplitError::JobError(format!("A job named '{}' already exists", new_id)));
+    }
+
+    // Syncing first drops stale entries, e.g. one left behind under the new ID
+    let mut status_manager = StatusManager::new(&jobs_dir)?;
+    status_manager.sync_with_jobs(&jobs_manager.discover_jobs()?)?;
+    if status_manager.get(old_id).is_some_and(|e| e.status.is_stuck()) {
+        return Err(WorkSplitError::JobError(format!(
+            "Job '{}' is in progress; cancel or reset it before renaming",
+            old_id
+        )));
+    }
+
+    fs::rename(&old_file, &new_file)?;
+    let mut report = MoveReport::default();
+    if status_manager.get(old_id).is_some() {
+        if let Err(e) = status_manager.rename_job(old_id, new_id) {
+            // Keep the file and its status together
+            fs::rename(&new_file, &old_file)?;
+            return Err(e.into());
+        }
+        report.status_moved = true;
+    }
+    report.staged_moved = StagingArea::new(&jobs_dir).rename_job(old_id, new_id)?;
+    let mut frozen = FrozenManifest::load(&jobs_dir)?;
+    if frozen.rename_job(old_id, new_id) {
+        frozen.save(&jobs_dir)?;
+        report.frozen_moved = true;
+    }
+
+    for id in jobs_manager.discover_jobs()? {
+        let path = jobs_dir.join(format!("{}.md", id));
+        let Ok(content) = fs::read_to_string(&path) else { continue };
+        if let Some(updated) = rename_dependency(&content, old_id, new_id) {
+            write_atomic(&path, updated)?;
+            report.dependents.push(id);
+        }
+    }
+    Ok(report)
+}
+
+/// `worksplit mv`
+pub fn rename_job(project_root: &Path, old_id: &str, new_id: &str) -> Result<(), WorkSplitError> {
+    let report = move_job(project_root, old_id, new_id)?;
+    println!("Renamed: {} -> {}", job_id(old_id), job_id(new_id));
+    if report.status_moved {
+        println!("  Status kept");
+    }
+    if report.staged_moved {
+        println!("  Staged outputs kept");
+    }
+    if report.frozen_moved {
+        println!("  Frozen files kept");
+    }
+    for id in &report.dependents {
+        println!("  Updated depends_on in {}", id);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::JobStatus;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rename_dependency() {
+        let flow = "---\noutput_file: a.rs\ndepends_on: [auth_001, auth_0011]\n---\n\nSee auth_001.\n";
+        assert_eq!(
+            rename_dependency(flow, "auth_001", "login_001").unwrap(),
+            "---\noutput_file: a.rs\ndepends_on: [login_001, auth_0011]\n---\n\nSee auth_001.\n"
+        );
+
+        let block = "---\ndepends_on:\n  - \"auth_001\"\n  - other\ntags: [auth_001]\n---\n";
+        assert_eq!(
+            rename_dependency(block, "auth_001", "login_001").unwrap(),
+            "---\ndepends_on:\n  - \"login_001\"\n  - other\ntags: [auth_001]\n---\n"
+        );
+
+        assert!(rename_dependency("---\ndepends_on: [other]\n---\n", "auth_001", "login_001").is_none());
+    }
+
+    #[test]
+    fn test_move_job_keeps_status_and_dependents() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let jobs_dir = root.join("jobs");
+        fs::create_dir_all(&jobs_dir).unwrap();
+        fs::write(jobs_dir.join("auth_001.md"), "---\noutput_dir: src/\noutput_file: auth.rs\n---\n\nAuth\n").unwrap();
+        fs::write(
+            jobs_dir.join("auth_002.md"),
+            "---\ndepends_on: [auth_001]\noutput_dir: src/\noutput_file: session.rs\n---\n\nSession\n",
+        )
+        .unwrap();
+        let mut status = StatusManager::new(&jobs_dir).unwrap();
+        status.sync_with_jobs(&["auth_001".to_string(), "auth_002".to_string()]).unwrap();
+        status.update_status("auth_001", JobStatus::Pass).unwrap();
+        status.mark_ran("auth_001").unwrap();
+        let mut frozen = FrozenManifest::default();
+        frozen.freeze("auth_001", Path::new("src/auth.rs"), "pub fn auth() {}");
+        frozen.save(&jobs_dir).unwrap();
+
+        let report = move_job(root, "auth_001.md", "login_001").unwrap();
+        assert_eq!(
+            report,
+            MoveReport {
+                status_moved: true,
+                staged_moved: false,
+                frozen_moved: true,
+                dependents: vec!["auth_002".to_string()],
+            }
+        );
+        let frozen = FrozenManifest::load(&jobs_dir).unwrap();
+        assert_eq!(frozen.get(Path::new("src/auth.rs")).unwrap().job_id, "login_001");
+        assert!(!jobs_dir.join("auth_001.md").exists());
+        assert!(jobs_dir.join("login_001.md").is_file());
+        assert!(fs::read_to_string(jobs_dir.join("auth_002.md")).unwrap().contains("depends_on: [login_001]"));
+
+        let status = StatusManager::new(&jobs_dir).unwrap();
+        assert!(status.get("auth_001").is_none());
+        let entry = status.get("login_001").unwrap();
+        assert_eq!(entry.status, JobStatus::Pass);
+        assert!(entry.ran);
+
+        assert!(move_job(root, "missing", "x").is_err());
+        assert!(move_job(root, "login_001", "auth_002").is_err());
+        assert!(move_job(root, "login_001", "../escape").is_err());
+    }
+}
@@ -1,23 +1,34 @@
+use clap::ValueEnum;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::info;
 
+use crate::core::job_templates::find_job_template;
 use crate::error::WorkSplitError;
-use crate::models::JobTemplate;
+use crate::models::{Config, JobTemplate, Language};
 
 /// Create a new job from a template
+///
+/// Paths left out follow the conventions of `language`, or of the project's
+/// configured language.
+#[allow(clippy::too_many_arguments)]
 pub fn create_new_job(
-    project_root: &PathBuf,
+    project_root: &Path,
     name: &str,
     template: JobTemplate,
+    language: Option<Language>,
     target_files: Option<Vec<PathBuf>>,
-    output_dir: &PathBuf,
+    output_dir: Option<PathBuf>,
     output_file: Option<String>,
     context_files: Option<Vec<PathBuf>>,
 ) -> Result<(), WorkSplitError> {
     // Validate job name
     validate_job_name(name)?;
 
+    let language = language.unwrap_or_else(|| project_language(project_root));
+    let output_dir = output_dir.unwrap_or_else(|| PathBuf::from(language.default_output_dir()));
+
     let jobs_dir = project_root.join("jobs");
 
     // Ensure jobs directory exists
@@ -36,8 +47,9 @@ pub fn create_new_job(
     let content = generate_template(
         template,
         name,
+        language,
         target_files.as_ref(),
-        output_dir,
+        &output_dir,
         output_file.as_ref(),
         context_files.as_ref(),
     );
@@ -67,8 +79,85 @@ pub fn create_new_job(
     Ok(())
 }
 
+/// Create a new job from a project template (`jobs/_templates/<template>.md`)
+///
+/// The template's `{{slot}}` placeholders are filled from the job's name and
+/// paths, then from `set` (`KEY=VALUE`, which also overrides those). The
+/// names of the built-in templates (`replace`, `edit`, ...) pick those.
+#[allow(clippy::too_many_arguments)]
+pub fn create_new_job_from_template(
+    project_root: &Path,
+    name: &str,
+    template: &str,
+    language: Option<Language>,
+    target_files: Option<Vec<PathBuf>>,
+    output_dir: Option<PathBuf>,
+    output_file: Option<String>,
+    context_files: Option<Vec<PathBuf>>,
+    set: &[String],
+) -> Result<(), WorkSplitError> {
+    if let Ok(builtin) = JobTemplate::from_str(template, true) {
+        return create_new_job(project_root, name, builtin, language, target_files, output_dir, output_file, context_files);
+    }
+    validate_job_name(name)?;
+    let jobs_dir = project_root.join("jobs");
+    let template = find_job_template(&jobs_dir, template)?;
+
+    let language = language.unwrap_or_else(|| project_language(project_root));
+    let output_dir = output_dir.unwrap_or_else(|| PathBuf::from(language.default_output_dir()));
+    let mut values = BTreeMap::from([
+        ("job_name".to_string(), name.to_string()),
+        ("title".to_string(), name_to_title(name)),
+        ("output_dir".to_string(), output_dir.display().to_string()),
+        ("output_file".to_string(), output_file.unwrap_or_else(|| default_output_file(name, language))),
+        ("context_files".to_string(), format_context_files(context_files.as_ref(), language)),
+        ("target_files".to_string(), format_target_files(target_files.as_ref(), language)),
+    ]);
+    for assignment in set {
+        let (key, value) = assignment.split_once('=').ok_or_else(|| WorkSplitError::JobError(
+            format!("--set expects KEY=VALUE, got '{}'", assignment)))?;
+        values.insert(key.trim().to_string(), value.to_string());
+    }
+
+    let job_file = jobs_dir.join(format!("{}.md", name));
+    if job_file.exists() {
+        return Err(WorkSplitError::JobAlreadyExists(name.to_string()));
+    }
+    let (content, unfilled) = template.render(&values);
+    fs::write(&job_file, &content)?;
+    info!("Created job file: {}", job_file.display());
+
+    println!("Created job: jobs/{}.md", name);
+    println!();
+    println!("Template: {} ({})", template.name, template.path.strip_prefix(project_root).unwrap_or(&template.path).display());
+    if !unfilled.is_empty() {
+        println!();
+        println!("Placeholders left to fill in: {}", unfilled.iter().map(|slot| format!("{{{{{}}}}}", slot)).collect::<Vec<_>>().join(", "));
+        println!("(or pass them with --set {}=...)", unfilled[0]);
+    }
+
+    println!();
+    println!("Next steps:");
+    println!("1. Edit the job file to add specific requirements");
+    println!("2. Run 'worksplit validate' to check the job");
+    println!("3. Run 'worksplit run --job {}' to execute", name);
+
+    Ok(())
+}
+
+/// The language configured in the project's worksplit.toml (Rust if none)
+pub(crate) fn project_language(project_root: &Path) -> Language {
+    Config::load_from_dir(project_root).unwrap_or_default().project.language
+}
+
+/// Default output file for a job: its last name segment, named the way
+/// `language` names files (`auth_001_login` -> `login.rs`, `Login.cs`)
+pub(crate) fn default_output_file(name: &str, language: Language) -> String {
+    language.source_file_name(name.split('_').next_back().unwrap_or(name))
+}
+
 /// Validate that the job name is valid
-fn validate_job_name(name: &str) -> Result<(), WorkSplitError> {
+pub(crate) fn validate_job_name(name: &str) -> Result<(), WorkSplitError> {
     if name.is_empty() {
         return Err(WorkSplitError::InvalidJobName(
             "Job name cannot be empty".to_string(),
@@ -96,45 +185,50 @@ fn validate_job_name(name: &str) -> Result<(), WorkSplitError> {
 }
 
 /// Generate template content based on template type
-fn generate_template(
+pub(crate) fn generate_template(
     template: JobTemplate,
     name: &str,
+    language: Language,
     target_files: Option<&Vec<PathBuf>>,
-    output_dir: &PathBuf,
+    output_dir: &Path,
     output_file: Option<&String>,
     context_files: Option<&Vec<PathBuf>>,
 ) -> String {
     let output_dir_str = output_dir.display().to_string();
-    let default_output_file = format!("{}.rs", name.split('_').last().unwrap_or(name));
     let output_file_str = output_file
         .cloned()
-        .unwrap_or(default_output_file);
+        .unwrap_or_else(|| default_output_file(name, language));
 
     match template {
         JobTemplate::Replace => generate_replace_template(
             name,
+            language,
             &output_dir_str,
             &output_file_str,
             context_files,
         ),
         JobTemplate::Edit => generate_edit_template(
             name,
+            language,
             target_files,
             &output_dir_str,
             &output_file_str,
         ),
         JobTemplate::Split => generate_split_template(
             name,
+            language,
             &output_dir_str,
             &output_file_str,
         ),
         JobTemplate::Sequential => generate_sequential_template(
             name,
+            language,
             &output_dir_str,
             &output_file_str,
         ),
         JobTemplate::Tdd => generate_tdd_template(
             name,
+            language,
             &output_dir_str,
             &output_file_str,
             context_files,
@@ -142,7 +236,12 @@ fn generate_template(
     }
 }
 
-fn format_context_files(context_files: Option<&Vec<PathBuf>>) -> String {
+/// Placeholder path of an example source file
+fn example_path(language: Language, stem: &str) -> String {
+    format!("{}{}", language.default_output_dir(), language.source_file_name(stem))
+}
+
+fn format_context_files(context_files: Option<&Vec<PathBuf>>, language: Language) -> String {
     match context_files {
         Some(files) if !files.is_empty() => {
             let formatted: Vec<String> = files
@@ -151,11 +250,11 @@ fn format_context_files(context_files: Option<&Vec<PathBuf>>) -> String {
                 .collect();
             formatted.join("\n")
         }
-        _ => "  # - src/example.rs".to_string(),
+        _ => format!("  # - {}", example_path(language, "example")),
     }
 }
 
-fn format_target_files(target_files: Option<&Vec<PathBuf>>) -> String {
+fn format_target_files(target_files: Option<&Vec<PathBuf>>, language: Language) -> String {
     match target_files {
         Some(files) if !files.is_empty() => {
             let formatted: Vec<String> = files
@@ -164,17 +263,18 @@ fn format_target_files(target_files: Option<&Vec<PathBuf>>) -> String {
                 .collect();
             formatted.join("\n")
         }
-        _ => "  - src/main.rs".to_string(),
+        _ => format!("  - {}", example_path(language, "main")),
     }
 }
 
 fn generate_replace_template(
     name: &str,
+    language: Language,
     output_dir: &str,
     output_file: &str,
     context_files: Option<&Vec<PathBuf>>,
 ) -> String {
-    let ctx = format_context_files(context_files);
+    let ctx = format_context_files(context_files, language);
     let title = name_to_title(name);
 
     format!(
@@ -202,7 +302,7 @@ output_file: {output_file}
 
 ## Example Usage
 
-```rust
+```{fence}
 // Show expected usage
 ```
 "#,
@@ -210,16 +310,18 @@ output_file: {output_file}
         output_dir = output_dir,
         output_file = output_file,
         title = title,
+        fence = language.fence_tag(),
     )
 }
 
 fn generate_edit_template(
     name: &str,
+    language: Language,
     target_files: Option<&Vec<PathBuf>>,
     output_dir: &str,
     output_file: &str,
 ) -> String {
-    let targets = format_target_files(target_files);
+    let targets = format_target_files(target_files, language);
     let title = name_to_title(name);
 
     format!(
@@ -257,21 +359,25 @@ Describe the specific edits using FILE/FIND/REPLACE/END format:
 
 fn generate_split_template(
     name: &str,
+    language: Language,
     output_dir: &str,
-    _output_file: &str,
+    output_file: &str,
 ) -> String {
     let title = name_to_title(name);
+    let entry = split_entry_file(language, output_file);
+    let part1 = language.source_file_name("part1");
+    let part2 = language.source_file_name("part2");
 
     format!(
         r#"---
 mode: split
-target_file: src/large_file.rs
+target_file: {target_file}
 output_dir: {output_dir}
-output_file: mod.rs
+output_file: {entry}
 output_files:
-  - {output_dir}mod.rs
-  - {output_dir}part1.rs
-  - {output_dir}part2.rs
+  - {output_dir}{entry}
+  - {output_dir}{part1}
+  - {output_dir}{part2}
 ---
 
 # {title}
@@ -281,50 +387,83 @@ Split large file into a directory-based module structure.
 
 ## File Structure
 
-- `mod.rs`: Main struct and public API
-- `part1.rs`: First logical group of functions
-- `part2.rs`: Second logical group of functions
+- `{entry}`: Main struct and public API
+- `{part1}`: First logical group of functions
+- `{part2}`: Second logical group of functions
 
 ## Function Signatures (REQUIRED)
 
-### part1.rs
-```rust
-pub(crate) fn function_one(
-    param: &Type,
-) -> Result<ReturnType, Error>
+### {part1}
+```{fence}
+{signature1}
 ```
 
-### part2.rs
-```rust
-pub(crate) fn function_two(
-    param: &Type,
-) -> Result<ReturnType, Error>
+### {part2}
+```{fence}
+{signature2}
 ```
 
 ## Extraction Plan
 
-- `function_one` and related helpers -> part1.rs
-- `function_two` and related helpers -> part2.rs
-- Struct definitions and public API remain in mod.rs
+- `function_one` and related helpers -> {part1}
+- `function_two` and related helpers -> {part2}
+- Struct definitions and public API remain in {entry}
 "#,
+        target_file = example_path(language, "large_file"),
         output_dir = output_dir,
+        entry = entry,
+        part1 = part1,
+        part2 = part2,
+        fence = language.fence_tag(),
+        signature1 = signature_example(language, "function_one"),
+        signature2 = signature_example(language, "function_two"),
         title = title,
     )
 }
 
+/// Entry file of a split module: the file the language resolves a
+/// directory import to, or the job's own output file
+fn split_entry_file(language: Language, output_file: &str) -> String {
+    match language {
+        Language::Rust => "mod.rs".to_string(),
+        Language::Typescript => "index.ts".to_string(),
+        Language::Solidity | Language::CSharp => output_file.to_string(),
+    }
+}
+
+/// Placeholder signature for the split template's function listings
+fn signature_example(language: Language, function: &str) -> String {
+    match language {
+        Language::Rust => format!(
+            "pub(crate) fn {}(\n    param: &Type,\n) -> Result<ReturnType, Error>",
+            function
+        ),
+        Language::Typescript => format!("export function {}(param: Type): ReturnType", function),
+        Language::Solidity => format!(
+            "function {}(Type memory param) internal returns (ReturnType)",
+            function
+        ),
+        Language::CSharp => format!("internal static ReturnType {}(Type param)", function),
+    }
+}
+
 fn generate_sequential_template(
     name: &str,
+    language: Language,
     output_dir: &str,
     output_file: &str,
 ) -> String {
     let title = name_to_title(name);
+    let file1 = language.source_file_name("file1");
+    let file2 = language.source_file_name("file2");
+    let file3 = language.source_file_name("file3");
 
     format!(
         r#"---
 output_files:
-  - {output_dir}file1.rs
-  - {output_dir}file2.rs
-  - {output_dir}file3.rs
+  - {output_dir}{file1}
+  - {output_dir}{file2}
+  - {output_dir}{file3}
 sequential: true
 output_dir: {output_dir}
 output_file: {output_file}
@@ -337,17 +476,17 @@ Multi-file feature that exceeds single-call token limits.
 
 ## File Responsibilities
 
-### file1.rs
+### {file1}
 - Core types and traits
 - Shared utilities
 
-### file2.rs
+### {file2}
 - Main implementation
-- Uses types from file1.rs
+- Uses types from {file1}
 
-### file3.rs
+### {file3}
 - Additional functionality
-- Depends on file1.rs and file2.rs
+- Depends on {file1} and {file2}
 
 ## Implementation Notes
 - Each file gets its own LLM call
@@ -356,19 +495,23 @@ Multi-file feature that exceeds single-call token limits.
 "#,
         output_dir = output_dir,
         output_file = output_file,
+        file1 = file1,
+        file2 = file2,
+        file3 = file3,
         title = title,
     )
 }
 
 fn generate_tdd_template(
     name: &str,
+    language: Language,
     output_dir: &str,
     output_file: &str,
     context_files: Option<&Vec<PathBuf>>,
 ) -> String {
-    let ctx = format_context_files(context_files);
+    let ctx = format_context_files(context_files, language);
     let title = name_to_title(name);
-    let test_file = output_file.replace(".rs", "_test.rs");
+    let test_file = language.test_file_name(output_file);
 
     format!(
         r#"---
@@ -462,4 +605,66 @@ mod tests {
         assert_eq!(name_to_title("template_002_new_job"), "Template New Job");
         assert_eq!(name_to_title("simple"), "Simple");
     }
+
+    #[test]
+    fn test_default_output_file_follows_language() {
+        assert_eq!(default_output_file("auth_001_login", Language::Rust), "login.rs");
+        assert_eq!(default_output_file("auth_001_login", Language::Typescript), "login.ts");
+        assert_eq!(default_output_file("token_001_vault", Language::Solidity), "Vault.sol");
+        assert_eq!(default_output_file("api_001_order_service", Language::CSharp), "Service.cs");
+    }
+
+    #[test]
+    fn test_create_new_job_from_project_template() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let templates = temp.path().join("jobs/_templates");
+        fs::create_dir_all(&templates).unwrap();
+        fs::write(
+            templates.join("service.md"),
+            "<!-- Service -->\n---\noutput_dir: {{output_dir}}\noutput_file: {{output_file}}\n---\n\n# {{title}} ({{job_name}})\n\n{{entity}} repository\n",
+        )
+        .unwrap();
+
+        let set = vec!["entity=Order".to_string(), "output_file=order_service.rs".to_string()];
+        create_new_job_from_template(temp.path(), "svc_001_orders", "service", Some(Language::Rust), None, None, None, None, &set).unwrap();
+        assert_eq!(
+            fs::read_to_string(temp.path().join("jobs/svc_001_orders.md")).unwrap(),
+            "---\noutput_dir: src/\noutput_file: order_service.rs\n---\n\n# Svc Orders (svc_001_orders)\n\nOrder repository\n"
+        );
+
+        let exists = create_new_job_from_template(temp.path(), "svc_001_orders", "service", None, None, None, None, None, &[]);
+        assert!(matches!(exists, Err(WorkSplitError::JobAlreadyExists(_))));
+        let bad_set = create_new_job_from_template(temp.path(), "svc_002", "service", None, None, None, None, None, &["entity".to_string()]);
+        assert!(bad_set.unwrap_err().to_string().contains("KEY=VALUE"));
+
+        create_new_job_from_template(temp.path(), "svc_003", "tdd", Some(Language::Rust), None, None, None, None, &[]).unwrap();
+        assert!(fs::read_to_string(temp.path().join("jobs/svc_003.md")).unwrap().contains("test_file:"));
+    }
+
+    #[test]
+    fn test_generate_template_uses_language_conventions() {
+        let dir = Path::new("contracts/");
+
+        let tdd = generate_template(
+            JobTemplate::Tdd, "token_001_vault", Language::Solidity, None, dir, None, None,
+        );
+        assert!(tdd.contains("output_dir: contracts/"));
+        assert!(tdd.contains("output_file: Vault.sol"));
+        assert!(tdd.contains("test_file: Vault.t.sol"));
+
+        let split = generate_template(
+            JobTemplate::Split, "ui_001_split", Language::Typescript, None, Path::new("src/ui/"), None, None,
+        );
+        assert!(split.contains("output_file: index.ts"));
+        assert!(split.contains("  - src/ui/part1.ts"));
+        assert!(split.contains("```typescript"));
+        assert!(!split.contains(".rs"));
+
+        let replace = generate_template(
+            JobTemplate::Replace, "api_001_orders", Language::CSharp, None, Path::new("src/"), None, None,
+        );
+        assert!(replace.contains("output_file: Orders.cs"));
+        assert!(replace.contains("# - src/Example.cs"));
+        assert!(replace.contains("```csharp"));
+    }
 }
@@ -0,0 +1,268 @@
+//! `worksplit new-job --interactive`: build a job file step by step
+//!
+//! Walks through the template, output path, context files (picked with a
+//! fuzzy search over the project's files) and instructions (edited in
+//! `$EDITOR`), then validates the job right away so it is ready to run.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Confirm, Editor, Input, Select};
+
+use crate::commands::new_job::{default_output_file, generate_template, validate_job_name};
+use crate::core::schema::{job_frontmatter, validate_value, SchemaKind};
+use crate::core::targets::walk_files;
+use crate::core::{apply_job_defaults, JobsManager};
+use crate::error::WorkSplitError;
+use crate::models::{Config, JobTemplate, Language};
+
+/// Matches shown per fuzzy search
+const MAX_MATCHES: usize = 15;
+
+const TEMPLATES: &[(JobTemplate, &str)] = &[
+    (JobTemplate::Replace, "replace     - generate a new file"),
+    (JobTemplate::Edit, "edit        - surgical changes to existing files"),
+    (JobTemplate::Split, "split       - break a large file into modules"),
+    (JobTemplate::Sequential, "sequential  - several files, one after another"),
+    (JobTemplate::Tdd, "tdd         - tests first, then the implementation"),
+];
+
+fn input_error(e: dialoguer::Error) -> WorkSplitError {
+    WorkSplitError::InteractiveInput(e.to_string())
+}
+
+/// Score how well `query` matches `candidate`: every query character must
+/// appear in order. Consecutive characters and characters at the start of a
+/// path segment or word score higher. `None` if it doesn't match.
+pub(crate) fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let chars: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0i64;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let index = (next..chars.len()).find(|&i| chars[i] == q)?;
+        score += 1;
+        if index > 0 && previous == Some(index - 1) {
+            score += 5;
+        }
+        if index == 0 || matches!(chars[index - 1], '/' | '_' | '-' | '.') {
+            score += 3;
+        }
+        previous = Some(index);
+        next = index + 1;
+    }
+    // Prefer shorter paths among equal matches
+    Some(score * 100 - chars.len() as i64)
+}
+
+/// The best `limit` files matching `query`, best first
+pub(crate) fn fuzzy_matches<'a>(query: &str, files: &'a [PathBuf], limit: usize) -> Vec<&'a PathBuf> {
+    let mut scored: Vec<(i64, &PathBuf)> = files
+        .iter()
+        .filter_map(|file| fuzzy_score(query, &file.display().to_string()).map(|score| (score, file)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    scored.into_iter().take(limit).map(|(_, file)| file).collect()
+}
+
+/// Pick up to `limit` files by repeated fuzzy search; an empty search ends it
+fn pick_files(theme: &ColorfulTheme, what: &str, files: &[PathBuf], limit: usize) -> Result<Vec<PathBuf>, WorkSplitError> {
+    let mut picked: Vec<PathBuf> = Vec::new();
+    while picked.len() < limit {
+        let query: String = Input::with_theme(theme)
+            .with_prompt(format!("{} ({}/{}) - search, or Enter to finish", what, picked.len(), limit))
+            .allow_empty(true)
+            .interact_text()
+            .map_err(input_error)?;
+        if query.trim().is_empty() {
+            break;
+        }
+
+        let candidates: Vec<PathBuf> = files.iter().filter(|f| !picked.contains(f)).cloned().collect();
+        let matches = fuzzy_matches(&query, &candidates, MAX_MATCHES);
+        if matches.is_empty() {
+            println!("No files match '{}'", query);
+            continue;
+        }
+        let mut items: Vec<String> = matches.iter().map(|p| p.display().to_string()).collect();
+        items.push("(search again)".to_string());
+        let choice = Select::with_theme(theme)
+            .with_prompt("Pick a file")
+            .items(&items)
+            .default(0)
+            .interact()
+            .map_err(input_error)?;
+        if let Some(&file) = matches.get(choice) {
+            picked.push(file.clone());
+        }
+    }
+    Ok(picked)
+}
+
+/// Problems that would stop the job from running, as `worksplit validate`
+/// reports them
+fn check_job(project_root: &Path, config: &Config, name: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    let job_file = project_root.join("jobs").join(format!("{}.md", name));
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), config.limits.clone());
+    if let Ok(mut frontmatter) = job_frontmatter(&job_file) {
+        apply_job_defaults(jobs_manager.job_defaults(), &mut frontmatter);
+        problems.extend(validate_value(SchemaKind::Job, &frontmatter));
+    }
+    match jobs_manager.parse_job(name) {
+        Ok(job) => {
+            for context_file in &job.metadata.context_files {
+                if !project_root.join(context_file).exists() {
+                    problems.push(format!("Context file not found: {}", context_file.display()));
+                }
+            }
+        }
+        Err(e) => problems.push(e.to_string()),
+    }
+    problems
+}
+
+/// Create a job interactively; `name` is asked for when not given and path
+/// defaults follow `language` (or the project's configured language)
+pub fn create_new_job_interactive(
+    project_root: &Path,
+    name: Option<String>,
+    language: Option<Language>,
+) -> Result<(), WorkSplitError> {
+    let theme = ColorfulTheme::default();
+    let config = Config::load_from_dir(project_root).unwrap_or_default();
+    let language = language.unwrap_or(config.project.language);
+
+    let name = match name {
+        Some(name) => {
+            validate_job_name(&name)?;
+            name
+        }
+        None => Input::with_theme(&theme)
+            .with_prompt("Job name (e.g. auth_001_login)")
+            .validate_with(|name: &String| validate_job_name(name).map_err(|e| e.to_string()))
+            .interact_text()
+            .map_err(input_error)?,
+    };
+    let jobs_dir = project_root.join("jobs");
+    let job_file = jobs_dir.join(format!("{}.md", name));
+    if job_file.exists() {
+        return Err(WorkSplitError::JobAlreadyExists(name));
+    }
+
+    let labels: Vec<&str> = TEMPLATES.iter().map(|(_, label)| *label).collect();
+    let choice = Select::with_theme(&theme)
+        .with_prompt("Template")
+        .items(&labels)
+        .default(0)
+        .interact()
+        .map_err(input_error)?;
+    let template = TEMPLATES[choice].0;
+
+    let output_dir: String = Input::with_theme(&theme)
+        .with_prompt("Output directory")
+        .default(language.default_output_dir().to_string())
+        .interact_text()
+        .map_err(input_error)?;
+    let output_file: String = Input::with_theme(&theme)
+        .with_prompt("Output file")
+        .default(default_output_file(&name, language))
+        .interact_text()
+        .map_err(input_error)?;
+
+    let mut project_files = Vec::new();
+    walk_files(project_root, project_root, &mut project_files)?;
+    project_files.sort();
+
+    let target_files = match template {
+        JobTemplate::Edit => Some(pick_files(&theme, "Files to edit", &project_files, usize::MAX)?),
+        _ => None,
+    };
+    let context_files = pick_files(&theme, "Context files", &project_files, config.limits.max_context_files)?;
+
+    let mut content = generate_template(
+        template,
+        &name,
+        language,
+        target_files.as_ref(),
+        Path::new(&output_dir),
+        Some(&output_file),
+        Some(&context_files),
+    );
+
+    fs::create_dir_all(&jobs_dir)?;
+    let mut open_editor = Confirm::with_theme(&theme)
+        .with_prompt("Write the instructions in $EDITOR now?")
+        .default(true)
+        .interact()
+        .map_err(input_error)?;
+    loop {
+        if open_editor {
+            if let Some(edited) = Editor::new().extension(".md").edit(&content).map_err(input_error)? {
+                content = edited;
+            }
+        }
+        fs::write(&job_file, &content)?;
+
+        let problems = check_job(project_root, &config, &name);
+        if problems.is_empty() {
+            println!("Created job: jobs/{}.md (valid)", name);
+            println!("Run 'worksplit run --job {}' to execute", name);
+            return Ok(());
+        }
+
+        println!("Job '{}' has problems:", name);
+        for problem in &problems {
+            println!("  - {}", problem);
+        }
+        let action = Select::with_theme(&theme)
+            .with_prompt("What now?")
+            .items(&["Fix it in $EDITOR", "Keep the file as is", "Discard the job"])
+            .default(0)
+            .interact()
+            .map_err(input_error)?;
+        match action {
+            0 => open_editor = true,
+            1 => {
+                println!("Kept jobs/{}.md; run 'worksplit validate' after fixing it", name);
+                return Ok(());
+            }
+            _ => {
+                fs::remove_file(&job_file)?;
+                println!("Discarded job '{}'", name);
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_matches() {
+        let files: Vec<PathBuf> = [
+            "src/core/runner/mod.rs",
+            "src/models/user.rs",
+            "src/services/user_service.rs",
+            "tests/runner_test.rs",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+        assert_eq!(fuzzy_score("xyz", "src/main.rs"), None);
+        assert_eq!(fuzzy_score("", "a.rs"), Some(-4));
+
+        let matches = fuzzy_matches("user", &files, 10);
+        assert_eq!(matches[0], &PathBuf::from("src/models/user.rs"));
+        assert_eq!(matches.len(), 2);
+
+        // Segment starts and consecutive characters beat scattered matches
+        let matches = fuzzy_matches("runmod", &files, 1);
+        assert_eq!(matches, vec![&PathBuf::from("src/core/runner/mod.rs")]);
+    }
+}
@@ -0,0 +1,113 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::core::{load_config, Runner};
+use crate::error::WorkSplitError;
+use crate::models::JobStatus;
+
+/// Options for `worksplit oneshot`
+#[derive(Debug, Default)]
+pub struct OneshotOptions {
+    /// File to generate, relative to the project root
+    pub output: PathBuf,
+    pub context: Vec<PathBuf>,
+    pub instructions: String,
+    pub profile: Option<String>,
+    pub model: Option<String>,
+}
+
+/// The instructions from `-m`, or from the positional argument, where `-`
+/// reads them from stdin
+pub fn read_instructions(argument: Option<&str>, message: Option<String>) -> Result<String, WorkSplitError> {
+    let instructions = match (message, argument) {
+        (Some(message), _) => message,
+        (None, Some("-")) => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            input
+        }
+        (None, Some(text)) => text.to_string(),
+        (None, None) => String::new(),
+    };
+    if instructions.trim().is_empty() {
+        return Err(WorkSplitError::JobError(
+            "No instructions given; pass them with -m, as an argument, or on stdin with -".to_string()));
+    }
+    Ok(instructions)
+}
+
+/// Job file for a one-shot generation; paths are quoted as JSON strings,
+/// which YAML reads as they are
+fn oneshot_job(options: &OneshotOptions) -> Result<String, WorkSplitError> {
+    let quote = |path: &Path| serde_json::to_string(&path.display().to_string()).unwrap_or_default();
+    let file_name = options.output.file_name().ok_or_else(|| WorkSplitError::JobError(format!(
+        "--output {} is not a file path", options.output.display())))?;
+    let output_dir = match options.output.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let mut job = String::from("---\ncontext_files:");
+    if options.context.is_empty() {
+        job.push_str(" []");
+    }
+    for path in &options.context {
+        job.push_str(&format!("\n  - {}", quote(path)));
+    }
+    job.push_str(&format!("\noutput_dir: {}\noutput_file: {}\n---\n\n{}\n",
+        quote(output_dir), quote(Path::new(file_name)), options.instructions.trim()));
+    Ok(job)
+}
+
+/// Run one generation with the full create/verify pipeline, without adding
+/// a job to the project. The temporary job file is removed afterwards.
+pub async fn run_oneshot_job(project_root: &Path, options: OneshotOptions) -> Result<(), WorkSplitError> {
+    let config = load_config(project_root, options.profile.as_deref(), options.model.clone(), None, None, false)?;
+    let mut runner = Runner::new(config, project_root.to_path_buf())?;
+
+    let job_id = format!("_oneshot_{}", std::process::id());
+    let job_path = runner.jobs_manager().jobs_dir().join(format!("{}.md", job_id));
+    fs::write(&job_path, oneshot_job(&options)?)?;
+    let result = runner.run_oneshot(&job_id).await;
+    let _ = fs::remove_file(&job_path);
+
+    let result = result?;
+    match result.status {
+        JobStatus::Pass => {
+            for path in &result.output_paths {
+                let relative = path.strip_prefix(project_root).unwrap_or(path);
+                match result.output_lines {
+                    Some(lines) => println!("Wrote {} ({} lines)", relative.display(), lines),
+                    None => println!("Wrote {}", relative.display()),
+                }
+            }
+            Ok(())
+        }
+        _ => Err(WorkSplitError::JobError(format!("One-shot generation of {} failed: {}",
+            options.output.display(), result.error.unwrap_or_else(|| format!("{:?}", result.status))))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oneshot_job_file() {
+        let options = OneshotOptions {
+            output: PathBuf::from("src/foo.rs"),
+            context: vec![PathBuf::from("src/lib.rs"), PathBuf::from("docs/a b.md")],
+            instructions: "Write foo()\n".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(oneshot_job(&options).unwrap(),
+            "---\ncontext_files:\n  - \"src/lib.rs\"\n  - \"docs/a b.md\"\noutput_dir: \"src\"\noutput_file: \"foo.rs\"\n---\n\nWrite foo()\n");
+
+        let bare = OneshotOptions { output: PathBuf::from("foo.rs"), instructions: "x".to_string(), ..Default::default() };
+        assert!(oneshot_job(&bare).unwrap().starts_with("---\ncontext_files: []\noutput_dir: \".\"\n"));
+
+        assert!(read_instructions(None, Some("  ".to_string())).is_err());
+        assert_eq!(read_instructions(Some("Write foo"), None).unwrap(), "Write foo");
+    }
+}
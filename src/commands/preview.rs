@@ -1,21 +1,26 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::Path;
 
-use crate::core::{load_config, JobsManager};
+use crate::core::{load_config, JobsManager, CONVENTIONS_FILE};
 use crate::error::WorkSplitError;
 use crate::models::OutputMode;
 
 /// Preview the prompt for a job without running it
-pub fn preview_job(project_root: &PathBuf, job_id: &str) -> Result<(), WorkSplitError> {
+pub fn preview_job(project_root: &Path, job_id: &str) -> Result<(), WorkSplitError> {
     // Load config
-    let config = load_config(project_root, None, None, None, false)?;
+    let config = load_config(project_root, None, None, None, None, false)?;
 
     // Create jobs manager
-    let jobs_manager = JobsManager::new(project_root.clone(), config.limits.clone());
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), config.limits.clone());
 
     // Parse job
     let job = jobs_manager.parse_job(job_id)?;
 
+    // Edit-mode targets with directories and globs expanded
+    let target_files = jobs_manager
+        .resolve_target_files(&job)
+        .unwrap_or_else(|_| job.metadata.target_files.clone().unwrap_or_default());
+
     // Display job info
     println!("=== JOB PREVIEW: {} ===\n", job_id);
     println!("Mode: {:?}", job.metadata.mode);
@@ -35,9 +40,9 @@ pub fn preview_job(project_root: &PathBuf, job_id: &str) -> Result<(), WorkSplit
     }
 
     // Show target files for edit mode
-    if let Some(target_files) = &job.metadata.target_files {
+    if job.metadata.target_files.is_some() {
         println!("\nTarget files (edit mode):");
-        for target in target_files {
+        for target in &target_files {
             let full_path = project_root.join(target);
             if let Ok(content) = fs::read_to_string(&full_path) {
                 println!("  - {} ({} lines)", target.display(), content.lines().count());
@@ -49,12 +54,13 @@ pub fn preview_job(project_root: &PathBuf, job_id: &str) -> Result<(), WorkSplit
 
     // Load and display system prompt
     let jobs_dir = project_root.join("jobs");
-    let system_prompt_name = match job.metadata.mode {
-        OutputMode::Edit => "_systemprompt_edit.md",
-        OutputMode::Split => "_systemprompt_split.md",
-        _ => "_systemprompt_create.md",
+    let system_prompt_name = match (&job.metadata.system_prompt, job.metadata.mode) {
+        (Some(name), _) => format!("_systemprompt_{}.md", name),
+        (None, OutputMode::Edit) => "_systemprompt_edit.md".to_string(),
+        (None, OutputMode::Split) => "_systemprompt_split.md".to_string(),
+        _ => "_systemprompt_create.md".to_string(),
     };
-    let system_prompt_path = jobs_dir.join(system_prompt_name);
+    let system_prompt_path = jobs_dir.join(&system_prompt_name);
 
     println!("\n=== SYSTEM PROMPT ({}) ===", system_prompt_name);
     if let Ok(system_prompt) = fs::read_to_string(&system_prompt_path) {
@@ -71,6 +77,20 @@ pub fn preview_job(project_root: &PathBuf, job_id: &str) -> Result<(), WorkSplit
         println!("(system prompt file not found)");
     }
 
+    if job.metadata.include_conventions {
+        if let Ok(conventions) = fs::read_to_string(jobs_dir.join(CONVENTIONS_FILE)) {
+            println!("\n=== CONVENTIONS ({}) ===", CONVENTIONS_FILE);
+            let preview_lines: Vec<&str> = conventions.lines().take(20).collect();
+            for line in &preview_lines {
+                println!("{}", line);
+            }
+            let total_lines = conventions.lines().count();
+            if total_lines > 20 {
+                println!("... ({} more lines)", total_lines - 20);
+            }
+        }
+    }
+
     // Display the job instructions
     println!("\n=== JOB INSTRUCTIONS ===");
     let instruction_lines: Vec<&str> = job.instructions.lines().take(30).collect();
@@ -108,8 +128,8 @@ pub fn preview_job(project_root: &PathBuf, job_id: &str) -> Result<(), WorkSplit
             total_chars += content.len();
         }
     }
-    if let Some(target_files) = &job.metadata.target_files {
-        for target in target_files {
+    if job.metadata.target_files.is_some() {
+        for target in &target_files {
             let full_path = project_root.join(target);
             if let Ok(content) = fs::read_to_string(&full_path) {
                 total_chars += content.len();
@@ -0,0 +1,109 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::core::{load_config, AssembledPrompt, PromptPhase, Runner};
+use crate::error::WorkSplitError;
+
+/// Clipboard commands tried in order; the first one that runs is used
+const CLIPBOARD_COMMANDS: &[&[&str]] = &[
+    &["pbcopy"],
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+    &["clip"],
+];
+
+/// Print (or copy to the clipboard) the exact requests a job's phase would
+/// send: system message and full prompt with context
+pub async fn print_prompt(project_root: &Path, job_id: &str, phase: Option<PromptPhase>, copy: bool) -> Result<(), WorkSplitError> {
+    let config = load_config(project_root, None, None, None, None, false)?;
+    let mut runner = Runner::new(config, project_root.to_path_buf())?;
+    let (phase, prompts) = runner.assemble_prompts(job_id, phase).await?;
+    let text = format_prompts(&prompts);
+
+    if copy {
+        let program = copy_to_clipboard(&text)?;
+        println!("Copied the {} prompt of '{}' to the clipboard ({} chars, via {})",
+            phase_name(phase), job_id, text.chars().count(), program);
+    } else {
+        print!("{}", text);
+    }
+    Ok(())
+}
+
+fn phase_name(phase: PromptPhase) -> &'static str {
+    match phase {
+        PromptPhase::Test => "test",
+        PromptPhase::Generate => "generation",
+        PromptPhase::Verify => "verification",
+    }
+}
+
+/// Each request's system message and prompt under `===` headers
+pub(crate) fn format_prompts(prompts: &[AssembledPrompt]) -> String {
+    let mut text = String::new();
+    for (index, request) in prompts.iter().enumerate() {
+        if index > 0 {
+            text.push('\n');
+        }
+        match request.system {
+            Some(system) => text.push_str(&format!("=== SYSTEM ({}) ===\n{}\n\n", request.label, system.trim_end())),
+            None => text.push_str(&format!("=== NO SYSTEM MESSAGE ({}) ===\n\n", request.label)),
+        }
+        text.push_str(&format!("=== PROMPT ===\n{}\n", request.prompt.trim_end()));
+        if let Some(suffix) = &request.suffix {
+            text.push_str(&format!("\n=== SUFFIX ===\n{}\n", suffix.trim_end()));
+        }
+        if let Some(note) = &request.note {
+            text.push_str(&format!("\n({})\n", note));
+        }
+    }
+    text
+}
+
+/// Pipe `text` into the first clipboard command available; returns its name
+fn copy_to_clipboard(text: &str) -> Result<&'static str, WorkSplitError> {
+    for command in CLIPBOARD_COMMANDS {
+        let Ok(mut child) = Command::new(command[0]).args(&command[1..]).stdin(Stdio::piped()).spawn() else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(command[0]);
+        }
+    }
+    Err(WorkSplitError::JobError(format!(
+        "No clipboard command found (tried {}); print the prompt and redirect it instead",
+        CLIPBOARD_COMMANDS.iter().map(|c| c[0]).collect::<Vec<_>>().join(", "))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_prompts_labels_each_request() {
+        let prompts = vec![
+            AssembledPrompt {
+                label: "edit prompt 1/2".to_string(),
+                system: Some("Be brief.\n"),
+                prompt: "Edit a.rs".to_string(),
+                suffix: None,
+                note: None,
+            },
+            AssembledPrompt {
+                label: "fill a.rs lines 3-4".to_string(),
+                system: None,
+                prompt: "fn a() {".to_string(),
+                suffix: Some("}".to_string()),
+                note: Some("later".to_string()),
+            },
+        ];
+        let text = format_prompts(&prompts);
+        assert!(text.starts_with("=== SYSTEM (edit prompt 1/2) ===\nBe brief.\n\n=== PROMPT ===\nEdit a.rs\n"));
+        assert!(text.contains("=== NO SYSTEM MESSAGE (fill a.rs lines 3-4) ===\n\n=== PROMPT ===\nfn a() {\n\n=== SUFFIX ===\n}\n\n(later)\n"));
+    }
+}
@@ -1,37 +1,172 @@
-use std::path::PathBuf;
+use std::path::Path;
 use crate::error::WorkSplitError;
+use crate::commands::status::status_label;
 use crate::core::status::StatusManager;
+use crate::core::JobsManager;
+use crate::models::Config;
 use crate::models::status::JobStatus;
 
-pub fn reset_jobs(
-    project_root: &PathBuf,
-    job_id: &str,
-    status_filter: Option<&str>,
-) -> Result<(), WorkSplitError> {
-    let mut status_manager = StatusManager::new(&project_root.join("jobs"))?;
-    
-    if job_id == "all" {
-        let filter = status_filter.unwrap_or("fail");
-        let to_reset: Vec<String> = status_manager
-            .all_entries()
-            .iter()
-            .filter(|e| match filter {
-                "fail" => e.status == JobStatus::Fail,
-                "partial" => e.status == JobStatus::Partial,
-                _ => false,
-            })
-            .map(|e| e.id.clone())
+/// Which jobs `reset`, `retry` and `cancel` act on: the jobs matching any
+/// selector, narrowed down by status and tag
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JobSelection {
+    /// Job IDs, globs over job IDs (`auth_*`), `tag:<name>` or `all`;
+    /// none selects every job, like `all`
+    pub selectors: Vec<String>,
+    /// Only jobs with this status, e.g. `fail` or `pending_work`
+    pub status: Option<String>,
+    /// Only jobs with this tag
+    pub tag: Option<String>,
+}
+
+impl JobSelection {
+    /// A single job ID (or `all`)
+    pub fn job(job_id: &str) -> Self {
+        Self { selectors: vec![job_id.to_string()], ..Default::default() }
+    }
+
+    /// Whether nothing was asked for
+    pub fn is_empty(&self) -> bool {
+        self.selectors.is_empty() && self.status.is_none() && self.tag.is_none()
+    }
+
+    /// Whether the selectors name every job
+    pub fn is_all(&self) -> bool {
+        self.selectors.is_empty() || self.selectors.iter().any(|s| s == "all")
+    }
+
+    /// The selected jobs that have a status entry, sorted, with their status
+    pub fn select(&self, project_root: &Path, status_manager: &StatusManager) -> Result<Vec<(String, JobStatus)>, WorkSplitError> {
+        let status = self.status.as_deref().map(parse_status).transpose()?;
+        let config = Config::load_from_dir(project_root).unwrap_or_default();
+        let jobs_manager = JobsManager::new(project_root.to_path_buf(), config.limits);
+
+        let mut ids: Vec<String> = if self.is_all() {
+            status_manager.all_entries().iter().map(|e| e.id.clone()).collect()
+        } else {
+            jobs_manager.select_jobs(&self.selectors)?
+        };
+        if let Some(tag) = &self.tag {
+            let tagged = jobs_manager.select_jobs(&[format!("tag:{}", tag)])?;
+            ids.retain(|id| tagged.contains(id));
+        }
+
+        let mut selected: Vec<(String, JobStatus)> = ids
+            .into_iter()
+            .filter_map(|id| status_manager.get(&id).map(|entry| (id, entry.status)))
+            .filter(|(_, job_status)| status.is_none_or(|wanted| *job_status == wanted))
             .collect();
-        
-        for id in &to_reset {
-            status_manager.reset_job(id)?;
-            println!("Reset: {}", id);
+        selected.sort();
+        Ok(selected)
+    }
+
+    /// What the selection matched, for messages: `auth_*, tag:backend, status fail`
+    pub fn describe(&self) -> String {
+        let mut parts = self.selectors.clone();
+        parts.extend(self.tag.iter().map(|tag| format!("tag:{}", tag)));
+        parts.extend(self.status.iter().map(|status| format!("status {}", status)));
+        parts.join(", ")
+    }
+}
+
+/// A status as written on the command line (`fail`, `pending_work`,
+/// `pending-work`)
+pub fn parse_status(name: &str) -> Result<JobStatus, WorkSplitError> {
+    serde_json::from_value(serde_json::Value::String(name.trim().to_lowercase().replace('-', "_"))).map_err(|_| {
+        WorkSplitError::JobError(format!(
+            "Unknown status '{}'; expected created, pending_test, pending_work, pending_verification, pending_test_run, pass, fail or partial",
+            name
+        ))
+    })
+}
+
+/// Reset the selected jobs to created status; `all` alone resets the failed
+/// jobs. Returns the jobs that were reset.
+pub fn reset_jobs(project_root: &Path, selection: &JobSelection) -> Result<Vec<String>, WorkSplitError> {
+    if selection.is_empty() {
+        return Err(WorkSplitError::JobError(
+            "Name the jobs to reset: job IDs, globs (auth_*), tag:<name>, 'all', --status or --tag".to_string(),
+        ));
+    }
+    let mut selection = selection.clone();
+    if selection.is_all() && selection.status.is_none() && selection.tag.is_none() {
+        selection.status = Some("fail".to_string());
+    }
+
+    let mut status_manager = StatusManager::new(&project_root.join("jobs"))?;
+    let to_reset = selection.select(project_root, &status_manager)?;
+    if to_reset.is_empty() {
+        println!("No jobs match {}.", selection.describe());
+        return Ok(Vec::new());
+    }
+
+    for (id, from) in &to_reset {
+        status_manager.reset_job(id)?;
+        println!("Reset: {} ({} -> {})", id, status_label(*from), status_label(JobStatus::Created));
+    }
+    println!("\nReset {} job(s). Run 'worksplit run' to re-execute.", to_reset.len());
+    Ok(to_reset.into_iter().map(|(id, _)| id).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn project(jobs: &[(&str, &str, JobStatus)]) -> TempDir {
+        let temp = TempDir::new().unwrap();
+        let jobs_dir = temp.path().join("jobs");
+        fs::create_dir_all(&jobs_dir).unwrap();
+        let mut status = StatusManager::new(&jobs_dir).unwrap();
+        let ids: Vec<String> = jobs.iter().map(|(id, _, _)| id.to_string()).collect();
+        for (id, tags, _) in jobs {
+            fs::write(jobs_dir.join(format!("{}.md", id)), format!("---\ntags: [{}]\noutput_dir: src/\noutput_file: a.rs\n---\n\nWrite a.\n", tags)).unwrap();
         }
-        println!("\nReset {} job(s). Run 'worksplit run' to re-execute.", to_reset.len());
-    } else {
-        status_manager.reset_job(job_id)?;
-        println!("Reset: {}", job_id);
-    }
-    
-    Ok(())
-}
\ No newline at end of file
+        status.sync_with_jobs(&ids).unwrap();
+        for (id, _, job_status) in jobs {
+            status.update_status(id, *job_status).unwrap();
+        }
+        temp
+    }
+
+    #[test]
+    fn test_selection_by_glob_tag_and_status() {
+        let temp = project(&[
+            ("auth_001", "backend", JobStatus::Fail),
+            ("auth_002", "backend", JobStatus::Pass),
+            ("ui_001", "frontend", JobStatus::Fail),
+            ("ui_002", "backend", JobStatus::Partial),
+        ]);
+        let status = StatusManager::new(&temp.path().join("jobs")).unwrap();
+        let select = |selectors: &[&str], status_name: Option<&str>, tag: Option<&str>| {
+            let selection = JobSelection {
+                selectors: selectors.iter().map(|s| s.to_string()).collect(),
+                status: status_name.map(str::to_string),
+                tag: tag.map(str::to_string),
+            };
+            selection.select(temp.path(), &status).unwrap().into_iter().map(|(id, _)| id).collect::<Vec<_>>()
+        };
+
+        assert_eq!(select(&["auth_*"], None, None), ["auth_001", "auth_002"]);
+        assert_eq!(select(&[], Some("fail"), None), ["auth_001", "ui_001"]);
+        assert_eq!(select(&[], Some("FAIL"), Some("backend")), ["auth_001"]);
+        assert_eq!(select(&["tag:backend"], None, None), ["auth_001", "auth_002", "ui_002"]);
+        assert_eq!(select(&["ui_*", "auth_002"], None, Some("backend")), ["auth_002", "ui_002"]);
+        assert_eq!(select(&["all"], Some("partial"), None), ["ui_002"]);
+        assert!(parse_status("broken").unwrap_err().to_string().contains("Unknown status 'broken'"));
+        assert_eq!(parse_status("pending-work").unwrap(), JobStatus::PendingWork);
+    }
+
+    #[test]
+    fn test_reset_all_resets_failed_jobs() {
+        let temp = project(&[("a_001", "x", JobStatus::Fail), ("a_002", "x", JobStatus::Pass), ("b_001", "x", JobStatus::Partial)]);
+        assert_eq!(reset_jobs(temp.path(), &JobSelection::job("all")).unwrap(), ["a_001"]);
+        assert!(reset_jobs(temp.path(), &JobSelection::default()).is_err());
+
+        let reset = reset_jobs(temp.path(), &JobSelection { selectors: vec!["*_00?".into()], ..Default::default() }).unwrap();
+        assert_eq!(reset, ["a_001", "a_002", "b_001"]);
+        let status = StatusManager::new(&temp.path().join("jobs")).unwrap();
+        assert!(status.all_entries().iter().all(|e| e.status == JobStatus::Created));
+    }
+}
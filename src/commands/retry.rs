@@ -1,31 +1,57 @@
-use std::path::PathBuf;
+use std::path::Path;
 use crate::error::WorkSplitError;
-use crate::commands::reset::reset_jobs;
-use crate::commands::run::run_jobs;
+use crate::commands::reset::{reset_jobs, JobSelection};
+use crate::commands::run::{run_project, RunOptions};
+use crate::core::dependency::order_by_dependencies;
+use crate::core::JobsManager;
+use crate::models::{Config, JobStatus};
+
+/// Retry the selected jobs by resetting them to created status and running
+/// them again, dependencies first; `all` alone retries the failed jobs.
+pub async fn retry_jobs(project_root: &Path, selection: &JobSelection) -> Result<(), WorkSplitError> {
+    // Reset the jobs to created status
+    let reset = reset_jobs(project_root, selection)?;
+    if reset.is_empty() {
+        return Ok(());
+    }
+
+    let config = Config::load_from_dir(project_root).unwrap_or_default();
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), config.limits);
+    let jobs = reset.iter().map(|id| jobs_manager.parse_job(id)).collect::<Result<Vec<_>, _>>()?;
+    let ordered: Vec<String> = order_by_dependencies(&jobs)?.into_iter().map(|job| job.id.clone()).collect();
+
+    // Run the jobs immediately
+    let (mut passed, mut failed) = (Vec::new(), Vec::new());
+    for job_id in &ordered {
+        let options = RunOptions {
+            job_id: Some(job_id.clone()),
+            // Not needed since reset clears the ran flag
+            rerun: false,
+            ..Default::default()
+        };
+        match run_project(project_root, options).await {
+            Ok(Some(summary)) if summary.results.iter().all(|r| r.status == JobStatus::Pass) => {
+                passed.push(job_id.clone())
+            }
+            Ok(_) => failed.push(job_id.clone()),
+            // A single job reports its own error
+            Err(e) if ordered.len() == 1 => return Err(e),
+            Err(e) => {
+                println!("{}: {}", job_id, e);
+                failed.push(job_id.clone());
+            }
+        }
+    }
+
+    if ordered.len() > 1 {
+        println!("\n=== Retry Summary ===");
+        println!("Retried: {}", ordered.len());
+        println!("Passed:  {}", passed.len());
+        println!("Failed:  {}", failed.len());
+        if !failed.is_empty() {
+            println!("  {}", failed.join(", "));
+        }
+    }
 
-/// Retry a failed job by resetting it to created status and running it again.
-pub async fn retry_job(project_root: &PathBuf, job_id: &str) -> Result<(), WorkSplitError> {
-    // Reset the job to created status
-    reset_jobs(project_root, job_id, None)?;
-    
-    // Run the job immediately
-    let options = crate::commands::run::RunOptions {
-        job_id: Some(job_id.to_string()),
-        dry_run: false,
-        resume: false,
-        reset: None,
-        model: None,
-        url: None,
-        timeout: None,
-        job_timeout: None,
-        no_stream: false,
-        stop_on_fail: false,
-        batch: false,
-        max_concurrent: 0,
-        rerun: false, // Not needed since reset clears the ran flag
-    };
-    
-    run_jobs(project_root, options).await?;
-    
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,12 +1,22 @@
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 
+use crate::commands::apply::print_staging_report;
 use crate::commands::archive::run_auto_archive;
-use crate::core::{load_config, Runner};
+use crate::core::budget::RunBudget;
+use crate::core::ci;
+use crate::core::mock_fixtures::MockFixtures;
+use crate::core::output_paths::check_output_paths;
+use crate::core::recording::Recorder;
+use crate::core::stream_log::StreamLog;
+use crate::core::timings::{render_table, PhaseTimings};
+use crate::core::{load_config, JobResult, Runner, RunSummary};
 use crate::error::WorkSplitError;
 use crate::models::JobStatus;
 
 /// Run options
+#[derive(Default, Clone)]
 pub struct RunOptions {
     /// Specific job to run (if None, run all pending)
     pub job_id: Option<String>,
@@ -16,6 +26,8 @@ pub struct RunOptions {
     pub resume: bool,
     /// Reset specific job to created status
     pub reset: Option<String>,
+    /// Named `[profile.<name>]` section of worksplit.toml to apply
+    pub profile: Option<String>,
     /// Model override
     pub model: Option<String>,
     /// URL override
@@ -34,49 +46,141 @@ pub struct RunOptions {
     pub max_concurrent: usize,
     /// Include jobs that have already been run (ran=true)
     pub rerun: bool,
+    /// Overwrite files generated by other passed jobs and frozen files
+    pub force: bool,
+    /// Write outputs to jobs/_staging/ instead of the project tree
+    pub plan_only: bool,
+    /// Re-prompt only the failed edits of this partial edit job
+    pub continue_job: Option<String>,
+    /// Stop starting new jobs after this many
+    pub max_jobs: Option<usize>,
+    /// Stop starting new jobs after this many minutes
+    pub max_minutes: Option<u64>,
+    /// Stop starting new jobs once this many LLM tokens are used
+    pub max_tokens: Option<u64>,
+    /// Serve model responses from this fixture directory instead of Ollama
+    pub mock: Option<PathBuf>,
+    /// Record every model request and response to jobs/_recordings/
+    pub record: bool,
+    /// Serve model responses from a `--record` recording instead of Ollama
+    pub replay: Option<PathBuf>,
+    /// Copy each job's token stream to `<dir>/<job>.log`
+    pub stream_to: Option<PathBuf>,
+    /// Print GitHub Actions groups and annotations, and exit with a code
+    /// telling failed and partial jobs apart
+    pub ci: bool,
 }
 
-impl Default for RunOptions {
-    fn default() -> Self {
-        Self {
-            job_id: None,
-            dry_run: false,
-            resume: false,
-            reset: None,
-            model: None,
-            url: None,
-            timeout: None,
-            job_timeout: None,
-            no_stream: false,
-            stop_on_fail: false,
-            batch: false,
-            max_concurrent: 0,
-            rerun: false,
+
+/// Run jobs
+pub async fn run_jobs(project_root: &Path, options: RunOptions) -> Result<(), WorkSplitError> {
+    let ci = options.ci;
+    let summary = run_project(project_root, options).await?;
+    if let (true, Some(summary)) = (ci, &summary) {
+        finish_ci_run(ci::RunCounts::of(summary));
+    }
+    Ok(())
+}
+
+/// Report the run's counts to GitHub Actions and exit with
+/// [`ci::RunCounts::exit_code`] when any job failed or is partial
+pub fn finish_ci_run(counts: ci::RunCounts) {
+    println!("{}", ci::annotation("notice", None, "WorkSplit run", &format!(
+        "{} passed, {} failed, {} partial, {} skipped", counts.passed, counts.failed, counts.partial, counts.skipped)));
+    if let Some(path) = std::env::var_os("GITHUB_OUTPUT") {
+        let written = std::fs::OpenOptions::new().create(true).append(true).open(&path)
+            .and_then(|mut file| file.write_all(counts.step_outputs().as_bytes()));
+        if let Err(e) = written {
+            warn!("Failed to write step outputs to {}: {}", PathBuf::from(path).display(), e);
         }
     }
+    let code = counts.exit_code();
+    if code != 0 {
+        std::process::exit(code);
+    }
 }
 
-/// Run jobs
-pub async fn run_jobs(project_root: &PathBuf, options: RunOptions) -> Result<(), WorkSplitError> {
-    let config = load_config(
+/// Run jobs, returning the summary of the jobs run (`None` for a reset or a
+/// dry run)
+pub async fn run_project(project_root: &Path, options: RunOptions) -> Result<Option<RunSummary>, WorkSplitError> {
+    let mut config = load_config(
         project_root,
+        options.profile.as_deref(),
         options.model,
         options.url,
         options.timeout,
         options.no_stream,
     )?;
+    if options.plan_only {
+        config.safety.read_only = true;
+    }
+    let read_only = config.safety.read_only;
+    // Streamed tokens already show progress, and would tear through the bars
+    let progress_bars = config.behavior.progress_bars && !config.behavior.stream_output && !options.ci;
+    let stream_to = options.stream_to.clone().or_else(|| config.behavior.stream_to.clone());
 
-    let mut runner = Runner::new(config, project_root.clone())?;
+    let mut runner = Runner::new(config, project_root.to_path_buf())?;
+    runner.set_progress_bars(progress_bars);
+    runner.set_force(options.force);
+    runner.set_ci_annotations(options.ci);
+    if let Some(dir) = &options.mock {
+        info!("Using mock responses from {}", dir.display());
+        runner.set_mock_fixtures(MockFixtures::new(project_root.join(dir))?);
+    }
+    if let Some(path) = &options.replay {
+        info!("Replaying responses from {}", path.display());
+        runner.set_mock_fixtures(MockFixtures::from_recording(project_root.join(path))?);
+    }
+    if options.record {
+        let recorder = Recorder::create(&project_root.join("jobs"))?;
+        println!("Recording model interactions to {}", recorder.path().display());
+        runner.set_recorder(recorder);
+    }
+    if let Some(dir) = &stream_to {
+        let log = StreamLog::create(&project_root.join(dir))?;
+        println!("Streaming generations to {}/<job>.log", dir.display());
+        runner.set_stream_log(log);
+    }
+    runner.set_budget(RunBudget {
+        max_jobs: options.max_jobs,
+        max_duration: options.max_minutes.map(|m| std::time::Duration::from_secs(m * 60)),
+        max_tokens: options.max_tokens,
+    });
 
     // Handle reset
     if let Some(job_id) = options.reset {
         runner.reset_job(&job_id)?;
         println!("Reset job '{}' to created status", job_id);
-        return Ok(());
+        return Ok(None);
+    }
+
+    // Finish a partial edit job
+    if let Some(job_id) = options.continue_job {
+        if options.dry_run {
+            println!("=== DRY RUN ===\n");
+            for edit in runner.status_manager().get_failed_edits(&job_id).unwrap_or_default() {
+                println!("  {}: {}", edit.file_path, edit.find_preview);
+            }
+            println!("\nRun without --dry-run to re-prompt these edits.");
+            return Ok(None);
+        }
+
+        let result = runner.continue_job(&job_id).await?;
+        print_job_result(&result);
+        print_phase_timings(std::slice::from_ref(&result));
+        if result.status == JobStatus::Partial {
+            println!("\nSome edits still failed. Run 'worksplit run --continue {}' again or edit manually.", job_id);
+        }
+        if read_only {
+            print_staging_report(project_root)?;
+        }
+        return Ok(Some(RunSummary::single(result)));
     }
 
+    check_output_paths_before_run(project_root, &runner, options.job_id.as_deref())?;
+
     // Run specific job or all jobs
-    if let Some(job_id) = options.job_id {
+    let summary = if let Some(job_id) = options.job_id {
         info!("Running single job: {}", job_id);
         // Keep behavior, but update dry-run output text to mention jobs
         
@@ -93,18 +197,20 @@ pub async fn run_jobs(project_root: &PathBuf, options: RunOptions) -> Result<(),
                 println!("  {} [Error parsing job]", job_id);
             }
             println!("\nRun without --dry-run to execute.");
-            return Ok(());
+            return Ok(None);
         }
 
         let result = runner.run_single(&job_id).await?;
         
-        print_job_result(&result.job_id, result.status, result.error.as_deref(), result.output_lines);
+        print_job_result(&result);
+        print_phase_timings(std::slice::from_ref(&result));
         
         // Exit with error if job failed and stop_on_fail is set
         if options.stop_on_fail && result.status == JobStatus::Fail {
             println!("\nStopping due to failure (--stop-on-fail)");
             std::process::exit(1);
         }
+        Some(RunSummary::single(result))
     } else if options.batch {
         info!("Running in batch mode");
 
@@ -113,7 +219,7 @@ pub async fn run_jobs(project_root: &PathBuf, options: RunOptions) -> Result<(),
             // Simplified preview for batch mode
             println!("Would process jobs in dependency order/batches.");
             println!("Run without --dry-run to execute.");
-            return Ok(());
+            return Ok(None);
         }
 
         let summary = runner.run_batch(options.resume, options.stop_on_fail, options.max_concurrent, options.rerun).await?;
@@ -122,21 +228,21 @@ pub async fn run_jobs(project_root: &PathBuf, options: RunOptions) -> Result<(),
         println!("Processed: {}", summary.processed);
         println!("Passed:    {}", summary.passed);
         println!("Failed:    {}", summary.failed);
-        if summary.skipped > 0 {
-            println!("Skipped:   {} (not processed)", summary.skipped);
-        }
+        print_skipped(&summary);
         
         if !summary.results.is_empty() {
             println!("\nResults:");
             for result in &summary.results {
-                print_job_result(&result.job_id, result.status, result.error.as_deref(), result.output_lines);
+                print_job_result(result);
             }
+            print_phase_timings(&summary.results);
         }
         
         if options.stop_on_fail && summary.failed > 0 {
             println!("\nStopping due to failure (--stop-on-fail)");
             std::process::exit(1);
         }
+        Some(summary)
     } else {
         info!("Running all pending jobs");
 
@@ -144,7 +250,7 @@ pub async fn run_jobs(project_root: &PathBuf, options: RunOptions) -> Result<(),
             println!("=== DRY RUN (ALL) ===\n");
             println!("Would process all pending jobs in order.");
             println!("Run without --dry-run to execute.");
-            return Ok(());
+            return Ok(None);
         }
 
         let summary = runner.run_all(options.resume, options.stop_on_fail, options.rerun).await?;
@@ -153,15 +259,14 @@ pub async fn run_jobs(project_root: &PathBuf, options: RunOptions) -> Result<(),
         println!("Processed: {}", summary.processed);
         println!("Passed:    {}", summary.passed);
         println!("Failed:    {}", summary.failed);
-        if summary.skipped > 0 {
-            println!("Skipped:   {} (not processed)", summary.skipped);
-        }
+        print_skipped(&summary);
         
         if !summary.results.is_empty() {
             println!("\nResults:");
             for result in &summary.results {
-                print_job_result(&result.job_id, result.status, result.error.as_deref(), result.output_lines);
+                print_job_result(result);
             }
+            print_phase_timings(&summary.results);
         }
         
         // Exit with error if any job failed and stop_on_fail is set
@@ -169,6 +274,12 @@ pub async fn run_jobs(project_root: &PathBuf, options: RunOptions) -> Result<(),
             println!("\nStopping due to failure (--stop-on-fail)");
             std::process::exit(1);
         }
+        Some(summary)
+    };
+
+    if read_only {
+        print_staging_report(project_root)?;
+        return Ok(summary);
     }
 
     // Run auto-archive after jobs complete (which triggers auto-cleanup)
@@ -179,20 +290,88 @@ pub async fn run_jobs(project_root: &PathBuf, options: RunOptions) -> Result<(),
         }
     }
 
-    Ok(())
+    Ok(summary)
+}
+
+fn print_skipped(summary: &RunSummary) {
+    if summary.skipped == 0 {
+        return;
+    }
+    match &summary.budget_exhausted {
+        Some(reason) => println!("Skipped:   {} (run budget: {})", summary.skipped, reason),
+        None => println!("Skipped:   {} (not processed)", summary.skipped),
+    }
+    for job_id in &summary.skipped_jobs {
+        println!("  {} [SKIPPED]", job_id);
+    }
 }
 
-fn print_job_result(job_id: &str, status: JobStatus, error: Option<&str>, lines: Option<usize>) {
-    let status_str = match status {
+fn print_job_result(result: &JobResult) {
+    let status_str = match result.status {
         JobStatus::Pass => "PASS",
         JobStatus::Fail => "FAIL",
+        JobStatus::Partial => "PARTIAL",
         _ => "???",
     };
     
-    let lines_str = lines.map(|l| format!(" ({} lines)", l)).unwrap_or_default();
+    let lines_str = result.output_lines.map(|l| format!(" ({} lines)", l)).unwrap_or_default();
+    let retries_str = match result.request_retries.len() {
+        0 => String::new(),
+        1 => " (1 request retried)".to_string(),
+        n => format!(" ({} request retries)", n),
+    };
     
-    match error {
-        Some(err) => println!("  {} [{}]{}: {}", job_id, status_str, lines_str, err),
-        None => println!("  {} [{}]{}", job_id, status_str, lines_str),
+    match &result.error {
+        // The job file lets editors link the failure (see `init --editor`)
+        Some(err) => println!("  {} [{}]{}{} (jobs/{}.md): {}", result.job_id, status_str, lines_str, retries_str,
+            result.job_id, err),
+        None => println!("  {} [{}]{}{}", result.job_id, status_str, lines_str, retries_str),
+    }
+}
+
+/// Where each job's wall time went, by phase
+fn print_phase_timings(results: &[JobResult]) {
+    let rows: Vec<(&str, PhaseTimings)> = results.iter()
+        .filter(|r| !r.phase_timings.total().is_zero())
+        .map(|r| (r.job_id.as_str(), r.phase_timings))
+        .collect();
+    if rows.is_empty() {
+        return;
+    }
+    println!("\nPhase timings:");
+    for line in render_table(&rows).lines() {
+        println!("  {}", line);
+    }
+}
+
+/// Refuse to start when a job about to run has conflicting output paths.
+/// Only issues involving the selected job (or, without one, a job that
+/// hasn't run yet) block the run.
+fn check_output_paths_before_run(project_root: &Path, runner: &Runner, job_id: Option<&str>) -> Result<(), WorkSplitError> {
+    let jobs_manager = runner.jobs_manager();
+    let jobs: Vec<_> = jobs_manager
+        .discover_jobs()?
+        .iter()
+        .filter_map(|id| jobs_manager.parse_job(id).ok())
+        .collect();
+    let pending = |id: &str| match job_id {
+        Some(selected) => id == selected,
+        None => runner
+            .status_manager()
+            .get(id)
+            .is_none_or(|entry| entry.status == JobStatus::Created),
+    };
+    let blocking: Vec<String> = check_output_paths(project_root, &jobs)
+        .into_iter()
+        .filter(|issue| issue.job_ids.iter().any(|id| pending(id)))
+        .map(|issue| format!("  - {}", issue))
+        .collect();
+    if blocking.is_empty() {
+        Ok(())
+    } else {
+        Err(WorkSplitError::OutputPathConflict(format!(
+            "{}\nFix the job files (see 'worksplit validate') before running.",
+            blocking.join("\n")
+        )))
     }
 }
@@ -0,0 +1,325 @@
+//! `worksplit scan-todos`: turn `TODO(worksplit): ...` comments into jobs
+//!
+//! Each source file with tagged comments gets one edit-mode job targeting
+//! it, with the comments as its instructions. Re-running the scan adds
+//! comments written since to the file's existing job and leaves the rest
+//! of that job alone.
+
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::info;
+
+use crate::core::atomic_write::write_atomic;
+use crate::core::targets::walk_files;
+use crate::error::WorkSplitError;
+
+/// Tag applied to every generated job, for selecting them with `tag:todo`
+pub const TODO_TAG: &str = "todo";
+
+/// A `TODO(worksplit)` or `FIXME(worksplit)` comment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoComment {
+    /// 1-based line number
+    pub line: usize,
+    pub text: String,
+}
+
+/// What the scan did (or would do) with a file's job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TodoJobState {
+    /// No job existed; one was written
+    Created,
+    /// The existing job was missing some of the file's TODOs; they were
+    /// appended to its instructions
+    Updated { added: usize },
+    /// The existing job already lists every TODO
+    Unchanged,
+}
+
+/// The job generated (or that would be) for one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoJob {
+    pub id: String,
+    pub file: PathBuf,
+    pub todos: Vec<TodoComment>,
+    pub state: TodoJobState,
+}
+
+fn todo_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"(?://+|#+|/\*+|\*|--)\s*(?:TODO|FIXME)\(worksplit\):\s*(.*?)\s*(?:\*/)?\s*$").unwrap()
+    })
+}
+
+/// Tagged comments in a file's content
+pub fn find_todos(content: &str) -> Vec<TodoComment> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let text = todo_regex().captures(line)?.get(1)?.as_str();
+            (!text.is_empty()).then(|| TodoComment { line: i + 1, text: text.to_string() })
+        })
+        .collect()
+}
+
+/// Job ID for a file's TODOs (`src/net/retry.rs` ->
+/// `todo_src_net_retry_rs_<hash>`). The readable part folds every
+/// separator into `_`, so a hash of the exact path keeps `src/a_b.rs` and
+/// `src/a/b.rs` apart.
+pub fn todo_job_id(file: &Path) -> String {
+    let path = file.to_string_lossy().replace('\\', "/");
+    let slug: String = path
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    // FNV-1a: stable across runs and platforms, unlike std's hasher
+    let hash = path.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("todo_{}_{:08x}", slug.trim_matches('_'), hash as u32)
+}
+
+/// Instruction line for one TODO
+fn todo_item(todo: &TodoComment) -> String {
+    format!("- Line {}: {}", todo.line, todo.text)
+}
+
+/// Job file content for a file's TODOs; paths are quoted as JSON strings,
+/// which YAML reads as they are
+pub fn generate_todo_job(file: &Path, todos: &[TodoComment]) -> String {
+    let quote = |path: &str| serde_json::to_string(path).unwrap_or_default();
+    let output_dir = match file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.display().to_string(),
+        _ => ".".to_string(),
+    };
+    let output_file = file.file_name().unwrap_or_default().to_string_lossy();
+    let items: Vec<String> = todos.iter().map(todo_item).collect();
+
+    format!(
+        r#"---
+mode: edit
+target_files:
+  - {target}
+output_dir: {output_dir}
+output_file: {output_file}
+tags: [{tag}]
+---
+
+# TODOs in {file}
+
+Resolve these `TODO(worksplit)` comments, removing each comment once its work is done:
+
+{items}
+"#,
+        target = quote(&file.display().to_string()),
+        output_dir = quote(&output_dir),
+        output_file = quote(&output_file),
+        tag = TODO_TAG,
+        file = file.display(),
+        items = items.join("\n"),
+    )
+}
+
+/// Scan the project for tagged comments and write a job per file, unless
+/// `dry_run`
+///
+/// Hidden entries, build directories, `jobs/` and anything
+/// `.worksplitignore` excludes are skipped, as are Markdown files (docs
+/// that show the syntax) and files that aren't UTF-8. A file whose job
+/// already exists gets the TODOs that job doesn't mention yet appended.
+pub fn scan_todos(project_root: &Path, dry_run: bool) -> Result<Vec<TodoJob>, WorkSplitError> {
+    let mut files = Vec::new();
+    walk_files(project_root, project_root, &mut files)?;
+
+    let mut found: BTreeMap<PathBuf, Vec<TodoComment>> = BTreeMap::new();
+    for file in files {
+        if file.extension().is_some_and(|ext| ext == "md") {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(project_root.join(&file)) else { continue };
+        let todos = find_todos(&content);
+        if !todos.is_empty() {
+            found.insert(file, todos);
+        }
+    }
+
+    let jobs_dir = project_root.join("jobs");
+    let mut jobs = Vec::new();
+    for (file, todos) in found {
+        let id = todo_job_id(&file);
+        let job_file = jobs_dir.join(format!("{}.md", id));
+        let state = match fs::read_to_string(&job_file) {
+            Ok(existing) => {
+                let missing: Vec<String> = todos.iter()
+                    .filter(|todo| !existing.contains(&todo.text))
+                    .map(todo_item)
+                    .collect();
+                if missing.is_empty() {
+                    TodoJobState::Unchanged
+                } else {
+                    if !dry_run {
+                        let content = format!("{}\n{}\n", existing.trim_end(), missing.join("\n"));
+                        write_atomic(&job_file, &content)?;
+                        info!("Added {} TODO(s) to {}", missing.len(), job_file.display());
+                    }
+                    TodoJobState::Updated { added: missing.len() }
+                }
+            }
+            Err(_) => {
+                if !dry_run {
+                    fs::create_dir_all(&jobs_dir)?;
+                    write_atomic(&job_file, generate_todo_job(&file, &todos))?;
+                    info!("Created job file: {}", job_file.display());
+                }
+                TodoJobState::Created
+            }
+        };
+        jobs.push(TodoJob { id, file, todos, state });
+    }
+    Ok(jobs)
+}
+
+/// `worksplit scan-todos`
+pub fn scan_todo_jobs(project_root: &Path, dry_run: bool) -> Result<(), WorkSplitError> {
+    let jobs = scan_todos(project_root, dry_run)?;
+    if jobs.is_empty() {
+        println!("No TODO(worksplit) comments found.");
+        return Ok(());
+    }
+
+    let (create, update) = if dry_run { ("Would create", "Would update") } else { ("Created", "Updated") };
+    for job in &jobs {
+        let state = match job.state {
+            TodoJobState::Created => create.to_string(),
+            TodoJobState::Updated { added } => format!("{} (+{} TODO(s))", update, added),
+            TodoJobState::Unchanged => "Up to date".to_string(),
+        };
+        println!("{}: jobs/{}.md ({}, {} TODO(s))", state, job.id, job.file.display(), job.todos.len());
+    }
+    let count = |wanted: fn(&TodoJobState) -> bool| jobs.iter().filter(|job| wanted(&job.state)).count();
+    let created = count(|state| *state == TodoJobState::Created);
+    let updated = count(|state| matches!(state, TodoJobState::Updated { .. }));
+    println!();
+    println!("{} {} job(s), {} {}; {} up to date.", create, created, update.to_lowercase(), updated, jobs.len() - created - updated);
+    if updated > 0 {
+        println!("Updated jobs that already ran need 'worksplit reset <job>' to run the new TODOs.");
+    }
+    if created + updated > 0 && !dry_run {
+        println!("Review them, then run 'worksplit run'");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::new_job::validate_job_name;
+    use crate::core::JobsManager;
+    use crate::models::{LimitsConfig, OutputMode};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_todos() {
+        let content = "fn a() {}\n// TODO(worksplit): implement retry logic here\n    # FIXME(worksplit): handle  timeouts\n/* TODO(worksplit): close the socket */\n// TODO: not tagged\n// TODO(worksplit):\nlet s = \"TODO(worksplit): not a comment\";\n";
+        assert_eq!(
+            find_todos(content),
+            vec![
+                TodoComment { line: 2, text: "implement retry logic here".to_string() },
+                TodoComment { line: 3, text: "handle  timeouts".to_string() },
+                TodoComment { line: 4, text: "close the socket".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_todo_job_id() {
+        let id = todo_job_id(Path::new("src/net/retry.rs"));
+        assert!(id.starts_with("todo_src_net_retry_rs_") && id.len() == "todo_src_net_retry_rs_".len() + 8, "{}", id);
+        assert_eq!(id, todo_job_id(Path::new("src/net/retry.rs")));
+        assert!(validate_job_name(&id).is_ok());
+        assert!(todo_job_id(Path::new("scripts/Deploy-All.py")).starts_with("todo_scripts_deploy_all_py_"));
+
+        // Same readable part, different files
+        let ids: Vec<String> = ["src/a_b.rs", "src/a/b.rs", "src/a-b.rs", "src/A_b.rs"]
+            .iter()
+            .map(|path| todo_job_id(Path::new(path)))
+            .collect();
+        for (i, id) in ids.iter().enumerate() {
+            assert!(!ids[i + 1..].contains(id), "{:?}", ids);
+        }
+    }
+
+    #[test]
+    fn test_scan_todos_writes_edit_jobs() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("src/net")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("src/net/retry.rs"), "pub fn send() {}\n// TODO(worksplit): implement retry logic here\n").unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(root.join("README.md"), "// TODO(worksplit): documented example\n").unwrap();
+        fs::write(root.join("target/gen.rs"), "// TODO(worksplit): build output\n").unwrap();
+
+        let dry = scan_todos(root, true).unwrap();
+        assert_eq!(dry.len(), 1);
+        assert_eq!(dry[0].state, TodoJobState::Created);
+        assert!(!root.join("jobs").exists());
+
+        let jobs = scan_todos(root, false).unwrap();
+        let id = todo_job_id(Path::new("src/net/retry.rs"));
+        assert_eq!(jobs[0].id, id);
+        let manager = JobsManager::new(root.to_path_buf(), LimitsConfig::default());
+        let job = manager.parse_job(&id).unwrap();
+        assert_eq!(job.metadata.mode, OutputMode::Edit);
+        assert_eq!(job.metadata.target_files, Some(vec![PathBuf::from("src/net/retry.rs")]));
+        assert_eq!(job.metadata.output_path(), PathBuf::from("src/net/retry.rs"));
+        assert_eq!(job.metadata.tags, vec![TODO_TAG.to_string()]);
+        assert!(job.instructions.contains("- Line 2: implement retry logic here"));
+    }
+
+    #[test]
+    fn test_rescan_adds_new_todos_to_existing_jobs() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("src/a")).unwrap();
+        fs::write(root.join("src/a_b.rs"), "// TODO(worksplit): first\n").unwrap();
+        fs::write(root.join("src/a/b.rs"), "// TODO(worksplit): second\n").unwrap();
+
+        let jobs = scan_todos(root, false).unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert!(jobs.iter().all(|job| job.state == TodoJobState::Created));
+        assert_ne!(jobs[0].id, jobs[1].id);
+
+        // Hand edits to the job are kept; only the new TODO is added
+        let job_file = root.join("jobs").join(format!("{}.md", todo_job_id(Path::new("src/a_b.rs"))));
+        let edited = fs::read_to_string(&job_file).unwrap().replace("Resolve these", "Carefully resolve these");
+        fs::write(&job_file, &edited).unwrap();
+        fs::write(root.join("src/a_b.rs"), "// TODO(worksplit): first\nfn x() {}\n// TODO(worksplit): third\n").unwrap();
+
+        let dry = scan_todos(root, true).unwrap();
+        assert_eq!(dry[1].state, TodoJobState::Updated { added: 1 });
+        assert_eq!(fs::read_to_string(&job_file).unwrap(), edited);
+
+        let jobs = scan_todos(root, false).unwrap();
+        assert_eq!(jobs[0].state, TodoJobState::Unchanged);
+        assert_eq!(jobs[1].state, TodoJobState::Updated { added: 1 });
+        assert_eq!(fs::read_to_string(&job_file).unwrap(), format!("{}- Line 3: third\n", edited));
+        assert!(scan_todos(root, false).unwrap().iter().all(|job| job.state == TodoJobState::Unchanged));
+    }
+
+    #[test]
+    fn test_generated_job_quotes_paths() {
+        let file = Path::new("src/odd: name #1.rs");
+        let content = generate_todo_job(file, &[TodoComment { line: 1, text: "fix".to_string() }]);
+        let frontmatter = content.trim_start_matches("---\n").split("\n---\n").next().unwrap();
+        let metadata: crate::models::JobMetadata = serde_yaml::from_str(frontmatter).unwrap();
+        assert_eq!(metadata.target_files, Some(vec![file.to_path_buf()]));
+        assert_eq!(metadata.output_dir, PathBuf::from("src"));
+        assert_eq!(metadata.output_file, "odd: name #1.rs");
+    }
+}
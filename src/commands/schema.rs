@@ -0,0 +1,33 @@
+use std::fs;
+use std::path::Path;
+
+use crate::core::schema::{schema_json, SchemaKind};
+use crate::error::WorkSplitError;
+
+/// Print a JSON Schema, or write schemas to `out_dir`
+///
+/// With `out_dir`, writes the selected schema (or all of them) as
+/// `<out_dir>/<name>.schema.json`. Without it, prints the selected schema.
+pub fn dump_schema(kind: Option<SchemaKind>, out_dir: Option<&Path>) -> Result<(), WorkSplitError> {
+    let Some(out_dir) = out_dir else {
+        let Some(kind) = kind else {
+            return Err(WorkSplitError::JobError(
+                "Specify a schema (job, config, status) or --out <dir> to write all of them".to_string(),
+            ));
+        };
+        print!("{}", schema_json(kind));
+        return Ok(());
+    };
+
+    fs::create_dir_all(out_dir)?;
+    let kinds = match kind {
+        Some(kind) => vec![kind],
+        None => SchemaKind::ALL.to_vec(),
+    };
+    for kind in kinds {
+        let path = out_dir.join(kind.file_name());
+        fs::write(&path, schema_json(kind))?;
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
@@ -1,12 +1,128 @@
-use std::path::PathBuf;
+use chrono::{Local, Utc};
+use std::collections::BTreeSet;
+use std::path::Path;
 
+use crate::commands::archive::archive_status;
+use crate::core::file_cache::CacheStats;
+use crate::core::history::{self, ChangeReport};
+use crate::core::spool::Spool;
 use crate::core::{JobsManager, StatusManager};
 use crate::error::WorkSplitError;
 use crate::models::{JobStatus, LimitsConfig};
 
-/// Show job status
-pub fn show_status(project_root: &PathBuf, verbose: bool) -> Result<(), WorkSplitError> {
-    let jobs_manager = JobsManager::new(project_root.clone(), LimitsConfig::default());
+/// Upper-case label for a status
+pub(crate) fn status_label(status: JobStatus) -> &'static str {
+    match status {
+        JobStatus::Created => "CREATED",
+        JobStatus::PendingTest => "PENDING TEST",
+        JobStatus::PendingWork => "PENDING WORK",
+        JobStatus::PendingVerification => "PENDING VERIFY",
+        JobStatus::PendingTestRun => "PENDING TEST RUN",
+        JobStatus::Pass => "PASS",
+        JobStatus::Fail => "FAIL",
+        JobStatus::Partial => "PARTIAL",
+    }
+}
+
+/// Bytes as B, KB, MB or GB
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Print the file cache statistics the latest run recorded
+fn print_cache_stats(jobs_dir: &Path) {
+    let Some((updated_at, stats)) = CacheStats::load(jobs_dir) else {
+        println!("File cache: no statistics yet (recorded by `worksplit run`)");
+        println!();
+        return;
+    };
+    println!("File cache (latest run, {}):", updated_at.with_timezone(&Local).format("%Y-%m-%d %H:%M"));
+    let hit_rate = stats.hit_rate().map_or_else(|| "-".to_string(), |rate| format!("{:.1}%", rate * 100.0));
+    println!("  Lookups: {} hits, {} misses (hit rate {})", stats.hits, stats.misses, hit_rate);
+    let limit = match stats.max_bytes {
+        0 => "unbounded".to_string(),
+        max => format!("limit {}", format_bytes(max)),
+    };
+    println!(
+        "  Held:    {} file(s), {} lines, {} ({}, {} evicted)",
+        stats.entry_count,
+        stats.total_lines,
+        format_bytes(stats.total_bytes),
+        limit,
+        stats.evictions
+    );
+    println!();
+}
+
+/// Print what the run history says changed since `report.since`
+fn print_changes(report: &ChangeReport) {
+    println!("Since {}:", report.since.with_timezone(&Local).format("%Y-%m-%d %H:%M"));
+    if report.jobs.is_empty() {
+        println!("  No jobs ran.");
+        println!();
+        return;
+    }
+
+    let minutes = report.job_time.num_seconds() as f64 / 60.0;
+    println!(
+        "  {} run(s), {} job run(s), {} file(s) generated, {} tokens, {:.1} min",
+        report.runs,
+        report.jobs.iter().map(|j| j.runs).sum::<usize>(),
+        report.files.len(),
+        report.tokens,
+        minutes
+    );
+
+    let statuses: BTreeSet<JobStatus> = report.counts_before.keys().chain(report.counts_after.keys()).copied().collect();
+    let deltas: Vec<String> = statuses.into_iter()
+        .filter_map(|status| {
+            let before = report.counts_before.get(&status).copied().unwrap_or(0);
+            let after = report.counts_after.get(&status).copied().unwrap_or(0);
+            (before != after).then(|| format!("{} {} -> {} ({:+})", status_label(status), before, after, after as i64 - before as i64))
+        })
+        .collect();
+    if !deltas.is_empty() {
+        println!("  {}", deltas.join(", "));
+    }
+
+    println!("\nJobs that changed status:");
+    let mut changed = 0;
+    for job in report.changed() {
+        changed += 1;
+        println!("  {} {} -> {}", job.job_id, status_label(job.before), status_label(job.after));
+    }
+    if changed == 0 {
+        println!("  (none)");
+    }
+    let unchanged: Vec<&str> = report.jobs.iter().filter(|j| j.before == j.after).map(|j| j.job_id.as_str()).collect();
+    if !unchanged.is_empty() {
+        println!("Ran without a status change: {}", unchanged.join(", "));
+    }
+
+    if !report.files.is_empty() {
+        println!("\nFiles (re)generated:");
+        for file in &report.files {
+            println!("  {}", file.display());
+        }
+    }
+    println!();
+}
+
+/// Show job status. `since` adds what changed since then, from the run
+/// history (see [`history::parse_since`] for accepted values).
+pub fn show_status(project_root: &Path, verbose: bool, by_failure: bool, since: Option<&str>, cache: bool) -> Result<(), WorkSplitError> {
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), LimitsConfig::default());
     
     if !jobs_manager.jobs_folder_exists() {
         return Err(WorkSplitError::JobsFolderNotFound(
@@ -16,8 +132,12 @@ pub fn show_status(project_root: &PathBuf, verbose: bool) -> Result<(), WorkSpli
 
     // Discover jobs and sync
     let discovered = jobs_manager.discover_jobs()?;
+    let spool = Spool::new(jobs_manager.jobs_dir());
     let mut status_manager = StatusManager::new(jobs_manager.jobs_dir())?;
+    // Spool workers may be saving the status file at the same time
+    status_manager.set_shared(spool.exists());
     status_manager.sync_with_jobs(&discovered)?;
+    status_manager.sync_drafts(&jobs_manager.draft_jobs(&discovered))?;
 
     let summary = status_manager.get_summary();
 
@@ -25,6 +145,28 @@ pub fn show_status(project_root: &PathBuf, verbose: bool) -> Result<(), WorkSpli
     println!("{}", summary);
     println!();
 
+    if spool.exists() {
+        let counts = spool.counts()?;
+        println!("Spool: {} queued, {} running, {} done", counts.queued, counts.running, counts.done);
+        println!();
+    }
+
+    let archived = archive_status(jobs_manager.jobs_dir())?.all_entries().len();
+    if archived > 0 {
+        println!("Archived: {} job(s) in jobs/archive/", archived);
+        println!();
+    }
+
+    if cache {
+        print_cache_stats(jobs_manager.jobs_dir());
+    }
+
+    if let Some(since) = since {
+        let events = history::load(jobs_manager.jobs_dir());
+        let since = history::parse_since(since, &events, Utc::now())?;
+        print_changes(&ChangeReport::since(since, &events, &status_manager.all_entries()));
+    }
+
     if verbose {
         let entries = status_manager.all_entries();
         let mut sorted: Vec<_> = entries.into_iter().collect();
@@ -35,22 +177,22 @@ pub fn show_status(project_root: &PathBuf, verbose: bool) -> Result<(), WorkSpli
         } else {
             println!("Jobs:");
             for entry in sorted {
-                let status_str = match entry.status {
-                    JobStatus::Created => "CREATED",
-                    JobStatus::PendingTest => "PENDING TEST",
-                    JobStatus::PendingWork => "PENDING WORK",
-                    JobStatus::PendingVerification => "PENDING VERIFY",
-                    JobStatus::PendingTestRun => "PENDING TEST RUN",
-                    JobStatus::Pass => "PASS",
-                    JobStatus::Fail => "FAIL",
-                    JobStatus::Partial => "PARTIAL",
-                };
+                let status_str = status_label(entry.status);
 
                 // Show ran indicator for jobs that have been executed
                 let ran_str = if entry.ran { " (ran)" } else { "" };
+                let draft_str = if entry.draft { " (draft)" } else { "" };
 
-                print!("  {} [{}]{}", entry.id, status_str, ran_str);
+                print!("  {} [{}]{}{}", entry.id, status_str, ran_str, draft_str);
                 
+                if let Some(kind) = entry.failure {
+                    print!(" ({})", kind);
+                }
+
+                if let Some(rubric) = entry.rubric {
+                    print!(" [rubric {}]", rubric);
+                }
+
                 if let Some(ref error) = entry.error {
                     print!(" - {}", error);
                 }
@@ -60,6 +202,31 @@ pub fn show_status(project_root: &PathBuf, verbose: bool) -> Result<(), WorkSpli
         }
     }
 
+    if by_failure {
+        let by_kind = status_manager.failures_by_kind();
+        if by_kind.is_empty() {
+            println!("No failed or partial jobs.");
+        } else {
+            println!("Failures by kind:");
+            for (kind, ids) in &by_kind {
+                println!("  {:<18} {:>3}  {}", kind.as_str(), ids.len(), ids.join(", "));
+            }
+        }
+        println!();
+    }
+
+    let mut drafts: Vec<&str> = status_manager.all_entries().into_iter()
+        .filter(|e| e.draft)
+        .map(|e| e.id.as_str())
+        .collect();
+    if !drafts.is_empty() {
+        drafts.sort();
+        println!("\n{} draft job(s), never run until `draft: true` is removed:", drafts.len());
+        for id in drafts {
+            println!("  {} [DRAFT]", id);
+        }
+    }
+
     // Show ran but non-pass jobs (likely manually fixed)
     let ran_non_pass = status_manager.get_ran_non_pass_jobs();
     if !ran_non_pass.is_empty() {
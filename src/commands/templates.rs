@@ -0,0 +1,36 @@
+use clap::ValueEnum;
+use std::path::Path;
+
+use crate::core::job_templates::{list_job_templates, BUILTIN_SLOTS};
+use crate::core::prompt_templates::TEMPLATES_DIR;
+use crate::error::WorkSplitError;
+use crate::models::JobTemplate;
+
+/// Print the built-in job templates and the project's own
+/// (`jobs/_templates/*.md`) with their slots to fill in
+pub fn list_templates(project_root: &Path) -> Result<(), WorkSplitError> {
+    let builtin: Vec<String> = JobTemplate::value_variants()
+        .iter()
+        .filter_map(|t| t.to_possible_value().map(|v| v.get_name().to_string()))
+        .collect();
+    println!("Built-in templates (new-job --type or --template):");
+    println!("  {}", builtin.join(", "));
+
+    let templates = list_job_templates(&project_root.join("jobs"))?;
+    println!();
+    if templates.is_empty() {
+        println!("No project templates: add job files with {{{{slot}}}} placeholders to jobs/{}/", TEMPLATES_DIR);
+        return Ok(());
+    }
+
+    println!("Project templates (new-job --template), jobs/{}/:", TEMPLATES_DIR);
+    let width = templates.iter().map(|t| t.name.len()).max().unwrap_or(0);
+    for template in &templates {
+        println!("  {:<width$}  {}", template.name, template.description.as_deref().unwrap_or(""));
+        let slots: Vec<String> = template.slots().into_iter().filter(|s| !BUILTIN_SLOTS.contains(&s.as_str())).collect();
+        if !slots.is_empty() {
+            println!("  {:<width$}  slots: {} (--set {}=...)", "", slots.join(", "), slots[0]);
+        }
+    }
+    Ok(())
+}
@@ -1,22 +1,32 @@
-use std::path::PathBuf;
+use std::path::Path;
 
-use crate::core::JobsManager;
+use crate::core::anchors::expand_anchors;
+use crate::core::injection::scan_job;
+use crate::core::job_lint::{lint_job, JobLint};
+use crate::core::output_paths::check_output_paths;
+use crate::core::schema::{config_document, job_frontmatter, validate_value, SchemaKind};
+use crate::core::status::{migrate_status_document, STATUS_DB_FILE};
+use crate::core::{apply_job_defaults, JobsManager};
 use crate::error::WorkSplitError;
-use crate::models::Config;
+use crate::models::{Config, InjectionPolicy, STATUS_FILE_VERSION};
 
 /// Validation result
 pub struct ValidationResult {
     pub valid: bool,
     pub errors: Vec<String>,
     pub warnings: Vec<String>,
+    /// Job-authoring problems (`--lint`); they don't make the project invalid
+    pub lints: Vec<JobLint>,
 }
 
-/// Validate jobs folder structure
-pub fn validate_jobs(project_root: &PathBuf) -> Result<ValidationResult, WorkSplitError> {
+/// Validate jobs folder structure, and with `lint` also check job files for
+/// common authoring problems
+pub fn validate_jobs(project_root: &Path, lint: bool) -> Result<ValidationResult, WorkSplitError> {
     let mut result = ValidationResult {
         valid: true,
         errors: Vec::new(),
         warnings: Vec::new(),
+        lints: Vec::new(),
     };
 
     let jobs_dir = project_root.join("jobs");
@@ -47,23 +57,59 @@ pub fn validate_jobs(project_root: &PathBuf) -> Result<ValidationResult, WorkSpl
         result.warnings.push("Missing _managerinstruction.md (optional)".to_string());
     }
 
-    // Check job status file
+    // Check job status file (a status database replaces it)
     let status_file = jobs_dir.join("_jobstatus.json");
-    if !status_file.exists() {
+    if !status_file.exists() && !jobs_dir.join(STATUS_DB_FILE).exists() {
         result.warnings.push("Missing _jobstatus.json (will be created on first run)".to_string());
+    } else if let Ok(content) = std::fs::read_to_string(&status_file) {
+        match serde_json::from_str(&content).map_err(|e| format!("invalid JSON: {}", e)).and_then(migrate_status_document) {
+            Ok((document, version)) => {
+                if version < STATUS_FILE_VERSION {
+                    result.warnings.push(format!(
+                        "_jobstatus.json uses format version {} (upgraded to {} on the next run)",
+                        version, STATUS_FILE_VERSION
+                    ));
+                }
+                for violation in validate_value(SchemaKind::Status, &document) {
+                    result.errors.push(format!("_jobstatus.json: {}", violation));
+                    result.valid = false;
+                }
+            }
+            Err(e) => {
+                result.errors.push(format!("_jobstatus.json: {}", e));
+                result.valid = false;
+            }
+        }
     }
 
     // Load config from worksplit.toml (or use defaults)
     let config = Config::load_from_dir(project_root).unwrap_or_default();
+    let injection_check = config.safety.injection_check;
 
     // Validate individual job files
-    let jobs_manager = JobsManager::new(project_root.clone(), config.limits);
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), config.limits);
+    let mut parsed_jobs = Vec::new();
     match jobs_manager.discover_jobs() {
         Ok(jobs) => {
             if jobs.is_empty() {
                 result.warnings.push("No job files found".to_string());
             } else {
                 for job_id in jobs {
+                    // Drafts are allowed to be incomplete
+                    if jobs_manager.is_draft(&job_id) {
+                        result.warnings.push(format!("Job '{}': draft, not validated", job_id));
+                        continue;
+                    }
+
+                    // Schema check catches misspelled keys that parsing silently ignores
+                    if let Ok(mut frontmatter) = job_frontmatter(&jobs_dir.join(format!("{}.md", job_id))) {
+                        apply_job_defaults(jobs_manager.job_defaults(), &mut frontmatter);
+                        for violation in validate_value(SchemaKind::Job, &frontmatter) {
+                            result.errors.push(format!("Job '{}': {}", job_id, violation));
+                            result.valid = false;
+                        }
+                    }
+
                     match jobs_manager.parse_job(&job_id) {
                         Ok(job) => {
                             // Validate context files exist
@@ -78,6 +124,45 @@ pub fn validate_jobs(project_root: &PathBuf) -> Result<ValidationResult, WorkSpl
                                 }
                             }
 
+                            // Screened as the prompt will see them
+                            let mut assembled = job.clone();
+                            match expand_anchors(project_root, &job.instructions, job.metadata.workdir.as_deref()) {
+                                Ok(expanded) => assembled.instructions = expanded,
+                                Err(e) => {
+                                    result.errors.push(format!("Job '{}': {}", job_id, e));
+                                    result.valid = false;
+                                }
+                            }
+
+                            if injection_check != InjectionPolicy::Off {
+                                for (part, findings) in scan_job(&assembled) {
+                                    for finding in findings {
+                                        let message = match part {
+                                            "instructions" => format!("Job '{}': possible prompt injection: {}", job_id, finding),
+                                            _ => format!("Job '{}': possible prompt injection in {}: {}", job_id, part, finding),
+                                        };
+                                        if injection_check == InjectionPolicy::Refuse {
+                                            result.errors.push(message);
+                                            result.valid = false;
+                                        } else {
+                                            result.warnings.push(message);
+                                        }
+                                    }
+                                }
+                            }
+
+                            if let Some(ref name) = job.metadata.system_prompt {
+                                let path = jobs_manager.named_prompt_path(name);
+                                if !path.exists() {
+                                    result.errors.push(format!(
+                                        "Job '{}': system prompt not found: jobs/{}",
+                                        job_id,
+                                        path.file_name().unwrap().to_string_lossy()
+                                    ));
+                                    result.valid = false;
+                                }
+                            }
+
                             // Check output directory
                             let output_dir = project_root.join(&job.metadata.output_dir);
                             if !output_dir.exists() {
@@ -87,6 +172,7 @@ pub fn validate_jobs(project_root: &PathBuf) -> Result<ValidationResult, WorkSpl
                                     job.metadata.output_dir.display()
                                 ));
                             }
+                            parsed_jobs.push(job);
                         }
                         Err(e) => {
                             result.errors.push(format!("Job '{}': {}", job_id, e));
@@ -102,10 +188,35 @@ pub fn validate_jobs(project_root: &PathBuf) -> Result<ValidationResult, WorkSpl
         }
     }
 
+    if lint {
+        for job in &parsed_jobs {
+            result.lints.extend(lint_job(project_root, job));
+        }
+    }
+
+    // Output paths that collide, escape the project or clobber context
+    for issue in check_output_paths(project_root, &parsed_jobs) {
+        result.errors.push(issue.message);
+        result.valid = false;
+    }
+
     // Check config file
     let config_file = project_root.join("worksplit.toml");
     if !config_file.exists() {
         result.warnings.push("Missing worksplit.toml (using defaults)".to_string());
+    } else {
+        match config_document(&config_file) {
+            Ok(document) => {
+                for violation in validate_value(SchemaKind::Config, &document) {
+                    result.errors.push(format!("worksplit.toml: {}", violation));
+                    result.valid = false;
+                }
+            }
+            Err(e) => {
+                result.errors.push(format!("worksplit.toml: {}", e));
+                result.valid = false;
+            }
+        }
     }
 
     Ok(result)
@@ -137,7 +248,16 @@ pub fn print_validation_result(result: &ValidationResult) {
         println!();
     }
 
-    if result.valid && result.errors.is_empty() && result.warnings.is_empty() {
+    if !result.lints.is_empty() {
+        println!("Lint:");
+        for lint in &result.lints {
+            println!("  - {}", lint);
+            println!("    fix: {}", lint.suggestion);
+        }
+        println!();
+    }
+
+    if result.valid && result.errors.is_empty() && result.warnings.is_empty() && result.lints.is_empty() {
         println!("All checks passed!");
     }
 }
@@ -0,0 +1,21 @@
+use std::path::Path;
+
+use crate::core::{describe_warm_up, load_config, OllamaClient};
+use crate::error::WorkSplitError;
+
+/// Check that Ollama is up and the model is pulled, then load the model into
+/// memory and report how long that took
+pub async fn warm_up_model(project_root: &Path, profile: Option<&str>, model: Option<String>, url: Option<String>) -> Result<(), WorkSplitError> {
+    let config = load_config(project_root, profile, model, url, None, false)?;
+    let ollama = OllamaClient::new(config.ollama.clone())?;
+
+    ollama.ensure_running().await?;
+    if !ollama.check_model().await? {
+        println!("Model '{}' is not in Ollama's model list; pull it with `ollama pull {}`",
+            config.ollama.model, config.ollama.model);
+    }
+    println!("Warming up model '{}' at {}...", config.ollama.model, config.ollama.url);
+    let warm_up = ollama.warm_up().await?;
+    println!("{}", describe_warm_up(&warm_up));
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::Path;
+use tracing::{info, warn};
+
+use crate::commands::status::status_label;
+use crate::core::spool::{default_worker_name, Claim, Spool};
+use crate::core::{describe_warm_up, load_config, JobResult, JobsManager, OllamaClient, Runner, StatusManager};
+use crate::error::WorkSplitError;
+use crate::models::{Config, JobStatus};
+
+/// Worker options
+#[derive(Default)]
+pub struct WorkerOptions {
+    /// Exit once the queue is empty instead of polling
+    pub once: bool,
+    /// Seconds between checks of an empty queue
+    pub poll_seconds: u64,
+    /// Requeue jobs claimed this many minutes ago (workers that died)
+    pub requeue_after: Option<u64>,
+    /// Worker name recorded in claims and results (default: host-pid)
+    pub name: Option<String>,
+    /// Named `[profile.<name>]` section of worksplit.toml to apply
+    pub profile: Option<String>,
+    /// Model override
+    pub model: Option<String>,
+    /// URL override
+    pub url: Option<String>,
+}
+
+/// Move jobs into jobs/queue/ for workers to pick up
+pub fn enqueue_jobs(project_root: &Path, selectors: &[String]) -> Result<(), WorkSplitError> {
+    let config = Config::load_from_dir(project_root)?;
+    let jobs_manager = JobsManager::new(project_root.to_path_buf(), config.limits);
+    let job_ids = jobs_manager.select_jobs(selectors)?;
+    if job_ids.is_empty() {
+        println!("No jobs to enqueue.");
+        return Ok(());
+    }
+
+    let spool = Spool::new(jobs_manager.jobs_dir());
+    let mut status_manager = StatusManager::new(jobs_manager.jobs_dir())?;
+    status_manager.set_shared(true);
+    for job_id in &job_ids {
+        spool.enqueue(job_id)?;
+        status_manager.remove_job(job_id)?;
+        println!("Queued: {}", job_id);
+    }
+    println!("\nQueued {} job(s) in jobs/queue/", job_ids.len());
+    Ok(())
+}
+
+/// Claim queued jobs one at a time and run them until the queue is empty
+/// (with `once`) or forever
+pub async fn run_worker(project_root: &Path, options: WorkerOptions) -> Result<(), WorkSplitError> {
+    let config = load_config(project_root, options.profile.as_deref(), options.model, options.url, None, false)?;
+    let jobs_dir = project_root.join("jobs");
+    let spool = Spool::new(&jobs_dir);
+    let worker = options.name.unwrap_or_else(default_worker_name);
+    let poll = std::time::Duration::from_secs(options.poll_seconds.max(1));
+    info!("Worker {} watching {}", worker, jobs_dir.join("queue").display());
+    if config.ollama.warmup {
+        match OllamaClient::new(config.ollama.clone())?.warm_up().await {
+            Ok(warm_up) => info!("{}", describe_warm_up(&warm_up)),
+            Err(e) => warn!("Model warm-up failed: {}", e),
+        }
+    }
+
+    let mut processed = 0;
+    loop {
+        if let Some(minutes) = options.requeue_after {
+            spool.requeue_stale(chrono::Duration::minutes(minutes as i64))?;
+        }
+        let Some(claim) = spool.claim(&worker)? else {
+            if options.once {
+                break;
+            }
+            tokio::time::sleep(poll).await;
+            continue;
+        };
+
+        let result = run_claimed(project_root, &config, &spool, &claim).await;
+        let (status, error) = match result {
+            Ok(result) => (result.status, result.error),
+            Err(e) => (JobStatus::Fail, Some(e.to_string())),
+        };
+        let result = spool.finish(&claim, status, error)?;
+        match &result.error {
+            Some(error) => println!("  {} [{}]: {}", result.job_id, status_label(result.status), error),
+            None => println!("  {} [{}]", result.job_id, status_label(result.status)),
+        }
+        processed += 1;
+    }
+
+    println!("\nWorker {} processed {} job(s)", worker, processed);
+    Ok(())
+}
+
+/// Check a claimed job out into jobs/ as `_spool_<id>.md`, a name job
+/// discovery skips (like `worksplit oneshot`), so a `worksplit run` in the
+/// same project can't run it a second time. Its status entry only lives
+/// while it runs; the spool keeps the result.
+async fn run_claimed(project_root: &Path, config: &Config, spool: &Spool, claim: &Claim) -> Result<JobResult, WorkSplitError> {
+    let job_id = format!("_spool_{}", claim.job_id);
+    let job_file = project_root.join("jobs").join(format!("{}.md", job_id));
+    fs::copy(spool.running_file(claim), &job_file)?;
+
+    let result = async {
+        let mut runner = Runner::new(config.clone(), project_root.to_path_buf())?;
+        runner.share_status();
+        runner.run_oneshot(&job_id).await
+    }.await;
+
+    if let Err(e) = fs::remove_file(&job_file) {
+        warn!("Failed to remove {}: {}", job_file.display(), e);
+    }
+    result
+}
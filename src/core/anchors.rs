@@ -0,0 +1,157 @@
+//! Code anchors in job instructions
+//!
+//! `{{file:src/lib.rs#L40-L80}}` in a job's instructions is replaced by those
+//! lines of the file, quoted in a fence under their path and line numbers,
+//! so instructions can point at specific code without adding the whole file
+//! as context. `#L40` quotes a single line; without a range the whole file is
+//! quoted. Paths are relative to the job's `workdir` (a leading `/` starts at
+//! the project root), like `context_files`, and must stay inside the
+//! project.
+
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::core::output_paths::resolve_inside;
+use crate::error::WorkSplitError;
+
+fn anchor_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"\{\{file:([^#}\s]+)(?:#L(\d+)(?:-L?(\d+))?)?\}\}").unwrap())
+}
+
+/// An anchor found in instructions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anchor {
+    /// The anchor as written, braces included
+    pub text: String,
+    /// File relative to the project root
+    pub path: PathBuf,
+    /// First and last line (1-based, inclusive); `None` for the whole file
+    pub lines: Option<(usize, usize)>,
+}
+
+impl Anchor {
+    /// The file relative to the project root, or `None` if it lies outside
+    /// the project (`..`, or a symlink pointing out)
+    pub fn resolve(&self, project_root: &Path) -> Option<PathBuf> {
+        if self.path.is_absolute() {
+            return None;
+        }
+        resolve_inside(project_root, &self.path)
+    }
+
+    /// The quoted lines, with the path and line numbers above the fence
+    fn snippet(&self, project_root: &Path) -> Result<String, WorkSplitError> {
+        let error = |message: String| WorkSplitError::InvalidAnchor { anchor: self.text.clone(), message };
+        let outside = || error(format!("{} is outside the project", self.path.display()));
+        let path = project_root.join(self.resolve(project_root).ok_or_else(outside)?);
+        let content = fs::read_to_string(&path)
+            .map_err(|e| error(format!("cannot read {}: {}", self.path.display(), e)))?;
+        let lines: Vec<&str> = content.lines().collect();
+
+        let (header, quoted) = match self.lines {
+            None => (self.path.display().to_string(), lines.as_slice()),
+            Some((start, end)) => {
+                if start == 0 || end < start {
+                    return Err(error(format!("invalid line range {}-{}", start, end)));
+                }
+                if end > lines.len() {
+                    return Err(error(format!("{} has only {} lines", self.path.display(), lines.len())));
+                }
+                let range = if start == end { format!("line {}", start) } else { format!("lines {}-{}", start, end) };
+                (format!("{} ({})", self.path.display(), range), &lines[start - 1..end])
+            }
+        };
+        Ok(format!("`{}`:\n```\n{}\n```", header, quoted.join("\n")))
+    }
+}
+
+/// Anchors in `instructions`, in order; `workdir` resolves relative paths
+pub fn find_anchors(instructions: &str, workdir: Option<&Path>) -> Vec<Anchor> {
+    anchor_regex().captures_iter(instructions).map(|caps| {
+        let written = Path::new(&caps[1]);
+        let path = match (written.strip_prefix("/"), workdir) {
+            (Ok(from_root), _) => from_root.to_path_buf(),
+            (Err(_), Some(workdir)) => workdir.join(written),
+            (Err(_), None) => written.to_path_buf(),
+        };
+        let lines = caps.get(2).map(|start| {
+            let start: usize = start.as_str().parse().unwrap_or(0);
+            let end = caps.get(3).map_or(start, |end| end.as_str().parse().unwrap_or(0));
+            (start, end)
+        });
+        Anchor { text: caps[0].to_string(), path, lines }
+    }).collect()
+}
+
+/// Replace every anchor in `instructions` with the code it points at
+///
+/// Fails on the first anchor whose file can't be read or whose lines are
+/// out of range.
+pub fn expand_anchors(project_root: &Path, instructions: &str, workdir: Option<&Path>) -> Result<String, WorkSplitError> {
+    let mut expanded = instructions.to_string();
+    for anchor in find_anchors(instructions, workdir) {
+        let snippet = anchor.snippet(project_root)?;
+        expanded = expanded.replacen(&anchor.text, &snippet, 1);
+    }
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_expand_anchors() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("pkg/src")).unwrap();
+        fs::write(temp.path().join("pkg/src/lib.rs"), "fn a() {}\nfn b() {}\nfn c() {}\n").unwrap();
+        fs::write(temp.path().join("shared.rs"), "pub struct S;\n").unwrap();
+
+        let instructions = "Change {{file:src/lib.rs#L2-L3}} like {{file:src/lib.rs#L1}}, using {{file:/shared.rs}}.";
+        let anchors = find_anchors(instructions, Some(Path::new("pkg")));
+        assert_eq!(anchors.len(), 3);
+        assert_eq!(anchors[0].path, PathBuf::from("pkg/src/lib.rs"));
+        assert_eq!(anchors[1].lines, Some((1, 1)));
+        assert_eq!(anchors[2], Anchor { text: "{{file:/shared.rs}}".into(), path: "shared.rs".into(), lines: None });
+
+        let expanded = expand_anchors(temp.path(), instructions, Some(Path::new("pkg"))).unwrap();
+        assert_eq!(expanded, "Change `pkg/src/lib.rs (lines 2-3)`:\n```\nfn b() {}\nfn c() {}\n```\
+            \x20like `pkg/src/lib.rs (line 1)`:\n```\nfn a() {}\n```, using `shared.rs`:\n```\npub struct S;\n```.");
+
+        // Plain template syntax is left alone
+        assert_eq!(expand_anchors(temp.path(), "{{ name }}", None).unwrap(), "{{ name }}");
+
+        let past_end = expand_anchors(temp.path(), "{{file:pkg/src/lib.rs#L2-L9}}", None).unwrap_err();
+        assert!(past_end.to_string().contains("has only 3 lines"));
+        let backwards = expand_anchors(temp.path(), "{{file:pkg/src/lib.rs#L3-L2}}", None).unwrap_err();
+        assert!(backwards.to_string().contains("invalid line range 3-2"));
+        let missing = expand_anchors(temp.path(), "{{file:nope.rs}}", None).unwrap_err();
+        assert!(missing.to_string().starts_with("Instruction anchor {{file:nope.rs}}: cannot read nope.rs"));
+    }
+
+    #[test]
+    fn test_anchors_outside_project() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir_all(project.join("pkg")).unwrap();
+        fs::write(temp.path().join("secret.txt"), "TOKEN=1\n").unwrap();
+
+        for instructions in ["{{file:../secret.txt}}", "{{file:/../secret.txt}}", "{{file:src/../../secret.txt#L1}}"] {
+            let err = expand_anchors(&project, instructions, None).unwrap_err();
+            assert!(err.to_string().contains("is outside the project"), "{}", err);
+        }
+        let err = expand_anchors(&project, "{{file:../../secret.txt}}", Some(Path::new("pkg"))).unwrap_err();
+        assert!(err.to_string().contains("is outside the project"));
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(temp.path().join("secret.txt"), project.join("link.txt")).unwrap();
+            let err = expand_anchors(&project, "{{file:link.txt}}", None).unwrap_err();
+            assert!(err.to_string().contains("is outside the project"));
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Crash-safe file writes
+//!
+//! Content is written to `<path>.tmp` next to the target, flushed to disk
+//! and renamed over the target. A crash or a failed write part-way through
+//! leaves the previous file intact instead of a truncated one.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Temporary file a write to `path` goes through
+pub fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Write `content` to `path`, replacing it atomically
+///
+/// The replaced file's permissions are kept, and a symlink is written
+/// through rather than replaced by a regular file.
+pub fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> io::Result<()> {
+    let target = if path.is_symlink() {
+        fs::canonicalize(path)?
+    } else {
+        path.to_path_buf()
+    };
+    let temp = temp_path(&target);
+
+    let result = (|| {
+        let mut file = File::create(&temp)?;
+        file.write_all(content.as_ref())?;
+        if let Ok(metadata) = fs::metadata(&target) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&temp, &target)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result?;
+
+    // Make the rename itself durable; not supported on every platform
+    #[cfg(unix)]
+    if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+        let _ = File::open(parent).and_then(|dir| dir.sync_all());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_atomic_creates_and_replaces() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("lib.rs");
+        assert_eq!(temp_path(&path), temp.path().join("lib.rs.tmp"));
+
+        write_atomic(&path, "fn a() {}\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fn a() {}\n");
+        write_atomic(&path, "fn b() {}\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fn b() {}\n");
+        assert!(!temp_path(&path).exists());
+    }
+
+    #[test]
+    fn test_failed_write_keeps_the_original() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("lib.rs");
+        fs::write(&path, "original").unwrap();
+        // The temporary file can't be created where a directory is in the way
+        fs::create_dir(temp_path(&path)).unwrap();
+
+        assert!(write_atomic(&path, "replacement").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_keeps_permissions_and_symlinks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = TempDir::new().unwrap();
+        let script = temp.path().join("build.sh");
+        fs::write(&script, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        write_atomic(&script, "#!/bin/sh\necho hi\n").unwrap();
+        assert_eq!(fs::metadata(&script).unwrap().permissions().mode() & 0o777, 0o755);
+
+        let link = temp.path().join("link.sh");
+        std::os::unix::fs::symlink(&script, &link).unwrap();
+        write_atomic(&link, "#!/bin/sh\necho linked\n").unwrap();
+        assert!(link.is_symlink());
+        assert_eq!(fs::read_to_string(&script).unwrap(), "#!/bin/sh\necho linked\n");
+    }
+}
@@ -0,0 +1,101 @@
+//! Resource budget for a run (`run --max-jobs / --max-minutes / --max-tokens`)
+//!
+//! Budgets are checked before each job starts: a job that is already running
+//! always finishes, and every job after the limit is reported as skipped.
+
+use std::time::{Duration, Instant};
+
+/// Limits for one `worksplit run`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunBudget {
+    /// Maximum number of jobs to start
+    pub max_jobs: Option<usize>,
+    /// Maximum wall time before no new job is started
+    pub max_duration: Option<Duration>,
+    /// Maximum LLM tokens (prompt + generated) for the whole run
+    pub max_tokens: Option<u64>,
+}
+
+impl RunBudget {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_jobs.is_none() && self.max_duration.is_none() && self.max_tokens.is_none()
+    }
+
+    /// Start tracking a run against this budget
+    pub fn start(self, tokens_before: u64) -> BudgetTracker {
+        BudgetTracker {
+            budget: self,
+            started: Instant::now(),
+            tokens_before,
+        }
+    }
+}
+
+/// A running budget
+#[derive(Debug, Clone)]
+pub struct BudgetTracker {
+    budget: RunBudget,
+    started: Instant,
+    tokens_before: u64,
+}
+
+impl BudgetTracker {
+    /// Why no further job may start, or `None` while within budget.
+    ///
+    /// `jobs_started` counts jobs run so far, `tokens_total` is the client's
+    /// running token count.
+    pub fn exhausted(&self, jobs_started: usize, tokens_total: u64) -> Option<String> {
+        self.exhausted_after(jobs_started, tokens_total, self.started.elapsed())
+    }
+
+    fn exhausted_after(&self, jobs_started: usize, tokens_total: u64, elapsed: Duration) -> Option<String> {
+        if let Some(max) = self.budget.max_jobs {
+            if jobs_started >= max {
+                return Some(format!("job budget reached ({} job(s))", max));
+            }
+        }
+        if let Some(max) = self.budget.max_duration {
+            if elapsed >= max {
+                return Some(format!("time budget reached ({} minute(s))", max.as_secs() / 60));
+            }
+        }
+        if let Some(max) = self.budget.max_tokens {
+            let used = tokens_total.saturating_sub(self.tokens_before);
+            if used >= max {
+                return Some(format!("token budget reached ({} of {} tokens)", used, max));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_budget_never_exhausts() {
+        let tracker = RunBudget::default().start(0);
+        assert!(RunBudget::default().is_unlimited());
+        assert_eq!(tracker.exhausted_after(10_000, u64::MAX, Duration::from_secs(86_400)), None);
+    }
+
+    #[test]
+    fn test_budget_limits() {
+        let budget = RunBudget {
+            max_jobs: Some(3),
+            max_duration: Some(Duration::from_secs(600)),
+            max_tokens: Some(1000),
+        };
+        // Tokens used before the run started don't count
+        let tracker = budget.start(500);
+
+        assert_eq!(tracker.exhausted_after(2, 1499, Duration::from_secs(599)), None);
+        assert!(tracker.exhausted_after(3, 0, Duration::ZERO).unwrap().contains("job budget"));
+        assert!(tracker.exhausted_after(0, 0, Duration::from_secs(600)).unwrap().contains("10 minute"));
+        assert_eq!(
+            tracker.exhausted_after(0, 1500, Duration::ZERO).unwrap(),
+            "token budget reached (1000 of 1000 tokens)"
+        );
+    }
+}
@@ -0,0 +1,68 @@
+//! Finding the project files a build's error output points at
+//!
+//! Compilers name the failing location as `path:line` (rustc, solc, eslint,
+//! gcc) or `path(line,col)` (tsc). Paths are relative to the directory the
+//! build ran in, or absolute.
+
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+use crate::core::output_paths::normalize_relative;
+
+/// Project-relative paths of existing files mentioned with a line number in
+/// `output`, in order of first mention. `build_dir` is where the build ran,
+/// relative to `project_root`.
+pub fn files_in_build_output(output: &str, project_root: &Path, build_dir: &Path) -> Vec<PathBuf> {
+    let location = Regex::new(r#"(?:^|[\s"'`(\[<])((?:[A-Za-z]:)?[\w./\\-]*\w\.\w+)(?::\d+|\(\d+)"#).unwrap();
+    let mut files: Vec<PathBuf> = Vec::new();
+    for captures in location.captures_iter(output) {
+        let mentioned = Path::new(&captures[1]);
+        let relative = if mentioned.is_absolute() {
+            let Ok(inside) = mentioned.strip_prefix(project_root) else { continue };
+            normalize_relative(inside)
+        } else {
+            normalize_relative(&build_dir.join(mentioned))
+        };
+        let Some(relative) = relative else { continue };
+        if project_root.join(&relative).is_file() && !files.contains(&relative) {
+            files.push(relative);
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_files_in_build_output() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        for file in ["src/lib.rs", "src/api.rs", "web/src/app.ts", "web/src/util.ts"] {
+            fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let rustc = "error[E0425]: cannot find value `x`\n --> src/api.rs:12:5\n   |\nwarning: unused\n --> src/lib.rs:3:1\n --> src/api.rs:40:1\n";
+        assert_eq!(
+            files_in_build_output(rustc, root, Path::new("")),
+            vec![PathBuf::from("src/api.rs"), PathBuf::from("src/lib.rs")]
+        );
+
+        // tsc run inside a workspace, plus an absolute path and a dependency outside the project
+        let tsc = format!(
+            "src/app.ts(4,7): error TS2322: Type 'string' is not assignable\n{}/web/src/util.ts:9:1 - error\n/usr/lib/node/x.d.ts:1:1\nsrc/missing.ts:1:1\n",
+            root.display()
+        );
+        assert_eq!(
+            files_in_build_output(&tsc, root, Path::new("web")),
+            vec![PathBuf::from("web/src/app.ts"), PathBuf::from("web/src/util.ts")]
+        );
+
+        assert!(files_in_build_output("Finished dev [unoptimized] in 0.5s", root, Path::new("")).is_empty());
+        assert!(files_in_build_output("--> ../outside.rs:1:1", root, Path::new("")).is_empty());
+    }
+}
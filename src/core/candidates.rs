@@ -0,0 +1,163 @@
+//! Best-of-N generation (`candidates: N` in the job frontmatter)
+//!
+//! Every candidate is verified on its own. The best one continues through the
+//! normal pipeline; the others are kept under `jobs/_candidates/<job>/` for
+//! inspection.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::atomic_write::write_atomic;
+use crate::core::output_paths::normalize_relative;
+use crate::core::VerificationResult;
+use crate::error::WorkSplitError;
+use crate::models::RubricScores;
+
+/// Folder inside jobs/ holding the candidates that weren't kept
+pub const CANDIDATES_DIR: &str = "_candidates";
+/// Verdicts of every candidate of a job
+const SUMMARY_FILE: &str = "summary.json";
+
+/// A generated candidate and its verification
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub files: Vec<(PathBuf, String)>,
+    /// The model's full answer
+    pub response: String,
+    pub result: VerificationResult,
+    pub reason: Option<String>,
+    pub rubric: Option<RubricScores>,
+}
+
+impl Candidate {
+    /// Verdict first, then the rubric average
+    fn rank(&self) -> (u8, f32) {
+        let verdict = match self.result {
+            VerificationResult::Pass => 3,
+            VerificationResult::PassWithWarnings => 2,
+            VerificationResult::FailSoft => 1,
+            VerificationResult::FailHard => 0,
+        };
+        (verdict, self.rubric.map_or(0.0, |scores| scores.average()))
+    }
+}
+
+/// Index of the best candidate: the best verdict, then the highest rubric
+/// average; the earliest candidate wins a tie
+pub fn best_candidate(candidates: &[Candidate]) -> Option<usize> {
+    let mut best: Option<usize> = None;
+    for (index, candidate) in candidates.iter().enumerate() {
+        if best.is_none_or(|b| candidate.rank() > candidates[b].rank()) {
+            best = Some(index);
+        }
+    }
+    best
+}
+
+#[derive(Serialize)]
+struct CandidateSummary<'a> {
+    candidate: usize,
+    kept: bool,
+    verdict: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rubric: Option<RubricScores>,
+    files: Vec<&'a Path>,
+}
+
+/// Write the candidates other than `kept` to `jobs/_candidates/<job>/<n>/`
+/// (numbered from 1), replacing those of an earlier run, plus a
+/// `summary.json` with every candidate's verdict. Returns the job's folder.
+pub fn save_candidates(
+    jobs_dir: &Path,
+    job_id: &str,
+    candidates: &[Candidate],
+    kept: usize,
+) -> Result<PathBuf, WorkSplitError> {
+    let dir = jobs_dir.join(CANDIDATES_DIR).join(job_id);
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+
+    let mut summary = Vec::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        if index != kept {
+            for (path, content) in &candidate.files {
+                // The model chose these paths; keep them inside the folder
+                let Some(relative) = normalize_relative(path) else { continue };
+                let target = dir.join((index + 1).to_string()).join(relative);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                write_atomic(&target, content)?;
+            }
+        }
+        summary.push(CandidateSummary {
+            candidate: index + 1,
+            kept: index == kept,
+            verdict: format!("{:?}", candidate.result),
+            reason: candidate.reason.as_deref(),
+            rubric: candidate.rubric,
+            files: candidate.files.iter().map(|(path, _)| path.as_path()).collect(),
+        });
+    }
+
+    let json = serde_json::to_string_pretty(&summary).map_err(|e| WorkSplitError::IoError(e.to_string()))?;
+    write_atomic(&dir.join(SUMMARY_FILE), &json)?;
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn candidate(result: VerificationResult, average: Option<u8>, content: &str) -> Candidate {
+        Candidate {
+            files: vec![(PathBuf::from("src/lib.rs"), content.to_string())],
+            response: content.to_string(),
+            result,
+            reason: None,
+            rubric: average.map(|score| RubricScores { correctness: score, completeness: score, style: score, tests: None }),
+        }
+    }
+
+    #[test]
+    fn test_best_candidate() {
+        use VerificationResult::*;
+        assert_eq!(best_candidate(&[]), None);
+        assert_eq!(best_candidate(&[candidate(FailHard, None, "a"), candidate(PassWithWarnings, None, "b"),
+            candidate(FailSoft, None, "c")]), Some(1));
+        // The rubric breaks ties between equal verdicts, then the earliest wins
+        assert_eq!(best_candidate(&[candidate(Pass, Some(4), "a"), candidate(Pass, Some(5), "b"),
+            candidate(Pass, Some(5), "c")]), Some(1));
+        assert_eq!(best_candidate(&[candidate(Pass, None, "a"), candidate(Pass, None, "b")]), Some(0));
+    }
+
+    #[test]
+    fn test_save_candidates() {
+        let temp = TempDir::new().unwrap();
+        let stale = temp.path().join(CANDIDATES_DIR).join("job/3/src/old.rs");
+        fs::create_dir_all(stale.parent().unwrap()).unwrap();
+        fs::write(&stale, "old").unwrap();
+
+        let mut escaping = candidate(VerificationResult::FailSoft, None, "c");
+        escaping.files.push((PathBuf::from("../../outside.rs"), "x".to_string()));
+        let candidates = [candidate(VerificationResult::FailHard, None, "a"), candidate(VerificationResult::Pass, None, "b"), escaping];
+        let dir = save_candidates(temp.path(), "job", &candidates, 1).unwrap();
+
+        assert_eq!(fs::read_to_string(dir.join("1/src/lib.rs")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(dir.join("3/src/lib.rs")).unwrap(), "c");
+        assert!(!dir.join("2").exists());
+        assert!(!stale.exists());
+        assert!(!temp.path().join("outside.rs").exists());
+
+        let summary: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.join(SUMMARY_FILE)).unwrap()).unwrap();
+        assert_eq!(summary[1]["kept"], true);
+        assert_eq!(summary[0]["verdict"], "FailHard");
+        assert_eq!(summary[2]["files"][1], "../../outside.rs");
+    }
+}
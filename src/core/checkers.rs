@@ -0,0 +1,72 @@
+//! Compile checks of single generated files, keyed by extension (`[checkers]`)
+//!
+//! With build verification on, each generated file whose extension has a
+//! checker is checked on its own, so a repo mixing languages verifies every
+//! file with its own toolchain instead of relying on one `build_command`. A
+//! checker is either a built-in preset (`rustc`, `tsc`, `solc`, `py_compile`)
+//! or a shell command run from the project root, where `{path}` is the file.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Built-in checkers and their commands
+pub const PRESETS: [(&str, &str); 4] = [
+    ("rustc", "rustc --edition 2021 --crate-type lib --emit=metadata -o /dev/null {path}"),
+    ("tsc", "npx --no-install tsc --noEmit {path}"),
+    ("solc", "solc {path}"),
+    // py_compile without writing __pycache__
+    ("py_compile", "python3 -c \"import sys; compile(open(sys.argv[1]).read(), sys.argv[1], 'exec')\" {path}"),
+];
+
+/// The command for a checker: a preset's, or the checker itself
+pub fn checker_command(checker: &str) -> &str {
+    PRESETS.iter()
+        .find(|(name, _)| *name == checker)
+        .map_or(checker, |(_, command)| command)
+}
+
+/// A compile check of one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCheck {
+    /// The file, relative to the project root
+    pub path: PathBuf,
+    /// Shell command with the path filled in
+    pub command: String,
+}
+
+/// The checks for `files`, in order; files without a checker for their
+/// extension are skipped
+pub fn file_checks(checkers: &BTreeMap<String, String>, files: &[PathBuf]) -> Vec<FileCheck> {
+    files.iter()
+        .filter_map(|path| {
+            let checker = checkers.get(path.extension()?.to_string_lossy().as_ref())?;
+            Some(FileCheck { path: path.clone(), command: fill_path(checker_command(checker), path) })
+        })
+        .collect()
+}
+
+fn fill_path(command: &str, path: &Path) -> String {
+    command.replace("{path}", &path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_checks_by_extension() {
+        let checkers = BTreeMap::from([
+            ("py".to_string(), "py_compile".to_string()),
+            ("json".to_string(), "jq empty {path}".to_string()),
+        ]);
+        let files = vec![PathBuf::from("src/app.py"), PathBuf::from("src/lib.rs"), PathBuf::from("data/a.json"), PathBuf::from("Makefile")];
+        let checks = file_checks(&checkers, &files);
+
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].path, PathBuf::from("src/app.py"));
+        assert!(checks[0].command.starts_with("python3 -c "));
+        assert!(checks[0].command.ends_with(" src/app.py"));
+        assert_eq!(checks[1], FileCheck { path: "data/a.json".into(), command: "jq empty data/a.json".into() });
+        assert_eq!(checker_command("rustc"), PRESETS[0].1);
+    }
+}
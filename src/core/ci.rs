@@ -0,0 +1,164 @@
+//! GitHub Actions workflow commands for `worksplit run --ci`
+//!
+//! Each job's output is folded into a `::group::`, and every job that
+//! doesn't pass gets an `::error` (failed) or `::warning` (partial)
+//! annotation on its generated files, or on its job file when it produced
+//! none. Passed jobs get a `::notice` on each file they generated.
+
+use std::path::{Path, PathBuf};
+
+use crate::core::{JobResult, RunSummary};
+use crate::error::WorkSplitError;
+use crate::models::JobStatus;
+
+/// Exit code bit set when any job failed
+pub const EXIT_FAILED: i32 = 2;
+/// Exit code bit set when any job is partial
+pub const EXIT_PARTIAL: i32 = 4;
+
+/// Start a collapsible group in the log
+pub fn group(title: &str) -> String {
+    format!("::group::{}", escape_data(title))
+}
+
+/// End the group started last
+pub const END_GROUP: &str = "::endgroup::";
+
+/// One annotation; `file` is relative to the repository root
+pub fn annotation(level: &str, file: Option<&Path>, title: &str, message: &str) -> String {
+    let mut properties = Vec::new();
+    if let Some(file) = file {
+        properties.push(format!("file={}", escape_property(&file.display().to_string())));
+    }
+    properties.push(format!("title={}", escape_property(title)));
+    format!("::{} {}::{}", level, properties.join(","), escape_data(message))
+}
+
+/// Annotations for a finished job; `root` is the project root the job's
+/// paths are relative to
+pub fn job_annotations(root: &Path, job_id: &str, result: &Result<JobResult, WorkSplitError>) -> Vec<String> {
+    let (status, error, outputs) = match result {
+        Ok(r) => (r.status, r.error.clone(), r.output_paths.as_slice()),
+        Err(e) => (JobStatus::Fail, Some(e.to_string()), &[][..]),
+    };
+    let (level, title, message) = match status {
+        JobStatus::Pass => {
+            return outputs.iter()
+                .map(|path| annotation("notice", Some(&repo_path(root, path)), &format!("WorkSplit {}", job_id),
+                    &format!("Generated by job {}", job_id)))
+                .collect();
+        }
+        JobStatus::Partial => ("warning", format!("WorkSplit {} partial", job_id),
+            error.unwrap_or_else(|| "Some edits could not be applied".to_string())),
+        _ => ("error", format!("WorkSplit {} failed", job_id),
+            error.unwrap_or_else(|| "Job failed".to_string())),
+    };
+    let files: Vec<PathBuf> = if outputs.is_empty() {
+        vec![repo_path(root, &root.join("jobs").join(format!("{}.md", job_id)))]
+    } else {
+        outputs.iter().map(|path| repo_path(root, path)).collect()
+    };
+    files.iter().map(|file| annotation(level, Some(file), &title, &message)).collect()
+}
+
+/// Job counts of a run, or of several runs added up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunCounts {
+    pub passed: usize,
+    pub failed: usize,
+    pub partial: usize,
+    pub skipped: usize,
+}
+
+impl RunCounts {
+    pub fn of(summary: &RunSummary) -> Self {
+        Self {
+            passed: summary.passed,
+            failed: summary.failed,
+            partial: summary.results.iter().filter(|r| r.status == JobStatus::Partial).count(),
+            skipped: summary.skipped,
+        }
+    }
+
+    pub fn add(&mut self, other: RunCounts) {
+        self.passed += other.passed;
+        self.failed += other.failed;
+        self.partial += other.partial;
+        self.skipped += other.skipped;
+    }
+
+    /// Exit code: 0 when nothing failed or is partial, otherwise
+    /// [`EXIT_FAILED`] and/or [`EXIT_PARTIAL`] combined
+    pub fn exit_code(&self) -> i32 {
+        let mut code = 0;
+        if self.failed > 0 {
+            code |= EXIT_FAILED;
+        }
+        if self.partial > 0 {
+            code |= EXIT_PARTIAL;
+        }
+        code
+    }
+
+    /// `name=value` lines for `$GITHUB_OUTPUT`, so later steps can read the counts
+    pub fn step_outputs(&self) -> String {
+        format!("passed={}\nfailed={}\npartial={}\nskipped={}\n", self.passed, self.failed, self.partial, self.skipped)
+    }
+}
+
+/// `path` relative to the checkout (`$GITHUB_WORKSPACE`), or to the project
+/// root outside of Actions
+fn repo_path(root: &Path, path: &Path) -> PathBuf {
+    let workspace = std::env::var_os("GITHUB_WORKSPACE").map(PathBuf::from);
+    let relative = workspace.iter().map(PathBuf::as_path).chain([root])
+        .find_map(|base| path.strip_prefix(base).ok())
+        .unwrap_or(path);
+    relative.to_path_buf()
+}
+
+fn escape_data(value: &str) -> String {
+    value.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(job_id: &str, status: JobStatus, error: Option<&str>, outputs: Vec<PathBuf>) -> JobResult {
+        JobResult {
+            job_id: job_id.to_string(), status, error: error.map(String::from), output_paths: outputs,
+            output_lines: None, test_path: None, test_lines: None, retry_attempted: false,
+            implicit_context_files: Vec::new(), failure: None, request_retries: Vec::new(),
+            phase_timings: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_job_annotations_and_exit_code() {
+        let root = Path::new("/work/project");
+        let failed = Ok(result("001_api", JobStatus::Fail, Some("Build failed: a, b\n100%"), vec![root.join("src/api.rs")]));
+        assert_eq!(job_annotations(root, "001_api", &failed),
+            ["::error file=src/api.rs,title=WorkSplit 001_api failed::Build failed: a, b%0A100%25"]);
+
+        let errored = Err(WorkSplitError::JobError("boom".into()));
+        assert_eq!(job_annotations(root, "002_db", &errored),
+            ["::error file=jobs/002_db.md,title=WorkSplit 002_db failed::Job error: boom"]);
+
+        let passed = Ok(result("003_ui", JobStatus::Pass, None, vec![root.join("src/ui.rs")]));
+        assert_eq!(job_annotations(root, "003_ui", &passed),
+            ["::notice file=src/ui.rs,title=WorkSplit 003_ui::Generated by job 003_ui"]);
+
+        let mut summary = RunSummary { processed: 2, passed: 1, ..Default::default() };
+        assert_eq!(RunCounts::of(&summary).exit_code(), 0);
+        summary.results.push(result("004_cli", JobStatus::Partial, None, Vec::new()));
+        let mut counts = RunCounts::of(&summary);
+        assert_eq!(counts.exit_code(), EXIT_PARTIAL);
+        counts.add(RunCounts { failed: 1, ..Default::default() });
+        assert_eq!(counts.exit_code(), EXIT_FAILED | EXIT_PARTIAL);
+        assert_eq!(counts.step_outputs(), "passed=1\nfailed=1\npartial=1\nskipped=0\n");
+    }
+}
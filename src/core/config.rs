@@ -1,19 +1,32 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::info;
 
+use crate::core::offline;
 use crate::error::WorkSplitError;
 use crate::models::Config;
 
-/// Load configuration from project directory with CLI overrides
+/// Load configuration from project directory, applying the named profile
+/// and then the CLI overrides
 pub fn load_config(
-    project_root: &PathBuf,
+    project_root: &Path,
+    profile: Option<&str>,
     model: Option<String>,
     url: Option<String>,
     timeout: Option<u64>,
     no_stream: bool,
 ) -> Result<Config, WorkSplitError> {
-    let config = Config::load_from_dir(project_root)?;
-    let config = config.with_overrides(model, url, timeout, no_stream);
+    let mut config = Config::load_from_dir(project_root)?;
+    if let Some(name) = profile {
+        config = config.with_profile(name)?;
+        info!("Using profile '{}'", name);
+    }
+    let mut config = config.with_overrides(model, url, timeout, no_stream);
+    if offline::offline_from_env() {
+        config.safety.offline = true;
+    }
+    if config.safety.offline {
+        offline::check_config(&config)?;
+    }
 
     info!(
         "Configuration loaded: model={}, url={}, timeout={}s",
@@ -39,6 +52,7 @@ pub fn find_project_root() -> Result<PathBuf, WorkSplitError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::ConfigError;
     use tempfile::TempDir;
     use std::fs;
 
@@ -46,7 +60,8 @@ mod tests {
     fn test_load_config_default() {
         let temp_dir = TempDir::new().unwrap();
         let config = load_config(
-            &temp_dir.path().to_path_buf(),
+            temp_dir.path(),
+            None,
             None,
             None,
             None,
@@ -69,7 +84,8 @@ url = "http://custom:8080"
 "#).unwrap();
 
         let config = load_config(
-            &temp_dir.path().to_path_buf(),
+            temp_dir.path(),
+            None,
             None,
             None,
             None,
@@ -84,7 +100,8 @@ url = "http://custom:8080"
     fn test_load_config_with_overrides() {
         let temp_dir = TempDir::new().unwrap();
         let config = load_config(
-            &temp_dir.path().to_path_buf(),
+            temp_dir.path(),
+            None,
             Some("codellama".to_string()),
             Some("http://remote:11434".to_string()),
             Some(600),
@@ -96,4 +113,47 @@ url = "http://custom:8080"
         assert_eq!(config.ollama.timeout_seconds, 600);
         assert!(!config.behavior.stream_output);
     }
+
+    #[test]
+    fn test_load_config_with_profile() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("worksplit.toml"),
+            "[ollama]\nmodel = \"llama3\"\n\n[profile.fast]\nmodel = \"qwen2.5-coder:7b\"\ntimeout_seconds = 60\n",
+        ).unwrap();
+
+        let config = load_config(temp_dir.path(), Some("fast"), None, None, None, false).unwrap();
+        assert_eq!(config.ollama.model, "qwen2.5-coder:7b");
+        assert_eq!(config.ollama.timeout_seconds, 60);
+
+        // CLI overrides win over the profile
+        let config = load_config(temp_dir.path(), Some("fast"), Some("codellama".to_string()), None, None, false).unwrap();
+        assert_eq!(config.ollama.model, "codellama");
+
+        let result = load_config(temp_dir.path(), Some("slow"), None, None, None, false);
+        assert!(matches!(result, Err(WorkSplitError::Config(ConfigError::UnknownProfile { .. }))));
+    }
+
+    #[test]
+    fn test_load_config_offline_rejects_remote_ollama() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("worksplit.toml"),
+            "[ollama]\nurl = \"http://gpu-box:11434\"\n\n[safety]\noffline = true\n",
+        ).unwrap();
+
+        let result = load_config(temp_dir.path(), None, None, None, None, false);
+        assert!(matches!(result, Err(WorkSplitError::OfflineViolation { .. })));
+
+        // A CLI override back to localhost satisfies the check
+        let config = load_config(
+            temp_dir.path(),
+            None,
+            None,
+            Some("http://127.0.0.1:11434".to_string()),
+            None,
+            false,
+        ).unwrap();
+        assert!(config.safety.offline);
+    }
 }
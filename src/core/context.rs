@@ -0,0 +1,189 @@
+//! Assembling a job's context from its sources
+//!
+//! A file can reach the prompt more than once: listed in `context_files`,
+//! resolved from `context_symbols`, and modified earlier in the run. Entries
+//! are deduplicated by canonical path (the first one wins) and then put in
+//! the configured [`ContextOrder`]. When the model reports that a prompt
+//! doesn't fit its context window, [`JobContext::shrink`] cuts it down for
+//! one retry.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::output_paths::normalize_relative;
+use crate::core::truncate::summarize;
+use crate::models::ContextOrder;
+
+/// A job's context, by where it came from
+#[derive(Debug, Clone, Default)]
+pub struct JobContext {
+    /// `context_files` and files resolved from `context_symbols`
+    pub listed: Vec<(PathBuf, String)>,
+    /// Current content of output files that already exist
+    /// (`include_existing_output`)
+    pub existing_output: Vec<(PathBuf, String)>,
+    /// Files modified by earlier jobs in the run
+    pub implicit: Vec<(PathBuf, String)>,
+    /// Fetched `context_urls`
+    pub urls: Vec<(PathBuf, String)>,
+    /// Retrieved snippets, best match first
+    pub retrieved: Vec<(PathBuf, String)>,
+}
+
+/// Identity of a context entry: the canonical path when the file exists,
+/// otherwise the normalized path (URLs and snippet labels)
+pub fn context_key(project_root: &Path, path: &Path) -> PathBuf {
+    fs::canonicalize(project_root.join(path))
+        .ok()
+        .or_else(|| normalize_relative(path))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+impl JobContext {
+    /// All entries without duplicates, in `order`
+    pub fn assemble(self, project_root: &Path, order: ContextOrder) -> Vec<(PathBuf, String)> {
+        // Deduplicate in listed order, so the job's own entry is the one kept
+        let mut seen = HashSet::new();
+        let mut dedup = |files: Vec<(PathBuf, String)>| -> Vec<(PathBuf, String)> {
+            files
+                .into_iter()
+                .filter(|(path, _)| seen.insert(context_key(project_root, path)))
+                .collect()
+        };
+        let listed = dedup(self.listed);
+        let existing_output = dedup(self.existing_output);
+        let implicit = dedup(self.implicit);
+        let urls = dedup(self.urls);
+        let mut retrieved = dedup(self.retrieved);
+
+        match order {
+            ContextOrder::Listed => [listed, existing_output, implicit, urls, retrieved].concat(),
+            ContextOrder::MostRelevantLast => {
+                retrieved.reverse();
+                [retrieved, urls, implicit, existing_output, listed].concat()
+            }
+        }
+    }
+
+    /// Make the context smaller: drop what the job didn't ask for (files
+    /// modified earlier in the run, retrieved snippets) or, when there is
+    /// none, reduce the listed files to their signatures. Returns what was
+    /// done, or None when nothing could be cut.
+    pub fn shrink(&mut self) -> Option<String> {
+        let implicit = self.implicit.len() + self.retrieved.len();
+        if implicit > 0 {
+            self.implicit.clear();
+            self.retrieved.clear();
+            return Some(format!("dropped {} implicit context entries", implicit));
+        }
+        let mut summarized = 0;
+        for (path, content) in &mut self.listed {
+            let summary = summarize(content, path);
+            if summary.len() < content.len() {
+                *content = summary;
+                summarized += 1;
+            }
+        }
+        (summarized > 0).then(|| format!("summarized {} context file(s)", summarized))
+    }
+}
+
+/// Whether `a` and `b` name the same project file, ignoring `./` and
+/// `..` segments
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    a == b || normalize_relative(a).is_some_and(|a| Some(a) == normalize_relative(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn file(path: &str) -> (PathBuf, String) {
+        (PathBuf::from(path), format!("// {}", path))
+    }
+
+    fn paths(files: &[(PathBuf, String)]) -> Vec<String> {
+        files.iter().map(|(p, _)| p.display().to_string()).collect()
+    }
+
+    #[test]
+    fn test_assemble_deduplicates_by_canonical_path() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/a.rs"), "").unwrap();
+        fs::write(root.join("src/b.rs"), "").unwrap();
+
+        let context = JobContext {
+            listed: vec![file("src/a.rs"), file("./src/a.rs")],
+            existing_output: vec![file("src/a.rs"), file("src/out.rs")],
+            implicit: vec![(root.join("src/a.rs"), String::new()), (root.join("src/b.rs"), String::new())],
+            urls: vec![file("https://docs.example.com/api")],
+            retrieved: vec![file("src/c.rs (lines 1-40)")],
+        };
+        let files = context.assemble(root, ContextOrder::Listed);
+        assert_eq!(
+            paths(&files),
+            vec![
+                "src/a.rs".to_string(),
+                "src/out.rs".to_string(),
+                root.join("src/b.rs").display().to_string(),
+                "https://docs.example.com/api".to_string(),
+                "src/c.rs (lines 1-40)".to_string(),
+            ]
+        );
+        assert_eq!(files[0].1, "// src/a.rs");
+    }
+
+    #[test]
+    fn test_assemble_most_relevant_last() {
+        let temp = TempDir::new().unwrap();
+        let context = JobContext {
+            listed: vec![file("src/a.rs"), file("src/b.rs")],
+            existing_output: Vec::new(),
+            implicit: vec![file("src/gen.rs")],
+            urls: vec![file("https://docs.example.com/api")],
+            retrieved: vec![file("src/best.rs (lines 1-40)"), file("src/good.rs (lines 41-80)")],
+        };
+        let files = context.assemble(temp.path(), ContextOrder::MostRelevantLast);
+        assert_eq!(
+            paths(&files),
+            vec![
+                "src/good.rs (lines 41-80)",
+                "src/best.rs (lines 1-40)",
+                "https://docs.example.com/api",
+                "src/gen.rs",
+                "src/a.rs",
+                "src/b.rs",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shrink_drops_implicit_context_first() {
+        let listed = (PathBuf::from("src/lib.rs"), "pub fn add(a: i32, b: i32) -> i32 {\n    let sum = a + b;\n    sum\n}".to_string());
+        let mut context = JobContext {
+            listed: vec![listed.clone()],
+            implicit: vec![file("src/gen.rs")],
+            retrieved: vec![file("snippet")],
+            ..Default::default()
+        };
+
+        assert_eq!(context.shrink().as_deref(), Some("dropped 2 implicit context entries"));
+        assert!(context.implicit.is_empty() && context.retrieved.is_empty());
+        assert_eq!(context.listed, vec![listed]);
+
+        assert_eq!(context.shrink().as_deref(), Some("summarized 1 context file(s)"));
+        assert!(context.listed[0].1.starts_with("pub fn add(a: i32, b: i32) -> i32 {"));
+        assert!(!context.listed[0].1.contains("let sum"), "{}", context.listed[0].1);
+        assert_eq!(context.shrink(), None);
+    }
+
+    #[test]
+    fn test_same_file() {
+        assert!(same_file(Path::new("src/a.rs"), Path::new("./src/lib/../a.rs")));
+        assert!(!same_file(Path::new("src/a.rs"), Path::new("src/b.rs")));
+    }
+}
@@ -1,49 +1,114 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeSet, HashMap};
+use crate::core::output_paths::{depends_on, normalize_relative, touched_files};
 use crate::models::job::Job;
 use crate::error::WorkSplitError;
 
 /// Topological sort of jobs based on depends_on
+///
+/// Jobs that touch the same file are also ordered by ID, unless `depends_on`
+/// already orders them the other way, so one can't clobber the other's work
+/// mid-run. Otherwise independent jobs come out in ID order.
 pub fn order_by_dependencies(jobs: &[Job]) -> Result<Vec<&Job>, WorkSplitError> {
-    let mut graph: HashMap<&str, Vec<&str>> = HashMap::new();
-    let mut in_degree: HashMap<&str, usize> = HashMap::new();
-    
-    // Build graph
+    let mut deps: HashMap<&str, Vec<&str>> = HashMap::new();
     for job in jobs {
-        in_degree.entry(&job.id).or_insert(0);
-        if let Some(deps) = &job.metadata.depends_on {
-            for dep in deps {
-                graph.entry(dep.as_str()).or_default().push(&job.id);
-                *in_degree.entry(&job.id).or_insert(0) += 1;
+        let list = deps.entry(&job.id).or_default();
+        for dep in job.metadata.depends_on.iter().flatten() {
+            if jobs.iter().any(|j| &j.id == dep) {
+                list.push(dep.as_str());
             }
         }
     }
-    
-    // Kahn's algorithm
-    let mut queue: VecDeque<&str> = in_degree
+
+    // Implicit ordering between jobs sharing a file
+    let mut sorted: Vec<&Job> = jobs.iter().collect();
+    sorted.sort_by(|a, b| a.id.cmp(&b.id));
+    let files: Vec<Vec<_>> = sorted
+        .iter()
+        .map(|job| touched_files(job).iter().filter_map(|p| normalize_relative(p)).collect())
+        .collect();
+    for (i, earlier) in sorted.iter().enumerate() {
+        for (j, later) in sorted.iter().enumerate().skip(i + 1) {
+            if files[i].iter().any(|f| files[j].contains(f))
+                && !depends_on(&earlier.id, &later.id, &deps)
+                && !depends_on(&later.id, &earlier.id, &deps)
+            {
+                deps.entry(&later.id).or_default().push(&earlier.id);
+            }
+        }
+    }
+
+    // Kahn's algorithm, taking the lowest ready ID first
+    let mut in_degree: HashMap<&str, usize> = deps.iter().map(|(&id, list)| (id, list.len())).collect();
+    let mut ready: BTreeSet<&str> = in_degree
         .iter()
         .filter(|(_, &deg)| deg == 0)
         .map(|(&id, _)| id)
         .collect();
-    
+
     let mut result = Vec::new();
-    while let Some(id) = queue.pop_front() {
+    while let Some(id) = ready.pop_first() {
         if let Some(job) = jobs.iter().find(|j| j.id == id) {
             result.push(job);
         }
-        if let Some(neighbors) = graph.get(id) {
-            for &neighbor in neighbors {
-                let deg = in_degree.get_mut(neighbor).unwrap();
+        for (&dependent, list) in &deps {
+            for _ in list.iter().filter(|&&dep| dep == id) {
+                let deg = in_degree.get_mut(dependent).unwrap();
                 *deg -= 1;
                 if *deg == 0 {
-                    queue.push_back(neighbor);
+                    ready.insert(dependent);
                 }
             }
         }
     }
-    
+
     if result.len() != jobs.len() {
         return Err(WorkSplitError::CyclicDependency);
     }
-    
+
     Ok(result)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::JobMetadata;
+    use std::path::PathBuf;
+
+    fn job(id: &str, yaml: &str) -> Job {
+        let metadata: JobMetadata = serde_yaml::from_str(yaml).unwrap();
+        Job::new(id.to_string(), metadata, String::new(), PathBuf::from(format!("jobs/{}.md", id)))
+    }
+
+    fn ids(jobs: &[Job]) -> Vec<&str> {
+        order_by_dependencies(jobs).unwrap().iter().map(|j| j.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_order_by_dependencies() {
+        let jobs = vec![
+            job("003_c", "output_dir: src/\noutput_file: c.rs"),
+            job("001_a", "depends_on: [002_b]\noutput_dir: src/\noutput_file: a.rs"),
+            job("002_b", "output_dir: src/\noutput_file: b.rs"),
+        ];
+        assert_eq!(ids(&jobs), vec!["002_b", "001_a", "003_c"]);
+
+        let cyclic = vec![
+            job("001_a", "depends_on: [002_b]\noutput_dir: src/\noutput_file: a.rs"),
+            job("002_b", "depends_on: [001_a]\noutput_dir: src/\noutput_file: b.rs"),
+        ];
+        assert!(matches!(order_by_dependencies(&cyclic), Err(WorkSplitError::CyclicDependency)));
+    }
+
+    #[test]
+    fn test_jobs_sharing_a_file_are_ordered() {
+        // 004 rewrites the file 002 edits, so it waits for 002; 001 still
+        // follows its explicit dependency on 004
+        let jobs = vec![
+            job("001_a", "depends_on: [004_write]\noutput_dir: src/\noutput_file: a.rs"),
+            job("002_edit", "mode: edit\ntarget_files: [src/lib.rs]\noutput_dir: src/\noutput_file: lib.rs"),
+            job("003_other", "output_dir: src/\noutput_file: other.rs"),
+            job("004_write", "output_dir: src/\noutput_file: lib.rs"),
+        ];
+        assert_eq!(ids(&jobs), vec!["002_edit", "003_other", "004_write", "001_a"]);
+    }
+}
@@ -0,0 +1,298 @@
+//! Embeddings-based context retrieval (`jobs/_embeddings/`)
+//!
+//! Source files are split into fixed-size line chunks and embedded with
+//! Ollama's `/api/embeddings`. A job's instructions are embedded the same
+//! way, and the most similar chunks are added to its context. Like the
+//! symbol index, the vector index is refreshed incrementally: only files
+//! whose modification time changed are re-embedded.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tracing::{debug, info};
+
+use crate::core::atomic_write::write_atomic;
+use crate::core::ollama::OllamaClient;
+use crate::core::symbols::is_indexed;
+use crate::core::targets::walk_files;
+use crate::error::WorkSplitError;
+use crate::models::RetrievalConfig;
+
+/// Folder inside jobs/ holding the vector index
+pub const EMBEDDINGS_DIR: &str = "_embeddings";
+
+/// Index file name inside the embeddings folder
+const INDEX_FILE: &str = "index.json";
+
+/// An embedded range of lines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chunk {
+    /// 1-based first line
+    pub start_line: usize,
+    /// 1-based last line (inclusive)
+    pub end_line: usize,
+    pub vector: Vec<f32>,
+}
+
+/// Chunks of one file, with the modification time they were embedded at
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileEmbeddings {
+    /// Modification time in milliseconds since the Unix epoch
+    pub modified_ms: u64,
+    pub chunks: Vec<Chunk>,
+}
+
+/// Embedded chunks of every source file, keyed by relative path
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingIndex {
+    /// Embedding model the vectors came from
+    pub model: String,
+    /// Lines per chunk the index was built with
+    pub chunk_lines: usize,
+    pub files: BTreeMap<PathBuf, FileEmbeddings>,
+}
+
+/// A retrieved piece of a project file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snippet {
+    pub path: PathBuf,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub similarity: f32,
+    pub content: String,
+}
+
+impl Snippet {
+    /// Name the snippet is shown under in the prompt
+    pub fn label(&self) -> PathBuf {
+        PathBuf::from(format!("{} (lines {}-{})", self.path.display(), self.start_line, self.end_line))
+    }
+}
+
+/// Split `content` into ranges of `chunk_lines` lines (1-based, inclusive),
+/// leaving out ranges that are only whitespace
+pub fn chunk_ranges(content: &str, chunk_lines: usize) -> Vec<(usize, usize)> {
+    let lines: Vec<&str> = content.lines().collect();
+    lines
+        .chunks(chunk_lines.max(1))
+        .enumerate()
+        .filter(|(_, chunk)| chunk.iter().any(|line| !line.trim().is_empty()))
+        .map(|(i, chunk)| {
+            let start = i * chunk_lines.max(1) + 1;
+            (start, start + chunk.len() - 1)
+        })
+        .collect()
+}
+
+/// Lines `start..=end` (1-based) of `content`
+fn line_range(content: &str, start: usize, end: usize) -> String {
+    content
+        .lines()
+        .skip(start - 1)
+        .take(end + 1 - start)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Cosine similarity of two vectors; 0 when either is zero or the lengths differ
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+fn modified_ms(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as u64)
+}
+
+impl EmbeddingIndex {
+    /// Load `jobs/_embeddings/index.json`; a missing or unreadable index starts empty
+    pub fn load(jobs_dir: &Path) -> Self {
+        fs::read_to_string(jobs_dir.join(EMBEDDINGS_DIR).join(INDEX_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, jobs_dir: &Path) -> Result<(), WorkSplitError> {
+        let dir = jobs_dir.join(EMBEDDINGS_DIR);
+        fs::create_dir_all(&dir)?;
+        let content = serde_json::to_string(self).map_err(|e| WorkSplitError::IoError(e.to_string()))?;
+        write_atomic(&dir.join(INDEX_FILE), content)?;
+        Ok(())
+    }
+
+    /// Bring the index up to date with the project, embedding new and
+    /// changed files. Switching model or chunk size rebuilds it. Returns
+    /// whether anything changed.
+    pub async fn refresh(
+        &mut self,
+        project_root: &Path,
+        ollama: &OllamaClient,
+        config: &RetrievalConfig,
+    ) -> Result<bool, WorkSplitError> {
+        let mut changed = false;
+        if self.model != config.model || self.chunk_lines != config.chunk_lines {
+            self.model = config.model.clone();
+            self.chunk_lines = config.chunk_lines;
+            self.files.clear();
+            changed = true;
+        }
+
+        let mut found = Vec::new();
+        walk_files(project_root, project_root, &mut found)?;
+        found.retain(|path| is_indexed(path));
+
+        let present: HashSet<&PathBuf> = found.iter().collect();
+        let before = self.files.len();
+        self.files.retain(|path, _| present.contains(path));
+        changed |= self.files.len() != before;
+
+        for path in found {
+            let full_path = project_root.join(&path);
+            let Some(modified) = modified_ms(&full_path) else { continue };
+            if self.files.get(&path).is_some_and(|entry| entry.modified_ms == modified) {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(&full_path) else { continue };
+            debug!("Embedding {}", path.display());
+            let mut chunks = Vec::new();
+            for (start_line, end_line) in chunk_ranges(&content, config.chunk_lines) {
+                let text = format!("{}\n{}", path.display(), line_range(&content, start_line, end_line));
+                let vector = ollama.embed(&config.model, &text).await?;
+                chunks.push(Chunk { start_line, end_line, vector });
+            }
+            self.files.insert(path, FileEmbeddings { modified_ms: modified, chunks });
+            changed = true;
+        }
+        Ok(changed)
+    }
+
+    /// The `top_k` chunks most similar to `query`, best first, skipping
+    /// files in `exclude` and chunks below `min_similarity`
+    pub fn search(
+        &self,
+        query: &[f32],
+        top_k: usize,
+        min_similarity: f32,
+        exclude: &[PathBuf],
+    ) -> Vec<(&PathBuf, &Chunk, f32)> {
+        let mut scored: Vec<(&PathBuf, &Chunk, f32)> = self
+            .files
+            .iter()
+            .filter(|(path, _)| !exclude.contains(path))
+            .flat_map(|(path, entry)| entry.chunks.iter().map(move |chunk| (path, chunk)))
+            .map(|(path, chunk)| (path, chunk, cosine_similarity(query, &chunk.vector)))
+            .filter(|(_, _, similarity)| *similarity >= min_similarity)
+            .collect();
+        scored.sort_by(|a, b| b.2.total_cmp(&a.2));
+        scored.truncate(top_k);
+        scored
+    }
+}
+
+/// Refresh the index and return the snippets most relevant to `query`,
+/// leaving out the files in `exclude` (relative to the project root)
+pub async fn retrieve_context(
+    project_root: &Path,
+    jobs_dir: &Path,
+    ollama: &OllamaClient,
+    config: &RetrievalConfig,
+    query: &str,
+    exclude: &[PathBuf],
+) -> Result<Vec<Snippet>, WorkSplitError> {
+    let mut index = EmbeddingIndex::load(jobs_dir);
+    if index.refresh(project_root, ollama, config).await? {
+        info!("Embedding index updated ({} files)", index.files.len());
+        index.save(jobs_dir)?;
+    }
+    if index.files.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let query_vector = ollama.embed(&config.model, query).await?;
+    let mut snippets = Vec::new();
+    for (path, chunk, similarity) in index.search(&query_vector, config.top_k, config.min_similarity, exclude) {
+        let Ok(content) = fs::read_to_string(project_root.join(path)) else { continue };
+        snippets.push(Snippet {
+            path: path.clone(),
+            start_line: chunk.start_line,
+            end_line: chunk.end_line,
+            similarity,
+            content: line_range(&content, chunk.start_line, chunk.end_line),
+        });
+    }
+    Ok(snippets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(start_line: usize, end_line: usize, vector: Vec<f32>) -> Chunk {
+        Chunk { start_line, end_line, vector }
+    }
+
+    #[test]
+    fn test_chunk_ranges() {
+        let content = "a\nb\nc\n\n\n\ng\n";
+        assert_eq!(chunk_ranges(content, 3), vec![(1, 3), (7, 7)]);
+        assert_eq!(chunk_ranges(content, 10), vec![(1, 7)]);
+        assert!(chunk_ranges("", 3).is_empty());
+        assert_eq!(line_range(content, 2, 3), "b\nc");
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_search_ranks_and_filters_chunks() {
+        let mut index = EmbeddingIndex { model: "m".to_string(), chunk_lines: 40, files: BTreeMap::new() };
+        index.files.insert(
+            PathBuf::from("src/billing.rs"),
+            FileEmbeddings { modified_ms: 1, chunks: vec![chunk(1, 40, vec![1.0, 0.1]), chunk(41, 60, vec![0.0, 1.0])] },
+        );
+        index.files.insert(
+            PathBuf::from("src/invoice.rs"),
+            FileEmbeddings { modified_ms: 1, chunks: vec![chunk(1, 12, vec![1.0, 0.5])] },
+        );
+
+        let found: Vec<(String, usize)> = index
+            .search(&[1.0, 0.0], 5, 0.3, &[])
+            .iter()
+            .map(|(path, chunk, _)| (path.display().to_string(), chunk.start_line))
+            .collect();
+        assert_eq!(found, vec![("src/billing.rs".to_string(), 1), ("src/invoice.rs".to_string(), 1)]);
+
+        let excluded = index.search(&[1.0, 0.0], 1, 0.0, &[PathBuf::from("src/billing.rs")]);
+        assert_eq!(excluded.len(), 1);
+        assert_eq!(excluded[0].0, &PathBuf::from("src/invoice.rs"));
+    }
+
+    #[test]
+    fn test_snippet_label() {
+        let snippet = Snippet {
+            path: PathBuf::from("src/billing.rs"),
+            start_line: 41,
+            end_line: 80,
+            similarity: 0.8,
+            content: String::new(),
+        };
+        assert_eq!(snippet.label(), PathBuf::from("src/billing.rs (lines 41-80)"));
+    }
+}
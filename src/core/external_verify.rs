@@ -0,0 +1,276 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{debug, info};
+
+use crate::core::VerificationResult;
+use crate::error::WorkSplitError;
+use crate::models::ExternalVerifyConfig;
+
+/// Client for an external verification service (e.g. an internal review bot)
+///
+/// Generated files are POSTed as JSON and the service answers with a verdict,
+/// which is merged with the local LLM verification result.
+pub struct ExternalVerifier {
+    client: Client,
+    config: ExternalVerifyConfig,
+    url: String,
+}
+
+/// A generated file sent to the service
+#[derive(Debug, Serialize)]
+struct ExternalFile<'a> {
+    path: String,
+    content: &'a str,
+}
+
+/// Request body sent to the external service
+#[derive(Debug, Serialize)]
+struct ExternalVerifyRequest<'a> {
+    job_id: &'a str,
+    instructions: &'a str,
+    files: Vec<ExternalFile<'a>>,
+    /// Local verification result ("pass", "pass_with_warnings", "fail_soft", "fail_hard")
+    local_result: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    local_error: Option<&'a str>,
+}
+
+/// Verdict returned by the external service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalVerdictKind {
+    Pass,
+    PassWithWarnings,
+    Fail,
+}
+
+/// Response body from the external service
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExternalVerdict {
+    pub verdict: ExternalVerdictKind,
+    /// Individual findings (review comments, rule violations, ...)
+    #[serde(default)]
+    pub findings: Vec<String>,
+}
+
+impl ExternalVerifier {
+    /// Create a verifier, or `None` if no service URL is configured
+    pub fn from_config(config: &ExternalVerifyConfig) -> Result<Option<Self>, WorkSplitError> {
+        let Some(url) = config.url.clone() else {
+            return Ok(None);
+        };
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .map_err(|e| WorkSplitError::ExternalVerification(e.to_string()))?;
+
+        Ok(Some(Self { client, config: config.clone(), url }))
+    }
+
+    /// Whether a failure to reach the service should fail the job
+    pub fn is_required(&self) -> bool {
+        self.config.required
+    }
+
+    /// Send generated files to the service and wait for its verdict
+    pub async fn verify(
+        &self,
+        job_id: &str,
+        instructions: &str,
+        generated_files: &[(PathBuf, String)],
+        local: (VerificationResult, Option<&str>),
+    ) -> Result<ExternalVerdict, WorkSplitError> {
+        let request = ExternalVerifyRequest {
+            job_id,
+            instructions,
+            files: generated_files
+                .iter()
+                .map(|(path, content)| ExternalFile { path: path.display().to_string(), content })
+                .collect(),
+            local_result: verification_label(local.0),
+            local_error: local.1,
+        };
+
+        info!("Sending {} file(s) to external verification: {}", generated_files.len(), self.url);
+
+        let mut builder = self.client.post(&self.url).json(&request);
+        if let Some(var) = &self.config.token_env {
+            match std::env::var(var) {
+                Ok(token) => builder = builder.bearer_auth(token),
+                Err(_) => debug!("External verification token variable {} is not set", var),
+            }
+        }
+
+        let response = builder.send().await.map_err(|e| {
+            if e.is_timeout() {
+                WorkSplitError::ExternalVerification(format!(
+                    "No verdict from {} within {}s",
+                    self.url, self.config.timeout_seconds
+                ))
+            } else {
+                WorkSplitError::ExternalVerification(format!("Request to {} failed: {}", self.url, e))
+            }
+        })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(WorkSplitError::ExternalVerification(format!(
+                "{} returned HTTP {}: {}",
+                self.url,
+                status.as_u16(),
+                body
+            )));
+        }
+
+        let verdict: ExternalVerdict = response
+            .json()
+            .await
+            .map_err(|e| WorkSplitError::ExternalVerification(format!("Invalid verdict: {}", e)))?;
+
+        info!("External verification verdict: {:?} ({} finding(s))", verdict.verdict, verdict.findings.len());
+        Ok(verdict)
+    }
+}
+
+fn verification_label(result: VerificationResult) -> &'static str {
+    match result {
+        VerificationResult::Pass => "pass",
+        VerificationResult::PassWithWarnings => "pass_with_warnings",
+        VerificationResult::FailSoft => "fail_soft",
+        VerificationResult::FailHard => "fail_hard",
+    }
+}
+
+/// Merge the local verification result with the external verdict.
+///
+/// The stricter of the two wins. External findings are appended to the
+/// failure reason so they reach the retry prompt.
+pub fn merge_verification(
+    local: (VerificationResult, Option<String>),
+    external: &ExternalVerdict,
+) -> (VerificationResult, Option<String>) {
+    let (local_result, local_error) = local;
+
+    match external.verdict {
+        ExternalVerdictKind::Pass => (local_result, local_error),
+        ExternalVerdictKind::PassWithWarnings => {
+            let result = if local_result == VerificationResult::Pass {
+                VerificationResult::PassWithWarnings
+            } else {
+                local_result
+            };
+            (result, local_error)
+        }
+        ExternalVerdictKind::Fail => {
+            let result = if local_result == VerificationResult::FailHard {
+                VerificationResult::FailHard
+            } else {
+                VerificationResult::FailSoft
+            };
+            let external_reason = if external.findings.is_empty() {
+                "External review failed".to_string()
+            } else {
+                format!("External review: {}", external.findings.join("; "))
+            };
+            let error = match local_error.filter(|_| !local_result.is_pass()) {
+                Some(e) => format!("{}\n{}", e, external_reason),
+                None => external_reason,
+            };
+            (result, Some(error))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::post;
+    use axum::{Json, Router};
+
+    fn verdict(kind: ExternalVerdictKind, findings: &[&str]) -> ExternalVerdict {
+        ExternalVerdict {
+            verdict: kind,
+            findings: findings.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_merge_external_pass_keeps_local() {
+        let (result, error) = merge_verification(
+            (VerificationResult::FailSoft, Some("missing fn".to_string())),
+            &verdict(ExternalVerdictKind::Pass, &[]),
+        );
+        assert_eq!(result, VerificationResult::FailSoft);
+        assert_eq!(error.as_deref(), Some("missing fn"));
+    }
+
+    #[test]
+    fn test_merge_external_warnings_downgrade_pass() {
+        let (result, error) = merge_verification(
+            (VerificationResult::Pass, None),
+            &verdict(ExternalVerdictKind::PassWithWarnings, &["style nit"]),
+        );
+        assert_eq!(result, VerificationResult::PassWithWarnings);
+        assert!(error.is_none());
+    }
+
+    #[test]
+    fn test_merge_external_fail_overrides_local_pass() {
+        let (result, error) = merge_verification(
+            (VerificationResult::Pass, None),
+            &verdict(ExternalVerdictKind::Fail, &["uses unwrap", "no tests"]),
+        );
+        assert_eq!(result, VerificationResult::FailSoft);
+        assert_eq!(error.as_deref(), Some("External review: uses unwrap; no tests"));
+    }
+
+    #[test]
+    fn test_merge_external_fail_combines_with_local_failure() {
+        let (result, error) = merge_verification(
+            (VerificationResult::FailHard, Some("does not compile".to_string())),
+            &verdict(ExternalVerdictKind::Fail, &[]),
+        );
+        assert_eq!(result, VerificationResult::FailHard);
+        assert_eq!(error.as_deref(), Some("does not compile\nExternal review failed"));
+    }
+
+    #[test]
+    fn test_from_config_disabled_without_url() {
+        let verifier = ExternalVerifier::from_config(&ExternalVerifyConfig::default()).unwrap();
+        assert!(verifier.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_verify_against_service() {
+        let app = Router::new().route(
+            "/verify",
+            post(|Json(body): Json<serde_json::Value>| async move {
+                assert_eq!(body["job_id"], "001_test");
+                assert_eq!(body["files"][0]["path"], "src/lib.rs");
+                assert_eq!(body["local_result"], "pass");
+                Json(serde_json::json!({ "verdict": "fail", "findings": ["no docs"] }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let config = ExternalVerifyConfig {
+            url: Some(format!("http://{}/verify", addr)),
+            ..Default::default()
+        };
+        let verifier = ExternalVerifier::from_config(&config).unwrap().unwrap();
+        let files = vec![(PathBuf::from("src/lib.rs"), "pub fn a() {}".to_string())];
+
+        let verdict = verifier
+            .verify("001_test", "Add a", &files, (VerificationResult::Pass, None))
+            .await
+            .unwrap();
+        assert_eq!(verdict.verdict, ExternalVerdictKind::Fail);
+        assert_eq!(verdict.findings, vec!["no docs".to_string()]);
+    }
+}
@@ -0,0 +1,51 @@
+//! Raw model responses of failed jobs (`jobs/_failures/<job>/attempt-N.md`)
+//!
+//! When nothing could be extracted from a response or verification failed
+//! outright, the responses of the job's last run are kept here, so the
+//! evidence survives without streaming or scrollback. Each failure replaces
+//! the job's previous artifacts.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::WorkSplitError;
+
+/// Folder inside the jobs folder
+pub const FAILURES_DIR: &str = "_failures";
+
+/// Folder holding the artifacts of `job_id`
+pub fn failure_dir(jobs_dir: &Path, job_id: &str) -> PathBuf {
+    jobs_dir.join(FAILURES_DIR).join(job_id)
+}
+
+/// Write each response, in the order received, to `attempt-N.md` in the
+/// job's folder, replacing earlier artifacts; returns the folder
+pub fn save_responses(jobs_dir: &Path, job_id: &str, responses: &[String]) -> Result<PathBuf, WorkSplitError> {
+    let dir = failure_dir(jobs_dir, job_id);
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+    for (index, response) in responses.iter().enumerate() {
+        fs::write(dir.join(format!("attempt-{}.md", index + 1)), response)?;
+    }
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_responses_replaces_earlier_artifacts() {
+        let temp = TempDir::new().unwrap();
+        let first = save_responses(temp.path(), "001_a", &["one".into(), "two".into(), "three".into()]).unwrap();
+        assert_eq!(first, temp.path().join("_failures/001_a"));
+        assert_eq!(fs::read_to_string(first.join("attempt-2.md")).unwrap(), "two");
+
+        let second = save_responses(temp.path(), "001_a", &["again".into()]).unwrap();
+        assert_eq!(fs::read_to_string(second.join("attempt-1.md")).unwrap(), "again");
+        assert!(!second.join("attempt-2.md").exists());
+    }
+}
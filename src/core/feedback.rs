@@ -0,0 +1,164 @@
+//! Sorting verification feedback into issue categories
+//!
+//! A failed verification's reason is split into issues (one per line,
+//! bullet or `;`-separated part), and each issue gets a [`FeedbackCategory`] from the words it
+//! uses. When every issue is ignored, by `[verification] ignore_feedback`,
+//! `ignore_feedback_patterns` or because it asks for tests a job doesn't
+//! write, regenerating can't address anything the job asked for: the
+//! runner skips the retry and passes the job with warnings. Otherwise the
+//! retry only gets the issues that weren't ignored.
+
+use regex::Regex;
+
+use crate::error::WorkSplitError;
+use crate::models::{FeedbackCategory, Job, VerificationConfig};
+
+/// Words that put an issue in a category, checked in order
+const CATEGORY_WORDS: [(FeedbackCategory, &str); 5] = [
+    (FeedbackCategory::MissingTests, r"\b(tests?|testing|test cases?|coverage)\b"),
+    (FeedbackCategory::Documentation, r"\b(docs?|doc comments?|docstrings?|documentation|documented|comments?)\b"),
+    (FeedbackCategory::WrongSignature, r"\b(signatures?|parameters?|arguments?|return type|misspelled|renamed|should be (named|called)|must be (named|called)|wrong name)\b"),
+    (FeedbackCategory::MissingFunction, r"\b(missing|not implemented|unimplemented|stubs?|todo|not defined|does not exist|doesn't exist|absent)\b"),
+    (FeedbackCategory::Style, r"\b(style|styling|naming|formatting|formatted|indentation|whitespace|idiomatic|nitpicks?|readability|readable|consider)\b"),
+];
+
+/// One complaint of the verifier
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedbackIssue {
+    pub category: FeedbackCategory,
+    pub text: String,
+}
+
+/// Split `feedback` into issues, one per line or `;`-separated part, with
+/// list markers removed; headings such as `Issues:` are dropped
+pub fn parse_feedback(feedback: &str) -> Vec<FeedbackIssue> {
+    let marker = Regex::new(r"^\s*(?:[-*•]|\d+[.)])\s*").unwrap();
+    let rules: Vec<(FeedbackCategory, Regex)> = CATEGORY_WORDS.iter()
+        .map(|(category, words)| (*category, Regex::new(&format!("(?i){}", words)).unwrap()))
+        .collect();
+    feedback.lines()
+        .flat_map(|line| line.split(';'))
+        .map(|part| marker.replace(part, "").trim().to_string())
+        .filter(|text| !text.is_empty() && !is_heading(text))
+        .map(|text| {
+            let category = rules.iter()
+                .find(|(_, words)| words.is_match(&text))
+                .map_or(FeedbackCategory::Other, |(category, _)| *category);
+            FeedbackIssue { category, text }
+        })
+        .collect()
+}
+
+/// A short line introducing a list, such as `Issues:`
+fn is_heading(text: &str) -> bool {
+    text.ends_with(':') && text.split_whitespace().count() <= 3
+}
+
+/// Which feedback issues don't warrant a retry (`[verification]`)
+#[derive(Debug, Clone)]
+pub struct FeedbackRules {
+    ignore: Vec<FeedbackCategory>,
+    patterns: Vec<Regex>,
+    tests_out_of_scope: bool,
+}
+
+impl FeedbackRules {
+    pub fn from_config(config: &VerificationConfig) -> Result<Self, WorkSplitError> {
+        let patterns = config.ignore_feedback_patterns.iter()
+            .map(|pattern| Regex::new(pattern).map_err(|e| WorkSplitError::ConfigError(format!(
+                "Invalid [verification] ignore_feedback_patterns entry '{}': {}", pattern, e))))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            ignore: config.ignore_feedback.clone(),
+            patterns,
+            tests_out_of_scope: config.tests_out_of_scope,
+        })
+    }
+
+    /// Whether `issue` is outside what `job` asked for or ignored by the
+    /// project
+    pub fn is_ignored(&self, issue: &FeedbackIssue, job: &Job) -> bool {
+        self.ignore.contains(&issue.category)
+            || self.patterns.iter().any(|pattern| pattern.is_match(&issue.text))
+            || (self.tests_out_of_scope && issue.category == FeedbackCategory::MissingTests
+                && !job.metadata.is_tdd_enabled() && job.metadata.test_scope.is_none())
+    }
+
+    /// Split `feedback` into the issues a retry should address and the
+    /// ignored ones
+    pub fn sort(&self, feedback: &str, job: &Job) -> SortedFeedback {
+        let (ignored, actionable) = parse_feedback(feedback).into_iter()
+            .partition(|issue| self.is_ignored(issue, job));
+        SortedFeedback { actionable, ignored }
+    }
+}
+
+/// Verification feedback split by [`FeedbackRules`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SortedFeedback {
+    pub actionable: Vec<FeedbackIssue>,
+    pub ignored: Vec<FeedbackIssue>,
+}
+
+impl SortedFeedback {
+    /// Every issue is ignored, so a retry can't improve the job
+    pub fn all_ignored(&self) -> bool {
+        self.actionable.is_empty() && !self.ignored.is_empty()
+    }
+
+    /// The feedback to send with a retry: only the actionable issues once
+    /// some were ignored, `feedback` itself otherwise
+    pub fn retry_feedback(&self, feedback: &str) -> String {
+        if self.ignored.is_empty() {
+            return feedback.to_string();
+        }
+        self.actionable.iter().map(|issue| format!("- {}", issue.text)).collect::<Vec<_>>().join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::JobMetadata;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_feedback_categories() {
+        let issues = parse_feedback("Issues:\n- add is missing\n- `sub` takes two parameters, not one\n2. No unit tests\n* Consider shorter variable names\n\nMissing doc comment on add; The sum is wrong");
+        let categories: Vec<FeedbackCategory> = issues.iter().map(|i| i.category).collect();
+        assert_eq!(categories, [
+            FeedbackCategory::MissingFunction,
+            FeedbackCategory::WrongSignature,
+            FeedbackCategory::MissingTests,
+            FeedbackCategory::Style,
+            FeedbackCategory::Documentation,
+            FeedbackCategory::Other,
+        ]);
+        assert_eq!(issues[1].text, "`sub` takes two parameters, not one");
+    }
+
+    #[test]
+    fn test_feedback_rules_ignore_out_of_scope_issues() {
+        let config = VerificationConfig {
+            ignore_feedback: vec![FeedbackCategory::Style],
+            ignore_feedback_patterns: vec!["(?i)error handling".into()],
+            ..Default::default()
+        };
+        let rules = FeedbackRules::from_config(&config).unwrap();
+        let metadata: JobMetadata = serde_yaml::from_str("output_dir: src/\noutput_file: add.rs\n").unwrap();
+        let mut job = Job::new("001_add".into(), metadata, String::new(), PathBuf::from("jobs/001_add.md"));
+
+        assert!(rules.sort("- no tests\n- use snake_case naming\n- Add error handling", &job).all_ignored());
+        let sorted = rules.sort("- no tests\n- add is missing", &job);
+        assert!(!sorted.all_ignored());
+        assert_eq!(sorted.retry_feedback("- no tests\n- add is missing"), "- add is missing");
+        assert_eq!(rules.sort("add is wrong", &job).retry_feedback("add is wrong"), "add is wrong");
+        assert!(!rules.sort("  \n", &job).all_ignored());
+
+        job.metadata.test_file = Some("add_test.rs".into());
+        assert!(!rules.sort("no tests", &job).all_ignored());
+
+        let bad = VerificationConfig { ignore_feedback_patterns: vec!["(".into()], ..Default::default() };
+        assert!(matches!(FeedbackRules::from_config(&bad), Err(WorkSplitError::ConfigError(_))));
+    }
+}
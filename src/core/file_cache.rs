@@ -1,8 +1,13 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 use tracing::debug;
 
+/// File with the statistics of the latest run's cache, in jobs/
+pub const CACHE_STATS_FILE: &str = "_cachestats.json";
+
 /// Entry in the file cache
 pub struct CacheEntry {
     /// File content
@@ -11,28 +16,85 @@ pub struct CacheEntry {
     pub modified: SystemTime,
     /// Line count
     pub line_count: usize,
+    /// Tick of the last lookup, for least-recently-used eviction
+    last_used: u64,
 }
 
 /// Cache statistics
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct CacheStats {
     pub entry_count: usize,
     pub total_bytes: usize,
     pub total_lines: usize,
+    /// Lookups answered from the cache
+    #[serde(default)]
+    pub hits: u64,
+    /// Lookups that read the file (not cached, or modified since)
+    #[serde(default)]
+    pub misses: u64,
+    /// Entries dropped to stay under `max_bytes`
+    #[serde(default)]
+    pub evictions: u64,
+    /// Byte limit on cached content (0 = unbounded)
+    #[serde(default)]
+    pub max_bytes: usize,
+}
+
+impl CacheStats {
+    /// Share of lookups answered from the cache, if there were any
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+
+    /// Record these statistics as the latest run's
+    pub fn save(&self, jobs_dir: &Path) -> std::io::Result<()> {
+        let saved = SavedCacheStats { updated_at: Utc::now(), stats: self.clone() };
+        let json = serde_json::to_string_pretty(&saved).map_err(std::io::Error::other)?;
+        crate::core::atomic_write::write_atomic(&jobs_dir.join(CACHE_STATS_FILE), json)
+    }
+
+    /// The latest run's statistics, with when they were recorded
+    pub fn load(jobs_dir: &Path) -> Option<(DateTime<Utc>, CacheStats)> {
+        let content = std::fs::read_to_string(jobs_dir.join(CACHE_STATS_FILE)).ok()?;
+        let saved: SavedCacheStats = serde_json::from_str(&content).ok()?;
+        Some((saved.updated_at, saved.stats))
+    }
 }
 
-/// Cache for file contents to avoid re-reading unchanged files
+#[derive(Serialize, Deserialize)]
+struct SavedCacheStats {
+    updated_at: DateTime<Utc>,
+    #[serde(flatten)]
+    stats: CacheStats,
+}
+
+/// Cache for file contents to avoid re-reading unchanged files. With a
+/// byte limit, the least recently used files are dropped to stay under it.
+#[derive(Default)]
 pub struct FileCache {
     /// Cached file entries keyed by absolute path
     entries: HashMap<PathBuf, CacheEntry>,
+    /// Byte limit on cached content (0 = unbounded)
+    max_bytes: usize,
+    /// Bytes of content held
+    total_bytes: usize,
+    /// Lookup counter, the clock of `CacheEntry::last_used`
+    tick: u64,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
 }
 
 impl FileCache {
-    /// Create empty cache
+    /// Create empty, unbounded cache
     pub fn new() -> Self {
-        Self {
-            entries: HashMap::new(),
-        }
+        Self::default()
+    }
+
+    /// Create empty cache holding at most `max_bytes` of content (0 = unbounded)
+    pub fn with_max_bytes(max_bytes: usize) -> Self {
+        Self { max_bytes, ..Self::default() }
     }
 
     /// Get cached entry if present (without loading)
@@ -43,9 +105,10 @@ impl FileCache {
     /// Get or load a file entry
     pub fn get_or_load(&mut self, path: &Path) -> Result<&CacheEntry, std::io::Error> {
         let canonical_path = path.canonicalize()?;
+        self.tick += 1;
         
         // Check if we need to reload (cache miss or file modified)
-        let needs_reload = match self.entries.get(&canonical_path) {
+        let needs_reload = match self.entries.get_mut(&canonical_path) {
             Some(entry) => {
                 let metadata = std::fs::metadata(&canonical_path)?;
                 let current_modified = metadata.modified()?;
@@ -54,6 +117,7 @@ impl FileCache {
                     true
                 } else {
                     debug!("Cache hit for file: {}", canonical_path.display());
+                    entry.last_used = self.tick;
                     false
                 }
             }
@@ -64,6 +128,7 @@ impl FileCache {
         };
 
         if needs_reload {
+            self.misses += 1;
             // File is not cached or has been modified, read it
             let content = std::fs::read_to_string(&canonical_path)?;
             let line_count = content.lines().count();
@@ -74,31 +139,63 @@ impl FileCache {
                 content,
                 modified,
                 line_count,
+                last_used: self.tick,
             };
 
-            self.entries.insert(canonical_path.clone(), entry);
+            self.total_bytes += entry.content.len();
+            if let Some(old) = self.entries.insert(canonical_path.clone(), entry) {
+                self.total_bytes -= old.content.len();
+            }
+            self.evict(&canonical_path);
+        } else {
+            self.hits += 1;
         }
         
         // Return reference to the entry (guaranteed to exist now)
         Ok(self.entries.get(&canonical_path).unwrap())
     }
 
+    /// Drop least recently used entries until the cache is under its byte
+    /// limit, keeping `keep` (the entry just loaded) even if it alone is over
+    fn evict(&mut self, keep: &Path) {
+        while self.max_bytes > 0 && self.total_bytes > self.max_bytes {
+            let Some(oldest) = self.entries.iter()
+                .filter(|(path, _)| path.as_path() != keep)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(path, _)| path.clone()) else { break };
+            if let Some(entry) = self.entries.remove(&oldest) {
+                debug!("Evicted from cache: {}", oldest.display());
+                self.total_bytes -= entry.content.len();
+                self.evictions += 1;
+            }
+        }
+    }
+
     /// Invalidate a specific path from cache
     pub fn invalidate(&mut self, path: &Path) {
         let canonical_path = path.canonicalize().ok();
         if let Some(canonical_path) = canonical_path {
-            self.entries.remove(&canonical_path);
+            if let Some(entry) = self.entries.remove(&canonical_path) {
+                self.total_bytes -= entry.content.len();
+            }
         }
     }
 
     /// Clear entire cache
     pub fn clear(&mut self) {
         self.entries.clear();
+        self.total_bytes = 0;
     }
 
     /// Get cache statistics
     pub fn stats(&self) -> CacheStats {
-        let mut stats = CacheStats::default();
+        let mut stats = CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            max_bytes: self.max_bytes,
+            ..CacheStats::default()
+        };
         for entry in self.entries.values() {
             stats.entry_count += 1;
             stats.total_bytes += entry.content.len();
@@ -200,4 +297,32 @@ mod tests {
         assert_eq!(stats.entry_count, 1);
         assert_eq!(stats.total_lines, 1);
     }
+
+    #[test]
+    fn test_file_cache_evicts_least_recently_used() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths: Vec<PathBuf> = ["a", "b", "c"].iter().map(|name| temp_dir.path().join(name)).collect();
+        for path in &paths {
+            std::fs::write(path, "0123456789").unwrap();
+        }
+
+        let mut cache = FileCache::with_max_bytes(25);
+        cache.get_or_load(&paths[0]).unwrap();
+        cache.get_or_load(&paths[1]).unwrap();
+        // `a` was used more recently than `b`, so loading `c` drops `b`
+        cache.get_or_load(&paths[0]).unwrap();
+        cache.get_or_load(&paths[2]).unwrap();
+
+        let canonical = |path: &PathBuf| path.canonicalize().unwrap();
+        assert!(cache.get(&canonical(&paths[0])).is_some());
+        assert!(cache.get(&canonical(&paths[1])).is_none());
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions), (1, 3, 1));
+        assert_eq!(stats.total_bytes, 20);
+        assert_eq!(stats.hit_rate(), Some(0.25));
+
+        stats.save(temp_dir.path()).unwrap();
+        let (_, loaded) = CacheStats::load(temp_dir.path()).unwrap();
+        assert_eq!((loaded.evictions, loaded.max_bytes), (1, 25));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,126 @@
+//! Frozen outputs (`jobs/_frozen.json`)
+//!
+//! `worksplit freeze` records a content hash of the files a passed job
+//! generated. Runs refuse to overwrite a frozen file unless they have
+//! `--force`, so a rerun or another job writing the same path cannot
+//! clobber reviewed code. The hash tells whether the file changed since.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::core::atomic_write::write_atomic;
+use crate::core::provenance::sha256;
+use crate::error::WorkSplitError;
+
+/// Manifest file name inside jobs/
+pub const FROZEN_FILE: &str = "_frozen.json";
+
+/// A file frozen as reviewed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrozenFile {
+    /// The job that generated it
+    pub job_id: String,
+    /// `sha256:<hex>` of the content when it was frozen; empty when built
+    /// without the "provenance" feature
+    pub content_hash: String,
+    pub frozen_at: DateTime<Utc>,
+}
+
+/// `jobs/_frozen.json`: files keyed by their path relative to the project
+/// root
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrozenManifest {
+    pub files: BTreeMap<String, FrozenFile>,
+}
+
+impl FrozenManifest {
+    /// The manifest in `jobs_dir`, empty if nothing is frozen
+    pub fn load(jobs_dir: &Path) -> Result<Self, WorkSplitError> {
+        match fs::read_to_string(jobs_dir.join(FROZEN_FILE)) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| WorkSplitError::IoError(format!("Invalid {}: {}", FROZEN_FILE, e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, jobs_dir: &Path) -> Result<(), WorkSplitError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| WorkSplitError::IoError(e.to_string()))?;
+        write_atomic(&jobs_dir.join(FROZEN_FILE), json)?;
+        Ok(())
+    }
+
+    /// Freeze `path` (relative to the project root) with its current content
+    pub fn freeze(&mut self, job_id: &str, path: &Path, content: &str) {
+        self.files.insert(
+            key(path),
+            FrozenFile { job_id: job_id.to_string(), content_hash: sha256(content), frozen_at: Utc::now() },
+        );
+    }
+
+    /// Unfreeze the files of `job_id`; returns their paths
+    pub fn unfreeze_job(&mut self, job_id: &str) -> Vec<String> {
+        let paths: Vec<String> = self.files.iter()
+            .filter(|(_, file)| file.job_id == job_id)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &paths {
+            self.files.remove(path);
+        }
+        paths
+    }
+
+    /// Move the files of `old_id` over to `new_id` (`worksplit mv`); returns
+    /// whether any were frozen
+    pub fn rename_job(&mut self, old_id: &str, new_id: &str) -> bool {
+        let mut renamed = false;
+        for file in self.files.values_mut().filter(|file| file.job_id == old_id) {
+            file.job_id = new_id.to_string();
+            renamed = true;
+        }
+        renamed
+    }
+
+    /// The entry for `path` (relative to the project root), if it is frozen
+    pub fn get(&self, path: &Path) -> Option<&FrozenFile> {
+        self.files.get(&key(path))
+    }
+}
+
+fn key(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_freeze_and_unfreeze() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(FrozenManifest::load(temp.path()).unwrap(), FrozenManifest::default());
+
+        let mut manifest = FrozenManifest::default();
+        manifest.freeze("001_a", Path::new("src/a.rs"), "fn a() {}");
+        manifest.freeze("001_a", Path::new("src/a_test.rs"), "#[test] fn t() {}");
+        manifest.freeze("002_b", Path::new("src/b.rs"), "fn b() {}");
+        manifest.save(temp.path()).unwrap();
+
+        let mut loaded = FrozenManifest::load(temp.path()).unwrap();
+        let entry = loaded.get(Path::new("src/a.rs")).unwrap();
+        assert_eq!((entry.job_id.as_str(), entry.content_hash.clone()), ("001_a", sha256("fn a() {}")));
+        assert!(loaded.get(Path::new("src/c.rs")).is_none());
+
+        assert_eq!(loaded.unfreeze_job("001_a"), ["src/a.rs", "src/a_test.rs"]);
+        assert_eq!(loaded.files.keys().collect::<Vec<_>>(), ["src/b.rs"]);
+
+        assert!(loaded.rename_job("002_b", "003_b"));
+        assert!(!loaded.rename_job("002_b", "004_b"));
+        assert_eq!(loaded.unfreeze_job("003_b"), ["src/b.rs"]);
+    }
+}
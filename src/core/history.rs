@@ -0,0 +1,307 @@
+//! Run history (`jobs/_history.jsonl`)
+//!
+//! Every run appends a line when it starts and one per job it processes,
+//! recording the status the job moved from and to and the files it wrote.
+//! `worksplit status --since` replays the log to report what changed.
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::error::WorkSplitError;
+use crate::models::{FailureKind, JobStatus, JobStatusEntry, RubricScores};
+
+/// History file name inside jobs/
+pub const HISTORY_FILE: &str = "_history.jsonl";
+
+/// One processed job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub job_id: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// Status before the job ran
+    pub from: JobStatus,
+    /// Status after the job ran
+    pub to: JobStatus,
+    /// Files written or edited, relative to the project (or staging) root
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<FailureKind>,
+    /// LLM tokens (prompt + generated) the job used
+    #[serde(default)]
+    pub tokens: u64,
+    /// Verifier scores, when the job was scored on the rubric
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rubric: Option<RubricScores>,
+}
+
+/// A line of the history file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum HistoryEvent {
+    /// A run started with `jobs` jobs queued
+    Run { started_at: DateTime<Utc>, jobs: usize },
+    Job(JobRecord),
+}
+
+/// Append an event to `jobs/_history.jsonl`, or to the status database
+/// when there is one
+pub fn append(jobs_dir: &Path, event: &HistoryEvent) -> Result<(), WorkSplitError> {
+    #[cfg(feature = "sqlite")]
+    if let Some(db) = open_db(jobs_dir)? {
+        return Ok(db.append_event(event)?);
+    }
+    let line = serde_json::to_string(event).map_err(|e| WorkSplitError::IoError(e.to_string()))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(jobs_dir.join(HISTORY_FILE))?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+/// All events, oldest first. Unreadable lines (e.g. a line cut off by a
+/// crash) are skipped.
+pub fn load(jobs_dir: &Path) -> Vec<HistoryEvent> {
+    #[cfg(feature = "sqlite")]
+    match open_db(jobs_dir) {
+        Ok(Some(db)) => return db.events().unwrap_or_default(),
+        Ok(None) => {}
+        Err(_) => return Vec::new(),
+    }
+    fs::read_to_string(jobs_dir.join(HISTORY_FILE))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// The status database, if the project keeps its history there
+#[cfg(feature = "sqlite")]
+fn open_db(jobs_dir: &Path) -> Result<Option<crate::core::status_db::StatusDb>, WorkSplitError> {
+    let path = jobs_dir.join(crate::core::status::STATUS_DB_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(crate::core::status_db::StatusDb::open(&path)?))
+}
+
+/// Start of the most recent run
+pub fn last_run_start(events: &[HistoryEvent]) -> Option<DateTime<Utc>> {
+    events.iter().rev().find_map(|event| match event {
+        HistoryEvent::Run { started_at, .. } => Some(*started_at),
+        HistoryEvent::Job(_) => None,
+    })
+}
+
+/// Parse a `--since` value: `last-run`, an RFC 3339 timestamp, a local
+/// `YYYY-MM-DD` or `YYYY-MM-DD HH:MM`, or an age such as `30m`, `12h`, `2d`, `1w`
+pub fn parse_since(value: &str, events: &[HistoryEvent], now: DateTime<Utc>) -> Result<DateTime<Utc>, WorkSplitError> {
+    let value = value.trim();
+    let invalid = || {
+        WorkSplitError::JobError(format!(
+            "Invalid --since '{}': use last-run, a date (2024-05-01), a time (2024-05-01 09:00), \
+             an RFC 3339 timestamp or an age (30m, 12h, 2d, 1w)",
+            value
+        ))
+    };
+
+    if value == "last-run" {
+        return last_run_start(events).ok_or_else(|| WorkSplitError::JobError("No runs recorded yet".to_string()));
+    }
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    let local = |naive: NaiveDateTime| Local.from_local_datetime(&naive).earliest().map(|at| at.with_timezone(&Utc));
+    if let Ok(naive) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M") {
+        return local(naive).ok_or_else(invalid);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return local(date.and_hms_opt(0, 0, 0).unwrap()).ok_or_else(invalid);
+    }
+
+    let split = value.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let amount: i64 = value[..split].parse().map_err(|_| invalid())?;
+    let age = match &value[split..] {
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => return Err(invalid()),
+    };
+    Ok(now - age)
+}
+
+/// How one job changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobChange {
+    pub job_id: String,
+    pub before: JobStatus,
+    pub after: JobStatus,
+    /// Times the job was processed
+    pub runs: usize,
+}
+
+/// What changed since a point in time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChangeReport {
+    pub since: DateTime<Utc>,
+    /// Runs started since then
+    pub runs: usize,
+    /// Jobs processed since then, by ID
+    pub jobs: Vec<JobChange>,
+    /// Files (re)generated since then
+    pub files: BTreeSet<PathBuf>,
+    pub tokens: u64,
+    /// Time spent processing jobs
+    pub job_time: Duration,
+    /// Job count per status, then and now
+    pub counts_before: BTreeMap<JobStatus, usize>,
+    pub counts_after: BTreeMap<JobStatus, usize>,
+}
+
+impl ChangeReport {
+    /// Replay `events` against the current status `entries`
+    pub fn since(since: DateTime<Utc>, events: &[HistoryEvent], entries: &[&JobStatusEntry]) -> Self {
+        let mut report = ChangeReport { since, job_time: Duration::zero(), ..Default::default() };
+        let mut records: BTreeMap<&str, Vec<&JobRecord>> = BTreeMap::new();
+        for event in events {
+            match event {
+                HistoryEvent::Run { started_at, .. } if *started_at >= since => report.runs += 1,
+                HistoryEvent::Job(record) if record.started_at >= since => {
+                    records.entry(record.job_id.as_str()).or_default().push(record);
+                    report.files.extend(record.outputs.iter().cloned());
+                    report.tokens += record.tokens;
+                    report.job_time += record.finished_at - record.started_at;
+                }
+                _ => {}
+            }
+        }
+
+        let current: BTreeMap<&str, JobStatus> = entries.iter().map(|e| (e.id.as_str(), e.status)).collect();
+        for (job_id, records) in &records {
+            let after = current.get(job_id).copied().unwrap_or(records[records.len() - 1].to);
+            report.jobs.push(JobChange {
+                job_id: job_id.to_string(),
+                before: records[0].from,
+                after,
+                runs: records.len(),
+            });
+        }
+
+        // A job's status back then is what its first record since started from
+        for entry in entries {
+            let before = records.get(entry.id.as_str()).map_or(entry.status, |r| r[0].from);
+            *report.counts_before.entry(before).or_default() += 1;
+            *report.counts_after.entry(entry.status).or_default() += 1;
+        }
+        report
+    }
+
+    /// Jobs whose status differs from before
+    pub fn changed(&self) -> impl Iterator<Item = &JobChange> {
+        self.jobs.iter().filter(|job| job.before != job.after)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-05-01T09:00:00Z").unwrap().with_timezone(&Utc) + Duration::minutes(minutes)
+    }
+
+    fn job(job_id: &str, minute: i64, from: JobStatus, to: JobStatus, outputs: &[&str]) -> HistoryEvent {
+        HistoryEvent::Job(JobRecord {
+            job_id: job_id.to_string(),
+            started_at: at(minute),
+            finished_at: at(minute + 1),
+            from,
+            to,
+            outputs: outputs.iter().map(PathBuf::from).collect(),
+            failure: None,
+            tokens: 100,
+            rubric: None,
+        })
+    }
+
+    fn entry(id: &str, status: JobStatus) -> JobStatusEntry {
+        let mut entry = JobStatusEntry::new(id.to_string());
+        entry.status = status;
+        entry
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let temp = TempDir::new().unwrap();
+        let run = HistoryEvent::Run { started_at: at(0), jobs: 1 };
+        append(temp.path(), &run).unwrap();
+        append(temp.path(), &job("a", 0, JobStatus::Created, JobStatus::Pass, &["src/a.rs"])).unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(temp.path().join(HISTORY_FILE))
+            .unwrap()
+            .write_all(b"{\"event\":\"job\",\"job_id\"")
+            .unwrap();
+
+        let events = load(temp.path());
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], run);
+        assert!(load(&temp.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let events = vec![
+            HistoryEvent::Run { started_at: at(0), jobs: 1 },
+            HistoryEvent::Run { started_at: at(30), jobs: 2 },
+        ];
+        let now = at(120);
+        assert_eq!(parse_since("last-run", &events, now).unwrap(), at(30));
+        assert_eq!(parse_since("2024-05-01T09:10:00Z", &events, now).unwrap(), at(10));
+        assert_eq!(parse_since("90m", &events, now).unwrap(), at(30));
+        assert_eq!(parse_since("1d", &events, now).unwrap(), at(120 - 24 * 60));
+        assert!(parse_since("2024-05-01", &events, now).is_ok());
+        assert!(parse_since("2024-05-01 08:30", &events, now).is_ok());
+        assert!(parse_since("yesterday", &events, now).is_err());
+        assert!(parse_since("5y", &events, now).is_err());
+        assert!(parse_since("last-run", &[], now).is_err());
+    }
+
+    #[test]
+    fn test_change_report_replays_history() {
+        let events = vec![
+            HistoryEvent::Run { started_at: at(0), jobs: 2 },
+            job("a", 0, JobStatus::Created, JobStatus::Fail, &["src/a.rs"]),
+            job("b", 2, JobStatus::Created, JobStatus::Pass, &["src/b.rs"]),
+            HistoryEvent::Run { started_at: at(30), jobs: 1 },
+            job("a", 30, JobStatus::Fail, JobStatus::Pass, &["src/a.rs", "tests/a.rs"]),
+        ];
+        let entries = [entry("a", JobStatus::Pass), entry("b", JobStatus::Pass), entry("c", JobStatus::Created)];
+        let entries: Vec<&JobStatusEntry> = entries.iter().collect();
+
+        let report = ChangeReport::since(at(30), &events, &entries);
+        assert_eq!(report.runs, 1);
+        assert_eq!(
+            report.jobs,
+            vec![JobChange { job_id: "a".to_string(), before: JobStatus::Fail, after: JobStatus::Pass, runs: 1 }]
+        );
+        assert_eq!(report.files, BTreeSet::from([PathBuf::from("src/a.rs"), PathBuf::from("tests/a.rs")]));
+        assert_eq!(report.tokens, 100);
+        assert_eq!(report.job_time, Duration::minutes(1));
+        assert_eq!(report.counts_before[&JobStatus::Pass], 1);
+        assert_eq!(report.counts_before[&JobStatus::Fail], 1);
+        assert_eq!(report.counts_after[&JobStatus::Pass], 2);
+
+        let report = ChangeReport::since(at(0), &events, &entries);
+        assert_eq!(report.runs, 2);
+        assert_eq!(report.jobs[0], JobChange { job_id: "a".to_string(), before: JobStatus::Created, after: JobStatus::Pass, runs: 2 });
+        assert_eq!(report.changed().count(), 2);
+        assert_eq!(report.counts_before[&JobStatus::Created], 3);
+        assert_eq!(report.tokens, 300);
+    }
+}
@@ -0,0 +1,138 @@
+//! `.worksplitignore`: files WorkSplit should not pick up on its own
+//!
+//! The file sits in the project root and uses gitignore syntax. Ignored
+//! files are skipped by job discovery, directory and glob expansion of
+//! `target_files`, the symbol index behind `context_symbols` and the
+//! `new-job --interactive` file picker. Files named explicitly in a job are
+//! still used.
+
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+use crate::core::targets::glob_to_regex;
+use crate::error::WorkSplitError;
+
+/// Ignore file name in the project root
+pub const IGNORE_FILE: &str = ".worksplitignore";
+
+#[derive(Debug)]
+struct Rule {
+    regex: Regex,
+    /// `!pattern`: re-include what an earlier rule ignored
+    negated: bool,
+    /// `pattern/`: only matches directories
+    dir_only: bool,
+}
+
+/// Parsed `.worksplitignore` rules; later rules override earlier ones
+#[derive(Debug, Default)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    /// Load `.worksplitignore` from the project root; no file ignores nothing
+    pub fn load(project_root: &Path) -> Result<Self, WorkSplitError> {
+        match fs::read_to_string(project_root.join(IGNORE_FILE)) {
+            Ok(content) => Self::parse(&content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Self, WorkSplitError> {
+        let mut rules = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, pattern) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, line.strip_prefix('\\').unwrap_or(line)),
+            };
+            let (dir_only, pattern) = match pattern.strip_suffix('/') {
+                Some(rest) => (true, rest),
+                None => (false, pattern),
+            };
+            // A pattern without an inner slash matches at any depth
+            let glob = match pattern.strip_prefix('/') {
+                Some(anchored) => anchored.to_string(),
+                None if pattern.contains('/') => pattern.to_string(),
+                None => format!("**/{}", pattern),
+            };
+            let regex = glob_to_regex(&glob).map_err(|_| WorkSplitError::InvalidIgnorePattern {
+                line: index + 1,
+                pattern: line.to_string(),
+            })?;
+            rules.push(Rule { regex, negated, dir_only });
+        }
+        Ok(Self { rules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether a project-relative path is ignored, either itself or through
+    /// one of its parent directories
+    pub fn is_ignored(&self, relative: &Path, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let parent_ignored = relative
+            .ancestors()
+            .skip(1)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .any(|dir| self.matches(dir, true));
+        parent_ignored || self.matches(relative, is_dir)
+    }
+
+    /// Whether the last rule matching the path itself ignores it
+    fn matches(&self, relative: &Path, is_dir: bool) -> bool {
+        let path = relative.to_string_lossy().replace('\\', "/");
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| (is_dir || !rule.dir_only) && rule.regex.is_match(&path))
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gitignore_syntax() {
+        let rules = IgnoreRules::parse(
+            "# build output\nvendor/\n*.generated.rs\n/docs/*.md\n!docs/keep.md\nsrc/**/fixtures\n\\!bang.rs\n",
+        )
+        .unwrap();
+        let ignored = |path: &str| rules.is_ignored(Path::new(path), false);
+
+        assert!(rules.is_ignored(Path::new("vendor"), true));
+        assert!(ignored("vendor/lib/a.rs"));
+        assert!(ignored("crates/x/vendor/a.rs"));
+        assert!(!ignored("vendor"), "dir-only rule must not match a file");
+        assert!(ignored("src/api.generated.rs"));
+        assert!(ignored("docs/guide.md"));
+        assert!(!ignored("docs/keep.md"));
+        assert!(!ignored("sub/docs/guide.md"), "leading slash anchors to the root");
+        assert!(ignored("src/a/b/fixtures/data.json"));
+        assert!(ignored("!bang.rs"));
+        assert!(!ignored("src/main.rs"));
+    }
+
+    #[test]
+    fn test_missing_file_ignores_nothing() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let rules = IgnoreRules::load(temp_dir.path()).unwrap();
+        assert!(rules.is_empty());
+        assert!(!rules.is_ignored(Path::new("anything.rs"), false));
+
+        let err = IgnoreRules::parse("ok.rs\nbad[\n").unwrap_err();
+        assert!(matches!(err, WorkSplitError::InvalidIgnorePattern { line: 2, .. }), "{}", err);
+    }
+}
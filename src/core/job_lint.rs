@@ -0,0 +1,241 @@
+//! Job-authoring checks (`worksplit validate --lint`)
+//!
+//! Each rule flags a job file that parses and validates fine but is likely
+//! to get a poor result from the model, together with the change to the job
+//! file that fixes it.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::core::output_paths::normalize_relative;
+use crate::core::targets::expand_target_files;
+use crate::models::{Job, OutputMode};
+
+/// Instructions shorter than this rarely carry enough detail
+pub const MIN_INSTRUCTION_CHARS: usize = 80;
+
+/// A likely problem in a job file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobLint {
+    pub job_id: String,
+    /// Rule that flagged it, e.g. `short-instructions`
+    pub rule: &'static str,
+    pub message: String,
+    /// Change to the job file that fixes it
+    pub suggestion: String,
+}
+
+impl fmt::Display for JobLint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Job '{}' [{}]: {}", self.job_id, self.rule, self.message)
+    }
+}
+
+/// Code fence language tag for an output file
+fn fence_language(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or_default() {
+        "rs" => "rust",
+        "ts" | "tsx" => "typescript",
+        "js" | "jsx" => "javascript",
+        "sol" => "solidity",
+        "cs" => "csharp",
+        "py" => "python",
+        "go" => "go",
+        _ => "<language>",
+    }
+}
+
+/// Number of code blocks in `text` whose opening fence has no language tag
+fn untagged_code_blocks(text: &str) -> usize {
+    let mut in_block = false;
+    let mut untagged = 0;
+    for line in text.lines() {
+        let Some(info) = line.trim_start().strip_prefix("```") else { continue };
+        if !in_block && info.trim().is_empty() {
+            untagged += 1;
+        }
+        in_block = !in_block;
+    }
+    untagged
+}
+
+/// Whether `mode` edits its target files in place
+fn edits_in_place(mode: OutputMode) -> bool {
+    matches!(mode, OutputMode::Edit | OutputMode::ReplacePattern | OutputMode::UpdateFixtures)
+}
+
+/// Check one job for common authoring problems
+pub fn lint_job(project_root: &Path, job: &Job) -> Vec<JobLint> {
+    let metadata = &job.metadata;
+    let mut lints = Vec::new();
+    let mut lint = |rule: &'static str, message: String, suggestion: String| {
+        lints.push(JobLint { job_id: job.id.clone(), rule, message, suggestion });
+    };
+
+    let length = job.instructions.trim().chars().count();
+    if length < MIN_INSTRUCTION_CHARS {
+        lint(
+            "short-instructions",
+            format!("instructions are only {} characters long", length),
+            "describe what to implement: signatures, behavior, constraints and edge cases".to_string(),
+        );
+    }
+
+    let untagged = untagged_code_blocks(&job.instructions);
+    if untagged > 0 {
+        let language = fence_language(&metadata.output_path());
+        lint(
+            "missing-language-hint",
+            format!("{} code block(s) in the instructions have no language tag", untagged),
+            format!("open them with ```{} so the model knows which language the snippet is in", language),
+        );
+    }
+
+    // Context files the job also writes or edits
+    let normalized = |paths: Vec<PathBuf>| -> Vec<PathBuf> { paths.iter().filter_map(|p| normalize_relative(p)).collect() };
+    let in_place = edits_in_place(metadata.mode);
+    let touched = if in_place {
+        normalized(metadata.get_target_files())
+    } else {
+        normalized(metadata.get_output_files().into_iter().chain(metadata.test_path()).collect())
+    };
+    for context in &metadata.context_files {
+        if !normalize_relative(context).is_some_and(|c| touched.contains(&c)) {
+            continue;
+        }
+        if in_place {
+            lint(
+                "context-is-output",
+                format!("{} is both a context file and an edit target", context.display()),
+                format!("remove {} from context_files; target files are already sent to the model", context.display()),
+            );
+        } else {
+            lint(
+                "context-is-output",
+                format!("{} is both a context file and an output of this job", context.display()),
+                format!(
+                    "the output replaces the whole file: use mode: edit with target_files: [{}], or remove it from context_files",
+                    context.display()
+                ),
+            );
+        }
+    }
+
+    // Target files that resolve to nothing
+    let mut targets: Vec<PathBuf> = Vec::new();
+    if in_place {
+        targets.extend(metadata.target_files.iter().flatten().cloned());
+    }
+    if metadata.mode == OutputMode::Split {
+        targets.extend(metadata.target_file.clone());
+    }
+    for entry in targets {
+        let missing: Vec<PathBuf> = match expand_target_files(project_root, std::slice::from_ref(&entry)) {
+            Ok(files) => files.into_iter().filter(|f| !project_root.join(f).is_file()).collect(),
+            Err(_) => vec![entry.clone()],
+        };
+        for file in missing {
+            let message = if file == entry {
+                format!("target {} matches no file", entry.display())
+            } else {
+                format!("target {} (from {}) does not exist", file.display(), entry.display())
+            };
+            lint(
+                "unreachable-target",
+                message,
+                format!("fix the path relative to the project root, or remove {} from the targets", entry.display()),
+            );
+        }
+    }
+
+    if metadata.sequential == Some(true) && metadata.output_files.is_none() {
+        lint(
+            "sequential-without-output-files",
+            "sequential: true has no effect without output_files".to_string(),
+            "list the files to generate under output_files, or remove sequential: true".to_string(),
+        );
+    }
+
+    lints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::JobMetadata;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const INSTRUCTIONS: &str = "Implement UserService with create, get and delete methods. \
+        Return a Result with a descriptive error for unknown IDs.";
+
+    fn job(yaml: &str, instructions: &str) -> Job {
+        let metadata: JobMetadata = serde_yaml::from_str(yaml).unwrap();
+        Job::new("001_job".to_string(), metadata, instructions.to_string(), PathBuf::from("jobs/001_job.md"))
+    }
+
+    fn rules(lints: &[JobLint]) -> Vec<&str> {
+        lints.iter().map(|l| l.rule).collect()
+    }
+
+    #[test]
+    fn test_clean_job_has_no_lints() {
+        let temp = TempDir::new().unwrap();
+        let instructions = format!("{}\n\n```rust\nfn get(id: u64) -> Result<User, Error>\n```\n", INSTRUCTIONS);
+        let job = job("context_files: [src/models.rs]\noutput_dir: src/\noutput_file: service.rs", &instructions);
+        assert!(lint_job(temp.path(), &job).is_empty());
+    }
+
+    #[test]
+    fn test_short_instructions_and_untagged_code_blocks() {
+        let temp = TempDir::new().unwrap();
+        let job = job("output_dir: src/\noutput_file: service.rs", "Add get().\n\n```\nfn get()\n```\n\n```\nfn put()\n```\n");
+        let lints = lint_job(temp.path(), &job);
+        assert_eq!(rules(&lints), vec!["short-instructions", "missing-language-hint"]);
+        assert!(lints[1].message.starts_with("2 code block(s)"));
+        assert!(lints[1].suggestion.contains("```rust"));
+    }
+
+    #[test]
+    fn test_context_file_that_is_an_output() {
+        let temp = TempDir::new().unwrap();
+        let replace = job("context_files: [src/./service.rs]\noutput_dir: src/\noutput_file: service.rs", INSTRUCTIONS);
+        let lints = lint_job(temp.path(), &replace);
+        assert_eq!(rules(&lints), vec!["context-is-output"]);
+        assert!(lints[0].suggestion.contains("mode: edit"));
+
+        fs::create_dir_all(temp.path().join("src")).unwrap();
+        fs::write(temp.path().join("src/service.rs"), "fn get() {}\n").unwrap();
+        let edit = job(
+            "mode: edit\ncontext_files: [src/service.rs]\ntarget_files: [src/service.rs]\noutput_dir: src/\noutput_file: service.rs",
+            INSTRUCTIONS,
+        );
+        let lints = lint_job(temp.path(), &edit);
+        assert_eq!(rules(&lints), vec!["context-is-output"]);
+        assert!(lints[0].suggestion.contains("remove src/service.rs from context_files"));
+    }
+
+    #[test]
+    fn test_unreachable_targets() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("src")).unwrap();
+        fs::write(temp.path().join("src/lib.rs"), "").unwrap();
+        let edit = job(
+            "mode: edit\ntarget_files: [src/lib.rs, src/mian.rs, \"src/**/*.ts\"]\noutput_dir: src/\noutput_file: lib.rs",
+            INSTRUCTIONS,
+        );
+        let lints = lint_job(temp.path(), &edit);
+        let messages: Vec<&str> = lints.iter().map(|l| l.message.as_str()).collect();
+        assert_eq!(messages, vec!["target src/mian.rs matches no file", "target src/**/*.ts matches no file"]);
+        assert!(lints.iter().all(|l| l.rule == "unreachable-target"));
+    }
+
+    #[test]
+    fn test_sequential_without_output_files() {
+        let temp = TempDir::new().unwrap();
+        let job = job("sequential: true\noutput_dir: src/\noutput_file: service.rs", INSTRUCTIONS);
+        let lints = lint_job(temp.path(), &job);
+        assert_eq!(rules(&lints), vec!["sequential-without-output-files"]);
+        assert_eq!(lints[0].to_string(), "Job '001_job' [sequential-without-output-files]: sequential: true has no effect without output_files");
+    }
+}
@@ -0,0 +1,146 @@
+//! Project job templates (`jobs/_templates/*.md`)
+//!
+//! A job template is a job file with `{{slot}}` placeholders, for teams whose
+//! jobs follow conventions the built-in `new-job --type` templates don't.
+//! `worksplit new-job --template <name>` fills the slots it knows
+//! ([`BUILTIN_SLOTS`]) and the ones given with `--set`; slots left over are
+//! listed so they can be filled in by hand. A leading `<!-- ... -->` comment
+//! describes the template in `worksplit templates list` and is not copied
+//! into the job. Prompt templates (`*.tmpl`) share the folder.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use crate::core::prompt_templates::TEMPLATES_DIR;
+use crate::error::WorkSplitError;
+
+/// Extension of job template files
+pub const JOB_TEMPLATE_EXTENSION: &str = "md";
+
+/// Slots `new-job` fills without `--set`
+pub const BUILTIN_SLOTS: &[&str] = &["job_name", "title", "output_dir", "output_file", "context_files", "target_files"];
+
+fn slot_pattern() -> Regex {
+    Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap()
+}
+
+/// A job template of the project
+#[derive(Debug, Clone)]
+pub struct JobTemplateFile {
+    /// File stem, as given to `--template`
+    pub name: String,
+    pub path: PathBuf,
+    /// Text of the leading `<!-- ... -->` comment
+    pub description: Option<String>,
+    /// The template without its description
+    pub content: String,
+}
+
+impl JobTemplateFile {
+    pub fn load(path: &Path) -> Result<Self, WorkSplitError> {
+        let source = fs::read_to_string(path)?;
+        let name = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let trimmed = source.trim_start();
+        let (description, content) = match trimmed.strip_prefix("<!--").and_then(|rest| rest.split_once("-->")) {
+            Some((comment, rest)) => (Some(comment.trim().to_string()), rest.trim_start().to_string()),
+            None => (None, source),
+        };
+        Ok(Self { name, path: path.to_path_buf(), description, content })
+    }
+
+    /// Slot names in order of first use
+    pub fn slots(&self) -> Vec<String> {
+        let mut slots: Vec<String> = Vec::new();
+        for captures in slot_pattern().captures_iter(&self.content) {
+            let slot = captures[1].to_string();
+            if !slots.contains(&slot) {
+                slots.push(slot);
+            }
+        }
+        slots
+    }
+
+    /// The job file with `values` filled in, and the slots that had no value
+    /// (left in place)
+    pub fn render(&self, values: &BTreeMap<String, String>) -> (String, Vec<String>) {
+        let mut unfilled: Vec<String> = Vec::new();
+        let rendered = slot_pattern().replace_all(&self.content, |captures: &regex::Captures| {
+            match values.get(&captures[1]) {
+                Some(value) => value.clone(),
+                None => {
+                    if !unfilled.iter().any(|slot| slot == &captures[1]) {
+                        unfilled.push(captures[1].to_string());
+                    }
+                    captures[0].to_string()
+                }
+            }
+        });
+        (rendered.into_owned(), unfilled)
+    }
+}
+
+/// The project's job templates, by name
+pub fn list_job_templates(jobs_dir: &Path) -> Result<Vec<JobTemplateFile>, WorkSplitError> {
+    let dir = jobs_dir.join(TEMPLATES_DIR);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == JOB_TEMPLATE_EXTENSION))
+        .collect();
+    paths.sort();
+    paths.iter().map(|path| JobTemplateFile::load(path)).collect()
+}
+
+/// The job template called `name`
+pub fn find_job_template(jobs_dir: &Path, name: &str) -> Result<JobTemplateFile, WorkSplitError> {
+    let templates = list_job_templates(jobs_dir)?;
+    let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+    let message = if names.is_empty() {
+        format!("No job template '{}': jobs/{}/ has no *.{} templates", name, TEMPLATES_DIR, JOB_TEMPLATE_EXTENSION)
+    } else {
+        format!("No job template '{}' (available: {})", name, names.join(", "))
+    };
+    templates.into_iter().find(|t| t.name == name).ok_or(WorkSplitError::JobError(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_job_templates_render_slots() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join(TEMPLATES_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("create.tmpl"), "{{ instructions }}").unwrap();
+        fs::write(
+            dir.join("service.md"),
+            "<!-- Service with a repository -->\n---\noutput_dir: {{output_dir}}\noutput_file: {{ output_file }}\n---\n\n# {{title}}\n\nStore {{entity}} rows in {{table}}; {{entity}} has an id.\n",
+        )
+        .unwrap();
+
+        let templates = list_job_templates(temp.path()).unwrap();
+        assert_eq!(templates.len(), 1);
+        let template = &templates[0];
+        assert_eq!(template.name, "service");
+        assert_eq!(template.description.as_deref(), Some("Service with a repository"));
+        assert_eq!(template.slots(), ["output_dir", "output_file", "title", "entity", "table"]);
+
+        let values: BTreeMap<String, String> = [("output_dir", "src/"), ("output_file", "orders.rs"), ("title", "Orders"), ("entity", "order")]
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .into();
+        let (job, unfilled) = template.render(&values);
+        assert!(job.starts_with("---\noutput_dir: src/\noutput_file: orders.rs\n---\n\n# Orders\n"), "{}", job);
+        assert!(job.contains("Store order rows in {{table}}; order has an id."), "{}", job);
+        assert_eq!(unfilled, ["table"]);
+
+        let missing = find_job_template(temp.path(), "api").unwrap_err().to_string();
+        assert!(missing.contains("available: service"), "{}", missing);
+    }
+}
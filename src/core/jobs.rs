@@ -4,9 +4,17 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 
+use crate::core::anchors::expand_anchors;
 use crate::core::file_cache::{CacheStats, FileCache};
+use crate::core::ignore::IgnoreRules;
+use crate::core::prompts::{VERIFY_LENIENT_ADDENDUM, VERIFY_STRICT_ADDENDUM};
+use crate::core::schema::job_frontmatter;
+use crate::core::symbols::SymbolIndex;
+use crate::core::targets::{closest_by_file_name, expand_target_files, glob_to_regex};
+use crate::core::truncate::truncate_context;
+use crate::core::url_context::UrlContextLoader;
 use crate::error::{JobParseError, WorkSplitError};
-use crate::models::{Config, Job, JobMetadata, LimitsConfig};
+use crate::models::{Config, Job, JobDefaultsConfig, JobMetadata, JobValidationError, LimitsConfig, UrlContextConfig, VerificationLevel};
 use crate::templates::get_templates;
 
 /// Jobs folder manager
@@ -19,6 +27,10 @@ pub struct JobsManager {
     limits: LimitsConfig,
     /// Cache for context file contents
     cache: FileCache,
+    /// Replace missing context files with the closest file of the same name
+    substitute_missing_context: bool,
+    /// `[job_defaults]` from the project's worksplit.toml
+    job_defaults: JobDefaultsConfig,
 }
 
 /// Constant for the test prompt filename
@@ -29,6 +41,43 @@ const EDIT_PROMPT_FILE: &str = "_systemprompt_edit.md";
 const VERIFY_EDIT_PROMPT_FILE: &str = "_systemprompt_verify_edit.md";
 /// Constant for the split mode prompt filename
 const SPLIT_PROMPT_FILE: &str = "_systemprompt_split.md";
+/// Project coding conventions, appended to creation and edit prompts
+pub const CONVENTIONS_FILE: &str = "_conventions.md";
+
+/// Fill in `[job_defaults]` for the fields a job's frontmatter leaves out;
+/// default context files go ahead of the job's own, without duplicates
+pub fn apply_job_defaults(defaults: &JobDefaultsConfig, frontmatter: &mut serde_json::Value) {
+    let Some(fields) = frontmatter.as_object_mut() else { return };
+    let mut set_missing = |key: &str, value: serde_json::Value| {
+        if fields.get(key).is_none_or(|v| v.is_null()) {
+            fields.insert(key.to_string(), value);
+        }
+    };
+    if let Some(output_dir) = &defaults.output_dir {
+        set_missing("output_dir", serde_json::json!(output_dir));
+    }
+    if let Some(verify) = defaults.verify {
+        set_missing("verify", serde_json::json!(verify));
+    }
+    if let Some(sequential) = defaults.sequential {
+        set_missing("sequential", serde_json::json!(sequential));
+    }
+
+    if !defaults.context_files.is_empty() {
+        // Anything but a list is left for deserialization to reject
+        let own: Vec<serde_json::Value> = match fields.get("context_files") {
+            Some(serde_json::Value::Array(own)) => own.clone(),
+            None | Some(serde_json::Value::Null) => Vec::new(),
+            Some(_) => return,
+        };
+        let mut merged: Vec<serde_json::Value> = defaults.context_files.iter()
+            .map(|path| serde_json::json!(path))
+            .filter(|path| !own.contains(path))
+            .collect();
+        merged.extend(own);
+        fields.insert("context_files".to_string(), serde_json::Value::Array(merged));
+    }
+}
 
 impl JobsManager {
     /// Create a new jobs manager
@@ -36,12 +85,25 @@ impl JobsManager {
         let jobs_dir = project_root.join("jobs");
         Self {
             jobs_dir,
-            project_root,
+            cache: FileCache::with_max_bytes(limits.file_cache_max_bytes),
             limits,
-            cache: FileCache::new(),
+            substitute_missing_context: false,
+            job_defaults: Config::load_from_dir(&project_root).map(|c| c.job_defaults).unwrap_or_default(),
+            project_root,
         }
     }
 
+    /// The `[job_defaults]` merged into every parsed job
+    pub fn job_defaults(&self) -> &JobDefaultsConfig {
+        &self.job_defaults
+    }
+
+    /// Replace missing context files with the closest project file of the
+    /// same name (`[behavior] substitute_missing_context`)
+    pub fn set_substitute_missing_context(&mut self, enabled: bool) {
+        self.substitute_missing_context = enabled;
+    }
+
     /// Get the jobs directory path
     pub fn jobs_dir(&self) -> &Path {
         &self.jobs_dir
@@ -59,6 +121,7 @@ impl JobsManager {
             return Err(WorkSplitError::JobsFolderNotFound(self.jobs_dir.clone()));
         }
 
+        let ignore = IgnoreRules::load(&self.project_root)?;
         let mut jobs = Vec::new();
 
         for entry in fs::read_dir(&self.jobs_dir)? {
@@ -71,6 +134,12 @@ impl JobsManager {
                     if filename.starts_with('_') {
                         continue;
                     }
+                    if let Ok(relative) = path.strip_prefix(&self.project_root) {
+                        if ignore.is_ignored(relative, false) {
+                            debug!("Skipping ignored job file: {}", filename);
+                            continue;
+                        }
+                    }
                     // Only process .md files
                     if filename.ends_with(".md") {
                         let id = filename.trim_end_matches(".md").to_string();
@@ -85,6 +154,57 @@ impl JobsManager {
         Ok(jobs)
     }
 
+    /// Job IDs matching any of the selectors, in discovery order. A selector
+    /// is `tag:<name>`, a glob over job IDs (`auth_*`) or a job ID.
+    pub fn select_jobs(&self, selectors: &[String]) -> Result<Vec<String>, WorkSplitError> {
+        let discovered = self.discover_jobs()?;
+        let mut selected = Vec::new();
+
+        for selector in selectors {
+            let matching: Vec<String> = if let Some(tag) = selector.strip_prefix("tag:") {
+                discovered
+                    .iter()
+                    .filter(|id| match self.parse_job(id) {
+                        Ok(job) => job.metadata.tags.iter().any(|t| t == tag),
+                        Err(e) => {
+                            warn!("Skipping job '{}' while selecting by tag: {}", id, e);
+                            false
+                        }
+                    })
+                    .cloned()
+                    .collect()
+            } else if selector.contains(['*', '?', '[']) {
+                let pattern = glob_to_regex(selector)
+                    .map_err(|e| WorkSplitError::JobError(format!("Invalid job pattern '{}': {}", selector, e)))?;
+                discovered.iter().filter(|id| pattern.is_match(id)).cloned().collect()
+            } else if discovered.contains(selector) {
+                vec![selector.clone()]
+            } else {
+                return Err(WorkSplitError::JobError(format!("No job named '{}'", selector)));
+            };
+
+            for id in matching {
+                if !selected.contains(&id) {
+                    selected.push(id);
+                }
+            }
+        }
+        selected.sort();
+        Ok(selected)
+    }
+
+    /// Whether the job file is marked `draft: true`. Only that key is read,
+    /// so a half-written job still counts as a draft.
+    pub fn is_draft(&self, job_id: &str) -> bool {
+        job_frontmatter(&self.jobs_dir.join(format!("{}.md", job_id)))
+            .is_ok_and(|frontmatter| frontmatter["draft"] == true)
+    }
+
+    /// The draft jobs among `job_ids`
+    pub fn draft_jobs(&self, job_ids: &[String]) -> Vec<String> {
+        job_ids.iter().filter(|id| self.is_draft(id)).cloned().collect()
+    }
+
     /// Parse a job file
     pub fn parse_job(&self, job_id: &str) -> Result<Job, WorkSplitError> {
         let file_path = self.jobs_dir.join(format!("{}.md", job_id));
@@ -103,16 +223,45 @@ impl JobsManager {
             )
         })?;
 
-        // Deserialize the metadata
-        let metadata: JobMetadata = data.deserialize().map_err(|e| {
+        // Deserialize the metadata, with the project's defaults filled in
+        let mut frontmatter: serde_json::Value = data.deserialize().map_err(|e| {
+            JobParseError::YamlError(file_path.clone(), e.to_string())
+        })?;
+        apply_job_defaults(&self.job_defaults, &mut frontmatter);
+        let mut metadata: JobMetadata = serde_json::from_value(frontmatter).map_err(|e| {
             JobParseError::YamlError(file_path.clone(), e.to_string())
         })?;
 
         // Validate metadata
         metadata.validate(self.limits.max_context_files)?;
 
-        // Get the markdown body (instructions)
-        let instructions = parsed.content.trim().to_string();
+        // From here on every path is relative to the project root
+        if let Some(ref workdir) = metadata.workdir {
+            if !self.project_root.join(workdir).is_dir() {
+                return Err(WorkSplitError::WorkdirNotFound(workdir.clone()));
+            }
+            metadata.apply_workdir();
+        }
+
+        // Get the markdown body (instructions), after the instructions file
+        let mut instructions = parsed.content.trim().to_string();
+        if let Some(ref instructions_file) = metadata.instructions_file {
+            // Also after following symlinks
+            let path = self.project_root.join(instructions_file);
+            if let (Ok(root), Ok(canonical)) = (self.project_root.canonicalize(), path.canonicalize()) {
+                if !canonical.starts_with(root) {
+                    return Err(JobValidationError::InstructionsFileOutsideProject(instructions_file.clone()).into());
+                }
+            }
+            let shared = fs::read_to_string(&path).map_err(|e| {
+                JobParseError::InstructionsFileError(file_path.clone(), instructions_file.clone(), e)
+            })?;
+            instructions = [shared.trim(), instructions.as_str()].iter()
+                .filter(|part| !part.is_empty())
+                .copied()
+                .collect::<Vec<_>>()
+                .join("\n\n");
+        }
 
         debug!("Parsed job '{}' with {} context files", job_id, metadata.context_files.len());
 
@@ -124,6 +273,14 @@ impl JobsManager {
         ))
     }
 
+    /// Parse a job for prompting: code anchors in its instructions
+    /// (`{{file:path#L40-L80}}`) are replaced by the code they point at
+    pub fn parse_job_for_prompt(&self, job_id: &str) -> Result<Job, WorkSplitError> {
+        let mut job = self.parse_job(job_id)?;
+        job.instructions = expand_anchors(&self.project_root, &job.instructions, job.metadata.workdir.as_deref())?;
+        Ok(job)
+    }
+
     /// Get template content for a system prompt file
     fn get_template_for_prompt(&self, filename: &str) -> Option<&'static str> {
         // Load config to get language
@@ -214,6 +371,64 @@ impl JobsManager {
         }
     }
 
+    /// Verify prompt for a job's verification level. Strict and lenient jobs
+    /// use `_systemprompt_verify_<level>.md` (`_systemprompt_verify_edit_<level>.md`
+    /// for edit jobs) when the project has one, otherwise `base` with
+    /// instructions for the level appended.
+    pub fn verify_prompt_for_level(&self, base: &str, level: VerificationLevel, edit: bool) -> Result<String, WorkSplitError> {
+        let addendum = match level {
+            VerificationLevel::Normal => return Ok(base.to_string()),
+            VerificationLevel::Strict => VERIFY_STRICT_ADDENDUM,
+            VerificationLevel::Lenient => VERIFY_LENIENT_ADDENDUM,
+        };
+        let kind = if edit { "verify_edit" } else { "verify" };
+        let path = self.jobs_dir.join(format!("_systemprompt_{}_{}.md", kind, level.as_str()));
+        if path.exists() {
+            return Ok(fs::read_to_string(&path)?);
+        }
+        Ok(format!("{}\n\n{}", base, addendum))
+    }
+
+    /// Path of the custom system prompt `name` (`jobs/_systemprompt_<name>.md`)
+    pub fn named_prompt_path(&self, name: &str) -> PathBuf {
+        self.jobs_dir.join(format!("_systemprompt_{}.md", name))
+    }
+
+    /// Generation system prompt for `job`: the prompt named by its
+    /// `system_prompt`, or `default` (the mode's prompt) if it has none
+    pub fn generation_prompt(&self, default: &str, job: &Job) -> Result<String, WorkSplitError> {
+        match job.metadata.system_prompt {
+            Some(ref name) => self.load_system_prompt(&format!("_systemprompt_{}.md", name)),
+            None => Ok(default.to_string()),
+        }
+    }
+
+    /// Load the project's coding conventions (`jobs/_conventions.md`), if any
+    pub fn load_conventions(&self) -> Result<Option<String>, WorkSplitError> {
+        let path = self.jobs_dir.join(CONVENTIONS_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let conventions = fs::read_to_string(&path)?;
+        Ok(Some(conventions).filter(|c| !c.trim().is_empty()))
+    }
+
+    /// A creation or edit system prompt for `job`: `base` with the project
+    /// conventions appended, unless the job sets `include_conventions: false`
+    pub fn prompt_with_conventions(&self, base: &str, job: &Job) -> Result<String, WorkSplitError> {
+        if !job.metadata.include_conventions {
+            return Ok(base.to_string());
+        }
+        Ok(match self.load_conventions()? {
+            Some(conventions) => format!(
+                "{}\n\n[CONVENTIONS]\nFollow these project conventions:\n{}",
+                base.trim_end(),
+                conventions.trim()
+            ),
+            None => base.to_string(),
+        })
+    }
+
     /// Load the split mode system prompt
     pub fn load_split_prompt(&self) -> Result<String, WorkSplitError> {
         self.load_system_prompt(SPLIT_PROMPT_FILE)
@@ -270,15 +485,119 @@ impl JobsManager {
     }
 
     /// Load all context files for a job
+    ///
+    /// With `context_truncation` set, oversized files are shrunk using the
+    /// chosen strategy instead of being rejected. Missing files are
+    /// recovered as [`JobsManager::recover_missing_context`] describes.
     pub fn load_context_files(&mut self, job: &Job) -> Result<Vec<(PathBuf, String)>, WorkSplitError> {
-        let mut files = Vec::new();
-        for path in &job.metadata.context_files {
-            let content = self.load_context_file(path)?;
+        let mut paths = job.metadata.context_files.clone();
+        paths.extend(self.resolve_context_symbols(job)?);
+
+        let mut files: Vec<(PathBuf, String)> = Vec::new();
+        for path in &paths {
+            let path = &if self.project_root.join(path).exists() {
+                path.clone()
+            } else {
+                match self.recover_missing_context(job, path)? {
+                    Some(found) if !paths.contains(&found) && !files.iter().any(|(p, _)| *p == found) => found,
+                    _ => continue,
+                }
+            };
+            let content = match (self.load_context_file(path), job.metadata.context_truncation) {
+                (Err(WorkSplitError::ContextFileTooLarge { lines, max, .. }), Some(strategy)) => {
+                    let full_path = self.project_root.join(path);
+                    let entry = self.cache.get_or_load(&full_path).map_err(WorkSplitError::Io)?;
+                    info!(
+                        "Truncating context file '{}' ({} lines > {}) using {:?}",
+                        path.display(),
+                        lines,
+                        max,
+                        strategy
+                    );
+                    truncate_context(&entry.content, path, strategy, max, &job.instructions)
+                }
+                (result, _) => result?,
+            };
             files.push((path.clone(), content));
         }
         Ok(files)
     }
 
+    /// Stand-in for a context file that doesn't exist: with
+    /// `substitute_missing_context`, the project file of the same name
+    /// closest to its path. Otherwise the job goes on without it (`None`)
+    /// if it sets `allow_missing_context`, or fails.
+    fn recover_missing_context(&self, job: &Job, path: &Path) -> Result<Option<PathBuf>, WorkSplitError> {
+        if self.substitute_missing_context {
+            if let Some(found) = closest_by_file_name(&self.project_root, path)? {
+                warn!("Job '{}': context file {} not found, using {} instead", job.id, path.display(), found.display());
+                return Ok(Some(found));
+            }
+        }
+        if job.metadata.allow_missing_context {
+            warn!("Job '{}': context file {} not found, continuing without it", job.id, path.display());
+            return Ok(None);
+        }
+        Err(WorkSplitError::ContextFileNotFound(path.to_path_buf()))
+    }
+
+    /// Files defining the job's `context_symbols` that aren't already listed
+    /// in `context_files`. Refreshes `jobs/_index.json` first.
+    pub fn resolve_context_symbols(&mut self, job: &Job) -> Result<Vec<PathBuf>, WorkSplitError> {
+        let Some(symbols) = job.metadata.context_symbols.as_ref().filter(|s| !s.is_empty()) else {
+            return Ok(Vec::new());
+        };
+
+        let mut index = SymbolIndex::load(&self.jobs_dir);
+        if index.refresh(&self.project_root, &mut self.cache)? {
+            index.save(&self.jobs_dir)?;
+        }
+
+        let mut files: Vec<PathBuf> = Vec::new();
+        for name in symbols {
+            let path = index
+                .defining_files(name)
+                .first()
+                .map(|p| p.to_path_buf())
+                .ok_or_else(|| WorkSplitError::SymbolNotFound(name.clone()))?;
+            debug!("Context symbol '{}' resolved to {}", name, path.display());
+            if !job.metadata.context_files.contains(&path) && !files.contains(&path) {
+                files.push(path);
+            }
+        }
+
+        let count = job.metadata.context_files.len() + files.len();
+        if count > self.limits.max_context_files {
+            return Err(JobValidationError::TooManyContextFiles { count, max: self.limits.max_context_files }.into());
+        }
+        Ok(files)
+    }
+
+    /// Edit-mode target files with directory and glob entries expanded
+    pub fn resolve_target_files(&self, job: &Job) -> Result<Vec<PathBuf>, WorkSplitError> {
+        expand_target_files(&self.project_root, &job.metadata.get_target_files())
+    }
+
+    /// Fetch the job's context_urls (cached under jobs/_context_cache/)
+    pub async fn load_context_urls(
+        &self,
+        job: &Job,
+        config: &UrlContextConfig,
+    ) -> Result<Vec<(PathBuf, String)>, WorkSplitError> {
+        let Some(urls) = job.metadata.context_urls.as_ref().filter(|u| !u.is_empty()) else {
+            return Ok(Vec::new());
+        };
+
+        let loader = UrlContextLoader::new(&self.jobs_dir, config.clone())?;
+        let mut files = Vec::new();
+        for url in urls {
+            let content = loader.load(url).await?;
+            debug!("Loaded context URL '{}' ({} lines)", url, content.lines().count());
+            files.push((PathBuf::from(url), content));
+        }
+        Ok(files)
+    }
+
     /// Clear the file cache
     pub fn clear_cache(&mut self) {
         self.cache.clear();
@@ -288,8 +607,13 @@ impl JobsManager {
     pub fn check_file_sizes(&mut self, job: &Job) -> Result<(), WorkSplitError> {
         let limit = 900;
         
-        // Check context files
-        for path in &job.metadata.context_files {
+        // Check context files (oversized ones are truncated when a strategy is set)
+        let context_files: &[PathBuf] = if job.metadata.context_truncation.is_some() {
+            &[]
+        } else {
+            &job.metadata.context_files
+        };
+        for path in context_files {
             let full_path = self.project_root.join(path);
             if !full_path.exists() { continue; }
             let entry = self.cache.get_or_load(&full_path).map_err(WorkSplitError::Io)?;
@@ -310,7 +634,7 @@ impl JobsManager {
         }
         
         // Check target files (for edit mode)
-        for path in job.metadata.get_target_files() {
+        for path in self.resolve_target_files(job)? {
             let full_path = self.project_root.join(&path);
             if !full_path.exists() { continue; }
             let entry = self.cache.get_or_load(&full_path).map_err(WorkSplitError::Io)?;
@@ -362,7 +686,7 @@ impl JobsManager {
             .map(|(_, content)| Self::estimate_tokens(content))
             .sum();
         let instruction_tokens = Self::estimate_tokens(instructions);
-        let output_buffer = 1200; // Reserve for 900 LOC output
+        let output_buffer = crate::core::token_budget::OUTPUT_RESERVE_TOKENS;
 
         let total = system_tokens + context_tokens + instruction_tokens + output_buffer;
         
@@ -401,6 +725,287 @@ mod tests {
         assert_eq!(id, "my_job_001");
     }
 
+    #[test]
+    fn test_verify_prompt_for_level() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("jobs")).unwrap();
+        let manager = JobsManager::new(temp_dir.path().to_path_buf(), LimitsConfig::default());
+
+        assert_eq!(manager.verify_prompt_for_level("Check it", VerificationLevel::Normal, false).unwrap(), "Check it");
+        let strict = manager.verify_prompt_for_level("Check it", VerificationLevel::Strict, false).unwrap();
+        assert!(strict.starts_with("Check it\n\nSTRICT VERIFICATION"));
+
+        fs::write(temp_dir.path().join("jobs/_systemprompt_verify_edit_lenient.md"), "Be kind").unwrap();
+        assert_eq!(manager.verify_prompt_for_level("Check it", VerificationLevel::Lenient, true).unwrap(), "Be kind");
+        let lenient = manager.verify_prompt_for_level("Check it", VerificationLevel::Lenient, false).unwrap();
+        assert!(lenient.contains("LENIENT VERIFICATION"));
+    }
+
+    #[test]
+    fn test_generation_prompt() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("jobs")).unwrap();
+        fs::write(temp_dir.path().join("jobs/001_a.md"), "---\noutput_dir: src/\noutput_file: a.rs\n---\nDo it\n").unwrap();
+        fs::write(
+            temp_dir.path().join("jobs/002_b.md"),
+            "---\nsystem_prompt: migrations\noutput_dir: src/\noutput_file: b.rs\n---\nDo it\n",
+        )
+        .unwrap();
+        let manager = JobsManager::new(temp_dir.path().to_path_buf(), LimitsConfig::default());
+        let (a, b) = (manager.parse_job("001_a").unwrap(), manager.parse_job("002_b").unwrap());
+
+        assert_eq!(manager.generation_prompt("Write code", &a).unwrap(), "Write code");
+        assert!(matches!(manager.generation_prompt("Write code", &b), Err(WorkSplitError::SystemPromptNotFound(_))));
+
+        fs::write(manager.named_prompt_path("migrations"), "Write SQL migrations").unwrap();
+        assert_eq!(manager.generation_prompt("Write code", &b).unwrap(), "Write SQL migrations");
+        assert_eq!(manager.discover_jobs().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_instructions_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("jobs")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("specs")).unwrap();
+        fs::write(temp_dir.path().join("specs/auth.md"), "Tokens expire after an hour.\n").unwrap();
+        fs::write(temp_dir.path().join("jobs/001_a.md"),
+            "---\ninstructions_file: specs/auth.md\noutput_dir: src/\noutput_file: a.rs\n---\nWrite the login handler.\n").unwrap();
+        fs::write(temp_dir.path().join("jobs/002_b.md"),
+            "---\ninstructions_file: specs/auth.md\noutput_dir: src/\noutput_file: b.rs\n---\n").unwrap();
+        fs::write(temp_dir.path().join("jobs/003_c.md"),
+            "---\ninstructions_file: specs/missing.md\noutput_dir: src/\noutput_file: c.rs\n---\n").unwrap();
+        let manager = JobsManager::new(temp_dir.path().to_path_buf(), LimitsConfig::default());
+
+        assert_eq!(manager.parse_job("001_a").unwrap().instructions, "Tokens expire after an hour.\n\nWrite the login handler.");
+        assert_eq!(manager.parse_job("002_b").unwrap().instructions, "Tokens expire after an hour.");
+        assert!(matches!(manager.parse_job("003_c"),
+            Err(WorkSplitError::JobParsing(JobParseError::InstructionsFileError(_, _, _)))));
+    }
+
+    #[test]
+    fn test_instructions_file_outside_project() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(project.join("jobs")).unwrap();
+        fs::create_dir_all(project.join("packages/api")).unwrap();
+        fs::write(temp_dir.path().join("secrets.env"), "TOKEN=1\n").unwrap();
+        let outside = temp_dir.path().join("secrets.env");
+        for (id, frontmatter) in [
+            ("001_parent", "instructions_file: ../secrets.env".to_string()),
+            ("002_absolute", format!("instructions_file: {}", outside.display())),
+            ("003_workdir", "workdir: packages/api\ninstructions_file: ../../../secrets.env".to_string()),
+        ] {
+            fs::write(project.join(format!("jobs/{}.md", id)),
+                format!("---\n{}\noutput_dir: src/\noutput_file: a.rs\n---\nDo it\n", frontmatter)).unwrap();
+        }
+        let manager = JobsManager::new(project.clone(), LimitsConfig::default());
+
+        for id in ["001_parent", "002_absolute", "003_workdir"] {
+            assert!(matches!(manager.parse_job(id),
+                Err(WorkSplitError::JobValidation(JobValidationError::InstructionsFileOutsideProject(_)))), "{}", id);
+        }
+
+        // A symlink inside the project that points out of it
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&outside, project.join("specs.md")).unwrap();
+            fs::write(project.join("jobs/004_link.md"),
+                "---\ninstructions_file: specs.md\noutput_dir: src/\noutput_file: a.rs\n---\nDo it\n").unwrap();
+            assert!(matches!(manager.parse_job("004_link"),
+                Err(WorkSplitError::JobValidation(JobValidationError::InstructionsFileOutsideProject(_)))));
+        }
+    }
+
+    #[test]
+    fn test_prompt_with_conventions() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("jobs")).unwrap();
+        fs::write(temp_dir.path().join("jobs/001_a.md"), "---\noutput_dir: src/\noutput_file: a.rs\n---\nDo it\n").unwrap();
+        fs::write(
+            temp_dir.path().join("jobs/002_b.md"),
+            "---\ninclude_conventions: false\noutput_dir: src/\noutput_file: b.rs\n---\nDo it\n",
+        )
+        .unwrap();
+        let manager = JobsManager::new(temp_dir.path().to_path_buf(), LimitsConfig::default());
+        let (a, b) = (manager.parse_job("001_a").unwrap(), manager.parse_job("002_b").unwrap());
+
+        assert_eq!(manager.prompt_with_conventions("Write code", &a).unwrap(), "Write code");
+
+        fs::write(temp_dir.path().join("jobs/_conventions.md"), "- Use thiserror for errors\n").unwrap();
+        assert_eq!(
+            manager.prompt_with_conventions("Write code\n", &a).unwrap(),
+            "Write code\n\n[CONVENTIONS]\nFollow these project conventions:\n- Use thiserror for errors"
+        );
+        assert_eq!(manager.prompt_with_conventions("Write code", &b).unwrap(), "Write code");
+        assert_eq!(manager.discover_jobs().unwrap().len(), 2);
+    }
+
+    fn write_oversized_context_job(temp_dir: &std::path::Path, truncation: &str) -> JobsManager {
+        fs::create_dir_all(temp_dir.join("jobs")).unwrap();
+        let big: String = (0..50).map(|i| format!("// line {}\n", i)).collect();
+        fs::write(temp_dir.join("big.rs"), big).unwrap();
+        fs::write(
+            temp_dir.join("jobs/001_big.md"),
+            format!(
+                "---\ncontext_files:\n  - big.rs\n{}output_dir: src/\noutput_file: out.rs\n---\nDo it\n",
+                truncation
+            ),
+        )
+        .unwrap();
+        let limits = LimitsConfig {
+            max_context_lines: 10,
+            ..Default::default()
+        };
+        JobsManager::new(temp_dir.to_path_buf(), limits)
+    }
+
+    #[test]
+    fn test_oversized_context_rejected_without_truncation() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut manager = write_oversized_context_job(temp_dir.path(), "");
+        let job = manager.parse_job("001_big").unwrap();
+        let result = manager.load_context_files(&job);
+        assert!(matches!(result, Err(WorkSplitError::ContextFileTooLarge { .. })));
+    }
+
+    #[test]
+    fn test_oversized_context_truncated_with_strategy() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut manager = write_oversized_context_job(temp_dir.path(), "context_truncation: head\n");
+        let job = manager.parse_job("001_big").unwrap();
+        let files = manager.load_context_files(&job).unwrap();
+        assert_eq!(files[0].1.lines().count(), 10);
+        assert!(files[0].1.ends_with("[... 41 lines omitted ...]"));
+    }
+
+    #[test]
+    fn test_missing_context_recovery() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("jobs")).unwrap();
+        fs::create_dir_all(root.join("src/models")).unwrap();
+        fs::write(root.join("src/models/user.rs"), "pub struct User;\n").unwrap();
+        let job = |id: &str, extra: &str| {
+            fs::write(
+                root.join(format!("jobs/{}.md", id)),
+                format!("---\ncontext_files: [src/model/user.rs, src/gone.rs]\n{}output_dir: src/\noutput_file: out.rs\n---\nDo it\n", extra),
+            )
+            .unwrap();
+        };
+        job("001_strict", "");
+        job("002_lenient", "allow_missing_context: true\n");
+
+        let mut manager = JobsManager::new(root.to_path_buf(), LimitsConfig::default());
+        let strict = manager.parse_job("001_strict").unwrap();
+        let lenient = manager.parse_job("002_lenient").unwrap();
+        assert!(matches!(manager.load_context_files(&strict), Err(WorkSplitError::ContextFileNotFound(_))));
+        assert!(manager.load_context_files(&lenient).unwrap().is_empty());
+
+        manager.set_substitute_missing_context(true);
+        // src/gone.rs has no namesake to stand in for it
+        assert!(matches!(manager.load_context_files(&strict), Err(WorkSplitError::ContextFileNotFound(_))));
+        let files = manager.load_context_files(&lenient).unwrap();
+        assert_eq!(files, vec![(PathBuf::from("src/models/user.rs"), "pub struct User;\n".to_string())]);
+    }
+
+    #[test]
+    fn test_context_symbols_resolve_to_defining_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("jobs")).unwrap();
+        fs::create_dir_all(root.join("src/services")).unwrap();
+        fs::write(root.join("src/services/user.rs"), "pub struct UserService;\n").unwrap();
+        fs::write(root.join("src/config.rs"), "pub struct Config;\n").unwrap();
+        fs::write(
+            root.join("jobs/001_sym.md"),
+            "---\ncontext_files:\n  - src/config.rs\ncontext_symbols: [UserService, Config]\noutput_dir: src/\noutput_file: out.rs\n---\nDo it\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("jobs/002_missing.md"),
+            "---\ncontext_symbols: [Missing]\noutput_dir: src/\noutput_file: out.rs\n---\nDo it\n",
+        )
+        .unwrap();
+
+        let mut manager = JobsManager::new(root.to_path_buf(), LimitsConfig::default());
+        let job = manager.parse_job("001_sym").unwrap();
+        let files = manager.load_context_files(&job).unwrap();
+        let paths: Vec<&PathBuf> = files.iter().map(|(p, _)| p).collect();
+        assert_eq!(paths, vec![&PathBuf::from("src/config.rs"), &PathBuf::from("src/services/user.rs")]);
+        assert!(root.join("jobs/_index.json").exists());
+
+        let job = manager.parse_job("002_missing").unwrap();
+        assert!(matches!(manager.load_context_files(&job), Err(WorkSplitError::SymbolNotFound(_))));
+    }
+
+    #[test]
+    fn test_discover_jobs_respects_worksplitignore() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("jobs")).unwrap();
+        for name in ["001_a.md", "002_wip.md", "_manager.md", "notes.txt"] {
+            fs::write(root.join("jobs").join(name), "").unwrap();
+        }
+        let manager = JobsManager::new(root.to_path_buf(), LimitsConfig::default());
+        assert_eq!(manager.discover_jobs().unwrap(), vec!["001_a", "002_wip"]);
+
+        fs::write(root.join(".worksplitignore"), "jobs/*_wip.md\n").unwrap();
+        assert_eq!(manager.discover_jobs().unwrap(), vec!["001_a"]);
+    }
+
+    #[test]
+    fn test_select_jobs() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("jobs")).unwrap();
+        for (id, tags) in [("auth_001", "[benchmark, backend]"), ("auth_002", "[]"), ("ui_001", "[benchmark]")] {
+            fs::write(
+                root.join(format!("jobs/{}.md", id)),
+                format!("---\ntags: {}\noutput_dir: src/\noutput_file: {}.rs\n---\nDo it\n", tags, id),
+            )
+            .unwrap();
+        }
+        let manager = JobsManager::new(root.to_path_buf(), LimitsConfig::default());
+        let select = |selectors: &[&str]| {
+            manager.select_jobs(&selectors.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+        };
+
+        assert_eq!(select(&["tag:benchmark"]).unwrap(), vec!["auth_001", "ui_001"]);
+        assert_eq!(select(&["auth_*"]).unwrap(), vec!["auth_001", "auth_002"]);
+        assert_eq!(select(&["ui_001", "tag:backend", "auth_001"]).unwrap(), vec!["auth_001", "ui_001"]);
+        assert!(select(&["tag:none"]).unwrap().is_empty());
+        assert!(select(&["nope"]).unwrap_err().to_string().contains("No job named 'nope'"));
+    }
+
+    #[test]
+    fn test_job_defaults_fill_in_missing_fields() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("jobs")).unwrap();
+        fs::write(
+            temp_dir.path().join("worksplit.toml"),
+            "[job_defaults]\noutput_dir = \"src/gen/\"\nverify = false\ncontext_files = [\"src/lib.rs\"]\n",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("jobs/001_a.md"), "---\noutput_file: a.rs\n---\nDo it\n").unwrap();
+        fs::write(
+            temp_dir.path().join("jobs/002_b.md"),
+            "---\ncontext_files: [src/b.rs, src/lib.rs]\noutput_dir: src/\noutput_file: b.rs\nverify: true\n---\nDo it\n",
+        )
+        .unwrap();
+        let manager = JobsManager::new(temp_dir.path().to_path_buf(), LimitsConfig::default());
+
+        let a = manager.parse_job("001_a").unwrap();
+        assert_eq!(a.metadata.output_path(), PathBuf::from("src/gen/a.rs"));
+        assert!(!a.metadata.verify);
+        assert_eq!(a.metadata.context_files, vec![PathBuf::from("src/lib.rs")]);
+
+        let b = manager.parse_job("002_b").unwrap();
+        assert_eq!(b.metadata.output_path(), PathBuf::from("src/b.rs"));
+        assert!(b.metadata.verify);
+        assert_eq!(b.metadata.context_files, vec![PathBuf::from("src/b.rs"), PathBuf::from("src/lib.rs")]);
+        assert_eq!(b.metadata.sequential, None);
+    }
+
     #[test]
     fn test_load_test_prompt() {
         // This test would require a mock file system setup
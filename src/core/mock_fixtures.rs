@@ -0,0 +1,201 @@
+//! Canned model responses for `worksplit run --mock <fixture_dir>`
+//!
+//! Responses are read per job instead of calling Ollama, so the parsing,
+//! writing and verification pipeline can be exercised deterministically:
+//!
+//! - `<fixture_dir>/<job_id>/`: every file is one response, consumed in
+//!   file name order (`01_create.md`, `02_verify.txt`, ...). Running out of
+//!   responses is an error.
+//! - `<fixture_dir>/<job_id>.md` (or `.txt`): the first response; every
+//!   later request (verification, retries) gets `PASS`.
+//!
+//! A recording made with `run --record` can be served the same way
+//! (`run --replay <recording>`): each job gets its recorded responses in order.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use tracing::warn;
+
+use crate::core::recording::{load_recording, RecordedExchange};
+use crate::error::OllamaError;
+
+/// Response given once a single-file fixture has been used
+const SINGLE_FILE_FOLLOW_UP: &str = "PASS";
+
+/// Fixture responses for a mocked run
+#[derive(Debug)]
+pub struct MockFixtures {
+    source: MockSource,
+    /// Responses already served per job
+    served: Mutex<HashMap<String, usize>>,
+}
+
+#[derive(Debug)]
+enum MockSource {
+    Dir(PathBuf),
+    Recording { path: PathBuf, exchanges: HashMap<String, Vec<RecordedExchange>> },
+}
+
+impl MockFixtures {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, OllamaError> {
+        let dir = dir.into();
+        if !dir.is_dir() {
+            return Err(OllamaError::MockFixture(format!(
+                "Fixture directory {} does not exist",
+                dir.display()
+            )));
+        }
+        Ok(Self { source: MockSource::Dir(dir), served: Mutex::new(HashMap::new()) })
+    }
+
+    /// Serve the responses of a `run --record` recording
+    pub fn from_recording(path: impl Into<PathBuf>) -> Result<Self, OllamaError> {
+        let path = path.into();
+        let recorded = load_recording(&path).map_err(|e| OllamaError::MockFixture(e.to_string()))?;
+        let mut exchanges: HashMap<String, Vec<RecordedExchange>> = HashMap::new();
+        for exchange in recorded {
+            if let Some(job_id) = exchange.job_id.clone() {
+                exchanges.entry(job_id).or_default().push(exchange);
+            }
+        }
+        Ok(Self { source: MockSource::Recording { path, exchanges }, served: Mutex::new(HashMap::new()) })
+    }
+
+    /// The next canned response for `job_id`, which is about to be sent `prompt`
+    pub fn next_response(&self, job_id: Option<&str>, prompt: &str) -> Result<String, OllamaError> {
+        let job_id = job_id.ok_or_else(|| {
+            OllamaError::MockFixture("No job is running; mock responses are served per job".to_string())
+        })?;
+        let mut served = self.served.lock().unwrap();
+        let index = *served.get(job_id).unwrap_or(&0);
+        let response = match &self.source {
+            MockSource::Dir(dir) => dir_response(dir, job_id, index)?,
+            MockSource::Recording { path, exchanges } => {
+                let recorded = exchanges.get(job_id).map(Vec::as_slice).unwrap_or_default();
+                let exchange = recorded.get(index).ok_or_else(|| {
+                    OllamaError::MockFixture(format!(
+                        "Job '{}' made request {} but {} recorded only {} for it",
+                        job_id,
+                        index + 1,
+                        path.display(),
+                        recorded.len()
+                    ))
+                })?;
+                if exchange.prompt != prompt {
+                    warn!("Prompt {} of job '{}' differs from the recording", index + 1, job_id);
+                }
+                exchange.response.clone()
+            }
+        };
+        served.insert(job_id.to_string(), index + 1);
+        Ok(response)
+    }
+}
+
+fn dir_response(dir: &Path, job_id: &str, index: usize) -> Result<String, OllamaError> {
+    let job_dir = dir.join(job_id);
+    if job_dir.is_dir() {
+        let files = sorted_files(&job_dir)?;
+        let path = files.get(index).ok_or_else(|| {
+            OllamaError::MockFixture(format!(
+                "Job '{}' made request {} but {} has only {} response file(s)",
+                job_id,
+                index + 1,
+                job_dir.display(),
+                files.len()
+            ))
+        })?;
+        return read(path);
+    }
+
+    for extension in ["md", "txt"] {
+        let path = dir.join(format!("{}.{}", job_id, extension));
+        if path.is_file() {
+            return if index == 0 { read(&path) } else { Ok(SINGLE_FILE_FOLLOW_UP.to_string()) };
+        }
+    }
+
+    Err(OllamaError::MockFixture(format!(
+        "No fixture for job '{}': add {}/ or {}.md",
+        job_id,
+        job_dir.display(),
+        job_dir.display()
+    )))
+}
+
+fn sorted_files(dir: &Path) -> Result<Vec<PathBuf>, OllamaError> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| OllamaError::MockFixture(format!("Cannot read {}: {}", dir.display(), e)))?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn read(path: &Path) -> Result<String, OllamaError> {
+    fs::read_to_string(path)
+        .map_err(|e| OllamaError::MockFixture(format!("Cannot read {}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_directory_fixtures_in_order() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("001_a")).unwrap();
+        fs::write(temp.path().join("001_a/02_verify.txt"), "PASS").unwrap();
+        fs::write(temp.path().join("001_a/01_create.md"), "```rust\nfn a() {}\n```").unwrap();
+
+        let mock = MockFixtures::new(temp.path()).unwrap();
+        assert!(matches!(mock.next_response(None, ""), Err(OllamaError::MockFixture(_))));
+
+        let job = Some("001_a");
+        assert!(mock.next_response(job, "").unwrap().contains("fn a()"));
+        assert_eq!(mock.next_response(job, "").unwrap(), "PASS");
+        let err = mock.next_response(job, "").unwrap_err().to_string();
+        assert!(err.contains("only 2 response file(s)"), "{}", err);
+    }
+
+    #[test]
+    fn test_single_file_fixture() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("002_b.md"), "first").unwrap();
+
+        let mock = MockFixtures::new(temp.path()).unwrap();
+        assert_eq!(mock.next_response(Some("002_b"), "").unwrap(), "first");
+        assert_eq!(mock.next_response(Some("002_b"), "").unwrap(), SINGLE_FILE_FOLLOW_UP);
+        assert!(mock.next_response(Some("003_missing"), "").unwrap_err().to_string().contains("No fixture for job '003_missing'"));
+        assert!(MockFixtures::new(temp.path().join("nope")).is_err());
+    }
+
+    #[test]
+    fn test_replay_recording_per_job() {
+        let temp = TempDir::new().unwrap();
+        let exchange = |job: &str, response: &str| {
+            serde_json::to_string(&RecordedExchange {
+                job_id: Some(job.to_string()),
+                system_prompt: None,
+                prompt: "prompt".to_string(),
+                response: response.to_string(),
+            })
+            .unwrap()
+        };
+        let recording = temp.path().join("run.jsonl");
+        let lines = [exchange("001_a", "a1"), exchange("002_b", "b1"), exchange("001_a", "a2")];
+        fs::write(&recording, lines.join("\n")).unwrap();
+
+        let mock = MockFixtures::from_recording(&recording).unwrap();
+        assert_eq!(mock.next_response(Some("002_b"), "prompt").unwrap(), "b1");
+        assert_eq!(mock.next_response(Some("001_a"), "prompt").unwrap(), "a1");
+        assert_eq!(mock.next_response(Some("001_a"), "changed prompt").unwrap(), "a2");
+        assert!(mock.next_response(Some("001_a"), "prompt").unwrap_err().to_string().contains("recorded only 2"));
+    }
+}
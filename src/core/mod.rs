@@ -1,12 +1,57 @@
+pub mod anchors;
+pub mod atomic_write;
+pub mod budget;
+pub mod build_output;
+pub mod candidates;
+pub mod checkers;
+pub mod ci;
 pub mod config;
+pub mod context;
 pub mod dependency;
+#[cfg(feature = "embeddings")]
+pub mod embeddings;
+pub mod external_verify;
+pub mod failure_artifacts;
+pub mod feedback;
 pub mod file_cache;
+pub mod freeze;
+pub mod history;
+pub mod ignore;
+pub mod injection;
+pub mod job_lint;
+pub mod job_templates;
 pub mod jobs;
+pub mod mock_fixtures;
+pub mod module_index;
+pub mod notify;
+pub mod offline;
 pub mod ollama;
+pub mod output_paths;
+pub mod output_size;
 pub mod parser;
+pub mod postprocess;
+pub mod progress;
+pub mod projects;
+pub mod prompt_templates;
 pub mod prompts;
+pub mod provenance;
+pub mod recording;
+pub mod retry_policy;
 pub mod runner;
+pub mod schema;
+pub mod spool;
+pub mod staging;
 pub mod status;
+#[cfg(feature = "sqlite")]
+pub mod status_db;
+pub mod status_webhook;
+pub mod stream_log;
+pub mod symbols;
+pub mod targets;
+pub mod timings;
+pub mod token_budget;
+pub mod truncate;
+pub mod url_context;
 
 pub use config::*;
 pub use jobs::*;
@@ -0,0 +1,261 @@
+//! Deterministic `mod.rs` / `index.ts` / `__init__.py` updates
+//!
+//! With `generate_module_index: true`, every generated source file gets a
+//! declaration in the module index of its directory, so split and
+//! multi-file outputs are wired into the build without another LLM call.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use tracing::warn;
+
+/// Module index kinds WorkSplit knows how to update
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IndexKind {
+    Rust,
+    TypeScript,
+    JavaScript,
+    Python,
+}
+
+impl IndexKind {
+    fn of(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(Self::Rust),
+            "ts" | "tsx" => Some(Self::TypeScript),
+            "js" | "jsx" | "mjs" => Some(Self::JavaScript),
+            "py" => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    /// Declaration added to the index for module `stem`
+    fn declaration(self, stem: &str) -> String {
+        match self {
+            Self::Rust => format!("pub mod {};", stem),
+            Self::TypeScript | Self::JavaScript => format!("export * from './{}';", stem),
+            Self::Python => format!("from . import {}", stem),
+        }
+    }
+
+    /// Whether `content` already declares module `stem` (in any form)
+    fn declares(self, content: &str, stem: &str) -> bool {
+        let stem = regex::escape(stem);
+        let pattern = match self {
+            Self::Rust => format!(r"(?m)^\s*(pub(\([^)]*\))?\s+)?mod\s+{}\s*[;{{]", stem),
+            Self::TypeScript | Self::JavaScript => format!(r#"from\s+['"]\./{}(\.[jt]sx?)?['"]"#, stem),
+            Self::Python => format!(r"(?m)^\s*from\s+\.(\s+import\s+.*\b{0}\b|{0}\s+import)", stem),
+        };
+        Regex::new(&pattern).is_ok_and(|re| re.is_match(content))
+    }
+}
+
+/// Whether `path` is itself a module index (or crate root)
+pub fn is_index_file(path: &Path) -> bool {
+    matches!(
+        path.file_name().and_then(|n| n.to_str()),
+        Some("mod.rs" | "lib.rs" | "main.rs" | "index.ts" | "index.tsx" | "index.js" | "__init__.py")
+    )
+}
+
+/// Whether Rust files in `dir` are declared by a crate root (`lib.rs`,
+/// `main.rs`) rather than a `mod.rs`: `src/`, `src/bin/`, `tests/`,
+/// `examples/` and `benches/`
+fn is_crate_root_dir(dir: &Path) -> bool {
+    dir.ends_with("src/bin")
+        || matches!(dir.file_name().and_then(|n| n.to_str()), Some("src" | "tests" | "examples" | "benches"))
+}
+
+/// The index file that should declare `file`. For Rust an existing
+/// `dir/mod.rs`, sibling `dir.rs`, `lib.rs` or `main.rs` is preferred, in
+/// that order; otherwise `dir/mod.rs` is created. A crate root is never
+/// created: without `lib.rs` or `main.rs` there is no index.
+fn index_for(file: &Path, kind: IndexKind, exists: &dyn Fn(&Path) -> bool) -> Option<PathBuf> {
+    let dir = file.parent().unwrap_or(Path::new(""));
+    match kind {
+        IndexKind::Rust => {
+            let crate_root = is_crate_root_dir(dir);
+            let mut candidates = Vec::new();
+            if !crate_root {
+                candidates.push(dir.join("mod.rs"));
+                if dir.file_name().is_some() {
+                    candidates.push(dir.with_extension("rs"));
+                }
+            }
+            candidates.push(dir.join("lib.rs"));
+            candidates.push(dir.join("main.rs"));
+            match candidates.into_iter().find(|c| exists(c)) {
+                Some(index) => Some(index),
+                None if crate_root => None,
+                None => Some(dir.join("mod.rs")),
+            }
+        }
+        IndexKind::TypeScript => Some(dir.join("index.ts")),
+        IndexKind::JavaScript => Some(dir.join("index.js")),
+        IndexKind::Python => Some(dir.join("__init__.py")),
+    }
+}
+
+/// Compute index updates for `files` (project-relative). `read` returns the
+/// current content of a file, or `None` if it doesn't exist. Returns each
+/// index that changes with its new content; indexes that already declare
+/// every module are left out. A newly created index is itself declared in
+/// the index of its parent directory.
+pub fn index_updates(files: &[PathBuf], read: &dyn Fn(&Path) -> Option<String>) -> Vec<(PathBuf, String)> {
+    let exists = |path: &Path| read(path).is_some();
+    let mut updates: Vec<(PathBuf, String)> = Vec::new();
+    let mut pending: VecDeque<PathBuf> = files.iter().cloned().collect();
+
+    while let Some(file) = pending.pop_front() {
+        let Some(kind) = IndexKind::of(&file) else { continue };
+        let Some(stem) = file.file_stem().and_then(|s| s.to_str()) else { continue };
+        // Files at the project root have no module index to join
+        if is_index_file(&file) || file.parent().is_none_or(|dir| dir.as_os_str().is_empty()) {
+            continue;
+        }
+
+        let Some(index) = index_for(&file, kind, &exists) else {
+            warn!("No lib.rs or main.rs declares {}; add `mod {};` to the crate root yourself", file.display(), stem);
+            continue;
+        };
+        let position = updates.iter().position(|(path, _)| *path == index);
+        let existing = match position {
+            Some(i) => Some(updates[i].1.clone()),
+            None => read(&index),
+        };
+        let created = existing.is_none() && !files.contains(&index);
+        let current = existing.unwrap_or_default();
+        if kind.declares(&current, stem) {
+            continue;
+        }
+
+        let mut content = current.trim_end().to_string();
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        content.push_str(&kind.declaration(stem));
+        content.push('\n');
+
+        match position {
+            Some(i) => updates[i].1 = content,
+            None => updates.push((index.clone(), content)),
+        }
+        if created {
+            // `dir/mod.rs` is declared as `mod dir;` one level up, and so on
+            if let Some(dir) = index.parent().filter(|d| d.file_name().is_some()) {
+                let extension = file.extension().unwrap_or_default();
+                pending.push_back(dir.with_extension(extension));
+            }
+        }
+    }
+    updates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn reader(files: &[(&str, &str)]) -> impl Fn(&Path) -> Option<String> {
+        let map: HashMap<PathBuf, String> =
+            files.iter().map(|(p, c)| (PathBuf::from(p), c.to_string())).collect();
+        move |path| map.get(path).cloned()
+    }
+
+    #[test]
+    fn test_index_files() {
+        assert!(is_index_file(Path::new("src/handlers/mod.rs")));
+        assert!(is_index_file(Path::new("src/lib.rs")));
+        assert!(is_index_file(Path::new("src/main.rs")));
+        assert!(is_index_file(Path::new("web/src/index.ts")));
+        assert!(is_index_file(Path::new("web/src/index.tsx")));
+        assert!(is_index_file(Path::new("pkg/__init__.py")));
+        assert!(!is_index_file(Path::new("src/handlers/users.rs")));
+        assert!(!is_index_file(Path::new("web/src/main.ts")));
+    }
+
+    #[test]
+    fn test_crate_root_is_never_created() {
+        let read = reader(&[]);
+        let files = vec![
+            PathBuf::from("src/services/auth.rs"),
+            PathBuf::from("src/config.rs"),
+            PathBuf::from("tests/helpers.rs"),
+            PathBuf::from("src/bin/tool.rs"),
+        ];
+        assert_eq!(
+            index_updates(&files, &read),
+            vec![(PathBuf::from("src/services/mod.rs"), "pub mod auth;\n".to_string())]
+        );
+
+        let read = reader(&[("src/main.rs", "fn main() {}\n")]);
+        assert_eq!(
+            index_updates(&[PathBuf::from("src/config.rs")], &read),
+            vec![(PathBuf::from("src/main.rs"), "fn main() {}\npub mod config;\n".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_rust_index_updates() {
+        let read = reader(&[
+            ("src/handlers/mod.rs", "pub mod users;\n"),
+            ("src/lib.rs", "pub mod handlers;\n"),
+        ]);
+        let files = vec![
+            PathBuf::from("src/handlers/users.rs"),
+            PathBuf::from("src/handlers/orders.rs"),
+            PathBuf::from("src/config.rs"),
+            PathBuf::from("src/models/user.rs"),
+            PathBuf::from("src/models/mod.rs"),
+            PathBuf::from("src/services/auth.rs"),
+        ];
+        let updates = index_updates(&files, &read);
+
+        assert_eq!(
+            updates,
+            vec![
+                (PathBuf::from("src/handlers/mod.rs"), "pub mod users;\npub mod orders;\n".to_string()),
+                (
+                    PathBuf::from("src/lib.rs"),
+                    "pub mod handlers;\npub mod config;\npub mod services;\n".to_string()
+                ),
+                (PathBuf::from("src/models/mod.rs"), "pub mod user;\n".to_string()),
+                (PathBuf::from("src/services/mod.rs"), "pub mod auth;\n".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rust_sibling_module_file_preferred() {
+        let read = reader(&[("src/core.rs", "mod helpers;\n")]);
+        let files = vec![PathBuf::from("src/core/helpers.rs"), PathBuf::from("src/core/runner.rs")];
+        assert_eq!(
+            index_updates(&files, &read),
+            vec![(PathBuf::from("src/core.rs"), "mod helpers;\npub mod runner;\n".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_typescript_and_python_indexes() {
+        let read = reader(&[("web/api/index.ts", "export * from \"./users\";\n")]);
+        let files = vec![
+            PathBuf::from("web/api/users.ts"),
+            PathBuf::from("web/api/orders.ts"),
+            PathBuf::from("pkg/models/user.py"),
+            PathBuf::from("README.md"),
+            PathBuf::from("build.rs"),
+        ];
+        assert_eq!(
+            index_updates(&files, &read),
+            vec![
+                (
+                    PathBuf::from("web/api/index.ts"),
+                    "export * from \"./users\";\nexport * from './orders';\n".to_string()
+                ),
+                (PathBuf::from("pkg/models/__init__.py"), "from . import user\n".to_string()),
+                (PathBuf::from("pkg/__init__.py"), "from . import models\n".to_string()),
+            ]
+        );
+    }
+}
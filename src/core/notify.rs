@@ -0,0 +1,315 @@
+//! Notifications when a run finishes or a job fails (`[notify]`)
+//!
+//! Notifications go to the desktop and/or a webhook. A notification that
+//! can't be delivered is logged; it never fails the run.
+
+use reqwest::Client;
+use serde::Serialize;
+use std::path::Path;
+#[cfg(feature = "notify")]
+use std::process::{Command, Stdio};
+#[cfg(feature = "notify")]
+use std::time::Duration;
+#[cfg(feature = "notify")]
+use tracing::debug;
+use tracing::warn;
+
+use crate::core::runner::RunSummary;
+use crate::models::{FailureKind, JobStatus, NotifyConfig};
+
+/// Discord rejects messages over 2000 characters
+#[cfg(feature = "notify")]
+const MAX_MESSAGE_CHARS: usize = 1900;
+
+/// A job listed in a run summary notification
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FailedJob {
+    pub job_id: String,
+    pub error: Option<String>,
+}
+
+/// What happened
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Notification {
+    RunFinished {
+        processed: usize,
+        passed: usize,
+        failed: usize,
+        skipped: usize,
+        failed_jobs: Vec<FailedJob>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        budget_exhausted: Option<String>,
+    },
+    JobFailed {
+        job_id: String,
+        error: Option<String>,
+        failure: Option<FailureKind>,
+    },
+}
+
+impl Notification {
+    pub fn run_finished(summary: &RunSummary) -> Self {
+        Notification::RunFinished {
+            processed: summary.processed,
+            passed: summary.passed,
+            failed: summary.failed,
+            skipped: summary.skipped,
+            failed_jobs: summary
+                .results
+                .iter()
+                .filter(|r| r.status == JobStatus::Fail)
+                .map(|r| FailedJob { job_id: r.job_id.clone(), error: r.error.clone() })
+                .collect(),
+            budget_exhausted: summary.budget_exhausted.clone(),
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Notification::RunFinished { failed: 0, .. } => "WorkSplit run finished",
+            Notification::RunFinished { .. } => "WorkSplit run finished with failures",
+            Notification::JobFailed { .. } => "WorkSplit job failed",
+        }
+    }
+
+    /// Plain-text description
+    pub fn body(&self) -> String {
+        match self {
+            Notification::RunFinished { processed, passed, failed, skipped, failed_jobs, budget_exhausted } => {
+                let mut body = format!("{} processed: {} passed, {} failed", processed, passed, failed);
+                if *skipped > 0 {
+                    body.push_str(&format!(", {} skipped", skipped));
+                }
+                if let Some(reason) = budget_exhausted {
+                    body.push_str(&format!(" (run budget: {})", reason));
+                }
+                for job in failed_jobs {
+                    body.push_str(&format!("\n- {}", job.job_id));
+                    if let Some(error) = &job.error {
+                        body.push_str(&format!(": {}", first_line(error)));
+                    }
+                }
+                body
+            }
+            Notification::JobFailed { job_id, error, failure } => {
+                let mut body = job_id.clone();
+                if let Some(kind) = failure {
+                    body.push_str(&format!(" [{}]", kind));
+                }
+                if let Some(error) = error {
+                    body.push_str(&format!(": {}", first_line(error)));
+                }
+                body
+            }
+        }
+    }
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default()
+}
+
+/// Body POSTed to the webhook: `text` for Slack, `content` for Discord,
+/// the rest for anything else
+#[cfg(feature = "notify")]
+#[derive(Debug, Serialize)]
+struct WebhookPayload<'a> {
+    text: &'a str,
+    content: &'a str,
+    project: &'a str,
+    #[serde(flatten)]
+    notification: &'a Notification,
+}
+
+/// Project directory name, to tell runs of different projects apart
+pub fn project_name(project_root: &Path) -> String {
+    project_root
+        .canonicalize()
+        .unwrap_or_else(|_| project_root.to_path_buf())
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Sends notifications to the configured channels
+pub struct Notifier {
+    config: NotifyConfig,
+    client: Client,
+    /// Project directory name, to tell runs of different projects apart
+    project: String,
+}
+
+impl Notifier {
+    /// Create a notifier, or `None` if no channel is configured
+    #[cfg(feature = "notify")]
+    pub fn from_config(config: &NotifyConfig, project_root: &Path) -> Option<Self> {
+        if !config.is_enabled() {
+            return None;
+        }
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .unwrap_or_default();
+        Some(Self { config: config.clone(), client, project: project_name(project_root) })
+    }
+
+    /// Without the "notify" feature nothing is ever sent
+    #[cfg(not(feature = "notify"))]
+    pub fn from_config(config: &NotifyConfig, _project_root: &Path) -> Option<Self> {
+        if config.is_enabled() {
+            warn!("[notify] is set, but WorkSplit was built without the \"notify\" feature; no notifications are sent");
+        }
+        None
+    }
+
+    /// Notify that a run finished (`on_finish`)
+    pub async fn run_finished(&self, summary: &RunSummary) {
+        if self.config.on_finish {
+            self.send(&Notification::run_finished(summary)).await;
+        }
+    }
+
+    /// Notify that a job failed (`on_failure`)
+    pub async fn job_failed(&self, job_id: &str, error: Option<&str>, failure: Option<FailureKind>) {
+        if self.config.on_failure {
+            let notification = Notification::JobFailed {
+                job_id: job_id.to_string(),
+                error: error.map(String::from),
+                failure,
+            };
+            self.send(&notification).await;
+        }
+    }
+
+    #[cfg(not(feature = "notify"))]
+    async fn send(&self, _notification: &Notification) {}
+
+    #[cfg(feature = "notify")]
+    async fn send(&self, notification: &Notification) {
+        let mut message = format!("{} ({}): {}", notification.title(), self.project, notification.body());
+        if message.chars().count() > MAX_MESSAGE_CHARS {
+            message = message.chars().take(MAX_MESSAGE_CHARS).collect::<String>() + "...";
+        }
+
+        if self.config.desktop {
+            if let Err(e) = desktop_notification(notification.title(), &notification.body()) {
+                warn!("Desktop notification failed: {}", e);
+            }
+        }
+
+        if let Some(url) = &self.config.webhook_url {
+            let payload = WebhookPayload { text: &message, content: &message, project: &self.project, notification };
+            match self.client.post(url).json(&payload).send().await {
+                Ok(response) if response.status().is_success() => debug!("Notification sent to {}", url),
+                Ok(response) => warn!("Notification webhook {} returned HTTP {}", url, response.status().as_u16()),
+                Err(e) => warn!("Notification webhook {} failed: {}", url, e),
+            }
+        }
+    }
+}
+
+/// Quote a string for AppleScript
+#[cfg(all(feature = "notify", target_os = "macos"))]
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(feature = "notify")]
+fn desktop_notification(title: &str, body: &str) -> std::io::Result<()> {
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {} with title {}",
+            applescript_string(body),
+            applescript_string(title)
+        ));
+        command
+    };
+    #[cfg(not(target_os = "macos"))]
+    let mut command = {
+        let mut command = Command::new("notify-send");
+        command.arg(title).arg(body);
+        command
+    };
+
+    let status = command.stdout(Stdio::null()).stderr(Stdio::null()).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!("{:?} exited with {}", command.get_program(), status)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::runner::JobResult;
+
+    fn result(job_id: &str, status: JobStatus, error: Option<&str>) -> JobResult {
+        JobResult {
+            job_id: job_id.to_string(),
+            status,
+            error: error.map(String::from),
+            output_paths: Vec::new(),
+            output_lines: None,
+            test_path: None,
+            test_lines: None,
+            retry_attempted: false,
+            implicit_context_files: Vec::new(),
+            failure: None,
+            request_retries: Vec::new(),
+            phase_timings: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_run_finished_notification() {
+        let summary = RunSummary {
+            processed: 3,
+            passed: 2,
+            failed: 1,
+            skipped: 1,
+            results: vec![
+                result("001_a", JobStatus::Pass, None),
+                result("002_b", JobStatus::Fail, Some("Build failed\nerror[E0308]: mismatched types")),
+                result("003_c", JobStatus::Pass, None),
+            ],
+            skipped_jobs: vec!["004_d".to_string()],
+            budget_exhausted: Some("job limit of 3 reached".to_string()),
+        };
+        let notification = Notification::run_finished(&summary);
+        assert_eq!(notification.title(), "WorkSplit run finished with failures");
+        assert_eq!(
+            notification.body(),
+            "3 processed: 2 passed, 1 failed, 1 skipped (run budget: job limit of 3 reached)\n- 002_b: Build failed"
+        );
+
+        let json = serde_json::to_value(&notification).unwrap();
+        assert_eq!(json["event"], "run_finished");
+        assert_eq!(json["failed_jobs"][0]["job_id"], "002_b");
+    }
+
+    #[test]
+    fn test_job_failed_notification() {
+        let notification = Notification::JobFailed {
+            job_id: "002_b".to_string(),
+            error: Some("Model response contained no code".to_string()),
+            failure: Some(FailureKind::ExtractionEmpty),
+        };
+        assert_eq!(notification.body(), "002_b [extraction_empty]: Model response contained no code");
+        let json = serde_json::to_value(&notification).unwrap();
+        assert_eq!(json["event"], "job_failed");
+        assert_eq!(json["failure"], "extraction_empty");
+    }
+
+    #[cfg(feature = "notify")]
+    #[test]
+    fn test_notifier_disabled_without_channels() {
+        let config = NotifyConfig::default();
+        assert!(Notifier::from_config(&config, Path::new(".")).is_none());
+        let config = NotifyConfig { desktop: true, ..Default::default() };
+        assert!(Notifier::from_config(&config, Path::new(".")).is_some());
+    }
+}
@@ -0,0 +1,111 @@
+//! Offline mode: guarantee that nothing contacts a non-local address
+
+use reqwest::Url;
+use std::net::IpAddr;
+
+use crate::error::WorkSplitError;
+use crate::models::{Config, Job};
+
+/// Environment variable that forces offline mode (set by `--offline`)
+pub const OFFLINE_ENV: &str = "WORKSPLIT_OFFLINE";
+
+/// Whether offline mode was requested through the environment
+pub fn offline_from_env() -> bool {
+    std::env::var(OFFLINE_ENV).is_ok_and(|v| matches!(v.trim(), "1" | "true" | "yes"))
+}
+
+/// Whether a URL points at this machine (localhost or a loopback address)
+pub fn is_local_url(url: &str) -> bool {
+    let Ok(parsed) = Url::parse(url) else {
+        return false;
+    };
+    match parsed.host_str() {
+        Some(host) => is_local_host(host),
+        None => false,
+    }
+}
+
+fn is_local_host(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.eq_ignore_ascii_case("localhost") || host.to_ascii_lowercase().ends_with(".localhost") {
+        return true;
+    }
+    match host.parse::<IpAddr>() {
+        Ok(ip) => ip.is_loopback() || ip.is_unspecified(),
+        Err(_) => false,
+    }
+}
+
+fn check_url(component: &str, url: &str) -> Result<(), WorkSplitError> {
+    if is_local_url(url) {
+        Ok(())
+    } else {
+        Err(WorkSplitError::OfflineViolation {
+            component: component.to_string(),
+            url: url.to_string(),
+        })
+    }
+}
+
+/// Check every address the configuration would contact
+pub fn check_config(config: &Config) -> Result<(), WorkSplitError> {
+    check_url("Ollama", &config.ollama.url)?;
+    if let Some(url) = &config.external_verify.url {
+        check_url("External verification", url)?;
+    }
+    if let Some(url) = &config.notify.webhook_url {
+        check_url("Notification webhook", url)?;
+    }
+    if let Some(url) = &config.status_webhook.url {
+        check_url("Status webhook", url)?;
+    }
+    Ok(())
+}
+
+/// Check the addresses a job would contact (`context_urls`)
+pub fn check_job(job: &Job) -> Result<(), WorkSplitError> {
+    for url in job.metadata.context_urls.iter().flatten() {
+        check_url(&format!("Job '{}' context_urls", job.id), url)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ExternalVerifyConfig;
+
+    #[test]
+    fn test_is_local_url() {
+        assert!(is_local_url("http://localhost:11434"));
+        assert!(is_local_url("http://127.0.0.1:8080/verify"));
+        assert!(is_local_url("http://[::1]:11434"));
+        assert!(is_local_url("http://ollama.localhost"));
+        assert!(!is_local_url("http://192.168.1.20:11434"));
+        assert!(!is_local_url("https://docs.rs/serde"));
+        assert!(!is_local_url("not a url"));
+    }
+
+    #[test]
+    fn test_check_config() {
+        let mut config = Config::default();
+        assert!(check_config(&config).is_ok());
+
+        config.notify.webhook_url = Some("https://hooks.slack.com/services/T0/B0/x".to_string());
+        let err = check_config(&config).unwrap_err();
+        assert!(matches!(err, WorkSplitError::OfflineViolation { ref component, .. } if component == "Notification webhook"));
+        config.notify.webhook_url = Some("http://localhost:9000/hook".to_string());
+        assert!(check_config(&config).is_ok());
+
+        config.external_verify = ExternalVerifyConfig {
+            url: Some("https://review.example.com/verify".to_string()),
+            ..Default::default()
+        };
+        let err = check_config(&config).unwrap_err();
+        assert!(matches!(err, WorkSplitError::OfflineViolation { ref component, .. } if component == "External verification"));
+
+        config.ollama.url = "http://gpu-box:11434".to_string();
+        let err = check_config(&config).unwrap_err();
+        assert!(matches!(err, WorkSplitError::OfflineViolation { ref component, .. } if component == "Ollama"));
+    }
+}
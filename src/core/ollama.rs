@@ -3,16 +3,49 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::io::{self, Write};
 use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tokio::sync::broadcast;
 use tracing::{debug, error, info, warn};
 
+use crate::core::mock_fixtures::MockFixtures;
+use crate::core::parser::sanitize_response;
+use crate::core::provenance::{GeneratedWrite, RequestFingerprint};
+use crate::core::recording::{RecordedExchange, Recorder};
+use crate::core::retry_policy::{jitter_unit, RequestRetry, RetryPolicy};
+use crate::core::stream_log::StreamLog;
 use crate::error::OllamaError;
-use crate::models::OllamaConfig;
+use crate::models::{ModelApi, OllamaConfig, RetryStep};
 
 /// Ollama API client
 pub struct OllamaClient {
     client: Client,
     config: OllamaConfig,
+    /// Optional channel that receives generated content as it streams in
+    output_sink: Option<broadcast::Sender<String>>,
+    /// Prompt + generated tokens across all requests made by this client
+    tokens_used: Arc<AtomicU64>,
+    /// Canned responses replacing Ollama (`run --mock` / `run --replay`)
+    mock: Option<MockFixtures>,
+    /// Receives every request/response pair (`run --record`)
+    recorder: Option<Recorder>,
+    /// Receives every streamed token, per job (`run --stream-to`)
+    stream_log: Option<StreamLog>,
+    /// Job the current requests belong to
+    current_job: Mutex<Option<String>>,
+    /// Model/temperature overrides of the current retry, if any
+    retry_step: Mutex<Option<RetryStep>>,
+    /// Retries of failed requests (`[ollama.retry]`)
+    retry_policy: RetryPolicy,
+    /// Request retries since the last `take_request_retries`
+    request_retries: Mutex<Vec<RequestRetry>>,
+    /// Responses as the model sent them since the last `take_responses`
+    responses: Mutex<Vec<String>>,
+    /// Model and prompt of the latest answered request
+    last_request: Mutex<Option<RequestFingerprint>>,
+    /// Files written from responses since the last `take_writes`
+    writes: Mutex<Vec<GeneratedWrite>>,
 }
 
 /// Chat message for Ollama chat API
@@ -30,6 +63,38 @@ impl ChatMessage {
     pub fn user(content: impl Into<String>) -> Self {
         Self { role: "user".to_string(), content: content.into() }
     }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self { role: "assistant".to_string(), content: content.into() }
+    }
+}
+
+/// Build the messages of a single-turn request
+fn single_turn(system_prompt: Option<&str>, prompt: &str) -> Vec<ChatMessage> {
+    let mut messages = Vec::new();
+    if let Some(sys) = system_prompt {
+        messages.push(ChatMessage::system(sys));
+    }
+    messages.push(ChatMessage::user(prompt));
+    messages
+}
+
+/// Approximate characters in a conversation
+pub fn conversation_chars(messages: &[ChatMessage]) -> usize {
+    messages.iter().map(|m| m.content.len()).sum()
+}
+
+/// Drop the oldest answer/feedback exchanges of a retry conversation until it
+/// fits in `max_chars`
+///
+/// The conversation is a system prompt, the original request, then pairs of
+/// assistant answers and follow-up user messages. The first two messages and
+/// the latest pair are always kept; returns whether the result fits.
+pub fn trim_conversation(messages: &mut Vec<ChatMessage>, max_chars: usize) -> bool {
+    while conversation_chars(messages) > max_chars && messages.len() > 4 {
+        messages.drain(2..4);
+    }
+    conversation_chars(messages) <= max_chars
 }
 
 /// Request body for Ollama chat endpoint
@@ -38,18 +103,68 @@ struct ChatRequest {
     model: String,
     messages: Vec<ChatMessage>,
     stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<ChatOptions>,
+}
+
+/// Sampling options for a chat request
+#[derive(Debug, Serialize)]
+struct ChatOptions {
+    temperature: f32,
+}
+
+/// Request body for Ollama generate endpoint
+#[derive(Debug, Serialize)]
+struct GenerateRequest {
+    model: String,
+    prompt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    /// Code after the gap, for fill-in-the-middle
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suffix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    template: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    raw: bool,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<ChatOptions>,
 }
 
-/// Response from Ollama chat endpoint (streaming)
+/// Outcome of loading the model with a warm-up prompt
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarmUp {
+    pub model: String,
+    /// Wall time of the warm-up request
+    pub elapsed: Duration,
+    /// Time Ollama spent loading the model, as it reports it; near zero when
+    /// the model was already in memory
+    pub load_duration: Option<Duration>,
+}
+
+/// Response from Ollama chat or generate endpoint (streaming)
 #[derive(Debug, Deserialize)]
 struct ChatResponse {
     #[serde(default)]
     message: Option<ChatMessageResponse>,
+    /// Generated text (generate endpoint)
+    #[serde(default)]
+    response: Option<String>,
+    /// Reasoning text (generate endpoint)
+    #[serde(default)]
+    thinking: Option<String>,
+    #[serde(default)]
     done: bool,
+    /// Set when Ollama aborts the request mid-stream
+    #[serde(default)]
+    error: Option<String>,
     #[serde(default)]
     total_duration: Option<u64>,
     #[serde(default)]
     eval_count: Option<u64>,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
 }
 
 /// Message content in chat response
@@ -64,6 +179,41 @@ struct ChatMessageResponse {
     thinking: Option<String>,
 }
 
+/// One line on how long a warm-up took, e.g. "Model 'x' loaded in 11.9s"
+pub fn describe_warm_up(warm_up: &WarmUp) -> String {
+    match warm_up.load_duration {
+        // Ollama reports a few milliseconds for a model already in memory
+        Some(load) if load >= Duration::from_millis(100) => format!("Model '{}' loaded in {:.1}s (warm-up took {:.1}s)",
+            warm_up.model, load.as_secs_f64(), warm_up.elapsed.as_secs_f64()),
+        Some(_) => format!("Model '{}' was already loaded (warm-up took {:.1}s)", warm_up.model, warm_up.elapsed.as_secs_f64()),
+        None => format!("Model '{}' ready after {:.1}s", warm_up.model, warm_up.elapsed.as_secs_f64()),
+    }
+}
+
+/// Whether an Ollama error message says the prompt doesn't fit in the
+/// model's context window (wording differs between Ollama versions)
+pub fn is_context_overflow(message: &str) -> bool {
+    const PHRASES: &[&str] = &[
+        "context length", "context window", "context size", "num_ctx",
+        "prompt too long", "prompt is too long", "too many tokens",
+    ];
+    let message = message.to_lowercase();
+    PHRASES.iter().any(|phrase| message.contains(phrase))
+}
+
+/// Error for a failed generation request; `body` is usually
+/// `{"error": "..."}`
+fn request_error(status: u16, body: String) -> OllamaError {
+    #[derive(Deserialize)]
+    struct ErrorBody {
+        error: String,
+    }
+    match serde_json::from_str::<ErrorBody>(&body) {
+        Ok(ErrorBody { error }) if is_context_overflow(&error) => OllamaError::ContextOverflow(error),
+        _ => OllamaError::HttpError { status, message: body },
+    }
+}
+
 impl OllamaClient {
     /// Create a new Ollama client with the given configuration
     pub fn new(config: OllamaConfig) -> Result<Self, OllamaError> {
@@ -72,7 +222,93 @@ impl OllamaClient {
             .build()
             .map_err(|e| OllamaError::RequestFailed(e.to_string()))?;
 
-        Ok(Self { client, config })
+        Ok(Self {
+            client,
+            output_sink: None,
+            tokens_used: Arc::new(AtomicU64::new(0)),
+            mock: None,
+            recorder: None,
+            stream_log: None,
+            current_job: Mutex::new(None),
+            retry_step: Mutex::new(None),
+            retry_policy: RetryPolicy::from_config(&config.retry),
+            request_retries: Mutex::new(Vec::new()),
+            responses: Mutex::new(Vec::new()),
+            last_request: Mutex::new(None),
+            writes: Mutex::new(Vec::new()),
+            config,
+        })
+    }
+
+    /// Forward streamed content to a broadcast channel (used by `worksplit serve`)
+    pub fn set_output_sink(&mut self, sink: broadcast::Sender<String>) {
+        self.output_sink = Some(sink);
+    }
+
+    /// Answer every request from fixture files instead of Ollama
+    pub fn set_mock_fixtures(&mut self, fixtures: MockFixtures) {
+        self.mock = Some(fixtures);
+    }
+
+    /// Record every request/response pair
+    pub fn set_recorder(&mut self, recorder: Recorder) {
+        self.recorder = Some(recorder);
+    }
+
+    /// Copy every streamed token to a file per job, printed or not
+    pub fn set_stream_log(&mut self, log: StreamLog) {
+        self.stream_log = Some(log);
+    }
+
+    /// Open the current job's stream log for a new request
+    fn begin_stream_log(&self, model: &str) -> Option<std::fs::File> {
+        let log = self.stream_log.as_ref()?;
+        let job_id = self.current_job.lock().unwrap().clone();
+        log.begin(job_id.as_deref(), model)
+            .map_err(|e| warn!("Failed to open stream log {}: {}", log.path(job_id.as_deref()).display(), e))
+            .ok()
+    }
+
+    /// Attribute the following requests to `job_id` (for mocks, recordings and stream logs)
+    pub fn set_current_job(&self, job_id: &str) {
+        *self.current_job.lock().unwrap() = Some(job_id.to_string());
+    }
+
+    /// Apply a retry ladder step (or a verification voter's model) to the
+    /// following requests; `None` restores the configured model and default
+    /// sampling
+    pub fn set_retry_step(&self, step: Option<RetryStep>) {
+        *self.retry_step.lock().unwrap() = step;
+    }
+
+    /// Request retries made since the last call, for the job result
+    pub fn take_request_retries(&self) -> Vec<RequestRetry> {
+        std::mem::take(&mut *self.request_retries.lock().unwrap())
+    }
+
+    /// Raw responses received since the last call, before sanitizing, for
+    /// the failure artifacts of a job
+    pub fn take_responses(&self) -> Vec<String> {
+        std::mem::take(&mut *self.responses.lock().unwrap())
+    }
+
+    /// Note that `content`, from the latest response, was written to `path`
+    /// (for `jobs/_provenance.json`)
+    pub fn record_write(&self, path: &std::path::Path, content: &str) {
+        if let Some(request) = self.last_request.lock().unwrap().clone() {
+            self.writes.lock().unwrap().push(GeneratedWrite::new(path, request, content));
+        }
+    }
+
+    /// Files written since the last call, in the order they were written
+    pub fn take_writes(&self) -> Vec<GeneratedWrite> {
+        std::mem::take(&mut *self.writes.lock().unwrap())
+    }
+
+    /// Total prompt + generated tokens used by this client so far (as reported
+    /// by the server, or estimated)
+    pub fn tokens_used(&self) -> u64 {
+        self.tokens_used.load(Ordering::Relaxed)
     }
 
     /// Generate a response from Ollama using the chat API
@@ -81,37 +317,138 @@ impl OllamaClient {
     /// - `prompt`: The user prompt/message
     /// - `stream_to_stdout`: If true, stream response to stdout as received
     /// 
-    /// Returns the complete response text.
+    /// Returns the complete response text. With mock fixtures set, the
+    /// response comes from them instead; with a recorder set, the exchange
+    /// is recorded.
     pub async fn generate(
         &self,
         system_prompt: Option<&str>,
         prompt: &str,
         stream_to_stdout: bool,
     ) -> Result<String, OllamaError> {
-        let url = format!("{}/api/chat", self.config.url);
-        
-        // Build messages array with optional system prompt
-        let mut messages = Vec::new();
-        if let Some(sys) = system_prompt {
-            messages.push(ChatMessage::system(sys));
+        self.chat(&single_turn(system_prompt, prompt), stream_to_stdout).await
+    }
+
+    /// Continue a conversation: send the full message history and return the
+    /// assistant's reply
+    ///
+    /// Mock fixtures and recordings key on the last user message; the first
+    /// system message is recorded as the system prompt. The reply is cleaned
+    /// up per `[ollama.sanitize]`; recordings keep it as the model sent it.
+    pub async fn chat(&self, messages: &[ChatMessage], stream_to_stdout: bool) -> Result<String, OllamaError> {
+        self.send(messages, None, stream_to_stdout).await
+    }
+
+    /// Ask the model for the code between `prefix` and `suffix`
+    /// (fill-in-the-middle, through the generate endpoint)
+    ///
+    /// The model's template must support a suffix. Mock fixtures and
+    /// recordings key on the prefix; the reply isn't sanitized.
+    pub async fn fill_in_middle(&self, prefix: &str, suffix: &str, stream_to_stdout: bool) -> Result<String, OllamaError> {
+        self.send(&[ChatMessage::user(prefix)], Some(suffix), stream_to_stdout).await
+    }
+
+    async fn send(&self, messages: &[ChatMessage], suffix: Option<&str>, stream_to_stdout: bool) -> Result<String, OllamaError> {
+        let job_id = self.current_job.lock().unwrap().clone();
+        let prompt = messages.iter().rev().find(|m| m.role == "user").map_or("", |m| m.content.as_str());
+        let response = match &self.mock {
+            Some(mock) => self.mock_generate(mock, job_id.as_deref(), messages, prompt, stream_to_stdout)?,
+            None => self.generate_from_server(messages, suffix, stream_to_stdout).await?,
+        };
+
+        if let Some(recorder) = &self.recorder {
+            let exchange = RecordedExchange {
+                job_id,
+                system_prompt: messages.iter().find(|m| m.role == "system").map(|m| m.content.clone()),
+                prompt: prompt.to_string(),
+                response: response.clone(),
+            };
+            if let Err(e) = recorder.record(&exchange) {
+                warn!("Failed to record response to {}: {}", recorder.path().display(), e);
+            }
         }
-        messages.push(ChatMessage::user(prompt));
-        
-        let request = ChatRequest {
-            model: self.config.model.clone(),
-            messages,
+        self.responses.lock().unwrap().push(response.clone());
+        *self.last_request.lock().unwrap() = Some(RequestFingerprint::new(&self.current_model(), messages, suffix));
+        if suffix.is_some() {
+            return Ok(response);
+        }
+        Ok(sanitize_response(&response, &self.config.sanitize, &self.current_model()))
+    }
+
+    /// Model of the following requests: the current retry step's, else the configured one
+    fn current_model(&self) -> String {
+        let step = self.retry_step.lock().unwrap();
+        step.as_ref().and_then(|s| s.model.clone()).unwrap_or_else(|| self.config.model.clone())
+    }
+
+    /// Build the chat request, applying the current retry step
+    fn chat_request(&self, messages: &[ChatMessage]) -> ChatRequest {
+        let step = self.retry_step.lock().unwrap().clone().unwrap_or_default();
+        ChatRequest {
+            model: self.current_model(),
+            messages: messages.to_vec(),
             stream: true,
+            options: step.temperature.map(|temperature| ChatOptions { temperature }),
+        }
+    }
+
+    /// Build the generate request: the conversation as a single prompt,
+    /// with the model's `[ollama.models]` settings
+    fn generate_request(&self, messages: &[ChatMessage], suffix: Option<&str>) -> GenerateRequest {
+        let step = self.retry_step.lock().unwrap().clone().unwrap_or_default();
+        let model = self.current_model();
+        let settings = self.config.model_config(&model).cloned().unwrap_or_default();
+        let system = messages.iter().find(|m| m.role == "system").map(|m| m.content.clone());
+        let turns: Vec<&ChatMessage> = messages.iter().filter(|m| m.role != "system").collect();
+        let mut prompt = match turns.as_slice() {
+            [only] => only.content.clone(),
+            turns => turns.iter()
+                .map(|m| format!("### {}\n{}", if m.role == "assistant" { "Assistant" } else { "User" }, m.content))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        };
+        // Raw prompts skip the template, and with it the system prompt
+        let system = match system {
+            Some(system) if settings.raw => {
+                prompt = format!("{}\n\n{}", system, prompt);
+                None
+            }
+            system => system,
         };
+        GenerateRequest {
+            model,
+            prompt,
+            system,
+            suffix: suffix.map(str::to_string),
+            template: settings.template,
+            raw: settings.raw,
+            stream: true,
+            options: step.temperature.map(|temperature| ChatOptions { temperature }),
+        }
+    }
 
-        debug!("Sending chat request to Ollama: {}", url);
-        debug!("Using model: {}, system_prompt: {}", 
-            self.config.model, 
-            system_prompt.map(|s| format!("{}...", &s[..s.len().min(50)])).unwrap_or_else(|| "none".to_string()));
+    /// Stream a completion from the Ollama server: through the chat endpoint,
+    /// or the generate endpoint for fill-in-the-middle and models configured
+    /// with `api = "generate"`
+    async fn generate_from_server(
+        &self,
+        messages: &[ChatMessage],
+        suffix: Option<&str>,
+        stream_to_stdout: bool,
+    ) -> Result<String, OllamaError> {
+        let model = self.current_model();
+        let request = if suffix.is_some() || self.config.api_for(&model) == ModelApi::Generate {
+            let url = format!("{}/api/generate", self.config.url);
+            debug!("Sending generate request to Ollama: {}", url);
+            self.client.post(url).json(&self.generate_request(messages, suffix))
+        } else {
+            let url = format!("{}/api/chat", self.config.url);
+            debug!("Sending chat request to Ollama: {}", url);
+            self.client.post(url).json(&self.chat_request(messages))
+        };
+        debug!("Using model: {}, {} messages", model, messages.len());
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&request)
+        let response = request
             .send()
             .await
             .map_err(|e| {
@@ -130,10 +467,12 @@ impl OllamaClient {
         if !response.status().is_success() {
             let status = response.status().as_u16();
             let message = response.text().await.unwrap_or_default();
-            return Err(OllamaError::HttpError { status, message });
+            return Err(request_error(status, message));
         }
 
+        let mut stream_log = self.begin_stream_log(&model);
         let mut full_response = String::new();
+        let mut reported_tokens: Option<u64> = None;
         let mut stream = response.bytes_stream();
         let mut buffer = String::new();
         let mut generation_done = false;
@@ -141,19 +480,46 @@ impl OllamaClient {
         let mut thinking_token_count = 0usize;
         let mut last_progress_log = std::time::Instant::now();
         let mut last_token_time = std::time::Instant::now();
+        let mut last_heartbeat = std::time::Instant::now();
         let generation_start = std::time::Instant::now();
-        let progress_interval = std::time::Duration::from_secs(10);
-        let stall_timeout = std::time::Duration::from_secs(120); // 2 minute stall timeout
+        let seconds = |secs: u64| (secs > 0).then(|| Duration::from_secs(secs));
+        let progress_interval = Duration::from_secs(self.config.progress_interval_seconds);
+        let stall_timeout = seconds(self.config.stall_timeout_seconds);
+        let heartbeat = seconds(self.config.heartbeat_seconds);
         let thinking_timeout = std::time::Duration::from_secs(120); // 2 minute thinking timeout
 
-        while let Some(chunk_result) = stream.next().await {
-            let chunk = chunk_result.map_err(|e| OllamaError::StreamError(e.to_string()))?;
-            
-            // Check for stall (no tokens for too long)
-            if last_token_time.elapsed() > stall_timeout {
-                warn!("Generation stalled - no tokens received for {:?}", stall_timeout);
-                return Err(OllamaError::Timeout(stall_timeout.as_secs()));
-            }
+        loop {
+            // Wake up for the stall deadline or the next heartbeat, whichever is first
+            let until_stall = stall_timeout.map(|t| t.saturating_sub(last_token_time.elapsed()));
+            let until_heartbeat = heartbeat.map(|h| h.saturating_sub(last_heartbeat.elapsed()));
+            let next = match until_stall.into_iter().chain(until_heartbeat).min() {
+                Some(wait) => tokio::time::timeout(wait, stream.next()).await.ok(),
+                None => Some(stream.next().await),
+            };
+            let chunk_result = match next {
+                Some(Some(chunk_result)) => chunk_result,
+                Some(None) => break,
+                None => {
+                    let idle = last_token_time.elapsed();
+                    if let Some(stall_timeout) = stall_timeout.filter(|t| idle >= *t) {
+                        warn!("Generation stalled - no tokens received for {:?}", stall_timeout);
+                        return Err(OllamaError::StallTimeout(stall_timeout.as_secs()));
+                    }
+                    if heartbeat.is_some_and(|h| last_heartbeat.elapsed() >= h) {
+                        info!("Waiting for the model: no tokens for {:.0}s ({} received, {:.0}s elapsed)...",
+                            idle.as_secs_f32(), token_count, generation_start.elapsed().as_secs_f32());
+                        last_heartbeat = std::time::Instant::now();
+                    }
+                    continue;
+                }
+            };
+            let chunk = chunk_result.map_err(|e| {
+                if e.is_timeout() {
+                    OllamaError::Timeout(self.config.timeout_seconds)
+                } else {
+                    OllamaError::StreamError(e.to_string())
+                }
+            })?;
             
             // Ollama sends newline-delimited JSON
             let chunk_str = String::from_utf8_lossy(&chunk);
@@ -183,12 +549,21 @@ impl OllamaClient {
                     }
                 };
 
+                if let Some(message) = parsed.error {
+                    return Err(if is_context_overflow(&message) {
+                        OllamaError::ContextOverflow(message)
+                    } else {
+                        OllamaError::StreamError(message)
+                    });
+                }
+
                 // Extract content from message field (chat API format)
                 // GLM models use a "thinking" field during reasoning phase before outputting content
                 let message = parsed.message.as_ref();
-                let content = message.map(|m| m.content.as_str()).unwrap_or("");
+                let content = message.map(|m| m.content.as_str()).or(parsed.response.as_deref()).unwrap_or("");
                 let is_thinking = message
                     .and_then(|m| m.thinking.as_ref())
+                    .or(parsed.thinking.as_ref())
                     .map(|t| !t.is_empty())
                     .unwrap_or(false);
                 
@@ -198,6 +573,7 @@ impl OllamaClient {
                     thinking_token_count += 1;
                 }
                 last_token_time = std::time::Instant::now();
+                last_heartbeat = last_token_time;
 
                 // Check for thinking timeout (model stuck in reasoning loop)
                 if full_response.is_empty() && generation_start.elapsed() > thinking_timeout {
@@ -231,17 +607,35 @@ impl OllamaClient {
                     io::stdout().flush().ok();
                 }
 
+                if let Some(sink) = &self.output_sink {
+                    if !content.is_empty() {
+                        // No subscribers is fine - nobody is watching
+                        let _ = sink.send(content.to_string());
+                    }
+                }
+
+                if let Some(file) = &mut stream_log {
+                    let _ = file.write_all(content.as_bytes());
+                }
+
                 if parsed.done {
                     generation_done = true;
                     if stream_to_stdout {
                         println!(); // Final newline
                     }
+                    if let Some(file) = &mut stream_log {
+                        let _ = writeln!(file);
+                    }
                     if let Some(duration) = parsed.total_duration {
                         debug!("Generation completed in {}ms", duration / 1_000_000);
                     }
                     if let Some(count) = parsed.eval_count {
                         debug!("Tokens generated: {}", count);
                     }
+                    reported_tokens = match (parsed.prompt_eval_count, parsed.eval_count) {
+                        (None, None) => None,
+                        (prompt, eval) => Some(prompt.unwrap_or(0) + eval.unwrap_or(0)),
+                    };
                     break;
                 }
             }
@@ -253,45 +647,96 @@ impl OllamaClient {
         }
 
         info!("Generated {} characters", full_response.len());
+        // Servers that don't report counts get the usual ~4 chars/token estimate
+        let tokens = reported_tokens.unwrap_or_else(|| {
+            let chars = conversation_chars(messages) + full_response.len();
+            (chars / 4) as u64
+        });
+        self.tokens_used.fetch_add(tokens, Ordering::Relaxed);
         Ok(full_response)
     }
 
-    /// Generate with automatic retry on thinking timeout
-    /// 
-    /// If the model gets stuck in a thinking loop, retries once.
-    /// On second failure, returns the error for human review.
+    /// Serve a canned response, streaming and counting it like a real one
+    fn mock_generate(
+        &self,
+        mock: &MockFixtures,
+        job_id: Option<&str>,
+        messages: &[ChatMessage],
+        prompt: &str,
+        stream_to_stdout: bool,
+    ) -> Result<String, OllamaError> {
+        let response = mock.next_response(job_id, prompt)?;
+        debug!("Serving mock response ({} chars)", response.len());
+        if stream_to_stdout {
+            println!("{}", response);
+        }
+        if let Some(sink) = &self.output_sink {
+            let _ = sink.send(response.clone());
+        }
+        if let Some(mut file) = self.begin_stream_log("mock") {
+            let _ = writeln!(file, "{}", response);
+        }
+        let chars = conversation_chars(messages) + response.len();
+        self.tokens_used.fetch_add((chars / 4) as u64, Ordering::Relaxed);
+        Ok(response)
+    }
+
+    /// Generate, retrying failed requests per `[ollama.retry]`
+    ///
+    /// By default a model stuck in a thinking loop is retried once; on the
+    /// second failure the error is returned for human review.
     pub async fn generate_with_retry(
         &self,
         system_prompt: Option<&str>,
         prompt: &str,
         stream_to_stdout: bool,
     ) -> Result<String, OllamaError> {
-        match self.generate(system_prompt, prompt, stream_to_stdout).await {
-            Ok(response) => Ok(response),
-            Err(OllamaError::ThinkingTimeout { duration_secs, thinking_tokens }) => {
-                warn!(
-                    "Thinking timeout on first attempt ({} tokens in {}s). Retrying once...",
-                    thinking_tokens, duration_secs
-                );
-                
-                // Retry once
-                match self.generate(system_prompt, prompt, stream_to_stdout).await {
-                    Ok(response) => {
-                        info!("Retry succeeded after initial thinking timeout");
-                        Ok(response)
-                    }
-                    Err(e @ OllamaError::ThinkingTimeout { .. }) => {
-                        error!(
-                            "SYSTEM PROMPT ERROR: Model failed twice due to thinking loop. \
-                            The system prompt needs adjustment. Error: {}",
-                            e
-                        );
-                        Err(e)
+        self.chat_with_retry(&single_turn(system_prompt, prompt), stream_to_stdout).await
+    }
+
+    /// [`chat`](Self::chat), retrying failed requests as `[ollama.retry]`
+    /// says (by default, a thinking timeout is retried once)
+    pub async fn chat_with_retry(&self, messages: &[ChatMessage], stream_to_stdout: bool) -> Result<String, OllamaError> {
+        self.send_with_retry(messages, None, stream_to_stdout).await
+    }
+
+    /// [`fill_in_middle`](Self::fill_in_middle), retrying failed requests as
+    /// `[ollama.retry]` says
+    pub async fn fill_in_middle_with_retry(&self, prefix: &str, suffix: &str, stream_to_stdout: bool) -> Result<String, OllamaError> {
+        self.send_with_retry(&[ChatMessage::user(prefix)], Some(suffix), stream_to_stdout).await
+    }
+
+    async fn send_with_retry(&self, messages: &[ChatMessage], suffix: Option<&str>, stream_to_stdout: bool) -> Result<String, OllamaError> {
+        let max_attempts = self.retry_policy.max_attempts();
+        let mut attempt = 1;
+        loop {
+            match self.send(messages, suffix, stream_to_stdout).await {
+                Ok(response) => {
+                    if attempt > 1 {
+                        info!("Request succeeded on attempt {}/{}", attempt, max_attempts);
                     }
-                    Err(e) => Err(e),
+                    return Ok(response);
                 }
+                Err(e) if self.retry_policy.should_retry(&e, attempt) => {
+                    let delay = self.retry_policy.delay(attempt, jitter_unit());
+                    warn!("Request attempt {}/{} failed: {}. Retrying in {:.1}s...",
+                        attempt, max_attempts, e, delay.as_secs_f32());
+                    debug!("Request retry: attempt={} max_attempts={} delay_ms={} job={:?} error={:?}",
+                        attempt, max_attempts, delay.as_millis(), self.current_job.lock().unwrap(), e);
+                    self.request_retries.lock().unwrap().push(RequestRetry { attempt, error: e.to_string(), delay });
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e @ OllamaError::ThinkingTimeout { .. }) if attempt > 1 => {
+                    error!(
+                        "SYSTEM PROMPT ERROR: Model failed {} times due to thinking loop. \
+                        The system prompt needs adjustment. Error: {}",
+                        attempt, e
+                    );
+                    return Err(e);
+                }
+                Err(e) => return Err(e),
             }
-            Err(e) => Err(e),
         }
     }
 
@@ -322,6 +767,11 @@ impl OllamaClient {
     /// Try to start Ollama if it's not running
     /// Returns true if Ollama was started or is already running
     pub async fn ensure_running(&self) -> Result<bool, OllamaError> {
+        if self.mock.is_some() {
+            debug!("Mock fixtures in use, not contacting Ollama");
+            return Ok(true);
+        }
+
         // First check if already running
         match self.health_check().await {
             Ok(true) => {
@@ -428,6 +878,104 @@ impl OllamaClient {
         }
     }
 
+    /// Embed `text` with `model` using `/api/embeddings`
+    pub async fn embed(&self, model: &str, text: &str) -> Result<Vec<f32>, OllamaError> {
+        if self.mock.is_some() {
+            return Err(OllamaError::MockFixture("embeddings are not available with mock fixtures".to_string()));
+        }
+
+        #[derive(Serialize)]
+        struct EmbeddingsRequest<'a> {
+            model: &'a str,
+            prompt: &'a str,
+        }
+
+        #[derive(Deserialize)]
+        struct EmbeddingsResponse {
+            embedding: Vec<f32>,
+        }
+
+        let url = format!("{}/api/embeddings", self.config.url);
+        let response = self
+            .client
+            .post(&url)
+            .json(&EmbeddingsRequest { model, prompt: text })
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let message = response.text().await.unwrap_or_default();
+            return Err(OllamaError::HttpError { status, message });
+        }
+
+        let body: EmbeddingsResponse = response
+            .json()
+            .await
+            .map_err(|e| OllamaError::ParseError(e.to_string()))?;
+        if body.embedding.is_empty() {
+            return Err(OllamaError::ParseError(format!("model '{}' returned an empty embedding", model)));
+        }
+        Ok(body.embedding)
+    }
+
+    /// Send a one-token prompt to the configured model so Ollama loads it
+    /// into memory before the first job. Not counted in `tokens_used`.
+    pub async fn warm_up(&self) -> Result<WarmUp, OllamaError> {
+        let model = self.config.model.clone();
+        if self.mock.is_some() {
+            return Ok(WarmUp { model, elapsed: Duration::ZERO, load_duration: None });
+        }
+
+        #[derive(Serialize)]
+        struct WarmUpOptions {
+            num_predict: u32,
+        }
+
+        #[derive(Serialize)]
+        struct WarmUpRequest<'a> {
+            model: &'a str,
+            prompt: &'a str,
+            stream: bool,
+            options: WarmUpOptions,
+        }
+
+        #[derive(Deserialize)]
+        struct WarmUpResponse {
+            #[serde(default)]
+            load_duration: Option<u64>,
+        }
+
+        let url = format!("{}/api/generate", self.config.url);
+        let started = std::time::Instant::now();
+        let response = self
+            .client
+            .post(&url)
+            .json(&WarmUpRequest { model: &model, prompt: "Hi", stream: false, options: WarmUpOptions { num_predict: 1 } })
+            .send()
+            .await
+            .map_err(|e| {
+                if e.is_connect() {
+                    OllamaError::ConnectionRefused(format!("Could not connect to Ollama at {}", self.config.url))
+                } else if e.is_timeout() {
+                    OllamaError::Timeout(self.config.timeout_seconds)
+                } else {
+                    OllamaError::from(e)
+                }
+            })?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let message = response.text().await.unwrap_or_default();
+            return Err(OllamaError::HttpError { status, message });
+        }
+        let body: WarmUpResponse = response
+            .json()
+            .await
+            .map_err(|e| OllamaError::ParseError(e.to_string()))?;
+        Ok(WarmUp { model, elapsed: started.elapsed(), load_duration: body.load_duration.map(Duration::from_nanos) })
+    }
+
     /// Check if the specified model is available
     pub async fn check_model(&self) -> Result<bool, OllamaError> {
         let url = format!("{}/api/tags", self.config.url);
@@ -487,6 +1035,74 @@ mod tests {
         let user = ChatMessage::user("Hello");
         assert_eq!(user.role, "user");
         assert_eq!(user.content, "Hello");
+
+        let assistant = ChatMessage::assistant("Hi");
+        assert_eq!(assistant.role, "assistant");
+        assert_eq!(conversation_chars(&[sys, user, assistant]), 15 + 5 + 2);
+    }
+
+    #[test]
+    fn test_context_overflow_errors() {
+        let overflow = request_error(500, r#"{"error":"the input length exceeds the context length"}"#.to_string());
+        assert!(matches!(overflow, OllamaError::ContextOverflow(ref m) if m == "the input length exceeds the context length"));
+        assert!(is_context_overflow("prompt too long; exceeded max context length by 112 tokens"));
+        assert!(matches!(request_error(500, r#"{"error":"model not found"}"#.to_string()),
+            OllamaError::HttpError { status: 500, .. }));
+        assert!(matches!(request_error(502, "Bad Gateway".to_string()), OllamaError::HttpError { status: 502, .. }));
+    }
+
+    #[test]
+    fn test_describe_warm_up() {
+        let warm_up = |load_ms: Option<u64>| WarmUp {
+            model: "coder".to_string(),
+            elapsed: Duration::from_millis(12_400),
+            load_duration: load_ms.map(Duration::from_millis),
+        };
+        assert_eq!(describe_warm_up(&warm_up(Some(11_900))), "Model 'coder' loaded in 11.9s (warm-up took 12.4s)");
+        assert_eq!(describe_warm_up(&warm_up(Some(3))), "Model 'coder' was already loaded (warm-up took 12.4s)");
+        assert_eq!(describe_warm_up(&warm_up(None)), "Model 'coder' ready after 12.4s");
+    }
+
+    #[test]
+    fn test_trim_conversation() {
+        let mut messages = vec![
+            ChatMessage::system("sys"),
+            ChatMessage::user("request"),
+            ChatMessage::assistant("answer 1"),
+            ChatMessage::user("feedback 1"),
+            ChatMessage::assistant("answer 2"),
+            ChatMessage::user("feedback 2"),
+        ];
+        assert!(trim_conversation(&mut messages, 1000));
+        assert_eq!(messages.len(), 6);
+
+        assert!(trim_conversation(&mut messages, 40));
+        let contents: Vec<&str> = messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["sys", "request", "answer 2", "feedback 2"]);
+
+        assert!(!trim_conversation(&mut messages, 10));
+        assert_eq!(messages.len(), 4);
+    }
+
+    #[test]
+    fn test_generate_request() {
+        use crate::models::ModelConfig;
+
+        let mut config = OllamaConfig { model: "coder-7b".to_string(), ..OllamaConfig::default() };
+        let client = OllamaClient::new(config.clone()).unwrap();
+        let request = client.generate_request(&single_turn(Some("sys"), "write a"), None);
+        assert_eq!((request.system.as_deref(), request.prompt.as_str(), request.raw), (Some("sys"), "write a", false));
+
+        let turns = [ChatMessage::user("write a"), ChatMessage::assistant("fn a"), ChatMessage::user("again")];
+        let fim = client.generate_request(&turns, Some("}\n"));
+        assert_eq!(fim.prompt, "### User\nwrite a\n\n### Assistant\nfn a\n\n### User\nagain");
+        assert_eq!(fim.suffix.as_deref(), Some("}\n"));
+
+        config.models.insert("coder".to_string(), ModelConfig { api: Some(ModelApi::Generate), raw: true, template: None });
+        assert_eq!(config.api_for("coder-7b"), ModelApi::Generate);
+        assert_eq!(config.api_for("llama3"), ModelApi::Chat);
+        let raw = OllamaClient::new(config).unwrap().generate_request(&single_turn(Some("sys"), "write a"), None);
+        assert_eq!((raw.system, raw.prompt.as_str(), raw.raw), (None, "sys\n\nwrite a", true));
     }
 
     #[test]
@@ -498,12 +1114,28 @@ mod tests {
                 ChatMessage::user("Hello"),
             ],
             stream: true,
+            options: None,
         };
         let json = serde_json::to_string(&request).unwrap();
         assert!(json.contains("\"model\":\"qwen3\""));
         assert!(json.contains("\"stream\":true"));
         assert!(json.contains("\"role\":\"system\""));
         assert!(json.contains("\"role\":\"user\""));
+        assert!(!json.contains("options"));
+    }
+
+    #[test]
+    fn test_chat_request_applies_retry_step() {
+        let client = OllamaClient::new(OllamaConfig::default()).unwrap();
+        client.set_retry_step(Some(RetryStep { model: Some("bigger".to_string()), temperature: Some(0.2) }));
+        let json = serde_json::to_value(client.chat_request(&single_turn(None, "Hello"))).unwrap();
+        assert_eq!(json["model"], "bigger");
+        assert!((json["options"]["temperature"].as_f64().unwrap() - 0.2).abs() < 1e-6);
+
+        client.set_retry_step(None);
+        let request = client.chat_request(&single_turn(None, "Hello"));
+        assert_eq!(request.model, OllamaConfig::default().model);
+        assert!(request.options.is_none());
     }
 
     #[test]
@@ -0,0 +1,429 @@
+//! Checks on the files jobs write
+//!
+//! [`check_output_paths`] runs in `worksplit validate` and before `worksplit
+//! run` sends anything to the model, so a misconfigured job fails in
+//! milliseconds instead of after a full generation. [`check_write_path`]
+//! guards every individual write, including paths the model chose itself.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+use crate::core::targets::glob_to_regex;
+use crate::error::WorkSplitError;
+use crate::models::{Job, OutputMode};
+
+/// A problem with the output paths of one or more jobs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputPathIssue {
+    /// Jobs involved, the offending job first
+    pub job_ids: Vec<String>,
+    pub message: String,
+}
+
+impl OutputPathIssue {
+    pub fn involves(&self, job_id: &str) -> bool {
+        self.job_ids.iter().any(|id| id == job_id)
+    }
+}
+
+impl fmt::Display for OutputPathIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Resolve `.` and `..` in a project-relative path without touching the
+/// filesystem. Returns `None` for absolute paths and paths that climb out of
+/// the project root.
+pub fn normalize_relative(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return None;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(normalized)
+}
+
+/// `path` (relative to `root`, or absolute under it) as a normalized
+/// root-relative path, or `None` if it leaves `root`, also after resolving
+/// symlinks in the part of it that exists
+pub fn resolve_inside(root: &Path, path: &Path) -> Option<PathBuf> {
+    let relative = if path.is_absolute() { path.strip_prefix(root).ok()? } else { path };
+    let relative = normalize_relative(relative)?;
+
+    // A symlinked directory inside the project could still point elsewhere
+    if let Ok(canonical_root) = root.canonicalize() {
+        let mut existing = root.join(&relative);
+        while !existing.exists() {
+            if !existing.pop() {
+                break;
+            }
+        }
+        if let Ok(canonical) = existing.canonicalize() {
+            if !canonical.starts_with(&canonical_root) {
+                return None;
+            }
+        }
+    }
+    Some(relative)
+}
+
+/// Check that `path` may be written: it must stay inside `root` (also after
+/// resolving symlinks) and, when `allowed_dirs` is non-empty, lie under one
+/// of those root-relative directories.
+pub fn check_write_path(root: &Path, path: &Path, allowed_dirs: &[PathBuf]) -> Result<(), WorkSplitError> {
+    let relative = resolve_inside(root, path).ok_or_else(|| WorkSplitError::OutputOutsideRoot(path.to_path_buf()))?;
+
+    if allowed_dirs.is_empty()
+        || allowed_dirs
+            .iter()
+            .filter_map(|dir| normalize_relative(dir))
+            .any(|dir| relative.starts_with(dir))
+    {
+        Ok(())
+    } else {
+        Err(WorkSplitError::OutputNotAllowed {
+            path: relative,
+            allowed: allowed_dirs.iter().map(|d| d.display().to_string()).collect::<Vec<_>>().join(", "),
+        })
+    }
+}
+
+/// The first of `rules` (`[safety] protected_paths`) that `path` matches.
+/// Rules are globs relative to `root`; a rule that matches a directory also
+/// protects everything below it.
+pub fn protected_rule<'a>(root: &Path, path: &Path, rules: &'a [String]) -> Option<&'a str> {
+    let relative = normalize_relative(path.strip_prefix(root).unwrap_or(path))?;
+    let candidates: Vec<String> = relative
+        .ancestors()
+        .filter(|p| !p.as_os_str().is_empty())
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .collect();
+    rules.iter().map(String::as_str).find(|rule| {
+        let rule = rule.trim_end_matches('/');
+        glob_to_regex(rule).is_ok_and(|regex| candidates.iter().any(|c| regex.is_match(c)))
+    })
+}
+
+/// Refuse to write `path` when it matches a `[safety] protected_paths` rule
+pub fn check_protected_path(root: &Path, path: &Path, rules: &[String]) -> Result<(), WorkSplitError> {
+    match protected_rule(root, path, rules) {
+        Some(rule) => Err(WorkSplitError::ProtectedPathViolation {
+            path: path.strip_prefix(root).unwrap_or(path).to_path_buf(),
+            rule: format!("[safety] protected_paths rule \"{}\"", rule),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Files a job creates or overwrites wholesale (not edits in place)
+pub(crate) fn written_files(job: &Job) -> Vec<PathBuf> {
+    let metadata = &job.metadata;
+    match metadata.mode {
+        OutputMode::Edit | OutputMode::ReplacePattern | OutputMode::UpdateFixtures => Vec::new(),
+        _ => {
+            let mut files = metadata.get_output_files();
+            files.extend(metadata.test_path());
+            files
+        }
+    }
+}
+
+/// Files a job edits in place
+fn edited_files(job: &Job) -> Vec<PathBuf> {
+    match job.metadata.mode {
+        OutputMode::Edit | OutputMode::ReplacePattern | OutputMode::UpdateFixtures => job.metadata.get_target_files(),
+        _ => Vec::new(),
+    }
+}
+
+/// Every file a job may touch, including edit targets
+pub(crate) fn touched_files(job: &Job) -> Vec<PathBuf> {
+    let mut files = written_files(job);
+    files.extend(edited_files(job));
+    if job.metadata.mode == OutputMode::Split {
+        files.extend(job.metadata.target_file.clone());
+    }
+    files
+}
+
+/// Whether `job` depends on `other`, directly or through other jobs
+pub(crate) fn depends_on(job: &str, other: &str, deps: &HashMap<&str, Vec<&str>>) -> bool {
+    let mut stack = vec![job];
+    let mut seen = HashSet::new();
+    while let Some(id) = stack.pop() {
+        if !seen.insert(id) {
+            continue;
+        }
+        for &dep in deps.get(id).into_iter().flatten() {
+            if dep == other {
+                return true;
+            }
+            stack.push(dep);
+        }
+    }
+    false
+}
+
+/// Check the output paths of all jobs against each other and the project.
+///
+/// Reports paths that leave the project root, target WorkSplit's own
+/// `jobs/_*` files or would be read back as job files (`jobs/*.md`), are
+/// written by more than one job, are overwritten after an earlier job edited
+/// them, or overwrite an existing file that a later job (by ID order, with no
+/// `depends_on` link either way) reads as context.
+pub fn check_output_paths(project_root: &Path, jobs: &[Job]) -> Vec<OutputPathIssue> {
+    let mut issues = Vec::new();
+    let mut sorted: Vec<&Job> = jobs.iter().collect();
+    sorted.sort_by(|a, b| a.id.cmp(&b.id));
+
+    for job in &sorted {
+        for path in touched_files(job) {
+            let issue = |message: String| OutputPathIssue { job_ids: vec![job.id.clone()], message };
+            match normalize_relative(&path) {
+                None => issues.push(issue(format!(
+                    "Job '{}': output path {} is outside the project root; use a path relative to the project",
+                    job.id,
+                    path.display()
+                ))),
+                Some(normalized) => {
+                    let protected = normalized.starts_with("jobs")
+                        && normalized
+                            .file_name()
+                            .and_then(|n| n.to_str())
+                            .is_some_and(|n| n.starts_with('_'));
+                    // `worksplit run` treats every jobs/*.md file as a job
+                    let read_as_job = normalized.parent() == Some(Path::new("jobs"))
+                        && normalized.extension().is_some_and(|e| e == "md");
+                    if protected {
+                        issues.push(issue(format!(
+                            "Job '{}': output path {} is a protected WorkSplit file (jobs/_*); choose another output",
+                            job.id,
+                            path.display()
+                        )));
+                    } else if read_as_job {
+                        issues.push(issue(format!(
+                            "Job '{}': output path {} would be picked up as a job file; write it to a subfolder such as jobs/reports/ or docs/",
+                            job.id,
+                            path.display()
+                        )));
+                    }
+                }
+            }
+        }
+    }
+
+    // Files written by more than one job
+    let mut writers: BTreeMap<PathBuf, Vec<&str>> = BTreeMap::new();
+    for job in &sorted {
+        let mut own: Vec<PathBuf> = written_files(job).iter().filter_map(|p| normalize_relative(p)).collect();
+        own.sort();
+        own.dedup();
+        for path in own {
+            writers.entry(path).or_default().push(&job.id);
+        }
+    }
+    for (path, ids) in &writers {
+        if ids.len() > 1 {
+            issues.push(OutputPathIssue {
+                job_ids: ids.iter().map(|id| id.to_string()).collect(),
+                message: format!(
+                    "Jobs {} all write {}; give each job its own output file or use mode: edit for the later ones",
+                    ids.iter().map(|id| format!("'{}'", id)).collect::<Vec<_>>().join(", "),
+                    path.display()
+                ),
+            });
+        }
+    }
+
+    let deps: HashMap<&str, Vec<&str>> = sorted
+        .iter()
+        .map(|job| {
+            let list = job.metadata.depends_on.iter().flatten().map(String::as_str).collect();
+            (job.id.as_str(), list)
+        })
+        .collect();
+
+    // Edits lost when a later, unrelated job rewrites the whole file
+    for editor in &sorted {
+        for path in edited_files(editor).iter().filter_map(|p| normalize_relative(p)) {
+            for &writer in writers.get(&path).into_iter().flatten() {
+                if writer > editor.id.as_str()
+                    && !depends_on(&editor.id, writer, &deps)
+                    && !depends_on(writer, &editor.id, &deps)
+                {
+                    issues.push(OutputPathIssue {
+                        job_ids: vec![writer.to_string(), editor.id.clone()],
+                        message: format!(
+                            "Job '{}' rewrites {} after job '{}' edits it, discarding those edits; add depends_on: [{}] to '{}' or write to a different path",
+                            writer,
+                            path.display(),
+                            editor.id,
+                            writer,
+                            editor.id
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    // Existing files overwritten before a later, unrelated job reads them
+    for (path, ids) in &writers {
+        if !project_root.join(path).exists() {
+            continue;
+        }
+        for &writer in ids {
+            for reader in sorted.iter().filter(|j| j.id.as_str() > writer) {
+                let reads = reader
+                    .metadata
+                    .context_files
+                    .iter()
+                    .any(|c| normalize_relative(c).as_ref() == Some(path));
+                if reads && !depends_on(&reader.id, writer, &deps) && !depends_on(writer, &reader.id, &deps) {
+                    issues.push(OutputPathIssue {
+                        job_ids: vec![writer.to_string(), reader.id.clone()],
+                        message: format!(
+                            "Job '{}' overwrites {}, which job '{}' reads as context; add depends_on: [{}] to '{}' if it should see the new file, or write to a different path",
+                            writer,
+                            path.display(),
+                            reader.id,
+                            writer,
+                            reader.id
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::JobMetadata;
+    use tempfile::TempDir;
+
+    fn job(id: &str, yaml: &str) -> Job {
+        let metadata: JobMetadata = serde_yaml::from_str(yaml).unwrap();
+        Job::new(id.to_string(), metadata, String::new(), PathBuf::from(format!("jobs/{}.md", id)))
+    }
+
+    #[test]
+    fn test_normalize_relative() {
+        assert_eq!(normalize_relative(Path::new("src/./a/../b.rs")), Some(PathBuf::from("src/b.rs")));
+        assert_eq!(normalize_relative(Path::new("src/../../etc/passwd")), None);
+        assert_eq!(normalize_relative(Path::new("/etc/passwd")), None);
+    }
+
+    #[test]
+    fn test_check_write_path() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        let allowed = vec![PathBuf::from("src/"), PathBuf::from("tests/")];
+
+        assert!(check_write_path(root, &root.join("src/new/mod.rs"), &allowed).is_ok());
+        assert!(check_write_path(root, Path::new("tests/a.rs"), &allowed).is_ok());
+        assert!(check_write_path(root, &root.join("build.rs"), &[]).is_ok());
+        assert!(matches!(
+            check_write_path(root, &root.join("build.rs"), &allowed),
+            Err(WorkSplitError::OutputNotAllowed { .. })
+        ));
+        assert!(matches!(
+            check_write_path(root, Path::new("/etc/passwd"), &[]),
+            Err(WorkSplitError::OutputOutsideRoot(_))
+        ));
+        assert!(matches!(
+            check_write_path(root, &root.join("src/../../escape.rs"), &[]),
+            Err(WorkSplitError::OutputOutsideRoot(_))
+        ));
+
+        #[cfg(unix)]
+        {
+            let outside = TempDir::new().unwrap();
+            std::os::unix::fs::symlink(outside.path(), root.join("src/link")).unwrap();
+            assert!(matches!(
+                check_write_path(root, &root.join("src/link/x.rs"), &allowed),
+                Err(WorkSplitError::OutputOutsideRoot(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_protected_rule() {
+        let root = Path::new("/project");
+        let rules: Vec<String> = ["Cargo.toml", ".github/**", "migrations/", "**/*.lock"].iter().map(|r| r.to_string()).collect();
+
+        assert_eq!(protected_rule(root, &root.join("Cargo.toml"), &rules), Some("Cargo.toml"));
+        assert_eq!(protected_rule(root, Path::new("./Cargo.toml"), &rules), Some("Cargo.toml"));
+        assert_eq!(protected_rule(root, &root.join("crates/a/Cargo.toml"), &rules), None);
+        assert_eq!(protected_rule(root, &root.join(".github/workflows/ci.yml"), &rules), Some(".github/**"));
+        assert_eq!(protected_rule(root, &root.join("migrations/0001/up.sql"), &rules), Some("migrations/"));
+        assert_eq!(protected_rule(root, &root.join("web/yarn.lock"), &rules), Some("**/*.lock"));
+        assert_eq!(protected_rule(root, &root.join("src/main.rs"), &rules), None);
+        assert!(matches!(
+            check_protected_path(root, &root.join(".github/ci.yml"), &rules),
+            Err(WorkSplitError::ProtectedPathViolation { rule, .. }) if rule.contains(".github/**")
+        ));
+        assert!(check_protected_path(root, &root.join("Cargo.toml"), &[]).is_ok());
+    }
+
+    #[test]
+    fn test_check_output_paths() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("src/shared.rs"), "// existing").unwrap();
+
+        let jobs = vec![
+            job("001_escape", "output_dir: ../\noutput_file: out.rs"),
+            job("002_protected", "output_dir: jobs/\noutput_file: _jobstatus.json"),
+            job("002_report", "mode: report\noutput_dir: jobs/\noutput_file: review.md"),
+            job("003_a", "output_dir: src/\noutput_file: dup.rs"),
+            job("004_b", "output_dir: src/\noutput_file: dup.rs"),
+            job("005_writer", "output_dir: src/\noutput_file: shared.rs"),
+            job("006_reader", "context_files: [src/shared.rs]\noutput_dir: src/\noutput_file: r.rs"),
+            job("007_dependent", "context_files: [src/shared.rs]\ndepends_on: [005_writer]\noutput_dir: src/\noutput_file: d.rs"),
+        ];
+        let issues = check_output_paths(temp.path(), &jobs);
+        let messages: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+
+        assert_eq!(issues.len(), 5, "{:#?}", messages);
+        assert!(messages[0].contains("outside the project root"));
+        assert!(messages[1].contains("protected"));
+        assert!(messages[2].contains("picked up as a job file"));
+        assert!(messages[3].contains("'003_a', '004_b' all write src/dup.rs"));
+        assert_eq!(issues[4].job_ids, vec!["005_writer".to_string(), "006_reader".to_string()]);
+        assert!(issues[4].involves("006_reader"));
+        assert!(!issues.iter().any(|i| i.involves("007_dependent")));
+    }
+
+    #[test]
+    fn test_check_output_paths_edit_then_rewrite() {
+        let temp = TempDir::new().unwrap();
+        let jobs = vec![
+            job("001_edit", "mode: edit\ntarget_files: [src/lib.rs]\noutput_dir: src/\noutput_file: lib.rs"),
+            job("002_rewrite", "output_dir: src/\noutput_file: lib.rs"),
+            job("003_edit_after", "mode: edit\ntarget_files: [src/lib.rs]\noutput_dir: src/\noutput_file: lib.rs"),
+        ];
+        let issues = check_output_paths(temp.path(), &jobs);
+        assert_eq!(issues.len(), 1, "{:#?}", issues);
+        assert_eq!(issues[0].job_ids, vec!["002_rewrite".to_string(), "001_edit".to_string()]);
+        assert!(issues[0].message.contains("discarding those edits"));
+
+        let mut ordered = jobs;
+        ordered[0].metadata.depends_on = Some(vec!["002_rewrite".to_string()]);
+        assert!(check_output_paths(temp.path(), &ordered).is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+//! Enforcing `[limits] max_output_lines` on generated files
+//!
+//! A created file longer than the limit either fails its job with a
+//! suggestion or, by default, gets a follow-up split-mode job
+//! (`<job>_split`) that the runner writes to jobs/ and runs once the job
+//! passes. The split keeps the original file as the module's entry point
+//! and moves the rest into part files, so callers keep working:
+//!
+//! - Rust: `src/big.rs` + `src/big/part1.rs`, ... (`mod.rs`, `lib.rs` and
+//!   `main.rs` get their parts next to them)
+//! - other languages: `big_part1.ts`, ... next to the file
+
+use std::path::{Path, PathBuf};
+
+use crate::core::module_index::is_index_file;
+use crate::core::parser::count_lines;
+
+/// A generated file over the line limit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OversizedOutput {
+    /// Relative to the project root
+    pub path: PathBuf,
+    pub lines: usize,
+}
+
+/// The files in `files` longer than `max_lines`
+pub fn find_oversized(files: &[(PathBuf, String)], max_lines: usize) -> Vec<OversizedOutput> {
+    files
+        .iter()
+        .map(|(path, content)| OversizedOutput { path: path.clone(), lines: count_lines(content) })
+        .filter(|output| output.lines > max_lines)
+        .collect()
+}
+
+/// Failure message for `oversized_outputs = "fail"`
+pub fn oversized_message(job_id: &str, oversized: &[OversizedOutput], max_lines: usize) -> String {
+    let files: Vec<String> = oversized
+        .iter()
+        .map(|o| format!("- {} has {} lines", o.path.display(), o.lines))
+        .collect();
+    format!(
+        "{}\nThe limit is {} lines per file ([limits] max_output_lines). Split the job's output across \
+         several files (output_files), or split the file with 'worksplit new-job {}_split --type split'.",
+        files.join("\n"),
+        max_lines,
+        job_id
+    )
+}
+
+/// ID of the follow-up job splitting `path`; `multiple` when the job has
+/// several oversized files
+pub fn split_job_id(job_id: &str, path: &Path, multiple: bool) -> String {
+    if !multiple {
+        return format!("{}_split", job_id);
+    }
+    let stem: String = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{}_split_{}", job_id, stem)
+}
+
+/// Part files for splitting `path` into `parts` files
+pub fn split_parts(path: &Path, parts: usize) -> Vec<PathBuf> {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+    (1..=parts)
+        .map(|n| match extension.as_str() {
+            "rs" if is_index_file(path) => dir.join(format!("part{}.rs", n)),
+            "rs" => dir.join(&stem).join(format!("part{}.rs", n)),
+            "" => dir.join(format!("{}_part{}", stem, n)),
+            _ => dir.join(format!("{}_part{}.{}", stem, n, extension)),
+        })
+        .collect()
+}
+
+/// The follow-up split-mode job for an oversized output of `job_id`
+pub fn split_job(job_id: &str, oversized: &OversizedOutput, max_lines: usize) -> String {
+    let path = &oversized.path;
+    // One part per limit's worth of lines; the entry file keeps some too
+    let parts = oversized.lines.div_ceil(max_lines.max(1)).max(2);
+    let part_files = split_parts(path, parts);
+    let display = |p: &Path| p.to_string_lossy().replace('\\', "/");
+    let output_dir = path.parent().map(display).filter(|d| !d.is_empty()).map_or(String::from("./"), |d| format!("{}/", d));
+    let output_file = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+
+    let mut job = String::from("---\nmode: split\n");
+    // Overwrites the job's output, so it must come after it
+    job.push_str(&format!("depends_on: [{}]\n", job_id));
+    job.push_str(&format!("target_file: {}\n", display(path)));
+    job.push_str(&format!("output_dir: {}\noutput_file: {}\noutput_files:\n", output_dir, output_file));
+    job.push_str(&format!("  - {}\n", display(path)));
+    for part in &part_files {
+        job.push_str(&format!("  - {}\n", display(part)));
+    }
+    job.push_str("---\n\n");
+    job.push_str(&format!("# Split {}\n\n", display(path)));
+    job.push_str(&format!(
+        "Job `{}` generated `{}` with {} lines, over the project's limit of {} lines per file \
+         (`[limits] max_output_lines`). Split it without changing its behavior.\n\n",
+        job_id,
+        display(path),
+        oversized.lines,
+        max_lines
+    ));
+    job.push_str("## File Structure\n\n");
+    job.push_str(&format!(
+        "- `{}`: stays the entry point; declares or imports the parts and re-exports everything it made public before\n",
+        display(path)
+    ));
+    for (n, part) in part_files.iter().enumerate() {
+        job.push_str(&format!("- `{}`: part {} of the remaining items, grouped by what they do\n", display(part), n + 1));
+    }
+    job.push_str(&format!(
+        "\nKeep every file under {} lines. Code that uses `{}` must keep working without changes.\n",
+        max_lines,
+        display(path)
+    ));
+    job
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::JobsManager;
+    use crate::models::LimitsConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_split_parts_per_language() {
+        assert_eq!(split_parts(Path::new("src/big.rs"), 2), [PathBuf::from("src/big/part1.rs"), PathBuf::from("src/big/part2.rs")]);
+        assert_eq!(split_parts(Path::new("src/store/mod.rs"), 1), [PathBuf::from("src/store/part1.rs")]);
+        assert_eq!(split_parts(Path::new("src/ui/view.ts"), 1), [PathBuf::from("src/ui/view_part1.ts")]);
+        assert_eq!(split_job_id("001_api", Path::new("src/api.rs"), false), "001_api_split");
+        assert_eq!(split_job_id("001_api", Path::new("src/Api-Types.ts"), true), "001_api_split_api_types");
+    }
+
+    #[test]
+    fn test_split_job_for_oversized_output() {
+        let files = vec![(PathBuf::from("src/big.rs"), "fn a() {}\n".repeat(25)), (PathBuf::from("src/small.rs"), "fn b() {}\n".into())];
+        let oversized = find_oversized(&files, 10);
+        assert_eq!(oversized, [OversizedOutput { path: PathBuf::from("src/big.rs"), lines: 25 }]);
+
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("jobs")).unwrap();
+        fs::write(temp.path().join("jobs/001_big_split.md"), split_job("001_big", &oversized[0], 10)).unwrap();
+        let job = JobsManager::new(temp.path().to_path_buf(), LimitsConfig::default()).parse_job("001_big_split").unwrap();
+        assert!(job.metadata.is_split_mode());
+        assert_eq!(job.metadata.target_file, Some(PathBuf::from("src/big.rs")));
+        assert_eq!(job.metadata.depends_on, Some(vec!["001_big".to_string()]));
+        assert_eq!(job.metadata.get_output_files(), [
+            PathBuf::from("src/big.rs"),
+            PathBuf::from("src/big/part1.rs"),
+            PathBuf::from("src/big/part2.rs"),
+            PathBuf::from("src/big/part3.rs"),
+        ]);
+        assert!(job.instructions.contains("25 lines, over the project's limit of 10"), "{}", job.instructions);
+
+        let message = oversized_message("001_big", &oversized, 10);
+        assert!(message.starts_with("- src/big.rs has 25 lines\nThe limit is 10 lines"), "{}", message);
+        assert!(message.contains("worksplit new-job 001_big_split --type split"), "{}", message);
+    }
+}
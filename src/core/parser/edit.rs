@@ -1,14 +1,102 @@
-use std::path::PathBuf;
+use minijinja::context;
+use regex::Regex;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::core::prompt_templates::PromptTemplates;
+use crate::models::FailedEdit;
+
+use super::prompts::{context_template_files, with_trailing_newline};
 
 /// A single edit instruction for a file
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EditInstruction {
     /// File path to edit
     pub file_path: PathBuf,
-    /// Text to find (exact match)
+    /// Text to find (exact match), or a regex pattern for `FIND_REGEX:`
     pub find: String,
-    /// Text to replace with
+    /// Text to replace with (`$1`, `${name}` expand captures for regex edits)
     pub replace: String,
+    /// Only match where the FIND text starts within these lines
+    /// (`FIND (lines 120-160):`)
+    pub line_range: Option<LineRange>,
+    /// `find` is a regex pattern (`FIND_REGEX:`)
+    pub regex: bool,
+    /// `find` names a Rust item (`ITEM: fn UserService::create_user`)
+    pub item: Option<ItemOp>,
+}
+
+/// Structural operation for an `ITEM:` block (Rust files only)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemOp {
+    /// `REPLACE:` the whole item, attributes and doc comments included
+    Replace,
+    /// `APPEND:` fields, variants or items at the end of the item's body
+    Append,
+}
+
+impl ItemOp {
+    /// Block keyword, as written in edit responses
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ItemOp::Replace => "REPLACE",
+            ItemOp::Append => "APPEND",
+        }
+    }
+
+    /// Parse a block keyword (`REPLACE` / `APPEND`, any case)
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_uppercase().as_str() {
+            "REPLACE" => Some(ItemOp::Replace),
+            "APPEND" => Some(ItemOp::Append),
+            _ => None,
+        }
+    }
+}
+
+/// A 1-based, inclusive line window for a FIND block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl std::fmt::Display for LineRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "lines {}-{}", self.start, self.end)
+    }
+}
+
+/// Parse a FIND header: `FIND:`, `FIND (lines 120-160):`, `FIND (line 42):`
+/// or `FIND (near line 50):` (a hint only, no window). `FIND_REGEX` takes the
+/// same forms.
+///
+/// Returns `None` if the line is not a FIND header, otherwise whether the
+/// block is a regex and the line window.
+fn parse_find_header(line: &str) -> Option<(bool, Option<LineRange>)> {
+    let lower = line.trim().to_lowercase();
+    let (regex, rest) = match lower.strip_prefix("find_regex") {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, lower.strip_prefix("find")?.trim_start()),
+    };
+    if rest == ":" {
+        return Some((regex, None));
+    }
+    let inner = rest.strip_prefix('(')?.strip_suffix(':')?.trim_end().strip_suffix(')')?.trim();
+    if inner.starts_with("near") {
+        return Some((regex, None));
+    }
+
+    let numbers: Vec<usize> = inner
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|n| n.parse().ok())
+        .collect();
+    let range = match numbers.as_slice() {
+        [line] if inner.starts_with("line") => Some(LineRange { start: *line, end: *line }),
+        [a, b] if inner.starts_with("line") => Some(LineRange { start: *a.min(b), end: *a.max(b) }),
+        _ => None,
+    };
+    Some((regex, range))
 }
 
 /// Parsed edits for multiple files
@@ -41,12 +129,20 @@ impl ParsedEdits {
 /// 
 /// Multiple FILE blocks can be present for different files.
 /// Multiple FIND/REPLACE/END blocks can be under a single FILE.
-pub fn parse_edit_instructions(response: &str) -> ParsedEdits {
+/// `FIND (lines 120-160):` restricts matching to a line window.
+/// `FIND_REGEX:` takes a regex pattern instead of exact text when
+/// `regex_edits` is on; otherwise its text is matched literally like `FIND:`.
+/// `ITEM: <path>` followed by `REPLACE:` or `APPEND:` edits a named Rust item.
+pub fn parse_edit_instructions(response: &str, regex_edits: bool) -> ParsedEdits {
     let mut edits = Vec::new();
     let mut affected_files = Vec::new();
     let mut current_file_path: Option<PathBuf> = None;
     let mut find_text = String::new();
     let mut replace_text = String::new();
+    let mut line_range: Option<LineRange> = None;
+    let mut is_regex = false;
+    let mut item_op: Option<ItemOp> = None;
+    let mut in_item = false;
     let mut in_find_block = false;
     let mut in_replace_block = false;
 
@@ -62,17 +158,33 @@ pub fn parse_edit_instructions(response: &str) -> ParsedEdits {
         }
 
         // Handle FIND block start
-        if trimmed_line.to_lowercase() == "find:" {
+        if let Some((regex, range)) = parse_find_header(trimmed_line) {
             in_find_block = true;
             find_text.clear();
+            line_range = range;
+            is_regex = regex && regex_edits;
+            in_item = false;
+            continue;
+        }
+
+        // Handle ITEM header (the path is the whole block); inside a block
+        // `item:` is code, e.g. a struct field or a YAML key
+        if !in_find_block && !in_replace_block && trimmed_line.to_lowercase().starts_with("item:") {
+            in_find_block = false;
+            find_text = trimmed_line[5..].trim().to_string();
+            line_range = None;
+            is_regex = false;
+            in_item = true;
             continue;
         }
 
-        // Handle REPLACE block start
-        if trimmed_line.to_lowercase() == "replace:" {
+        // Handle REPLACE (or APPEND after ITEM) block start
+        let lower = trimmed_line.to_lowercase();
+        if lower == "replace:" || (in_item && lower == "append:") {
             in_find_block = false;
             in_replace_block = true;
             replace_text.clear();
+            item_op = if in_item { ItemOp::parse(lower.trim_end_matches(':')) } else { None };
             continue;
         }
 
@@ -86,6 +198,9 @@ pub fn parse_edit_instructions(response: &str) -> ParsedEdits {
                         file_path: file_path.clone(),
                         find: find_text.trim().to_string(),
                         replace: replace_text.trim().to_string(),
+                        line_range: line_range.take(),
+                        regex: is_regex,
+                        item: item_op.take(),
                     });
                     if !affected_files.contains(file_path) {
                         affected_files.push(file_path.clone());
@@ -94,6 +209,7 @@ pub fn parse_edit_instructions(response: &str) -> ParsedEdits {
             }
             find_text.clear();
             replace_text.clear();
+            in_item = false;
             continue;
         }
 
@@ -135,54 +251,272 @@ fn normalize_whitespace(text: &str) -> String {
 /// Find fuzzy match location in content
 /// Returns (start_idx, end_idx, matched_text) if found
 pub fn find_fuzzy_match(content: &str, find_text: &str) -> Option<(usize, usize, String)> {
+    find_fuzzy_matches(content, find_text).into_iter().next()
+}
+
+/// Byte offset at which each line of `content` starts
+fn line_starts(content: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+/// Every place `find_text` matches after whitespace normalization, in file
+/// order and without overlaps, as (start_idx, end_idx, matched_text)
+pub fn find_fuzzy_matches(content: &str, find_text: &str) -> Vec<(usize, usize, String)> {
+    find_fuzzy_matches_in(content, find_text, None)
+}
+
+/// Fuzzy matches starting inside `range`
+fn find_fuzzy_matches_in(
+    content: &str,
+    find_text: &str,
+    range: Option<LineRange>,
+) -> Vec<(usize, usize, String)> {
     let normalized_find = normalize_whitespace(find_text);
     let find_lines: Vec<&str> = normalized_find.lines().collect();
+    let mut found = Vec::new();
     
     if find_lines.is_empty() {
-        return None;
+        return found;
     }
     
     let content_lines: Vec<&str> = content.lines().collect();
+    let starts = line_starts(content);
+    let (first, last) = match range {
+        Some(range) => (range.start.saturating_sub(1), range.end.min(content_lines.len())),
+        None => (0, content_lines.len()),
+    };
     
-    // Slide through content looking for a normalized match
-    for start_line in 0..content_lines.len() {
+    // Slide through content looking for normalized matches
+    let mut start_line = first;
+    while start_line < last {
         if start_line + find_lines.len() > content_lines.len() {
             break;
         }
         
-        let mut matches = true;
-        for (i, find_line) in find_lines.iter().enumerate() {
-            let content_line_normalized = normalize_whitespace(content_lines[start_line + i]);
-            if content_line_normalized != *find_line {
-                matches = false;
-                break;
-            }
+        let matches = find_lines.iter().enumerate().all(|(i, find_line)| {
+            normalize_whitespace(content_lines[start_line + i]) == *find_line
+        });
+        
+        if !matches {
+            start_line += 1;
+            continue;
         }
         
-        if matches {
-            // Found a match - calculate byte positions in original content
-            let mut start_byte = 0;
-            for line in content_lines.iter().take(start_line) {
-                start_byte += line.len() + 1; // +1 for newline
-            }
-            
-            let mut end_byte = start_byte;
-            for i in 0..find_lines.len() {
-                end_byte += content_lines[start_line + i].len();
-                if start_line + i + 1 < content_lines.len() {
-                    end_byte += 1; // newline
-                }
+        // Found a match - calculate byte positions in original content
+        let last_line = start_line + find_lines.len() - 1;
+        let start_byte = starts[start_line];
+        let end_byte = starts[last_line] + content_lines[last_line].len();
+        
+        // Extract the actual matched text from original content
+        let matched_text = content_lines[start_line..start_line + find_lines.len()]
+            .join("\n");
+        
+        found.push((start_byte, end_byte, matched_text));
+        start_line += find_lines.len();
+    }
+    
+    found
+}
+
+/// How to apply an edit whose FIND text matches several places, and only
+/// after whitespace normalization
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FuzzyPick {
+    /// Replace the candidate with this index
+    One(usize),
+    /// Replace every candidate
+    All,
+    /// Don't apply the edit
+    Fail,
+}
+
+/// Chooses between fuzzy match candidates, given the content, the edit and
+/// the candidates in file order
+pub type FuzzyPicker<'a> = dyn FnMut(&str, &EditInstruction, &[(usize, usize, String)]) -> FuzzyPick + 'a;
+
+/// A fuzzy match candidate with `context` lines around it, numbered and with
+/// the matched lines marked by `>`, for showing to a user choosing between
+/// candidates
+pub fn fuzzy_candidate_preview(content: &str, candidate: &(usize, usize, String), context: usize) -> String {
+    let first = line_of(content, candidate.0);
+    let last = line_of(content, candidate.1);
+    let lines: Vec<&str> = content.lines().collect();
+    let from = first.saturating_sub(context).max(1);
+    let to = (last + context).min(lines.len());
+    (from..=to)
+        .map(|n| {
+            let marker = if (first..=last).contains(&n) { '>' } else { ' ' };
+            format!("{} {:>5} | {}", marker, n, lines[n - 1])
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Splice `replace` over the chosen fuzzy candidates
+fn apply_fuzzy_candidates(
+    content: &str,
+    edit: &EditInstruction,
+    candidates: &[(usize, usize, String)],
+    pick: &mut FuzzyPicker<'_>,
+) -> Result<String, String> {
+    let chosen: Vec<&(usize, usize, String)> = match candidates {
+        [only] => vec![only],
+        _ => match pick(content, edit, candidates) {
+            FuzzyPick::One(index) if index < candidates.len() => vec![&candidates[index]],
+            FuzzyPick::All => candidates.iter().collect(),
+            FuzzyPick::One(_) | FuzzyPick::Fail => {
+                let lines: Vec<String> = candidates
+                    .iter()
+                    .map(|(start, _, _)| line_of(content, *start).to_string())
+                    .collect();
+                return Err(format!(
+                    "FIND text for {} matches {} places only after whitespace normalization (lines {}), \
+                     so the edit was not applied. Copy the FIND text exactly or add a line range.\nSearched for: {:?}",
+                    edit.file_path.display(),
+                    candidates.len(),
+                    lines.join(", "),
+                    edit.find.chars().take(100).collect::<String>()
+                ));
             }
-            
-            // Extract the actual matched text from original content
-            let matched_text = content_lines[start_line..start_line + find_lines.len()]
-                .join("\n");
-            
-            return Some((start_byte, end_byte, matched_text));
-        }
+        },
+    };
+    
+    // Splice from the end so earlier offsets stay valid
+    let mut result = content.to_string();
+    for (start, end, _) in chosen.iter().rev() {
+        result.replace_range(*start..*end, &edit.replace);
     }
+    tracing::info!(
+        "Fuzzy match applied for {} at {} place(s) (whitespace normalized)",
+        edit.file_path.display(),
+        chosen.len()
+    );
+    Ok(result)
+}
+
+/// 1-based line number containing byte offset `pos`
+fn line_of(content: &str, pos: usize) -> usize {
+    content[..pos].matches('\n').count() + 1
+}
+
+/// Apply an edit whose FIND must start within `range`
+fn apply_edit_in_range(
+    content: &str,
+    edit: &EditInstruction,
+    range: LineRange,
+    pick: &mut FuzzyPicker<'_>,
+) -> Result<String, String> {
+    let starts = line_starts(content);
+    let window_start = starts.get(range.start.saturating_sub(1)).copied().unwrap_or(content.len());
+    let window_end = starts.get(range.end).copied().unwrap_or(content.len() + 1);
+    let splice = |start: usize, end: usize| {
+        format!("{}{}{}", &content[..start], edit.replace, &content[end..])
+    };
+    
+    // Strategy 1: Exact match starting inside the window
+    if let Some((start, _)) = content
+        .match_indices(edit.find.as_str())
+        .find(|(i, _)| (window_start..window_end).contains(i))
+    {
+        return Ok(splice(start, start + edit.find.len()));
+    }
+    
+    // Strategy 2: Fuzzy match starting inside the window
+    let candidates = find_fuzzy_matches_in(content, &edit.find, Some(range));
+    if !candidates.is_empty() {
+        return apply_fuzzy_candidates(content, edit, &candidates, pick);
+    }
+    
+    // Point at matches outside the window, if any
+    let outside = content
+        .find(edit.find.as_str())
+        .or_else(|| find_fuzzy_match(content, &edit.find).map(|(start, _, _)| start))
+        .map(|start| format!("\n\nFound at line {}, outside the requested range", line_of(content, start)))
+        .unwrap_or_default();
     
-    None
+    Err(format!(
+        "FIND text not found within {} of {}.\nSearched for: {:?}{}",
+        range,
+        edit.file_path.display(),
+        edit.find.chars().take(100).collect::<String>(),
+        outside
+    ))
+}
+
+/// Apply an `ITEM:` edit by locating the item with syn
+fn apply_item_edit(content: &str, edit: &EditInstruction, op: ItemOp) -> Result<String, String> {
+    if edit.file_path.extension().and_then(|e| e.to_str()) != Some("rs") {
+        return Err(format!(
+            "ITEM edits only work on Rust files, not {}",
+            edit.file_path.display()
+        ));
+    }
+    #[cfg(feature = "rust-ast")]
+    {
+        super::rust_ast::apply_item_edit(content, edit, op)
+    }
+    #[cfg(not(feature = "rust-ast"))]
+    {
+        let _ = (content, op);
+        Err("ITEM edits need WorkSplit built with the \"rust-ast\" feature".to_string())
+    }
+}
+
+/// Apply a `FIND_REGEX:` edit. The pattern runs in multi-line mode (`^`/`$`
+/// match at line boundaries) and must match exactly once, inside the line
+/// range if one is given; REPLACE expands `$1` / `${name}` captures.
+fn apply_regex_edit(content: &str, edit: &EditInstruction) -> Result<String, String> {
+    let regex = Regex::new(&format!("(?m){}", edit.find)).map_err(|e| {
+        format!("Invalid FIND_REGEX pattern for {}: {}", edit.file_path.display(), e)
+    })?;
+
+    let window = edit.line_range.map(|range| {
+        let starts = line_starts(content);
+        let start = starts.get(range.start.saturating_sub(1)).copied().unwrap_or(content.len());
+        let end = starts.get(range.end).copied().unwrap_or(content.len() + 1);
+        start..end
+    });
+    let matches: Vec<_> = regex
+        .captures_iter(content)
+        .filter(|caps| {
+            let start = caps.get(0).map(|m| m.start()).unwrap_or(0);
+            window.as_ref().is_none_or(|w| w.contains(&start))
+        })
+        .collect();
+    let scope = edit.line_range.map(|range| format!(" within {}", range)).unwrap_or_default();
+
+    match matches.as_slice() {
+        [caps] => {
+            let whole = caps.get(0).expect("capture 0 is always present");
+            let mut replacement = String::new();
+            caps.expand(&edit.replace, &mut replacement);
+            Ok(format!("{}{}{}", &content[..whole.start()], replacement, &content[whole.end()..]))
+        }
+        [] => Err(format!(
+            "FIND_REGEX pattern matched nothing in {}{}.\nPattern: {:?}",
+            edit.file_path.display(),
+            scope,
+            edit.find
+        )),
+        many => {
+            let lines: Vec<String> = many
+                .iter()
+                .filter_map(|caps| caps.get(0))
+                .map(|m| line_of(content, m.start()).to_string())
+                .collect();
+            Err(format!(
+                "FIND_REGEX pattern matched {} times in {}{} (lines {}); it must match exactly once. \
+                 Make the pattern more specific or add a line range.\nPattern: {:?}",
+                many.len(),
+                edit.file_path.display(),
+                scope,
+                lines.join(", "),
+                edit.find
+            ))
+        }
+    }
 }
 
 /// Apply a single edit to file content
@@ -191,22 +525,42 @@ pub fn find_fuzzy_match(content: &str, find_text: &str) -> Option<(usize, usize,
 /// Matching strategy:
 /// 1. Try exact match first
 /// 2. If exact fails, try fuzzy match (normalized whitespace)
-/// 3. Fuzzy match auto-applies with the actual matched text
+/// 3. Fuzzy match auto-applies with the actual matched text; with several
+///    fuzzy candidates the first one is used (see [`apply_edit_with`])
+///
+/// With a line range (`FIND (lines 120-160):`) both strategies only accept
+/// matches that start inside the range. Regex edits skip both strategies and
+/// must match exactly once.
 pub fn apply_edit(content: &str, edit: &EditInstruction) -> Result<String, String> {
+    apply_edit_with(content, edit, &mut |_, _, _| FuzzyPick::One(0))
+}
+
+/// [`apply_edit`], with `pick` choosing which candidates to replace when the
+/// FIND text matches several places only after whitespace normalization
+pub fn apply_edit_with(
+    content: &str,
+    edit: &EditInstruction,
+    pick: &mut FuzzyPicker<'_>,
+) -> Result<String, String> {
+    if let Some(op) = edit.item {
+        return apply_item_edit(content, edit, op);
+    }
+    if edit.regex {
+        return apply_regex_edit(content, edit);
+    }
+    if let Some(range) = edit.line_range {
+        return apply_edit_in_range(content, edit, range, pick);
+    }
+    
     // Strategy 1: Exact match
     if content.contains(&edit.find) {
         return Ok(content.replacen(&edit.find, &edit.replace, 1));
     }
     
     // Strategy 2: Fuzzy match with normalized whitespace
-    if let Some((_start, _end, matched_text)) = find_fuzzy_match(content, &edit.find) {
-        // Apply edit using the actual text found in the file
-        let result = content.replacen(&matched_text, &edit.replace, 1);
-        tracing::info!(
-            "Fuzzy match applied for {} (whitespace normalized)",
-            edit.file_path.display()
-        );
-        return Ok(result);
+    let candidates = find_fuzzy_matches(content, &edit.find);
+    if !candidates.is_empty() {
+        return apply_fuzzy_candidates(content, edit, &candidates, pick);
     }
     
     // No match found - provide detailed feedback
@@ -263,74 +617,151 @@ pub fn apply_edits(content: &str, edits: &[&EditInstruction]) -> Result<String,
     Ok(result)
 }
 
+/// A file to edit as the edit template sees it: its content with a line
+/// number marker every 10 lines
+#[derive(Debug, Serialize)]
+struct NumberedFile {
+    path: String,
+    lines: usize,
+    numbered: String,
+}
+
+fn numbered_file(path: &Path, content: &str) -> NumberedFile {
+    let mut numbered = String::new();
+    for (idx, line) in content.lines().enumerate() {
+        let line_num = idx + 1;
+        // Add line number marker every 10 lines or at first line
+        if line_num == 1 || line_num % 10 == 0 {
+            numbered.push_str(&format!("[Line {:>4}] ", line_num));
+        } else {
+            numbered.push_str("            "); // Padding to align code
+        }
+        numbered.push_str(line);
+        numbered.push('\n');
+    }
+    NumberedFile { path: path.display().to_string(), lines: content.lines().count(), numbered }
+}
+
+fn edit_context(
+    system_prompt: &str,
+    target_files: &[(PathBuf, String)],
+    context_files: &[(PathBuf, String)],
+    instructions: &str,
+    regex_edits: bool,
+) -> minijinja::Value {
+    let item_edits =
+        cfg!(feature = "rust-ast") && target_files.iter().any(|(p, _)| p.extension().is_some_and(|e| e == "rs"));
+    let context_files = context_template_files(context_files, target_files.iter().map(|(path, _)| path.as_path()));
+    let target_files: Vec<NumberedFile> = target_files.iter().map(|(path, content)| numbered_file(path, content)).collect();
+    context! {
+        system_prompt,
+        item_edits,
+        regex_edits,
+        target_files,
+        context_files,
+        instructions,
+    }
+}
+
 /// Assemble a creation prompt for edit mode
 pub fn assemble_edit_prompt(
+    templates: &PromptTemplates,
     system_prompt: &str,
     target_files: &[(PathBuf, String)],  // Files to be edited with their current content
     context_files: &[(PathBuf, String)], // Additional context
     instructions: &str,
+    regex_edits: bool, // Advertise FIND_REGEX blocks
 ) -> String {
-    let mut prompt = String::new();
+    templates.render("edit", edit_context(system_prompt, target_files, context_files, instructions, regex_edits))
+}
 
-    // System prompt
-    prompt.push_str("[SYSTEM]\n");
-    prompt.push_str(system_prompt);
-    prompt.push_str("\n\n");
-    
-    // Edit mode instructions
-    prompt.push_str("[EDIT MODE]\n");
-    prompt.push_str("You are making surgical edits to existing files. ");
-    prompt.push_str("Use the following format for each edit:\n\n");
-    prompt.push_str("FILE: path/to/file.rs\n");
-    prompt.push_str("FIND:\n<exact text to find>\n");
-    prompt.push_str("REPLACE:\n<replacement text>\n");
-    prompt.push_str("END\n\n");
-    prompt.push_str("Important:\n");
-    prompt.push_str("- FIND text must match exactly (including whitespace)\n");
-    prompt.push_str("- Include enough context in FIND to be unique\n");
-    prompt.push_str("- Multiple edits can be made to the same file\n");
-    prompt.push_str("- Use line number hints like 'FIND (near line 50):' to reference locations\n\n");
-
-    // Target files to be edited (with line number hints)
-    prompt.push_str("[TARGET FILES]\n");
-    prompt.push_str("These are the files you will be editing (line numbers shown every 10 lines):\n\n");
-    for (path, content) in target_files {
-        prompt.push_str(&format!("### File: {} ({} lines)\n", path.display(), content.lines().count()));
-        prompt.push_str("```\n");
-        for (idx, line) in content.lines().enumerate() {
-            let line_num = idx + 1;
-            // Add line number marker every 10 lines or at first line
-            if line_num == 1 || line_num % 10 == 0 {
-                prompt.push_str(&format!("[Line {:>4}] ", line_num));
-            } else {
-                prompt.push_str("            "); // Padding to align code
-            }
-            prompt.push_str(line);
-            prompt.push('\n');
-        }
-        prompt.push_str("```\n\n");
-    }
-
-    // Additional context files
-    if !context_files.is_empty() {
-        prompt.push_str("[CONTEXT]\n");
-        for (path, content) in context_files {
-            prompt.push_str(&format!("### File: {}\n", path.display()));
-            prompt.push_str("```\n");
-            prompt.push_str(content);
-            if !content.ends_with('\n') {
-                prompt.push('\n');
+/// Assemble a prompt for `run --continue`: only the edits that failed last
+/// time, with the reasons, against the files' current contents
+pub fn assemble_continue_prompt(
+    templates: &PromptTemplates,
+    system_prompt: &str,
+    target_files: &[(PathBuf, String)],
+    failed_edits: &[FailedEdit],
+    instructions: &str,
+) -> String {
+    let failed: Vec<minijinja::Value> = failed_edits
+        .iter()
+        .map(|edit| {
+            let op = edit.item.as_deref().and_then(ItemOp::parse);
+            let find_marker = match op {
+                Some(_) => "ITEM: ",
+                None if edit.regex => "FIND_REGEX:\n",
+                None => "FIND:\n",
+            };
+            context! {
+                file_path => &edit.file_path,
+                reason => &edit.reason,
+                suggested_line => edit.suggested_line,
+                find_marker,
+                find => if edit.find.is_empty() { &edit.find_preview } else { &edit.find },
+                op => op.map_or("REPLACE", ItemOp::as_str),
+                replace => &edit.replace,
             }
-            prompt.push_str("```\n\n");
+        })
+        .collect();
+    let regex_edits = failed_edits.iter().any(|edit| edit.regex);
+    let ctx = edit_context(system_prompt, target_files, &[], instructions, regex_edits);
+    templates.render("continue", context! { failed_edits => failed, ..ctx })
+}
+
+/// Marks the line a fill-in-the-middle edit replaces when the job sets no
+/// `fill_range`
+pub const FILL_MARKER: &str = "worksplit:fill";
+
+/// The lines a fill-in-the-middle edit replaces, as a 0-based half-open
+/// range: `lines` (1-based, inclusive) if given, else the line marked with
+/// [`FILL_MARKER`]
+pub fn fill_site(content: &str, lines: Option<(usize, usize)>) -> Result<(usize, usize), String> {
+    let total = content.lines().count();
+    if let Some((start, end)) = lines {
+        if end > total {
+            return Err(format!("fill_range ends at line {} but the file has {} lines", end, total));
         }
+        return Ok((start - 1, end));
+    }
+    let marked: Vec<usize> = content.lines().enumerate().filter(|(_, l)| l.contains(FILL_MARKER)).map(|(i, _)| i).collect();
+    match marked.as_slice() {
+        [line] => Ok((*line, line + 1)),
+        [] => Err(format!("No line is marked '{}' and the job sets no fill_range", FILL_MARKER)),
+        lines => Err(format!("{} lines are marked '{}'; mark one or set fill_range", lines.len(), FILL_MARKER)),
     }
+}
 
-    // Instructions
-    prompt.push_str("[INSTRUCTIONS]\n");
-    prompt.push_str(instructions);
-    prompt.push_str("\n\n");
+/// Line comment syntax of a file, for instructions inside code
+fn comment_prefix(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "py" | "sh" | "bash" | "rb" | "pl" | "r" | "toml" | "yaml" | "yml" => "#",
+        "sql" | "lua" | "hs" => "--",
+        _ => "//",
+    }
+}
 
-    prompt
+/// Assemble the prompt of a fill-in-the-middle edit: the code before the
+/// change site, ending with the instructions as comments at `indent` where
+/// the new code goes. The code after the site is sent as the suffix. The
+/// built-in template leaves context files out: FIM models expect code.
+pub fn assemble_fim_prompt(
+    templates: &PromptTemplates,
+    path: &Path,
+    prefix: &str,
+    indent: &str,
+    context_files: &[(PathBuf, String)],
+    instructions: &str,
+) -> String {
+    let instructions: Vec<&str> = instructions.lines().map(str::trim_end).filter(|l| !l.trim().is_empty()).collect();
+    templates.render("fim", context! {
+        path => path.display().to_string(),
+        prefix => if prefix.is_empty() { String::new() } else { with_trailing_newline(prefix) },
+        comment => comment_prefix(path),
+        indent,
+        instructions,
+        context_files => context_template_files(context_files, [path]),
+    })
 }
 
 #[cfg(test)]
@@ -347,7 +778,7 @@ REPLACE:
 fn new() {}
 END
 "#;
-        let parsed = parse_edit_instructions(response);
+        let parsed = parse_edit_instructions(response, false);
         assert_eq!(parsed.edits.len(), 1);
         assert_eq!(parsed.edits[0].file_path, PathBuf::from("src/main.rs"));
         assert_eq!(parsed.edits[0].find, "fn old() {}");
@@ -371,7 +802,7 @@ REPLACE:
 line2_new
 END
 "#;
-        let parsed = parse_edit_instructions(response);
+        let parsed = parse_edit_instructions(response, false);
         assert_eq!(parsed.edits.len(), 2);
         assert_eq!(parsed.affected_files.len(), 2);
     }
@@ -393,7 +824,7 @@ REPLACE:
 fn new2() {}
 END
 "#;
-        let parsed = parse_edit_instructions(response);
+        let parsed = parse_edit_instructions(response, false);
         assert_eq!(parsed.edits.len(), 2);
         // Note: affected_files will have duplicates removed
     }
@@ -405,6 +836,9 @@ END
             file_path: PathBuf::from("test.rs"),
             find: "fn old() {}".to_string(),
             replace: "fn new() {}".to_string(),
+            line_range: None,
+            regex: false,
+            item: None,
         };
         let result = apply_edit(content, &edit).unwrap();
         assert_eq!(result, "fn new() {}\nfn other() {}");
@@ -417,6 +851,9 @@ END
             file_path: PathBuf::from("test.rs"),
             find: "fn old() {}".to_string(),
             replace: "fn new() {}".to_string(),
+            line_range: None,
+            regex: false,
+            item: None,
         };
         let result = apply_edit(content, &edit);
         assert!(result.is_err());
@@ -429,6 +866,9 @@ END
             file_path: PathBuf::from("test.rs"),
             find: "fn old() {}".to_string(),
             replace: "".to_string(),
+            line_range: None,
+            regex: false,
+            item: None,
         };
         let result = apply_edit(content, &edit).unwrap();
         assert_eq!(result, "\nfn other() {}");
@@ -441,11 +881,17 @@ END
             file_path: PathBuf::from("test.rs"),
             find: "fn old1() {}".to_string(),
             replace: "fn new1() {}".to_string(),
+            line_range: None,
+            regex: false,
+            item: None,
         };
         let edit2 = EditInstruction {
             file_path: PathBuf::from("test.rs"),
             find: "fn old2() {}".to_string(),
             replace: "fn new2() {}".to_string(),
+            line_range: None,
+            regex: false,
+            item: None,
         };
         let result = apply_edits(content, &[&edit1, &edit2]).unwrap();
         assert_eq!(result, "fn new1() {}\nfn new2() {}\nfn other() {}");
@@ -461,7 +907,7 @@ replace:
 fn new() {}
 end
 "#;
-        let parsed = parse_edit_instructions(response);
+        let parsed = parse_edit_instructions(response, false);
         assert_eq!(parsed.edits.len(), 1);
         assert_eq!(parsed.edits[0].file_path, PathBuf::from("src/main.rs"));
     }
@@ -484,6 +930,9 @@ end
             file_path: PathBuf::from("test.rs"),
             find: "        let x = 1;".to_string(),  // Wrong indent
             replace: "    let y = 2;".to_string(),
+            line_range: None,
+            regex: false,
+            item: None,
         };
         let result = apply_edit(content, &edit).unwrap();
         assert_eq!(result, "fn main() {\n    let y = 2;\n}");
@@ -497,6 +946,9 @@ end
             file_path: PathBuf::from("test.rs"),
             find: "\tfield: i32,".to_string(),  // Tab instead of spaces
             replace: "    new_field: String,".to_string(),
+            line_range: None,
+            regex: false,
+            item: None,
         };
         let result = apply_edit(content, &edit).unwrap();
         assert_eq!(result, "struct Foo {\n    new_field: String,\n}");
@@ -509,6 +961,9 @@ end
             file_path: PathBuf::from("test.rs"),
             find: "  fn bar() {\n      println!(\"hello\");\n  }".to_string(),  // Different indent
             replace: "    fn baz() {\n        println!(\"world\");\n    }".to_string(),
+            line_range: None,
+            regex: false,
+            item: None,
         };
         let result = apply_edit(content, &edit).unwrap();
         assert!(result.contains("fn baz()"));
@@ -523,6 +978,9 @@ end
             file_path: PathBuf::from("test.rs"),
             find: "fn old() {}".to_string(),
             replace: "fn new() {}".to_string(),
+            line_range: None,
+            regex: false,
+            item: None,
         };
         let result = apply_edit(content, &edit).unwrap();
         assert_eq!(result, "fn new() {}");
@@ -538,6 +996,9 @@ end
             file_path: PathBuf::from("test.rs"),
             find: "let x = 1;".to_string(),  // No indent in find
             replace: "let x = 2;".to_string(),  // No indent in replace
+            line_range: None,
+            regex: false,
+            item: None,
         };
         let result = apply_edit(content, &edit).unwrap();
         assert_eq!(result, "    let x = 2;");  // Original 4-space indent preserved
@@ -552,8 +1013,272 @@ end
             file_path: PathBuf::from("test.rs"),
             find: "    let x = 1;   ".to_string(),  // Extra trailing spaces - won't exact match
             replace: "    let x = 2;".to_string(),
+            line_range: None,
+            regex: false,
+            item: None,
         };
         let result = apply_edit(content, &edit).unwrap();
         assert!(result.contains("let x = 2;"));
     }
+
+    #[test]
+    fn test_ambiguous_fuzzy_match_is_picked() {
+        let content = "fn a() {\n  reset( );\n}\nfn b() {\n\treset( );\n}\n";
+        let edit = EditInstruction {
+            file_path: PathBuf::from("test.rs"),
+            find: "    reset( );".to_string(),
+            replace: "    reset(true);".to_string(),
+            line_range: None,
+            regex: false,
+            item: None,
+        };
+        let candidates = find_fuzzy_matches(content, &edit.find);
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(fuzzy_candidate_preview(content, &candidates[1], 1),
+            "      4 | fn b() {\n>     5 | \treset( );\n      6 | }");
+
+        // apply_edit keeps taking the first candidate
+        assert_eq!(apply_edit(content, &edit).unwrap(),
+            "fn a() {\n    reset(true);\n}\nfn b() {\n\treset( );\n}\n");
+
+        let mut offered = 0;
+        let second = apply_edit_with(content, &edit, &mut |_, _, found| { offered = found.len(); FuzzyPick::One(1) }).unwrap();
+        assert_eq!(offered, 2);
+        assert_eq!(second, "fn a() {\n  reset( );\n}\nfn b() {\n    reset(true);\n}\n");
+
+        let all = apply_edit_with(content, &edit, &mut |_, _, _| FuzzyPick::All).unwrap();
+        assert_eq!(all.matches("reset(true)").count(), 2);
+
+        let refused = apply_edit_with(content, &edit, &mut |_, _, _| FuzzyPick::Fail).unwrap_err();
+        assert!(refused.contains("matches 2 places only after whitespace normalization (lines 2, 5)"), "{}", refused);
+
+        // A single candidate is applied without asking
+        let ranged = EditInstruction { line_range: Some(LineRange { start: 4, end: 6 }), ..edit.clone() };
+        let only = apply_edit_with(content, &ranged, &mut |_, _, _| panic!("not ambiguous")).unwrap();
+        assert!(only.ends_with("fn b() {\n    reset(true);\n}\n"));
+    }
+
+    #[test]
+    fn test_parse_find_line_range() {
+        let response = r#"FILE: src/lib.rs
+FIND (lines 120-160):
+return x;
+REPLACE:
+return y;
+END
+FIND (line 42):
+let a = 1;
+REPLACE:
+let a = 2;
+END
+FIND (near line 50):
+let b = 1;
+REPLACE:
+let b = 2;
+END"#;
+
+        let parsed = parse_edit_instructions(response, false);
+        assert_eq!(parsed.edits.len(), 3);
+        assert_eq!(parsed.edits[0].line_range, Some(LineRange { start: 120, end: 160 }));
+        assert_eq!(parsed.edits[1].line_range, Some(LineRange { start: 42, end: 42 }));
+        assert_eq!(parsed.edits[2].line_range, None);
+        assert_eq!(parsed.edits[2].find, "let b = 1;");
+    }
+
+    #[test]
+    fn test_line_range_selects_repeated_match() {
+        let content = "fn a() {\n    return x;\n}\nfn b() {\n    return x;\n}\n";
+        let edit = EditInstruction {
+            file_path: PathBuf::from("test.rs"),
+            find: "return x;".to_string(),
+            replace: "return y;".to_string(),
+            line_range: Some(LineRange { start: 4, end: 6 }),
+            regex: false,
+            item: None,
+        };
+        let result = apply_edit(content, &edit).unwrap();
+        assert_eq!(result, "fn a() {\n    return x;\n}\nfn b() {\n    return y;\n}\n");
+
+        // Fuzzy matching is also restricted to the window
+        let edit = EditInstruction {
+            find: "fn   b() {\n  return x;".to_string(),
+            replace: "fn b() {\n    return z;".to_string(),
+            ..edit
+        };
+        let result = apply_edit(content, &edit).unwrap();
+        assert_eq!(result, "fn a() {\n    return x;\n}\nfn b() {\n    return z;\n}\n");
+    }
+
+    #[test]
+    fn test_line_range_rejects_match_outside_window() {
+        let content = "let a = 1;\nlet b = 2;\nlet c = 3;\n";
+        let edit = EditInstruction {
+            file_path: PathBuf::from("test.rs"),
+            find: "let a = 1;".to_string(),
+            replace: "let a = 10;".to_string(),
+            line_range: Some(LineRange { start: 2, end: 3 }),
+            regex: false,
+            item: None,
+        };
+        let err = apply_edit(content, &edit).unwrap_err();
+        assert!(err.contains("within lines 2-3"), "{}", err);
+        assert!(err.contains("Found at line 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_find_regex() {
+        let response = r#"FILE: src/lib.rs
+FIND_REGEX (lines 1-20):
+fn (\w+)_old\(\)
+REPLACE:
+fn ${1}_new()
+END"#;
+
+        let parsed = parse_edit_instructions(response, true);
+        assert_eq!(parsed.edits.len(), 1);
+        assert!(parsed.edits[0].regex);
+        assert_eq!(parsed.edits[0].find, r"fn (\w+)_old\(\)");
+        assert_eq!(parsed.edits[0].line_range, Some(LineRange { start: 1, end: 20 }));
+
+        // Without regex_edits the pattern is plain FIND text
+        let literal = parse_edit_instructions(response, false);
+        assert!(!literal.edits[0].regex);
+        assert_eq!(literal.edits[0].line_range, Some(LineRange { start: 1, end: 20 }));
+        assert!(apply_edit("fn load_old() {}\n", &literal.edits[0]).is_err());
+        assert_eq!(apply_edit("fn (\\w+)_old\\(\\)\n", &literal.edits[0]).unwrap(), "fn ${1}_new()\n");
+    }
+
+    #[test]
+    fn test_edit_prompt_offers_regex_only_when_enabled() {
+        let targets = vec![(PathBuf::from("src/lib.rs"), "fn a() {}\n".to_string())];
+        let templates = PromptTemplates::default();
+        let off = assemble_edit_prompt(&templates, "sys", &targets, &[], "Rename a", false);
+        assert!(!off.contains("FIND_REGEX"));
+        let on = assemble_edit_prompt(&templates, "sys", &targets, &[], "Rename a", true);
+        assert!(on.contains("'FIND_REGEX:' takes a regex"));
+    }
+
+    #[test]
+    fn test_regex_edit_expands_captures() {
+        let content = "let timeout = config.get(\"timeout\").unwrap();\nlet retries = 3;\n";
+        let edit = EditInstruction {
+            file_path: PathBuf::from("test.rs"),
+            find: r#"config\.get\("(\w+)"\)\.unwrap\(\)"#.to_string(),
+            replace: r#"config.get("$1").expect("missing $1")"#.to_string(),
+            line_range: None,
+            regex: true,
+            item: None,
+        };
+        let result = apply_edit(content, &edit).unwrap();
+        assert_eq!(
+            result,
+            "let timeout = config.get(\"timeout\").expect(\"missing timeout\");\nlet retries = 3;\n"
+        );
+    }
+
+    #[test]
+    fn test_regex_edit_requires_single_match() {
+        let content = "let a = 1;\nlet b = 1;\n";
+        let edit = EditInstruction {
+            file_path: PathBuf::from("test.rs"),
+            find: r"^let (\w) = 1;$".to_string(),
+            replace: "let $1 = 2;".to_string(),
+            line_range: None,
+            regex: true,
+            item: None,
+        };
+        let err = apply_edit(content, &edit).unwrap_err();
+        assert!(err.contains("matched 2 times"), "{}", err);
+        assert!(err.contains("(lines 1, 2)"), "{}", err);
+
+        // A line range narrows it down to one match
+        let ranged = EditInstruction { line_range: Some(LineRange { start: 2, end: 2 }), ..edit.clone() };
+        assert_eq!(apply_edit(content, &ranged).unwrap(), "let a = 1;\nlet b = 2;\n");
+
+        let none = EditInstruction { find: r"^let c".to_string(), ..edit.clone() };
+        assert!(apply_edit(content, &none).unwrap_err().contains("matched nothing"));
+
+        let invalid = EditInstruction { find: "let (".to_string(), ..edit };
+        assert!(apply_edit(content, &invalid).unwrap_err().contains("Invalid FIND_REGEX pattern"));
+    }
+
+    #[test]
+    fn test_parse_item_blocks() {
+        let response = r#"FILE: src/config.rs
+ITEM: struct Config
+APPEND:
+pub timeout: u64
+END
+ITEM: fn Config::load
+REPLACE:
+pub fn load() -> Self { Self::default() }
+END
+APPEND:
+not an edit
+END"#;
+
+        let parsed = parse_edit_instructions(response, false);
+        assert_eq!(parsed.edits.len(), 2);
+        assert_eq!(parsed.edits[0].find, "struct Config");
+        assert_eq!(parsed.edits[0].item, Some(ItemOp::Append));
+        assert_eq!(parsed.edits[0].replace, "pub timeout: u64");
+        assert_eq!(parsed.edits[1].find, "fn Config::load");
+        assert_eq!(parsed.edits[1].item, Some(ItemOp::Replace));
+    }
+
+    #[test]
+    fn test_item_lines_inside_find_replace_blocks() {
+        let response = r#"FILE: src/edit.rs
+FIND:
+pub struct Edit {
+    item: Foo,
+}
+REPLACE:
+pub struct Edit {
+    item: Foo,
+    regex: bool,
+}
+END"#;
+
+        let parsed = parse_edit_instructions(response, false);
+        assert_eq!(parsed.edits.len(), 1);
+        assert_eq!(parsed.edits[0].find, "pub struct Edit {\n    item: Foo,\n}");
+        assert_eq!(parsed.edits[0].replace, "pub struct Edit {\n    item: Foo,\n    regex: bool,\n}");
+        assert_eq!(parsed.edits[0].item, None);
+    }
+
+    #[test]
+    fn test_assemble_continue_prompt() {
+        let targets = vec![(PathBuf::from("src/lib.rs"), "fn beta() {}\n".to_string())];
+        let failed = vec![FailedEdit {
+            file_path: "src/lib.rs".to_string(),
+            find_preview: "fn betaa() {}".to_string(),
+            reason: "FIND text not found".to_string(),
+            suggested_line: Some(1),
+            find: "fn betaa() {}".to_string(),
+            replace: "fn two() {}".to_string(),
+            regex: false,
+            item: None,
+        }];
+        let prompt = assemble_continue_prompt(&PromptTemplates::default(), "sys", &targets, &failed, "Rename beta");
+
+        assert!(prompt.contains("### File: src/lib.rs"));
+        assert!(prompt.contains("[FAILED EDITS]"));
+        assert!(prompt.contains("Reason: FIND text not found"));
+        assert!(prompt.contains("Closest match near line 1"));
+        assert!(prompt.contains("FIND:\nfn betaa() {}\nREPLACE:\nfn two() {}\nEND"));
+    }
+
+    #[test]
+    fn test_fill_site_and_fim_prompt() {
+        let content = "fn a() {\n    // worksplit:fill\n}\n";
+        assert_eq!(fill_site(content, None), Ok((1, 2)));
+        assert_eq!(fill_site(content, Some((1, 3))), Ok((0, 3)));
+        assert!(fill_site(content, Some((2, 4))).unwrap_err().contains("has 3 lines"));
+        assert!(fill_site("fn a() {}\n", None).unwrap_err().contains("fill_range"));
+
+        let templates = PromptTemplates::default();
+        let prompt = assemble_fim_prompt(&templates, Path::new("src/a.py"), "def a():", "    ", &[], "Return one.\n\nNo imports.");
+        assert_eq!(prompt, "def a():\n    # Return one.\n    # No imports.\n");
+    }
 }
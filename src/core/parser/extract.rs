@@ -1,9 +1,12 @@
 //! Code extraction and parsing functions for LLM responses.
 
 use regex::Regex;
+use serde::Deserialize;
 use std::path::PathBuf;
 use tracing::debug;
 
+use crate::models::{RubricScores, SanitizeConfig};
+
 use super::{ExtractedFile, ParsedReplacePatterns, ReplacePatternInstruction, StructLiteralMatch, VerificationResult};
 
 /// Strip nested fence formats from content
@@ -121,6 +124,27 @@ pub fn extract_code_files(response: &str) -> Vec<ExtractedFile> {
     }
 }
 
+/// Extract the document from a report mode response
+///
+/// Unlike code extraction, fenced blocks inside the answer are part of the
+/// report and kept. Only a wrapper around the whole response (`~~~worksplit`
+/// or a ```markdown fence) is removed.
+pub fn extract_report(response: &str) -> String {
+    let trimmed = response.trim();
+
+    let worksplit_re = Regex::new(r"(?is)^~~~worksplit[^\n]*\n(.*?)\n?~~~worksplit$").unwrap();
+    if let Some(caps) = worksplit_re.captures(trimmed) {
+        return caps[1].trim().to_string();
+    }
+
+    let markdown_re = Regex::new(r"(?is)^```(?:markdown|md)[ \t]*\n(.*?)\n?```$").unwrap();
+    if let Some(caps) = markdown_re.captures(trimmed) {
+        return caps[1].trim().to_string();
+    }
+
+    trimmed.to_string()
+}
+
 /// Strip worksplit delimiter lines from content
 fn strip_worksplit_delimiters(content: &str) -> String {
     content
@@ -151,6 +175,122 @@ pub fn extract_code(response: &str) -> String {
         .join("\n\n")
 }
 
+/// Clean up a model response before anything is extracted from it
+///
+/// Reasoning models such as deepseek-r1 open with `<think>...</think>` blocks,
+/// and chatty models introduce the answer ("Sure! Here is the code:"). Both
+/// end up in the file when the answer isn't fenced, and a reasoning block
+/// that mentions failing confuses verification. `[ollama.sanitize]` says
+/// what to strip for `model`.
+pub fn sanitize_response(response: &str, config: &SanitizeConfig, model: &str) -> String {
+    let (thinking, preamble) = config.for_model(model);
+    let mut cleaned = response.to_string();
+    if thinking {
+        cleaned = strip_thinking(&cleaned, &config.thinking_tags);
+    }
+    if preamble {
+        cleaned = strip_preamble(&cleaned);
+    }
+    if cleaned.len() != response.len() {
+        debug!("Sanitized response of {}: {} -> {} chars", model, response.len(), cleaned.len());
+    }
+    cleaned
+}
+
+/// Remove the reasoning blocks a response starts with
+///
+/// Only leading blocks are removed, so code that handles such tags is kept.
+/// An unclosed block runs to the end of the response (the model ran out
+/// while reasoning). A closing tag on its own line without an opening one
+/// ends reasoning whose opening tag was part of the chat template.
+fn strip_thinking(response: &str, tags: &[String]) -> String {
+    let mut rest = response;
+    let mut stripped = true;
+    while stripped {
+        stripped = false;
+        for tag in tags {
+            let open = format!("<{}>", tag);
+            let close = format!("</{}>", tag);
+            if let Some(after) = rest.trim_start().strip_prefix(open.as_str()) {
+                rest = after.find(close.as_str()).map_or("", |i| &after[i + close.len()..]);
+                stripped = true;
+            }
+        }
+    }
+
+    for tag in tags {
+        let orphan = Regex::new(&format!(r"(?m)^[ \t]*</{}>[ \t]*$", regex::escape(tag))).unwrap();
+        if let Some(m) = orphan.find(rest) {
+            if !rest[..m.start()].contains(&format!("<{}>", tag)) {
+                rest = &rest[m.end()..];
+            }
+        }
+    }
+    rest.trim_start().to_string()
+}
+
+/// Remove leading lines that only introduce the answer: an opener such as
+/// "Sure", "Here is" or "I'll" on a line ending in a colon. A line naming a
+/// verdict is kept for verification.
+fn strip_preamble(response: &str) -> String {
+    let opener = Regex::new(
+        r"(?i)^(sure|okay|ok|certainly|of course|absolutely|alright|great|here('s| is| are)|below is|the following|i('ll| will|'ve| have)|let me|as requested)\b.*:$"
+    ).unwrap();
+    let verdict = Regex::new(r"\b(PASS|FAIL)").unwrap();
+
+    let mut rest = response.trim_start();
+    while let Some(line) = rest.lines().next() {
+        let line = line.trim();
+        if !opener.is_match(line) || verdict.is_match(line) {
+            break;
+        }
+        let next = rest.find('\n').map_or("", |i| &rest[i + 1..]).trim_start();
+        if next.is_empty() {
+            break;
+        }
+        rest = next;
+    }
+    rest.to_string()
+}
+
+/// Extract the code of a fill-in-the-middle answer
+///
+/// Completion models answer with bare code, but may leak their special
+/// tokens, wrap the code in a fence, or carry on past the gap into the code
+/// after it (`suffix`); all of that is cut. The result ends with a newline
+/// unless it's empty.
+pub fn extract_fim_middle(response: &str, suffix: &str) -> String {
+    let end_token = Regex::new(r"<\|?(endoftext|end_of_text|eot_id|file_sep|fim_pad)\|?>|<EOT>").unwrap();
+    let mut middle = match end_token.find(response) {
+        Some(m) => &response[..m.start()],
+        None => response,
+    }
+    .to_string();
+    let fim_token = Regex::new(r"<\|?fim[_-](prefix|suffix|middle)\|?>|<(PRE|SUF|MID)>").unwrap();
+    middle = fim_token.replace_all(&middle, "").into_owned();
+    if middle.trim_start().starts_with("```") {
+        middle = strip_nested_fences(&middle);
+    }
+
+    // The first lines after the gap showing up again means the model went on
+    // past it. Lines of only brackets would match too often to tell.
+    let suffix_start: Vec<&str> = suffix.lines().map(str::trim).filter(|l| !l.is_empty()).take(3).collect();
+    if suffix_start.iter().any(|l| l.chars().filter(|c| c.is_alphanumeric()).count() >= 3) {
+        let lines: Vec<&str> = middle.lines().collect();
+        let echoed = (0..lines.len()).find(|&i| {
+            let following = lines[i..].iter().map(|l| l.trim()).filter(|l| !l.is_empty());
+            following.take(suffix_start.len()).eq(suffix_start.iter().copied())
+        });
+        if let Some(i) = echoed {
+            debug!("FIM answer repeats the code after the gap from line {}", i + 1);
+            middle = lines[..i].join("\n");
+        }
+    }
+
+    let middle = middle.trim_start_matches(['\r', '\n']).trim_end();
+    if middle.is_empty() { String::new() } else { format!("{}\n", middle) }
+}
+
 /// Parse replace pattern instructions from LLM response
 pub fn parse_replace_pattern_instructions(response: &str) -> ParsedReplacePatterns {
     let mut instructions = Vec::new();
@@ -281,6 +421,125 @@ pub fn parse_verification(response: &str) -> (VerificationResult, Option<String>
     }
 }
 
+/// Rubric answer as the verifier writes it
+#[derive(Deserialize)]
+struct RubricResponse {
+    correctness: u8,
+    completeness: u8,
+    style: u8,
+    #[serde(default)]
+    tests: Option<u8>,
+    #[serde(default)]
+    summary: Option<String>,
+}
+
+/// Parse a rubric verification response: a JSON object with a 1-5 score per
+/// axis and an optional summary, possibly wrapped in a code fence or prose.
+/// Returns `None` if there is no such object or a score is out of range.
+pub fn parse_rubric(response: &str) -> Option<(RubricScores, Option<String>)> {
+    let start = response.find('{')?;
+    let end = response.rfind('}')?;
+    let parsed: RubricResponse = serde_json::from_str(response.get(start..=end)?).ok()?;
+
+    let scores = RubricScores {
+        correctness: parsed.correctness,
+        completeness: parsed.completeness,
+        style: parsed.style,
+        tests: parsed.tests,
+    };
+    if scores.axes().iter().any(|(_, score)| !(1..=5).contains(score)) {
+        debug!("Rubric score out of range: {:?}", scores);
+        return None;
+    }
+    let summary = parsed.summary.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    Some((scores, summary))
+}
+
+/// Judge rubric scores against the passing threshold: an average below it
+/// fails soft (so the retry gets the scores as feedback); any single axis
+/// below it passes with warnings
+pub fn rubric_verdict(scores: &RubricScores, summary: Option<&str>, threshold: f32) -> (VerificationResult, Option<String>) {
+    if scores.average() < threshold {
+        let mut reason = format!("Rubric score {} is below {}", scores, threshold);
+        if let Some(summary) = summary {
+            reason.push_str(": ");
+            reason.push_str(summary);
+        }
+        return (VerificationResult::FailSoft, Some(reason));
+    }
+    if scores.axes().iter().any(|(_, score)| f32::from(*score) < threshold) {
+        debug!("Rubric passed with weak axes: {}", scores);
+        return (VerificationResult::PassWithWarnings, None);
+    }
+    (VerificationResult::Pass, None)
+}
+
+/// Whether `votes` verification votes already decide a majority vote of
+/// `total` (a tie fails)
+pub fn majority_decided(votes: &[VerificationResult], total: usize) -> bool {
+    let passes = votes.iter().filter(|v| v.is_pass()).count();
+    let fails = votes.len() - passes;
+    passes > total / 2 || fails >= total - total / 2
+}
+
+/// Combine verification votes: more than half must pass (a tie fails).
+///
+/// The outcome is the most common passing (or failing) result, preferring the
+/// more severe one on a tie. Failing votes' distinct reasons are joined so
+/// the retry sees all of them.
+pub fn majority_verdict(votes: &[(VerificationResult, Option<String>)]) -> (VerificationResult, Option<String>) {
+    let count = |result: VerificationResult| votes.iter().filter(|(v, _)| *v == result).count();
+    let passes = votes.iter().filter(|(v, _)| v.is_pass()).count();
+
+    if passes * 2 > votes.len() {
+        let result = if count(VerificationResult::Pass) > count(VerificationResult::PassWithWarnings) {
+            VerificationResult::Pass
+        } else {
+            VerificationResult::PassWithWarnings
+        };
+        let reason = votes.iter().find(|(v, _)| *v == result).and_then(|(_, reason)| reason.clone());
+        return (result, reason);
+    }
+
+    let result = if count(VerificationResult::FailHard) >= count(VerificationResult::FailSoft) {
+        VerificationResult::FailHard
+    } else {
+        VerificationResult::FailSoft
+    };
+    let mut reasons: Vec<&str> = Vec::new();
+    for reason in votes.iter().filter(|(v, _)| !v.is_pass()).filter_map(|(_, reason)| reason.as_deref()) {
+        if !reasons.contains(&reason) {
+            reasons.push(reason);
+        }
+    }
+    let reason = (!reasons.is_empty()).then(|| reasons.join("; "));
+    (result, reason)
+}
+
+/// Combine the verdicts of files verified one at a time into the job's
+/// verdict: the most severe result wins, and the reasons of the files that
+/// share it are kept, each prefixed with its file
+pub fn combine_file_verdicts(verdicts: &[(PathBuf, (VerificationResult, Option<String>))]) -> (VerificationResult, Option<String>) {
+    let severity = |result: &VerificationResult| match result {
+        VerificationResult::Pass => 0,
+        VerificationResult::PassWithWarnings => 1,
+        VerificationResult::FailSoft => 2,
+        VerificationResult::FailHard => 3,
+    };
+    let result = verdicts
+        .iter()
+        .map(|(_, (result, _))| *result)
+        .max_by_key(severity)
+        .unwrap_or(VerificationResult::Pass);
+    // A soft failure's reason still matters to the retry next to a hard one
+    let reasons: Vec<String> = verdicts
+        .iter()
+        .filter(|(_, (file_result, _))| *file_result == result || (!result.is_pass() && !file_result.is_pass()))
+        .filter_map(|(path, (_, reason))| reason.as_ref().map(|reason| format!("{}: {}", path.display(), reason)))
+        .collect();
+    (result, (!reasons.is_empty()).then(|| reasons.join("\n")))
+}
+
 fn extract_reason_after_pattern(response: &str, patterns: &[&str]) -> Option<String> {
     let lower = response.to_lowercase();
     
@@ -435,10 +694,8 @@ fn find_matching_brace(content: &str) -> Option<(usize, usize)> {
                     return Some((pos + 1, last_comma_or_field));
                 }
             }
-            ',' => {
-                if depth == 1 {
-                    last_comma_or_field = pos + 1;
-                }
+            ',' if depth == 1 => {
+                last_comma_or_field = pos + 1;
             }
             _ => {}
         }
@@ -482,6 +739,7 @@ mod tests {
     use super::*;
     use crate::models::JobStatus;
     use crate::core::parser::prompts::assemble_sequential_creation_prompt;
+    use crate::core::prompt_templates::PromptTemplates;
 
     #[test]
     fn test_extract_code_with_worksplit_fences() {
@@ -607,6 +865,87 @@ mod tests {
         assert_eq!(msg, Some("Unclear verification response".to_string()));
     }
 
+    #[test]
+    fn test_parse_rubric() {
+        let response = "Scores:\n```json\n{\"correctness\": 4, \"completeness\": 5, \"style\": 3, \"tests\": null, \"summary\": \"naming is inconsistent\"}\n```";
+        let (scores, summary) = parse_rubric(response).unwrap();
+        assert_eq!(scores, RubricScores { correctness: 4, completeness: 5, style: 3, tests: None });
+        assert_eq!(scores.average(), 4.0);
+        assert_eq!(summary.as_deref(), Some("naming is inconsistent"));
+
+        let (scores, summary) = parse_rubric(r#"{"correctness":2,"completeness":2,"style":4,"tests":3,"summary":""}"#).unwrap();
+        assert_eq!(scores.tests, Some(3));
+        assert_eq!(summary, None);
+
+        assert!(parse_rubric("PASS").is_none());
+        assert!(parse_rubric(r#"{"correctness": 6, "completeness": 5, "style": 5}"#).is_none());
+        assert!(parse_rubric(r#"{"correctness": 4}"#).is_none());
+    }
+
+    #[test]
+    fn test_majority_verdict() {
+        use VerificationResult::*;
+        let vote = |result, reason: Option<&str>| (result, reason.map(str::to_string));
+
+        // One noisy failure is outvoted
+        let votes = [vote(Pass, None), vote(FailHard, Some("missing import")), vote(Pass, None)];
+        assert_eq!(majority_verdict(&votes), (Pass, None));
+
+        let votes = [vote(FailSoft, Some("no error handling")), vote(PassWithWarnings, None), vote(FailHard, Some("wrong name")),
+            vote(FailSoft, Some("no error handling"))];
+        assert_eq!(majority_verdict(&votes), (FailSoft, Some("no error handling; wrong name".to_string())));
+
+        // A tie fails
+        let votes = [vote(Pass, None), vote(FailHard, None)];
+        assert_eq!(majority_verdict(&votes), (FailHard, None));
+    }
+
+    #[test]
+    fn test_combine_file_verdicts() {
+        use VerificationResult::*;
+        let verdict = |path: &str, result, reason: Option<&str>| (PathBuf::from(path), (result, reason.map(str::to_string)));
+
+        let verdicts = [verdict("src/a.rs", Pass, None), verdict("src/b.rs", PassWithWarnings, Some("long function"))];
+        assert_eq!(combine_file_verdicts(&verdicts), (PassWithWarnings, Some("src/b.rs: long function".to_string())));
+
+        let verdicts = [
+            verdict("src/a.rs", FailSoft, Some("no error handling")),
+            verdict("src/b.rs", PassWithWarnings, Some("long function")),
+            verdict("src/c.rs", FailHard, Some("wrong name")),
+        ];
+        assert_eq!(
+            combine_file_verdicts(&verdicts),
+            (FailHard, Some("src/a.rs: no error handling\nsrc/c.rs: wrong name".to_string()))
+        );
+        assert_eq!(combine_file_verdicts(&[]), (Pass, None));
+    }
+
+    #[test]
+    fn test_majority_decided() {
+        use VerificationResult::*;
+        assert!(!majority_decided(&[Pass], 3));
+        assert!(majority_decided(&[Pass, PassWithWarnings], 3));
+        assert!(majority_decided(&[FailSoft, FailHard], 3));
+        assert!(!majority_decided(&[Pass, FailHard], 3));
+        assert!(majority_decided(&[FailHard], 2));
+        assert!(majority_decided(&[Pass], 1));
+    }
+
+    #[test]
+    fn test_rubric_verdict() {
+        let scores = |correctness, style| RubricScores { correctness, completeness: 4, style, tests: Some(4) };
+
+        assert_eq!(rubric_verdict(&scores(5, 4), None, 3.5), (VerificationResult::Pass, None));
+        assert_eq!(rubric_verdict(&scores(5, 3), None, 3.5), (VerificationResult::PassWithWarnings, None));
+
+        let (result, reason) = rubric_verdict(&scores(2, 2), Some("off-by-one in the loop"), 3.5);
+        assert_eq!(result, VerificationResult::FailSoft);
+        assert_eq!(
+            reason.as_deref(),
+            Some("Rubric score 3.0/5 (correctness 2, completeness 4, style 2, tests 4) is below 3.5: off-by-one in the loop")
+        );
+    }
+
     #[test]
     fn test_verification_result_is_pass() {
         assert!(VerificationResult::Pass.is_pass());
@@ -784,6 +1123,7 @@ fn main() {}
     #[test]
     fn test_assemble_sequential_creation_prompt_basic() {
         let prompt = assemble_sequential_creation_prompt(
+            &PromptTemplates::default(),
             "You are a helpful assistant.",
             &[],
             &[],
@@ -810,6 +1150,7 @@ fn main() {}
         ];
 
         let prompt = assemble_sequential_creation_prompt(
+            &PromptTemplates::default(),
             "System prompt",
             &context_files,
             &[],
@@ -831,6 +1172,7 @@ fn main() {}
         ];
 
         let prompt = assemble_sequential_creation_prompt(
+            &PromptTemplates::default(),
             "System prompt",
             &[],
             &previously_generated,
@@ -855,6 +1197,7 @@ fn main() {}
         ];
 
         let prompt = assemble_sequential_creation_prompt(
+            &PromptTemplates::default(),
             "System prompt",
             &[],
             &[],
@@ -883,6 +1226,7 @@ fn main() {}
         ];
 
         let prompt = assemble_sequential_creation_prompt(
+            &PromptTemplates::default(),
             "System prompt",
             &context,
             &previously_generated,
@@ -984,4 +1328,56 @@ fn ignored() {}
         assert_eq!(files[0].path, Some(PathBuf::from("src/preferred.rs")));
         assert!(files[0].content.contains("preferred"));
     }
+
+    #[test]
+    fn test_extract_report_keeps_inner_fences() {
+        let report = "# Review\n\nThe loader:\n\n```rust\nfn load() {}\n```\n\nLooks fine.";
+        assert_eq!(extract_report(&format!("\n{}\n", report)), report);
+        assert_eq!(extract_report(&format!("```markdown\n{}\n```", report)), report);
+        assert_eq!(extract_report(&format!("~~~worksplit:docs/review.md\n{}\n~~~worksplit", report)), report);
+        // A response that merely starts with a code block is not a wrapper
+        let code_first = "```rust\nfn a() {}\n```\n\nExplained above.";
+        assert_eq!(extract_report(code_first), code_first);
+    }
+
+    #[test]
+    fn test_sanitize_response_strips_reasoning_and_preamble() {
+        let config = SanitizeConfig::default();
+        let response = "<think>\nThe user wants main. Should it fail?\n</think>\n\nSure! Here is the code:\n\nfn main() {}";
+        assert_eq!(sanitize_response(response, &config, "deepseek-r1:14b"), "fn main() {}");
+        // Opening tag in the chat template, unclosed block, verdicts
+        assert_eq!(sanitize_response("reasoning...\n</think>\nPASS", &config, "m"), "PASS");
+        assert_eq!(sanitize_response("<think>never finished", &config, "m"), "");
+        assert_eq!(sanitize_response("Okay, FAIL:\n- missing main", &config, "m"), "Okay, FAIL:\n- missing main");
+        // Tags inside the answer are code
+        let code = "fn tags() -> &'static str {\n    \"<think></think>\"\n}";
+        assert_eq!(sanitize_response(code, &config, "m"), code);
+        assert_eq!(sanitize_response("Here is the code:", &config, "m"), "Here is the code:");
+    }
+
+    #[test]
+    fn test_sanitize_response_per_model() {
+        use crate::models::SanitizeOverride;
+
+        let mut config = SanitizeConfig::default();
+        config.models.insert("qwen".to_string(), SanitizeOverride { strip_thinking: Some(false), strip_preamble: None });
+        config.models.insert("qwen3".to_string(), SanitizeOverride { strip_thinking: None, strip_preamble: Some(false) });
+        let response = "<think>hmm</think>\nHere you go:\nfn a() {}";
+        assert_eq!(sanitize_response(response, &config, "qwen2.5-coder"), "<think>hmm</think>\nHere you go:\nfn a() {}");
+        assert_eq!(sanitize_response(response, &config, "qwen3:8b"), "Here you go:\nfn a() {}");
+        assert_eq!(sanitize_response(response, &config, "llama3"), "Here you go:\nfn a() {}");
+    }
+
+    #[test]
+    fn test_extract_fim_middle() {
+        let suffix = "\n    total\n}\n\nfn other() {}\n";
+        assert_eq!(extract_fim_middle("    let total = a + b;<|endoftext|>junk", suffix), "    let total = a + b;\n");
+        assert_eq!(extract_fim_middle("```rust\nlet total = a + b;\n```", suffix), "let total = a + b;\n");
+        // Carrying on into the suffix is cut
+        let runaway = "\n    let total = a + b;\n    total\n}\n\nfn other() {}\n";
+        assert_eq!(extract_fim_middle(runaway, suffix), "    let total = a + b;\n");
+        // Closing brackets alone aren't taken for the suffix
+        assert_eq!(extract_fim_middle("    if x {\n    }\n", "}\n"), "    if x {\n    }\n");
+        assert_eq!(extract_fim_middle("<|fim_middle|>\n", suffix), "");
+    }
 }
@@ -3,6 +3,8 @@
 mod edit;
 mod extract;
 mod prompts;
+#[cfg(feature = "rust-ast")]
+mod rust_ast;
 
 pub use edit::*;
 pub use extract::*;
@@ -59,7 +61,7 @@ impl VerificationResult {
     }
 
     /// Convert verification result to job status
-    pub fn to_job_status(&self) -> JobStatus {
+    pub fn to_job_status(self) -> JobStatus {
         match self {
             VerificationResult::Pass | VerificationResult::PassWithWarnings => JobStatus::Pass,
             VerificationResult::FailSoft | VerificationResult::FailHard => JobStatus::Fail,
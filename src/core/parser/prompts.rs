@@ -1,42 +1,99 @@
 //! Prompt assembly functions for LLM interactions.
+//!
+//! The prompt text lives in templates (see [`PromptTemplates`]); these
+//! functions only build the values the templates are rendered with.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use minijinja::context;
+use serde::Serialize;
+
+use crate::core::context::same_file;
+use crate::core::prompt_templates::PromptTemplates;
+
+/// A file as templates see it. `content` always ends with a newline, so a
+/// template can close the code fence right after it.
+#[derive(Debug, Serialize)]
+pub(crate) struct TemplateFile {
+    pub path: String,
+    pub content: String,
+}
+
+impl TemplateFile {
+    pub(crate) fn new(path: &std::path::Path, content: &str) -> Self {
+        Self { path: path.display().to_string(), content: with_trailing_newline(content) }
+    }
+}
+
+pub(crate) fn with_trailing_newline(content: &str) -> String {
+    let mut content = content.to_string();
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content
+}
+
+pub(crate) fn template_files(files: &[(PathBuf, String)]) -> Vec<TemplateFile> {
+    files.iter().map(|(path, content)| TemplateFile::new(path, content)).collect()
+}
+
+/// Context files as templates see them, leaving out files the prompt
+/// already shows in another section (generated, previous or target files)
+pub(crate) fn context_template_files<'a>(
+    context_files: &[(PathBuf, String)],
+    shown: impl IntoIterator<Item = &'a Path>,
+) -> Vec<TemplateFile> {
+    let shown: Vec<&Path> = shown.into_iter().collect();
+    context_files
+        .iter()
+        .filter(|(path, _)| !shown.iter().any(|s| same_file(path, s)))
+        .map(|(path, content)| TemplateFile::new(path, content))
+        .collect()
+}
+
+fn paths_of(files: &[(PathBuf, String)]) -> impl Iterator<Item = &Path> {
+    files.iter().map(|(path, _)| path.as_path())
+}
+
+fn display_paths(paths: &[PathBuf]) -> Vec<String> {
+    paths.iter().map(|p| p.display().to_string()).collect()
+}
 
 /// Assemble a creation prompt
 pub fn assemble_creation_prompt(
+    templates: &PromptTemplates,
     system_prompt: &str,
     context_files: &[(PathBuf, String)],
     instructions: &str,
     output_path: &str,
 ) -> String {
-    let mut prompt = String::new();
-
-    // System prompt
-    prompt.push_str("[SYSTEM]\n");
-    prompt.push_str(system_prompt);
-    prompt.push_str("\n\n");
-
-    // Context files
-    if !context_files.is_empty() {
-        prompt.push_str("[CONTEXT]\n");
-        for (path, content) in context_files {
-            prompt.push_str(&format!("### File: {}\n", path.display()));
-            prompt.push_str("```\n");
-            prompt.push_str(content);
-            if !content.ends_with('\n') {
-                prompt.push('\n');
-            }
-            prompt.push_str("```\n\n");
-        }
-    }
-
-    // Instructions
-    prompt.push_str("[INSTRUCTIONS]\n");
-    prompt.push_str(instructions);
-    prompt.push_str("\n\n");
-    prompt.push_str(&format!("Output to: {}\n", output_path));
+    templates.render("create", context! {
+        system_prompt,
+        context_files => template_files(context_files),
+        instructions,
+        output_path,
+    })
+}
 
-    prompt
+/// Assemble a prompt for report mode
+///
+/// There is no language system prompt: the model writes a markdown document,
+/// not code. `previous` carries the last attempt and its verification
+/// feedback when retrying.
+pub fn assemble_report_prompt(
+    templates: &PromptTemplates,
+    context_files: &[(PathBuf, String)],
+    instructions: &str,
+    output_path: &str,
+    previous: Option<(&str, &str)>,
+) -> String {
+    let previous = previous.map(|(report, feedback)| context! { report, feedback });
+    templates.render("report", context! {
+        context_files => template_files(context_files),
+        previous,
+        instructions,
+        output_path,
+    })
 }
 
 /// Assemble a creation prompt for sequential multi-file mode
@@ -44,6 +101,7 @@ pub fn assemble_creation_prompt(
 /// In sequential mode, each file is generated with its own LLM call.
 /// Previously generated files in this job are added as context for subsequent files.
 pub fn assemble_sequential_creation_prompt(
+    templates: &PromptTemplates,
     system_prompt: &str,
     context_files: &[(PathBuf, String)],
     previously_generated: &[(PathBuf, String)],
@@ -51,67 +109,19 @@ pub fn assemble_sequential_creation_prompt(
     current_output_path: &str,
     remaining_files: &[PathBuf],
 ) -> String {
-    let mut prompt = String::new();
-
-    // System prompt
-    prompt.push_str("[SYSTEM]\n");
-    prompt.push_str(system_prompt);
-    prompt.push_str("\n\n");
-
-    // Original context files
-    if !context_files.is_empty() {
-        prompt.push_str("[CONTEXT]\n");
-        for (path, content) in context_files {
-            prompt.push_str(&format!("### File: {}\n", path.display()));
-            prompt.push_str("```\n");
-            prompt.push_str(content);
-            if !content.ends_with('\n') {
-                prompt.push('\n');
-            }
-            prompt.push_str("```\n\n");
-        }
-    }
-
-    // Previously generated files
-    if !previously_generated.is_empty() {
-        prompt.push_str("[PREVIOUSLY GENERATED IN THIS JOB]\n");
-        prompt.push_str("These files were already generated as part of this same task. ");
-        prompt.push_str("Use them as reference for consistency.\n\n");
-        for (path, content) in previously_generated {
-            prompt.push_str(&format!("### File: {}\n", path.display()));
-            prompt.push_str("```\n");
-            prompt.push_str(content);
-            if !content.ends_with('\n') {
-                prompt.push('\n');
-            }
-            prompt.push_str("```\n\n");
-        }
-    }
-
-    // Instructions
-    prompt.push_str("[INSTRUCTIONS]\n");
-    prompt.push_str(instructions);
-    prompt.push_str("\n\n");
-
-    // Current file to generate
-    prompt.push_str("[CURRENT OUTPUT FILE]\n");
-    prompt.push_str(&format!("Generate: {}\n\n", current_output_path));
-
-    // List remaining files for context
-    if !remaining_files.is_empty() {
-        prompt.push_str("[REMAINING FILES]\n");
-        prompt.push_str("These files will be generated after this one:\n");
-        for path in remaining_files {
-            prompt.push_str(&format!("  - {}\n", path.display()));
-        }
-        prompt.push_str("\nConsider their requirements when designing interfaces.\n");
-    }
-
-    prompt
+    templates.render("sequential_create", context! {
+        system_prompt,
+        context_files => context_template_files(context_files, paths_of(previously_generated)),
+        previously_generated => template_files(previously_generated),
+        instructions,
+        output_path => current_output_path,
+        remaining_files => display_paths(remaining_files),
+    })
 }
 
 /// Assemble a verification prompt (single file version for backward compatibility)
 pub fn assemble_verification_prompt(
+    templates: &PromptTemplates,
     system_prompt: &str,
     context_files: &[(PathBuf, String)],
     generated_output: &str,
@@ -119,6 +129,7 @@ pub fn assemble_verification_prompt(
     instructions: &str,
 ) -> String {
     assemble_verification_prompt_multi(
+        templates,
         system_prompt,
         context_files,
         &[(PathBuf::from(output_path), generated_output.to_string())],
@@ -128,98 +139,54 @@ pub fn assemble_verification_prompt(
 
 /// Assemble a verification prompt for multiple generated files
 pub fn assemble_verification_prompt_multi(
+    templates: &PromptTemplates,
     system_prompt: &str,
     context_files: &[(PathBuf, String)],
     generated_files: &[(PathBuf, String)],
     instructions: &str,
 ) -> String {
-    let mut prompt = String::new();
-
-    // System prompt
-    prompt.push_str("[SYSTEM]\n");
-    prompt.push_str(system_prompt);
-    prompt.push_str("\n\n");
-
-    // Context files
-    if !context_files.is_empty() {
-        prompt.push_str("[CONTEXT]\n");
-        for (path, content) in context_files {
-            prompt.push_str(&format!("### File: {}\n", path.display()));
-            prompt.push_str("```\n");
-            prompt.push_str(content);
-            if !content.ends_with('\n') {
-                prompt.push('\n');
-            }
-            prompt.push_str("```\n\n");
-        }
-    }
-
-    // Generated output(s)
-    prompt.push_str("[GENERATED OUTPUT]\n");
-    for (path, content) in generated_files {
-        prompt.push_str(&format!("### File: {}\n", path.display()));
-        prompt.push_str("```\n");
-        prompt.push_str(content);
-        if !content.ends_with('\n') {
-            prompt.push('\n');
-        }
-        prompt.push_str("```\n\n");
-    }
-
-    // Original instructions
-    prompt.push_str("[ORIGINAL INSTRUCTIONS]\n");
-    prompt.push_str(instructions);
-    prompt.push('\n');
+    assemble_verification_prompt_with_build(templates, system_prompt, context_files, generated_files, instructions, None)
+}
 
-    prompt
+/// Assemble a verification prompt that also carries the real build and lint
+/// diagnostics (`verify_build`), so the verifier doesn't have to guess whether
+/// the code compiles
+pub fn assemble_verification_prompt_with_build(
+    templates: &PromptTemplates,
+    system_prompt: &str,
+    context_files: &[(PathBuf, String)],
+    generated_files: &[(PathBuf, String)],
+    instructions: &str,
+    build_output: Option<&str>,
+) -> String {
+    templates.render("verify", context! {
+        system_prompt,
+        context_files => context_template_files(context_files, paths_of(generated_files)),
+        generated_files => template_files(generated_files),
+        instructions,
+        build_output => build_output.map(with_trailing_newline),
+    })
 }
 
 /// Assemble a test generation prompt for TDD workflow
 pub fn assemble_test_prompt(
+    templates: &PromptTemplates,
     system_prompt: &str,
     context_files: &[(PathBuf, String)],
     instructions: &str,
     test_path: &str,
 ) -> String {
-    let mut prompt = String::new();
-
-    // System prompt
-    prompt.push_str("[SYSTEM]\n");
-    prompt.push_str(system_prompt);
-    prompt.push_str("\n\n");
-
-    // Context files
-    if !context_files.is_empty() {
-        prompt.push_str("[CONTEXT]\n");
-        for (path, content) in context_files {
-            prompt.push_str(&format!("### File: {}\n", path.display()));
-            prompt.push_str("```\n");
-            prompt.push_str(content);
-            if !content.ends_with('\n') {
-                prompt.push('\n');
-            }
-            prompt.push_str("```\n\n");
-        }
-    }
-
-    // Requirements
-    prompt.push_str("[REQUIREMENTS]\n");
-    prompt.push_str(instructions);
-    prompt.push_str("\n\n");
-
-    // Test output info with TDD context
-    prompt.push_str("[TEST OUTPUT]\n");
-    prompt.push_str(&format!("Generate tests for: {}\n\n", test_path));
-    prompt.push_str("The implementation does not exist yet. Generate tests that will:\n");
-    prompt.push_str("1. Verify the requirements are met when implementation exists\n");
-    prompt.push_str("2. Cover edge cases and error conditions\n");
-    prompt.push_str("3. Be immediately runnable once implementation is created\n");
-
-    prompt
+    templates.render("test", context! {
+        system_prompt,
+        context_files => template_files(context_files),
+        instructions,
+        test_path,
+    })
 }
 
 /// Assemble a retry prompt with feedback from failed verification (single file version)
 pub fn assemble_retry_prompt(
+    templates: &PromptTemplates,
     system_prompt: &str,
     context_files: &[(PathBuf, String)],
     instructions: &str,
@@ -228,6 +195,7 @@ pub fn assemble_retry_prompt(
     verification_error: &str,
 ) -> String {
     assemble_retry_prompt_multi(
+        templates,
         system_prompt,
         context_files,
         instructions,
@@ -238,129 +206,97 @@ pub fn assemble_retry_prompt(
 
 /// Assemble a retry prompt with feedback from failed verification (multi-file version)
 pub fn assemble_retry_prompt_multi(
+    templates: &PromptTemplates,
     system_prompt: &str,
     context_files: &[(PathBuf, String)],
     instructions: &str,
     previous_outputs: &[(PathBuf, String)],
     verification_error: &str,
 ) -> String {
-    let mut prompt = String::new();
-
-    // System prompt
-    prompt.push_str("[SYSTEM]\n");
-    prompt.push_str(system_prompt);
-    prompt.push_str("\n\n");
-
-    // Context files
-    if !context_files.is_empty() {
-        prompt.push_str("[CONTEXT]\n");
-        for (path, content) in context_files {
-            prompt.push_str(&format!("### File: {}\n", path.display()));
-            prompt.push_str("```\n");
-            prompt.push_str(content);
-            if !content.ends_with('\n') {
-                prompt.push('\n');
-            }
-            prompt.push_str("```\n\n");
-        }
-    }
-
-    // Previous attempt(s)
-    prompt.push_str("[PREVIOUS ATTEMPT]\n");
-    for (path, content) in previous_outputs {
-        prompt.push_str(&format!("### File: {}\n", path.display()));
-        prompt.push_str("```\n");
-        prompt.push_str(content);
-        if !content.ends_with('\n') {
-            prompt.push('\n');
-        }
-        prompt.push_str("```\n\n");
-    }
-
-    // Verification feedback
-    prompt.push_str("[VERIFICATION FEEDBACK]\n");
-    prompt.push_str("The previous attempt failed verification with the following feedback:\n");
-    prompt.push_str(verification_error);
-    prompt.push_str("\n\n");
-
-    // Instructions
-    prompt.push_str("[INSTRUCTIONS]\n");
-    prompt.push_str(instructions);
-    prompt.push_str("\n\n");
-    
-    // List output files
-    if previous_outputs.len() == 1 {
-        prompt.push_str(&format!("Output to: {}\n\n", previous_outputs[0].0.display()));
-    } else {
-        prompt.push_str("Output files:\n");
-        for (path, _) in previous_outputs {
-            prompt.push_str(&format!("  - {}\n", path.display()));
-        }
-        prompt.push('\n');
-    }
-    prompt.push_str("Please fix the issues mentioned in the verification feedback and generate improved code.\n");
+    templates.render("retry", context! {
+        system_prompt,
+        context_files => context_template_files(context_files, paths_of(previous_outputs)),
+        previous_outputs => template_files(previous_outputs),
+        feedback => verification_error,
+        instructions,
+    })
+}
 
-    prompt
+/// Assemble the follow-up turn of a retry conversation: the verification
+/// feedback on the model's previous answer
+pub fn assemble_retry_followup_prompt(
+    templates: &PromptTemplates,
+    output_files: &[PathBuf],
+    verification_error: &str,
+) -> String {
+    templates.render("retry_followup", context! {
+        output_files => display_paths(output_files),
+        feedback => verification_error,
+    })
 }
 
 /// Assemble a split prompt for breaking a large file into modules
 pub fn assemble_split_prompt(
+    templates: &PromptTemplates,
     system_prompt: &str,
     target_file: (&PathBuf, &str),
     context_files: &[(PathBuf, String)],
     instructions: &str,
     output_files: &[PathBuf],
 ) -> String {
-    let mut prompt = String::new();
-
-    // System prompt
-    prompt.push_str("[SYSTEM]\n");
-    prompt.push_str(system_prompt);
-    prompt.push_str("\n\n");
-
-    // Target file to split
-    prompt.push_str("[TARGET FILE TO SPLIT]\n");
-    prompt.push_str(&format!("### File: {} (to be split into modules)\n", target_file.0.display()));
-    prompt.push_str("```\n");
-    prompt.push_str(target_file.1);
-    if !target_file.1.ends_with('\n') {
-        prompt.push('\n');
-    }
-    prompt.push_str("```\n\n");
-
-    // Additional context files
-    if !context_files.is_empty() {
-        prompt.push_str("[ADDITIONAL CONTEXT]\n");
-        for (path, content) in context_files {
-            prompt.push_str(&format!("### File: {}\n", path.display()));
-            prompt.push_str("```\n");
-            prompt.push_str(content);
-            if !content.ends_with('\n') {
-                prompt.push('\n');
-            }
-            prompt.push_str("```\n\n");
-        }
-    }
-
-    // Instructions
-    prompt.push_str("[INSTRUCTIONS]\n");
-    prompt.push_str(instructions);
-    prompt.push_str("\n\n");
+    templates.render("split", context! {
+        system_prompt,
+        target_file => TemplateFile::new(target_file.0, target_file.1),
+        context_files => context_template_files(context_files, [target_file.0.as_path()]),
+        instructions,
+        output_files => display_paths(output_files),
+    })
+}
 
-    // Output files to generate
-    prompt.push_str("[OUTPUT FILES]\n");
-    prompt.push_str("Generate the following files:\n");
-    for path in output_files {
-        prompt.push_str(&format!("  - {}\n", path.display()));
-    }
-    prompt.push_str("\nUse the ~~~worksplit:path/to/file.rs delimiter for each output file.\n");
-    prompt.push_str("Ensure ALL functionality from the target file is preserved across the output files.\n");
+/// Assemble the planning prompt of a planned split: one interface stub per
+/// output file, no implementations
+pub fn assemble_split_plan_prompt(
+    templates: &PromptTemplates,
+    system_prompt: &str,
+    target_file: (&PathBuf, &str),
+    context_files: &[(PathBuf, String)],
+    instructions: &str,
+    output_files: &[PathBuf],
+) -> String {
+    templates.render("split_plan", context! {
+        system_prompt,
+        target_file => TemplateFile::new(target_file.0, target_file.1),
+        context_files => context_template_files(context_files, [target_file.0.as_path()]),
+        instructions,
+        output_files => display_paths(output_files),
+    })
+}
 
-    prompt
+/// Assemble the prompt implementing one module of a planned split. `plan`
+/// holds the interface stubs of all output files.
+pub fn assemble_planned_split_prompt(
+    templates: &PromptTemplates,
+    system_prompt: &str,
+    target_file: (&PathBuf, &str),
+    context_files: &[(PathBuf, String)],
+    plan: &[(PathBuf, String)],
+    instructions: &str,
+    current_output_path: &str,
+) -> String {
+    templates.render("planned_split", context! {
+        system_prompt,
+        target_file => TemplateFile::new(target_file.0, target_file.1),
+        context_files => context_template_files(context_files, std::iter::once(target_file.0.as_path()).chain(paths_of(plan))),
+        plan => template_files(plan),
+        instructions,
+        output_path => current_output_path,
+    })
 }
 
 /// Assemble a sequential split prompt (one file at a time)
+#[allow(clippy::too_many_arguments)]
 pub fn assemble_sequential_split_prompt(
+    templates: &PromptTemplates,
     system_prompt: &str,
     target_file: (&PathBuf, &str),
     context_files: &[(PathBuf, String)],
@@ -369,74 +305,79 @@ pub fn assemble_sequential_split_prompt(
     current_output_path: &str,
     remaining_files: &[PathBuf],
 ) -> String {
-    let mut prompt = String::new();
-
-    // System prompt
-    prompt.push_str("[SYSTEM]\n");
-    prompt.push_str(system_prompt);
-    prompt.push_str("\n\n");
-
-    // Target file to split
-    prompt.push_str("[TARGET FILE TO SPLIT]\n");
-    prompt.push_str(&format!("### File: {} (original file being split)\n", target_file.0.display()));
-    prompt.push_str("```\n");
-    prompt.push_str(target_file.1);
-    if !target_file.1.ends_with('\n') {
-        prompt.push('\n');
-    }
-    prompt.push_str("```\n\n");
-
-    // Additional context files
-    if !context_files.is_empty() {
-        prompt.push_str("[ADDITIONAL CONTEXT]\n");
-        for (path, content) in context_files {
-            prompt.push_str(&format!("### File: {}\n", path.display()));
-            prompt.push_str("```\n");
-            prompt.push_str(content);
-            if !content.ends_with('\n') {
-                prompt.push('\n');
-            }
-            prompt.push_str("```\n\n");
-        }
-    }
+    templates.render("sequential_split", context! {
+        system_prompt,
+        target_file => TemplateFile::new(target_file.0, target_file.1),
+        context_files => context_template_files(context_files, std::iter::once(target_file.0.as_path()).chain(paths_of(previously_generated))),
+        previously_generated => template_files(previously_generated),
+        instructions,
+        output_path => current_output_path,
+        remaining_files => display_paths(remaining_files),
+    })
+}
 
-    // Previously generated files
-    if !previously_generated.is_empty() {
-        prompt.push_str("[ALREADY GENERATED IN THIS SPLIT]\n");
-        prompt.push_str("These files were already generated from the target file. ");
-        prompt.push_str("Ensure consistency and avoid duplicating code that's already in these files.\n\n");
-        for (path, content) in previously_generated {
-            prompt.push_str(&format!("### File: {}\n", path.display()));
-            prompt.push_str("```\n");
-            prompt.push_str(content);
-            if !content.ends_with('\n') {
-                prompt.push('\n');
-            }
-            prompt.push_str("```\n\n");
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verification_prompt_with_build_output() {
+        let templates = PromptTemplates::default();
+        let files = vec![(PathBuf::from("src/a.rs"), "fn a() {}".to_string())];
+
+        let prompt = assemble_verification_prompt_with_build(&templates, "sys", &[], &files, "Add a", None);
+        assert!(!prompt.contains("[BUILD OUTPUT]"));
+
+        let output = "$ cargo clippy\nwarning: unused variable: `x`\n";
+        let prompt = assemble_verification_prompt_with_build(&templates, "sys", &[], &files, "Add a", Some(output));
+        let build = prompt.find("[BUILD OUTPUT]").unwrap();
+        assert!(build > prompt.find("[ORIGINAL INSTRUCTIONS]").unwrap());
+        assert!(prompt[build..].contains("warning: unused variable: `x`\n```\n"));
     }
 
-    // Instructions
-    prompt.push_str("[INSTRUCTIONS]\n");
-    prompt.push_str(instructions);
-    prompt.push_str("\n\n");
-
-    // Current file to generate
-    prompt.push_str("[CURRENT OUTPUT FILE]\n");
-    prompt.push_str(&format!("Generate ONLY this file: {}\n", current_output_path));
-    prompt.push_str("Extract the appropriate code from the target file into this module.\n\n");
-
-    // List remaining files for context
-    if !remaining_files.is_empty() {
-        prompt.push_str("[REMAINING FILES]\n");
-        prompt.push_str("These files will be generated after this one:\n");
-        for path in remaining_files {
-            prompt.push_str(&format!("  - {}\n", path.display()));
-        }
-        prompt.push_str("\nDo NOT include code that belongs in these files. Focus only on the current file.\n");
+    #[test]
+    fn test_report_prompt() {
+        let templates = PromptTemplates::default();
+        let context = vec![(PathBuf::from("src/a.rs"), "fn a() {}".to_string())];
+        let prompt = assemble_report_prompt(&templates, &context, "Review a()", "docs/review.md", None);
+        assert!(!prompt.contains("[SYSTEM]"));
+        assert!(prompt.contains("### File: src/a.rs"));
+        assert!(prompt.contains("Write the document saved as: docs/review.md"));
+        assert!(!prompt.contains("[PREVIOUS ATTEMPT]"));
+
+        let prompt = assemble_report_prompt(&templates, &context, "Review a()", "docs/review.md", Some(("# Review", "missing risks")));
+        assert!(prompt.contains("[PREVIOUS ATTEMPT]\n# Review"));
+        assert!(prompt.contains("missing risks"));
     }
 
-    prompt.push_str("\nOutput the file using the ~~~worksplit:path/to/file.rs delimiter.\n");
+    #[test]
+    fn test_retry_followup_prompt() {
+        let templates = PromptTemplates::default();
+        let prompt = assemble_retry_followup_prompt(&templates, &[PathBuf::from("src/a.rs")], "a() is never called");
+        assert!(prompt.starts_with("[VERIFICATION FEEDBACK]"));
+        assert!(prompt.contains("a() is never called"));
+        assert!(prompt.contains("Output to: src/a.rs"));
 
-    prompt
+        let prompt = assemble_retry_followup_prompt(&templates, &[PathBuf::from("src/a.rs"), PathBuf::from("src/b.rs")], "x");
+        assert!(prompt.contains("  - src/a.rs\n  - src/b.rs\n"));
+    }
+
+    #[test]
+    fn test_context_already_in_prompt_is_not_repeated() {
+        let templates = PromptTemplates::default();
+        let context = vec![
+            (PathBuf::from("./src/a.rs"), "fn old_a() {}".to_string()),
+            (PathBuf::from("src/b.rs"), "fn b() {}".to_string()),
+        ];
+        let generated = vec![(PathBuf::from("src/a.rs"), "fn a() {}".to_string())];
+
+        let prompt = assemble_sequential_creation_prompt(&templates, "sys", &context, &generated, "Add c", "src/c.rs", &[]);
+        assert!(!prompt.contains("old_a"));
+        assert!(prompt.contains("fn a() {}"));
+        assert!(prompt.contains("fn b() {}"));
+
+        let prompt = assemble_verification_prompt_with_build(&templates, "sys", &context, &generated, "Add a", None);
+        assert!(!prompt.contains("old_a"));
+        assert_eq!(prompt.matches("src/a.rs").count(), 1);
+    }
 }
@@ -0,0 +1,494 @@
+//! Structural edits on named Rust items (`ITEM:` blocks in edit mode)
+//!
+//! Items are located with `syn` instead of string matching, so the edit still
+//! applies after the file has been reformatted.
+
+use proc_macro2::{LineColumn, Span};
+use syn::spanned::Spanned;
+use syn::{Fields, ImplItem, Item, TraitItem, Type};
+
+use super::edit::{EditInstruction, ItemOp};
+
+/// Keywords accepted in front of an item path (`fn UserService::create_user`)
+const ITEM_KINDS: &[&str] = &[
+    "fn", "struct", "enum", "union", "trait", "impl", "mod", "const", "static", "type", "macro",
+];
+
+/// A parsed `ITEM:` path
+#[derive(Debug)]
+struct ItemPath {
+    /// Item kind keyword, if given
+    kind: Option<String>,
+    /// `a::b::c` segments, generics stripped
+    segments: Vec<String>,
+    /// Trait name for `impl Trait for Type`
+    trait_name: Option<String>,
+}
+
+impl ItemPath {
+    fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let (kind, rest) = match text.split_once(char::is_whitespace) {
+            Some((first, rest)) if ITEM_KINDS.contains(&first) => (Some(first.to_string()), rest.trim()),
+            _ => (None, text),
+        };
+
+        let (trait_name, type_path) = match rest.split_once(" for ") {
+            Some((trait_path, type_path)) if kind.as_deref() == Some("impl") => {
+                (split_path(trait_path).pop(), type_path)
+            }
+            _ => (None, rest),
+        };
+
+        let segments = split_path(type_path);
+        if segments.is_empty() {
+            return Err(format!("Empty ITEM path: {:?}", text));
+        }
+        Ok(Self { kind, segments, trait_name })
+    }
+
+    fn kind_matches(&self, kind: &str) -> bool {
+        self.kind.as_deref().is_none_or(|k| k == kind)
+    }
+}
+
+/// Split `a::b<T>::c` into `["a", "b", "c"]`
+fn split_path(path: &str) -> Vec<String> {
+    let mut without_generics = String::new();
+    let mut depth = 0usize;
+    for c in path.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            c if depth == 0 => without_generics.push(c),
+            _ => {}
+        }
+    }
+    without_generics
+        .split("::")
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// The body of an item that `APPEND:` can add to
+#[derive(Debug)]
+struct Body {
+    /// Position of the closing brace
+    close: LineColumn,
+    /// Fields or variants (comma separated) rather than items
+    comma_list: bool,
+    /// Whether the body already has fields, variants or items
+    non_empty: bool,
+    /// End of the last field or variant when it has no trailing comma
+    missing_comma: Option<LineColumn>,
+}
+
+/// A located item
+#[derive(Debug)]
+struct Located {
+    kind: &'static str,
+    start: LineColumn,
+    end: LineColumn,
+    body: Option<Body>,
+}
+
+impl Located {
+    fn new(kind: &'static str, span: Span, body: Option<Body>) -> Self {
+        Self { kind, start: span.start(), end: span.end(), body }
+    }
+}
+
+/// Apply an `ITEM:` edit: `edit.find` names the item and `edit.replace` is
+/// the new item (`REPLACE:`) or the fields/variants/items to add to its body
+/// (`APPEND:`). The item must resolve to exactly one definition.
+pub fn apply_item_edit(content: &str, edit: &EditInstruction, op: ItemOp) -> Result<String, String> {
+    let file = syn::parse_file(content).map_err(|e| {
+        format!(
+            "Could not parse {} as Rust (line {}): {}",
+            edit.file_path.display(),
+            e.span().start().line,
+            e
+        )
+    })?;
+    let path = ItemPath::parse(&edit.find)?;
+
+    let mut found = Vec::new();
+    locate(&file.items, &path, &path.segments, &mut found);
+    let item = match found.as_slice() {
+        [item] => item,
+        [] => {
+            return Err(format!(
+                "ITEM {:?} not found in {}",
+                edit.find,
+                edit.file_path.display()
+            ))
+        }
+        many => {
+            let lines: Vec<String> = many.iter().map(|item| item.start.line.to_string()).collect();
+            return Err(format!(
+                "ITEM {:?} is ambiguous in {}: {} items match (lines {}). Add the kind (e.g. `fn`) or the parent type.",
+                edit.find,
+                edit.file_path.display(),
+                many.len(),
+                lines.join(", ")
+            ));
+        }
+    };
+
+    let offsets = LineOffsets::new(content);
+    match op {
+        ItemOp::Replace => Ok(replace_item(content, &offsets, item, &edit.replace)),
+        ItemOp::Append => match &item.body {
+            Some(body) => Ok(append_to_body(content, &offsets, body, &edit.replace)),
+            None => Err(format!(
+                "Cannot APPEND to {} {:?} in {}: only structs with named fields, enums, impls, traits and inline modules have a body",
+                item.kind,
+                edit.find,
+                edit.file_path.display()
+            )),
+        },
+    }
+}
+
+/// Collect items matching `segments` (the remaining part of `path`)
+fn locate(items: &[Item], path: &ItemPath, segments: &[String], found: &mut Vec<Located>) {
+    let Some((first, rest)) = segments.split_first() else {
+        return;
+    };
+
+    for item in items {
+        if rest.is_empty() {
+            if let Item::Impl(imp) = item {
+                let trait_matches = match (&path.trait_name, &imp.trait_) {
+                    (Some(name), Some((_, trait_path, _))) => {
+                        trait_path.segments.last().is_some_and(|s| s.ident == name)
+                    }
+                    (None, None) => true,
+                    _ => false,
+                };
+                if path.kind.as_deref() == Some("impl")
+                    && trait_matches
+                    && type_name(&imp.self_ty).as_deref() == Some(first.as_str())
+                {
+                    found.push(Located::new("impl", item.span(), Some(items_body(imp.brace_token.span.close(), !imp.items.is_empty()))));
+                }
+                continue;
+            }
+            if let Some((kind, name)) = item_name(item) {
+                if name == *first && path.kind_matches(kind) {
+                    found.push(Located::new(kind, item.span(), item_body(item)));
+                }
+            }
+            continue;
+        }
+
+        match item {
+            Item::Mod(module) if module.ident == first => {
+                if let Some((_, items)) = &module.content {
+                    locate(items, path, rest, found);
+                }
+            }
+            Item::Impl(imp) if rest.len() == 1 && type_name(&imp.self_ty).as_deref() == Some(first.as_str()) => {
+                for impl_item in &imp.items {
+                    let (kind, name) = match impl_item {
+                        ImplItem::Fn(f) => ("fn", &f.sig.ident),
+                        ImplItem::Const(c) => ("const", &c.ident),
+                        ImplItem::Type(t) => ("type", &t.ident),
+                        _ => continue,
+                    };
+                    if *name == rest[0] && path.kind_matches(kind) {
+                        found.push(Located::new(kind, impl_item.span(), None));
+                    }
+                }
+            }
+            Item::Trait(tr) if rest.len() == 1 && tr.ident == first => {
+                for trait_item in &tr.items {
+                    let (kind, name) = match trait_item {
+                        TraitItem::Fn(f) => ("fn", &f.sig.ident),
+                        TraitItem::Const(c) => ("const", &c.ident),
+                        TraitItem::Type(t) => ("type", &t.ident),
+                        _ => continue,
+                    };
+                    if *name == rest[0] && path.kind_matches(kind) {
+                        found.push(Located::new(kind, trait_item.span(), None));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Kind keyword and name of a named item
+fn item_name(item: &Item) -> Option<(&'static str, String)> {
+    let (kind, ident) = match item {
+        Item::Fn(i) => ("fn", &i.sig.ident),
+        Item::Struct(i) => ("struct", &i.ident),
+        Item::Enum(i) => ("enum", &i.ident),
+        Item::Union(i) => ("union", &i.ident),
+        Item::Trait(i) => ("trait", &i.ident),
+        Item::Mod(i) => ("mod", &i.ident),
+        Item::Const(i) => ("const", &i.ident),
+        Item::Static(i) => ("static", &i.ident),
+        Item::Type(i) => ("type", &i.ident),
+        Item::Macro(i) => ("macro", i.ident.as_ref()?),
+        _ => return None,
+    };
+    Some((kind, ident.to_string()))
+}
+
+/// Last path segment of a type (`crate::models::Config<T>` -> `Config`)
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn items_body(close: Span, non_empty: bool) -> Body {
+    Body { close: close.start(), comma_list: false, non_empty, missing_comma: None }
+}
+
+fn item_body(item: &Item) -> Option<Body> {
+    match item {
+        Item::Struct(s) => match &s.fields {
+            Fields::Named(fields) => Some(Body {
+                close: fields.brace_token.span.close().start(),
+                comma_list: true,
+                non_empty: !fields.named.is_empty(),
+                missing_comma: (!fields.named.trailing_punct())
+                    .then(|| fields.named.last().map(|f| f.span().end()))
+                    .flatten(),
+            }),
+            _ => None,
+        },
+        Item::Enum(e) => Some(Body {
+            close: e.brace_token.span.close().start(),
+            comma_list: true,
+            non_empty: !e.variants.is_empty(),
+            missing_comma: (!e.variants.trailing_punct())
+                .then(|| e.variants.last().map(|v| v.span().end()))
+                .flatten(),
+        }),
+        Item::Trait(t) => Some(items_body(t.brace_token.span.close(), !t.items.is_empty())),
+        Item::Mod(m) => m
+            .content
+            .as_ref()
+            .map(|(brace, items)| items_body(brace.span.close(), !items.is_empty())),
+        _ => None,
+    }
+}
+
+/// Byte offsets of line/column positions (columns count chars)
+struct LineOffsets<'a> {
+    content: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineOffsets<'a> {
+    fn new(content: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { content, starts }
+    }
+
+    fn byte(&self, pos: LineColumn) -> usize {
+        let line_start = self.starts.get(pos.line.saturating_sub(1)).copied().unwrap_or(self.content.len());
+        self.content[line_start..]
+            .char_indices()
+            .nth(pos.column)
+            .map_or(self.content.len(), |(i, _)| line_start + i)
+    }
+
+    /// Start of the line containing `pos`
+    fn line_start(&self, pos: LineColumn) -> usize {
+        self.starts.get(pos.line.saturating_sub(1)).copied().unwrap_or(self.content.len())
+    }
+
+    /// Leading whitespace of the line containing `pos`
+    fn indent(&self, pos: LineColumn) -> &'a str {
+        let line = &self.content[self.line_start(pos)..];
+        let end = line.find(|c: char| c != ' ' && c != '\t').unwrap_or(line.len());
+        &line[..end]
+    }
+}
+
+/// Strip the common indentation from `text` and indent every non-empty line
+/// with `indent`
+fn reindent(text: &str, indent: &str) -> String {
+    let common = text
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    text.lines()
+        .map(|l| if l.trim().is_empty() { String::new() } else { format!("{}{}", indent, &l[common.min(l.len() - l.trim_start().len())..]) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn replace_item(content: &str, offsets: &LineOffsets, item: &Located, replacement: &str) -> String {
+    let mut start = offsets.byte(item.start);
+    let mut end = offsets.byte(item.end);
+    let indent = offsets.indent(item.start);
+
+    if replacement.trim().is_empty() {
+        // Delete the item along with its lines
+        if content[offsets.line_start(item.start)..start].trim().is_empty() {
+            start = offsets.line_start(item.start);
+        }
+        if content[end..].starts_with('\n') {
+            end += 1;
+        } else if content[end..].starts_with("\r\n") {
+            end += 2;
+        }
+        return format!("{}{}", &content[..start], &content[end..]);
+    }
+
+    // The first line goes where the old item started, already indented
+    let new_item = reindent(replacement, indent);
+    format!("{}{}{}", &content[..start], new_item.trim_start(), &content[end..])
+}
+
+fn append_to_body(content: &str, offsets: &LineOffsets, body: &Body, addition: &str) -> String {
+    let close = offsets.byte(body.close);
+    let outer = offsets.indent(body.close);
+    let mut block = reindent(addition, &format!("{}    ", outer));
+    if body.comma_list && !block.trim_end().ends_with(',') {
+        block = format!("{},", block.trim_end());
+    }
+    if !body.comma_list && body.non_empty {
+        block = format!("\n{}", block);
+    }
+
+    let close_line_start = offsets.line_start(body.close);
+    let mut result = if content[close_line_start..close].trim().is_empty() {
+        // `}` on its own line: insert the new lines above it
+        format!("{}{}\n{}", &content[..close_line_start], block, &content[close_line_start..])
+    } else {
+        // `struct A {}` or `{ a: u8 }`: break the body open
+        let before = content[..close].trim_end();
+        format!("{}\n{}\n{}{}", before, block, outer, &content[close..])
+    };
+
+    if let Some(last) = body.missing_comma {
+        let at = offsets.byte(last);
+        result.insert(at, ',');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn item_edit(path: &str, replace: &str, op: ItemOp) -> EditInstruction {
+        EditInstruction {
+            file_path: PathBuf::from("src/service.rs"),
+            find: path.to_string(),
+            replace: replace.to_string(),
+            line_range: None,
+            regex: false,
+            item: Some(op),
+        }
+    }
+
+    const SERVICE: &str = r#"use std::fmt;
+
+pub struct Config {
+    pub name: String,
+    pub retries: u32
+}
+
+impl UserService {
+    /// Create a user
+    #[tracing::instrument]
+    pub fn create_user(&self,
+                       name: &str) -> User {
+        User::new( name )
+    }
+
+    pub fn delete_user(&self, id: u64) {}
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+"#;
+
+    #[test]
+    fn test_replace_method_in_reformatted_impl() {
+        let edit = item_edit(
+            "fn UserService::create_user",
+            "/// Create a user\npub fn create_user(&self, name: &str) -> User {\n    User::named(name)\n}",
+            ItemOp::Replace,
+        );
+        let result = apply_item_edit(SERVICE, &edit, ItemOp::Replace).unwrap();
+        assert!(result.contains(
+            "impl UserService {\n    /// Create a user\n    pub fn create_user(&self, name: &str) -> User {\n        User::named(name)\n    }\n\n    pub fn delete_user"
+        ), "{}", result);
+        assert!(!result.contains("tracing::instrument"));
+        assert!(syn::parse_file(&result).is_ok());
+    }
+
+    #[test]
+    fn test_append_field_adds_missing_comma() {
+        let edit = item_edit("struct Config", "pub timeout: u64", ItemOp::Append);
+        let result = apply_item_edit(SERVICE, &edit, ItemOp::Append).unwrap();
+        assert!(result.contains(
+            "pub struct Config {\n    pub name: String,\n    pub retries: u32,\n    pub timeout: u64,\n}"
+        ), "{}", result);
+    }
+
+    #[test]
+    fn test_append_method_to_impl() {
+        let edit = item_edit("impl UserService", "pub fn count(&self) -> usize {\n    0\n}", ItemOp::Append);
+        let result = apply_item_edit(SERVICE, &edit, ItemOp::Append).unwrap();
+        assert!(result.contains(
+            "    pub fn delete_user(&self, id: u64) {}\n\n    pub fn count(&self) -> usize {\n        0\n    }\n}"
+        ), "{}", result);
+
+        // Trait impls are addressed with `impl Trait for Type`
+        let edit = item_edit("impl Display for Config", "", ItemOp::Replace);
+        let result = apply_item_edit(SERVICE, &edit, ItemOp::Replace).unwrap();
+        assert!(!result.contains("impl fmt::Display"));
+        assert!(result.contains("impl UserService"));
+    }
+
+    #[test]
+    fn test_append_to_empty_struct() {
+        let content = "struct Empty {}\n";
+        let edit = item_edit("Empty", "a: u8", ItemOp::Append);
+        let result = apply_item_edit(content, &edit, ItemOp::Append).unwrap();
+        assert_eq!(result, "struct Empty {\n    a: u8,\n}\n");
+    }
+
+    #[test]
+    fn test_item_errors() {
+        let edit = item_edit("fn missing", "", ItemOp::Replace);
+        assert!(apply_item_edit(SERVICE, &edit, ItemOp::Replace).unwrap_err().contains("not found"));
+
+        // Module paths select between same-named functions
+        let content = "mod a { pub fn run() {} }\nmod b { pub fn run() {} }\nfn run() {}\n";
+        let edit = item_edit("a::run", "pub fn run() { go() }", ItemOp::Replace);
+        let result = apply_item_edit(content, &edit, ItemOp::Replace).unwrap();
+        assert!(result.starts_with("mod a { pub fn run() { go() } }"), "{}", result);
+
+        let content = "impl A { fn new() {} }\nimpl A { fn new() {} }\n";
+        let edit = item_edit("A::new", "", ItemOp::Replace);
+        let err = apply_item_edit(content, &edit, ItemOp::Replace).unwrap_err();
+        assert!(err.contains("ambiguous") && err.contains("lines 1, 2"), "{}", err);
+
+        let edit = item_edit("fn UserService::create_user", "x", ItemOp::Append);
+        assert!(apply_item_edit(SERVICE, &edit, ItemOp::Append).unwrap_err().contains("Cannot APPEND"));
+
+        let edit = item_edit("fn a", "", ItemOp::Replace);
+        assert!(apply_item_edit("fn a( {", &edit, ItemOp::Replace).unwrap_err().contains("Could not parse"));
+    }
+}
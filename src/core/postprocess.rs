@@ -0,0 +1,150 @@
+//! Post-processing of generated files before they're written
+//! (`[postprocess.<ext>]`)
+//!
+//! Each rule runs its commands in order, each receiving the content on stdin
+//! and printing the new content, then strips trailing whitespace and
+//! normalizes line endings. A step that fails is logged and skipped, never
+//! failing the job: the content it was given is kept.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tracing::{debug, warn};
+
+use crate::models::{LineEndings, PostprocessRule};
+
+/// Rule key applying to extensions without a rule of their own
+pub const ANY_EXTENSION: &str = "*";
+
+/// The rule for `path`: its extension's, else the `*` rule
+fn rule_for<'a>(rules: &'a BTreeMap<String, PostprocessRule>, path: &Path) -> Option<&'a PostprocessRule> {
+    path.extension()
+        .and_then(|ext| rules.get(ext.to_string_lossy().as_ref()))
+        .or_else(|| rules.get(ANY_EXTENSION))
+}
+
+/// Post-process the content of `path` (relative to `project_root`, where
+/// commands run) according to `rules`
+pub fn postprocess(rules: &BTreeMap<String, PostprocessRule>, project_root: &Path, path: &Path, content: &str) -> String {
+    postprocess_with_env(rules, project_root, path, content, &BTreeMap::new())
+}
+
+/// [`postprocess`] with extra environment variables for the commands (a
+/// job's `env`)
+pub fn postprocess_with_env(rules: &BTreeMap<String, PostprocessRule>, project_root: &Path, path: &Path, content: &str,
+                            env: &BTreeMap<String, String>) -> String {
+    let Some(rule) = rule_for(rules, path) else { return content.to_string() };
+    let mut content = content.to_string();
+    for command in &rule.commands {
+        let command = command.replace("{path}", &path.display().to_string());
+        match run_filter(&command, project_root, env, &content) {
+            Ok(output) => {
+                debug!("Post-processed {} with `{}`", path.display(), command);
+                content = output;
+            }
+            Err(e) => warn!("Post-processing {}: `{}` failed, keeping its input: {}", path.display(), command, e),
+        }
+    }
+    if rule.trim_trailing_whitespace {
+        content = trim_trailing_whitespace(&content);
+    }
+    normalize_line_endings(&content, rule.line_endings)
+}
+
+/// Run `command` through the shell with `input` on stdin; its stdout is
+/// the result
+fn run_filter(command: &str, dir: &Path, env: &BTreeMap<String, String>, input: &str) -> Result<String, String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(dir)
+        .envs(env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    // Write from another thread so a command that prints before it has read
+    // all its input can't deadlock on a full pipe
+    let mut stdin = child.stdin.take().ok_or("no stdin")?;
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    let _ = writer.join();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{}: {}", output.status, stderr.trim()));
+    }
+    let stdout = String::from_utf8(output.stdout).map_err(|_| "output is not UTF-8".to_string())?;
+    if stdout.trim().is_empty() {
+        return Err("no output".to_string());
+    }
+    Ok(stdout)
+}
+
+/// Strip spaces and tabs at the end of every line
+fn trim_trailing_whitespace(content: &str) -> String {
+    content
+        .split_inclusive('\n')
+        .map(|line| {
+            let (text, ending) = match line.strip_suffix("\r\n") {
+                Some(text) => (text, "\r\n"),
+                None => line.strip_suffix('\n').map_or((line, ""), |text| (text, "\n")),
+            };
+            format!("{}{}", text.trim_end_matches([' ', '\t']), ending)
+        })
+        .collect()
+}
+
+fn normalize_line_endings(content: &str, line_endings: LineEndings) -> String {
+    match line_endings {
+        LineEndings::Keep => content.to_string(),
+        LineEndings::Lf => content.replace("\r\n", "\n"),
+        LineEndings::Crlf => content.replace("\r\n", "\n").replace('\n', "\r\n"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn rules(entries: &[(&str, PostprocessRule)]) -> BTreeMap<String, PostprocessRule> {
+        entries.iter().map(|(ext, rule)| (ext.to_string(), rule.clone())).collect()
+    }
+
+    #[test]
+    fn test_whitespace_and_line_endings() {
+        assert_eq!(trim_trailing_whitespace("a  \r\nb\t\nc "), "a\r\nb\nc");
+        assert_eq!(normalize_line_endings("a\r\nb\n", LineEndings::Lf), "a\nb\n");
+        assert_eq!(normalize_line_endings("a\r\nb\n", LineEndings::Crlf), "a\r\nb\r\n");
+        assert_eq!(normalize_line_endings("a\r\nb\n", LineEndings::Keep), "a\r\nb\n");
+    }
+
+    #[test]
+    fn test_postprocess_picks_the_rule_by_extension() {
+        let root = PathBuf::from(".");
+        let trim = PostprocessRule { trim_trailing_whitespace: true, ..PostprocessRule::default() };
+        let upper = PostprocessRule { commands: vec!["tr a-z A-Z".to_string()], ..PostprocessRule::default() };
+        let rules = rules(&[("rs", upper), (ANY_EXTENSION, trim)]);
+
+        assert_eq!(postprocess(&rules, &root, Path::new("src/a.rs"), "fn a() {}  \n"), "FN A() {}  \n");
+        assert_eq!(postprocess(&rules, &root, Path::new("README.md"), "text  \n"), "text\n");
+        assert_eq!(postprocess(&BTreeMap::new(), &root, Path::new("a.rs"), "x  "), "x  ");
+    }
+
+    #[test]
+    fn test_failing_command_keeps_its_input() {
+        let root = PathBuf::from(".");
+        let rule = PostprocessRule {
+            commands: vec!["exit 3".to_string(), "sed 's/a/b/'".to_string(), "true".to_string()],
+            line_endings: LineEndings::Crlf,
+            ..PostprocessRule::default()
+        };
+        // `exit 3` fails and `true` prints nothing: both are skipped
+        assert_eq!(postprocess(&rules(&[("ts", rule)]), &root, Path::new("a.ts"), "a\n"), "b\r\n");
+    }
+}
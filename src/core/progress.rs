@@ -0,0 +1,300 @@
+//! Live progress bars for `run_all` / `run_batch`
+//!
+//! An overall bar counts the jobs processed, with an ETA from the durations
+//! recorded in `jobs/_history.jsonl`; sequential and split jobs get a second
+//! bar counting their files. Bars are drawn on stderr only when it's a
+//! terminal. Log lines go through [`LogWriter`], which clears the bars
+//! while a line is printed so the two don't garble each other. Without the
+//! "progress" feature there are no bars and runs only log.
+
+#[cfg(feature = "progress")]
+use indicatif::{MultiProgress, ProgressBar, ProgressState, ProgressStyle};
+use std::io::{self, Write};
+#[cfg(feature = "progress")]
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+#[cfg(feature = "progress")]
+use std::time::Instant;
+
+use crate::core::history::HistoryEvent;
+
+/// Recent jobs averaged for the ETA, so it follows model and hardware changes
+const HISTORY_WINDOW: usize = 50;
+
+/// Bars currently on screen, for [`LogWriter`]
+#[cfg(feature = "progress")]
+static ACTIVE: Mutex<Option<MultiProgress>> = Mutex::new(None);
+
+/// Average duration of the most recent jobs in the history, and how many
+/// jobs it covers
+pub fn history_average(events: &[HistoryEvent]) -> Option<(Duration, usize)> {
+    let durations: Vec<Duration> = events
+        .iter()
+        .rev()
+        .filter_map(|event| match event {
+            HistoryEvent::Job(record) => (record.finished_at - record.started_at).to_std().ok(),
+            HistoryEvent::Run { .. } => None,
+        })
+        .take(HISTORY_WINDOW)
+        .collect();
+    if durations.is_empty() {
+        return None;
+    }
+    Some((durations.iter().sum::<Duration>() / durations.len() as u32, durations.len()))
+}
+
+/// Time left for `total - done` jobs: the historical average blended with
+/// the `done_time` the finished jobs of this run took, less the part of its
+/// share the job `in_progress` has already used. None until there's
+/// something to go on.
+pub fn estimate_remaining(
+    history: Option<(Duration, usize)>,
+    done: u64,
+    total: u64,
+    done_time: Duration,
+    in_progress: Duration,
+) -> Option<Duration> {
+    let (history_time, history_jobs) = history.map_or((Duration::ZERO, 0), |(average, jobs)| (average * jobs as u32, jobs as u64));
+    let jobs = history_jobs + done;
+    if jobs == 0 {
+        return None;
+    }
+    let average = (history_time + done_time) / jobs as u32;
+    Some((average * total.saturating_sub(done) as u32).saturating_sub(in_progress.min(average)))
+}
+
+/// Time spent on the jobs of this run, for the ETA
+#[cfg(feature = "progress")]
+#[derive(Default)]
+struct RunTiming {
+    done_time: Duration,
+    current: Option<Instant>,
+}
+
+#[cfg(feature = "progress")]
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h{:02}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+/// The bars of one run
+#[cfg(feature = "progress")]
+pub struct RunProgress {
+    bars: MultiProgress,
+    run: ProgressBar,
+    timing: Arc<Mutex<RunTiming>>,
+}
+
+#[cfg(feature = "progress")]
+impl RunProgress {
+    /// Start the overall bar for `total_jobs` jobs
+    pub fn start(total_jobs: usize, history: &[HistoryEvent]) -> Self {
+        let bars = MultiProgress::new();
+        let average = history_average(history);
+        let timing = Arc::new(Mutex::new(RunTiming::default()));
+        let eta_timing = timing.clone();
+        let style = ProgressStyle::with_template("{bar:30.cyan/blue} {pos}/{len} jobs  ETA {history_eta}  {msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .with_key("history_eta", move |state: &ProgressState, w: &mut dyn std::fmt::Write| {
+                let timing = eta_timing.lock().unwrap_or_else(|e| e.into_inner());
+                let in_progress = timing.current.map_or(Duration::ZERO, |start| start.elapsed());
+                let eta = estimate_remaining(average, state.pos(), state.len().unwrap_or(0), timing.done_time, in_progress);
+                let _ = write!(w, "{}", eta.map_or_else(|| "?".to_string(), format_duration));
+            })
+            .progress_chars("=> ");
+        let run = bars.add(ProgressBar::new(total_jobs as u64).with_style(style));
+        run.enable_steady_tick(Duration::from_secs(1));
+        *ACTIVE.lock().unwrap_or_else(|e| e.into_inner()) = Some(bars.clone());
+        Self { bars, run, timing }
+    }
+
+    /// A job started
+    pub fn job_started(&self, job_id: &str) {
+        self.timing().current = Some(Instant::now());
+        self.run.set_message(job_id.to_string());
+    }
+
+    /// Jobs added to the run after it started
+    pub fn add_jobs(&self, count: usize) {
+        self.run.inc_length(count as u64);
+    }
+
+    /// A job finished, whatever its status
+    pub fn job_finished(&self) {
+        let mut timing = self.timing();
+        if let Some(start) = timing.current.take() {
+            timing.done_time += start.elapsed();
+        }
+        drop(timing);
+        self.run.inc(1);
+    }
+
+    fn timing(&self) -> std::sync::MutexGuard<'_, RunTiming> {
+        self.timing.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// A bar counting the `total_files` files of the current job
+    pub fn job_files(&self, job_id: &str, total_files: usize) -> FileProgress {
+        let style = ProgressStyle::with_template("  {bar:30.green/white} {pos}/{len} files  {msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("=> ");
+        let bar = self.bars.add(ProgressBar::new(total_files as u64).with_style(style));
+        bar.set_message(job_id.to_string());
+        FileProgress(Some(bar))
+    }
+}
+
+#[cfg(feature = "progress")]
+impl Drop for RunProgress {
+    fn drop(&mut self) {
+        self.run.finish_and_clear();
+        *ACTIVE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+/// Without the "progress" feature a run has no bars
+#[cfg(not(feature = "progress"))]
+pub struct RunProgress;
+
+#[cfg(not(feature = "progress"))]
+impl RunProgress {
+    pub fn start(_total_jobs: usize, _history: &[HistoryEvent]) -> Self {
+        Self
+    }
+
+    pub fn job_started(&self, _job_id: &str) {}
+
+    pub fn add_jobs(&self, _count: usize) {}
+
+    pub fn job_finished(&self) {}
+
+    pub fn job_files(&self, _job_id: &str, _total_files: usize) -> FileProgress {
+        FileProgress::hidden()
+    }
+}
+
+/// Files completed in one job; a no-op outside a run with progress bars
+#[cfg(feature = "progress")]
+pub struct FileProgress(Option<ProgressBar>);
+
+#[cfg(feature = "progress")]
+impl FileProgress {
+    pub fn hidden() -> Self {
+        Self(None)
+    }
+
+    /// One more file written
+    pub fn inc(&self) {
+        if let Some(bar) = &self.0 {
+            bar.inc(1);
+        }
+    }
+}
+
+/// Without the "progress" feature files are only logged
+#[cfg(not(feature = "progress"))]
+pub struct FileProgress;
+
+#[cfg(not(feature = "progress"))]
+impl FileProgress {
+    pub fn hidden() -> Self {
+        Self
+    }
+
+    pub fn inc(&self) {}
+}
+
+#[cfg(feature = "progress")]
+impl Drop for FileProgress {
+    fn drop(&mut self) {
+        if let Some(bar) = &self.0 {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+/// `tracing` writer that prints to stdout, hiding the progress bars while
+/// it does
+#[derive(Clone, Copy, Default)]
+pub struct LogWriter;
+
+impl Write for LogWriter {
+    #[cfg(not(feature = "progress"))]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stdout().write(buf)
+    }
+
+    #[cfg(feature = "progress")]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let active = ACTIVE.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        match active {
+            Some(bars) => bars.suspend(|| io::stdout().write_all(buf)).map(|_| buf.len()),
+            None => io::stdout().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for LogWriter {
+    type Writer = LogWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        *self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::history::JobRecord;
+    use crate::models::JobStatus;
+    use chrono::{TimeZone, Utc};
+
+    fn job(seconds: i64) -> HistoryEvent {
+        let started_at = Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+        HistoryEvent::Job(JobRecord {
+            job_id: "job".to_string(),
+            started_at,
+            finished_at: started_at + chrono::Duration::seconds(seconds),
+            from: JobStatus::Created,
+            to: JobStatus::Pass,
+            outputs: Vec::new(),
+            failure: None,
+            tokens: 0,
+            rubric: None,
+        })
+    }
+
+    #[test]
+    fn test_history_average() {
+        assert_eq!(history_average(&[]), None);
+        let run = HistoryEvent::Run { started_at: Utc::now(), jobs: 2 };
+        assert_eq!(history_average(&[run, job(10), job(30)]), Some((Duration::from_secs(20), 2)));
+        // Only the most recent jobs count
+        let mut events: Vec<HistoryEvent> = (0..HISTORY_WINDOW).map(|_| job(1000)).collect();
+        events.extend((0..HISTORY_WINDOW).map(|_| job(4)));
+        assert_eq!(history_average(&events), Some((Duration::from_secs(4), HISTORY_WINDOW)));
+    }
+
+    #[test]
+    fn test_estimate_remaining() {
+        let secs = Duration::from_secs;
+        assert_eq!(estimate_remaining(None, 0, 5, secs(0), secs(3)), None);
+        // History only: 4 jobs of 20s, 5s into the first one
+        assert_eq!(estimate_remaining(Some((secs(20), 4)), 0, 4, secs(0), secs(5)), Some(secs(75)));
+        // No history: this run's own pace
+        assert_eq!(estimate_remaining(None, 2, 5, secs(60), secs(0)), Some(secs(90)));
+        // A slow run pulls the historical average up; a job running longer
+        // than the average doesn't push the ETA below the jobs left
+        assert_eq!(estimate_remaining(Some((secs(10), 2)), 2, 4, secs(100), secs(5)), Some(secs(55)));
+        assert_eq!(estimate_remaining(Some((secs(10), 2)), 2, 4, secs(100), secs(90)), Some(secs(30)));
+        assert_eq!(estimate_remaining(Some((secs(10), 2)), 4, 4, secs(100), secs(0)), Some(Duration::ZERO));
+    }
+}
@@ -0,0 +1,137 @@
+//! Several WorkSplit projects run together (`worksplit-workspace.toml`)
+//!
+//! ```toml
+//! concurrency = "per_host"
+//!
+//! [[projects]]
+//! path = "services/api"
+//!
+//! [[projects]]
+//! path = "../frontend"
+//! name = "web"
+//! ```
+
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::WorkSplitError;
+
+/// Workspace file, in the directory `worksplit run --all-projects` runs from
+pub const PROJECTS_FILE: &str = "worksplit-workspace.toml";
+
+/// The projects of a workspace and how to run them
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectsFile {
+    #[serde(default)]
+    pub concurrency: ProjectConcurrency,
+    pub projects: Vec<ProjectEntry>,
+}
+
+/// A project listed in the workspace file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectEntry {
+    /// Project root, relative to the workspace file
+    pub path: PathBuf,
+    /// Name in the summary; the directory name by default
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// How the projects' queues are run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectConcurrency {
+    /// One project after another, in the listed order
+    #[default]
+    Sequential,
+    /// Projects using different Ollama URLs at the same time; projects on
+    /// the same host still take turns
+    PerHost,
+}
+
+/// A project ready to run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Project {
+    pub name: String,
+    pub root: PathBuf,
+}
+
+impl ProjectsFile {
+    /// Read `worksplit-workspace.toml` from `dir`
+    pub fn load(dir: &Path) -> Result<Self, WorkSplitError> {
+        let path = dir.join(PROJECTS_FILE);
+        let content = fs::read_to_string(&path).map_err(|e| WorkSplitError::ConfigError(format!(
+            "Cannot read {}: {}; --all-projects needs it in the current directory", path.display(), e)))?;
+        let file: Self = toml::from_str(&content)
+            .map_err(|e| WorkSplitError::ConfigError(format!("{}: {}", path.display(), e)))?;
+        if file.projects.is_empty() {
+            return Err(WorkSplitError::ConfigError(format!("{} lists no projects", path.display())));
+        }
+        Ok(file)
+    }
+
+    /// The listed projects with their roots resolved against `dir`; each
+    /// must have a jobs folder
+    pub fn projects(&self, dir: &Path) -> Result<Vec<Project>, WorkSplitError> {
+        let mut projects: Vec<Project> = Vec::new();
+        for entry in &self.projects {
+            let root = dir.join(&entry.path);
+            if !root.join("jobs").is_dir() {
+                return Err(WorkSplitError::JobsFolderNotFound(root.join("jobs")));
+            }
+            let name = entry.name.clone().unwrap_or_else(|| {
+                root.file_name().map_or_else(|| entry.path.display().to_string(), |n| n.to_string_lossy().to_string())
+            });
+            if projects.iter().any(|p| p.name == name) {
+                return Err(WorkSplitError::ConfigError(format!(
+                    "Two projects are named '{}' in {}; set `name` on one of them", name, PROJECTS_FILE)));
+            }
+            projects.push(Project { name, root });
+        }
+        Ok(projects)
+    }
+}
+
+/// Indices of the projects grouped by host, groups in order of first
+/// appearance; each group runs its projects one after another
+pub fn host_groups(hosts: &[String]) -> Vec<Vec<usize>> {
+    let mut groups: Vec<(&str, Vec<usize>)> = Vec::new();
+    for (index, host) in hosts.iter().enumerate() {
+        match groups.iter_mut().find(|(h, _)| *h == host.as_str()) {
+            Some((_, members)) => members.push(index),
+            None => groups.push((host, vec![index])),
+        }
+    }
+    groups.into_iter().map(|(_, members)| members).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_projects_and_group_by_host() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("services/api/jobs")).unwrap();
+        fs::create_dir_all(temp.path().join("web/jobs")).unwrap();
+        fs::write(temp.path().join(PROJECTS_FILE),
+            "concurrency = \"per_host\"\n\n[[projects]]\npath = \"services/api\"\n\n[[projects]]\npath = \"web\"\nname = \"frontend\"\n").unwrap();
+
+        let file = ProjectsFile::load(temp.path()).unwrap();
+        assert_eq!(file.concurrency, ProjectConcurrency::PerHost);
+        let projects = file.projects(temp.path()).unwrap();
+        assert_eq!(projects[0], Project { name: "api".into(), root: temp.path().join("services/api") });
+        assert_eq!(projects[1].name, "frontend");
+
+        fs::write(temp.path().join(PROJECTS_FILE), "[[projects]]\npath = \"missing\"\n").unwrap();
+        let file = ProjectsFile::load(temp.path()).unwrap();
+        assert!(matches!(file.projects(temp.path()), Err(WorkSplitError::JobsFolderNotFound(_))));
+
+        let hosts = ["http://a:11434", "http://b:11434", "http://a:11434"].map(String::from);
+        assert_eq!(host_groups(&hosts), vec![vec![0, 2], vec![1]]);
+    }
+}
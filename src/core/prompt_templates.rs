@@ -0,0 +1,190 @@
+//! Prompt templates (`jobs/_templates/`)
+//!
+//! Every prompt sent to the model is rendered from a template in Jinja
+//! syntax (rendered with minijinja). The built-in templates live in
+//! `templates/prompts/`; a project overrides one by putting a file of
+//! the same name in `jobs/_templates/`, e.g. `jobs/_templates/create.tmpl`.
+//! Overrides are parsed when the runner starts, so a broken template fails
+//! the run up front instead of in the middle of it.
+
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use minijinja::{Environment, Value};
+use tracing::{debug, warn};
+
+use crate::error::WorkSplitError;
+
+/// Template overrides folder inside the jobs folder
+pub const TEMPLATES_DIR: &str = "_templates";
+
+/// Extension of template files
+pub const TEMPLATE_EXTENSION: &str = "tmpl";
+
+/// Built-in templates by name
+const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    ("create", include_str!("../../templates/prompts/create.tmpl")),
+    ("report", include_str!("../../templates/prompts/report.tmpl")),
+    ("sequential_create", include_str!("../../templates/prompts/sequential_create.tmpl")),
+    ("verify", include_str!("../../templates/prompts/verify.tmpl")),
+    ("test", include_str!("../../templates/prompts/test.tmpl")),
+    ("retry", include_str!("../../templates/prompts/retry.tmpl")),
+    ("retry_followup", include_str!("../../templates/prompts/retry_followup.tmpl")),
+    ("split", include_str!("../../templates/prompts/split.tmpl")),
+    ("split_plan", include_str!("../../templates/prompts/split_plan.tmpl")),
+    ("planned_split", include_str!("../../templates/prompts/planned_split.tmpl")),
+    ("sequential_split", include_str!("../../templates/prompts/sequential_split.tmpl")),
+    ("edit", include_str!("../../templates/prompts/edit.tmpl")),
+    ("continue", include_str!("../../templates/prompts/continue.tmpl")),
+    ("fim", include_str!("../../templates/prompts/fim.tmpl")),
+];
+
+/// Names of the templates a project can override
+pub fn template_names() -> impl Iterator<Item = &'static str> {
+    BUILTIN_TEMPLATES.iter().map(|(name, _)| *name)
+}
+
+/// Source of a built-in template
+pub fn builtin_template(name: &str) -> Option<&'static str> {
+    BUILTIN_TEMPLATES.iter().find(|(n, _)| *n == name).map(|(_, source)| *source)
+}
+
+fn new_environment() -> Environment<'static> {
+    let mut env = Environment::new();
+    // Prompts are plain text: no HTML escaping, and block tags on their own
+    // line don't leave blank lines behind
+    env.set_auto_escape_callback(|_| minijinja::AutoEscape::None);
+    env.set_keep_trailing_newline(true);
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env
+}
+
+/// The prompt templates of a project: the built-ins plus its overrides
+#[derive(Debug)]
+pub struct PromptTemplates {
+    env: Environment<'static>,
+    overridden: Vec<String>,
+}
+
+impl Default for PromptTemplates {
+    /// The built-in templates only
+    fn default() -> Self {
+        let mut env = new_environment();
+        for (name, source) in BUILTIN_TEMPLATES {
+            env.add_template(name, source).expect("built-in prompt templates parse");
+        }
+        Self { env, overridden: Vec::new() }
+    }
+}
+
+impl PromptTemplates {
+    /// The built-ins with the overrides from `jobs/_templates/`
+    pub fn load(jobs_dir: &Path) -> Result<Self, WorkSplitError> {
+        let mut templates = Self::default();
+        let dir = jobs_dir.join(TEMPLATES_DIR);
+        if !dir.is_dir() {
+            return Ok(templates);
+        }
+
+        let mut paths: Vec<_> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == TEMPLATE_EXTENSION))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let name = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            if builtin_template(&name).is_none() {
+                return Err(WorkSplitError::PromptTemplate {
+                    path,
+                    message: format!(
+                        "not a prompt template name (expected one of: {})",
+                        template_names().collect::<Vec<_>>().join(", ")
+                    ),
+                });
+            }
+            let source = fs::read_to_string(&path)?;
+            if let Err(e) = templates.env.add_template_owned(name.clone(), source) {
+                return Err(WorkSplitError::PromptTemplate { path, message: e.to_string() });
+            }
+            debug!("Using prompt template override {}", path.display());
+            templates.overridden.push(name);
+        }
+        Ok(templates)
+    }
+
+    /// Names of the templates the project overrides
+    pub fn overridden(&self) -> &[String] {
+        &self.overridden
+    }
+
+    /// Render a template. An override that fails to render (e.g. it uses a
+    /// variable the way the context doesn't allow) falls back to the built-in.
+    pub fn render(&self, name: &str, ctx: Value) -> String {
+        match self.env.get_template(name).and_then(|t| t.render(&ctx)) {
+            Ok(prompt) => prompt,
+            Err(e) if self.overridden.iter().any(|n| n == name) => {
+                warn!("Prompt template '{}' failed to render ({}); using the built-in one", name, e);
+                static BUILTIN: OnceLock<PromptTemplates> = OnceLock::new();
+                BUILTIN.get_or_init(PromptTemplates::default).render(name, ctx)
+            }
+            Err(e) => panic!("built-in prompt template '{}' failed to render: {}", name, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minijinja::context;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_builtin_templates_parse() {
+        let templates = PromptTemplates::default();
+        assert!(templates.overridden().is_empty());
+        for name in template_names() {
+            assert!(templates.env.get_template(name).is_ok(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_load_overrides() {
+        let temp = TempDir::new().unwrap();
+        assert!(PromptTemplates::load(temp.path()).unwrap().overridden().is_empty());
+
+        let dir = temp.path().join(TEMPLATES_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("create.tmpl"), "Write {{ output_path }}: {{ instructions }}\n").unwrap();
+        fs::write(dir.join("notes.txt"), "not a template").unwrap();
+
+        let templates = PromptTemplates::load(temp.path()).unwrap();
+        assert_eq!(templates.overridden(), ["create".to_string()]);
+        let prompt = templates.render("create", context! { output_path => "src/a.rs", instructions => "add a()" });
+        assert_eq!(prompt, "Write src/a.rs: add a()\n");
+
+        // Unknown names and syntax errors fail at load time
+        fs::write(dir.join("crate.tmpl"), "typo").unwrap();
+        let err = PromptTemplates::load(temp.path()).unwrap_err().to_string();
+        assert!(err.contains("crate.tmpl") && err.contains("expected one of"), "{}", err);
+        fs::remove_file(dir.join("crate.tmpl")).unwrap();
+        fs::write(dir.join("verify.tmpl"), "{% if %}").unwrap();
+        let err = PromptTemplates::load(temp.path()).unwrap_err().to_string();
+        assert!(err.contains("verify.tmpl"), "{}", err);
+    }
+
+    #[test]
+    fn test_failing_override_falls_back_to_builtin() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join(TEMPLATES_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("test.tmpl"), "{{ instructions.missing.field }}").unwrap();
+
+        let templates = PromptTemplates::load(temp.path()).unwrap();
+        let ctx = context! { system_prompt => "sys", context_files => Vec::<String>::new(), instructions => "reqs", test_path => "t.rs" };
+        let prompt = templates.render("test", ctx);
+        assert!(prompt.contains("Generate tests for: t.rs"), "{}", prompt);
+    }
+}
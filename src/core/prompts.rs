@@ -50,6 +50,7 @@ END
 RULES:
 - FIND must match file content EXACTLY (whitespace matters)
 - Include enough context for uniqueness
+- For repeated text, use FIND (lines 120-160): to restrict the match to those lines
 - Output ONLY edit blocks - no explanations, no alternatives
 - Start output immediately
 
@@ -78,6 +79,53 @@ RULES:
 
 Output the fixed code using ~~~worksplit delimiters now."#;
 
+/// System prompt for report mode (design docs, reviews, summaries)
+pub const SYSTEM_PROMPT_REPORT: &str = r#"You are a technical writer. Write the requested document immediately.
+
+RULES:
+1. Read the prompt ONCE, then write the document in a single pass
+2. Output ONLY the document as markdown - no preamble, no closing remarks
+3. Answer exactly what the instructions ask for, in the structure they ask for
+4. Base every claim about the code on the provided context files
+5. Quote code only where it supports a point
+
+Start writing the document NOW."#;
+
+/// System prompt for verifying report mode output
+pub const SYSTEM_PROMPT_VERIFY_REPORT: &str = r#"NO THINKING. RESPOND IMMEDIATELY.
+
+Your FIRST token must be PASS or FAIL. Do not generate thinking tokens.
+
+The output is a document, not code. Judge its content:
+PASS = every requested section or question is covered and claims match the context
+FAIL: <reason> = a requirement is missing or a claim contradicts the context
+
+When in doubt: PASS"#;
+
+/// System prompt for rubric verification (`[verification] mode = "rubric"`)
+pub const SYSTEM_PROMPT_VERIFY_RUBRIC: &str = r#"NO THINKING. RESPOND IMMEDIATELY.
+
+Score the generated output from 1 (poor) to 5 (excellent) on each axis:
+correctness = does what the instructions ask, without bugs
+completeness = covers every requirement
+style = idiomatic, readable and consistent with the context
+tests = meaningful tests of the behavior; null if the job has no tests
+
+Respond with ONLY this JSON object, even if asked to answer PASS or FAIL:
+{"correctness": 4, "completeness": 5, "style": 3, "tests": null, "summary": "<main problem, or empty>"}
+
+Your FIRST token must be {"#;
+
+/// Appended to the verify prompt of `verification: strict` jobs
+pub const VERIFY_STRICT_ADDENDUM: &str = r#"STRICT VERIFICATION: this job must be exactly right.
+Report any doubt, missing edge case or deviation from the instructions as
+PASS_WITH_WARNINGS: <issue> or FAIL: <reason>. Warnings will be sent back for a fix."#;
+
+/// Appended to the verify prompt of `verification: lenient` jobs
+pub const VERIFY_LENIENT_ADDENDUM: &str = r#"LENIENT VERIFICATION: accept working code that covers the main requirements.
+Use FAIL_HARD only for code that is broken or does something else entirely;
+report style issues and minor gaps as PASS_WITH_WARNINGS: <issue>."#;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -89,6 +137,9 @@ mod tests {
         assert!(!SYSTEM_PROMPT_EDIT.is_empty());
         assert!(!SYSTEM_PROMPT_TEST.is_empty());
         assert!(!SYSTEM_PROMPT_RETRY.is_empty());
+        assert!(!SYSTEM_PROMPT_REPORT.is_empty());
+        assert!(SYSTEM_PROMPT_VERIFY_REPORT.contains("PASS"));
+        assert!(SYSTEM_PROMPT_VERIFY_RUBRIC.contains("\"correctness\""));
     }
 
     #[test]
@@ -0,0 +1,156 @@
+//! Provenance of generated files (`jobs/_provenance.json`)
+//!
+//! Every file the model writes is recorded with the job, the model and a
+//! hash of the prompt that produced it, plus a hash of the content as
+//! written, so a review can tell which code is generated, how it came about,
+//! and whether it was changed by hand since. The latest write of a file
+//! replaces its entry. Hashing needs the "provenance" feature; without it
+//! nothing is recorded.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+#[cfg(feature = "provenance")]
+use ring::digest;
+use serde::{Deserialize, Serialize};
+
+use crate::core::atomic_write::write_atomic;
+use crate::core::ollama::ChatMessage;
+use crate::error::WorkSplitError;
+
+/// Manifest file name inside jobs/
+pub const PROVENANCE_FILE: &str = "_provenance.json";
+
+/// Model and prompt of a request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestFingerprint {
+    pub model: String,
+    /// `sha256:<hex>` of the messages (and fill-in-the-middle suffix) sent
+    pub prompt_hash: String,
+}
+
+impl RequestFingerprint {
+    pub fn new(model: &str, messages: &[ChatMessage], suffix: Option<&str>) -> Self {
+        let mut text = String::new();
+        for message in messages {
+            text.push_str(&format!("{}\n{}\n", message.role, message.content));
+        }
+        if let Some(suffix) = suffix {
+            text.push_str(&format!("suffix\n{}\n", suffix));
+        }
+        Self { model: model.to_string(), prompt_hash: sha256(&text) }
+    }
+}
+
+/// A generated file, as written by the runner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedWrite {
+    /// Where the file was written
+    pub path: PathBuf,
+    /// The request the content came from
+    pub request: RequestFingerprint,
+    /// `sha256:<hex>` of the content written
+    pub content_hash: String,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl GeneratedWrite {
+    pub fn new(path: &Path, request: RequestFingerprint, content: &str) -> Self {
+        Self { path: path.to_path_buf(), request, content_hash: sha256(content), generated_at: Utc::now() }
+    }
+}
+
+/// Where a file came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileProvenance {
+    pub job_id: String,
+    pub model: String,
+    pub prompt_hash: String,
+    pub content_hash: String,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// `jobs/_provenance.json`: files keyed by their path relative to the
+/// project root
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvenanceManifest {
+    pub files: BTreeMap<String, FileProvenance>,
+}
+
+impl ProvenanceManifest {
+    /// The manifest in `jobs_dir`, empty if there is none yet
+    pub fn load(jobs_dir: &Path) -> Result<Self, WorkSplitError> {
+        match fs::read_to_string(jobs_dir.join(PROVENANCE_FILE)) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| WorkSplitError::IoError(format!("Invalid {}: {}", PROVENANCE_FILE, e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, jobs_dir: &Path) -> Result<(), WorkSplitError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| WorkSplitError::IoError(e.to_string()))?;
+        write_atomic(&jobs_dir.join(PROVENANCE_FILE), json)?;
+        Ok(())
+    }
+
+    /// Record that `job_id` wrote `path` (relative to the project root)
+    pub fn record(&mut self, job_id: &str, path: &Path, write: &GeneratedWrite) {
+        self.files.insert(
+            path.to_string_lossy().replace('\\', "/"),
+            FileProvenance {
+                job_id: job_id.to_string(),
+                model: write.request.model.clone(),
+                prompt_hash: write.request.prompt_hash.clone(),
+                content_hash: write.content_hash.clone(),
+                generated_at: write.generated_at,
+            },
+        );
+    }
+}
+
+/// `sha256:<hex>` of `text`
+#[cfg(feature = "provenance")]
+pub fn sha256(text: &str) -> String {
+    let hash = digest::digest(&digest::SHA256, text.as_bytes());
+    let hex: String = hash.as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256:{}", hex)
+}
+
+/// Without the "provenance" feature there is no hash: always empty
+#[cfg(not(feature = "provenance"))]
+pub fn sha256(_text: &str) -> String {
+    String::new()
+}
+
+#[cfg(all(test, feature = "provenance"))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_manifest_keeps_latest_write_per_file() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(ProvenanceManifest::load(temp.path()).unwrap(), ProvenanceManifest::default());
+
+        let messages = [ChatMessage::system("Write code."), ChatMessage::user("Write a()")];
+        let first = RequestFingerprint::new("qwen2.5-coder", &messages, None);
+        assert_eq!(first, RequestFingerprint::new("qwen2.5-coder", &messages, None));
+        assert_ne!(first.prompt_hash, RequestFingerprint::new("qwen2.5-coder", &messages[1..], None).prompt_hash);
+        let retry = RequestFingerprint::new("llama3.1", &messages, Some("}"));
+
+        let mut manifest = ProvenanceManifest::default();
+        manifest.record("001_a", Path::new("src/a.rs"), &GeneratedWrite::new(Path::new("/p/src/a.rs"), first, "fn a() {}"));
+        manifest.record("002_a", Path::new("src/a.rs"), &GeneratedWrite::new(Path::new("/p/src/a.rs"), retry.clone(), "fn a() { 1 }"));
+        manifest.save(temp.path()).unwrap();
+
+        let loaded = ProvenanceManifest::load(temp.path()).unwrap();
+        let entry = &loaded.files["src/a.rs"];
+        assert_eq!((entry.job_id.as_str(), entry.model.as_str()), ("002_a", "llama3.1"));
+        assert_eq!(entry.prompt_hash, retry.prompt_hash);
+        assert_eq!(entry.content_hash, sha256("fn a() { 1 }"));
+        assert!(entry.content_hash.starts_with("sha256:") && entry.content_hash.len() == 71);
+    }
+}
@@ -0,0 +1,111 @@
+//! Recordings of model interactions (`worksplit run --record`)
+//!
+//! Every request/response pair of a run is appended to
+//! `jobs/_recordings/run-<timestamp>.jsonl`, one JSON object per line.
+//! `worksplit run --replay <recording>` serves the responses back per job
+//! (see [`MockFixtures::from_recording`](crate::core::mock_fixtures::MockFixtures::from_recording)),
+//! so extraction regressions can be debugged against real transcripts.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::WorkSplitError;
+
+/// Recordings folder inside the jobs folder
+pub const RECORDINGS_DIR: &str = "_recordings";
+
+/// One request to the model and its response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedExchange {
+    /// Job the request was made for
+    pub job_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    pub prompt: String,
+    pub response: String,
+}
+
+/// Appends exchanges to a recording file as they happen, so a crashed or
+/// cancelled run still leaves a usable recording
+#[derive(Debug)]
+pub struct Recorder {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl Recorder {
+    /// Start a new recording in `jobs/_recordings/`
+    pub fn create(jobs_dir: &Path) -> Result<Self, WorkSplitError> {
+        let dir = jobs_dir.join(RECORDINGS_DIR);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("run-{}.jsonl", chrono::Utc::now().format("%Y%m%d-%H%M%S")));
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self { path, file: Mutex::new(file) })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self, exchange: &RecordedExchange) -> Result<(), WorkSplitError> {
+        let line = serde_json::to_string(exchange).map_err(|e| WorkSplitError::IoError(e.to_string()))?;
+        let mut file = self.file.lock().unwrap();
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+}
+
+/// Read every exchange of a recording, in the order they happened
+pub fn load_recording(path: &Path) -> Result<Vec<RecordedExchange>, WorkSplitError> {
+    let content = fs::read_to_string(path)?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                WorkSplitError::IoError(format!("{} line {}: invalid recording entry: {}", path.display(), index + 1, e))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_and_load() {
+        let temp = TempDir::new().unwrap();
+        let recorder = Recorder::create(temp.path()).unwrap();
+        assert!(recorder.path().starts_with(temp.path().join(RECORDINGS_DIR)));
+
+        let exchanges = vec![
+            RecordedExchange {
+                job_id: Some("001_a".to_string()),
+                system_prompt: Some("Be terse".to_string()),
+                prompt: "Write a\nfunction".to_string(),
+                response: "```rust\nfn a() {}\n```".to_string(),
+            },
+            RecordedExchange {
+                job_id: Some("001_a".to_string()),
+                system_prompt: None,
+                prompt: "Verify".to_string(),
+                response: "PASS".to_string(),
+            },
+        ];
+        for exchange in &exchanges {
+            recorder.record(exchange).unwrap();
+        }
+        assert_eq!(load_recording(recorder.path()).unwrap(), exchanges);
+
+        fs::write(temp.path().join("broken.jsonl"), "{\"job_id\": null}\n").unwrap();
+        let err = load_recording(&temp.path().join("broken.jsonl")).unwrap_err().to_string();
+        assert!(err.contains("line 1"), "{}", err);
+    }
+}
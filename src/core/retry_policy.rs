@@ -0,0 +1,115 @@
+//! Retries of individual model requests (`[ollama.retry]`)
+//!
+//! A request that fails with one of the configured failure classes is sent
+//! again after an exponential backoff with random jitter. This is separate
+//! from the retry ladder, which reruns a whole generation after failed
+//! verification.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use crate::error::OllamaError;
+use crate::models::{RequestRetryConfig, RetryClass};
+
+/// A failed attempt that was retried
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestRetry {
+    /// The attempt that failed, from 1
+    pub attempt: u32,
+    pub error: String,
+    /// Wait before the next attempt
+    pub delay: Duration,
+}
+
+/// When and how often to retry a failed request
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    backoff: Duration,
+    jitter: f64,
+    retry_on: Vec<RetryClass>,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &RequestRetryConfig) -> Self {
+        Self {
+            max_attempts: config.max_attempts.max(1),
+            backoff: Duration::from_millis(config.backoff_ms),
+            jitter: config.jitter.clamp(0.0, 1.0),
+            retry_on: config.retry_on.clone(),
+        }
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Whether to retry after `attempt` (from 1) failed with `error`
+    pub fn should_retry(&self, error: &OllamaError, attempt: u32) -> bool {
+        attempt < self.max_attempts && retry_class(error).is_some_and(|class| self.retry_on.contains(&class))
+    }
+
+    /// Wait after `attempt` failed: the backoff doubled for every earlier
+    /// retry, spread by the jitter. `unit` is a random number in [0, 1).
+    pub fn delay(&self, attempt: u32, unit: f64) -> Duration {
+        let base = self.backoff.saturating_mul(1 << attempt.saturating_sub(1).min(16));
+        base.mul_f64(1.0 + self.jitter * (2.0 * unit.clamp(0.0, 1.0) - 1.0))
+    }
+}
+
+/// The retry class of a request failure; None for failures a retry can't fix
+pub fn retry_class(error: &OllamaError) -> Option<RetryClass> {
+    match error {
+        OllamaError::ThinkingTimeout { .. } => Some(RetryClass::ThinkingTimeout),
+        OllamaError::Timeout(_) | OllamaError::StallTimeout(_) => Some(RetryClass::Timeout),
+        OllamaError::ConnectionRefused(_) | OllamaError::RequestFailed(_) => Some(RetryClass::Connection),
+        OllamaError::HttpError { status, .. } if *status >= 500 => Some(RetryClass::ServerError),
+        OllamaError::StreamError(_) => Some(RetryClass::Stream),
+        OllamaError::HttpError { .. } | OllamaError::ContextOverflow(_) | OllamaError::ParseError(_) | OllamaError::MockFixture(_) => None,
+    }
+}
+
+/// A random number in [0, 1) for the jitter
+pub fn jitter_unit() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_nanos());
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(max_attempts: u32, retry_on: Vec<RetryClass>) -> RetryPolicy {
+        RetryPolicy::from_config(&RequestRetryConfig { max_attempts, backoff_ms: 1000, jitter: 0.5, retry_on })
+    }
+
+    #[test]
+    fn test_should_retry() {
+        let policy = policy(3, vec![RetryClass::Connection, RetryClass::ServerError]);
+        let refused = OllamaError::ConnectionRefused("down".to_string());
+        assert!(policy.should_retry(&refused, 1));
+        assert!(policy.should_retry(&refused, 2));
+        assert!(!policy.should_retry(&refused, 3));
+        assert!(policy.should_retry(&OllamaError::HttpError { status: 503, message: String::new() }, 1));
+        assert!(!policy.should_retry(&OllamaError::HttpError { status: 404, message: String::new() }, 1));
+        assert!(!policy.should_retry(&OllamaError::Timeout(300), 1));
+        // The default retries a thinking timeout once, as before
+        let default = RetryPolicy::from_config(&RequestRetryConfig::default());
+        let thinking = OllamaError::ThinkingTimeout { duration_secs: 120, thinking_tokens: 10 };
+        assert!(default.should_retry(&thinking, 1));
+        assert!(!default.should_retry(&thinking, 2));
+    }
+
+    #[test]
+    fn test_delay_backs_off_with_jitter() {
+        let policy = policy(5, Vec::new());
+        assert_eq!(policy.delay(1, 0.5), Duration::from_secs(1));
+        assert_eq!(policy.delay(3, 0.5), Duration::from_secs(4));
+        assert_eq!(policy.delay(1, 0.0), Duration::from_millis(500));
+        assert_eq!(policy.delay(2, 1.0), Duration::from_secs(3));
+        let unit = jitter_unit();
+        assert!((0.0..1.0).contains(&unit));
+    }
+}
@@ -1,13 +1,20 @@
 use std::fs;
 use std::path::{Path, PathBuf};
+use tracing::info;
 
 use crate::core::{
-    assemble_edit_prompt, parse_edit_instructions, apply_edit, find_fuzzy_match,
-    OllamaClient, EditInstruction, SYSTEM_PROMPT_EDIT,
+    assemble_continue_prompt, assemble_edit_prompt, assemble_fim_prompt, parse_edit_instructions, apply_edit,
+    apply_edit_with, extract_fim_middle, fill_site, find_fuzzy_match, find_fuzzy_matches, fuzzy_candidate_preview,
+    FuzzyPick, OllamaClient, EditInstruction, SYSTEM_PROMPT_EDIT,
 };
+use crate::core::atomic_write::write_atomic;
+use crate::core::injection;
+use crate::core::output_paths::{check_protected_path, check_write_path};
+use crate::core::targets::{chunk_targets, expand_target_files};
+use crate::core::prompt_templates::PromptTemplates;
 use crate::error::WorkSplitError;
-use crate::models::{Config, Job};
-use crate::models::status::PartialEditState;
+use crate::models::{Config, FuzzyMatchPolicy, InjectionPolicy, Job};
+use crate::models::status::{FailedEdit as RecordedFailedEdit, PartialEditState};
 
 /// Result of a dry-run edit analysis
 #[derive(Debug, Clone)]
@@ -27,6 +34,7 @@ pub struct PlannedEdit {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::enum_variant_names)]
 pub enum PlannedEditStatus {
     WillApply,
     WillApplyFuzzy,
@@ -46,25 +54,27 @@ pub struct EditModeResult {
 /// Analyze what edits would be applied without actually applying them
 pub(crate) async fn dry_run_edit_mode(
     ollama: &OllamaClient,
+    templates: &PromptTemplates,
     project_root: &Path,
     config: &Config,
     job: &Job,
     context_files: &[(PathBuf, String)],
     edit_prompt: &str,
 ) -> Result<DryRunResult, WorkSplitError> {
-    let target_files = job.metadata.get_target_files();
+    let target_files = expand_target_files(project_root, &job.metadata.get_target_files())?;
     let mut target_file_contents: Vec<(PathBuf, String)> = Vec::new();
     for path in &target_files {
         let content = fs::read_to_string(project_root.join(path))?;
         target_file_contents.push((path.clone(), content));
     }
     
-    let prompt = assemble_edit_prompt(edit_prompt, &target_file_contents, context_files, &job.instructions);
+    let regex_edits = config.behavior.regex_edits;
+    let prompt = assemble_edit_prompt(templates, edit_prompt, &target_file_contents, context_files, &job.instructions, regex_edits);
     let response = ollama.generate_with_retry(Some(SYSTEM_PROMPT_EDIT), &prompt, config.behavior.stream_output)
         .await
         .map_err(|e| { WorkSplitError::Ollama(e) })?;
     
-    let parsed_edits = parse_edit_instructions(&response);
+    let parsed_edits = parse_edit_instructions(&response, regex_edits);
     let mut planned_edits = Vec::new();
     let mut warnings = Vec::new();
     
@@ -75,8 +85,25 @@ pub(crate) async fn dry_run_edit_mode(
         
         let line_number = None;
         
+        // Regex and item edits must match exactly once
+        if edit.regex || edit.item.is_some() {
+            let status = match apply_edit(&content, edit) {
+                Ok(_) => PlannedEditStatus::WillApply,
+                Err(e) => {
+                    warnings.push(e);
+                    PlannedEditStatus::WillFail
+                }
+            };
+            planned_edits.push(PlannedEdit {
+                file_path: edit.file_path.clone(),
+                line_number,
+                find_preview: edit.find.chars().take(50).collect(),
+                replace_preview: edit.replace.chars().take(50).collect(),
+                status,
+            });
+        }
         // Try exact match
-        if content.contains(&edit.find) {
+        else if content.contains(&edit.find) {
             let find_preview = edit.find.chars().take(50).collect::<String>();
             let replace_preview = edit.replace.chars().take(50).collect::<String>();
             planned_edits.push(PlannedEdit {
@@ -89,6 +116,14 @@ pub(crate) async fn dry_run_edit_mode(
         }
         // Try fuzzy match
         else if let Some((start, _end, _matched)) = find_fuzzy_match(&content, &edit.find) {
+            let candidates = find_fuzzy_matches(&content, &edit.find).len();
+            if candidates > 1 {
+                warnings.push(format!(
+                    "FIND text for {} matches {} places after whitespace normalization; [behavior] fuzzy_match decides which",
+                    edit.file_path.display(),
+                    candidates
+                ));
+            }
             let find_preview = edit.find.chars().take(50).collect::<String>();
             let replace_preview = edit.replace.chars().take(50).collect::<String>();
             planned_edits.push(PlannedEdit {
@@ -150,98 +185,394 @@ impl std::fmt::Display for DryRunResult {
 }
 
 /// Process edit mode job
+///
+/// Directory and glob targets are expanded first, and target sets larger than
+/// `limits.max_edit_chunk_lines` are edited over several prompts. Targets are
+/// read from `output_root` when a copy exists there (staged by an earlier job
+/// in read-only mode), otherwise from `project_root`. Edited files are written
+/// under `output_root`.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn process_edit_mode(
     ollama: &OllamaClient,
+    templates: &PromptTemplates,
     project_root: &Path,
+    output_root: &Path,
     config: &Config,
     job: &Job,
     context_files: &[(PathBuf, String)],
     edit_prompt: &str,
     _dry_run: bool,
 ) -> Result<EditModeResult, WorkSplitError> {
-    let target_files = job.metadata.get_target_files();
-    let mut target_file_contents: Vec<(PathBuf, String)> = Vec::new();
-    for path in &target_files {
-        let content = fs::read_to_string(project_root.join(path))?;
-        target_file_contents.push((path.clone(), content));
+    if job.metadata.is_fim_edit() {
+        return process_fim_mode(ollama, templates, project_root, output_root, config, job, context_files).await;
     }
-    
-    let prompt = assemble_edit_prompt(edit_prompt, &target_file_contents, context_files, &job.instructions);
+    let target_files = expand_target_files(project_root, &job.metadata.get_target_files())?;
+    check_targets_writable(output_root, config, &target_files)?;
+    let chunks = edit_chunks(project_root, output_root, config, &target_files)?;
+    if chunks.len() > 1 {
+        info!("Editing {} target files in {} prompts", target_files.len(), chunks.len());
+    }
+
+    // Aggregated over all chunks
+    let mut edits = EditAccumulator::new(config);
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        if chunks.len() > 1 {
+            info!("Edit prompt {}/{} ({} files)", index + 1, chunks.len(), chunk.len());
+        }
+        let prompt = assemble_edit_prompt(templates, edit_prompt, chunk, context_files, &job.instructions, config.behavior.regex_edits);
+        let response = ollama.generate_with_retry(Some(SYSTEM_PROMPT_EDIT), &prompt, config.behavior.stream_output)
+            .await
+            .map_err(|e| { WorkSplitError::Ollama(e) })?;
+        edits.apply(ollama, chunk, &response, output_root)?;
+    }
+
+    edits.into_result()
+}
+
+/// Target files with their contents, in one group per edit prompt: large
+/// target sets (directories, globs) are edited over several prompts
+pub(crate) fn edit_chunks(
+    project_root: &Path,
+    output_root: &Path,
+    config: &Config,
+    target_files: &[PathBuf],
+) -> Result<Vec<Vec<(PathBuf, String)>>, WorkSplitError> {
+    let target_file_contents = read_targets(project_root, output_root, target_files)?;
+    Ok(chunk_targets(target_file_contents, config.limits.max_edit_chunk_lines))
+}
+
+/// The single target file of a fill-in-the-middle edit
+fn fim_target(project_root: &Path, job: &Job) -> Result<PathBuf, WorkSplitError> {
+    let targets = expand_target_files(project_root, &job.metadata.get_target_files())?;
+    match <[PathBuf; 1]>::try_from(targets) {
+        Ok([target]) => Ok(target),
+        Err(targets) => Err(WorkSplitError::EditFailed(format!(
+            "edit_strategy: fim edits a single target file, but the targets match {}", targets.len()
+        ))),
+    }
+}
+
+/// A fill-in-the-middle request: the prompt (code before the site) and the
+/// code after it, for a fill site of `content`
+pub(crate) struct FimRequest {
+    pub path: PathBuf,
+    pub content: String,
+    /// Lines `start..end` of `content` are replaced
+    pub start: usize,
+    pub end: usize,
+    pub prompt: String,
+    pub suffix: String,
+}
+
+/// Build the fill-in-the-middle request for an `edit_strategy: fim` job
+///
+/// The code before the change site (ending with the instructions as
+/// comments) and the code after it are sent to the model, which writes what
+/// goes between. Both sides are cut to half of `limits.max_edit_chunk_lines`
+/// around the site; the file keeps all of its code.
+pub(crate) fn fim_request(
+    templates: &PromptTemplates,
+    project_root: &Path,
+    output_root: &Path,
+    config: &Config,
+    job: &Job,
+    context_files: &[(PathBuf, String)],
+) -> Result<FimRequest, WorkSplitError> {
+    let path = fim_target(project_root, job)?;
+    let (_, content) = read_targets(project_root, output_root, std::slice::from_ref(&path))?.remove(0);
+    let (start, end) = fill_site(&content, job.metadata.fill_lines())
+        .map_err(|e| WorkSplitError::EditFailed(format!("{}: {}", path.display(), e)))?;
+
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let (before, after) = (&lines[..start], &lines[end..]);
+    let window = (config.limits.max_edit_chunk_lines / 2).max(1);
+    let prefix = before[before.len().saturating_sub(window)..].concat();
+    let suffix = after[..after.len().min(window)].concat();
+    // Instructions go at the indentation of the code they replace
+    let indent_of = |line: &str| line[..line.len() - line.trim_start().len()].to_string();
+    let indent = lines[start..].iter().find(|l| !l.trim().is_empty()).map(|l| indent_of(l)).unwrap_or_default();
+    let prompt = assemble_fim_prompt(templates, &path, &prefix, &indent, context_files, &job.instructions);
+    Ok(FimRequest { path, content, start, end, prompt, suffix })
+}
+
+/// Process an `edit_strategy: fim` edit job (see [`fim_request`])
+async fn process_fim_mode(
+    ollama: &OllamaClient,
+    templates: &PromptTemplates,
+    project_root: &Path,
+    output_root: &Path,
+    config: &Config,
+    job: &Job,
+    context_files: &[(PathBuf, String)],
+) -> Result<EditModeResult, WorkSplitError> {
+    let request = fim_request(templates, project_root, output_root, config, job, context_files)?;
+    let FimRequest { path, content, start, end, prompt, suffix } = request;
+    check_targets_writable(output_root, config, std::slice::from_ref(&path))?;
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let (before, after) = (&lines[..start], &lines[end..]);
+
+    info!("Filling {} lines {}-{} ({} lines before, {} after)", path.display(), start + 1, end, before.len(), after.len());
+    let response = ollama.fill_in_middle_with_retry(&prompt, &suffix, config.behavior.stream_output)
+        .await
+        .map_err(WorkSplitError::Ollama)?;
+    let middle = extract_fim_middle(&response, &suffix);
+    if middle.is_empty() {
+        return Err(WorkSplitError::EmptyExtraction("Fill-in-the-middle answer contained no code".to_string()));
+    }
+
+    let mut edited = before.concat();
+    if !edited.is_empty() && !edited.ends_with('\n') {
+        edited.push('\n');
+    }
+    edited.push_str(&middle);
+    edited.push_str(&after.concat());
+
+    let full_path = output_root.join(&path);
+    injection::check_write(config.safety.injection_check, output_root, &full_path, &edited)?;
+    if let Some(parent) = full_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(&full_path, &edited)?;
+    ollama.record_write(&full_path, &edited);
+    Ok(EditModeResult {
+        total_lines: crate::core::count_lines(&edited),
+        generated_files: vec![(path, edited)],
+        output_paths: vec![full_path],
+        partial_state: None,
+        suggestions: Vec::new(),
+    })
+}
+
+/// Re-prompt for the edits that failed in an earlier run (`run --continue`)
+///
+/// Only the files named in `failed` are sent, with their current contents
+/// (staged copy first), the failed FIND/REPLACE blocks and the failure reasons.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn continue_edit_mode(
+    ollama: &OllamaClient,
+    templates: &PromptTemplates,
+    project_root: &Path,
+    output_root: &Path,
+    config: &Config,
+    job: &Job,
+    failed: &[RecordedFailedEdit],
+    edit_prompt: &str,
+) -> Result<EditModeResult, WorkSplitError> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for edit in failed {
+        let path = PathBuf::from(&edit.file_path);
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    check_targets_writable(output_root, config, &paths)?;
+    let target_file_contents = read_targets(project_root, output_root, &paths)?;
+
+    info!("Continuing {} failed edit(s) across {} file(s)", failed.len(), paths.len());
+    let prompt = assemble_continue_prompt(templates, edit_prompt, &target_file_contents, failed, &job.instructions);
     let response = ollama.generate_with_retry(Some(SYSTEM_PROMPT_EDIT), &prompt, config.behavior.stream_output)
         .await
         .map_err(|e| { WorkSplitError::Ollama(e) })?;
-    
-    let parsed_edits = parse_edit_instructions(&response);
-    let mut generated_files: Vec<(PathBuf, String)> = Vec::new();
-    let mut full_output_paths: Vec<PathBuf> = Vec::new();
-    let mut total_lines = 0;
-    
-    let mut partial_state = PartialEditState::new();
-    let mut failed_edits = Vec::new();
-    
-    for (path, original_content) in &target_file_contents {
-        let file_edits: Vec<&EditInstruction> = parsed_edits.edits_for_file(path);
-        if file_edits.is_empty() { continue; }
-        
-        let mut current_content = original_content.clone();
-        let mut file_edits_applied = 0;
-        
-        for edit in &file_edits {
-            let result = apply_edit(&current_content, edit);
-            match result {
-                Ok(edited) => {
-                    current_content = edited;
-                    file_edits_applied += 1;
+
+    let mut edits = EditAccumulator::new(config);
+    edits.apply(ollama, &target_file_contents, &response, output_root)?;
+    edits.into_result()
+}
+
+/// Refuse edit targets outside the project, outside `[safety]
+/// allowed_output_dirs` or matching `[safety] protected_paths` before
+/// prompting; edits are only ever written back to these files
+fn check_targets_writable(output_root: &Path, config: &Config, paths: &[PathBuf]) -> Result<(), WorkSplitError> {
+    for path in paths {
+        check_write_path(output_root, &output_root.join(path), &config.safety.allowed_output_dirs)?;
+        check_protected_path(output_root, &output_root.join(path), &config.safety.protected_paths)?;
+    }
+    Ok(())
+}
+
+/// Read target files, preferring a staged copy under `output_root`
+fn read_targets(
+    project_root: &Path,
+    output_root: &Path,
+    paths: &[PathBuf],
+) -> Result<Vec<(PathBuf, String)>, WorkSplitError> {
+    let mut contents = Vec::new();
+    for path in paths {
+        let staged = output_root.join(path);
+        let source = if staged.exists() { staged } else { project_root.join(path) };
+        let content = fs::read_to_string(source)?;
+        contents.push((path.clone(), content));
+    }
+    Ok(contents)
+}
+
+/// Let the user choose on the terminal where an edit with several fuzzy
+/// matches applies; without a terminal the edit fails. Jobs running in
+/// parallel ask one at a time.
+fn ask_fuzzy_pick(content: &str, edit: &EditInstruction, candidates: &[(usize, usize, String)]) -> FuzzyPick {
+    use dialoguer::{theme::ColorfulTheme, Select};
+    use std::io::IsTerminal;
+    use std::sync::Mutex;
+
+    static ASKING: Mutex<()> = Mutex::new(());
+
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        return FuzzyPick::Fail;
+    }
+    let _asking = ASKING.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    eprintln!(
+        "\nThe FIND text for {} matches {} places only after whitespace normalization:\n{}",
+        edit.file_path.display(),
+        candidates.len(),
+        edit.find
+    );
+    let mut items = Vec::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        let line = content[..candidate.0].matches('\n').count() + 1;
+        eprintln!("\n[{}] line {}:\n{}", index + 1, line, fuzzy_candidate_preview(content, candidate, 3));
+        items.push(format!("[{}] line {}", index + 1, line));
+    }
+    items.push("All of them".to_string());
+    items.push("None (fail the edit)".to_string());
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Apply the edit at")
+        .items(&items)
+        .default(0)
+        .interact();
+    match selection {
+        Ok(index) if index < candidates.len() => FuzzyPick::One(index),
+        Ok(index) if index == candidates.len() => FuzzyPick::All,
+        _ => FuzzyPick::Fail,
+    }
+}
+
+/// Applies edit responses and collects results across one or more prompts
+#[derive(Default)]
+struct EditAccumulator {
+    fuzzy_match: FuzzyMatchPolicy,
+    regex_edits: bool,
+    injection_check: InjectionPolicy,
+    generated_files: Vec<(PathBuf, String)>,
+    output_paths: Vec<PathBuf>,
+    total_lines: usize,
+    edit_count: usize,
+    partial_state: PartialEditState,
+    failed_edits: Vec<FailedEdit>,
+}
+
+impl EditAccumulator {
+    fn new(config: &Config) -> Self {
+        Self {
+            fuzzy_match: config.behavior.fuzzy_match,
+            regex_edits: config.behavior.regex_edits,
+            injection_check: config.safety.injection_check,
+            ..Default::default()
+        }
+    }
+
+    /// Apply the edits in `response` to `files` and write changed files under `output_root`
+    fn apply(
+        &mut self,
+        ollama: &OllamaClient,
+        files: &[(PathBuf, String)],
+        response: &str,
+        output_root: &Path,
+    ) -> Result<(), WorkSplitError> {
+        let parsed_edits = parse_edit_instructions(response, self.regex_edits);
+        self.edit_count += parsed_edits.edits.len();
+
+        for (path, original_content) in files {
+            let file_edits: Vec<&EditInstruction> = parsed_edits.edits_for_file(path);
+            if file_edits.is_empty() { continue; }
+
+            let mut current_content = original_content.clone();
+            let mut file_edits_applied = 0;
+
+            for edit in &file_edits {
+                let find_preview = edit.find.chars().take(50).collect::<String>();
+                let policy = self.fuzzy_match;
+                let mut pick = |content: &str, edit: &EditInstruction, candidates: &[(usize, usize, String)]| {
+                    match policy {
+                        FuzzyMatchPolicy::First => FuzzyPick::One(0),
+                        FuzzyMatchPolicy::Fail => FuzzyPick::Fail,
+                        FuzzyMatchPolicy::All => FuzzyPick::All,
+                        FuzzyMatchPolicy::Ask => ask_fuzzy_pick(content, edit, candidates),
+                    }
+                };
+                match apply_edit_with(&current_content, edit, &mut pick) {
+                    Ok(edited) => {
+                        current_content = edited;
+                        file_edits_applied += 1;
+                        self.partial_state.add_successful_edit(edit.file_path.display().to_string(), find_preview);
+                    }
+                    Err(e) => {
+                        // Collect failed edit with fuzzy match hint
+                        let fuzzy_hint = find_fuzzy_match(&current_content, &edit.find)
+                            .map(|(start, _end, _matched)| start);
+
+                        // Keep the full edit so `run --continue` can re-prompt with it
+                        self.partial_state.failed_edits.push(RecordedFailedEdit {
+                            file_path: edit.file_path.display().to_string(),
+                            find_preview: find_preview.clone(),
+                            reason: e.clone(),
+                            suggested_line: fuzzy_hint,
+                            find: edit.find.clone(),
+                            replace: edit.replace.clone(),
+                            regex: edit.regex,
+                            item: edit.item.map(|op| op.as_str().to_string()),
+                        });
+                        self.failed_edits.push(FailedEdit {
+                            file_path: edit.file_path.clone(),
+                            find: edit.find.clone(),
+                            find_preview,
+                            reason: e,
+                            suggested_line: fuzzy_hint,
+                        });
+                    }
                 }
-                Err(e) => {
-                    // Collect failed edit with fuzzy match hint
-                    let find_preview = edit.find.chars().take(50).collect::<String>();
-                    let fuzzy_hint = if let Some((start, _end, _matched)) = find_fuzzy_match(&current_content, &edit.find) {
-                        Some(start)
-                    } else {
-                        None
-                    };
-                    
-                    failed_edits.push(FailedEdit {
-                        file_path: edit.file_path.clone(),
-                        find: edit.find.clone(),
-                        find_preview,
-                        reason: e,
-                        suggested_line: fuzzy_hint,
-                    });
-                    
-                    // Add to partial state
-                    partial_state.add_failed_edit(edit.file_path.display().to_string(), edit.find.clone());
+            }
+
+            if file_edits_applied > 0 {
+                self.total_lines += crate::core::count_lines(&current_content);
+                let full_path = output_root.join(path);
+                injection::check_write(self.injection_check, output_root, &full_path, &current_content)?;
+                if let Some(parent) = full_path.parent() {
+                    fs::create_dir_all(parent)?;
                 }
+                write_atomic(&full_path, &current_content)?;
+                ollama.record_write(&full_path, &current_content);
+                self.generated_files.push((path.clone(), current_content));
+                self.output_paths.push(full_path);
             }
         }
-        
-        if file_edits_applied > 0 {
-            total_lines += crate::core::count_lines(&current_content);
-            let full_path = project_root.join(path);
-            fs::write(&full_path, &current_content)?;
-            generated_files.push((path.clone(), current_content));
-            full_output_paths.push(full_path);
-        }
+        Ok(())
     }
-    
-    if generated_files.is_empty() {
-        return Err(WorkSplitError::EditFailed("Edit mode produced no edits".to_string()));
+
+    fn into_result(self) -> Result<EditModeResult, WorkSplitError> {
+        if self.edit_count == 0 {
+            return Err(WorkSplitError::EmptyExtraction("Edit response contained no FILE/FIND/REPLACE blocks".to_string()));
+        }
+        if self.generated_files.is_empty() {
+            return Err(WorkSplitError::EditFailed("Edit mode produced no edits".to_string()));
+        }
+
+        let suggestions = generate_suggestions(&self.failed_edits, self.edit_count);
+
+        Ok(EditModeResult {
+            generated_files: self.generated_files,
+            output_paths: self.output_paths,
+            total_lines: self.total_lines,
+            partial_state: if self.failed_edits.is_empty() {
+                None
+            } else {
+                Some(self.partial_state)
+            },
+            suggestions,
+        })
     }
-    
-    let suggestions = generate_suggestions(&failed_edits, parsed_edits.edits.len());
-    
-    Ok(EditModeResult {
-        generated_files,
-        output_paths: full_output_paths,
-        total_lines,
-        partial_state: if failed_edits.is_empty() {
-            None
-        } else {
-            Some(partial_state)
-        },
-        suggestions,
-    })
 }
 
 /// Failed edit with fuzzy match hint
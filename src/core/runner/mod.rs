@@ -1,32 +1,155 @@
 // Core orchestration - the main Runner struct and run methods
 
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Arc;
 use tracing::{error, info, warn};
 
+use crate::core::atomic_write::write_atomic;
+use crate::core::postprocess::postprocess_with_env;
+use crate::core::budget::RunBudget;
+use crate::core::build_output::files_in_build_output;
+use crate::core::candidates::{best_candidate, save_candidates, Candidate};
+use crate::core::checkers;
+use crate::core::ci;
+use crate::core::context::{context_key, JobContext};
+#[cfg(feature = "embeddings")]
+use crate::core::embeddings;
+use crate::core::external_verify::ExternalVerifier;
+use crate::core::failure_artifacts::save_responses;
+use crate::core::feedback::FeedbackRules;
+use crate::core::history::{self, HistoryEvent, JobRecord};
+use crate::core::module_index;
+use crate::core::notify::Notifier;
+use crate::core::status_webhook::StatusWebhook;
+use crate::core::injection;
+use crate::core::output_size::{find_oversized, oversized_message, split_job, split_job_id, OversizedOutput};
+use crate::core::offline;
+use crate::core::output_paths::{check_protected_path, check_write_path, touched_files, written_files};
+use crate::core::progress::{FileProgress, RunProgress};
+use crate::core::prompt_templates::PromptTemplates;
+use crate::core::freeze::FrozenManifest;
+use crate::core::provenance::ProvenanceManifest;
+use crate::core::retry_policy::RequestRetry;
+use crate::core::staging::StagingArea;
+use crate::core::timings::{Phase, PhaseClock, PhaseTimings};
+use crate::core::token_budget;
 use crate::core::{
-    assemble_creation_prompt, assemble_test_prompt, assemble_sequential_split_prompt,
-    count_lines, extract_code, extract_code_files, JobsManager, OllamaClient, StatusManager,
-    SYSTEM_PROMPT_CREATE, SYSTEM_PROMPT_TEST,
+    assemble_creation_prompt, assemble_report_prompt, assemble_test_prompt, assemble_sequential_split_prompt,
+    combine_file_verdicts, count_lines, extract_code, extract_code_files, extract_report, majority_decided, majority_verdict, ChatMessage,
+    JobsManager, OllamaClient, StatusManager, VerificationResult, SYSTEM_PROMPT_CREATE, SYSTEM_PROMPT_REPORT, SYSTEM_PROMPT_TEST,
+};
+use crate::error::{OllamaError, WorkSplitError};
+use crate::models::{
+    BuildTarget, Config, ErrorType, FailureKind, InjectionPolicy, JobStatus, JobStatusEntry, Job, OversizedOutputPolicy, OwnedOutputPolicy, PartialEditState, RetryStep, RubricScores,
 };
-use crate::error::WorkSplitError;
-use crate::models::{Config, ErrorType, JobStatus, Job};
 
 mod edit;
+mod prompt;
 mod sequential;
+mod split;
 mod verify;
 
+pub use prompt::{AssembledPrompt, PromptPhase};
+
+/// Lines of build, lint or test output included in the verification prompt, per command
+const MAX_BUILD_OUTPUT_LINES: usize = 200;
+
+/// Failure kind for a verification verdict that didn't pass
+/// Files in a replace mode answer; an unnamed code block is the job's output
+fn files_in_response(response: &str, default_output_path: &Path) -> Vec<(PathBuf, String)> {
+    extract_code_files(response)
+        .into_iter()
+        .map(|file| (file.path.unwrap_or_else(|| default_output_path.to_path_buf()), file.content))
+        .collect()
+}
+
+fn verification_failure(result: VerificationResult) -> Option<FailureKind> {
+    match result {
+        VerificationResult::FailHard => Some(FailureKind::VerificationHard),
+        VerificationResult::FailSoft => Some(FailureKind::VerificationSoft),
+        VerificationResult::Pass | VerificationResult::PassWithWarnings => None,
+    }
+}
+
+/// A command's output for the verification prompt, under the command line
+/// and capped at `MAX_BUILD_OUTPUT_LINES`
+fn command_section(cmd: &str, output: &str, success: bool) -> String {
+    let mut lines: Vec<&str> = output.lines().collect();
+    let omitted = lines.len().saturating_sub(MAX_BUILD_OUTPUT_LINES);
+    lines.truncate(MAX_BUILD_OUTPUT_LINES);
+    let mut text = format!("$ {}{}\n", cmd, if success { "" } else { "  (failed)" });
+    if lines.is_empty() {
+        text.push_str("(no output)\n");
+    }
+    for line in lines {
+        text.push_str(line);
+        text.push('\n');
+    }
+    if omitted > 0 {
+        text.push_str(&format!("[... {} more lines omitted ...]\n", omitted));
+    }
+    text
+}
+
 /// Job runner - orchestrates the creation and verification workflow
 pub struct Runner {
     config: Config,
     jobs_manager: JobsManager,
     status_manager: StatusManager,
     ollama: OllamaClient,
+    /// Prompt templates, with the project's `jobs/_templates/` overrides
+    prompt_templates: PromptTemplates,
+    /// External verification service, if configured
+    external_verifier: Option<ExternalVerifier>,
+    /// Run and job failure notifications, if configured
+    notifier: Option<Notifier>,
+    /// Job status transitions posted as they happen (`[status_webhook]`)
+    status_webhook: Option<StatusWebhook>,
+    /// Staging area receiving all outputs in read-only mode
+    staging: Option<StagingArea>,
     project_root: PathBuf,
     /// Track files modified during current run session
     modified_files: Vec<PathBuf>,
+    /// Job that wrote each output file during the current run session
+    written_by: HashMap<PathBuf, String>,
+    /// Outputs of the current job already checked against their owners
+    ownership_checked: HashSet<PathBuf>,
+    /// Overwrite files owned by other passed jobs and frozen files
+    /// (`run --force`)
+    force: bool,
+    /// Job being processed
+    current_job: Option<String>,
+    /// `env` of the job being processed, for its commands
+    job_env: BTreeMap<String, String>,
+    /// Cut the job's context down ([`JobContext::shrink`]) for the retry
+    /// after the prompt overflowed the model's context window
+    shrink_context: bool,
+    /// Created files of the job over `[limits] max_output_lines`, split
+    /// by a follow-up job once it passes
+    oversized_outputs: Vec<OversizedOutput>,
+    /// Results of follow-up split jobs, for the run summary
+    split_results: Vec<JobResult>,
+    /// Limits for `run_all` / `run_batch`
+    budget: RunBudget,
+    /// Draw progress bars during `run_all` / `run_batch`
+    progress_bars: bool,
+    /// Bars of the run in progress
+    progress: Option<RunProgress>,
+    /// Fold each job's output into a GitHub Actions group and annotate its
+    /// files (`run --ci`)
+    ci_annotations: bool,
+    /// Phase times of the job in progress
+    phase_clock: PhaseClock,
+    /// Verification feedback not worth a retry (`[verification]`)
+    feedback_rules: FeedbackRules,
+    /// Concurrency of the `run_batch` in progress (0 = unlimited); files
+    /// verified one at a time are verified in parallel during a batch
+    batch_concurrency: Option<usize>,
 }
 
 /// Result of running a job
@@ -41,6 +164,12 @@ pub struct JobResult {
     pub test_lines: Option<usize>,
     pub retry_attempted: bool,
     pub implicit_context_files: Vec<PathBuf>,
+    /// Why the job failed or is partial
+    pub failure: Option<FailureKind>,
+    /// Model requests that failed and were sent again (`[ollama.retry]`)
+    pub request_retries: Vec<RequestRetry>,
+    /// Time spent in each phase of the job
+    pub phase_timings: PhaseTimings,
 }
 
 impl JobResult {
@@ -57,28 +186,201 @@ pub struct RunSummary {
     pub failed: usize,
     pub skipped: usize,
     pub results: Vec<JobResult>,
+    /// Jobs not started because the run stopped early
+    pub skipped_jobs: Vec<String>,
+    /// Why the run stopped early, if a run budget was exhausted
+    pub budget_exhausted: Option<String>,
+}
+
+impl RunSummary {
+    /// Summary of a run of one job
+    pub fn single(result: JobResult) -> Self {
+        let mut summary = Self { processed: 1, ..Default::default() };
+        match result.status {
+            JobStatus::Pass => summary.passed = 1,
+            JobStatus::Fail => summary.failed = 1,
+            _ => {}
+        }
+        summary.results.push(result);
+        summary
+    }
+
+    /// Count a processed job's result
+    fn add(&mut self, result: JobResult) {
+        self.processed += 1;
+        match result.status {
+            JobStatus::Pass => self.passed += 1,
+            JobStatus::Fail => self.failed += 1,
+            _ => {}
+        }
+        self.results.push(result);
+    }
+
+    /// Phase times summed over all jobs
+    pub fn phase_totals(&self) -> PhaseTimings {
+        let mut totals = PhaseTimings::default();
+        for result in &self.results {
+            totals.add(&result.phase_timings);
+        }
+        totals
+    }
+
+    /// Record every job in `queue` that didn't get a result as skipped
+    fn mark_unprocessed_skipped<'a>(&mut self, queue: impl IntoIterator<Item = &'a String>) {
+        self.skipped_jobs = queue
+            .into_iter()
+            .filter(|id| !self.results.iter().any(|r| &r.job_id == *id))
+            .cloned()
+            .collect();
+        self.skipped = self.skipped_jobs.len();
+    }
+}
+
+/// Outcome of `Runner::fix_build`
+#[derive(Debug, Default)]
+pub struct BuildFixReport {
+    /// Build command that ran
+    pub command: String,
+    /// Whether the last build passed
+    pub passed: bool,
+    /// Auto-fix attempts made (0 if the build already passed)
+    pub attempts: u8,
+    /// Files sent to the model, relative to the project root
+    pub files: Vec<PathBuf>,
+    /// Output of the last build
+    pub output: String,
 }
 
 impl Runner {
     pub fn new(config: Config, project_root: PathBuf) -> Result<Self, WorkSplitError> {
-        let jobs_manager = JobsManager::new(project_root.clone(), config.limits.clone());
-        let status_manager = StatusManager::new(jobs_manager.jobs_dir())?;
+        let mut jobs_manager = JobsManager::new(project_root.to_path_buf(), config.limits.clone());
+        jobs_manager.set_substitute_missing_context(config.behavior.substitute_missing_context);
+        if config.safety.offline {
+            offline::check_config(&config)?;
+            // Fail before any job runs rather than midway through the queue
+            for job_id in jobs_manager.discover_jobs()? {
+                if let Ok(job) = jobs_manager.parse_job(&job_id) {
+                    offline::check_job(&job)?;
+                }
+            }
+        }
+        let mut status_manager = StatusManager::with_backend(jobs_manager.jobs_dir(), config.behavior.status_backend)?;
+        let status_webhook = StatusWebhook::spawn(&config.status_webhook, &project_root);
+        if let Some(webhook) = &status_webhook {
+            status_manager.set_status_webhook(webhook.clone());
+        }
         let ollama = OllamaClient::new(config.ollama.clone())?;
+        let prompt_templates = PromptTemplates::load(jobs_manager.jobs_dir())?;
+        let external_verifier = ExternalVerifier::from_config(&config.external_verify)?;
+        let notifier = Notifier::from_config(&config.notify, &project_root);
+        let feedback_rules = FeedbackRules::from_config(&config.verification)?;
+        let staging = config.safety.read_only.then(|| StagingArea::new(jobs_manager.jobs_dir()));
 
         Ok(Self {
             config,
             jobs_manager,
             status_manager,
             ollama,
+            prompt_templates,
+            external_verifier,
+            notifier,
+            status_webhook,
+            staging,
             project_root,
             modified_files: Vec::new(),
+            written_by: HashMap::new(),
+            ownership_checked: HashSet::new(),
+            force: false,
+            current_job: None,
+            job_env: BTreeMap::new(),
+            shrink_context: false,
+            oversized_outputs: Vec::new(),
+            split_results: Vec::new(),
+            budget: RunBudget::default(),
+            progress_bars: false,
+            progress: None,
+            ci_annotations: false,
+            phase_clock: PhaseClock::default(),
+            feedback_rules,
+            batch_concurrency: None,
         })
     }
 
-    pub async fn run_all(&mut self, resume_stuck: bool, stop_on_fail: bool, include_ran: bool) -> Result<RunSummary, WorkSplitError> {
-        self.modified_files.clear();
+    /// Answer model requests from fixture files instead of Ollama (`run --mock`)
+    pub fn set_mock_fixtures(&mut self, fixtures: crate::core::mock_fixtures::MockFixtures) {
+        self.ollama.set_mock_fixtures(fixtures);
+    }
+
+    /// Record every model request and response of this run
+    pub fn set_recorder(&mut self, recorder: crate::core::recording::Recorder) {
+        self.ollama.set_recorder(recorder);
+    }
+
+    /// Copy each job's token stream to its own file
+    pub fn set_stream_log(&mut self, log: crate::core::stream_log::StreamLog) {
+        self.ollama.set_stream_log(log);
+    }
+
+    /// Limit the number of jobs, wall time and tokens of `run_all` / `run_batch`
+    pub fn set_budget(&mut self, budget: RunBudget) {
+        self.budget = budget;
+    }
+
+    /// Overwrite files generated by other passed jobs and frozen files
+    /// without asking
+    pub fn set_force(&mut self, force: bool) {
+        self.force = force;
+    }
+
+    /// Show a run progress bar, and a file bar for multi-file jobs, during
+    /// `run_all` / `run_batch`
+    pub fn set_progress_bars(&mut self, enabled: bool) {
+        self.progress_bars = enabled;
+    }
+
+    /// Print GitHub Actions groups and annotations for each job
+    pub fn set_ci_annotations(&mut self, enabled: bool) {
+        self.ci_annotations = enabled;
+    }
+
+    /// LLM tokens (prompt + generated) used by this runner so far
+    pub fn tokens_used(&self) -> u64 {
+        self.ollama.tokens_used()
+    }
+
+    /// Merge status saves with other processes using the same jobs folder
+    /// (spool workers) instead of overwriting their entries
+    pub fn share_status(&mut self) {
+        self.status_manager.set_shared(true);
+    }
+
+    fn start_progress(&mut self, total_jobs: usize) {
+        if self.progress_bars {
+            self.progress = Some(RunProgress::start(total_jobs, &history::load(self.jobs_manager.jobs_dir())));
+        }
+    }
+
+    /// A bar counting the files of a multi-file job
+    fn file_progress(&self, job_id: &str, total_files: usize) -> FileProgress {
+        match &self.progress {
+            Some(progress) if total_files > 1 => progress.job_files(job_id, total_files),
+            _ => FileProgress::hidden(),
+        }
+    }
+
+    /// Register new job files in the status file, drop deleted ones and
+    /// mark drafts
+    fn sync_jobs(&mut self) -> Result<(), WorkSplitError> {
         let discovered = self.jobs_manager.discover_jobs()?;
         self.status_manager.sync_with_jobs(&discovered)?;
+        self.status_manager.sync_drafts(&self.jobs_manager.draft_jobs(&discovered))?;
+        Ok(())
+    }
+
+    pub async fn run_all(&mut self, resume_stuck: bool, stop_on_fail: bool, include_ran: bool) -> Result<RunSummary, WorkSplitError> {
+        self.modified_files.clear();
+        self.written_by.clear();
+        self.sync_jobs()?;
 
         let stuck = self.status_manager.get_stuck_jobs();
         if !stuck.is_empty() && !resume_stuck {
@@ -103,7 +405,11 @@ impl Runner {
         }
 
         if resume_stuck {
-            jobs_to_run.extend(stuck.iter().map(|e| e.id.clone()));
+            jobs_to_run.extend(stuck.iter().filter(|e| !e.draft).map(|e| e.id.clone()));
+        }
+        let drafts = self.status_manager.get_summary().draft;
+        if drafts > 0 {
+            info!("Skipping {} draft job(s)", drafts);
         }
         jobs_to_run.sort();
 
@@ -123,6 +429,7 @@ impl Runner {
                 return Err(WorkSplitError::Ollama(e));
             }
         }
+        self.warm_up().await;
 
         let create_prompt = self.jobs_manager.load_create_prompt()?;
         let verify_prompt = self.jobs_manager.load_verify_prompt()?;
@@ -133,19 +440,26 @@ impl Runner {
 
         let mut summary = RunSummary::default();
         let mut stopped_early = false;
-
-        for job_id in jobs_to_run {
+        self.record_run_start(jobs_to_run.len());
+        self.start_progress(jobs_to_run.len());
+        let budget = self.budget.start(self.ollama.tokens_used());
+
+        for job_id in &jobs_to_run {
+            if let Some(reason) = budget.exhausted(summary.processed, self.ollama.tokens_used()) {
+                warn!("Stopping run: {}", reason);
+                summary.budget_exhausted = Some(reason);
+                stopped_early = true;
+                break;
+            }
+            let job_id = job_id.clone();
             match self.run_job(&job_id, &create_prompt, &verify_prompt, test_prompt.as_deref(),
                               &edit_prompt, &verify_edit_prompt, split_prompt.as_deref()).await {
                 Ok(result) => {
-                    summary.processed += 1;
                     let job_failed = result.status == JobStatus::Fail;
-                    match result.status {
-                        JobStatus::Pass => summary.passed += 1,
-                        JobStatus::Fail => summary.failed += 1,
-                        _ => {}
+                    summary.add(result);
+                    for split in std::mem::take(&mut self.split_results) {
+                        summary.add(split);
                     }
-                    summary.results.push(result);
                     if stop_on_fail && job_failed {
                         info!("Stopping due to job failure (--stop-on-fail)");
                         stopped_early = true;
@@ -154,14 +468,8 @@ impl Runner {
                 }
                 Err(e) => {
                     error!("Job '{}' failed with error: {}", job_id, e);
-                    summary.processed += 1;
-                    summary.failed += 1;
-                    summary.results.push(JobResult {
-                        job_id: job_id.clone(), status: JobStatus::Fail,
-                        error: Some(e.to_string()), output_paths: Vec::new(),
-                        output_lines: None, test_path: None, test_lines: None,
-                        retry_attempted: false, implicit_context_files: Vec::new(),
-                    });
+                    let result = self.error_result(&job_id, &e);
+                    summary.add(result);
                     let _ = self.status_manager.set_failed(&job_id, e.to_string());
                     if stop_on_fail {
                         stopped_early = true;
@@ -171,12 +479,17 @@ impl Runner {
             }
         }
 
+        self.progress = None;
         if stopped_early {
-            summary.skipped = total_jobs - summary.processed;
+            summary.mark_unprocessed_skipped(&jobs_to_run);
         }
 
         info!("Run complete: {} passed, {} failed, {} remaining",
             summary.passed, summary.failed, self.status_manager.get_ready_jobs().len());
+        self.flush_status_webhook().await;
+        if let Some(notifier) = &self.notifier {
+            notifier.run_finished(&summary).await;
+        }
         Ok(summary)
     }
 
@@ -190,8 +503,8 @@ impl Runner {
         include_ran: bool,
     ) -> Result<RunSummary, WorkSplitError> {
         self.modified_files.clear();
-        let discovered = self.jobs_manager.discover_jobs()?;
-        self.status_manager.sync_with_jobs(&discovered)?;
+        self.written_by.clear();
+        self.sync_jobs()?;
 
         // Collect jobs to run
         let stuck = self.status_manager.get_stuck_jobs();
@@ -216,7 +529,11 @@ impl Runner {
         }
 
         if resume_stuck {
-            jobs_to_run.extend(stuck.iter().map(|e| e.id.clone()));
+            jobs_to_run.extend(stuck.iter().filter(|e| !e.draft).map(|e| e.id.clone()));
+        }
+        let drafts = self.status_manager.get_summary().draft;
+        if drafts > 0 {
+            info!("Skipping {} draft job(s)", drafts);
         }
         jobs_to_run.sort();
 
@@ -253,6 +570,7 @@ impl Runner {
             Ok(false) => warn!("Ollama may not be fully ready"),
             Err(e) => return Err(WorkSplitError::Ollama(e)),
         }
+        self.warm_up().await;
 
         // Load prompts once
         let create_prompt = Arc::new(self.jobs_manager.load_create_prompt()?);
@@ -264,12 +582,16 @@ impl Runner {
 
         let mut summary = RunSummary::default();
         let mut stopped_early = false;
+        self.record_run_start(jobs_to_run.len());
+        self.start_progress(jobs_to_run.len());
+        let budget = self.budget.start(self.ollama.tokens_used());
+
+        self.batch_concurrency = Some(max_concurrent);
 
         // Process each group
         for (group_idx, group) in groups.iter().enumerate() {
             if stopped_early {
-                summary.skipped += group.len() as usize;
-                continue;
+                break;
             }
 
             info!("=== Batch Group {}/{}: {} jobs ===", group_idx + 1, groups.len(), group.len());
@@ -288,6 +610,12 @@ impl Runner {
                 // Note: In a full implementation, you'd want to refactor Runner
                 // to be more parallel-friendly. For now, run sequentially within group.
                 for job_id in chunk {
+                    if let Some(reason) = budget.exhausted(summary.processed, self.ollama.tokens_used()) {
+                        warn!("Stopping batch: {}", reason);
+                        summary.budget_exhausted = Some(reason);
+                        stopped_early = true;
+                        break;
+                    }
                     match self.run_job(
                         job_id,
                         &create_prompt,
@@ -298,14 +626,11 @@ impl Runner {
                         split_prompt.as_ref().as_deref(),
                     ).await {
                         Ok(result) => {
-                            summary.processed += 1;
                             let job_failed = result.status == JobStatus::Fail;
-                            match result.status {
-                                JobStatus::Pass => summary.passed += 1,
-                                JobStatus::Fail => summary.failed += 1,
-                                _ => {}
+                            summary.add(result);
+                            for split in std::mem::take(&mut self.split_results) {
+                                summary.add(split);
                             }
-                            summary.results.push(result);
                             if stop_on_fail && job_failed {
                                 info!("Stopping batch due to job failure (--stop-on-fail)");
                                 stopped_early = true;
@@ -314,19 +639,8 @@ impl Runner {
                         }
                         Err(e) => {
                             error!("Job '{}' failed with error: {}", job_id, e);
-                            summary.processed += 1;
-                            summary.failed += 1;
-                            summary.results.push(JobResult {
-                                job_id: job_id.to_string(),
-                                status: JobStatus::Fail,
-                                error: Some(e.to_string()),
-                                output_paths: Vec::new(),
-                                output_lines: None,
-                                test_path: None,
-                                test_lines: None,
-                                retry_attempted: false,
-                                implicit_context_files: Vec::new(),
-                            });
+                            let result = self.error_result(job_id, &e);
+                            summary.add(result);
                             let _ = self.status_manager.set_failed(job_id, e.to_string());
                             if stop_on_fail {
                                 stopped_early = true;
@@ -338,21 +652,58 @@ impl Runner {
             }
         }
 
+        self.batch_concurrency = None;
+        self.progress = None;
         if stopped_early {
-            let total: usize = groups.iter().map(|g| g.len()).sum::<usize>();
-            summary.skipped = total - summary.processed;
+            summary.mark_unprocessed_skipped(groups.iter().flatten());
         }
 
         info!("Batch complete: {} passed, {} failed, {} skipped",
             summary.passed, summary.failed, summary.skipped);
+        self.flush_status_webhook().await;
+        if let Some(notifier) = &self.notifier {
+            notifier.run_finished(&summary).await;
+        }
         Ok(summary)
     }
 
+    /// Load the model before the first job (`[ollama] warmup`), so its load
+    /// time doesn't look like a stalled job. A failed warm-up is only logged:
+    /// the first job reports the real problem.
+    pub async fn warm_up(&self) {
+        if !self.config.ollama.warmup {
+            return;
+        }
+        info!("Warming up model '{}'...", self.config.ollama.model);
+        match self.ollama.warm_up().await {
+            Ok(warm_up) => info!("{}", crate::core::describe_warm_up(&warm_up)),
+            Err(e) => warn!("Model warm-up failed: {}", e),
+        }
+    }
+
     pub async fn run_single(&mut self, job_id: &str) -> Result<JobResult, WorkSplitError> {
         self.modified_files.clear();
-        let discovered = self.jobs_manager.discover_jobs()?;
-        self.status_manager.sync_with_jobs(&discovered)?;
+        self.written_by.clear();
+        self.sync_jobs()?;
+        if self.status_manager.get(job_id).is_some_and(|e| e.draft) {
+            return Err(WorkSplitError::JobError(format!(
+                "Job '{}' is a draft; remove `draft: true` from its frontmatter to run it", job_id)));
+        }
+        self.run_one(job_id).await
+    }
 
+    /// Run a job file that job discovery skips (`worksplit oneshot` writes
+    /// `jobs/_oneshot_*.md`). The job has a status entry only while it runs.
+    pub async fn run_oneshot(&mut self, job_id: &str) -> Result<JobResult, WorkSplitError> {
+        self.modified_files.clear();
+        self.written_by.clear();
+        self.status_manager.import_entries(vec![JobStatusEntry::new(job_id.to_string())])?;
+        let result = self.run_one(job_id).await;
+        self.status_manager.remove_job(job_id)?;
+        result
+    }
+
+    async fn run_one(&mut self, job_id: &str) -> Result<JobResult, WorkSplitError> {
         let create_prompt = self.jobs_manager.load_create_prompt()?;
         let verify_prompt = self.jobs_manager.load_verify_prompt()?;
         let test_prompt = self.jobs_manager.load_test_prompt().ok();
@@ -360,16 +711,166 @@ impl Runner {
         let verify_edit_prompt = self.jobs_manager.load_verify_edit_prompt()?;
         let split_prompt = self.jobs_manager.load_split_prompt().ok();
 
-        self.run_job(job_id, &create_prompt, &verify_prompt, test_prompt.as_deref(),
-                    &edit_prompt, &verify_edit_prompt, split_prompt.as_deref()).await
+        self.record_run_start(1);
+        let result = self.run_job(job_id, &create_prompt, &verify_prompt, test_prompt.as_deref(),
+                    &edit_prompt, &verify_edit_prompt, split_prompt.as_deref()).await;
+        // Follow-up split jobs report through their own status
+        self.split_results.clear();
+        result
     }
 
-    /// Run build command and return (success, output)
-    fn run_build_command(&self, cmd: &str) -> Result<(bool, String), WorkSplitError> {
+    /// Re-prompt for only the failed edits of a partial edit job (`run --continue`)
+    ///
+    /// Edits that apply are written and recorded as successful. If some still
+    /// fail, the job stays partial with the new failures; otherwise the
+    /// edited files are verified and the job passes or fails as usual.
+    pub async fn continue_job(&mut self, job_id: &str) -> Result<JobResult, WorkSplitError> {
+        let from = self.status_manager.get(job_id).map_or(JobStatus::Created, |e| e.status);
+        let (started_at, tokens_before) = (Utc::now(), self.ollama.tokens_used());
+        self.record_run_start(1);
+        self.ollama.take_request_retries();
+        self.ollama.take_responses();
+        self.ollama.take_writes();
+        self.phase_clock.take();
+        self.ci_job_started(job_id);
+        let mut result = self.continue_job_inner(job_id).await;
+        self.job_env.clear();
+        if let Ok(r) = &mut result {
+            r.request_retries = self.ollama.take_request_retries();
+            r.phase_timings = self.phase_clock.take();
+        }
+        self.save_failure_artifacts(job_id, &mut result);
+        self.record_job(job_id, from, started_at, tokens_before, &result);
+        self.record_outputs(job_id, &result);
+        self.record_provenance(job_id);
+        self.ci_job_finished(job_id, &result);
+        self.flush_status_webhook().await;
+        result
+    }
+
+    async fn continue_job_inner(&mut self, job_id: &str) -> Result<JobResult, WorkSplitError> {
+        self.modified_files.clear();
+        self.written_by.clear();
+        self.sync_jobs()?;
+
+        let job = self.jobs_manager.parse_job_for_prompt(job_id)?;
+        self.screen_instructions(&job)?;
+        self.check_frozen_targets(&job)?;
+        self.job_env = job.metadata.env.clone();
+        let previous = self.status_manager.get(job_id)
+            .filter(|e| e.status == JobStatus::Partial)
+            .and_then(|e| e.partial_state.clone())
+            .filter(|s| s.has_failures())
+            .ok_or_else(|| WorkSplitError::JobError(format!(
+                "Job '{}' has no failed edits to continue (only partial edit jobs can be continued)",
+                job_id
+            )))?;
+
+        let edit_prompt = self.jobs_manager.generation_prompt(&self.jobs_manager.load_edit_prompt()?, &job)?;
+        let edit_prompt = self.jobs_manager.prompt_with_conventions(&edit_prompt, &job)?;
+        self.ollama.set_current_job(job_id);
+        self.phase_clock.start(Phase::Generation);
+        let result = edit::continue_edit_mode(
+            &self.ollama,
+            &self.prompt_templates,
+            &self.project_root,
+            &self.output_root(),
+            &self.config,
+            &job,
+            &previous.failed_edits,
+            &edit_prompt,
+        ).await?;
+        self.phase_clock.stop();
+        self.modified_files.extend(result.output_paths.iter().cloned());
+
+        let mut final_status = JobStatus::Pass;
+        let mut final_error = None;
+        let mut failure = None;
+
+        if let Some(remaining) = result.partial_state {
+            // Keep earlier successes; only the edits that still fail are retried next time
+            let mut state = PartialEditState {
+                successful_edits: previous.successful_edits,
+                failed_edits: remaining.failed_edits,
+            };
+            state.successful_edits.extend(remaining.successful_edits);
+            info!("{} edit(s) still failing", state.failed_edits.len());
+            self.status_manager.set_partial(job_id, state)?;
+            final_status = JobStatus::Partial;
+            failure = Some(FailureKind::EditNoMatch);
+        } else {
+            self.status_manager.clear_partial_state(job_id)?;
+            if job.metadata.verify {
+                self.status_manager.update_status(job_id, JobStatus::PendingVerification)?;
+                self.phase_clock.start(Phase::Verification);
+                let verify_edit_prompt = self.jobs_manager.verify_prompt_for_level(
+                    &self.jobs_manager.load_verify_edit_prompt()?, job.metadata.verification, true)?;
+                let context_files = self.jobs_manager.load_context_files(&job)?;
+                let (local, rubric) = self.verify_code(
+                    &verify_edit_prompt,
+                    &context_files,
+                    &result.generated_files,
+                    &job.instructions,
+                    None,
+                ).await?;
+                self.status_manager.set_rubric(job_id, rubric)?;
+                let (verdict, error) = verify::apply_verification_level(job.metadata.verification,
+                    verify::run_external_verification(
+                        self.external_verifier.as_ref(),
+                        job_id,
+                        &job.instructions,
+                        &result.generated_files,
+                        local,
+                    ).await);
+                self.phase_clock.stop();
+                final_status = verdict.to_job_status();
+                final_error = error;
+                failure = verification_failure(verdict);
+            }
+            match &final_error {
+                Some(msg) => self.status_manager.set_failed(job_id, msg.clone())?,
+                None => self.status_manager.update_status(job_id, final_status)?,
+            }
+        }
+
+        if let Some(staging) = &self.staging {
+            // Files staged by the original run stay part of the job
+            let mut staged_files = staging.load_manifest()?.into_iter()
+                .find(|j| j.job_id == job_id)
+                .map(|j| j.files)
+                .unwrap_or_default();
+            for file in result.output_paths.iter()
+                .filter_map(|p| p.strip_prefix(staging.root()).ok().map(Path::to_path_buf))
+            {
+                if !staged_files.contains(&file) {
+                    staged_files.push(file);
+                }
+            }
+            staging.record(job_id, final_status, staged_files)?;
+        }
+
+        if let Some(kind) = failure {
+            self.status_manager.set_failure(job_id, kind)?;
+        }
+
+        info!("Job '{}' continued with status: {:?}", job_id, final_status);
+        Ok(JobResult {
+            job_id: job_id.to_string(), status: final_status, error: final_error,
+            output_paths: result.output_paths, output_lines: Some(result.total_lines),
+            test_path: None, test_lines: None,
+            retry_attempted: false, implicit_context_files: Vec::new(), failure,
+            request_retries: Vec::new(), phase_timings: PhaseTimings::default(),
+        })
+    }
+
+    /// Run build command in `dir` (relative to the project root) and
+    /// return (success, output)
+    fn run_build_command(&self, cmd: &str, dir: &Path) -> Result<(bool, String), WorkSplitError> {
         let output = Command::new("sh")
             .arg("-c")
             .arg(cmd)
-            .current_dir(&self.project_root)
+            .current_dir(self.project_root.join(dir))
+            .envs(&self.job_env)
             .output()?;
 
         let combined = format!(
@@ -439,18 +940,25 @@ impl Runner {
             let target_path = if let Some(ref path) = file.path {
                 self.project_root.join(path)
             } else if files.len() == 1 {
-                files[0].0.clone()
+                self.project_root.join(&files[0].0)
             } else {
                 continue;
             };
 
+            check_write_path(&self.project_root, &target_path, &self.config.safety.allowed_output_dirs)?;
+            check_protected_path(&self.project_root, &target_path, &self.config.safety.protected_paths)?;
+            injection::check_write(self.config.safety.injection_check, &self.project_root, &target_path, &file.content)?;
+            self.check_frozen(&target_path)?;
             if let Some(parent) = target_path.parent() {
                 if !parent.exists() {
                     fs::create_dir_all(parent)?;
                 }
             }
 
-            fs::write(&target_path, &file.content)?;
+            let relative = target_path.strip_prefix(&self.project_root).unwrap_or(&target_path);
+            let content = postprocess_with_env(&self.config.postprocess, &self.project_root, relative, &file.content, &self.job_env);
+            write_atomic(&target_path, &content)?;
+            self.ollama.record_write(&target_path, &content);
             info!("Wrote fixed file: {}", target_path.display());
             files_written += 1;
         }
@@ -458,21 +966,222 @@ impl Runner {
         Ok(files_written > 0)
     }
 
-    async fn verify_with_build(&self, _job: &Job, files: &[(PathBuf, String)]) -> Result<(), WorkSplitError> {
-        if !self.config.build.verify_build {
-            return Ok(());
+    /// Generate the job's `candidates`, verify each and return the answer and
+    /// files of the best one; the others are saved under `jobs/_candidates/<job>/`.
+    /// Returns no files if no candidate contained code.
+    async fn generate_candidates(
+        &self,
+        job: &Job,
+        prompt: &str,
+        verify_prompt: &str,
+        context_files: &[(PathBuf, String)],
+    ) -> Result<(String, Vec<(PathBuf, String)>), WorkSplitError> {
+        let verify_prompt = self.jobs_manager.verify_prompt_for_level(verify_prompt, job.metadata.verification, false)?;
+        let default_output_path = job.metadata.output_path();
+        let total = job.metadata.candidates;
+        let mut candidates = Vec::new();
+
+        for n in 1..=total {
+            info!("Generating candidate {}/{}", n, total);
+            let response = self.ollama.generate_with_retry(Some(SYSTEM_PROMPT_CREATE), prompt, self.config.behavior.stream_output).await?;
+            let files = files_in_response(&response, &default_output_path);
+            if files.iter().all(|(_, content)| content.trim().is_empty()) {
+                warn!("Candidate {} contained no code", n);
+                continue;
+            }
+            let ((result, reason), rubric) = self.verify_code(&verify_prompt, context_files, &files, &job.instructions, None).await?;
+            info!("Candidate {}/{}: {:?}", n, total, result);
+            candidates.push(Candidate { files, response, result, reason, rubric });
         }
 
-        let Some(ref cmd) = self.config.build.build_command else {
-            return Ok(());
+        let Some(kept) = best_candidate(&candidates) else {
+            return Ok((String::new(), Vec::new()));
         };
+        let dir = save_candidates(self.jobs_manager.jobs_dir(), &job.id, &candidates, kept)?;
+        info!("Keeping candidate {} of {}; the others are in {}", kept + 1, candidates.len(), dir.display());
+        let best = candidates.swap_remove(kept);
+        Ok((best.response, best.files))
+    }
 
+    /// Context for a prompt that also carries `files` (generated or previous
+    /// output) and the `fixed` texts, trimmed to fit the context window;
+    /// `files` are always sent whole
+    fn fit_context(&self, prompt: &str, context_files: &[(PathBuf, String)], fixed: &[&str],
+                   files: &[(PathBuf, String)]) -> Vec<(PathBuf, String)> {
+        let limit = self.config.limits.context_window_tokens;
+        let fixed_tokens = fixed.iter().map(|text| JobsManager::estimate_tokens(text)).sum::<usize>()
+            + files.iter().map(|(_, content)| JobsManager::estimate_tokens(content)).sum::<usize>();
+        let fitted = token_budget::fit_context(context_files, fixed_tokens, limit);
+        if !fitted.trimmed.is_empty() {
+            let names: Vec<String> = fitted.trimmed.iter().map(|p| p.display().to_string()).collect();
+            warn!("{} prompt exceeds the context window ({} tokens); trimmed context: {}", prompt, limit, names.join(", "));
+        }
+        if !fitted.fits {
+            warn!("{} prompt is still about {} tokens with its context removed (context window {}); \
+                the output is sent whole and may be truncated by the model", prompt, fitted.estimated, limit);
+        }
+        fitted.files
+    }
+
+    /// Verify generated code the way `[verification]` asks: a PASS/FAIL
+    /// verdict, or rubric scores (returned too) judged against the threshold
+    ///
+    /// An output of several files over `per_file_over_lines` is verified one
+    /// file at a time, each with the job's context, and the verdicts are
+    /// combined ([`combine_file_verdicts`]); the recorded scores are the
+    /// per-axis median. The files are verified in parallel during
+    /// `run_batch`, unless `verify_vote_models` switches models per vote.
+    async fn verify_code(
+        &self,
+        verify_prompt: &str,
+        context_files: &[(PathBuf, String)],
+        generated_files: &[(PathBuf, String)],
+        instructions: &str,
+        build_output: Option<&str>,
+    ) -> Result<((VerificationResult, Option<String>), Option<RubricScores>), WorkSplitError> {
+        let threshold = self.config.verification.per_file_over_lines;
+        let total_lines: usize = generated_files.iter().map(|(_, content)| count_lines(content)).sum();
+        if threshold == 0 || generated_files.len() < 2 || total_lines <= threshold {
+            return self.verify_files(verify_prompt, context_files, generated_files, instructions, build_output).await;
+        }
+
+        let concurrency = match self.batch_concurrency {
+            Some(_) if !self.config.behavior.verify_vote_models.is_empty() => 1,
+            Some(0) => generated_files.len(),
+            Some(limit) => limit,
+            None => 1,
+        };
+        info!(
+            "Output has {} lines in {} files (over {}); verifying each file on its own{}",
+            total_lines,
+            generated_files.len(),
+            threshold,
+            if concurrency > 1 { ", in parallel" } else { "" }
+        );
+        let checks: Vec<_> = generated_files
+            .iter()
+            .map(|(path, content)| {
+                let scoped = verify::file_scope_instructions(instructions, path, generated_files);
+                self.verify_file(verify_prompt, context_files, (path.clone(), content.clone()), scoped, build_output)
+            })
+            .collect();
+        let outcomes: Vec<_> = stream::iter(checks).buffered(concurrency).collect().await;
+
+        let mut verdicts = Vec::new();
+        let mut scores = Vec::new();
+        for outcome in outcomes {
+            let (path, (verdict, file_scores)) = outcome?;
+            info!("Verification of {}: {:?}", path.display(), verdict.0);
+            verdicts.push((path, verdict));
+            scores.extend(file_scores);
+        }
+        Ok((combine_file_verdicts(&verdicts), RubricScores::median(&scores)))
+    }
+
+    /// Verify one file of a larger output with its scoped instructions
+    async fn verify_file(
+        &self,
+        verify_prompt: &str,
+        context_files: &[(PathBuf, String)],
+        file: (PathBuf, String),
+        instructions: String,
+        build_output: Option<&str>,
+    ) -> Result<(PathBuf, ((VerificationResult, Option<String>), Option<RubricScores>)), WorkSplitError> {
+        let files = [file];
+        let outcome = self.verify_files(verify_prompt, context_files, &files, &instructions, build_output).await?;
+        let [(path, _)] = files;
+        Ok((path, outcome))
+    }
+
+    /// Verify `generated_files` in one prompt
+    ///
+    /// With `verify_votes` above 1 the verifiers vote and the majority wins;
+    /// the recorded scores are the per-axis median.
+    async fn verify_files(
+        &self,
+        verify_prompt: &str,
+        context_files: &[(PathBuf, String)],
+        generated_files: &[(PathBuf, String)],
+        instructions: &str,
+        build_output: Option<&str>,
+    ) -> Result<((VerificationResult, Option<String>), Option<RubricScores>), WorkSplitError> {
+        let context_files = &self.fit_context("Verification", context_files,
+            &[verify_prompt, instructions, build_output.unwrap_or_default()], generated_files);
+        let total = self.config.behavior.verify_votes.max(1);
+        let models = &self.config.behavior.verify_vote_models;
+        let mut votes = Vec::new();
+        let mut scores = Vec::new();
+
+        for vote in 0..total {
+            if !models.is_empty() {
+                let model = models[vote % models.len()].clone();
+                self.ollama.set_retry_step(Some(RetryStep { model: Some(model), temperature: None }));
+            }
+            let outcome = match self.config.verification.rubric_threshold() {
+                Some(threshold) => verify::run_rubric_verification(&self.ollama, &self.prompt_templates, verify_prompt,
+                    context_files, generated_files, instructions, build_output, threshold).await,
+                None => verify::run_verification(&self.ollama, &self.prompt_templates, verify_prompt,
+                    context_files, generated_files, instructions, build_output).await.map(|verdict| (verdict, None)),
+            };
+            self.ollama.set_retry_step(None);
+            let (verdict, vote_scores) = outcome?;
+
+            if total > 1 {
+                info!("Verification vote {}/{}: {:?}", vote + 1, total, verdict.0);
+            }
+            votes.push(verdict);
+            scores.extend(vote_scores);
+            let results: Vec<VerificationResult> = votes.iter().map(|(result, _)| *result).collect();
+            if majority_decided(&results, total) {
+                break;
+            }
+        }
+
+        Ok((majority_verdict(&votes), RubricScores::median(&scores)))
+    }
+
+    /// Run the build (and lint) commands and the `[checkers]` of the
+    /// generated files after generation, auto-fixing failures if enabled.
+    ///
+    /// Returns the diagnostics of the final, successful build plus the lint
+    /// and checker output, for the verification prompt; `None` if nothing ran.
+    async fn verify_with_build(&self, job: &Job, files: &[(PathBuf, String)]) -> Result<Option<String>, WorkSplitError> {
+        // A report changes no code, so there is nothing to build
+        if !self.config.build.verify_build || job.metadata.is_report_mode() {
+            return Ok(None);
+        }
+
+        // The build runs against the project tree, which staged outputs never touch
+        if self.staging.is_some() {
+            info!("Read-only mode: skipping build verification");
+            return Ok(None);
+        }
+
+        let mut diagnostics = Vec::new();
+
+        // Monorepo jobs build their own workspace
+        let target = self.config.build_target(job.metadata.workdir.as_deref());
+        if let Some(ref cmd) = target.build_command {
+            diagnostics.push(self.run_build_check(&target, cmd, files).await?);
+        }
+
+        let paths: Vec<PathBuf> = files.iter().map(|(p, _)| p.clone()).collect();
+        for check in checkers::file_checks(&self.config.checkers, &paths) {
+            diagnostics.push(self.run_file_check(&check).await?);
+        }
+
+        Ok((!diagnostics.is_empty()).then(|| diagnostics.join("\n")))
+    }
+
+    /// Run the build command, auto-fixing failures if enabled; returns the
+    /// build and lint diagnostics
+    async fn run_build_check(&self, target: &BuildTarget, cmd: &str, files: &[(PathBuf, String)]) -> Result<String, WorkSplitError> {
         info!("Running build verification command: {}", cmd);
 
-        let (success, build_output) = self.run_build_command(cmd)?;
+        let (success, build_output) = self.run_build_command(cmd, &target.dir)?;
 
         if success {
-            return Ok(());
+            return self.build_diagnostics(target, cmd, &build_output);
         }
 
         // Build failed - try auto-fix if enabled
@@ -484,72 +1193,505 @@ impl Runner {
 
         if !self.config.build.auto_fix {
             return Err(WorkSplitError::BuildFailed {
-                command: cmd.clone(),
+                command: cmd.to_string(),
                 output: error_context,
             });
         }
 
-        // Auto-fix loop
         let max_attempts = self.config.build.auto_fix_attempts;
-        let mut current_error = build_output;
+        let paths: Vec<PathBuf> = files.iter().map(|(p, _)| p.clone()).collect();
+        let (passed, _, current_error) = self.auto_fix_loop(target, cmd, build_output, max_attempts, ErrorType::Build, |_| paths.clone()).await?;
+        if passed {
+            return self.build_diagnostics(target, cmd, &current_error);
+        }
+
+        // All attempts exhausted
+        Err(WorkSplitError::BuildFailed {
+            command: cmd.to_string(),
+            output: format!(
+                "Build failed after {} auto-fix attempts:\n\nFiles:\n{}\n\nFinal error:\n{}",
+                max_attempts,
+                files.iter().map(|(p, _)| p.display().to_string()).collect::<Vec<_>>().join("\n"),
+                current_error
+            ),
+        })
+    }
+
+    /// Run a `[checkers]` compile check of one file from the project root,
+    /// auto-fixing just that file if enabled; returns the check's output
+    async fn run_file_check(&self, check: &checkers::FileCheck) -> Result<String, WorkSplitError> {
+        info!("Checking {}: {}", check.path.display(), check.command);
+        let (success, output) = self.run_build_command(&check.command, Path::new(""))?;
+        if success {
+            return Ok(command_section(&check.command, &output, true));
+        }
+
+        if !self.config.build.auto_fix {
+            return Err(WorkSplitError::BuildFailed {
+                command: check.command.clone(),
+                output: format!("Check of {} failed:\n{}", check.path.display(), output),
+            });
+        }
+
+        let max_attempts = self.config.build.auto_fix_attempts;
+        let root = BuildTarget { dir: PathBuf::new(), build_command: None, test_command: None, lint_command: None };
+        let (passed, _, output) = self.auto_fix_loop(&root, &check.command, output, max_attempts, ErrorType::Build,
+            |_| vec![check.path.clone()]).await?;
+        if passed {
+            return Ok(command_section(&check.command, &output, true));
+        }
 
+        Err(WorkSplitError::BuildFailed {
+            command: check.command.clone(),
+            output: format!("Check of {} failed after {} auto-fix attempts:\n{}", check.path.display(), max_attempts, output),
+        })
+    }
+
+    /// Run the job's `test_scope` command after generation, auto-fixing
+    /// failures if enabled
+    ///
+    /// Returns the passing run's output (capped at `MAX_BUILD_OUTPUT_LINES`),
+    /// for the verification prompt; `None` if the job has no `test_scope`.
+    async fn verify_with_test_scope(&self, job: &Job, files: &[(PathBuf, String)]) -> Result<Option<String>, WorkSplitError> {
+        let Some(ref cmd) = job.metadata.test_scope else {
+            return Ok(None);
+        };
+        if job.metadata.is_report_mode() {
+            return Ok(None);
+        }
+        if self.staging.is_some() {
+            info!("Read-only mode: skipping test_scope");
+            return Ok(None);
+        }
+
+        let target = self.config.build_target(job.metadata.workdir.as_deref());
+        info!("Running scoped tests: {}", cmd);
+        let (success, output) = self.run_build_command(cmd, &target.dir)?;
+        if success {
+            return Ok(Some(command_section(cmd, &output, true)));
+        }
+
+        let file_list = files.iter().map(|(p, _)| p.display().to_string()).collect::<Vec<_>>().join("\n");
+        if !self.config.build.auto_fix {
+            return Err(WorkSplitError::TestsFailed {
+                command: cmd.clone(),
+                output: format!("Tests failed after generating files:\n\nFiles generated:\n{}\n\nTest output:\n{}", file_list, output),
+            });
+        }
+
+        let max_attempts = self.config.build.auto_fix_attempts;
+        let paths: Vec<PathBuf> = files.iter().map(|(p, _)| p.clone()).collect();
+        let (passed, _, output) = self.auto_fix_loop(&target, cmd, output, max_attempts, ErrorType::Test, |_| paths.clone()).await?;
+        if passed {
+            return Ok(Some(command_section(cmd, &output, true)));
+        }
+
+        Err(WorkSplitError::TestsFailed {
+            command: cmd.clone(),
+            output: format!("Tests failed after {} auto-fix attempts:\n\nFiles:\n{}\n\nFinal output:\n{}",
+                max_attempts, file_list, output),
+        })
+    }
+
+    /// Alternate auto-fix and `cmd` (the build, or the tests for `error_type`
+    /// `Test`) until it passes or `max_attempts` fixes were tried. `files_for`
+    /// picks the files (relative to the project root) to send along with a
+    /// failed run's output.
+    ///
+    /// Returns whether the command passed, the attempts made and its last
+    /// output.
+    async fn auto_fix_loop(
+        &self,
+        target: &BuildTarget,
+        cmd: &str,
+        mut current_error: String,
+        max_attempts: u8,
+        error_type: ErrorType,
+        files_for: impl Fn(&str) -> Vec<PathBuf>,
+    ) -> Result<(bool, u8, String), WorkSplitError> {
         for attempt in 1..=max_attempts {
             info!("Auto-fix attempt {}/{}", attempt, max_attempts);
 
             // Read current file contents (may have been modified)
-            let current_files: Vec<(PathBuf, String)> = files.iter()
-                .filter_map(|(path, _)| {
-                    fs::read_to_string(path).ok().map(|content| (path.clone(), content))
+            let current_files: Vec<(PathBuf, String)> = files_for(&current_error).into_iter()
+                .filter_map(|path| {
+                    fs::read_to_string(self.project_root.join(&path)).ok().map(|content| (path, content))
                 })
                 .collect();
+            if current_files.is_empty() {
+                warn!("No files to fix for this {} output", error_type.lowercase_name());
+                return Ok((false, attempt - 1, current_error));
+            }
 
-            let fixed = self.attempt_auto_fix(&current_files, &current_error, ErrorType::Build).await?;
+            let fixed = self.attempt_auto_fix(&current_files, &current_error, error_type).await?;
 
             if !fixed {
                 warn!("Auto-fix attempt {} produced no changes", attempt);
                 continue;
             }
 
-            // Re-run build
-            let (success, new_output) = self.run_build_command(cmd)?;
+            // Re-run the command
+            let (success, new_output) = self.run_build_command(cmd, &target.dir)?;
 
             if success {
-                info!("Build succeeded after auto-fix attempt {}", attempt);
-                return Ok(());
+                info!("{} succeeded after auto-fix attempt {}", error_type.name(), attempt);
+                return Ok((true, attempt, new_output));
             }
 
             current_error = new_output;
-            warn!("Build still failing after auto-fix attempt {}", attempt);
+            warn!("{} still failing after auto-fix attempt {}", error_type.name(), attempt);
         }
+        Ok((false, max_attempts, current_error))
+    }
 
-        // All attempts exhausted
-        Err(WorkSplitError::BuildFailed {
-            command: cmd.clone(),
-            output: format!(
-                "Build failed after {} auto-fix attempts:\n\nFiles:\n{}\n\nFinal error:\n{}",
-                max_attempts,
-                files.iter().map(|(p, _)| p.display().to_string()).collect::<Vec<_>>().join("\n"),
-                current_error
-            ),
-        })
+    /// Run the build and auto-fix its errors without a job (`worksplit fix
+    /// --files` / `--from-build`). With no `files`, the files the build
+    /// output points at are fixed, re-read after every failed build.
+    pub async fn fix_build(&self, files: &[PathBuf], workdir: Option<&Path>, max_attempts: u8) -> Result<BuildFixReport, WorkSplitError> {
+        let target = self.config.build_target(workdir);
+        let cmd = target.build_command.clone().ok_or_else(|| WorkSplitError::ConfigError(
+            "No build_command configured in worksplit.toml. Add [build] build_command = \"your-build\"".into()))?;
+
+        info!("Running build command: {}", cmd);
+        let (success, output) = self.run_build_command(&cmd, &target.dir)?;
+        let mut report = BuildFixReport { command: cmd.clone(), passed: success, ..Default::default() };
+        if success {
+            report.output = output;
+            return Ok(report);
+        }
+
+        let sent = std::cell::RefCell::new(Vec::new());
+        let files_for = |output: &str| {
+            let files = if files.is_empty() {
+                files_in_build_output(output, &self.project_root, &target.dir)
+            } else {
+                files.to_vec()
+            };
+            let mut sent = sent.borrow_mut();
+            for file in &files {
+                if !sent.contains(file) {
+                    sent.push(file.clone());
+                }
+            }
+            files
+        };
+        let (passed, attempts, output) = self.auto_fix_loop(&target, &cmd, output, max_attempts, ErrorType::Build, files_for).await?;
+        report.passed = passed;
+        report.attempts = attempts;
+        report.files = sent.into_inner();
+        report.output = output;
+        Ok(report)
     }
 
+    /// Build output plus the lint command's output (e.g. clippy), capped at
+    /// `MAX_BUILD_OUTPUT_LINES` each
+    fn build_diagnostics(&self, target: &BuildTarget, build_cmd: &str, build_output: &str) -> Result<String, WorkSplitError> {
+        let mut diagnostics = command_section(build_cmd, build_output, true);
+        if let Some(ref lint_cmd) = target.lint_command {
+            info!("Running lint command for verification: {}", lint_cmd);
+            let (success, lint_output) = self.run_build_command(lint_cmd, &target.dir)?;
+            diagnostics.push('\n');
+            diagnostics.push_str(&command_section(lint_cmd, &lint_output, success));
+        }
+        Ok(diagnostics)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn run_job(&mut self, job_id: &str, create_prompt: &str, verify_prompt: &str,
                      test_prompt: Option<&str>, edit_prompt: &str, verify_edit_prompt: &str,
                      split_prompt: Option<&str>) -> Result<JobResult, WorkSplitError> {
+        self.ollama.set_current_job(job_id);
+        self.current_job = Some(job_id.to_string());
+        let from = self.status_manager.get(job_id).map_or(JobStatus::Created, |e| e.status);
+        let (started_at, tokens_before) = (Utc::now(), self.ollama.tokens_used());
+        if let Some(progress) = &self.progress {
+            progress.job_started(job_id);
+        }
+        self.ci_job_started(job_id);
+        self.ollama.take_request_retries();
+        self.ollama.take_responses();
+        self.ollama.take_writes();
+        self.phase_clock.take();
+        let mut result = self.run_job_inner(job_id, create_prompt, verify_prompt, test_prompt,
+            edit_prompt, verify_edit_prompt, split_prompt).await;
+        if let Err(WorkSplitError::Ollama(OllamaError::ContextOverflow(message))) = &result {
+            warn!("Job '{}' overflowed the model's context window ({}), retrying once with less context", job_id, message);
+            self.shrink_context = true;
+            result = self.run_job_inner(job_id, create_prompt, verify_prompt, test_prompt,
+                edit_prompt, verify_edit_prompt, split_prompt).await;
+            self.shrink_context = false;
+        }
+        // On failure the caller collects them for the result it builds
+        if let Ok(r) = &mut result {
+            r.request_retries = self.ollama.take_request_retries();
+            r.phase_timings = self.phase_clock.take();
+        }
+        self.save_failure_artifacts(job_id, &mut result);
+        self.current_job = None;
+        self.job_env.clear();
+        if let Some(progress) = &self.progress {
+            progress.job_finished();
+        }
+        self.record_job(job_id, from, started_at, tokens_before, &result);
+        self.record_outputs(job_id, &result);
+        self.record_provenance(job_id);
+        self.ci_job_finished(job_id, &result);
+
+        // Record why the job didn't pass, for `status --by-failure`
+        let failure = match &result {
+            Ok(r) => r.failure,
+            Err(e) => Some(FailureKind::from_error(e)),
+        };
+        if let Some(kind) = failure {
+            if let Err(e) = self.status_manager.set_failure(job_id, kind) {
+                warn!("Failed to record failure kind for '{}': {}", job_id, e);
+            }
+        }
+        if let Some(notifier) = &self.notifier {
+            match &result {
+                Ok(r) if r.status == JobStatus::Fail => notifier.job_failed(job_id, r.error.as_deref(), failure).await,
+                Err(e) => notifier.job_failed(job_id, Some(&e.to_string()), failure).await,
+                Ok(_) => {}
+            }
+        }
+        let oversized = std::mem::take(&mut self.oversized_outputs);
+        if !oversized.is_empty() && result.as_ref().is_ok_and(|r| r.status == JobStatus::Pass) {
+            self.split_oversized_outputs(job_id, &oversized, create_prompt, verify_prompt, test_prompt,
+                edit_prompt, verify_edit_prompt, split_prompt).await;
+        }
+        self.flush_status_webhook().await;
+        result
+    }
+
+    /// Write a split-mode job for each created file over `[limits]
+    /// max_output_lines` and run it (`oversized_outputs = "split"`). An
+    /// existing split job is run again rather than rewritten. In read-only
+    /// mode the job file is staged for `worksplit apply` instead of run.
+    #[allow(clippy::too_many_arguments)]
+    async fn split_oversized_outputs(&mut self, job_id: &str, oversized: &[OversizedOutput],
+                                     create_prompt: &str, verify_prompt: &str, test_prompt: Option<&str>,
+                                     edit_prompt: &str, verify_edit_prompt: &str, split_prompt: Option<&str>) {
+        let max_lines = self.config.limits.max_output_lines;
+        for output in oversized {
+            let split_id = split_job_id(job_id, &output.path, oversized.len() > 1);
+            let job_file = self.jobs_manager.jobs_dir().join(format!("{}.md", split_id));
+            info!("{} has {} lines, over [limits] max_output_lines ({}): splitting it with job '{}'",
+                output.path.display(), output.lines, max_lines, split_id);
+            if self.staging.is_some() {
+                if let Err(e) = self.stage_split_job(&split_id, &job_file, &split_job(job_id, output, max_lines)) {
+                    warn!("Failed to stage split job '{}': {}", split_id, e);
+                }
+                continue;
+            }
+            if !job_file.exists() {
+                if let Err(e) = write_atomic(&job_file, split_job(job_id, output, max_lines)) {
+                    warn!("Failed to write split job {}: {}", job_file.display(), e);
+                    continue;
+                }
+            }
+            if let Err(e) = self.sync_jobs() {
+                warn!("Failed to add split job '{}': {}", split_id, e);
+                continue;
+            }
+            if self.status_manager.get(&split_id).is_none() {
+                warn!("Split job '{}' isn't picked up by job discovery; run it by hand", split_id);
+                continue;
+            }
+
+            if let Some(progress) = &self.progress {
+                progress.add_jobs(1);
+            }
+            let result = Box::pin(self.run_job(&split_id, create_prompt, verify_prompt, test_prompt,
+                edit_prompt, verify_edit_prompt, split_prompt)).await;
+            let result = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    let _ = self.status_manager.set_failed(&split_id, e.to_string());
+                    self.error_result(&split_id, &e)
+                }
+            };
+            if result.status != JobStatus::Pass {
+                warn!("Split job '{}' didn't pass; {} stays over the limit", split_id, output.path.display());
+            }
+            self.split_results.push(result);
+        }
+    }
+
+    /// Stage a split job's file under `jobs/_staging/` (read-only mode); it
+    /// joins the job list, and runs, once the staged outputs are applied
+    fn stage_split_job(&self, split_id: &str, job_file: &Path, content: &str) -> Result<(), WorkSplitError> {
+        let Some(staging) = &self.staging else {
+            return Ok(());
+        };
+        let relative = job_file.strip_prefix(&self.project_root).unwrap_or(job_file);
+        let staged = staging.root().join(relative);
+        if let Some(parent) = staged.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(&staged, content)?;
+        staging.record(split_id, JobStatus::Created, vec![relative.to_path_buf()])?;
+        info!("Read-only mode: split job '{}' staged in {}; run it after 'worksplit apply'",
+            split_id, staging.root().display());
+        Ok(())
+    }
+
+    /// Check the job's assembled instructions, `test_scope` and `env` for
+    /// injected requests before anything reaches the model or a shell
+    /// (`[safety] injection_check`)
+    fn screen_instructions(&mut self, job: &Job) -> Result<(), WorkSplitError> {
+        let policy = self.config.safety.injection_check;
+        if policy == InjectionPolicy::Off {
+            return Ok(());
+        }
+        for (part, findings) in injection::scan_job(job) {
+            let source_name = format!("the {} of job '{}'", part, job.id);
+            if let Err(e) = injection::enforce(policy, &source_name, &findings) {
+                self.status_manager.set_failed(&job.id, e.to_string())?;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Hold created files to `[limits] max_output_lines`: fail the job,
+    /// warn, or note the files for a split job (`oversized_outputs`)
+    fn check_output_size(&mut self, job_id: &str, generated_files: &[(PathBuf, String)]) -> Result<(), WorkSplitError> {
+        let max_lines = self.config.limits.max_output_lines;
+        let oversized = find_oversized(generated_files, max_lines);
+        if oversized.is_empty() {
+            return Ok(());
+        }
+        let message = oversized_message(job_id, &oversized, max_lines);
+        match self.config.limits.oversized_outputs {
+            OversizedOutputPolicy::Fail => {
+                warn!("Output of {} exceeded the line limit:\n{}", job_id, message);
+                self.status_manager.set_failed(job_id, format!("Output exceeded line limit: {}", message.replace('\n', "; ")))?;
+                return Err(WorkSplitError::OutputTooLarge(message));
+            }
+            OversizedOutputPolicy::Warn => warn!("Output of {} exceeded the line limit:\n{}", job_id, message),
+            OversizedOutputPolicy::Split => self.oversized_outputs = oversized,
+        }
+        Ok(())
+    }
+
+    /// Result of a job that ended with an error
+    fn error_result(&mut self, job_id: &str, error: &WorkSplitError) -> JobResult {
+        JobResult {
+            job_id: job_id.to_string(), status: JobStatus::Fail,
+            error: Some(error.to_string()), output_paths: Vec::new(),
+            output_lines: None, test_path: None, test_lines: None,
+            retry_attempted: false, implicit_context_files: Vec::new(),
+            failure: Some(FailureKind::from_error(error)),
+            request_retries: self.ollama.take_request_retries(),
+            phase_timings: self.phase_clock.take(),
+        }
+    }
+
+    /// Wait for the job's status transitions to reach the status webhook,
+    /// so receivers see jobs in order and nothing is lost when the run ends
+    async fn flush_status_webhook(&self) {
+        if let Some(webhook) = &self.status_webhook {
+            webhook.flush().await;
+        }
+    }
+
+    fn ci_job_started(&self, job_id: &str) {
+        if self.ci_annotations {
+            println!("{}", ci::group(&format!("Job {}", job_id)));
+        }
+    }
+
+    /// Close the job's group and annotate its files
+    fn ci_job_finished(&self, job_id: &str, result: &Result<JobResult, WorkSplitError>) {
+        if self.ci_annotations {
+            println!("{}", ci::END_GROUP);
+            for line in ci::job_annotations(&self.project_root, job_id, result) {
+                println!("{}", line);
+            }
+        }
+    }
+
+    /// Keep the job's raw responses when nothing could be extracted or
+    /// verification failed outright, and point the job's error at them
+    fn save_failure_artifacts(&mut self, job_id: &str, result: &mut Result<JobResult, WorkSplitError>) {
+        let responses = self.ollama.take_responses();
+        let hard_failure = match result {
+            Ok(r) => r.status == JobStatus::Fail && r.failure == Some(FailureKind::VerificationHard),
+            Err(e) => FailureKind::from_error(e) == FailureKind::ExtractionEmpty,
+        };
+        if !hard_failure || responses.is_empty() {
+            return;
+        }
+        let dir = match save_responses(self.jobs_manager.jobs_dir(), job_id, &responses) {
+            Ok(dir) => dir,
+            Err(e) => {
+                warn!("Failed to save the responses of '{}': {}", job_id, e);
+                return;
+            }
+        };
+        let note = format!("raw responses in {}", dir.strip_prefix(&self.project_root).unwrap_or(&dir).display());
+        info!("Saved {} response(s) of '{}' to {}", responses.len(), job_id, dir.display());
+
+        let error = match result {
+            Ok(r) => {
+                let error = format!("{} ({})", r.error.as_deref().unwrap_or("Verification failed"), note);
+                r.error = Some(error.clone());
+                error
+            }
+            Err(e) => {
+                if let WorkSplitError::EmptyExtraction(message) = e {
+                    message.push_str(&format!(" ({})", note));
+                }
+                e.to_string()
+            }
+        };
+        if let Err(e) = self.status_manager.set_failed(job_id, error) {
+            warn!("Failed to record the error of '{}': {}", job_id, e);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn run_job_inner(&mut self, job_id: &str, create_prompt: &str, verify_prompt: &str,
+                           test_prompt: Option<&str>, edit_prompt: &str, verify_edit_prompt: &str,
+                           split_prompt: Option<&str>) -> Result<JobResult, WorkSplitError> {
         info!("Processing job: {}", job_id);
-        let job = self.jobs_manager.parse_job(job_id)?;
-        let context_files = self.load_context_files_with_implicit(&job)?;
+        let job = self.jobs_manager.parse_job_for_prompt(job_id)?;
+        self.screen_instructions(&job)?;
+        self.check_frozen_targets(&job)?;
+        self.job_env = job.metadata.env.clone();
+        let context_files = self.load_job_context(&job).await?;
+
+        // The job's own system prompt replaces the mode's default, and project
+        // conventions apply to everything the job generates
+        let generation_prompt = |default: &str| {
+            self.jobs_manager.prompt_with_conventions(&self.jobs_manager.generation_prompt(default, &job)?, &job)
+        };
+        let create_prompt = &generation_prompt(create_prompt)?;
+        let edit_prompt = &generation_prompt(edit_prompt)?;
+        let split_prompt = match (split_prompt, &job.metadata.system_prompt) {
+            (None, None) => None,
+            (default, _) => Some(generation_prompt(default.unwrap_or_default())?),
+        };
+        let split_prompt = split_prompt.as_deref();
+        let test_prompt = test_prompt.map(|p| self.jobs_manager.prompt_with_conventions(p, &job)).transpose()?;
+        let test_prompt = test_prompt.as_deref();
 
+        let context_window = self.config.limits.context_window_tokens;
         let (tokens, is_warning, is_error) = self.jobs_manager.check_token_budget(
-            create_prompt, &context_files, &job.instructions, 32000);
+            create_prompt, &context_files, &job.instructions, context_window);
         if is_error {
-            return Err(WorkSplitError::TokenBudgetExceeded { estimated: tokens, max: 32000 });
+            return Err(WorkSplitError::TokenBudgetExceeded { estimated: tokens, max: context_window });
         }
         if is_warning {
             warn!("Job '{}' has high token usage: {} estimated", job_id, tokens);
         }
 
+        // Before prompting, so a refused overwrite costs no generation
+        self.ownership_checked.clear();
+        for path in written_files(&job) {
+            self.check_ownership(job_id, &self.output_root().join(path))?;
+        }
+
         self.status_manager.update_status(job_id, JobStatus::PendingWork)?;
 
         let mut test_result_path: Option<PathBuf> = None;
@@ -562,110 +1704,182 @@ impl Runner {
             self.status_manager.update_status(job_id, JobStatus::PendingTest)?;
 
             let test_path = job.metadata.test_path().unwrap();
-            let test_gen_prompt = assemble_test_prompt(test_prompt_str, &context_files,
+            self.phase_clock.start(Phase::TestGeneration);
+            let test_gen_prompt = assemble_test_prompt(&self.prompt_templates, test_prompt_str, &context_files,
                 &job.instructions, &test_path.display().to_string());
 
             let test_response = self.ollama.generate_with_retry(Some(SYSTEM_PROMPT_TEST), &test_gen_prompt, self.config.behavior.stream_output)
                 .await.map_err(|e| { let _ = self.status_manager.set_failed(job_id, e.to_string()); WorkSplitError::Ollama(e) })?;
 
             let test_code = extract_code(&test_response);
-            let full_test_path = self.project_root.join(&test_path);
+            let full_test_path = self.output_root().join(&test_path);
             if let Some(parent) = full_test_path.parent() {
                 if !parent.exists() && self.config.behavior.create_output_dirs {
                     fs::create_dir_all(parent)?;
                 }
             }
             self.safe_write(&full_test_path, &test_code)?;
+            self.phase_clock.stop();
             test_result_path = Some(full_test_path);
             test_result_lines = Some(count_lines(&test_code));
         }
 
         let default_output_path = job.metadata.output_path();
         let mut generated_files: Vec<(PathBuf, String)> = Vec::new();
+        // Replace mode keeps its exchange so retries can continue it
+        let mut conversation: Option<Vec<ChatMessage>> = None;
+        let mut edit_partial: Option<PartialEditState> = None;
         let mut full_output_paths: Vec<PathBuf> = Vec::new();
         let mut total_lines = 0;
 
+        self.phase_clock.start(Phase::Generation);
         if job.metadata.is_split_mode() {
             let split_system_prompt = split_prompt.ok_or_else(|| WorkSplitError::SystemPromptNotFound(
                 self.jobs_manager.jobs_dir().join("_systemprompt_split.md")))?;
             let target_file_path = job.metadata.target_file.as_ref().unwrap();
             let output_files = job.metadata.get_output_files();
-            info!("Split mode (sequential): splitting {} into {} file(s)", target_file_path.display(), output_files.len());
-            
             let target_content = self.jobs_manager.load_target_file_unlimited(target_file_path)?;
-            let mut previously_generated: Vec<(PathBuf, String)> = Vec::new();
+
+            if job.metadata.is_planned_split() {
+                info!("Split mode (planned): splitting {} into {} file(s)", target_file_path.display(), output_files.len());
+                let file_progress = self.file_progress(job_id, output_files.len());
+                let files = match split::process_planned_split(&self.ollama, &self.prompt_templates, &self.config, &job,
+                    (target_file_path, &target_content), &context_files, split_system_prompt, &file_progress).await {
+                    Ok(files) => files,
+                    Err(e) => {
+                        self.status_manager.set_failed(job_id, e.to_string())?;
+                        return Err(e);
+                    }
+                };
+                for (output_path, content) in files {
+                    total_lines += count_lines(&content);
+                    let full_path = self.output_root().join(&output_path);
+                    if let Some(parent) = full_path.parent() {
+                        if !parent.exists() && self.config.behavior.create_output_dirs { fs::create_dir_all(parent)?; }
+                    }
+                    self.safe_write(&full_path, &content)?;
+                    generated_files.push((output_path, content));
+                    self.modified_files.push(full_path.clone());
+                    full_output_paths.push(full_path);
+                }
+            } else {
+                info!("Split mode (sequential): splitting {} into {} file(s)", target_file_path.display(), output_files.len());
             
-            for (idx, output_path) in output_files.iter().enumerate() {
-                let remaining_files: Vec<PathBuf> = output_files[idx + 1..].to_vec();
-                info!("[{}/{}] Splitting into: {}", idx + 1, output_files.len(), output_path.display());
+                let mut previously_generated: Vec<(PathBuf, String)> = Vec::new();
+                let file_progress = self.file_progress(job_id, output_files.len());
+            
+                for (idx, output_path) in output_files.iter().enumerate() {
+                    let remaining_files: Vec<PathBuf> = output_files[idx + 1..].to_vec();
+                    info!("[{}/{}] Splitting into: {}", idx + 1, output_files.len(), output_path.display());
                 
-                let prompt = assemble_sequential_split_prompt(split_system_prompt,
-                    (target_file_path, &target_content), &context_files, &previously_generated,
-                    &job.instructions, &output_path.display().to_string(), &remaining_files);
+                    let prompt = assemble_sequential_split_prompt(&self.prompt_templates, split_system_prompt,
+                        (target_file_path, &target_content), &context_files, &previously_generated,
+                        &job.instructions, &output_path.display().to_string(), &remaining_files);
                 
-                let response = self.ollama.generate_with_retry(Some(SYSTEM_PROMPT_CREATE), &prompt, self.config.behavior.stream_output)
-                    .await.map_err(|e| { let _ = self.status_manager.set_failed(job_id, e.to_string()); WorkSplitError::Ollama(e) })?;
+                    let response = self.ollama.generate_with_retry(Some(SYSTEM_PROMPT_CREATE), &prompt, self.config.behavior.stream_output)
+                        .await.map_err(|e| { let _ = self.status_manager.set_failed(job_id, e.to_string()); WorkSplitError::Ollama(e) })?;
                 
-                let extracted = extract_code_files(&response);
-                let content = if extracted.is_empty() { extract_code(&response) } else { extracted[0].content.clone() };
+                    let extracted = extract_code_files(&response);
+                    let content = if extracted.is_empty() { extract_code(&response) } else { extracted[0].content.clone() };
                 
-                if content.is_empty() {
-                    let msg = format!("Split produced no content for {}", output_path.display());
-                    self.status_manager.set_failed(job_id, msg.clone())?;
-                    return Err(WorkSplitError::EditFailed(msg));
-                }
+                    if content.is_empty() {
+                        let msg = format!("Split produced no content for {}", output_path.display());
+                        self.status_manager.set_failed(job_id, msg.clone())?;
+                        return Err(WorkSplitError::EmptyExtraction(msg));
+                    }
                 
-                total_lines += count_lines(&content);
-                let full_path = self.project_root.join(output_path);
-                if let Some(parent) = full_path.parent() {
-                    if !parent.exists() && self.config.behavior.create_output_dirs { fs::create_dir_all(parent)?; }
-                }
-                self.safe_write(&full_path, &content)?;
+                    total_lines += count_lines(&content);
+                    let full_path = self.output_root().join(output_path);
+                    if let Some(parent) = full_path.parent() {
+                        if !parent.exists() && self.config.behavior.create_output_dirs { fs::create_dir_all(parent)?; }
+                    }
+                    self.safe_write(&full_path, &content)?;
                 
-                previously_generated.push((output_path.clone(), content.clone()));
-                generated_files.push((output_path.clone(), content));
-                self.modified_files.push(full_path.clone());
-                full_output_paths.push(full_path);
+                    previously_generated.push((output_path.clone(), content.clone()));
+                    generated_files.push((output_path.clone(), content));
+                    self.modified_files.push(full_path.clone());
+                    full_output_paths.push(full_path);
+                    file_progress.inc();
+                }
+            }
+        } else if job.metadata.is_report_mode() {
+            let prompt = assemble_report_prompt(&self.prompt_templates, &context_files, &job.instructions,
+                &default_output_path.display().to_string(), None);
+            let response = self.ollama.generate_with_retry(Some(SYSTEM_PROMPT_REPORT), &prompt, self.config.behavior.stream_output)
+                .await.map_err(|e| { let _ = self.status_manager.set_failed(job_id, e.to_string()); WorkSplitError::Ollama(e) })?;
+
+            let report = extract_report(&response);
+            if report.is_empty() {
+                let msg = "Model response contained no report".to_string();
+                self.status_manager.set_failed(job_id, msg.clone())?;
+                return Err(WorkSplitError::EmptyExtraction(msg));
+            }
+
+            let full_path = self.output_root().join(&default_output_path);
+            if let Some(parent) = full_path.parent() {
+                if !parent.exists() && self.config.behavior.create_output_dirs { fs::create_dir_all(parent)?; }
             }
+            self.safe_write(&full_path, &report)?;
+            total_lines = count_lines(&report);
+            generated_files.push((default_output_path.clone(), report));
+            full_output_paths.push(full_path);
         } else if job.metadata.is_edit_mode() {
             let result = edit::process_edit_mode(
                 &self.ollama,
+                &self.prompt_templates,
                 &self.project_root,
+                &self.output_root(),
                 &self.config,
                 &job,
                 &context_files,
-                &edit_prompt,
+                edit_prompt,
                 false, // dry_run
             ).await?;
             generated_files = result.generated_files;
             full_output_paths = result.output_paths;
             total_lines = result.total_lines;
+            edit_partial = result.partial_state;
         } else if job.metadata.is_sequential() {
+            let file_progress = self.file_progress(job_id, job.metadata.get_output_files().len());
             let files = sequential::process_sequential_mode(
                 &self.ollama,
-                &self.project_root,
+                &self.prompt_templates,
+                &self.output_root(),
                 &self.config,
                 &job,
                 &context_files,
-                &create_prompt,
+                create_prompt,
+                &file_progress,
             ).await?;
             generated_files = files.0;
             full_output_paths = files.1;
             total_lines = files.2;
         } else {
-            let prompt = assemble_creation_prompt(create_prompt, &context_files, &job.instructions,
+            let prompt = assemble_creation_prompt(&self.prompt_templates, create_prompt, &context_files, &job.instructions,
                 &default_output_path.display().to_string());
-            let response = self.ollama.generate_with_retry(Some(SYSTEM_PROMPT_CREATE), &prompt, self.config.behavior.stream_output)
-                .await.map_err(|e| { let _ = self.status_manager.set_failed(job_id, e.to_string()); WorkSplitError::Ollama(e) })?;
-            
-            for file in extract_code_files(&response) {
-                let path = file.path.clone().unwrap_or_else(|| default_output_path.clone());
-                total_lines += count_lines(&file.content);
-                generated_files.push((path, file.content.clone()));
+            let (response, files) = if job.metadata.candidates > 1 {
+                self.generate_candidates(&job, &prompt, verify_prompt, &context_files).await
+                    .inspect_err(|e| { let _ = self.status_manager.set_failed(job_id, e.to_string()); })?
+            } else {
+                let response = self.ollama.generate_with_retry(Some(SYSTEM_PROMPT_CREATE), &prompt, self.config.behavior.stream_output)
+                    .await.map_err(|e| { let _ = self.status_manager.set_failed(job_id, e.to_string()); WorkSplitError::Ollama(e) })?;
+                let files = files_in_response(&response, &default_output_path);
+                (response, files)
+            };
+
+            for (path, content) in files {
+                total_lines += count_lines(&content);
+                generated_files.push((path, content));
+            }
+
+            if generated_files.iter().all(|(_, content)| content.trim().is_empty()) {
+                let msg = "Model response contained no code".to_string();
+                self.status_manager.set_failed(job_id, msg.clone())?;
+                return Err(WorkSplitError::EmptyExtraction(msg));
             }
             
             for (path, content) in &generated_files {
-                let full_path = self.project_root.join(path);
+                let full_path = self.output_root().join(path);
                 if let Some(parent) = full_path.parent() {
                     if !parent.exists() && self.config.behavior.create_output_dirs { fs::create_dir_all(parent)?; }
                 }
@@ -673,49 +1887,153 @@ impl Runner {
                 self.modified_files.push(full_path.clone());
                 full_output_paths.push(full_path);
             }
+
+            if self.config.behavior.retry_conversation {
+                conversation = Some(vec![
+                    ChatMessage::system(SYSTEM_PROMPT_CREATE),
+                    ChatMessage::user(prompt),
+                    ChatMessage::assistant(response),
+                ]);
+            }
         }
 
-        self.verify_with_build(&job, &generated_files).await?;
+        self.phase_clock.stop();
+
+        if job.metadata.generate_module_index {
+            for index_path in self.update_module_indexes(&generated_files)? {
+                self.modified_files.push(index_path.clone());
+                full_output_paths.push(index_path);
+            }
+        }
+
+        if let Some(ref expect) = job.metadata.expect {
+            let reasons = expect.check(&generated_files);
+            if !reasons.is_empty() {
+                let msg = reasons.iter().map(|r| format!("- {}", r)).collect::<Vec<_>>().join("\n");
+                warn!("Output expectations not met for {}:\n{}", job_id, msg);
+                self.status_manager.set_failed(job_id, format!("Output expectations not met: {}", reasons.join("; ")))?;
+                return Err(WorkSplitError::ExpectationFailed(msg));
+            }
+        }
+
+        if !job.metadata.is_split_mode() && !job.metadata.is_edit_mode() && !job.metadata.is_report_mode() {
+            self.check_output_size(job_id, &generated_files)?;
+        }
+
+        self.phase_clock.start(Phase::Build);
+        let build_output = self.verify_with_build(&job, &generated_files).await?;
+        let test_output = self.verify_with_test_scope(&job, &generated_files).await?;
+        let build_output = match (build_output, test_output) {
+            (Some(build), Some(tests)) => Some(format!("{}\n{}", build, tests)),
+            (build, tests) => build.or(tests),
+        };
+        self.phase_clock.stop();
 
         // Check if verification is disabled for this job
         let mut final_status = JobStatus::Pass;
         let mut final_error: Option<String> = None;
         let mut retry_attempted = false;
+        let mut failure: Option<FailureKind> = None;
 
+        // Before the final status, so the status webhook lists the outputs
+        self.record_output_paths(job_id, &full_output_paths, test_result_path.as_ref());
         if !job.metadata.verify {
             info!("Verification skipped (verify: false in job metadata)");
+            self.status_manager.set_rubric(job_id, None)?;
             self.status_manager.update_status(job_id, JobStatus::Pass)?;
         } else {
             self.status_manager.update_status(job_id, JobStatus::PendingVerification)?;
-
-            let effective_verify = if job.metadata.is_edit_mode() { verify_edit_prompt } else { verify_prompt };
-            let (mut final_result, mut err) = verify::run_verification(
-                &self.ollama,
-                effective_verify,
-                &context_files,
-                &generated_files,
-                &job.instructions,
-            ).await?;
+            self.phase_clock.start(Phase::Verification);
+
+            let base_verify = if job.metadata.is_edit_mode() { verify_edit_prompt } else { verify_prompt };
+            let effective_verify = self.jobs_manager.verify_prompt_for_level(
+                base_verify, job.metadata.verification, job.metadata.is_edit_mode())?;
+            let (local, mut rubric) = if job.metadata.is_report_mode() {
+                let report_context = self.fit_context("Verification", &context_files,
+                    &[verify::REPORT_VERIFY_PROMPT, &job.instructions], &generated_files);
+                (verify::run_report_verification(&self.ollama, &self.prompt_templates, &report_context, &generated_files, &job.instructions).await?, None)
+            } else {
+                self.verify_code(
+                    &effective_verify,
+                    &context_files,
+                    &generated_files,
+                    &job.instructions,
+                    build_output.as_deref(),
+                ).await?
+            };
+            let (mut final_result, err) = verify::apply_verification_level(job.metadata.verification,
+                verify::run_external_verification(
+                    self.external_verifier.as_ref(),
+                    job_id,
+                    &job.instructions,
+                    &generated_files,
+                    local,
+                ).await);
 
             final_status = final_result.to_job_status();
             final_error = err;
 
-            if !final_result.is_pass() {
-                info!("Verification failed, retrying...");
+            // One retry per step of the retry ladder, until verification passes
+            // or only complains about things the job didn't ask for
+            let mut latest_files = generated_files.clone();
+            let mut retries = self.config.behavior.retry_ladder.retries().into_iter().enumerate();
+            loop {
+                if final_result.is_pass() {
+                    break;
+                }
+                let feedback = final_error.clone().unwrap_or_default();
+                let sorted = self.feedback_rules.sort(&feedback, &job);
+                if sorted.all_ignored() {
+                    let issues: Vec<String> = sorted.ignored.iter()
+                        .map(|issue| format!("{} ({:?})", issue.text, issue.category)).collect();
+                    warn!("Verification of '{}' only raised ignored or out-of-scope issues, passing with warnings: {}",
+                        job_id, issues.join("; "));
+                    final_result = VerificationResult::PassWithWarnings;
+                    final_status = final_result.to_job_status();
+                    final_error = None;
+                    break;
+                }
+                let Some((attempt, step)) = retries.next() else { break };
+                info!("Verification failed, retry {} ({})...", attempt + 1, step);
                 retry_attempted = true;
-                let error_msg = final_error.clone().unwrap_or_default();
-                
-                let retry_files = verify::run_retry(
-                    &self.ollama,
-                    create_prompt,
-                    &context_files,
-                    &generated_files,
-                    &job.instructions,
-                    &error_msg,
-                ).await?;
+                let error_msg = sorted.retry_feedback(&feedback);
+
+                self.ollama.set_retry_step(Some(step));
+                self.phase_clock.start(Phase::Retry);
+                let retry_files = if job.metadata.is_report_mode() {
+                    let retry_context = self.fit_context("Retry", &context_files, &[&job.instructions, &error_msg], &latest_files[..1]);
+                    verify::run_report_retry(&self.ollama, &self.prompt_templates, &retry_context, &latest_files[0],
+                        &job.instructions, &error_msg).await
+                } else {
+                    let followup = match conversation.as_mut() {
+                        Some(messages) => verify::run_conversation_retry(&self.ollama, &self.prompt_templates, messages,
+                            &latest_files, &error_msg, self.config.behavior.retry_conversation_max_tokens).await,
+                        None => Ok(None),
+                    };
+                    match followup {
+                        Ok(Some(files)) => Ok(files),
+                        Ok(None) => {
+                            conversation = None;
+                            let retry_context = self.fit_context("Retry", &context_files,
+                                &[create_prompt, &job.instructions, &error_msg], &latest_files);
+                            verify::run_retry(
+                                &self.ollama,
+                                &self.prompt_templates,
+                                create_prompt,
+                                &retry_context,
+                                &latest_files,
+                                &job.instructions,
+                                &error_msg,
+                            ).await
+                        }
+                        Err(e) => Err(e),
+                    }
+                };
+                self.ollama.set_retry_step(None);
+                let retry_files = retry_files?;
 
                 for (path, content) in &retry_files {
-                    let full_path = self.project_root.join(path);
+                    let full_path = self.output_root().join(path);
                     if let Some(parent) = full_path.parent() {
                         if !parent.exists() && self.config.behavior.create_output_dirs { fs::create_dir_all(parent)?; }
                     }
@@ -723,20 +2041,42 @@ impl Runner {
                     self.modified_files.push(full_path.clone());
                 }
                 
-                full_output_paths = retry_files.iter().map(|(p, _)| self.project_root.join(p)).collect();
+                full_output_paths = retry_files.iter().map(|(p, _)| self.output_root().join(p)).collect();
                 
-                let (r, e) = verify::run_verification(
-                    &self.ollama,
-                    effective_verify,
-                    &context_files,
-                    &retry_files,
-                    &job.instructions,
-                ).await?;
+                // The earlier build output describes the files the retry replaced
+                self.phase_clock.start(Phase::Verification);
+                let (local, scores) = if job.metadata.is_report_mode() {
+                    let report_context = self.fit_context("Verification", &context_files,
+                        &[verify::REPORT_VERIFY_PROMPT, &job.instructions], &retry_files);
+                    (verify::run_report_verification(&self.ollama, &self.prompt_templates, &report_context, &retry_files, &job.instructions).await?, None)
+                } else {
+                    self.verify_code(
+                        &effective_verify,
+                        &context_files,
+                        &retry_files,
+                        &job.instructions,
+                        None,
+                    ).await?
+                };
+                rubric = scores;
+                let (r, e) = verify::apply_verification_level(job.metadata.verification,
+                    verify::run_external_verification(
+                        self.external_verifier.as_ref(),
+                        job_id,
+                        &job.instructions,
+                        &retry_files,
+                        local,
+                    ).await);
                 final_result = r;
                 final_error = e;
                 final_status = final_result.to_job_status();
+                latest_files = retry_files;
+                self.record_output_paths(job_id, &full_output_paths, test_result_path.as_ref());
             }
 
+            self.phase_clock.stop();
+            failure = verification_failure(final_result);
+            self.status_manager.set_rubric(job_id, rubric)?;
             if let Some(ref msg) = final_error {
                 self.status_manager.set_failed(job_id, msg.clone())?;
             } else {
@@ -744,6 +2084,23 @@ impl Runner {
             }
         }
 
+        // Some edits could not be applied: keep them for `run --continue`
+        if let Some(state) = edit_partial.filter(|_| final_error.is_none()) {
+            info!("{} edit(s) failed to apply; finish with 'worksplit run --continue {}'",
+                state.failed_edits.len(), job_id);
+            self.status_manager.set_partial(job_id, state)?;
+            final_status = JobStatus::Partial;
+            failure = Some(FailureKind::EditNoMatch);
+        }
+
+        if let Some(staging) = &self.staging {
+            let staged_files = full_output_paths.iter().chain(test_result_path.iter())
+                .filter_map(|p| p.strip_prefix(staging.root()).ok().map(Path::to_path_buf))
+                .collect();
+            staging.record(job_id, final_status, staged_files)?;
+            info!("Read-only mode: outputs staged in {}", staging.root().display());
+        }
+
         // Mark the job as having been run (regardless of outcome)
         // This prevents unnecessary reruns when the output was manually fixed
         if let Err(e) = self.status_manager.mark_ran(job_id) {
@@ -759,28 +2116,100 @@ impl Runner {
             job_id: job_id.to_string(), status: final_status, error: final_error,
             output_paths: full_output_paths, output_lines: Some(total_lines),
             test_path: test_result_path, test_lines: test_result_lines,
-            retry_attempted, implicit_context_files: Vec::new(),
+            retry_attempted, implicit_context_files: Vec::new(), failure,
+            request_retries: Vec::new(), phase_timings: PhaseTimings::default(),
         })
     }
 
-    fn load_context_files_with_implicit(&mut self, job: &crate::models::Job) -> Result<Vec<(PathBuf, String)>, WorkSplitError> {
-        let mut context_files = self.jobs_manager.load_context_files(job)?;
-        if !self.modified_files.is_empty() {
-            let max = self.config.limits.max_context_files;
-            let available = max.saturating_sub(context_files.len());
-            if available > 0 {
-                let output_path = self.project_root.join(job.metadata.output_path());
-                let implicit: Vec<&PathBuf> = self.modified_files.iter()
-                    .filter(|p| p.exists() && *p != &output_path)
-                    .take(available).collect();
-                for path in implicit {
-                    if let Ok(content) = fs::read_to_string(path) {
-                        context_files.push((path.clone(), content));
-                    }
+    /// Gather the job's context from all sources, deduplicated and in the
+    /// configured order
+    async fn load_job_context(&mut self, job: &crate::models::Job) -> Result<Vec<(PathBuf, String)>, WorkSplitError> {
+        let mut context = JobContext {
+            listed: self.jobs_manager.load_context_files(job)?,
+            ..Default::default()
+        };
+
+        // Output files already there (staged copy first), so regeneration
+        // starts from them
+        if job.metadata.includes_existing_output() {
+            for path in job.metadata.get_output_files() {
+                let staged = self.output_root().join(&path);
+                let source = if staged.exists() { staged } else { self.project_root.join(&path) };
+                if let Ok(content) = fs::read_to_string(&source) {
+                    context.existing_output.push((path, content));
+                }
+            }
+        }
+
+        // Files modified earlier in the run, up to max_context_files; files
+        // the job already lists don't take up a slot
+        let implicit_context = job.metadata.implicit_context.unwrap_or(self.config.behavior.implicit_context);
+        if implicit_context && !self.modified_files.is_empty() {
+            let available = self.config.limits.max_context_files.saturating_sub(context.listed.len());
+            let output_path = self.project_root.join(job.metadata.output_path());
+            let listed: HashSet<PathBuf> = context.listed.iter()
+                .map(|(path, _)| context_key(&self.project_root, path))
+                .collect();
+            let implicit: Vec<&PathBuf> = self.modified_files.iter()
+                .filter(|p| p.exists() && *p != &output_path)
+                .filter(|p| !listed.contains(&context_key(&self.project_root, p)))
+                .take(available).collect();
+            for path in implicit {
+                if let Ok(content) = fs::read_to_string(path) {
+                    let relative = path.strip_prefix(&self.project_root).unwrap_or(path);
+                    context.implicit.push((relative.to_path_buf(), content));
                 }
             }
         }
-        Ok(context_files)
+
+        context.urls = self.jobs_manager.load_context_urls(job, &self.config.context_urls).await?;
+        if job.metadata.context_retrieval.unwrap_or(self.config.retrieval.enabled) {
+            let known: Vec<&PathBuf> = context.listed.iter().chain(&context.implicit).map(|(path, _)| path).collect();
+            context.retrieved = self.retrieve_context(job, &known).await;
+        }
+        if self.shrink_context {
+            let shrunk = context.shrink().ok_or_else(|| WorkSplitError::Ollama(OllamaError::ContextOverflow(
+                format!("the context of '{}' can't be cut down any further", job.id))))?;
+            info!("Context of '{}': {}", job.id, shrunk);
+        }
+        Ok(context.assemble(&self.project_root, self.config.behavior.context_order))
+    }
+
+    /// Project snippets most similar to the job's instructions, leaving out
+    /// files already in context and the files the job writes. Retrieval is
+    /// best-effort: if it fails, the job runs without the snippets.
+    #[cfg(feature = "embeddings")]
+    async fn retrieve_context(&self, job: &crate::models::Job, context_files: &[&PathBuf]) -> Vec<(PathBuf, String)> {
+        let mut exclude: Vec<PathBuf> = context_files
+            .iter()
+            .map(|path| path.as_path())
+            .chain(touched_files(job).iter().map(PathBuf::as_path))
+            .filter_map(crate::core::output_paths::normalize_relative)
+            .collect();
+        exclude.dedup();
+
+        let snippets = match embeddings::retrieve_context(&self.project_root, self.jobs_manager.jobs_dir(),
+            &self.ollama, &self.config.retrieval, &job.instructions, &exclude).await {
+            Ok(snippets) => snippets,
+            Err(e) => {
+                warn!("Context retrieval failed for '{}', continuing without it: {}", job.id, e);
+                return Vec::new();
+            }
+        };
+        snippets
+            .into_iter()
+            .map(|snippet| {
+                info!("Retrieved context {} (similarity {:.2})", snippet.label().display(), snippet.similarity);
+                (snippet.label(), snippet.content)
+            })
+            .collect()
+    }
+
+    /// Without the "embeddings" feature, jobs run without retrieved context
+    #[cfg(not(feature = "embeddings"))]
+    async fn retrieve_context(&self, job: &crate::models::Job, _context_files: &[&PathBuf]) -> Vec<(PathBuf, String)> {
+        warn!("[retrieval] is enabled, but '{}' runs without retrieved context: WorkSplit was built without the \"embeddings\" feature", job.id);
+        Vec::new()
     }
 
     fn is_protected_path(&self, path: &Path) -> bool {
@@ -797,14 +2226,254 @@ impl Runner {
         false
     }
 
+    /// Declare generated files in their module indexes; returns the index
+    /// files written
+    fn update_module_indexes(&mut self, generated_files: &[(PathBuf, String)]) -> Result<Vec<PathBuf>, WorkSplitError> {
+        let output_root = self.output_root();
+        let project_root = self.project_root.clone();
+        let read = |path: &Path| {
+            fs::read_to_string(output_root.join(path))
+                .or_else(|_| fs::read_to_string(project_root.join(path)))
+                .ok()
+        };
+        let files: Vec<PathBuf> = generated_files.iter().map(|(path, _)| path.clone()).collect();
+
+        let mut written = Vec::new();
+        for (index, content) in module_index::index_updates(&files, &read) {
+            info!("Updating module index {}", index.display());
+            let full_path = output_root.join(&index);
+            // Indexes are shared by design: every job adds its own declarations
+            self.write_checked(&full_path, &content)?;
+            written.push(full_path);
+        }
+        Ok(written)
+    }
+
+    /// Root that generated files are written under: the staging area in
+    /// read-only mode, otherwise the project root
+    /// Append the start of a run to `jobs/_history.jsonl`
+    fn record_run_start(&self, jobs: usize) {
+        let event = HistoryEvent::Run { started_at: Utc::now(), jobs };
+        if let Err(e) = history::append(self.jobs_manager.jobs_dir(), &event) {
+            warn!("Failed to record run history: {}", e);
+        }
+    }
+
+    /// `path` relative to the output (or project) root
+    fn relative_output(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(self.output_root())
+            .or_else(|_| path.strip_prefix(&self.project_root))
+            .unwrap_or(path)
+            .to_path_buf()
+    }
+
+    /// Append a processed job to `jobs/_history.jsonl`
+    fn record_job(&self, job_id: &str, from: JobStatus, started_at: DateTime<Utc>, tokens_before: u64,
+                  result: &Result<JobResult, WorkSplitError>) {
+        let relative = |path: &PathBuf| self.relative_output(path);
+        let (to, outputs, failure) = match result {
+            Ok(r) => (r.status, r.output_paths.iter().chain(&r.test_path).map(relative).collect(), r.failure),
+            Err(e) => (JobStatus::Fail, Vec::new(), Some(FailureKind::from_error(e))),
+        };
+        let record = JobRecord {
+            job_id: job_id.to_string(),
+            started_at,
+            finished_at: Utc::now(),
+            from,
+            to,
+            outputs,
+            failure,
+            tokens: self.ollama.tokens_used().saturating_sub(tokens_before),
+            rubric: self.status_manager.get(job_id).and_then(|entry| entry.rubric),
+        };
+        if let Err(e) = history::append(self.jobs_manager.jobs_dir(), &HistoryEvent::Job(record)) {
+            warn!("Failed to record run history for '{}': {}", job_id, e);
+        }
+        // For `worksplit status --cache`
+        if let Err(e) = self.cache_stats().save(self.jobs_manager.jobs_dir()) {
+            warn!("Failed to record file cache statistics: {}", e);
+        }
+    }
+
+    /// Add the files the job wrote to `jobs/_provenance.json`, with the
+    /// request each came from
+    fn record_provenance(&self, job_id: &str) {
+        let writes = self.ollama.take_writes();
+        if writes.is_empty() || !cfg!(feature = "provenance") {
+            return;
+        }
+        let jobs_dir = self.jobs_manager.jobs_dir();
+        let mut manifest = match ProvenanceManifest::load(jobs_dir) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                warn!("Failed to record the provenance of the files '{}' wrote: {}", job_id, e);
+                return;
+            }
+        };
+        for write in &writes {
+            manifest.record(job_id, &self.relative_output(&write.path), write);
+        }
+        if let Err(e) = manifest.save(jobs_dir) {
+            warn!("Failed to record the provenance of the files '{}' wrote: {}", job_id, e);
+        }
+    }
+
+    /// Make the job the owner of the files it generated
+    fn record_outputs(&mut self, job_id: &str, result: &Result<JobResult, WorkSplitError>) {
+        if let Ok(result) = result {
+            self.record_output_paths(job_id, &result.output_paths, result.test_path.as_ref());
+        }
+    }
+
+    fn record_output_paths(&mut self, job_id: &str, output_paths: &[PathBuf], test_path: Option<&PathBuf>) {
+        let outputs: Vec<PathBuf> = output_paths.iter().chain(test_path)
+            .map(|path| self.relative_output(path))
+            .collect();
+        if outputs.is_empty() {
+            return;
+        }
+        if let Err(e) = self.status_manager.set_outputs(job_id, outputs) {
+            warn!("Failed to record the outputs of '{}': {}", job_id, e);
+        }
+    }
+
+    /// Check a file the job is about to overwrite against the job that
+    /// generated it last: overwriting another passed job's output is
+    /// logged, or refused under `[safety] owned_outputs = "refuse"` unless
+    /// the run has `--force`. Jobs may overwrite the outputs of jobs they
+    /// depend on.
+    fn check_ownership(&mut self, job_id: &str, path: &Path) -> Result<(), WorkSplitError> {
+        let relative = self.relative_output(path);
+        if !self.ownership_checked.insert(relative.clone()) {
+            return Ok(());
+        }
+        let Some(owner) = self.status_manager.owner_of(&relative)
+            .filter(|owner| owner.id != job_id && owner.status == JobStatus::Pass)
+            .map(|owner| owner.id.clone()) else { return Ok(()) };
+        if self.job_depends_on(job_id, &owner) {
+            return Ok(());
+        }
+        if self.force || self.config.safety.owned_outputs == OwnedOutputPolicy::Warn {
+            warn!("Job '{}' overwrites {}, which passed job '{}' generated", job_id, relative.display(), owner);
+            return Ok(());
+        }
+        Err(WorkSplitError::OutputPathConflict(format!(
+            "  - Job '{}' would overwrite {}, which passed job '{}' generated; rerun with --force to overwrite it, give this job its own output file or add depends_on: [{}] to '{}'",
+            job_id,
+            relative.display(),
+            owner,
+            owner,
+            job_id
+        )))
+    }
+
+    /// Refuse to overwrite a file frozen with `worksplit freeze`, unless the
+    /// run has `--force`
+    fn check_frozen(&self, path: &Path) -> Result<(), WorkSplitError> {
+        let relative = self.relative_output(path);
+        let frozen = FrozenManifest::load(self.jobs_manager.jobs_dir())?;
+        let Some(file) = frozen.get(&relative) else { return Ok(()) };
+        if self.force {
+            warn!("Overwriting {}, frozen with job '{}' (--force)", relative.display(), file.job_id);
+            return Ok(());
+        }
+        Err(WorkSplitError::FrozenOutput { path: relative, job_id: file.job_id.clone() })
+    }
+
+    /// Fail before prompting when the job would write or edit a frozen file
+    fn check_frozen_targets(&self, job: &Job) -> Result<(), WorkSplitError> {
+        if self.force {
+            return Ok(());
+        }
+        touched_files(job).iter().try_for_each(|path| self.check_frozen(&self.project_root.join(path)))
+    }
+
+    fn output_root(&self) -> PathBuf {
+        match &self.staging {
+            Some(staging) => staging.root().to_path_buf(),
+            None => self.project_root.clone(),
+        }
+    }
+
+    /// Write a job output. Fails if another job wrote the same file earlier
+    /// in this run, unless the current job depends on it: the second write
+    /// would silently discard the first job's work.
     fn safe_write(&mut self, path: &Path, content: &str) -> Result<(), WorkSplitError> {
+        if let Some(job_id) = self.current_job.clone() {
+            if let Some(writer) = self.written_by.get(path).filter(|w| **w != job_id) {
+                if !self.job_depends_on(&job_id, writer) {
+                    let relative = path.strip_prefix(self.output_root()).unwrap_or(path);
+                    return Err(WorkSplitError::OutputPathConflict(format!(
+                        "  - Job '{}' would overwrite {}, which job '{}' wrote earlier in this run; give each job its own output file or add depends_on: [{}] to '{}'",
+                        job_id,
+                        relative.display(),
+                        writer,
+                        writer,
+                        job_id
+                    )));
+                }
+            }
+            self.check_ownership(&job_id, path)?;
+            let written = self.write_checked(path, content)?;
+            self.ollama.record_write(path, &written);
+            self.written_by.insert(path.to_path_buf(), job_id);
+            return Ok(());
+        }
+        let written = self.write_checked(path, content)?;
+        self.ollama.record_write(path, &written);
+        Ok(())
+    }
+
+    /// Whether `job_id` depends on `other`, directly or through other jobs
+    fn job_depends_on(&self, job_id: &str, other: &str) -> bool {
+        let mut stack = vec![job_id.to_string()];
+        let mut seen = std::collections::HashSet::new();
+        while let Some(id) = stack.pop() {
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            let Ok(job) = self.jobs_manager.parse_job(&id) else { continue };
+            for dep in job.metadata.depends_on.into_iter().flatten() {
+                if dep == other {
+                    return true;
+                }
+                stack.push(dep);
+            }
+        }
+        false
+    }
+
+    /// Write a file inside the allowed output area; returns the content as
+    /// written, after post-processing
+    fn write_checked(&mut self, path: &Path, content: &str) -> Result<String, WorkSplitError> {
+        check_write_path(&self.output_root(), path, &self.config.safety.allowed_output_dirs)?;
+        check_protected_path(&self.output_root(), path, &self.config.safety.protected_paths)?;
+        injection::check_write(self.config.safety.injection_check, &self.output_root(), path, content)?;
+        self.check_frozen(path)?;
         if self.is_protected_path(path) {
-            return Err(WorkSplitError::ProtectedPathViolation(path.to_path_buf()));
+            return Err(WorkSplitError::ProtectedPathViolation {
+                path: path.to_path_buf(),
+                rule: "WorkSplit's own files (jobs/_*)".to_string(),
+            });
         }
-        fs::write(path, content)?;
+        if let Some(staging) = &self.staging {
+            if !path.starts_with(staging.root()) {
+                return Err(WorkSplitError::ProtectedPathViolation {
+                    path: path.to_path_buf(),
+                    rule: "[safety] read_only (outputs go to jobs/_staging/)".to_string(),
+                });
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        let root = self.output_root();
+        let relative = path.strip_prefix(&root).unwrap_or(path);
+        let content = postprocess_with_env(&self.config.postprocess, &root, relative, content, &self.job_env);
+        write_atomic(path, &content)?;
         // Invalidate cache entry since file was modified
         self.jobs_manager.invalidate_cache(path);
-        Ok(())
+        Ok(content)
     }
 
     pub fn get_summary(&self) -> crate::core::StatusSummary { self.status_manager.get_summary() }
@@ -813,6 +2482,10 @@ impl Runner {
         Ok(())
     }
     pub fn status_manager(&self) -> &StatusManager { &self.status_manager }
+    /// Stream generated output to the given channel in addition to stdout
+    pub fn set_output_sink(&mut self, sink: tokio::sync::broadcast::Sender<String>) {
+        self.ollama.set_output_sink(sink);
+    }
     pub fn jobs_manager(&self) -> &JobsManager { &self.jobs_manager }
     
     pub fn cache_stats(&self) -> crate::core::file_cache::CacheStats {
@@ -0,0 +1,206 @@
+// Assembling a job's prompts without sending them (`worksplit prompt`)
+
+use std::fs;
+use std::path::PathBuf;
+
+use super::{edit, verify, Runner};
+use crate::core::{
+    assemble_creation_prompt, assemble_edit_prompt, assemble_report_prompt, assemble_sequential_creation_prompt,
+    assemble_sequential_split_prompt, assemble_split_plan_prompt, assemble_test_prompt,
+    assemble_verification_prompt_multi, assemble_verification_prompt_with_build, SYSTEM_PROMPT_CREATE,
+    SYSTEM_PROMPT_EDIT, SYSTEM_PROMPT_REPORT, SYSTEM_PROMPT_TEST, SYSTEM_PROMPT_VERIFY, SYSTEM_PROMPT_VERIFY_REPORT,
+    SYSTEM_PROMPT_VERIFY_RUBRIC,
+};
+use crate::core::targets::expand_target_files;
+use crate::error::WorkSplitError;
+use crate::models::Job;
+
+/// A step of a job that prompts the model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PromptPhase {
+    /// Test generation (TDD jobs)
+    Test,
+    /// Code, edit, split or report generation
+    Generate,
+    /// Verification of the job's current output
+    Verify,
+}
+
+/// One request as it would be sent to the model
+#[derive(Debug, Clone)]
+pub struct AssembledPrompt {
+    /// What the request is for, e.g. "edit prompt 1/2"
+    pub label: String,
+    /// System message; fill-in-the-middle requests have none
+    pub system: Option<&'static str>,
+    pub prompt: String,
+    /// Code after the fill site, sent with a fill-in-the-middle prompt
+    pub suffix: Option<String>,
+    /// How later requests of the phase differ, when they depend on answers
+    pub note: Option<String>,
+}
+
+impl AssembledPrompt {
+    fn new(label: impl Into<String>, system: &'static str, prompt: String) -> Self {
+        Self { label: label.into(), system: Some(system), prompt, suffix: None, note: None }
+    }
+
+    fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+}
+
+impl Runner {
+    /// The phase `worksplit run` would prompt for first: tests for TDD
+    /// jobs, otherwise generation
+    pub fn first_prompt_phase(job: &Job) -> PromptPhase {
+        if job.metadata.is_tdd_enabled() { PromptPhase::Test } else { PromptPhase::Generate }
+    }
+
+    /// Assemble the requests a run would send for `phase` of a job (its first
+    /// phase if `None`), with the same system prompts, conventions, context
+    /// and templates, without contacting the model
+    ///
+    /// Requests that depend on earlier answers (later files of a sequential
+    /// job, the modules of a planned split) are not included; the first
+    /// request notes them. Verification uses the job's current output and no
+    /// build output.
+    pub async fn assemble_prompts(&mut self, job_id: &str, phase: Option<PromptPhase>)
+        -> Result<(PromptPhase, Vec<AssembledPrompt>), WorkSplitError> {
+        let job = self.jobs_manager.parse_job_for_prompt(job_id)?;
+        let phase = phase.unwrap_or_else(|| Self::first_prompt_phase(&job));
+        let context_files = self.load_job_context(&job).await?;
+        let prompts = match phase {
+            PromptPhase::Test => vec![self.test_prompt(&job, &context_files)?],
+            PromptPhase::Generate => self.generation_prompts(&job, &context_files)?,
+            PromptPhase::Verify => vec![self.verification_prompt(&job, &context_files)?],
+        };
+        Ok((phase, prompts))
+    }
+
+    fn test_prompt(&self, job: &Job, context_files: &[(PathBuf, String)]) -> Result<AssembledPrompt, WorkSplitError> {
+        let test_path = job.metadata.test_path().ok_or_else(|| WorkSplitError::JobError(format!(
+            "Job '{}' is not a TDD job; it has no test phase", job.id)))?;
+        let test_prompt = self.jobs_manager.prompt_with_conventions(&self.jobs_manager.load_test_prompt()?, job)?;
+        let prompt = assemble_test_prompt(&self.prompt_templates, &test_prompt, context_files,
+            &job.instructions, &test_path.display().to_string());
+        Ok(AssembledPrompt::new("test generation", SYSTEM_PROMPT_TEST, prompt))
+    }
+
+    fn generation_prompts(&self, job: &Job, context_files: &[(PathBuf, String)]) -> Result<Vec<AssembledPrompt>, WorkSplitError> {
+        let generation_prompt = |default: &str| {
+            self.jobs_manager.prompt_with_conventions(&self.jobs_manager.generation_prompt(default, job)?, job)
+        };
+        let output_files = job.metadata.get_output_files();
+        let default_output_path = job.metadata.output_path().display().to_string();
+
+        if job.metadata.is_split_mode() {
+            let split_prompt = match (self.jobs_manager.load_split_prompt().ok(), &job.metadata.system_prompt) {
+                (None, None) => return Err(WorkSplitError::SystemPromptNotFound(
+                    self.jobs_manager.jobs_dir().join("_systemprompt_split.md"))),
+                (default, _) => generation_prompt(&default.unwrap_or_default())?,
+            };
+            let target_file_path = job.metadata.target_file.as_ref().unwrap();
+            let target_content = self.jobs_manager.load_target_file_unlimited(target_file_path)?;
+            let target = (target_file_path, target_content.as_str());
+            return Ok(vec![if job.metadata.is_planned_split() {
+                let prompt = assemble_split_plan_prompt(&self.prompt_templates, &split_prompt, target, context_files,
+                    &job.instructions, &output_files);
+                AssembledPrompt::new("split plan", SYSTEM_PROMPT_CREATE, prompt)
+                    .with_note(format!("Each of the {} modules is then generated from the plan", output_files.len()))
+            } else {
+                let prompt = assemble_sequential_split_prompt(&self.prompt_templates, &split_prompt, target, context_files,
+                    &[], &job.instructions, &output_files[0].display().to_string(), &output_files[1..]);
+                AssembledPrompt::new(format!("split file 1/{}", output_files.len()), SYSTEM_PROMPT_CREATE, prompt)
+                    .with_note("Later files are prompted with the files generated before them")
+            }]);
+        }
+
+        if job.metadata.is_report_mode() {
+            let prompt = assemble_report_prompt(&self.prompt_templates, context_files, &job.instructions,
+                &default_output_path, None);
+            return Ok(vec![AssembledPrompt::new("report", SYSTEM_PROMPT_REPORT, prompt)]);
+        }
+
+        if job.metadata.is_fim_edit() {
+            let request = edit::fim_request(&self.prompt_templates, &self.project_root, &self.output_root(),
+                &self.config, job, context_files)?;
+            return Ok(vec![AssembledPrompt {
+                label: format!("fill {} lines {}-{}", request.path.display(), request.start + 1, request.end),
+                system: None,
+                prompt: request.prompt,
+                suffix: Some(request.suffix),
+                note: None,
+            }]);
+        }
+
+        if job.metadata.is_edit_mode() {
+            let edit_prompt = generation_prompt(&self.jobs_manager.load_edit_prompt()?)?;
+            let target_files = expand_target_files(&self.project_root, &job.metadata.get_target_files())?;
+            let chunks = edit::edit_chunks(&self.project_root, &self.output_root(), &self.config, &target_files)?;
+            let total = chunks.len();
+            return Ok(chunks.iter().enumerate().map(|(index, chunk)| {
+                let prompt = assemble_edit_prompt(&self.prompt_templates, &edit_prompt, chunk, context_files, &job.instructions, self.config.behavior.regex_edits);
+                AssembledPrompt::new(format!("edit prompt {}/{}", index + 1, total), SYSTEM_PROMPT_EDIT, prompt)
+            }).collect());
+        }
+
+        let create_prompt = generation_prompt(&self.jobs_manager.load_create_prompt()?)?;
+        if job.metadata.is_sequential() {
+            let prompt = assemble_sequential_creation_prompt(&self.prompt_templates, &create_prompt, context_files,
+                &[], &job.instructions, &output_files[0].display().to_string(), &output_files[1..]);
+            return Ok(vec![AssembledPrompt::new(format!("file 1/{}", output_files.len()), SYSTEM_PROMPT_CREATE, prompt)
+                .with_note("Later files are prompted with the files generated before them")]);
+        }
+
+        let prompt = assemble_creation_prompt(&self.prompt_templates, &create_prompt, context_files,
+            &job.instructions, &default_output_path);
+        Ok(vec![AssembledPrompt::new("generation", SYSTEM_PROMPT_CREATE, prompt)])
+    }
+
+    fn verification_prompt(&self, job: &Job, context_files: &[(PathBuf, String)]) -> Result<AssembledPrompt, WorkSplitError> {
+        if !job.metadata.verify {
+            return Err(WorkSplitError::JobError(format!(
+                "Job '{}' sets verify: false; it has no verification phase", job.id)));
+        }
+
+        // The job's current output, staged copy first
+        let paths = if job.metadata.is_edit_mode() {
+            self.jobs_manager.resolve_target_files(job)?
+        } else {
+            job.metadata.get_output_files()
+        };
+        let mut generated_files = Vec::new();
+        for path in paths {
+            let staged = self.output_root().join(&path);
+            let source = if staged.exists() { staged } else { self.project_root.join(&path) };
+            let content = fs::read_to_string(&source).map_err(|_| WorkSplitError::JobError(format!(
+                "Job '{}' has no output at {} to verify; run it first", job.id, path.display())))?;
+            generated_files.push((path, content));
+        }
+
+        if job.metadata.is_report_mode() {
+            let context_files = self.fit_context("Verification", context_files,
+                &[verify::REPORT_VERIFY_PROMPT, &job.instructions], &generated_files);
+            let prompt = assemble_verification_prompt_multi(&self.prompt_templates, verify::REPORT_VERIFY_PROMPT,
+                &context_files, &generated_files, &job.instructions);
+            return Ok(AssembledPrompt::new("report verification", SYSTEM_PROMPT_VERIFY_REPORT, prompt));
+        }
+
+        let base = if job.metadata.is_edit_mode() {
+            self.jobs_manager.load_verify_edit_prompt()?
+        } else {
+            self.jobs_manager.load_verify_prompt()?
+        };
+        let verify_prompt = self.jobs_manager.verify_prompt_for_level(&base, job.metadata.verification, job.metadata.is_edit_mode())?;
+        let context_files = self.fit_context("Verification", context_files, &[&verify_prompt, &job.instructions], &generated_files);
+        let prompt = assemble_verification_prompt_with_build(&self.prompt_templates, &verify_prompt, &context_files,
+            &generated_files, &job.instructions, None);
+        let system = match self.config.verification.rubric_threshold() {
+            Some(_) => SYSTEM_PROMPT_VERIFY_RUBRIC,
+            None => SYSTEM_PROMPT_VERIFY,
+        };
+        Ok(AssembledPrompt::new("verification", system, prompt))
+    }
+}
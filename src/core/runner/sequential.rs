@@ -5,17 +5,26 @@ use crate::core::{
     assemble_sequential_creation_prompt, extract_code, extract_code_files, count_lines,
     OllamaClient, SYSTEM_PROMPT_CREATE,
 };
+use crate::core::atomic_write::write_atomic;
+use crate::core::injection;
+use crate::core::output_paths::{check_protected_path, check_write_path};
+use crate::core::postprocess::postprocess_with_env;
+use crate::core::progress::FileProgress;
+use crate::core::prompt_templates::PromptTemplates;
 use crate::error::WorkSplitError;
 use crate::models::{Config, Job};
 
-/// Process sequential mode job
+/// Process sequential mode job, writing each file under `output_root`
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn process_sequential_mode(
     ollama: &OllamaClient,
-    project_root: &Path,
+    templates: &PromptTemplates,
+    output_root: &Path,
     config: &Config,
     job: &Job,
     context_files: &[(PathBuf, String)],
     create_prompt: &str,
+    progress: &FileProgress,
 ) -> Result<(Vec<(PathBuf, String)>, Vec<PathBuf>, usize), WorkSplitError> {
     let output_files = job.metadata.get_output_files();
     let mut previously_generated: Vec<(PathBuf, String)> = Vec::new();
@@ -25,7 +34,7 @@ pub(crate) async fn process_sequential_mode(
 
     for (idx, output_path) in output_files.iter().enumerate() {
         let remaining: Vec<PathBuf> = output_files[idx + 1..].to_vec();
-        let prompt = assemble_sequential_creation_prompt(create_prompt, context_files,
+        let prompt = assemble_sequential_creation_prompt(templates, create_prompt, context_files,
             &previously_generated, &job.instructions, &output_path.display().to_string(), &remaining);
         
         let response = ollama.generate_with_retry(Some(SYSTEM_PROMPT_CREATE), &prompt, config.behavior.stream_output)
@@ -40,17 +49,23 @@ pub(crate) async fn process_sequential_mode(
         };
         total_lines += count_lines(&content);
         
-        let full_path = project_root.join(output_path);
+        let full_path = output_root.join(output_path);
+        check_write_path(output_root, &full_path, &config.safety.allowed_output_dirs)?;
+        check_protected_path(output_root, &full_path, &config.safety.protected_paths)?;
         if let Some(parent) = full_path.parent() {
-            if !parent.exists() && config.behavior.create_output_dirs { 
+            if !parent.exists() && (config.behavior.create_output_dirs || config.safety.read_only) { 
                 fs::create_dir_all(parent)?; 
             }
         }
-        fs::write(&full_path, &content)?;
+        let content = postprocess_with_env(&config.postprocess, output_root, output_path, &content, &job.metadata.env);
+        injection::check_write(config.safety.injection_check, output_root, &full_path, &content)?;
+        write_atomic(&full_path, &content)?;
+        ollama.record_write(&full_path, &content);
         
         previously_generated.push((output_path.clone(), content.clone()));
         generated_files.push((output_path.clone(), content));
         full_output_paths.push(full_path);
+        progress.inc();
     }
 
     Ok((generated_files, full_output_paths, total_lines))
@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use futures::stream::{self, StreamExt};
+use tracing::info;
+
+use crate::core::module_index::is_index_file;
+use crate::core::progress::FileProgress;
+use crate::core::{
+    assemble_planned_split_prompt, assemble_sequential_split_prompt, assemble_split_plan_prompt,
+    extract_code, extract_code_files, OllamaClient, SYSTEM_PROMPT_CREATE,
+};
+use crate::core::prompt_templates::PromptTemplates;
+use crate::error::WorkSplitError;
+use crate::models::{Config, Job};
+
+/// Content of the single file in a generation response
+fn response_content(response: &str) -> String {
+    let extracted = extract_code_files(response);
+    if extracted.is_empty() { extract_code(response) } else { extracted[0].content.clone() }
+}
+
+/// Generate one module of a planned split (not streamed)
+async fn generate_module(ollama: &OllamaClient, path: PathBuf, prompt: String, progress: &FileProgress) -> Result<(PathBuf, String), WorkSplitError> {
+    let response = ollama.generate_with_retry(Some(SYSTEM_PROMPT_CREATE), &prompt, false).await?;
+    progress.inc();
+    Ok((path, response_content(&response)))
+}
+
+/// Process a `split_strategy: planned` split job
+///
+/// 1. One prompt plans the split: an interface stub for every output file.
+/// 2. All non-index modules are generated concurrently against that plan,
+///    at most `limits.max_split_concurrency` at a time. Responses are not
+///    streamed since they would interleave.
+/// 3. `mod.rs`/`index.ts`-style files are generated last, seeing every
+///    generated module.
+///
+/// Returns the generated files in `output_files` order; nothing is written.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn process_planned_split(
+    ollama: &OllamaClient,
+    templates: &PromptTemplates,
+    config: &Config,
+    job: &Job,
+    target: (&PathBuf, &str),
+    context_files: &[(PathBuf, String)],
+    split_prompt: &str,
+    progress: &FileProgress,
+) -> Result<Vec<(PathBuf, String)>, WorkSplitError> {
+    let output_files = job.metadata.get_output_files();
+
+    info!("Planned split: planning interfaces for {} file(s)", output_files.len());
+    let plan_prompt = assemble_split_plan_prompt(templates, split_prompt, target, context_files, &job.instructions, &output_files);
+    let plan_response = ollama
+        .generate_with_retry(Some(SYSTEM_PROMPT_CREATE), &plan_prompt, config.behavior.stream_output)
+        .await?;
+    let plan: Vec<(PathBuf, String)> = extract_code_files(&plan_response)
+        .into_iter()
+        .filter_map(|file| file.path.map(|path| (path, file.content)))
+        .collect();
+    if plan.is_empty() {
+        return Err(WorkSplitError::EmptyExtraction(
+            "Split plan contained no ~~~worksplit:path interface stubs".to_string(),
+        ));
+    }
+
+    let (indexes, modules): (Vec<&PathBuf>, Vec<&PathBuf>) =
+        output_files.iter().partition(|path| is_index_file(path));
+
+    info!("Planned split: generating {} module(s) concurrently", modules.len());
+    let prompts: Vec<(PathBuf, String)> = modules
+        .iter()
+        .map(|&path| {
+            let prompt = assemble_planned_split_prompt(templates, split_prompt, target, context_files, &plan,
+                &job.instructions, &path.display().to_string());
+            (path.clone(), prompt)
+        })
+        .collect();
+    let mut generated: Vec<(PathBuf, String)> = stream::iter(prompts)
+        .map(|(path, prompt)| generate_module(ollama, path, prompt, progress))
+        .buffered(config.limits.max_split_concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<_, _>>()?;
+
+    for index in indexes {
+        info!("Planned split: stitching {}", index.display());
+        let prompt = assemble_sequential_split_prompt(templates, split_prompt, target, context_files, &generated,
+            &job.instructions, &index.display().to_string(), &[]);
+        let response = ollama
+            .generate_with_retry(Some(SYSTEM_PROMPT_CREATE), &prompt, config.behavior.stream_output)
+            .await?;
+        generated.push((index.clone(), response_content(&response)));
+        progress.inc();
+    }
+
+    if let Some((path, _)) = generated.iter().find(|(_, content)| content.trim().is_empty()) {
+        return Err(WorkSplitError::EmptyExtraction(format!("Split produced no content for {}", path.display())));
+    }
+
+    generated.sort_by_key(|(path, _)| output_files.iter().position(|p| p == path));
+    Ok(generated)
+}
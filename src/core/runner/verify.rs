@@ -1,28 +1,38 @@
-use std::path::PathBuf;
-use tracing::info;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
 
+use crate::core::external_verify::{merge_verification, ExternalVerifier};
 use crate::core::{
-    assemble_verification_prompt_multi, assemble_retry_prompt_multi, extract_code_files,
-    parse_verification, OllamaClient, VerificationResult,
-    SYSTEM_PROMPT_VERIFY, SYSTEM_PROMPT_RETRY,
+    assemble_report_prompt, assemble_verification_prompt_multi, assemble_verification_prompt_with_build,
+    assemble_retry_followup_prompt, assemble_retry_prompt_multi, extract_code_files, extract_report,
+    parse_rubric, parse_verification, rubric_verdict, trim_conversation, ChatMessage, OllamaClient, VerificationResult,
+    SYSTEM_PROMPT_REPORT, SYSTEM_PROMPT_VERIFY, SYSTEM_PROMPT_RETRY, SYSTEM_PROMPT_VERIFY_REPORT, SYSTEM_PROMPT_VERIFY_RUBRIC,
 };
+use crate::core::prompt_templates::PromptTemplates;
 use crate::error::WorkSplitError;
+use crate::models::{RubricScores, VerificationLevel};
 
 /// Run verification on generated files
+///
+/// `build_output` is the build/lint output for these files, when
+/// `verify_build` ran, plus the job's `test_scope` output; it's included as
+/// a `[BUILD OUTPUT]` section.
 pub(crate) async fn run_verification(
     ollama: &OllamaClient,
+    templates: &PromptTemplates,
     verify_prompt: &str,
     context_files: &[(PathBuf, String)],
     generated_files: &[(PathBuf, String)],
     instructions: &str,
+    build_output: Option<&str>,
 ) -> Result<(VerificationResult, Option<String>), WorkSplitError> {
     let file_names: Vec<_> = generated_files.iter()
         .map(|(p, _)| p.display().to_string())
         .collect();
     info!("Starting verification of {} file(s): {:?}", generated_files.len(), file_names);
     
-    let verify_prompt_str = assemble_verification_prompt_multi(verify_prompt, context_files,
-        generated_files, instructions);
+    let verify_prompt_str = assemble_verification_prompt_with_build(templates, verify_prompt, context_files,
+        generated_files, instructions, build_output);
     
     info!("Verification prompt size: {} chars", verify_prompt_str.len());
     
@@ -37,16 +47,151 @@ pub(crate) async fn run_verification(
     Ok((result, error))
 }
 
+/// `instructions` for verifying `file` on its own, one of the job's
+/// `generated_files`
+pub(crate) fn file_scope_instructions(instructions: &str, file: &Path, generated_files: &[(PathBuf, String)]) -> String {
+    let others: Vec<String> = generated_files
+        .iter()
+        .filter(|(path, _)| path != file)
+        .map(|(path, _)| format!("`{}`", path.display()))
+        .collect();
+    format!(
+        "{}\n\n## Verification Scope\n\nThis job generates {} files, which are verified one at a time. \
+         Verify only `{}`, against the parts of the instructions above that concern it. \
+         The other files ({}) are verified separately, so don't fail this file because they aren't shown.",
+        instructions.trim_end(),
+        generated_files.len(),
+        file.display(),
+        others.join(", ")
+    )
+}
+
+/// Score generated files on the rubric and judge the scores against
+/// `threshold`
+///
+/// Takes the same prompt as [`run_verification`]. An answer that isn't a
+/// valid rubric is read as a PASS/FAIL verdict and returns no scores.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn run_rubric_verification(
+    ollama: &OllamaClient,
+    templates: &PromptTemplates,
+    verify_prompt: &str,
+    context_files: &[(PathBuf, String)],
+    generated_files: &[(PathBuf, String)],
+    instructions: &str,
+    build_output: Option<&str>,
+    threshold: f32,
+) -> Result<((VerificationResult, Option<String>), Option<RubricScores>), WorkSplitError> {
+    info!("Starting rubric verification of {} file(s)", generated_files.len());
+    let prompt = assemble_verification_prompt_with_build(templates, verify_prompt, context_files,
+        generated_files, instructions, build_output);
+    let response = ollama.generate_with_retry(Some(SYSTEM_PROMPT_VERIFY_RUBRIC), &prompt, false).await?;
+
+    match parse_rubric(&response) {
+        Some((scores, summary)) => {
+            info!("Rubric scores: {}", scores);
+            Ok((rubric_verdict(&scores, summary.as_deref(), threshold), Some(scores)))
+        }
+        None => {
+            warn!("Verifier didn't answer with rubric scores; reading it as a verdict");
+            Ok((parse_verification(&response), None))
+        }
+    }
+}
+
+/// Verifier instructions for report mode documents
+pub(super) const REPORT_VERIFY_PROMPT: &str =
+    "Check that the document below covers every requirement in the original instructions.";
+
+/// Verify a report mode document against the content requirements in the
+/// instructions (the project's code-oriented verify prompt doesn't apply)
+pub(crate) async fn run_report_verification(
+    ollama: &OllamaClient,
+    templates: &PromptTemplates,
+    context_files: &[(PathBuf, String)],
+    report: &[(PathBuf, String)],
+    instructions: &str,
+) -> Result<(VerificationResult, Option<String>), WorkSplitError> {
+    info!("Starting verification of report: {:?}", report.iter().map(|(p, _)| p).collect::<Vec<_>>());
+    let prompt = assemble_verification_prompt_multi(
+        templates,
+        REPORT_VERIFY_PROMPT,
+        context_files,
+        report,
+        instructions,
+    );
+    let response = ollama.generate_with_retry(Some(SYSTEM_PROMPT_VERIFY_REPORT), &prompt, false).await?;
+    let (result, error) = parse_verification(&response);
+    info!("Verification result: {:?}", result);
+    Ok((result, error))
+}
+
+/// Ask the external verification service (if configured) for its verdict and
+/// merge it with the local result.
+///
+/// If the service is unreachable the local result stands, unless the service
+/// is marked `required`, in which case the job soft-fails.
+pub(crate) async fn run_external_verification(
+    external: Option<&ExternalVerifier>,
+    job_id: &str,
+    instructions: &str,
+    generated_files: &[(PathBuf, String)],
+    local: (VerificationResult, Option<String>),
+) -> (VerificationResult, Option<String>) {
+    let Some(verifier) = external else {
+        return local;
+    };
+
+    match verifier.verify(job_id, instructions, generated_files, (local.0, local.1.as_deref())).await {
+        Ok(verdict) => {
+            for finding in &verdict.findings {
+                info!("External finding: {}", finding);
+            }
+            merge_verification(local, &verdict)
+        }
+        Err(e) if verifier.is_required() => {
+            warn!("{}", e);
+            (VerificationResult::FailSoft, Some(e.to_string()))
+        }
+        Err(e) => {
+            warn!("{} - keeping local verification result", e);
+            local
+        }
+    }
+}
+
+/// Apply a job's verification level to a verdict: lenient jobs pass soft
+/// failures with a warning, strict jobs turn warnings into soft failures
+/// (which the retry ladder then retries)
+pub(crate) fn apply_verification_level(
+    level: VerificationLevel,
+    (result, error): (VerificationResult, Option<String>),
+) -> (VerificationResult, Option<String>) {
+    match (level, result) {
+        (VerificationLevel::Lenient, VerificationResult::FailSoft) => {
+            warn!("Soft failure accepted (verification: lenient): {}", error.as_deref().unwrap_or("no reason given"));
+            (VerificationResult::PassWithWarnings, None)
+        }
+        (VerificationLevel::Strict, VerificationResult::PassWithWarnings) => {
+            let reason = error.unwrap_or_else(|| "Verification passed with warnings".to_string());
+            info!("Warnings fail the job (verification: strict): {}", reason);
+            (VerificationResult::FailSoft, Some(reason))
+        }
+        _ => (result, error),
+    }
+}
+
 /// Run retry logic for failed verification
 pub(crate) async fn run_retry(
     ollama: &OllamaClient,
+    templates: &PromptTemplates,
     create_prompt: &str,
     context_files: &[(PathBuf, String)],
     generated_files: &[(PathBuf, String)],
     instructions: &str,
     error_msg: &str,
 ) -> Result<Vec<(PathBuf, String)>, WorkSplitError> {
-    let retry_prompt = assemble_retry_prompt_multi(create_prompt, context_files,
+    let retry_prompt = assemble_retry_prompt_multi(templates, create_prompt, context_files,
         instructions, generated_files, error_msg);
     let retry_response = ollama.generate_with_retry(Some(SYSTEM_PROMPT_RETRY), &retry_prompt, true)
         .await
@@ -61,4 +206,55 @@ pub(crate) async fn run_retry(
     }
     
     Ok(retry_files)
-}
\ No newline at end of file
+}
+
+/// Retry as the next turn of the creation conversation: the verification
+/// feedback becomes a user message after the model's previous answer
+///
+/// Older attempts are dropped to keep the conversation within `max_tokens`.
+/// Returns `None`, leaving the conversation unchanged, if even the latest
+/// attempt doesn't fit.
+pub(crate) async fn run_conversation_retry(
+    ollama: &OllamaClient,
+    templates: &PromptTemplates,
+    conversation: &mut Vec<ChatMessage>,
+    previous_outputs: &[(PathBuf, String)],
+    error_msg: &str,
+    max_tokens: usize,
+) -> Result<Option<Vec<(PathBuf, String)>>, WorkSplitError> {
+    let output_files: Vec<PathBuf> = previous_outputs.iter().map(|(path, _)| path.clone()).collect();
+    let mut messages = conversation.clone();
+    messages.push(ChatMessage::user(assemble_retry_followup_prompt(templates, &output_files, error_msg)));
+    if !trim_conversation(&mut messages, max_tokens * 4) {
+        warn!("Retry conversation exceeds {} tokens; retrying with a fresh prompt", max_tokens);
+        return Ok(None);
+    }
+
+    let response = ollama.chat_with_retry(&messages, true).await.map_err(WorkSplitError::Ollama)?;
+    // A single output may come back as a plain code block, as it did the first time
+    let single = (output_files.len() == 1).then(|| output_files[0].clone());
+    let retry_files = extract_code_files(&response)
+        .into_iter()
+        .filter_map(|file| file.path.or_else(|| single.clone()).map(|path| (path, file.content)))
+        .collect();
+
+    messages.push(ChatMessage::assistant(response));
+    *conversation = messages;
+    Ok(Some(retry_files))
+}
+
+/// Rewrite a report mode document after failed verification
+pub(crate) async fn run_report_retry(
+    ollama: &OllamaClient,
+    templates: &PromptTemplates,
+    context_files: &[(PathBuf, String)],
+    previous: &(PathBuf, String),
+    instructions: &str,
+    error_msg: &str,
+) -> Result<Vec<(PathBuf, String)>, WorkSplitError> {
+    let (path, report) = previous;
+    let prompt = assemble_report_prompt(templates, context_files, instructions, &path.display().to_string(),
+        Some((report, error_msg)));
+    let response = ollama.generate_with_retry(Some(SYSTEM_PROMPT_REPORT), &prompt, true).await?;
+    Ok(vec![(path.clone(), extract_report(&response))])
+}
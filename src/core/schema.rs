@@ -0,0 +1,170 @@
+//! JSON Schemas for job frontmatter, `worksplit.toml` and `_jobstatus.json`
+//!
+//! Schemas are derived from the model types, so they always match what the
+//! parser accepts. `worksplit schema dump` writes them out for editors and
+//! external tooling; `worksplit validate` checks files against them. Both
+//! need the "schema" feature.
+
+#[cfg(feature = "schema")]
+use schemars::schema_for;
+use serde_json::Value;
+use std::path::Path;
+
+use crate::error::WorkSplitError;
+#[cfg(feature = "schema")]
+use crate::models::{Config, JobMetadata, StatusFile};
+
+/// Which schema to generate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SchemaKind {
+    /// Job file YAML frontmatter
+    Job,
+    /// worksplit.toml
+    Config,
+    /// jobs/_jobstatus.json
+    Status,
+}
+
+impl SchemaKind {
+    pub const ALL: [SchemaKind; 3] = [SchemaKind::Job, SchemaKind::Config, SchemaKind::Status];
+
+    /// File name used when writing the schema to disk
+    pub fn file_name(self) -> &'static str {
+        match self {
+            SchemaKind::Job => "job.schema.json",
+            SchemaKind::Config => "worksplit.schema.json",
+            SchemaKind::Status => "jobstatus.schema.json",
+        }
+    }
+}
+
+/// Generate the schema for `kind`
+#[cfg(feature = "schema")]
+pub fn schema_for_kind(kind: SchemaKind) -> Value {
+    let schema = match kind {
+        SchemaKind::Job => schema_for!(JobMetadata),
+        SchemaKind::Config => schema_for!(Config),
+        SchemaKind::Status => schema_for!(StatusFile),
+    };
+    schema.to_value()
+}
+
+/// Pretty-printed schema, as written by `schema dump`
+#[cfg(feature = "schema")]
+pub fn schema_json(kind: SchemaKind) -> String {
+    let mut json = serde_json::to_string_pretty(&schema_for_kind(kind)).unwrap_or_default();
+    json.push('\n');
+    json
+}
+
+/// Validate a document against a schema, returning one message per violation
+#[cfg(feature = "schema")]
+pub fn validate_value(kind: SchemaKind, instance: &Value) -> Vec<String> {
+    let schema = schema_for_kind(kind);
+    let validator = match jsonschema::validator_for(&schema) {
+        Ok(validator) => validator,
+        Err(e) => return vec![format!("Invalid {:?} schema: {}", kind, e)],
+    };
+
+    validator
+        .iter_errors(instance)
+        .map(|error| {
+            let path = error.instance_path().to_string();
+            if path.is_empty() {
+                error.to_string()
+            } else {
+                format!("{}: {}", path.trim_start_matches('/'), error)
+            }
+        })
+        .collect()
+}
+
+/// Without the "schema" feature there are no schemas to check against
+#[cfg(not(feature = "schema"))]
+pub fn validate_value(_kind: SchemaKind, _instance: &Value) -> Vec<String> {
+    Vec::new()
+}
+
+/// Read a job file's frontmatter as JSON
+pub fn job_frontmatter(path: &Path) -> Result<Value, WorkSplitError> {
+    use gray_matter::engine::YAML;
+    use gray_matter::Matter;
+
+    let content = std::fs::read_to_string(path)?;
+    let parsed = Matter::<YAML>::new().parse(&content);
+    let data = parsed
+        .data
+        .ok_or_else(|| WorkSplitError::JobError(format!("{}: no frontmatter found", path.display())))?;
+    data.deserialize()
+        .map_err(|e| WorkSplitError::JobError(format!("{}: {}", path.display(), e)))
+}
+
+/// Read `worksplit.toml` as JSON
+pub fn config_document(path: &Path) -> Result<Value, WorkSplitError> {
+    let content = std::fs::read_to_string(path)?;
+    let value: toml::Value = toml::from_str(&content)
+        .map_err(|e| WorkSplitError::JobError(format!("{}: {}", path.display(), e)))?;
+    serde_json::to_value(value).map_err(|e| WorkSplitError::JobError(e.to_string()))
+}
+
+#[cfg(all(test, feature = "schema"))]
+mod tests {
+    use super::*;
+    use crate::models::JobStatusEntry;
+    use serde_json::json;
+
+    #[test]
+    fn test_published_schemas_are_current() {
+        // Regenerate with: worksplit schema dump --out schemas/
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas");
+        for kind in SchemaKind::ALL {
+            let published = std::fs::read_to_string(dir.join(kind.file_name())).unwrap();
+            assert_eq!(published, schema_json(kind), "schemas/{} is out of date", kind.file_name());
+        }
+    }
+
+    #[test]
+    fn test_job_schema_accepts_valid_frontmatter() {
+        let job = json!({
+            "context_files": ["src/lib.rs"],
+            "output_dir": "src/",
+            "output_file": "out.rs",
+            "mode": "edit",
+            "target_files": ["src/handlers/"],
+            "context_truncation": "signatures_only"
+        });
+        assert!(validate_value(SchemaKind::Job, &job).is_empty());
+    }
+
+    #[test]
+    fn test_job_schema_rejects_unknown_and_mistyped_fields() {
+        let job = json!({
+            "contxt_files": ["src/lib.rs"],
+            "output_dir": "src/",
+            "output_file": "out.rs",
+            "mode": "rewrite"
+        });
+        let errors = validate_value(SchemaKind::Job, &job);
+        assert!(errors.iter().any(|e| e.contains("contxt_files")), "{:?}", errors);
+        assert!(errors.iter().any(|e| e.starts_with("mode:")), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_config_schema() {
+        let config = json!({ "ollama": { "model": "qwen3" }, "limits": { "max_output_lines": 500 } });
+        assert!(validate_value(SchemaKind::Config, &config).is_empty());
+
+        let config = json!({ "ollama": { "modle": "qwen3" } });
+        assert_eq!(validate_value(SchemaKind::Config, &config).len(), 1);
+    }
+
+    #[test]
+    fn test_status_schema_matches_serialized_entries() {
+        let file = StatusFile {
+            jobs: vec![JobStatusEntry::new("001_test".to_string())],
+            ..StatusFile::default()
+        };
+        let value = serde_json::to_value(&file).unwrap();
+        assert!(validate_value(SchemaKind::Status, &value).is_empty());
+    }
+}
@@ -0,0 +1,334 @@
+//! Directory-based job queue (`jobs/queue/`, `jobs/running/`, `jobs/done/`)
+//!
+//! `worksplit enqueue` moves job files into `queue/`. A worker claims one by
+//! creating `running/<id>.claim.json`, naming the worker, and then moving the
+//! job file into `running/`. The claim file is written in full under a
+//! temporary name and hard-linked into place, which fails if it exists, so
+//! on a shared filesystem exactly one of several racing workers gets each
+//! job. When the job is finished its file moves to `done/` next to a
+//! `done/<id>.json` result.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use crate::error::WorkSplitError;
+use crate::models::JobStatus;
+
+pub const QUEUE_DIR: &str = "queue";
+pub const RUNNING_DIR: &str = "running";
+pub const DONE_DIR: &str = "done";
+
+/// Who claimed a running job, and when (`running/<id>.claim.json`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claim {
+    pub job_id: String,
+    pub worker: String,
+    pub claimed_at: DateTime<Utc>,
+}
+
+/// Outcome of a spooled job (`done/<id>.json`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpoolResult {
+    pub job_id: String,
+    pub worker: String,
+    pub status: JobStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub claimed_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+
+/// Number of jobs in each spool directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpoolCounts {
+    pub queued: usize,
+    pub running: usize,
+    pub done: usize,
+}
+
+/// The spool under a jobs folder
+pub struct Spool {
+    jobs_dir: PathBuf,
+}
+
+impl Spool {
+    pub fn new(jobs_dir: &Path) -> Self {
+        Self { jobs_dir: jobs_dir.to_path_buf() }
+    }
+
+    fn dir(&self, name: &str) -> PathBuf {
+        self.jobs_dir.join(name)
+    }
+
+    fn job_file(&self, dir: &str, job_id: &str) -> PathBuf {
+        self.dir(dir).join(format!("{}.md", job_id))
+    }
+
+    fn claim_file(&self, job_id: &str) -> PathBuf {
+        self.dir(RUNNING_DIR).join(format!("{}.claim.json", job_id))
+    }
+
+    /// Whether any spool directory exists
+    pub fn exists(&self) -> bool {
+        [QUEUE_DIR, RUNNING_DIR, DONE_DIR].iter().any(|dir| self.dir(dir).is_dir())
+    }
+
+    /// Move `jobs/<id>.md` into the queue
+    pub fn enqueue(&self, job_id: &str) -> Result<(), WorkSplitError> {
+        let source = self.jobs_dir.join(format!("{}.md", job_id));
+        if !source.exists() {
+            return Err(WorkSplitError::JobError(format!("Job file not found: {}", source.display())));
+        }
+        for dir in [QUEUE_DIR, RUNNING_DIR] {
+            if self.job_file(dir, job_id).exists() {
+                return Err(WorkSplitError::JobError(format!("Job '{}' is already in jobs/{}/", job_id, dir)));
+            }
+        }
+        fs::create_dir_all(self.dir(QUEUE_DIR))?;
+        fs::rename(&source, self.job_file(QUEUE_DIR, job_id))?;
+        Ok(())
+    }
+
+    /// IDs of the jobs in one spool directory, sorted
+    pub fn job_ids(&self, dir: &str) -> Result<Vec<String>, WorkSplitError> {
+        let dir = self.dir(dir);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut ids: Vec<String> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+            .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    pub fn counts(&self) -> Result<SpoolCounts, WorkSplitError> {
+        Ok(SpoolCounts {
+            queued: self.job_ids(QUEUE_DIR)?.len(),
+            running: self.job_ids(RUNNING_DIR)?.len(),
+            done: self.job_ids(DONE_DIR)?.len(),
+        })
+    }
+
+    /// Claim the first queued job for `worker`, or `None` when the queue is
+    /// empty. Jobs another worker takes first are skipped.
+    pub fn claim(&self, worker: &str) -> Result<Option<Claim>, WorkSplitError> {
+        fs::create_dir_all(self.dir(RUNNING_DIR))?;
+        for job_id in self.job_ids(QUEUE_DIR)? {
+            let target = self.job_file(RUNNING_DIR, &job_id);
+            if target.exists() {
+                warn!("Job '{}' is queued and running at once; leaving it queued", job_id);
+                continue;
+            }
+            let claim = Claim { job_id, worker: worker.to_string(), claimed_at: Utc::now() };
+            if !self.write_claim(&claim)? {
+                // Another worker claimed it first
+                continue;
+            }
+            if let Err(e) = fs::rename(self.job_file(QUEUE_DIR, &claim.job_id), &target) {
+                let _ = fs::remove_file(self.claim_file(&claim.job_id));
+                if e.kind() == ErrorKind::NotFound {
+                    continue;
+                }
+                return Err(e.into());
+            }
+            info!("Claimed job '{}' as {}", claim.job_id, worker);
+            return Ok(Some(claim));
+        }
+        Ok(None)
+    }
+
+    /// Create the claim file, or return `false` if the job is already
+    /// claimed. It is written under a temporary name first and hard-linked
+    /// into place, so it never exists half-written.
+    fn write_claim(&self, claim: &Claim) -> Result<bool, WorkSplitError> {
+        let worker = claim.worker.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_");
+        let temp = self.dir(RUNNING_DIR).join(format!(".{}.{}.claim", claim.job_id, worker));
+        write_json(&temp, claim)?;
+        let linked = fs::hard_link(&temp, self.claim_file(&claim.job_id));
+        let _ = fs::remove_file(&temp);
+        match linked {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Path of a claimed job's file
+    pub fn running_file(&self, claim: &Claim) -> PathBuf {
+        self.job_file(RUNNING_DIR, &claim.job_id)
+    }
+
+    /// Move a claimed job to `done/` with its result
+    pub fn finish(&self, claim: &Claim, status: JobStatus, error: Option<String>) -> Result<SpoolResult, WorkSplitError> {
+        let result = SpoolResult {
+            job_id: claim.job_id.clone(),
+            worker: claim.worker.clone(),
+            status,
+            error,
+            claimed_at: claim.claimed_at,
+            finished_at: Utc::now(),
+        };
+        fs::create_dir_all(self.dir(DONE_DIR))?;
+        write_json(&self.dir(DONE_DIR).join(format!("{}.json", claim.job_id)), &result)?;
+        fs::rename(self.running_file(claim), self.job_file(DONE_DIR, &claim.job_id))?;
+        let _ = fs::remove_file(self.claim_file(&claim.job_id));
+        Ok(result)
+    }
+
+    /// The result of a finished job
+    pub fn result(&self, job_id: &str) -> Option<SpoolResult> {
+        let content = fs::read_to_string(self.dir(DONE_DIR).join(format!("{}.json", job_id))).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// IDs of the jobs with a claim file
+    fn claimed_ids(&self) -> Result<Vec<String>, WorkSplitError> {
+        let dir = self.dir(RUNNING_DIR);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut ids: Vec<String> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().to_str()?.strip_suffix(".claim.json").map(str::to_string))
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Put jobs claimed longer than `max_age` ago back in the queue, for
+    /// workers that died mid-job. Returns their IDs. Only the claim file
+    /// counts: the job file keeps its age from the queue.
+    pub fn requeue_stale(&self, max_age: Duration) -> Result<Vec<String>, WorkSplitError> {
+        let cutoff = Utc::now() - max_age;
+        let mut requeued = Vec::new();
+        for job_id in self.claimed_ids()? {
+            let claim: Option<Claim> = fs::read_to_string(self.claim_file(&job_id))
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok());
+            let claimed_at = match &claim {
+                Some(claim) => claim.claimed_at,
+                None => match fs::metadata(self.claim_file(&job_id)).and_then(|m| m.modified()) {
+                    Ok(modified) => modified.into(),
+                    Err(_) => continue,
+                },
+            };
+            if claimed_at > cutoff {
+                continue;
+            }
+            fs::create_dir_all(self.dir(QUEUE_DIR))?;
+            let moved = match fs::rename(self.job_file(RUNNING_DIR, &job_id), self.job_file(QUEUE_DIR, &job_id)) {
+                Ok(()) => true,
+                // The worker died before moving the job file out of the
+                // queue, or after finishing it
+                Err(e) if e.kind() == ErrorKind::NotFound => false,
+                Err(e) => return Err(e.into()),
+            };
+            let _ = fs::remove_file(self.claim_file(&job_id));
+            if moved {
+                let worker = claim.map_or_else(|| "unknown worker".to_string(), |c| c.worker);
+                warn!("Requeued job '{}' claimed by {} at {}", job_id, worker, claimed_at);
+                requeued.push(job_id);
+            }
+        }
+        Ok(requeued)
+    }
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), WorkSplitError> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| WorkSplitError::JobError(format!("Failed to serialize {}: {}", path.display(), e)))?;
+    crate::core::atomic_write::write_atomic(path, &json)?;
+    Ok(())
+}
+
+/// Name identifying this worker process: host name and process ID
+pub fn default_worker_name() -> String {
+    let host = std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|host| host.trim().to_string())
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| "localhost".to_string());
+    format!("{}-{}", host, std::process::id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn spool_with_jobs(ids: &[&str]) -> (TempDir, Spool) {
+        let temp_dir = TempDir::new().unwrap();
+        let spool = Spool::new(temp_dir.path());
+        for id in ids {
+            fs::write(temp_dir.path().join(format!("{}.md", id)), "---\n---\n").unwrap();
+            spool.enqueue(id).unwrap();
+        }
+        (temp_dir, spool)
+    }
+
+    #[test]
+    fn test_claim_and_finish_move_the_job_file() {
+        let (temp_dir, spool) = spool_with_jobs(&["b", "a"]);
+        assert!(spool.enqueue("a").is_err());
+        assert_eq!(spool.counts().unwrap(), SpoolCounts { queued: 2, running: 0, done: 0 });
+
+        let claim = spool.claim("w1").unwrap().unwrap();
+        assert_eq!(claim.job_id, "a");
+        assert!(spool.running_file(&claim).exists());
+        assert!(temp_dir.path().join("running/a.claim.json").exists());
+
+        let other = spool.claim("w2").unwrap().unwrap();
+        assert_eq!(other.job_id, "b");
+        assert!(spool.claim("w3").unwrap().is_none());
+
+        spool.finish(&claim, JobStatus::Pass, None).unwrap();
+        assert!(temp_dir.path().join("done/a.md").exists());
+        assert!(!temp_dir.path().join("running/a.claim.json").exists());
+        let result = spool.result("a").unwrap();
+        assert_eq!((result.status, result.worker.as_str()), (JobStatus::Pass, "w1"));
+        assert_eq!(spool.counts().unwrap(), SpoolCounts { queued: 0, running: 1, done: 1 });
+    }
+
+    #[test]
+    fn test_requeue_stale_claims() {
+        let (temp_dir, spool) = spool_with_jobs(&["a"]);
+        spool.claim("w1").unwrap().unwrap();
+        assert!(spool.requeue_stale(Duration::hours(1)).unwrap().is_empty());
+
+        assert_eq!(spool.requeue_stale(Duration::zero()).unwrap(), vec!["a"]);
+        assert!(temp_dir.path().join("queue/a.md").exists());
+        assert!(!temp_dir.path().join("running/a.claim.json").exists());
+    }
+
+    #[test]
+    fn test_claims_are_keyed_on_the_claim_file() {
+        // A job file that sat in the queue for a day is old, but its claim is not
+        let (temp_dir, spool) = spool_with_jobs(&["a", "b"]);
+        let day_old = std::time::SystemTime::now() - std::time::Duration::from_secs(86_400);
+        fs::File::options().write(true).open(temp_dir.path().join("queue/a.md")).unwrap().set_modified(day_old).unwrap();
+        let claim = spool.claim("w1").unwrap().unwrap();
+        assert_eq!(claim.job_id, "a");
+        assert!(spool.requeue_stale(Duration::minutes(1)).unwrap().is_empty());
+        assert!(spool.running_file(&claim).exists());
+
+        // A job someone else holds the claim to is skipped, and a claim
+        // left behind by a worker that died before moving the job is cleared
+        let stale = Claim { job_id: "b".to_string(), worker: "w0".to_string(), claimed_at: Utc::now() - Duration::hours(2) };
+        assert!(spool.write_claim(&stale).unwrap());
+        assert!(!spool.write_claim(&Claim { worker: "w2".to_string(), ..stale.clone() }).unwrap());
+        assert!(spool.claim("w2").unwrap().is_none());
+        assert!(spool.requeue_stale(Duration::hours(1)).unwrap().is_empty());
+        assert!(!temp_dir.path().join("running/b.claim.json").exists());
+        assert_eq!(spool.claim("w2").unwrap().unwrap().job_id, "b");
+        assert_eq!(fs::read_dir(temp_dir.path().join("running")).unwrap().count(), 4);
+    }
+}
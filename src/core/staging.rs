@@ -0,0 +1,205 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::core::atomic_write::write_atomic;
+use crate::error::WorkSplitError;
+use crate::models::JobStatus;
+
+/// Staging folder inside jobs/ used by read-only (plan-only) runs
+pub const STAGING_DIR: &str = "_staging";
+/// Manifest listing which job produced which staged file
+const MANIFEST_FILE: &str = "_manifest.json";
+
+/// A job whose outputs were written to the staging area
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StagedJob {
+    pub job_id: String,
+    pub status: JobStatus,
+    /// Paths relative to the project root
+    pub files: Vec<PathBuf>,
+}
+
+/// Staging area for read-only runs: mirrors the project tree under
+/// `jobs/_staging/` so outputs can be reviewed before `worksplit apply`.
+pub struct StagingArea {
+    root: PathBuf,
+}
+
+impl StagingArea {
+    pub fn new(jobs_dir: &Path) -> Self {
+        Self { root: jobs_dir.join(STAGING_DIR) }
+    }
+
+    /// Root of the staged tree
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Load the manifest (empty if nothing has been staged)
+    pub fn load_manifest(&self) -> Result<Vec<StagedJob>, WorkSplitError> {
+        let path = self.root.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| WorkSplitError::JobError(format!("Invalid staging manifest {}: {}", path.display(), e)))
+    }
+
+    fn save_manifest(&self, jobs: &[StagedJob]) -> Result<(), WorkSplitError> {
+        fs::create_dir_all(&self.root)?;
+        let content = serde_json::to_string_pretty(jobs)
+            .map_err(|e| WorkSplitError::JobError(e.to_string()))?;
+        fs::write(self.root.join(MANIFEST_FILE), content)?;
+        Ok(())
+    }
+
+    /// Record (or replace) the staged outputs of a job
+    pub fn record(&self, job_id: &str, status: JobStatus, files: Vec<PathBuf>) -> Result<(), WorkSplitError> {
+        let mut jobs = self.load_manifest()?;
+        jobs.retain(|j| j.job_id != job_id);
+        jobs.push(StagedJob { job_id: job_id.to_string(), status, files });
+        jobs.sort_by(|a, b| a.job_id.cmp(&b.job_id));
+        self.save_manifest(&jobs)
+    }
+
+    /// Attribute a job's staged outputs to its new ID; returns whether it
+    /// had any
+    pub fn rename_job(&self, job_id: &str, new_id: &str) -> Result<bool, WorkSplitError> {
+        let mut jobs = self.load_manifest()?;
+        let Some(job) = jobs.iter_mut().find(|j| j.job_id == job_id) else {
+            return Ok(false);
+        };
+        job.job_id = new_id.to_string();
+        jobs.sort_by(|a, b| a.job_id.cmp(&b.job_id));
+        self.save_manifest(&jobs)?;
+        Ok(true)
+    }
+
+    /// Copy staged files into the project tree and drop them from staging.
+    ///
+    /// Applies every staged job, or only `job_id` when given. Returns the
+    /// project-relative paths that were (or, with `dry_run`, would be) written.
+    pub fn apply(
+        &self,
+        project_root: &Path,
+        job_id: Option<&str>,
+        dry_run: bool,
+    ) -> Result<Vec<PathBuf>, WorkSplitError> {
+        let jobs = self.load_manifest()?;
+        let (selected, remaining): (Vec<StagedJob>, Vec<StagedJob>) = jobs
+            .into_iter()
+            .partition(|j| job_id.is_none_or(|id| j.job_id == id));
+
+        if let Some(id) = job_id {
+            if selected.is_empty() {
+                return Err(WorkSplitError::JobError(format!("No staged outputs for job '{}'", id)));
+            }
+        }
+
+        let mut applied = Vec::new();
+        for job in &selected {
+            for file in &job.files {
+                let staged = self.root.join(file);
+                if !staged.exists() {
+                    continue;
+                }
+                if !dry_run {
+                    let target = project_root.join(file);
+                    if let Some(parent) = target.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    write_atomic(&target, fs::read(&staged)?)?;
+                    info!("Applied {} ({})", file.display(), job.job_id);
+                }
+                applied.push(file.clone());
+            }
+        }
+
+        if !dry_run {
+            // Only remove staged copies no remaining job still refers to
+            for file in &applied {
+                if !remaining.iter().any(|j| j.files.contains(file)) {
+                    let _ = fs::remove_file(self.root.join(file));
+                }
+            }
+            if remaining.is_empty() {
+                let _ = fs::remove_dir_all(&self.root);
+            } else {
+                self.save_manifest(&remaining)?;
+            }
+        }
+
+        Ok(applied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn stage(area: &StagingArea, job_id: &str, file: &str, content: &str) {
+        let path = area.root().join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+        area.record(job_id, JobStatus::Pass, vec![PathBuf::from(file)]).unwrap();
+    }
+
+    #[test]
+    fn test_record_replaces_previous_entry() {
+        let temp_dir = TempDir::new().unwrap();
+        let area = StagingArea::new(temp_dir.path());
+
+        area.record("001", JobStatus::Fail, vec![PathBuf::from("src/a.rs")]).unwrap();
+        area.record("001", JobStatus::Pass, vec![PathBuf::from("src/b.rs")]).unwrap();
+
+        let manifest = area.load_manifest().unwrap();
+        assert_eq!(manifest.len(), 1);
+        assert_eq!(manifest[0].status, JobStatus::Pass);
+        assert_eq!(manifest[0].files, vec![PathBuf::from("src/b.rs")]);
+    }
+
+    #[test]
+    fn test_apply_single_job() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_root = temp_dir.path();
+        let area = StagingArea::new(&project_root.join("jobs"));
+        stage(&area, "001", "src/a.rs", "fn a() {}");
+        stage(&area, "002", "src/b.rs", "fn b() {}");
+
+        let applied = area.apply(project_root, Some("001"), false).unwrap();
+        assert_eq!(applied, vec![PathBuf::from("src/a.rs")]);
+        assert_eq!(fs::read_to_string(project_root.join("src/a.rs")).unwrap(), "fn a() {}");
+        assert!(!project_root.join("src/b.rs").exists());
+
+        let manifest = area.load_manifest().unwrap();
+        assert_eq!(manifest.len(), 1);
+        assert_eq!(manifest[0].job_id, "002");
+    }
+
+    #[test]
+    fn test_apply_all_dry_run_then_real() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_root = temp_dir.path();
+        let area = StagingArea::new(&project_root.join("jobs"));
+        stage(&area, "001", "src/a.rs", "fn a() {}");
+
+        let applied = area.apply(project_root, None, true).unwrap();
+        assert_eq!(applied.len(), 1);
+        assert!(!project_root.join("src/a.rs").exists());
+
+        area.apply(project_root, None, false).unwrap();
+        assert!(project_root.join("src/a.rs").exists());
+        assert!(!area.root().exists());
+    }
+
+    #[test]
+    fn test_apply_unknown_job() {
+        let temp_dir = TempDir::new().unwrap();
+        let area = StagingArea::new(temp_dir.path());
+        assert!(area.apply(temp_dir.path(), Some("missing"), false).is_err());
+    }
+}
@@ -1,84 +1,190 @@
-use std::collections::HashMap;
-use std::fs;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 use tracing::{debug, info, warn};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::core::atomic_write::write_atomic;
+#[cfg(feature = "sqlite")]
+use crate::core::history::{self, HISTORY_FILE};
+#[cfg(feature = "sqlite")]
+use crate::core::status_db::StatusDb;
+use crate::core::status_webhook::{StatusTransition, StatusWebhook};
 use crate::error::StatusError;
-use crate::models::{JobStatus, JobStatusEntry, PartialEditState, FailedEdit};
+use crate::models::{
+    StatusBackend, FailureKind, JobStatus, JobStatusEntry, PartialEditState, FailedEdit, RubricScores, StatusFile, STATUS_FILE_VERSION,
+};
 
 /// Thread-safe wrapper for StatusManager
 pub type SharedStatusManager = Arc<RwLock<StatusManager>>;
 
+/// Status file name inside jobs/
+pub const STATUS_FILE: &str = "_jobstatus.json";
+/// Status database inside jobs/, used instead of the status file once it
+/// exists ("sqlite" feature)
+pub const STATUS_DB_FILE: &str = "_jobstatus.db";
+
 /// Status file manager
 pub struct StatusManager {
     /// Path to the status file
     status_file: PathBuf,
     /// In-memory cache of status entries
     entries: HashMap<String, JobStatusEntry>,
+    /// Entries as of the last load or save, when the file is shared with
+    /// other processes (see [`StatusManager::set_shared`])
+    baseline: Option<HashMap<String, JobStatusEntry>>,
+    /// The status database, replacing the status file when it exists
+    #[cfg(feature = "sqlite")]
+    db: Option<StatusDb>,
+    /// Where status transitions are posted (`[status_webhook]`)
+    webhook: Option<StatusWebhook>,
 }
 
 impl StatusManager {
-    /// Create a new status manager and load existing status
+    /// Create a new status manager and load existing status, from
+    /// `_jobstatus.db` if there is one
     pub fn new(jobs_dir: &Path) -> Result<Self, StatusError> {
-        let status_file = jobs_dir.join("_jobstatus.json");
+        let status_file = jobs_dir.join(STATUS_FILE);
+        let db_file = jobs_dir.join(STATUS_DB_FILE);
         let mut manager = Self {
             status_file,
             entries: HashMap::new(),
+            baseline: None,
+            #[cfg(feature = "sqlite")]
+            db: None,
+            webhook: None,
         };
+        if db_file.exists() {
+            #[cfg(feature = "sqlite")]
+            {
+                manager.db = Some(StatusDb::open(&db_file)?);
+            }
+            #[cfg(not(feature = "sqlite"))]
+            return Err(StatusError::Database(db_file, SQLITE_DISABLED.to_string()));
+        }
         manager.load()?;
         Ok(manager)
     }
 
+    /// Create a status manager storing status in `backend`. The first time
+    /// SQLite is used, the status file and run history move into
+    /// `_jobstatus.db`.
+    pub fn with_backend(jobs_dir: &Path, backend: StatusBackend) -> Result<Self, StatusError> {
+        if backend == StatusBackend::Sqlite && !jobs_dir.join(STATUS_DB_FILE).exists() {
+            #[cfg(feature = "sqlite")]
+            migrate_to_sqlite(jobs_dir)?;
+            #[cfg(not(feature = "sqlite"))]
+            return Err(StatusError::Database(jobs_dir.join(STATUS_DB_FILE), SQLITE_DISABLED.to_string()));
+        }
+        Self::new(jobs_dir)
+    }
+
     /// Create a new thread-safe shared status manager
     pub fn new_shared(jobs_dir: &Path) -> Result<SharedStatusManager, StatusError> {
         let manager = Self::new(jobs_dir)?;
         Ok(Arc::new(RwLock::new(manager)))
     }
 
-    /// Load status from file
+    /// Load status from the database or file
     fn load(&mut self) -> Result<(), StatusError> {
+        self.entries = self.read()?;
+        info!("Loaded {} job status entries", self.entries.len());
+        Ok(())
+    }
+
+    /// Entries as currently stored
+    fn read(&mut self) -> Result<HashMap<String, JobStatusEntry>, StatusError> {
+        #[cfg(feature = "sqlite")]
+        if let Some(db) = &mut self.db {
+            return db.load();
+        }
+        self.read_file()
+    }
+
+    fn read_file(&self) -> Result<HashMap<String, JobStatusEntry>, StatusError> {
         if !self.status_file.exists() {
             debug!("Status file does not exist, starting fresh");
-            return Ok(());
+            return Ok(HashMap::new());
         }
 
         let content = fs::read_to_string(&self.status_file)
             .map_err(|e| StatusError::ReadError(self.status_file.clone(), e))?;
 
         if content.trim().is_empty() {
-            return Ok(());
+            return Ok(HashMap::new());
         }
 
-        let entries: Vec<JobStatusEntry> = serde_json::from_str(&content)
-            .map_err(|e| StatusError::ParseError(self.status_file.clone(), e.to_string()))?;
+        let parse_error = |message: String| StatusError::ParseError(self.status_file.clone(), message);
+        let document: Value = serde_json::from_str(&content).map_err(|e| parse_error(e.to_string()))?;
+        let (document, version) = migrate_status_document(document).map_err(parse_error)?;
+        let file: StatusFile = serde_json::from_value(document).map_err(|e| parse_error(e.to_string()))?;
 
-        self.entries = entries.into_iter().map(|e| (e.id.clone(), e)).collect();
-        info!("Loaded {} job status entries", self.entries.len());
+        if version < STATUS_FILE_VERSION && !file.jobs.is_empty() {
+            // Keep the original around; the next save writes the new format
+            let backup = self.status_file.with_file_name(format!("_jobstatus.v{}.json", version));
+            if !backup.exists() {
+                fs::write(&backup, &content).map_err(|e| StatusError::WriteError(backup.clone(), e))?;
+            }
+            info!(
+                "Migrated {} from format version {} to {} (original kept as {})",
+                self.status_file.display(),
+                version,
+                STATUS_FILE_VERSION,
+                backup.display()
+            );
+        }
 
-        Ok(())
+        Ok(file.jobs.into_iter().map(|e| (e.id.clone(), e)).collect())
+    }
+
+    /// Share the status file with other processes, e.g. spool workers on
+    /// other machines: every save takes `_jobstatus.lock`, re-reads the
+    /// file and only overwrites the entries this manager changed
+    pub fn set_shared(&mut self, shared: bool) {
+        self.baseline = shared.then(|| self.entries.clone());
     }
 
     /// Save status to file atomically (write to temp, then rename)
-    pub fn save(&self) -> Result<(), StatusError> {
-        let entries: Vec<&JobStatusEntry> = self.entries.values().collect();
-        let mut sorted_entries: Vec<_> = entries.into_iter().collect();
+    pub fn save(&mut self) -> Result<(), StatusError> {
+        let Some(baseline) = &self.baseline else { return self.write() };
+
+        let _lock = StatusLock::acquire(&self.status_file.with_file_name("_jobstatus.lock"))
+            .map_err(|e| StatusError::WriteError(self.status_file.clone(), e))?;
+        let baseline = baseline.clone();
+        let on_disk = self.read()?;
+        self.entries = merge_entries(&baseline, std::mem::take(&mut self.entries), on_disk);
+        self.baseline = Some(self.entries.clone());
+        self.write()
+    }
+
+    fn write(&mut self) -> Result<(), StatusError> {
+        #[cfg(feature = "sqlite")]
+        if let Some(db) = &mut self.db {
+            let count = db.save(&self.entries)?;
+            debug!("Saved {} changed job status entries", count);
+            return Ok(());
+        }
+
+        let mut sorted_entries: Vec<JobStatusEntry> = self.entries.values().cloned().collect();
         sorted_entries.sort_by(|a, b| a.id.cmp(&b.id));
+        let count = sorted_entries.len();
+        let file = StatusFile {
+            jobs: sorted_entries,
+            ..StatusFile::default()
+        };
 
-        let json = serde_json::to_string_pretty(&sorted_entries)
+        let json = serde_json::to_string_pretty(&file)
             .map_err(|e| StatusError::ParseError(self.status_file.clone(), e.to_string()))?;
 
-        // Write to temporary file first
-        let temp_file = self.status_file.with_extension("json.tmp");
-        fs::write(&temp_file, &json)
-            .map_err(|e| StatusError::WriteError(temp_file.clone(), e))?;
-
-        // Rename atomically
-        fs::rename(&temp_file, &self.status_file)
+        // Temporary file, fsync, rename: a crash never leaves a truncated file
+        write_atomic(&self.status_file, &json)
             .map_err(|e| StatusError::WriteError(self.status_file.clone(), e))?;
 
-        debug!("Saved {} job status entries", sorted_entries.len());
+        debug!("Saved {} job status entries", count);
         Ok(())
     }
 
@@ -111,6 +217,46 @@ impl StatusManager {
         self.save()
     }
 
+    /// Mark exactly the jobs in `drafts` as drafts, so they aren't scheduled
+    pub fn sync_drafts(&mut self, drafts: &[String]) -> Result<(), StatusError> {
+        let mut changed = false;
+        for entry in self.entries.values_mut() {
+            let draft = drafts.contains(&entry.id);
+            if entry.draft != draft {
+                entry.draft = draft;
+                changed = true;
+            }
+        }
+        if changed {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Post status transitions to `webhook` from now on
+    pub fn set_status_webhook(&mut self, webhook: StatusWebhook) {
+        self.webhook = Some(webhook);
+    }
+
+    /// Post the change of `job_id` from `from` to its current status, if
+    /// the status changed and a webhook is set
+    fn notify_transition(&self, job_id: &str, from: JobStatus) {
+        let (Some(webhook), Some(entry)) = (&self.webhook, self.entries.get(job_id)) else {
+            return;
+        };
+        if entry.status == from {
+            return;
+        }
+        webhook.send(StatusTransition {
+            job_id: job_id.to_string(),
+            from,
+            to: entry.status,
+            error: entry.error.clone(),
+            outputs: entry.outputs.clone(),
+            timestamp: chrono::Utc::now(),
+        });
+    }
+
     /// Get a job's status
     pub fn get(&self, job_id: &str) -> Option<&JobStatusEntry> {
         self.entries.get(job_id)
@@ -125,28 +271,96 @@ impl StatusManager {
     pub fn update_status(&mut self, job_id: &str, status: JobStatus) -> Result<(), StatusError> {
         let entry = self.entries.get_mut(job_id)
             .ok_or_else(|| StatusError::JobNotFound(job_id.to_string()))?;
+        let from = entry.status;
         entry.update_status(status);
-        self.save()
+        self.save()?;
+        self.notify_transition(job_id, from);
+        Ok(())
     }
 
     /// Update multiple job statuses in a single atomic write
     pub fn update_statuses_batch(&mut self, updates: &[(String, JobStatus)]) -> Result<(), StatusError> {
+        let mut changed = Vec::new();
         for (job_id, status) in updates {
             if let Some(entry) = self.entries.get_mut(job_id) {
+                changed.push((job_id, entry.status));
                 entry.update_status(*status);
             }
         }
-        self.save()
+        self.save()?;
+        for (job_id, from) in changed {
+            self.notify_transition(job_id, from);
+        }
+        Ok(())
     }
 
     /// Set a job as failed with an error message
     pub fn set_failed(&mut self, job_id: &str, error: String) -> Result<(), StatusError> {
         let entry = self.entries.get_mut(job_id)
             .ok_or_else(|| StatusError::JobNotFound(job_id.to_string()))?;
+        let from = entry.status;
         entry.set_failed(error);
+        self.save()?;
+        self.notify_transition(job_id, from);
+        Ok(())
+    }
+
+    /// Record why a failed or partial job didn't pass
+    pub fn set_failure(&mut self, job_id: &str, kind: FailureKind) -> Result<(), StatusError> {
+        let entry = self.entries.get_mut(job_id)
+            .ok_or_else(|| StatusError::JobNotFound(job_id.to_string()))?;
+        entry.failure = Some(kind);
+        self.save()
+    }
+
+    /// Record the scores of a job's latest verification (`None` when it
+    /// wasn't scored on the rubric)
+    pub fn set_rubric(&mut self, job_id: &str, rubric: Option<RubricScores>) -> Result<(), StatusError> {
+        let entry = self.entries.get_mut(job_id)
+            .ok_or_else(|| StatusError::JobNotFound(job_id.to_string()))?;
+        entry.rubric = rubric;
         self.save()
     }
 
+    /// Record the files a job generated, taking them over from the jobs
+    /// that owned them before
+    pub fn set_outputs(&mut self, job_id: &str, outputs: Vec<PathBuf>) -> Result<(), StatusError> {
+        if !self.entries.contains_key(job_id) {
+            return Err(StatusError::JobNotFound(job_id.to_string()));
+        }
+        for entry in self.entries.values_mut() {
+            if entry.id == job_id {
+                entry.outputs = outputs.clone();
+            } else {
+                entry.outputs.retain(|path| !outputs.contains(path));
+            }
+        }
+        self.save()
+    }
+
+    /// The job that generated `path` last
+    pub fn owner_of(&self, path: &Path) -> Option<&JobStatusEntry> {
+        self.entries.values().find(|entry| entry.outputs.iter().any(|p| p == path))
+    }
+
+    /// Failed and partial jobs grouped by failure kind (unclassified ones
+    /// count as `other`), job IDs sorted
+    pub fn failures_by_kind(&self) -> BTreeMap<FailureKind, Vec<String>> {
+        let mut by_kind: BTreeMap<FailureKind, Vec<String>> = BTreeMap::new();
+        for entry in self.entries.values() {
+            if matches!(entry.status, JobStatus::Fail | JobStatus::Partial) {
+                by_kind
+                    .entry(entry.failure.unwrap_or(FailureKind::Other))
+                    .or_default()
+                    .push(entry.id.clone());
+            }
+        }
+        for ids in by_kind.values_mut() {
+            ids.sort();
+        }
+        by_kind
+    }
+
     /// Get all jobs with a specific status
     pub fn get_by_status(&self, status: JobStatus) -> Vec<&JobStatusEntry> {
         self.entries
@@ -167,7 +381,7 @@ impl StatusManager {
     pub fn get_ready_jobs(&self) -> Vec<&JobStatusEntry> {
         self.entries
             .values()
-            .filter(|e| e.status.is_ready() && !e.ran)
+            .filter(|e| e.status.is_ready() && !e.ran && !e.draft)
             .collect()
     }
 
@@ -176,7 +390,7 @@ impl StatusManager {
     pub fn get_ready_jobs_include_ran(&self) -> Vec<&JobStatusEntry> {
         self.entries
             .values()
-            .filter(|e| e.status.is_ready())
+            .filter(|e| e.status.is_ready() && !e.draft)
             .collect()
     }
 
@@ -203,6 +417,9 @@ impl StatusManager {
                 JobStatus::Fail => summary.failed += 1,
                 JobStatus::Partial => summary.partial += 1,
             }
+            if entry.draft {
+                summary.draft += 1;
+            }
         }
         summary.total = self.entries.len();
         summary
@@ -212,13 +429,47 @@ impl StatusManager {
     pub fn reset_job(&mut self, job_id: &str) -> Result<(), StatusError> {
         let entry = self.entries.get_mut(job_id)
             .ok_or_else(|| StatusError::JobNotFound(job_id.to_string()))?;
+        let from = entry.status;
         entry.update_status(JobStatus::Created);
         entry.error = None;
         entry.partial_state = None;
+        entry.failure = None;
+        entry.rubric = None;
         entry.ran = false;
+        self.save()?;
+        self.notify_transition(job_id, from);
+        Ok(())
+    }
+
+    /// Replace the entries of the given jobs, e.g. with entries from
+    /// another machine
+    pub fn import_entries(&mut self, entries: Vec<JobStatusEntry>) -> Result<(), StatusError> {
+        for entry in entries {
+            self.entries.insert(entry.id.clone(), entry);
+        }
+        self.save()
+    }
+
+    /// Move a job's entry to a new ID, keeping its status and history
+    pub fn rename_job(&mut self, job_id: &str, new_id: &str) -> Result<(), StatusError> {
+        if self.entries.contains_key(new_id) {
+            return Err(StatusError::JobExists(new_id.to_string()));
+        }
+        let mut entry = self.entries.remove(job_id)
+            .ok_or_else(|| StatusError::JobNotFound(job_id.to_string()))?;
+        entry.id = new_id.to_string();
+        self.entries.insert(new_id.to_string(), entry);
         self.save()
     }
 
+    /// Drop a job's entry, e.g. once its file has left jobs/
+    pub fn remove_job(&mut self, job_id: &str) -> Result<(), StatusError> {
+        if self.entries.remove(job_id).is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+
     /// Mark a job as having been run (regardless of outcome)
     pub fn mark_ran(&mut self, job_id: &str) -> Result<(), StatusError> {
         let entry = self.entries.get_mut(job_id)
@@ -236,8 +487,11 @@ impl StatusManager {
     pub fn set_partial(&mut self, job_id: &str, state: PartialEditState) -> Result<(), StatusError> {
         let entry = self.entries.get_mut(job_id)
             .ok_or_else(|| StatusError::JobNotFound(job_id.to_string()))?;
+        let from = entry.status;
         entry.set_partial(state);
-        self.save()
+        self.save()?;
+        self.notify_transition(job_id, from);
+        Ok(())
     }
 
     /// Get the failed edits for a partial job (for --continue)
@@ -264,8 +518,173 @@ impl StatusManager {
     }
 }
 
+#[cfg(not(feature = "sqlite"))]
+const SQLITE_DISABLED: &str = "status is kept in SQLite, but this build of worksplit lacks the \"sqlite\" feature";
+
+/// Move `_jobstatus.json` and `_history.jsonl` into a new `_jobstatus.db`,
+/// keeping them as `*.migrated`. The database is built under a temporary
+/// name, so an interrupted migration leaves the JSON files in charge.
+#[cfg(feature = "sqlite")]
+fn migrate_to_sqlite(jobs_dir: &Path) -> Result<(), StatusError> {
+    let db_file = jobs_dir.join(STATUS_DB_FILE);
+    let temp_file = jobs_dir.join(format!("{}.tmp", STATUS_DB_FILE));
+    let _ = fs::remove_file(&temp_file);
+
+    let file = StatusManager::new(jobs_dir)?;
+    let events = history::load(jobs_dir);
+    {
+        let mut db = StatusDb::open(&temp_file)?;
+        db.save(&file.entries)?;
+        for event in &events {
+            db.append_event(event)?;
+        }
+    }
+    fs::rename(&temp_file, &db_file).map_err(|e| StatusError::WriteError(db_file.clone(), e))?;
+
+    for name in [STATUS_FILE, HISTORY_FILE] {
+        let path = jobs_dir.join(name);
+        if path.exists() {
+            if let Err(e) = fs::rename(&path, jobs_dir.join(format!("{}.migrated", name))) {
+                warn!("Failed to set {} aside after migrating it: {}", path.display(), e);
+            }
+        }
+    }
+    info!(
+        "Moved {} job status entries and {} history events into {}",
+        file.entries.len(),
+        events.len(),
+        db_file.display()
+    );
+    Ok(())
+}
+
+/// Combine this process's entries with the ones on disk: entries changed
+/// since `baseline` (including added and removed ones) are ours, the rest
+/// are whatever another process saved. A job this process only just
+/// discovered keeps the entry another process already has for it.
+fn merge_entries(
+    baseline: &HashMap<String, JobStatusEntry>,
+    mut ours: HashMap<String, JobStatusEntry>,
+    mut on_disk: HashMap<String, JobStatusEntry>,
+) -> HashMap<String, JobStatusEntry> {
+    let same = |a: &JobStatusEntry, b: &JobStatusEntry| serde_json::to_value(a).ok() == serde_json::to_value(b).ok();
+    let ids: Vec<String> = baseline.keys().chain(ours.keys()).chain(on_disk.keys()).cloned().collect();
+    let mut merged = HashMap::new();
+    for id in ids {
+        if merged.contains_key(&id) {
+            continue;
+        }
+        let entry = match (baseline.get(&id), ours.remove(&id)) {
+            (None, None) => on_disk.remove(&id),
+            (None, Some(entry)) => on_disk.remove(&id).or(Some(entry)),
+            (Some(before), Some(entry)) if same(before, &entry) => on_disk.remove(&id),
+            (Some(_), entry) => entry,
+        };
+        if let Some(entry) = entry {
+            merged.insert(id, entry);
+        }
+    }
+    merged
+}
+
+/// Exclusive lock on the status file between processes, released on drop.
+/// A lock older than [`StatusLock::STALE_AFTER`] was left by a process
+/// that died and is broken.
+struct StatusLock {
+    path: PathBuf,
+}
+
+impl StatusLock {
+    const STALE_AFTER: Duration = Duration::from_secs(30);
+    const TIMEOUT: Duration = Duration::from_secs(60);
+
+    fn acquire(path: &Path) -> std::io::Result<Self> {
+        let started = SystemTime::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(_) => return Ok(Self { path: path.to_path_buf() }),
+                Err(e) if e.kind() != std::io::ErrorKind::AlreadyExists => return Err(e),
+                Err(e) => {
+                    let age = fs::metadata(path)
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok());
+                    if age.is_some_and(|age| age > Self::STALE_AFTER) {
+                        warn!("Breaking stale lock {}", path.display());
+                        let _ = fs::remove_file(path);
+                        continue;
+                    }
+                    if started.elapsed().unwrap_or_default() > Self::TIMEOUT {
+                        return Err(std::io::Error::new(e.kind(), format!("timed out waiting for {}", path.display())));
+                    }
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for StatusLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Upgrade a parsed `_jobstatus.json` document to the current format.
+/// Returns the upgraded document and the version it was written in. Files
+/// from a newer release are refused rather than loaded with fields dropped.
+pub fn migrate_status_document(document: Value) -> Result<(Value, u32), String> {
+    let version = match &document {
+        // Version 1 predates the `version` field
+        Value::Array(_) => 1,
+        Value::Object(map) => map
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| "missing format \"version\"".to_string())? as u32,
+        _ => return Err("expected an object with \"version\" and \"jobs\"".to_string()),
+    };
+    if version > STATUS_FILE_VERSION {
+        return Err(format!(
+            "format version {} was written by a newer worksplit (this one supports up to {})",
+            version, STATUS_FILE_VERSION
+        ));
+    }
+
+    let mut document = document;
+    for from in version..STATUS_FILE_VERSION {
+        document = match from {
+            1 => migrate_v1(document),
+            _ => unreachable!("no migration from status format version {}", from),
+        };
+    }
+    Ok((document, version))
+}
+
+/// Version 1 -> 2: wrap the entry array and fill in fields older releases
+/// didn't write
+fn migrate_v1(document: Value) -> Value {
+    let Value::Array(mut jobs) = document else { return document };
+    for job in &mut jobs {
+        let Value::Object(entry) = job else { continue };
+        if !entry.contains_key("ran") {
+            // Finished jobs count as run, so they aren't redone after upgrading
+            let finished = matches!(entry.get("status").and_then(Value::as_str), Some("pass" | "fail" | "partial"));
+            entry.insert("ran".to_string(), Value::Bool(finished));
+        }
+        if entry.get("partial_state").is_some_and(Value::is_null) {
+            entry.remove("partial_state");
+        }
+        if !entry.contains_key("updated_at") {
+            if let Some(created_at) = entry.get("created_at").cloned() {
+                entry.insert("updated_at".to_string(), created_at);
+            }
+        }
+    }
+    json!({ "version": 2, "jobs": jobs })
+}
+
 /// Summary of job statuses
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize)]
 pub struct StatusSummary {
     pub total: usize,
     pub created: usize,
@@ -276,6 +695,8 @@ pub struct StatusSummary {
     pub passed: usize,
     pub failed: usize,
     pub partial: usize,
+    /// Jobs marked `draft: true` (also counted under their status)
+    pub draft: usize,
 }
 
 impl std::fmt::Display for StatusSummary {
@@ -290,7 +711,11 @@ impl std::fmt::Display for StatusSummary {
             self.partial,
             self.passed,
             self.failed
-        )
+        )?;
+        if self.draft > 0 {
+            write!(f, " | Draft: {}", self.draft)?;
+        }
+        Ok(())
     }
 }
 
@@ -311,6 +736,21 @@ mod tests {
         assert!(manager.entries.is_empty());
     }
 
+    #[test]
+    fn test_set_outputs_takes_ownership() {
+        let (_temp_dir, mut manager) = create_test_manager();
+        manager.sync_with_jobs(&["job1".to_string(), "job2".to_string()]).unwrap();
+
+        manager.set_outputs("job1", vec![PathBuf::from("src/a.rs"), PathBuf::from("src/b.rs")]).unwrap();
+        assert_eq!(manager.owner_of(Path::new("src/a.rs")).unwrap().id, "job1");
+
+        manager.set_outputs("job2", vec![PathBuf::from("src/a.rs")]).unwrap();
+        assert_eq!(manager.owner_of(Path::new("src/a.rs")).unwrap().id, "job2");
+        assert_eq!(manager.get("job1").unwrap().outputs, vec![PathBuf::from("src/b.rs")]);
+        assert!(manager.owner_of(Path::new("src/c.rs")).is_none());
+        assert!(manager.set_outputs("missing", Vec::new()).is_err());
+    }
+
     #[test]
     fn test_sync_with_jobs() {
         let (_temp_dir, mut manager) = create_test_manager();
@@ -323,6 +763,25 @@ mod tests {
         assert_eq!(manager.get("job1").unwrap().status, JobStatus::Created);
     }
 
+    #[test]
+    fn test_drafts_are_not_ready() {
+        let (_temp_dir, mut manager) = create_test_manager();
+        let ids = vec!["job1".to_string(), "job2".to_string()];
+        manager.sync_with_jobs(&ids).unwrap();
+        manager.sync_drafts(&["job2".to_string()]).unwrap();
+
+        let ready: Vec<&str> = manager.get_ready_jobs().iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ready, vec!["job1"]);
+        assert_eq!(manager.get_ready_jobs_include_ran().len(), 1);
+        let summary = manager.get_summary();
+        assert_eq!((summary.created, summary.draft), (2, 1));
+        assert!(summary.to_string().ends_with(" | Draft: 1"));
+
+        // Removing the flag makes the job schedulable again
+        manager.sync_drafts(&[]).unwrap();
+        assert_eq!(manager.get_ready_jobs().len(), 2);
+    }
+
     #[test]
     fn test_update_status() {
         let (_temp_dir, mut manager) = create_test_manager();
@@ -345,6 +804,31 @@ mod tests {
         assert_eq!(entry.error, Some("Test error".to_string()));
     }
 
+    #[test]
+    fn test_failures_by_kind() {
+        let (_temp_dir, mut manager) = create_test_manager();
+        let ids: Vec<String> = ["job1", "job2", "job3", "job4"].iter().map(|s| s.to_string()).collect();
+        manager.sync_with_jobs(&ids).unwrap();
+
+        for id in ["job1", "job3"] {
+            manager.set_failed(id, "Build failed".to_string()).unwrap();
+            manager.set_failure(id, FailureKind::BuildError).unwrap();
+        }
+        manager.set_failed("job2", "Cancelled by user".to_string()).unwrap();
+        manager.update_status("job4", JobStatus::Pass).unwrap();
+
+        let by_kind = manager.failures_by_kind();
+        assert_eq!(by_kind[&FailureKind::BuildError], vec!["job1", "job3"]);
+        assert_eq!(by_kind[&FailureKind::Other], vec!["job2"]);
+        assert_eq!(by_kind.len(), 2);
+
+        // Persisted, and cleared once the job is reset
+        let reloaded = StatusManager::new(manager.status_file.parent().unwrap()).unwrap();
+        assert_eq!(reloaded.get("job1").unwrap().failure, Some(FailureKind::BuildError));
+        manager.reset_job("job1").unwrap();
+        assert_eq!(manager.get("job1").unwrap().failure, None);
+    }
+
     #[test]
     fn test_get_summary() {
         let (_temp_dir, mut manager) = create_test_manager();
@@ -378,6 +862,98 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_shared_managers_keep_each_others_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut first = StatusManager::new(temp_dir.path()).unwrap();
+        first.sync_with_jobs(&["a".to_string(), "b".to_string(), "c".to_string()]).unwrap();
+        first.update_status("c", JobStatus::Pass).unwrap();
+
+        let mut second = StatusManager::new(temp_dir.path()).unwrap();
+        first.set_shared(true);
+        second.set_shared(true);
+
+        first.update_status("a", JobStatus::PendingWork).unwrap();
+        // `second` still holds `a` as created; saving must not undo `first`
+        second.update_status("b", JobStatus::Fail).unwrap();
+        // A job `first` just discovered keeps the entry `second` has
+        second.sync_with_jobs(&["a".to_string(), "b".to_string(), "c".to_string(), "d".to_string()]).unwrap();
+        second.update_status("d", JobStatus::PendingWork).unwrap();
+        first.sync_with_jobs(&["a".to_string(), "b".to_string(), "d".to_string()]).unwrap();
+        first.update_status("a", JobStatus::Pass).unwrap();
+
+        let loaded = StatusManager::new(temp_dir.path()).unwrap();
+        assert_eq!(loaded.get("a").unwrap().status, JobStatus::Pass);
+        assert_eq!(loaded.get("b").unwrap().status, JobStatus::Fail);
+        assert!(loaded.get("c").is_none());
+        assert_eq!(loaded.get("d").unwrap().status, JobStatus::PendingWork);
+        assert_eq!(first.get("b").unwrap().status, JobStatus::Fail);
+        assert!(!temp_dir.path().join("_jobstatus.lock").exists());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_backend_takes_over_status_and_history() {
+        use crate::core::history::{self, HistoryEvent, HISTORY_FILE};
+
+        let temp_dir = TempDir::new().unwrap();
+        let jobs_dir = temp_dir.path();
+        let mut manager = StatusManager::new(jobs_dir).unwrap();
+        manager.sync_with_jobs(&["a".to_string(), "b".to_string()]).unwrap();
+        manager.update_status("a", JobStatus::Pass).unwrap();
+        let run = HistoryEvent::Run { started_at: chrono::Utc::now(), jobs: 2 };
+        history::append(jobs_dir, &run).unwrap();
+
+        let mut manager = StatusManager::with_backend(jobs_dir, StatusBackend::Sqlite).unwrap();
+        assert!(jobs_dir.join(STATUS_DB_FILE).exists());
+        assert!(!jobs_dir.join(STATUS_FILE).exists());
+        assert!(jobs_dir.join(format!("{}.migrated", HISTORY_FILE)).exists());
+        assert_eq!(manager.get("a").unwrap().status, JobStatus::Pass);
+        manager.set_failed("b", "broken".to_string()).unwrap();
+
+        // Once the database exists it's used without asking for it
+        let reopened = StatusManager::new(jobs_dir).unwrap();
+        assert_eq!(reopened.get("b").unwrap().status, JobStatus::Fail);
+        assert!(!jobs_dir.join(STATUS_FILE).exists());
+        history::append(jobs_dir, &run).unwrap();
+        assert_eq!(history::load(jobs_dir), vec![run.clone(), run]);
+    }
+
+    #[test]
+    fn test_migrates_unversioned_status_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let status_file = temp_dir.path().join("_jobstatus.json");
+        let legacy = r#"[
+            {"id": "job1", "status": "pass", "created_at": "2025-01-01T00:00:00Z"},
+            {"id": "job2", "status": "created", "created_at": "2025-01-01T00:00:00Z",
+             "updated_at": "2025-01-02T00:00:00Z", "partial_state": null}
+        ]"#;
+        fs::write(&status_file, legacy).unwrap();
+
+        let mut manager = StatusManager::new(temp_dir.path()).unwrap();
+        let job1 = manager.get("job1").unwrap();
+        assert!(job1.ran);
+        assert_eq!(job1.updated_at, job1.created_at);
+        assert!(!manager.get("job2").unwrap().ran);
+        assert_eq!(fs::read_to_string(temp_dir.path().join("_jobstatus.v1.json")).unwrap(), legacy);
+
+        manager.save().unwrap();
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&status_file).unwrap()).unwrap();
+        assert_eq!(saved["version"], STATUS_FILE_VERSION);
+        assert_eq!(saved["jobs"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_refuses_newer_status_format() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("_jobstatus.json"), r#"{"version": 99, "jobs": []}"#).unwrap();
+        let err = StatusManager::new(temp_dir.path()).err().unwrap().to_string();
+        assert!(err.contains("newer worksplit"), "{}", err);
+
+        assert!(migrate_status_document(json!({"jobs": []})).is_err());
+        assert_eq!(migrate_status_document(json!([])).unwrap().1, 1);
+    }
+
     #[test]
     fn test_set_partial_status() {
         let (_temp_dir, mut manager) = create_test_manager();
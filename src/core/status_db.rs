@@ -0,0 +1,208 @@
+//! SQLite storage for job status and run history (`jobs/_jobstatus.db`,
+//! "sqlite" feature)
+//!
+//! Each job's entry is a row, so a save only writes the entries that
+//! changed. History events are rows too, with their time, job and status
+//! in columns for queries:
+//!
+//! ```sql
+//! SELECT job_id, COUNT(*) FROM history WHERE status = 'fail' GROUP BY job_id;
+//! ```
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::core::history::HistoryEvent;
+use crate::error::StatusError;
+use crate::models::{JobStatus, JobStatusEntry};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS jobs (
+    id TEXT PRIMARY KEY,
+    status TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    entry TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS history (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    at TEXT NOT NULL,
+    event TEXT NOT NULL,
+    job_id TEXT,
+    status TEXT,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS history_at ON history (at);
+CREATE INDEX IF NOT EXISTS history_job ON history (job_id);
+";
+
+/// An open status database
+pub struct StatusDb {
+    path: PathBuf,
+    conn: Mutex<Connection>,
+    /// Serialized entries as last read or written, to find the changed ones
+    written: HashMap<String, String>,
+}
+
+impl StatusDb {
+    /// Open (or create) the database at `path`
+    pub fn open(path: &Path) -> Result<Self, StatusError> {
+        let db_error = |e: rusqlite::Error| StatusError::Database(path.to_path_buf(), e.to_string());
+        let conn = Connection::open(path).map_err(db_error)?;
+        // Other processes (spool workers) may hold the write lock briefly
+        conn.busy_timeout(Duration::from_secs(30)).map_err(db_error)?;
+        conn.execute_batch(SCHEMA).map_err(db_error)?;
+        Ok(Self { path: path.to_path_buf(), conn: Mutex::new(conn), written: HashMap::new() })
+    }
+
+    fn error(&self, e: impl std::fmt::Display) -> StatusError {
+        StatusError::Database(self.path.clone(), e.to_string())
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// All entries
+    pub fn load(&mut self) -> Result<HashMap<String, JobStatusEntry>, StatusError> {
+        let rows: Vec<(String, String)> = {
+            let conn = self.conn();
+            let mut statement = conn.prepare("SELECT id, entry FROM jobs").map_err(|e| self.error(e))?;
+            let rows = statement
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|e| self.error(e))?
+                .collect::<Result<_, _>>()
+                .map_err(|e| self.error(e))?;
+            rows
+        };
+
+        let mut entries = HashMap::new();
+        self.written.clear();
+        for (id, json) in rows {
+            let entry: JobStatusEntry = serde_json::from_str(&json)
+                .map_err(|e| self.error(format!("entry '{}': {}", id, e)))?;
+            entries.insert(id.clone(), entry);
+            self.written.insert(id, json);
+        }
+        Ok(entries)
+    }
+
+    /// Write the entries that changed since the last load or save and
+    /// delete the ones that are gone, in one transaction. Returns the
+    /// number of rows written or deleted.
+    pub fn save(&mut self, entries: &HashMap<String, JobStatusEntry>) -> Result<usize, StatusError> {
+        let mut changed = Vec::new();
+        for (id, entry) in entries {
+            let json = serde_json::to_string(entry).map_err(|e| self.error(e))?;
+            if self.written.get(id) != Some(&json) {
+                changed.push((entry, json));
+            }
+        }
+        let removed: Vec<String> = self.written.keys().filter(|id| !entries.contains_key(*id)).cloned().collect();
+        if changed.is_empty() && removed.is_empty() {
+            return Ok(0);
+        }
+
+        {
+            let mut conn = self.conn();
+            let tx = conn.transaction().map_err(|e| self.error(e))?;
+            for (entry, json) in &changed {
+                tx.execute(
+                    "INSERT INTO jobs (id, status, updated_at, entry) VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT (id) DO UPDATE SET status = ?2, updated_at = ?3, entry = ?4",
+                    params![entry.id, status_name(entry.status), entry.updated_at.to_rfc3339(), json],
+                )
+                .map_err(|e| self.error(e))?;
+            }
+            for id in &removed {
+                tx.execute("DELETE FROM jobs WHERE id = ?1", params![id]).map_err(|e| self.error(e))?;
+            }
+            tx.commit().map_err(|e| self.error(e))?;
+        }
+
+        let count = changed.len() + removed.len();
+        for (entry, json) in changed {
+            self.written.insert(entry.id.clone(), json);
+        }
+        for id in removed {
+            self.written.remove(&id);
+        }
+        Ok(count)
+    }
+
+    /// Append a history event
+    pub fn append_event(&self, event: &HistoryEvent) -> Result<(), StatusError> {
+        let data = serde_json::to_string(event).map_err(|e| self.error(e))?;
+        let (at, kind, job_id, status): (DateTime<Utc>, &str, Option<&str>, Option<String>) = match event {
+            HistoryEvent::Run { started_at, .. } => (*started_at, "run", None, None),
+            HistoryEvent::Job(record) => (record.finished_at, "job", Some(&record.job_id), Some(status_name(record.to))),
+        };
+        self.conn()
+            .execute(
+                "INSERT INTO history (at, event, job_id, status, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![at.to_rfc3339(), kind, job_id, status, data],
+            )
+            .map_err(|e| self.error(e))?;
+        Ok(())
+    }
+
+    /// All history events, oldest first
+    pub fn events(&self) -> Result<Vec<HistoryEvent>, StatusError> {
+        let conn = self.conn();
+        let mut statement = conn.prepare("SELECT data FROM history ORDER BY seq").map_err(|e| self.error(e))?;
+        let rows: Vec<String> = statement
+            .query_map([], |row| row.get(0))
+            .map_err(|e| self.error(e))?
+            .collect::<Result<_, _>>()
+            .map_err(|e| self.error(e))?;
+        Ok(rows.iter().filter_map(|data| serde_json::from_str(data).ok()).collect())
+    }
+}
+
+/// A status as stored, e.g. `pending_work`
+fn status_name(status: JobStatus) -> String {
+    serde_json::to_value(status).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_writes_only_changed_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("_jobstatus.db");
+        let mut db = StatusDb::open(&path).unwrap();
+
+        let mut entries: HashMap<String, JobStatusEntry> = ["a", "b"]
+            .iter()
+            .map(|id| (id.to_string(), JobStatusEntry::new(id.to_string())))
+            .collect();
+        assert_eq!(db.save(&entries).unwrap(), 2);
+        assert_eq!(db.save(&entries).unwrap(), 0);
+
+        entries.get_mut("a").unwrap().update_status(JobStatus::Pass);
+        entries.remove("b");
+        assert_eq!(db.save(&entries).unwrap(), 2);
+
+        let mut reopened = StatusDb::open(&path).unwrap();
+        let loaded = reopened.load().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded["a"].status, JobStatus::Pass);
+        let status: String = reopened.conn().query_row("SELECT status FROM jobs WHERE id = 'a'", [], |row| row.get(0)).unwrap();
+        assert_eq!(status, "pass");
+    }
+
+    #[test]
+    fn test_history_events_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = StatusDb::open(&temp_dir.path().join("_jobstatus.db")).unwrap();
+        let event = HistoryEvent::Run { started_at: Utc::now(), jobs: 3 };
+        db.append_event(&event).unwrap();
+        assert_eq!(db.events().unwrap(), vec![event]);
+    }
+}
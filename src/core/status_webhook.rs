@@ -0,0 +1,187 @@
+//! Status webhooks (`[status_webhook]`)
+//!
+//! Every job status transition of a run is POSTed to the configured URL as
+//! JSON, in the order the transitions happened, so dashboards and chat bots
+//! can follow progress without polling `_jobstatus.json`. Requests go out
+//! from a background task and never hold up or fail the run; the runner
+//! waits for a job's transitions to be delivered before the next job. With
+//! `secret_env` set, the body is signed with HMAC-SHA256 in
+//! `X-WorkSplit-Signature-256: sha256=<hex>`. Webhooks need the "notify"
+//! feature.
+
+use chrono::{DateTime, Utc};
+#[cfg(feature = "notify")]
+use reqwest::Client;
+#[cfg(feature = "notify")]
+use ring::hmac;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "notify")]
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+#[cfg(feature = "notify")]
+use tracing::debug;
+use tracing::warn;
+
+#[cfg(feature = "notify")]
+use crate::core::notify::project_name;
+use crate::models::{JobStatus, StatusWebhookConfig};
+
+/// Header carrying the signature of the body
+pub const SIGNATURE_HEADER: &str = "X-WorkSplit-Signature-256";
+
+/// One job status change
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatusTransition {
+    pub job_id: String,
+    pub from: JobStatus,
+    pub to: JobStatus,
+    pub error: Option<String>,
+    /// Files the job generated, relative to the project root
+    pub outputs: Vec<PathBuf>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Body POSTed for a transition
+#[cfg(feature = "notify")]
+#[derive(Debug, Serialize)]
+struct TransitionPayload<'a> {
+    event: &'static str,
+    project: &'a str,
+    #[serde(flatten)]
+    transition: &'a StatusTransition,
+}
+
+enum Message {
+    Transition(StatusTransition),
+    /// Answered once every transition sent before it was delivered
+    Flush(oneshot::Sender<()>),
+}
+
+/// Queue of transitions for the webhook's background task
+#[derive(Debug, Clone)]
+pub struct StatusWebhook {
+    sender: mpsc::UnboundedSender<Message>,
+}
+
+impl StatusWebhook {
+    /// Start the background task, or `None` if no URL is configured. Must
+    /// be called inside the Tokio runtime.
+    #[cfg(feature = "notify")]
+    pub fn spawn(config: &StatusWebhookConfig, project_root: &Path) -> Option<Self> {
+        let url = config.url.clone()?;
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .unwrap_or_default();
+        let key = config.secret_env.as_ref().and_then(|var| match std::env::var(var) {
+            Ok(secret) => Some(hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes())),
+            Err(_) => {
+                warn!("Status webhook secret variable {} is not set; requests are not signed", var);
+                None
+            }
+        });
+        let project = project_name(project_root);
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                match message {
+                    Message::Transition(transition) => {
+                        let payload = TransitionPayload { event: "job_status", project: &project, transition: &transition };
+                        post(&client, &url, key.as_ref(), &payload).await;
+                    }
+                    Message::Flush(done) => {
+                        let _ = done.send(());
+                    }
+                }
+            }
+        });
+        Some(Self { sender })
+    }
+
+    /// Without the "notify" feature nothing is ever sent
+    #[cfg(not(feature = "notify"))]
+    pub fn spawn(config: &StatusWebhookConfig, _project_root: &Path) -> Option<Self> {
+        if config.url.is_some() {
+            warn!("[status_webhook] is set, but WorkSplit was built without the \"notify\" feature; no status updates are sent");
+        }
+        None
+    }
+
+    /// Queue a transition for delivery
+    pub fn send(&self, transition: StatusTransition) {
+        // The task only stops when the runtime does
+        let _ = self.sender.send(Message::Transition(transition));
+    }
+
+    /// Wait until every transition queued so far was delivered (or failed)
+    pub async fn flush(&self) {
+        let (done, wait) = oneshot::channel();
+        if self.sender.send(Message::Flush(done)).is_ok() {
+            let _ = wait.await;
+        }
+    }
+}
+
+/// `sha256=<hex>` HMAC of `body`
+#[cfg(feature = "notify")]
+pub fn signature(key: &hmac::Key, body: &[u8]) -> String {
+    let tag = hmac::sign(key, body);
+    let hex: String = tag.as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", hex)
+}
+
+#[cfg(feature = "notify")]
+async fn post(client: &Client, url: &str, key: Option<&hmac::Key>, payload: &TransitionPayload<'_>) {
+    let body = match serde_json::to_vec(payload) {
+        Ok(body) => body,
+        Err(e) => {
+            warn!("Failed to encode status webhook payload: {}", e);
+            return;
+        }
+    };
+    let mut request = client.post(url).header(reqwest::header::CONTENT_TYPE, "application/json");
+    if let Some(key) = key {
+        request = request.header(SIGNATURE_HEADER, signature(key, &body));
+    }
+    match request.body(body).send().await {
+        Ok(response) if response.status().is_success() => {
+            debug!("Status webhook: {} {:?} -> {:?}", payload.transition.job_id, payload.transition.from, payload.transition.to)
+        }
+        Ok(response) => warn!("Status webhook {} returned HTTP {}", url, response.status().as_u16()),
+        Err(e) => warn!("Status webhook {} failed: {}", url, e),
+    }
+}
+
+#[cfg(all(test, feature = "notify"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_and_payload() {
+        // RFC 4231 test case 2
+        let key = hmac::Key::new(hmac::HMAC_SHA256, b"Jefe");
+        assert_eq!(
+            signature(&key, b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        let transition = StatusTransition {
+            job_id: "001_api".into(),
+            from: JobStatus::PendingVerification,
+            to: JobStatus::Pass,
+            error: None,
+            outputs: vec![PathBuf::from("src/api.rs")],
+            timestamp: DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z").unwrap().with_timezone(&Utc),
+        };
+        let payload = TransitionPayload { event: "job_status", project: "shop", transition: &transition };
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            serde_json::json!({
+                "event": "job_status", "project": "shop", "job_id": "001_api", "from": "pending_verification",
+                "to": "pass", "error": null, "outputs": ["src/api.rs"], "timestamp": "2026-01-02T03:04:05Z",
+            })
+        );
+    }
+}
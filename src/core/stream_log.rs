@@ -0,0 +1,74 @@
+//! Per-job copies of the token stream (`worksplit run --stream-to <dir>`)
+//!
+//! Every token is appended to `<dir>/<job>.log` as it arrives, whether or not
+//! it's also printed, so a generation can be followed with `tail -f` from
+//! another terminal. Each request starts with a header line; a job's file is
+//! started over the first time the job makes a request in a run.
+
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::WorkSplitError;
+
+/// File for requests made outside a job (e.g. `fix --build`)
+const NO_JOB: &str = "_run";
+
+/// Writes the token stream of each job to its own file
+#[derive(Debug)]
+pub struct StreamLog {
+    dir: PathBuf,
+    /// Jobs whose file was started over in this run
+    started: Mutex<HashSet<String>>,
+}
+
+impl StreamLog {
+    pub fn create(dir: &Path) -> Result<Self, WorkSplitError> {
+        fs::create_dir_all(dir)?;
+        Ok(Self { dir: dir.to_path_buf(), started: Mutex::new(HashSet::new()) })
+    }
+
+    /// File the stream of `job_id` goes to
+    pub fn path(&self, job_id: Option<&str>) -> PathBuf {
+        self.dir.join(format!("{}.log", job_id.unwrap_or(NO_JOB)))
+    }
+
+    /// Open the job's file for a new request and write its header
+    pub fn begin(&self, job_id: Option<&str>, model: &str) -> Result<File, WorkSplitError> {
+        let first = self.started.lock().unwrap().insert(job_id.unwrap_or(NO_JOB).to_string());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(!first)
+            .truncate(first)
+            .open(self.path(job_id))?;
+        writeln!(file, "\n===== {} {} =====", chrono::Local::now().format("%Y-%m-%d %H:%M:%S"), model)?;
+        Ok(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_stream_log_starts_each_job_over_once() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join("streams");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("001_a.log"), "previous run").unwrap();
+
+        let log = StreamLog::create(&dir).unwrap();
+        write!(log.begin(Some("001_a"), "qwen").unwrap(), "first").unwrap();
+        write!(log.begin(Some("001_a"), "qwen").unwrap(), "second").unwrap();
+        write!(log.begin(None, "qwen").unwrap(), "fix").unwrap();
+
+        let content = fs::read_to_string(log.path(Some("001_a"))).unwrap();
+        assert!(!content.contains("previous run"));
+        assert!(content.contains("qwen =====\nfirst") && content.ends_with("qwen =====\nsecond"), "{}", content);
+        assert!(fs::read_to_string(dir.join("_run.log")).unwrap().ends_with("fix"));
+    }
+}
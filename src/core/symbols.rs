@@ -0,0 +1,266 @@
+//! Project-wide symbol index (`jobs/_index.json`)
+//!
+//! Top-level definitions (functions, types, exports) are extracted per file
+//! with a few regexes, so jobs can name the symbols they need in
+//! `context_symbols` instead of the files that define them. The index is
+//! refreshed incrementally: only files whose modification time changed are
+//! re-read.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::UNIX_EPOCH;
+
+use crate::core::file_cache::FileCache;
+use crate::core::targets::walk_files;
+use crate::error::WorkSplitError;
+
+/// Index file name inside the jobs folder
+pub const SYMBOL_INDEX_FILE: &str = "_index.json";
+
+/// Kind of a top-level definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Function,
+    Struct,
+    Enum,
+    Trait,
+    Type,
+    Class,
+    Interface,
+    Contract,
+    Const,
+}
+
+/// A definition found in a file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// 1-based line of the definition
+    pub line: usize,
+}
+
+/// Symbols of one file, with the modification time they were read at
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileSymbols {
+    /// Modification time in milliseconds since the Unix epoch
+    pub modified_ms: u64,
+    pub symbols: Vec<Symbol>,
+}
+
+/// Symbols of every source file in the project, keyed by relative path
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolIndex {
+    pub files: BTreeMap<PathBuf, FileSymbols>,
+}
+
+/// Definition patterns per file extension; the first capture is the name
+fn patterns(extension: &str) -> &'static [(SymbolKind, Regex)] {
+    static RUST: OnceLock<Vec<(SymbolKind, Regex)>> = OnceLock::new();
+    static TS: OnceLock<Vec<(SymbolKind, Regex)>> = OnceLock::new();
+    static PY: OnceLock<Vec<(SymbolKind, Regex)>> = OnceLock::new();
+    static SOL: OnceLock<Vec<(SymbolKind, Regex)>> = OnceLock::new();
+    static GO: OnceLock<Vec<(SymbolKind, Regex)>> = OnceLock::new();
+
+    fn build(list: &[(SymbolKind, &str)]) -> Vec<(SymbolKind, Regex)> {
+        list.iter().map(|(kind, re)| (*kind, Regex::new(re).unwrap())).collect()
+    }
+
+    match extension {
+        "rs" => RUST.get_or_init(|| {
+            let vis = r"^(?:pub(?:\([^)]*\))?\s+)?";
+            build(&[
+                (SymbolKind::Function, &format!(r"{vis}(?:const\s+)?(?:async\s+)?(?:unsafe\s+)?fn\s+(\w+)")),
+                (SymbolKind::Struct, &format!(r"{vis}struct\s+(\w+)")),
+                (SymbolKind::Enum, &format!(r"{vis}enum\s+(\w+)")),
+                (SymbolKind::Trait, &format!(r"{vis}(?:unsafe\s+)?trait\s+(\w+)")),
+                (SymbolKind::Type, &format!(r"{vis}type\s+(\w+)")),
+                (SymbolKind::Const, &format!(r"{vis}(?:const|static)\s+([A-Z_][A-Z0-9_]*)\s*:")),
+            ])
+        }),
+        "ts" | "tsx" | "js" | "jsx" | "mjs" => TS.get_or_init(|| {
+            let export = r"^(?:export\s+(?:default\s+)?)?(?:declare\s+)?";
+            build(&[
+                (SymbolKind::Function, &format!(r"{export}(?:async\s+)?function\*?\s+(\w+)")),
+                (SymbolKind::Class, &format!(r"{export}(?:abstract\s+)?class\s+(\w+)")),
+                (SymbolKind::Interface, &format!(r"{export}interface\s+(\w+)")),
+                (SymbolKind::Type, &format!(r"{export}type\s+(\w+)\s*[=<]")),
+                (SymbolKind::Enum, &format!(r"{export}(?:const\s+)?enum\s+(\w+)")),
+                (SymbolKind::Const, r"^export\s+(?:const|let|var)\s+(\w+)"),
+            ])
+        }),
+        "py" => PY.get_or_init(|| {
+            build(&[
+                (SymbolKind::Function, r"^(?:async\s+)?def\s+(\w+)"),
+                (SymbolKind::Class, r"^class\s+(\w+)"),
+                (SymbolKind::Const, r"^([A-Z_][A-Z0-9_]*)\s*(?::[^=]+)?="),
+            ])
+        }),
+        "sol" => SOL.get_or_init(|| {
+            build(&[
+                (SymbolKind::Contract, r"^(?:abstract\s+)?(?:contract|library)\s+(\w+)"),
+                (SymbolKind::Interface, r"^interface\s+(\w+)"),
+                (SymbolKind::Struct, r"^struct\s+(\w+)"),
+                (SymbolKind::Enum, r"^enum\s+(\w+)"),
+                (SymbolKind::Function, r"^function\s+(\w+)"),
+            ])
+        }),
+        "go" => GO.get_or_init(|| {
+            build(&[
+                (SymbolKind::Function, r"^func\s+(?:\([^)]*\)\s*)?(\w+)"),
+                (SymbolKind::Struct, r"^type\s+(\w+)\s+struct\b"),
+                (SymbolKind::Interface, r"^type\s+(\w+)\s+interface\b"),
+                (SymbolKind::Type, r"^type\s+(\w+)\s"),
+            ])
+        }),
+        _ => &[],
+    }
+}
+
+/// Whether symbols are extracted from files with this path
+pub fn is_indexed(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| !patterns(e).is_empty())
+}
+
+/// Extract top-level definitions from a source file. Only unindented lines
+/// are considered, so methods and nested items are left out.
+pub fn extract_symbols(path: &Path, content: &str) -> Vec<Symbol> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    let patterns = patterns(extension);
+    let mut symbols = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        if let Some((kind, caps)) = patterns.iter().find_map(|(kind, re)| re.captures(line).map(|c| (*kind, c))) {
+            symbols.push(Symbol { name: caps[1].to_string(), kind, line: index + 1 });
+        }
+    }
+    symbols
+}
+
+fn modified_ms(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as u64)
+}
+
+impl SymbolIndex {
+    /// Load `jobs/_index.json`; a missing or unreadable index starts empty
+    pub fn load(jobs_dir: &Path) -> Self {
+        fs::read_to_string(jobs_dir.join(SYMBOL_INDEX_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, jobs_dir: &Path) -> Result<(), WorkSplitError> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| WorkSplitError::IoError(e.to_string()))?;
+        fs::write(jobs_dir.join(SYMBOL_INDEX_FILE), content)?;
+        Ok(())
+    }
+
+    /// Bring the index up to date with the project. Files whose modification
+    /// time is unchanged are skipped; changed files are read through `cache`.
+    /// Returns whether anything changed.
+    pub fn refresh(&mut self, project_root: &Path, cache: &mut FileCache) -> Result<bool, WorkSplitError> {
+        let mut found = Vec::new();
+        walk_files(project_root, project_root, &mut found)?;
+        found.retain(|path| is_indexed(path));
+
+        let present: HashSet<&PathBuf> = found.iter().collect();
+        let before = self.files.len();
+        self.files.retain(|path, _| present.contains(path));
+        let mut changed = self.files.len() != before;
+
+        for path in found {
+            let full_path = project_root.join(&path);
+            let Some(modified) = modified_ms(&full_path) else { continue };
+            if self.files.get(&path).is_some_and(|entry| entry.modified_ms == modified) {
+                continue;
+            }
+            let Ok(entry) = cache.get_or_load(&full_path) else { continue };
+            let symbols = extract_symbols(&path, &entry.content);
+            self.files.insert(path, FileSymbols { modified_ms: modified, symbols });
+            changed = true;
+        }
+        Ok(changed)
+    }
+
+    /// Files defining `name`, shortest path first
+    pub fn defining_files(&self, name: &str) -> Vec<&PathBuf> {
+        let mut files: Vec<&PathBuf> = self
+            .files
+            .iter()
+            .filter(|(_, entry)| entry.symbols.iter().any(|s| s.name == name))
+            .map(|(path, _)| path)
+            .collect();
+        files.sort_by_key(|path| (path.components().count(), path.as_os_str().len()));
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn names(path: &str, content: &str) -> Vec<(String, SymbolKind)> {
+        extract_symbols(Path::new(path), content).into_iter().map(|s| (s.name, s.kind)).collect()
+    }
+
+    #[test]
+    fn test_extract_symbols() {
+        let rust = "pub struct UserService {\n    id: u32,\n}\n\nimpl UserService {\n    pub fn new() -> Self { todo!() }\n}\n\
+                    pub(crate) async fn load() {}\nconst MAX_USERS: usize = 5;\npub enum Role { Admin }\n";
+        assert_eq!(
+            names("src/user.rs", rust),
+            vec![
+                ("UserService".to_string(), SymbolKind::Struct),
+                ("load".to_string(), SymbolKind::Function),
+                ("MAX_USERS".to_string(), SymbolKind::Const),
+                ("Role".to_string(), SymbolKind::Enum),
+            ]
+        );
+
+        let ts = "export interface Config {}\nexport default class Api {}\nexport const VERSION = 1;\nfunction helper() {}\n";
+        assert_eq!(names("web/api.ts", ts).len(), 4);
+        assert_eq!(names("app/models.py", "class User:\n    def save(self): ...\ndef load(): ...\n").len(), 2);
+        assert!(names("README.md", "fn nothing() {}").is_empty());
+    }
+
+    #[test]
+    fn test_refresh_is_incremental() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("src/config")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("src/config/mod.rs"), "pub struct Config;\n").unwrap();
+        fs::write(root.join("src/config.rs"), "pub struct Config;\n").unwrap();
+        fs::write(root.join("target/gen.rs"), "pub struct Generated;\n").unwrap();
+
+        let mut cache = FileCache::new();
+        let mut index = SymbolIndex::default();
+        assert!(index.refresh(root, &mut cache).unwrap());
+        assert_eq!(index.files.len(), 2);
+        assert_eq!(
+            index.defining_files("Config"),
+            vec![&PathBuf::from("src/config.rs"), &PathBuf::from("src/config/mod.rs")]
+        );
+        assert!(index.defining_files("Generated").is_empty());
+
+        // Nothing changed on disk
+        assert!(!index.refresh(root, &mut cache).unwrap());
+
+        fs::remove_file(root.join("src/config.rs")).unwrap();
+        assert!(index.refresh(root, &mut cache).unwrap());
+        assert_eq!(index.defining_files("Config"), vec![&PathBuf::from("src/config/mod.rs")]);
+    }
+}
@@ -0,0 +1,297 @@
+//! Expansion of directory and glob entries in `target_files`
+
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::ignore::IgnoreRules;
+use crate::error::WorkSplitError;
+
+/// Directories never descended into when expanding a directory or glob
+const SKIP_DIRS: &[&str] = &["target", "node_modules", "jobs", "dist", "build"];
+
+/// Whether a target entry is a glob pattern
+pub fn is_glob(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+/// Expand `target_files` entries into concrete project-relative files.
+///
+/// - `src/handlers/` (or any existing directory): every file below it
+/// - `src/**/*.rs`, `src/api/*.ts`: files matching the glob
+/// - anything else: kept as-is
+///
+/// Order follows the entries, files within one entry are sorted, and
+/// duplicates are dropped. Directory or glob entries that match nothing are
+/// an error.
+pub fn expand_target_files(
+    project_root: &Path,
+    entries: &[PathBuf],
+) -> Result<Vec<PathBuf>, WorkSplitError> {
+    let mut files: Vec<PathBuf> = Vec::new();
+
+    for entry in entries {
+        let matched = if is_glob(entry) {
+            expand_glob(project_root, entry)?
+        } else if entry.to_string_lossy().ends_with('/') || project_root.join(entry).is_dir() {
+            let mut found = Vec::new();
+            walk_files(project_root, &project_root.join(entry), &mut found)?;
+            found.sort();
+            found
+        } else {
+            vec![entry.clone()]
+        };
+
+        if matched.is_empty() {
+            return Err(WorkSplitError::TargetPatternNoMatch(entry.display().to_string()));
+        }
+        for file in matched {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// The project file with the same name as `missing` closest to it: the
+/// one sharing the most directory names with it, then the shortest
+pub fn closest_by_file_name(project_root: &Path, missing: &Path) -> Result<Option<PathBuf>, WorkSplitError> {
+    let Some(name) = missing.file_name() else { return Ok(None) };
+    let dirs: Vec<_> = missing.parent().map(|p| p.components().collect()).unwrap_or_default();
+    let mut found = Vec::new();
+    walk_files(project_root, project_root, &mut found)?;
+    Ok(found
+        .into_iter()
+        .filter(|path| path.file_name() == Some(name))
+        .max_by_key(|path| {
+            let shared = path.parent().map_or(0, |p| p.components().filter(|c| dirs.contains(c)).count());
+            (shared, std::cmp::Reverse(path.components().count()), std::cmp::Reverse(path.clone()))
+        }))
+}
+
+/// Group target files into chunks of at most `max_lines` lines each, so a
+/// large set can be edited over several prompts. A file larger than the
+/// budget gets a chunk of its own.
+pub fn chunk_targets(files: Vec<(PathBuf, String)>, max_lines: usize) -> Vec<Vec<(PathBuf, String)>> {
+    let mut chunks: Vec<Vec<(PathBuf, String)>> = Vec::new();
+    let mut current: Vec<(PathBuf, String)> = Vec::new();
+    let mut current_lines = 0;
+
+    for (path, content) in files {
+        let lines = content.lines().count();
+        if !current.is_empty() && current_lines + lines > max_lines {
+            chunks.push(std::mem::take(&mut current));
+            current_lines = 0;
+        }
+        current_lines += lines;
+        current.push((path, content));
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn expand_glob(project_root: &Path, pattern: &Path) -> Result<Vec<PathBuf>, WorkSplitError> {
+    let pattern = pattern.to_string_lossy().replace('\\', "/");
+    let regex = glob_to_regex(&pattern)
+        .map_err(|e| WorkSplitError::TargetPatternNoMatch(format!("{} ({})", pattern, e)))?;
+
+    // Only walk the literal directory prefix of the pattern
+    let base: PathBuf = pattern
+        .split('/')
+        .take_while(|part| !part.contains(['*', '?', '[']))
+        .collect();
+    let base_dir = project_root.join(&base);
+    if !base_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut found = Vec::new();
+    walk_files(project_root, &base_dir, &mut found)?;
+    found.retain(|p| regex.is_match(&p.to_string_lossy().replace('\\', "/")));
+    found.sort();
+    Ok(found)
+}
+
+/// Translate a glob into an anchored regex (`**` crosses directories, `*` does not)
+pub(crate) fn glob_to_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                regex.push('[');
+                for c in chars.by_ref() {
+                    regex.push(c);
+                    if c == ']' {
+                        break;
+                    }
+                }
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+
+    Regex::new(&regex)
+}
+
+/// Collect files below `dir` as paths relative to `project_root`, leaving
+/// out hidden entries, build directories and anything `.worksplitignore`
+/// excludes
+pub(crate) fn walk_files(project_root: &Path, dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), WorkSplitError> {
+    let rules = IgnoreRules::load(project_root)?;
+    walk_dir(project_root, dir, &rules, out)
+}
+
+fn walk_dir(project_root: &Path, dir: &Path, rules: &IgnoreRules, out: &mut Vec<PathBuf>) -> Result<(), WorkSplitError> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if name.starts_with('.') {
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(project_root) else { continue };
+        let is_dir = path.is_dir();
+        if rules.is_ignored(relative, is_dir) {
+            continue;
+        }
+        if is_dir {
+            if !SKIP_DIRS.contains(&name.as_ref()) {
+                walk_dir(project_root, &path, rules, out)?;
+            }
+        } else {
+            out.push(relative.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        for file in [
+            "src/main.rs",
+            "src/handlers/users.rs",
+            "src/handlers/orders.rs",
+            "src/handlers/nested/admin.rs",
+            "src/handlers/README.md",
+            "src/.hidden/skip.rs",
+        ] {
+            let path = temp_dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "fn a() {}\n").unwrap();
+        }
+        temp_dir
+    }
+
+    #[test]
+    fn test_closest_by_file_name() {
+        let temp_dir = project();
+        fs::create_dir_all(temp_dir.path().join("lib/users")).unwrap();
+        fs::write(temp_dir.path().join("lib/users/users.rs"), "").unwrap();
+
+        let closest = |path: &str| closest_by_file_name(temp_dir.path(), Path::new(path)).unwrap();
+        assert_eq!(closest("src/api/handlers/users.rs"), Some(PathBuf::from("src/handlers/users.rs")));
+        assert_eq!(closest("lib/users.rs"), Some(PathBuf::from("lib/users/users.rs")));
+        assert_eq!(closest("orders.rs"), Some(PathBuf::from("src/handlers/orders.rs")));
+        assert_eq!(closest("src/missing.rs"), None);
+    }
+
+    #[test]
+    fn test_expand_directory() {
+        let temp_dir = project();
+        let files = expand_target_files(temp_dir.path(), &[PathBuf::from("src/handlers/")]).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("src/handlers/README.md"),
+                PathBuf::from("src/handlers/nested/admin.rs"),
+                PathBuf::from("src/handlers/orders.rs"),
+                PathBuf::from("src/handlers/users.rs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_globs() {
+        let temp_dir = project();
+        let root = temp_dir.path();
+
+        let files = expand_target_files(root, &[PathBuf::from("src/handlers/*.rs")]).unwrap();
+        assert_eq!(
+            files,
+            vec![PathBuf::from("src/handlers/orders.rs"), PathBuf::from("src/handlers/users.rs")]
+        );
+
+        let files = expand_target_files(root, &[PathBuf::from("src/**/*.rs")]).unwrap();
+        assert_eq!(files.len(), 4);
+        assert!(files.contains(&PathBuf::from("src/main.rs")));
+        assert!(!files.iter().any(|f| f.to_string_lossy().contains(".hidden")));
+    }
+
+    #[test]
+    fn test_expand_skips_worksplitignore() {
+        let temp_dir = project();
+        let root = temp_dir.path();
+        fs::write(root.join(".worksplitignore"), "nested/\n*.md\n").unwrap();
+
+        let files = expand_target_files(root, &[PathBuf::from("src/handlers/")]).unwrap();
+        assert_eq!(files, vec![PathBuf::from("src/handlers/orders.rs"), PathBuf::from("src/handlers/users.rs")]);
+
+        // Explicit paths are kept
+        let files = expand_target_files(root, &[PathBuf::from("src/handlers/README.md")]).unwrap();
+        assert_eq!(files, vec![PathBuf::from("src/handlers/README.md")]);
+    }
+
+    #[test]
+    fn test_expand_keeps_plain_paths_and_dedupes() {
+        let temp_dir = project();
+        let files = expand_target_files(
+            temp_dir.path(),
+            &[PathBuf::from("src/main.rs"), PathBuf::from("src/*.rs"), PathBuf::from("src/new.rs")],
+        )
+        .unwrap();
+        assert_eq!(files, vec![PathBuf::from("src/main.rs"), PathBuf::from("src/new.rs")]);
+    }
+
+    #[test]
+    fn test_expand_no_match_is_error() {
+        let temp_dir = project();
+        let result = expand_target_files(temp_dir.path(), &[PathBuf::from("src/**/*.py")]);
+        assert!(matches!(result, Err(WorkSplitError::TargetPatternNoMatch(_))));
+    }
+
+    #[test]
+    fn test_chunk_targets() {
+        let file = |name: &str, lines: usize| (PathBuf::from(name), "x\n".repeat(lines));
+        let chunks = chunk_targets(vec![file("a", 40), file("b", 50), file("c", 200), file("d", 10)], 100);
+        let names: Vec<Vec<String>> = chunks
+            .iter()
+            .map(|c| c.iter().map(|(p, _)| p.display().to_string()).collect())
+            .collect();
+        assert_eq!(names, vec![vec!["a", "b"], vec!["c"], vec!["d"]]);
+    }
+}
@@ -0,0 +1,149 @@
+//! Wall time per job phase (test generation, generation, verification,
+//! retries, build), for the latency table at the end of a run
+//!
+//! Time between phases (status updates, writing files) isn't counted, so the
+//! phases add up to a little less than the job's wall time.
+
+use std::time::{Duration, Instant};
+
+/// A timed phase of a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Test generation (TDD jobs)
+    TestGeneration,
+    /// Code, edit, split or report generation
+    Generation,
+    /// Verification, including external verification
+    Verification,
+    /// Regeneration after failed verification
+    Retry,
+    /// Build and lint commands, with build auto-fixes
+    Build,
+}
+
+/// Time spent in each phase
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PhaseTimings {
+    pub test_generation: Duration,
+    pub generation: Duration,
+    pub verification: Duration,
+    pub retries: Duration,
+    pub build: Duration,
+}
+
+impl PhaseTimings {
+    fn get_mut(&mut self, phase: Phase) -> &mut Duration {
+        match phase {
+            Phase::TestGeneration => &mut self.test_generation,
+            Phase::Generation => &mut self.generation,
+            Phase::Verification => &mut self.verification,
+            Phase::Retry => &mut self.retries,
+            Phase::Build => &mut self.build,
+        }
+    }
+
+    /// The phases in table order
+    fn columns(&self) -> [Duration; 5] {
+        [self.test_generation, self.generation, self.verification, self.retries, self.build]
+    }
+
+    pub fn total(&self) -> Duration {
+        self.columns().iter().sum()
+    }
+
+    pub fn add(&mut self, other: &PhaseTimings) {
+        self.test_generation += other.test_generation;
+        self.generation += other.generation;
+        self.verification += other.verification;
+        self.retries += other.retries;
+        self.build += other.build;
+    }
+}
+
+/// Times the phase in progress; starting a phase ends the previous one
+#[derive(Debug, Default)]
+pub struct PhaseClock {
+    timings: PhaseTimings,
+    current: Option<(Phase, Instant)>,
+}
+
+impl PhaseClock {
+    pub fn start(&mut self, phase: Phase) {
+        self.stop();
+        self.current = Some((phase, Instant::now()));
+    }
+
+    pub fn stop(&mut self) {
+        if let Some((phase, started)) = self.current.take() {
+            *self.timings.get_mut(phase) += started.elapsed();
+        }
+    }
+
+    /// End the phase in progress and return the timings so far, resetting
+    /// the clock
+    pub fn take(&mut self) -> PhaseTimings {
+        self.stop();
+        std::mem::take(&mut self.timings)
+    }
+}
+
+fn seconds(duration: Duration) -> String {
+    if duration.is_zero() { "-".to_string() } else { format!("{:.1}s", duration.as_secs_f64()) }
+}
+
+/// Per-job phase times with a total row and each phase's share of the total
+pub fn render_table(rows: &[(&str, PhaseTimings)]) -> String {
+    const HEADERS: [&str; 6] = ["Test gen", "Generate", "Verify", "Retries", "Build", "Total"];
+    let mut totals = PhaseTimings::default();
+    for (_, timings) in rows {
+        totals.add(timings);
+    }
+    let name_width = rows.iter().map(|(name, _)| name.len()).chain([5]).max().unwrap_or(5);
+
+    let mut lines = vec![format!("{:<name_width$}{}", "Job",
+        HEADERS.iter().map(|h| format!("  {:>8}", h)).collect::<String>())];
+    let row = |name: &str, timings: &PhaseTimings| {
+        let cells: String = timings.columns().iter().chain([timings.total()].iter())
+            .map(|d| format!("  {:>8}", seconds(*d)))
+            .collect();
+        format!("{:<name_width$}{}", name, cells)
+    };
+    for (name, timings) in rows {
+        lines.push(row(name, timings));
+    }
+    if rows.len() > 1 {
+        lines.push(row("Total", &totals));
+    }
+    let total = totals.total().as_secs_f64();
+    if total > 0.0 {
+        let shares: String = totals.columns().iter()
+            .map(|d| format!("  {:>8}", format!("{:.0}%", d.as_secs_f64() / total * 100.0)))
+            .collect();
+        lines.push(format!("{:<name_width$}{}", "Share", shares));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_and_table() {
+        let mut clock = PhaseClock::default();
+        clock.start(Phase::Generation);
+        clock.start(Phase::Verification);
+        let timings = clock.take();
+        assert!(clock.take().total().is_zero());
+        assert_eq!(timings.total(), timings.generation + timings.verification);
+
+        let a = PhaseTimings { generation: Duration::from_secs(3), verification: Duration::from_secs(1), ..Default::default() };
+        let b = PhaseTimings { generation: Duration::from_secs(1), build: Duration::from_secs(3), ..Default::default() };
+        let table = render_table(&[("job_a", a), ("job_b", b)]);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "Job    Test gen  Generate    Verify   Retries     Build     Total");
+        assert_eq!(lines[1], "job_a         -      3.0s      1.0s         -         -      4.0s");
+        assert_eq!(lines[3], "Total         -      4.0s      1.0s         -      3.0s      8.0s");
+        assert_eq!(lines[4], "Share        0%       50%       12%        0%       38%");
+    }
+}
@@ -0,0 +1,104 @@
+//! Keeping verification and retry prompts inside the model's context window
+//!
+//! These prompts carry the generated files as well as the job's context.
+//! When they would not fit, context files are cut down, largest first, and
+//! the generated files are sent whole: the verifier has to see all of the
+//! code it judges.
+
+use std::path::PathBuf;
+
+use crate::core::truncate::truncate_context;
+use crate::core::JobsManager;
+use crate::models::TruncationStrategy;
+
+/// Tokens kept free for the answer (about 900 lines of code)
+pub const OUTPUT_RESERVE_TOKENS: usize = 1200;
+
+/// Context files shorter than this are dropped rather than cut further
+const MIN_CONTEXT_LINES: usize = 20;
+
+/// Share of the context window a prompt may use
+fn usable(limit: usize) -> usize {
+    (limit as f64 * 0.9) as usize
+}
+
+/// Context files after fitting a prompt into the context window
+#[derive(Debug, Clone, PartialEq)]
+pub struct FittedContext {
+    pub files: Vec<(PathBuf, String)>,
+    /// Files cut down or dropped, in the order they were first trimmed
+    pub trimmed: Vec<PathBuf>,
+    /// Estimated tokens of the whole prompt with the fitted context
+    pub estimated: usize,
+    /// Whether the prompt fits; it may not even with every file dropped
+    pub fits: bool,
+}
+
+fn tokens(files: &[(PathBuf, String)]) -> usize {
+    files.iter().map(|(_, content)| JobsManager::estimate_tokens(content)).sum()
+}
+
+/// Trim `context_files` so that a prompt whose other parts (instructions,
+/// system prompt, generated files) come to `fixed_tokens` fits in `limit`
+/// tokens with room for the answer
+///
+/// The largest file is halved until the prompt fits; a file under
+/// `MIN_CONTEXT_LINES` lines is dropped. Cut files end with an omission
+/// marker.
+pub fn fit_context(context_files: &[(PathBuf, String)], fixed_tokens: usize, limit: usize) -> FittedContext {
+    let budget = usable(limit).saturating_sub(fixed_tokens + OUTPUT_RESERVE_TOKENS);
+    let mut files = context_files.to_vec();
+    let mut trimmed: Vec<PathBuf> = Vec::new();
+
+    while tokens(&files) > budget {
+        let Some(largest) = (0..files.len()).max_by_key(|&i| JobsManager::estimate_tokens(&files[i].1)) else {
+            break;
+        };
+        let (path, content) = &files[largest];
+        if !trimmed.contains(path) {
+            trimmed.push(path.clone());
+        }
+        let lines = content.lines().count();
+        if lines < MIN_CONTEXT_LINES * 2 {
+            files.remove(largest);
+        } else {
+            let cut = truncate_context(content, path, TruncationStrategy::Head, lines / 2, "");
+            files[largest].1 = cut;
+        }
+    }
+
+    let estimated = fixed_tokens + OUTPUT_RESERVE_TOKENS + tokens(&files);
+    FittedContext { files, trimmed, estimated, fits: estimated <= usable(limit) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, lines: usize) -> (PathBuf, String) {
+        (PathBuf::from(name), (0..lines).map(|i| format!("line {:>34}\n", i)).collect())
+    }
+
+    #[test]
+    fn test_fit_context_trims_largest_files_first() {
+        // 40 chars a line: 10 tokens
+        let context = vec![file("small.rs", 30), file("big.rs", 400)];
+        let untouched = fit_context(&context, 1000, 32000);
+        assert!(untouched.fits && untouched.trimmed.is_empty());
+        assert_eq!(untouched.files, context);
+
+        // 3100 usable: 1200 reserve + 1000 fixed leaves 900 for context (4300 now)
+        let fitted = fit_context(&context, 1000, 3445);
+        assert!(fitted.fits);
+        assert_eq!(fitted.trimmed, vec![PathBuf::from("big.rs")]);
+        assert_eq!(fitted.files[0], context[0]);
+        assert!(fitted.files[1].1.contains("lines omitted"));
+        assert!(fitted.estimated <= 3100);
+
+        // Generated output alone is over the limit: everything is dropped
+        let hopeless = fit_context(&context, 40000, 32000);
+        assert!(!hopeless.fits);
+        assert!(hopeless.files.is_empty());
+        assert_eq!(hopeless.trimmed.len(), 2);
+    }
+}
@@ -0,0 +1,343 @@
+//! Shrinking oversized context files (`context_truncation` in job frontmatter)
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::models::TruncationStrategy;
+
+/// Truncate `content` to at most `max_lines` lines using `strategy`.
+///
+/// Omitted regions are marked with `[... N lines omitted ...]` so the model
+/// knows the file is incomplete.
+pub fn truncate_context(
+    content: &str,
+    path: &Path,
+    strategy: TruncationStrategy,
+    max_lines: usize,
+    instructions: &str,
+) -> String {
+    if content.lines().count() <= max_lines {
+        return content.to_string();
+    }
+
+    let reduced = match strategy {
+        TruncationStrategy::Head => return head(content, max_lines),
+        TruncationStrategy::SignaturesOnly => signatures_only(content, path),
+        TruncationStrategy::RelevantSections => relevant_sections(content, max_lines, instructions),
+    };
+
+    // Heuristics may not shrink enough; fall back to keeping the head
+    head(&reduced, max_lines)
+}
+
+/// `content` with function bodies left out, for context that doesn't fit in
+/// the model's context window at all
+pub fn summarize(content: &str, path: &Path) -> String {
+    signatures_only(content, path)
+}
+
+fn omitted(count: usize) -> String {
+    format!("[... {} lines omitted ...]", count)
+}
+
+/// Keep the first `max_lines - 1` lines plus an omission marker
+fn head(content: &str, max_lines: usize) -> String {
+    let total = content.lines().count();
+    if total <= max_lines {
+        return content.to_string();
+    }
+    let keep = max_lines.saturating_sub(1);
+    let mut lines: Vec<String> = content.lines().take(keep).map(String::from).collect();
+    lines.push(omitted(total - keep));
+    lines.join("\n")
+}
+
+/// Strip function bodies, keeping declarations, types and signatures
+fn signatures_only(content: &str, path: &Path) -> String {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("py") => python_signatures(content),
+        _ => brace_signatures(content),
+    }
+}
+
+/// Whether a line opening a `{` block starts a function body (as opposed to
+/// a container such as a struct, impl, class or module)
+fn opens_function_body(line: &str) -> bool {
+    const CONTAINERS: &[&str] = &[
+        "struct ", "enum ", "impl ", "impl<", "trait ", "mod ", "class ", "interface ",
+        "namespace ", "contract ", "library ", "union ", "type ", "object ",
+    ];
+    let trimmed = line.trim_start();
+    if CONTAINERS.iter().any(|kw| {
+        trimmed.starts_with(kw) || trimmed.contains(&format!(" {}", kw))
+    }) {
+        return false;
+    }
+    trimmed.contains("fn ")
+        || trimmed.contains("function ")
+        || trimmed.contains("=>")
+        || (trimmed.contains('(') && trimmed.contains(')'))
+}
+
+/// Signature extraction for brace-delimited languages (Rust, TS/JS, Solidity, C#, ...)
+fn brace_signatures(content: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    // Depth at which the function body being skipped closes
+    let mut skip_until: Option<i32> = None;
+    let mut skipped = 0usize;
+    let mut depth: i32 = 0;
+
+    for line in content.lines() {
+        let opens = line.matches('{').count() as i32;
+        let closes = line.matches('}').count() as i32;
+        let new_depth = depth + opens - closes;
+
+        if let Some(target) = skip_until {
+            skipped += 1;
+            if new_depth <= target {
+                skip_until = None;
+                let indent: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+                out.push(format!("{}    // ... {} lines", indent, skipped.saturating_sub(1)));
+                out.push(line.to_string());
+                skipped = 0;
+            }
+            depth = new_depth;
+            continue;
+        }
+
+        out.push(line.to_string());
+        if new_depth > depth && opens > closes && opens_function_body(line) {
+            skip_until = Some(depth);
+        }
+        depth = new_depth;
+    }
+
+    out.join("\n")
+}
+
+/// Signature extraction for Python: keep `def`/`class` lines, drop bodies
+fn python_signatures(content: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut body_indent: Option<usize> = None;
+
+    for line in content.lines() {
+        let indent = line.len() - line.trim_start().len();
+        if let Some(def_indent) = body_indent {
+            if line.trim().is_empty() || indent > def_indent {
+                continue;
+            }
+            body_indent = None;
+        }
+        out.push(line.to_string());
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("def ") || trimmed.starts_with("async def ") {
+            out.push(format!("{}    ...", " ".repeat(indent)));
+            body_indent = Some(indent);
+        }
+    }
+
+    out.join("\n")
+}
+
+/// Split into top-level sections (blocks separated at brace depth 0 by blank lines)
+fn top_level_sections(content: &str) -> Vec<Vec<&str>> {
+    let mut sections: Vec<Vec<&str>> = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut depth: i32 = 0;
+
+    for line in content.lines() {
+        if depth == 0 && line.trim().is_empty() && !current.is_empty() {
+            sections.push(std::mem::take(&mut current));
+            continue;
+        }
+        current.push(line);
+        depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+        depth = depth.max(0);
+    }
+    if !current.is_empty() {
+        sections.push(current);
+    }
+    sections
+}
+
+/// Identifiers from the instructions worth matching against the file
+fn keywords(instructions: &str) -> HashSet<String> {
+    const STOPWORDS: &[&str] = &[
+        "this", "that", "with", "from", "should", "must", "will", "when", "then", "into",
+        "file", "code", "function", "method", "return", "returns", "create", "update", "make",
+        "each", "have", "uses", "using", "also", "only", "add", "implement",
+    ];
+    instructions
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|w| w.len() >= 4)
+        .map(str::to_lowercase)
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Keep the sections that mention the most instruction keywords
+fn relevant_sections(content: &str, max_lines: usize, instructions: &str) -> String {
+    let sections = top_level_sections(content);
+    let words = keywords(instructions);
+
+    let score = |section: &[&str]| -> usize {
+        let text = section.join("\n").to_lowercase();
+        words.iter().filter(|w| text.contains(w.as_str())).count()
+    };
+
+    // The first section usually holds imports; always keep it
+    let mut keep = vec![false; sections.len()];
+    let mut budget = max_lines;
+    if let Some(first) = sections.first() {
+        keep[0] = true;
+        budget = budget.saturating_sub(first.len() + 1);
+    }
+
+    let mut ranked: Vec<(usize, usize)> = sections
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, s)| (i, score(s)))
+        .filter(|(_, score)| *score > 0)
+        .collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    for (i, _) in ranked {
+        // Section lines + blank separator + possible omission marker
+        let cost = sections[i].len() + 2;
+        if cost <= budget {
+            keep[i] = true;
+            budget -= cost;
+        }
+    }
+
+    let mut out: Vec<String> = Vec::new();
+    let mut omitted_lines = 0usize;
+    for (i, section) in sections.iter().enumerate() {
+        if keep[i] {
+            if omitted_lines > 0 {
+                out.push(omitted(omitted_lines));
+                out.push(String::new());
+                omitted_lines = 0;
+            }
+            out.extend(section.iter().map(|l| l.to_string()));
+            out.push(String::new());
+        } else {
+            omitted_lines += section.len() + 1;
+        }
+    }
+    if omitted_lines > 0 {
+        out.push(omitted(omitted_lines));
+    }
+
+    out.join("\n").trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn numbered(n: usize) -> String {
+        (1..=n).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn test_small_content_untouched() {
+        let content = numbered(5);
+        let result = truncate_context(&content, &PathBuf::from("a.rs"), TruncationStrategy::Head, 10, "");
+        assert_eq!(result, content);
+    }
+
+    #[test]
+    fn test_head_truncation() {
+        let result = truncate_context(&numbered(20), &PathBuf::from("a.txt"), TruncationStrategy::Head, 5, "");
+        assert_eq!(result.lines().count(), 5);
+        assert!(result.starts_with("line 1\n"));
+        assert!(result.ends_with("[... 16 lines omitted ...]"));
+    }
+
+    #[test]
+    fn test_signatures_only_rust() {
+        let content = r#"use std::fmt;
+
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Self {
+        let p = Self { x, y };
+        p
+    }
+
+    pub fn len(&self) -> f64 {
+        let sq = (self.x * self.x + self.y * self.y) as f64;
+        sq.sqrt()
+    }
+}
+"#;
+        let result = signatures_only(content, &PathBuf::from("point.rs"));
+        assert!(result.contains("pub struct Point {"));
+        assert!(result.contains("    pub x: i32,"));
+        assert!(result.contains("    pub fn new(x: i32, y: i32) -> Self {"));
+        assert!(result.contains("    pub fn len(&self) -> f64 {"));
+        assert!(!result.contains("let p = Self"));
+        assert!(!result.contains("sq.sqrt()"));
+        assert!(result.contains("// ... 2 lines"));
+    }
+
+    #[test]
+    fn test_signatures_only_typescript_class() {
+        let content = r#"export class Store {
+  private items: string[] = [];
+
+  add(item: string): void {
+    this.items.push(item);
+  }
+}
+
+export function helper(a: number): number {
+  return a * 2;
+}
+"#;
+        let result = signatures_only(content, &PathBuf::from("store.ts"));
+        assert!(result.contains("export class Store {"));
+        assert!(result.contains("  private items: string[] = [];"));
+        assert!(result.contains("  add(item: string): void {"));
+        assert!(!result.contains("this.items.push"));
+        assert!(result.contains("export function helper(a: number): number {"));
+        assert!(!result.contains("return a * 2"));
+    }
+
+    #[test]
+    fn test_signatures_only_python() {
+        let content = "class Store:\n    def add(self, item):\n        self.items.append(item)\n        return item\n\ndef helper():\n    return 1\n";
+        let result = signatures_only(content, &PathBuf::from("store.py"));
+        assert_eq!(
+            result,
+            "class Store:\n    def add(self, item):\n        ...\ndef helper():\n    ..."
+        );
+    }
+
+    #[test]
+    fn test_relevant_sections_prefers_matching_blocks() {
+        let mut content = String::from("use std::io;\n\n");
+        for name in ["alpha", "parse_config", "gamma", "delta"] {
+            content.push_str(&format!("fn {}() {{\n    body();\n    body();\n}}\n\n", name));
+        }
+        let result = truncate_context(
+            &content,
+            &PathBuf::from("lib.rs"),
+            TruncationStrategy::RelevantSections,
+            10,
+            "Fix the bug in parse_config when the file is empty",
+        );
+        assert!(result.contains("use std::io;"));
+        assert!(result.contains("fn parse_config()"));
+        assert!(!result.contains("fn gamma()"));
+        assert!(result.contains("lines omitted"));
+        assert!(result.lines().count() <= 10);
+    }
+}
@@ -0,0 +1,262 @@
+use regex::Regex;
+use reqwest::Client;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, info, warn};
+
+use crate::error::WorkSplitError;
+use crate::models::UrlContextConfig;
+
+/// Name of the cache folder inside jobs/
+pub const CONTEXT_CACHE_DIR: &str = "_context_cache";
+
+/// Fetches `context_urls` and caches the (converted, truncated) text on disk
+pub struct UrlContextLoader {
+    client: Client,
+    cache_dir: PathBuf,
+    config: UrlContextConfig,
+}
+
+impl UrlContextLoader {
+    /// Create a loader that caches under `<jobs_dir>/_context_cache/`
+    pub fn new(jobs_dir: &Path, config: UrlContextConfig) -> Result<Self, WorkSplitError> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .map_err(|e| WorkSplitError::ContextUrlError {
+                url: String::new(),
+                message: e.to_string(),
+            })?;
+
+        Ok(Self {
+            client,
+            cache_dir: jobs_dir.join(CONTEXT_CACHE_DIR),
+            config,
+        })
+    }
+
+    /// Load a URL, using the cache when it is fresh enough.
+    ///
+    /// If the fetch fails and a stale cache entry exists, the stale copy is used.
+    pub async fn load(&self, url: &str) -> Result<String, WorkSplitError> {
+        let cache_path = self.cache_path(url);
+
+        if self.is_fresh(&cache_path) {
+            if let Ok(content) = fs::read_to_string(&cache_path) {
+                debug!("Using cached context for {}", url);
+                return Ok(content);
+            }
+        }
+
+        match self.fetch(url).await {
+            Ok(content) => {
+                fs::create_dir_all(&self.cache_dir)?;
+                fs::write(&cache_path, &content)?;
+                Ok(content)
+            }
+            Err(e) => match fs::read_to_string(&cache_path) {
+                Ok(stale) => {
+                    warn!("{} - using stale cached copy", e);
+                    Ok(stale)
+                }
+                Err(_) => Err(e),
+            },
+        }
+    }
+
+    async fn fetch(&self, url: &str) -> Result<String, WorkSplitError> {
+        info!("Fetching context URL: {}", url);
+        let fetch_error = |message: String| WorkSplitError::ContextUrlError {
+            url: url.to_string(),
+            message,
+        };
+
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| fetch_error(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(fetch_error(format!("HTTP {}", status.as_u16())));
+        }
+
+        let is_html = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.contains("text/html"));
+        let body = response.text().await.map_err(|e| fetch_error(e.to_string()))?;
+
+        let text = if self.config.html_to_text && (is_html || looks_like_html(&body)) {
+            html_to_text(&body)
+        } else {
+            body
+        };
+
+        Ok(truncate_lines(&text, self.config.max_lines))
+    }
+
+    fn is_fresh(&self, cache_path: &Path) -> bool {
+        let max_age = Duration::from_secs(self.config.cache_hours * 3600);
+        fs::metadata(cache_path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age < max_age)
+    }
+
+    fn cache_path(&self, url: &str) -> PathBuf {
+        self.cache_dir.join(cache_file_name(url))
+    }
+}
+
+/// Turn a URL into a readable, filesystem-safe cache file name
+pub fn cache_file_name(url: &str) -> String {
+    let without_scheme = url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://");
+    let mut name: String = without_scheme
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    name.truncate(120);
+
+    // FNV-1a keeps names unique when truncation or sanitizing collides
+    let hash = url
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+    format!("{}_{:08x}.txt", name, hash as u32)
+}
+
+fn looks_like_html(body: &str) -> bool {
+    let start = body.trim_start().get(..15).unwrap_or("").to_lowercase();
+    start.starts_with("<!doctype html") || start.starts_with("<html")
+}
+
+/// Crude HTML to text conversion: drops scripts/styles and tags, decodes
+/// common entities and collapses blank lines.
+pub fn html_to_text(html: &str) -> String {
+    let blocks = Regex::new(r"(?is)<(script|style|head|nav|footer)\b.*?</(script|style|head|nav|footer)>").unwrap();
+    let breaks = Regex::new(r"(?i)<(br|/p|/div|/li|/h[1-6]|/tr|/pre)\s*/?>").unwrap();
+    let tags = Regex::new(r"(?s)<[^>]+>").unwrap();
+
+    let text = blocks.replace_all(html, "");
+    let text = breaks.replace_all(&text, "\n");
+    let text = tags.replace_all(&text, "");
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+
+    let mut result = String::new();
+    let mut blank = false;
+    for line in text.lines().map(str::trim_end) {
+        if line.trim().is_empty() {
+            if !blank && !result.is_empty() {
+                result.push('\n');
+            }
+            blank = true;
+        } else {
+            result.push_str(line);
+            result.push('\n');
+            blank = false;
+        }
+    }
+    result.trim_end().to_string()
+}
+
+fn truncate_lines(text: &str, max_lines: usize) -> String {
+    let total = text.lines().count();
+    if total <= max_lines {
+        return text.to_string();
+    }
+    let mut kept: Vec<&str> = text.lines().take(max_lines).collect();
+    let note = format!("[... truncated {} of {} lines ...]", total - max_lines, total);
+    kept.push(&note);
+    kept.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_html_to_text() {
+        let html = r#"<!DOCTYPE html><html><head><title>x</title></head>
+<body><script>alert(1)</script><h1>API</h1><p>Use <code>get(&amp;key)</code>.</p>
+
+
+<ul><li>One</li><li>Two</li></ul></body></html>"#;
+        let text = html_to_text(html);
+        assert!(text.contains("API\n"));
+        assert!(text.contains("Use get(&key)."));
+        assert!(text.contains("One\nTwo"));
+        assert!(!text.contains("alert"));
+        assert!(!text.contains('<'));
+    }
+
+    #[test]
+    fn test_truncate_lines() {
+        let text = "a\nb\nc\nd";
+        assert_eq!(truncate_lines(text, 10), text);
+        assert_eq!(truncate_lines(text, 2), "a\nb\n[... truncated 2 of 4 lines ...]");
+    }
+
+    #[test]
+    fn test_cache_file_name() {
+        let a = cache_file_name("https://docs.rs/serde/latest/serde/");
+        let b = cache_file_name("https://docs.rs/serde/latest/serde/?x=1");
+        assert!(a.starts_with("docs.rs_serde_latest_serde_"));
+        assert!(a.ends_with(".txt"));
+        assert_ne!(a, b);
+    }
+
+    #[tokio::test]
+    async fn test_load_uses_fresh_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let loader = UrlContextLoader::new(temp_dir.path(), UrlContextConfig::default()).unwrap();
+        let url = "http://127.0.0.1:9/unreachable";
+
+        fs::create_dir_all(temp_dir.path().join(CONTEXT_CACHE_DIR)).unwrap();
+        fs::write(loader.cache_path(url), "cached docs").unwrap();
+
+        assert_eq!(loader.load(url).await.unwrap(), "cached docs");
+    }
+
+    #[tokio::test]
+    async fn test_load_fetches_and_caches() {
+        use axum::routing::get;
+        use axum::Router;
+
+        let app = Router::new().route(
+            "/doc",
+            get(|| async { axum::response::Html("<html><body><p>Hello docs</p></body></html>") }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let temp_dir = TempDir::new().unwrap();
+        let loader = UrlContextLoader::new(temp_dir.path(), UrlContextConfig::default()).unwrap();
+        let url = format!("http://{}/doc", addr);
+
+        assert_eq!(loader.load(&url).await.unwrap(), "Hello docs");
+        assert_eq!(fs::read_to_string(loader.cache_path(&url)).unwrap(), "Hello docs");
+    }
+
+    #[tokio::test]
+    async fn test_load_failure_without_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let loader = UrlContextLoader::new(temp_dir.path(), UrlContextConfig::default()).unwrap();
+        let result = loader.load("http://127.0.0.1:9/unreachable").await;
+        assert!(matches!(result, Err(WorkSplitError::ContextUrlError { .. })));
+    }
+}
@@ -50,6 +50,12 @@ pub enum WorkSplitError {
         output: String,
     },
 
+    #[error("Tests failed for {command}:\n{output}")]
+    TestsFailed {
+        command: String,
+        output: String,
+    },
+
     #[error("File too large: {path} has {lines} lines (max: {limit})\n\nManager action required:\n{suggestion}")]
     FileTooLarge {
         path: PathBuf,
@@ -73,6 +79,15 @@ pub enum WorkSplitError {
     #[error("Context file not found: {0}")]
     ContextFileNotFound(PathBuf),
 
+    #[error("Context symbol not found in the project: {0}")]
+    SymbolNotFound(String),
+
+    #[error("Instruction anchor {anchor}: {message}")]
+    InvalidAnchor { anchor: String, message: String },
+
+    #[error("Job workdir not found: {0}")]
+    WorkdirNotFound(PathBuf),
+
     #[error("Context file too large: {path} has {lines} lines (max: {max})")]
     ContextFileTooLarge {
         path: PathBuf,
@@ -80,8 +95,11 @@ pub enum WorkSplitError {
         max: usize,
     },
 
-    #[error("Output exceeded line limit: {lines} lines (max: {max})")]
-    OutputTooLarge { lines: usize, max: usize },
+    #[error("Failed to fetch context URL {url}: {message}")]
+    ContextUrlError { url: String, message: String },
+
+    #[error("Output exceeded line limit:\n{0}")]
+    OutputTooLarge(String),
 
     #[error("Token budget exceeded: estimated {estimated} tokens (max: {max})")]
     TokenBudgetExceeded { estimated: usize, max: usize },
@@ -96,8 +114,29 @@ pub enum WorkSplitError {
     #[error("Edit failed: {0}")]
     EditFailed(String),
 
-    #[error("Cannot write to protected path: {0}")]
-    ProtectedPathViolation(PathBuf),
+    #[error("No code extracted: {0}")]
+    EmptyExtraction(String),
+
+    #[error("Output expectations not met:\n{0}")]
+    ExpectationFailed(String),
+
+    #[error("Output path check failed:\n{0}")]
+    OutputPathConflict(String),
+
+    #[error("Refusing to write {0}: outside the project root")]
+    OutputOutsideRoot(PathBuf),
+
+    #[error("Refusing to write {path}: not under [safety] allowed_output_dirs ({allowed})")]
+    OutputNotAllowed { path: PathBuf, allowed: String },
+
+    #[error("Refusing to write {path}: protected by {rule}")]
+    ProtectedPathViolation { path: PathBuf, rule: String },
+
+    #[error("Refusing to write {path}: frozen with job '{job_id}' (`worksplit freeze`); run with --force to overwrite it or `worksplit freeze --remove {job_id}` to unfreeze it")]
+    FrozenOutput { path: PathBuf, job_id: String },
+
+    #[error("Possible prompt injection in {source_name} ([safety] injection_check = \"refuse\"):\n{findings}")]
+    InjectionDetected { source_name: String, findings: String },
 
     #[error("Invalid job name: {0}")]
     InvalidJobName(String),
@@ -108,6 +147,9 @@ pub enum WorkSplitError {
     #[error("Initialization error: {0}")]
     InitError(String),
 
+    #[error("Interactive input failed: {0}")]
+    InteractiveInput(String),
+
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
@@ -119,6 +161,24 @@ pub enum WorkSplitError {
 
     #[error("IO error: {0}")]
     IoError(String),
+
+    #[error("External verification error: {0}")]
+    ExternalVerification(String),
+
+    #[error("Target pattern '{0}' matched no files")]
+    TargetPatternNoMatch(String),
+
+    #[error("Invalid pattern in .worksplitignore line {line}: {pattern}")]
+    InvalidIgnorePattern { line: usize, pattern: String },
+
+    #[error("Prompt template {path}: {message}")]
+    PromptTemplate { path: PathBuf, message: String },
+
+    #[error("Offline mode: {component} would contact non-local address {url}")]
+    OfflineViolation { component: String, url: String },
+
+    #[error("Invalid bundle {path}: {message}")]
+    InvalidBundle { path: PathBuf, message: String },
 }
 
 /// Errors related to job file parsing
@@ -135,6 +195,9 @@ pub enum JobParseError {
 
     #[error("Invalid YAML in {0}: {1}")]
     YamlError(PathBuf, String),
+
+    #[error("Failed to read instructions_file {1} of {0}: {2}")]
+    InstructionsFileError(PathBuf, PathBuf, std::io::Error),
 }
 
 /// Errors related to status file operations
@@ -149,8 +212,14 @@ pub enum StatusError {
     #[error("Failed to parse status file {0}: {1}")]
     ParseError(PathBuf, String),
 
+    #[error("Status database {0}: {1}")]
+    Database(PathBuf, String),
+
     #[error("Job not found in status file: {0}")]
     JobNotFound(String),
+
+    #[error("Job already exists in status file: {0}")]
+    JobExists(String),
 }
 
 /// Errors related to Ollama API
@@ -162,9 +231,15 @@ pub enum OllamaError {
     #[error("Request timeout after {0} seconds")]
     Timeout(u64),
 
+    #[error("Generation stalled: no tokens for {0} seconds (raise [ollama] stall_timeout_seconds for slow models)")]
+    StallTimeout(u64),
+
     #[error("HTTP error: {status} - {message}")]
     HttpError { status: u16, message: String },
 
+    #[error("Prompt exceeds the model's context window: {0}")]
+    ContextOverflow(String),
+
     #[error("Failed to parse response: {0}")]
     ParseError(String),
 
@@ -179,6 +254,9 @@ pub enum OllamaError {
         duration_secs: u64,
         thinking_tokens: usize,
     },
+
+    #[error("Mock fixture error: {0}")]
+    MockFixture(String),
 }
 
 impl From<reqwest::Error> for OllamaError {
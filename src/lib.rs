@@ -11,6 +11,13 @@
 //! - **models**: Data structures (config, job, status)
 //! - **error**: Error types
 //! - **templates**: Language-specific templates for project initialization
+//!
+//! # Features
+//!
+//! - **server** (default): `commands::serve`, the web dashboard and REST API
+//! - **rust-ast** (default): `ITEM:` edit blocks that locate Rust items with `syn`
+//! - **notify** (default): desktop and webhook notifications (`[notify]`)
+//! - **embeddings** (default): context retrieval by embedding similarity (`[retrieval]`)
 
 pub mod commands;
 pub mod core;
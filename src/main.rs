@@ -1,8 +1,7 @@
 #![allow(dead_code)]
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
-use tracing::Level;
-use tracing_subscriber::FmtSubscriber;
+use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
 mod commands;
 mod core;
@@ -11,10 +10,19 @@ mod models;
 mod templates;
 
 use commands::{
-    archive_jobs, cancel_jobs, cleanup_archived_jobs, create_new_job, fix_all_jobs, fix_job,
-    init_project, lint_jobs, preview_job, print_validation_result, retry_job, run_jobs,
-    show_status, validate_jobs, RunOptions,
+    apply_staged, archive_jobs, ArchiveSelection, bench, cancel_jobs, cleanup_archived_jobs, create_new_job, fix_all_jobs, fix_job, freeze_jobs,
+    create_new_job_from_template, create_new_job_interactive, experiment, fix_build_errors, init_project, lint_jobs, preview_job, print_prompt, print_validation_result, read_instructions, rename_job, retry_jobs, scan_todo_jobs, show_frozen, unfreeze_jobs,
+    run_all_projects, run_jobs, run_oneshot_job, show_status, validate_jobs, enqueue_jobs, list_templates, run_worker, warm_up_model, BenchOptions, Editor, ExperimentOptions, OneshotOptions, RunOptions, WorkerOptions,
 };
+use commands::reset::JobSelection;
+#[cfg(feature = "bundle")]
+use commands::{export_jobs, import_jobs};
+#[cfg(feature = "schema")]
+use commands::dump_schema;
+#[cfg(feature = "server")]
+use commands::serve;
+#[cfg(feature = "schema")]
+use core::schema::SchemaKind;
 use models::{JobTemplate, Language};
 
 /// WorkSplit - Ollama-powered job orchestrator for code generation
@@ -26,6 +34,16 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// Log filter directives (e.g. "info,worksplit::core::ollama=debug").
+    /// Takes precedence over --verbose, which takes precedence over RUST_LOG.
+    #[arg(long, global = true, value_name = "FILTER")]
+    log_filter: Option<String>,
+
+    /// Fail fast if anything would contact a non-localhost address
+    /// (Ollama URL, external verification, context URLs)
+    #[arg(long, global = true)]
+    offline: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -48,28 +66,114 @@ enum Commands {
         /// which model they want before running this command.
         #[arg(short, long)]
         model: Option<String>,
+
+        /// Set up editor tasks (e.g. `.vscode/tasks.json`); in an existing
+        /// project only the editor tasks are added
+        #[arg(long, value_enum)]
+        editor: Option<Editor>,
     },
 
     /// Reset job status
     Reset {
-        /// Job ID to reset (or "all" for all failed jobs)
-        job: String,
+        /// Jobs to reset: IDs, globs (auth_*), tag:<name> or "all" (all failed jobs)
+        jobs: Vec<String>,
 
-        /// Reset all jobs matching status (e.g., "fail", "partial")
+        /// Only jobs with this status (e.g., "fail", "partial")
         #[arg(long)]
         status: Option<String>,
+
+        /// Only jobs with this tag
+        #[arg(long)]
+        tag: Option<String>,
+    },
+
+    /// Rename a job, keeping its status and updating depends_on in other jobs
+    Mv {
+        /// Current job ID
+        old: String,
+
+        /// New job ID
+        new: String,
+    },
+
+    /// Create edit-mode jobs from `TODO(worksplit): ...` comments in the project
+    ScanTodos {
+        /// List the jobs that would be created without writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Package jobs, their context and prompts into a bundle for another machine
+    #[cfg(feature = "bundle")]
+    Export {
+        /// Jobs to export: IDs, globs or tag:<name> (all jobs when empty)
+        selectors: Vec<String>,
+
+        /// Bundle file to write
+        #[arg(short, long, default_value = "worksplit-bundle.tar.gz")]
+        output: PathBuf,
     },
 
-    /// Cancel a running job (or all)
+    /// Unpack a bundle made by `worksplit export`, including outputs and status
+    #[cfg(feature = "bundle")]
+    Import {
+        /// Bundle file to read
+        bundle: PathBuf,
+
+        /// Replace local context files and worksplit.toml that differ from the bundle
+        #[arg(long)]
+        overwrite: bool,
+
+        /// Overwrite frozen files (`worksplit freeze`)
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Cancel running jobs
     Cancel {
-        /// Job ID to cancel (or "all")
-        job: String,
+        /// Jobs to cancel: IDs, globs (auth_*), tag:<name> or "all"
+        jobs: Vec<String>,
+
+        /// Only jobs with this status (e.g., "pending_work")
+        #[arg(long)]
+        status: Option<String>,
+
+        /// Only jobs with this tag
+        #[arg(long)]
+        tag: Option<String>,
     },
 
-    /// Retry a job (reset + run)
+    /// Retry jobs (reset + run)
     Retry {
-        /// Job ID to retry
-        job: String,
+        /// Jobs to retry: IDs, globs (auth_*), tag:<name> or "all" (all failed jobs)
+        jobs: Vec<String>,
+
+        /// Only jobs with this status (e.g., "fail", "partial")
+        #[arg(long)]
+        status: Option<String>,
+
+        /// Only jobs with this tag
+        #[arg(long)]
+        tag: Option<String>,
+    },
+
+    /// Write-protect the outputs of passed jobs: runs refuse to overwrite
+    /// them without --force
+    Freeze {
+        /// Jobs to freeze: IDs, globs (auth_*), tag:<name> or "all"; none lists the frozen files
+        jobs: Vec<String>,
+
+        /// Freeze every passed job
+        #[arg(long)]
+        passed: bool,
+
+        /// Only jobs with this tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Unfreeze the files of the selected jobs instead
+        #[arg(long)]
+        remove: bool,
     },
 
     /// Run pending jobs
@@ -94,6 +198,11 @@ enum Commands {
         #[arg(long)]
         reset: Option<String>,
 
+        /// Apply a named [profile.<name>] section of worksplit.toml
+        /// (e.g. fast, quality); --model/--url/--timeout still win
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
         /// Override the model to use
         #[arg(long)]
         model: Option<String>,
@@ -126,6 +235,141 @@ enum Commands {
         /// By default, jobs are only run once; use this to force re-execution
         #[arg(long)]
         rerun: bool,
+
+        /// Overwrite files generated by other passed jobs, even with
+        /// `[safety] owned_outputs = "refuse"`, and frozen files
+        #[arg(long)]
+        force: bool,
+
+        /// Read-only mode: write outputs to jobs/_staging/ instead of the project
+        /// (review them, then use `worksplit apply`)
+        #[arg(long)]
+        plan_only: bool,
+
+        /// Finish a partial edit job: re-prompt only the edits that failed
+        #[arg(long = "continue", value_name = "JOB")]
+        continue_job: Option<String>,
+
+        /// Stop starting new jobs after N jobs (remaining jobs are skipped)
+        #[arg(long, value_name = "N")]
+        max_jobs: Option<usize>,
+
+        /// Stop starting new jobs after M minutes of wall time
+        #[arg(long, value_name = "M")]
+        max_minutes: Option<u64>,
+
+        /// Stop starting new jobs once T LLM tokens (prompt + generated) are used
+        #[arg(long, value_name = "T")]
+        max_tokens: Option<u64>,
+
+        /// Simulate the run: read model responses per job from fixture files
+        /// in DIR instead of calling Ollama
+        #[arg(long, value_name = "DIR", conflicts_with = "replay")]
+        mock: Option<PathBuf>,
+
+        /// Record every model request and response to jobs/_recordings/
+        #[arg(long)]
+        record: bool,
+
+        /// Rerun the pipeline with the responses of a `--record` recording
+        /// instead of calling Ollama
+        #[arg(long, value_name = "RECORDING")]
+        replay: Option<PathBuf>,
+
+        /// Copy each job's token stream to DIR/<job>.log as it arrives (for
+        /// `tail -f`), also with --no-stream
+        #[arg(long, value_name = "DIR")]
+        stream_to: Option<PathBuf>,
+
+        /// Run the queues of every project listed in worksplit-workspace.toml
+        /// in the current directory and print one combined summary
+        #[arg(long, conflicts_with_all = ["job", "reset", "continue_job"])]
+        all_projects: bool,
+
+        /// GitHub Actions output: a log group per job, annotations on the
+        /// generated files, and exit code 2 (failed jobs), 4 (partial jobs)
+        /// or 6 (both)
+        #[arg(long)]
+        ci: bool,
+    },
+
+    /// Copy outputs staged by `run --plan-only` into the project
+    Apply {
+        /// Only apply the outputs of this job
+        #[arg(short, long)]
+        job: Option<String>,
+
+        /// Show what would be applied without copying files
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Compare system prompt variants: run the same jobs once per variant,
+    /// each in a temporary copy of the project, and report pass rates and
+    /// durations
+    Experiment {
+        /// Prompt variants to compare (comma-separated files)
+        #[arg(long, value_delimiter = ',', required = true)]
+        prompts: Vec<PathBuf>,
+
+        /// Jobs to run: `tag:<name>`, a glob like `auth_*` or job IDs
+        /// (comma-separated; default: all jobs)
+        #[arg(long, value_delimiter = ',')]
+        jobs: Vec<String>,
+
+        /// System prompt the variants replace (create, verify, edit,
+        /// verify_edit, split, test, fix)
+        #[arg(long, default_value = "create")]
+        kind: String,
+
+        /// Override the model to use
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Override the Ollama URL
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Override the timeout in seconds
+        #[arg(long)]
+        timeout: Option<u64>,
+
+        /// Read model responses per job from fixture files in DIR instead
+        /// of calling Ollama
+        #[arg(long, value_name = "DIR")]
+        mock: Option<PathBuf>,
+    },
+
+    /// Benchmark models: run reference jobs on each model, each run in a
+    /// temporary copy of the project, and report pass rate, duration and
+    /// tokens
+    Bench {
+        /// Models to compare (comma-separated); a bare tag reuses the
+        /// previous model's name: `qwen2.5-coder:7b,14b`
+        #[arg(long, value_delimiter = ',', required = true)]
+        models: Vec<String>,
+
+        /// Reference jobs: `tag:<name>`, a glob like `bench_*` or job IDs
+        /// (comma-separated)
+        #[arg(long, value_delimiter = ',', required = true)]
+        job: Vec<String>,
+
+        /// How many times each job runs per model
+        #[arg(long, default_value_t = 1)]
+        runs: usize,
+
+        /// Override the Ollama URL
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Override the timeout in seconds
+        #[arg(long)]
+        timeout: Option<u64>,
+
+        /// Read model responses per job from fixture files in DIR instead
+        /// of calling Ollama
+        #[arg(long, value_name = "DIR")]
+        mock: Option<PathBuf>,
     },
 
     /// Show job status
@@ -133,10 +377,28 @@ enum Commands {
         /// Show detailed status for each job
         #[arg(short, long)]
         verbose: bool,
+
+        /// Group failed and partial jobs by why they failed
+        #[arg(long)]
+        by_failure: bool,
+
+        /// Report what changed since a time: last-run, a date (2024-05-01),
+        /// a time (2024-05-01 09:00), an RFC 3339 timestamp or an age (12h, 2d)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Show the file cache statistics of the latest run
+        #[arg(long)]
+        cache: bool,
     },
 
     /// Validate jobs folder structure
-    Validate,
+    Validate {
+        /// Also check job files for authoring problems (short instructions,
+        /// untagged code blocks, missing targets, ...) and suggest fixes
+        #[arg(long)]
+        lint: bool,
+    },
 
     /// Preview the prompt for a job without running it
     Preview {
@@ -144,6 +406,20 @@ enum Commands {
         job: String,
     },
 
+    /// Print the exact prompt a job would send, with system message and context
+    Prompt {
+        /// Job ID
+        job: String,
+
+        /// Phase to assemble (default: the first one a run prompts for)
+        #[arg(long, value_enum)]
+        phase: Option<core::PromptPhase>,
+
+        /// Copy to the clipboard instead of printing
+        #[arg(long)]
+        copy: bool,
+    },
+
     /// Run linter on generated files
     Lint {
         /// Specific job to lint (if omitted, lints all passed jobs)
@@ -151,10 +427,24 @@ enum Commands {
         job: Option<String>,
     },
 
-    /// Auto-fix linter errors using LLM
+    /// Auto-fix lint errors in a job's output, or build errors (--files / --from-build)
     Fix {
-        /// Job ID whose output to fix
-        job: String,
+        /// Job ID whose output to fix (lint errors)
+        #[arg(required_unless_present_any = ["files", "from_build"], conflicts_with_all = ["files", "from_build"])]
+        job: Option<String>,
+
+        /// Run the build and fix its errors in these files, without a job
+        /// (comma-separated)
+        #[arg(long, value_delimiter = ',', conflicts_with = "from_build")]
+        files: Vec<PathBuf>,
+
+        /// Run the build and fix its errors in the files the build output names
+        #[arg(long)]
+        from_build: bool,
+
+        /// Workspace whose build command to run (see [workspaces])
+        #[arg(long)]
+        workdir: Option<PathBuf>,
     },
 
     /// Auto-fix all failed jobs
@@ -163,19 +453,40 @@ enum Commands {
     /// Create a new job from a template
     NewJob {
         /// Job name (e.g., "auth_001_login")
-        name: String,
+        #[arg(required_unless_present = "interactive")]
+        name: Option<String>,
+
+        /// Walk through template, paths, context files and instructions
+        /// step by step
+        #[arg(long, short = 'i')]
+        interactive: bool,
 
         /// Job template type
         #[arg(long = "type", short = 't', value_enum, default_value = "replace")]
         template: JobTemplate,
 
+        /// Template to fill in: a project template, jobs/_templates/<NAME>.md,
+        /// or a built-in type (see `worksplit templates list`)
+        #[arg(long = "template", value_name = "NAME", conflicts_with_all = ["template", "interactive"])]
+        project_template: Option<String>,
+
+        /// Value for a project template placeholder: `--set entity=order`
+        /// fills `{{entity}}` (repeatable)
+        #[arg(long = "set", value_name = "KEY=VALUE", requires = "project_template")]
+        set: Vec<String>,
+
+        /// Language whose file naming the job follows (defaults to the
+        /// project's configured language)
+        #[arg(short, long, visible_alias = "language", value_enum)]
+        lang: Option<Language>,
+
         /// Target files for edit mode
         #[arg(long = "targets", value_delimiter = ',')]
         target_files: Option<Vec<PathBuf>>,
 
-        /// Output directory
-        #[arg(long, short = 'o', default_value = "src/")]
-        output_dir: PathBuf,
+        /// Output directory (defaults to the language's source directory)
+        #[arg(long, short = 'o')]
+        output_dir: Option<PathBuf>,
 
         /// Output filename (defaults to job name + appropriate extension)
         #[arg(long, short = 'f')]
@@ -186,8 +497,99 @@ enum Commands {
         context_files: Option<Vec<PathBuf>>,
     },
 
-    /// Archive completed jobs older than X days
+    /// Move jobs into jobs/queue/ for `worksplit worker` to pick up
+    Enqueue {
+        /// Jobs to queue: IDs, globs or tag:<name>
+        #[arg(required = true)]
+        selectors: Vec<String>,
+    },
+
+    /// Claim and run queued jobs; several workers can share one jobs folder
+    Worker {
+        /// Exit once the queue is empty instead of waiting for more jobs
+        #[arg(long)]
+        once: bool,
+
+        /// Seconds between checks of an empty queue
+        #[arg(long, default_value = "10")]
+        poll: u64,
+
+        /// Requeue jobs claimed more than this many minutes ago (by workers that died)
+        #[arg(long, value_name = "MINUTES")]
+        requeue_after: Option<u64>,
+
+        /// Name recorded in claims and results (default: <host>-<pid>)
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Apply a named [profile.<name>] section of worksplit.toml
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// Override the model to use
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Override the Ollama URL
+        #[arg(long)]
+        url: Option<String>,
+    },
+
+    /// Load the model into memory and report how long it took
+    Warmup {
+        /// Apply a named [profile.<name>] section of worksplit.toml
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// Override the model to use
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Override the Ollama URL
+        #[arg(long)]
+        url: Option<String>,
+    },
+
+    /// Generate one file from instructions without creating a job file
+    ///
+    /// Runs the full create/verify pipeline on a temporary job that is
+    /// removed afterwards. Exits non-zero if the generation fails.
+    Oneshot {
+        /// Instructions, or `-` to read them from stdin
+        #[arg(conflicts_with = "message")]
+        instructions: Option<String>,
+
+        /// Instructions as an option
+        #[arg(short, long)]
+        message: Option<String>,
+
+        /// File to generate, relative to the project root
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Context file (can be given several times)
+        #[arg(short, long)]
+        context: Vec<PathBuf>,
+
+        /// Apply a named [profile.<name>] section of worksplit.toml
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// Override the model to use
+        #[arg(long)]
+        model: Option<String>,
+    },
+
+    /// Archive completed jobs older than X days, or the given jobs
     Archive {
+        /// Jobs to archive; they must have passed
+        #[arg(conflicts_with_all = ["days", "passed"])]
+        jobs: Vec<String>,
+
+        /// Archive every passed job, however recent
+        #[arg(long, conflicts_with = "days")]
+        passed: bool,
+
         /// Days threshold (uses config default if not specified)
         #[arg(short, long)]
         days: Option<u32>,
@@ -208,6 +610,36 @@ enum Commands {
         dry_run: bool,
     },
 
+    /// Start the web dashboard and REST API
+    #[cfg(feature = "server")]
+    Serve {
+        /// Port to listen on
+        #[arg(short, long, default_value = "8080")]
+        port: u16,
+
+        /// Address to bind to (use 0.0.0.0 to expose on the network)
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// Accept test_scope and env (commands run on this machine) in jobs
+        /// submitted through the API
+        #[arg(long)]
+        allow_job_commands: bool,
+    },
+
+    /// JSON Schemas for job files, worksplit.toml and _jobstatus.json
+    #[cfg(feature = "schema")]
+    Schema {
+        #[command(subcommand)]
+        action: SchemaAction,
+    },
+
+    /// Job templates for `new-job`
+    Templates {
+        #[command(subcommand)]
+        action: TemplatesAction,
+    },
+
     /// Print the full README documentation
     ///
     /// AI Agents: Use this command to get complete documentation, including
@@ -215,37 +647,118 @@ enum Commands {
     Readme,
 }
 
+#[derive(Subcommand)]
+enum TemplatesAction {
+    /// List the built-in templates and the project's jobs/_templates/*.md
+    /// with the placeholders each needs
+    List,
+}
+
+#[cfg(feature = "schema")]
+#[derive(Subcommand)]
+enum SchemaAction {
+    /// Print a schema, or write all schemas to a directory
+    Dump {
+        /// Schema to print (job, config, status)
+        #[arg(value_enum)]
+        kind: Option<SchemaKind>,
+
+        /// Write schema files to this directory instead of printing
+        #[arg(long, value_name = "DIR")]
+        out: Option<PathBuf>,
+    },
+}
+
+/// Build the log filter: `--log-filter` wins, then `--verbose`, then
+/// `RUST_LOG` (passed in as `rust_log`), then `info`.
+fn build_log_filter(log_filter: Option<&str>, verbose: bool, rust_log: Option<&str>) -> EnvFilter {
+    if let Some(directives) = log_filter {
+        match EnvFilter::try_new(directives) {
+            Ok(filter) => return filter,
+            Err(e) => eprintln!("Warning: invalid --log-filter '{}': {}", directives, e),
+        }
+    }
+    if verbose {
+        return EnvFilter::new("debug");
+    }
+
+    rust_log
+        .and_then(|directives| EnvFilter::try_new(directives).ok())
+        .unwrap_or_else(|| EnvFilter::new("info"))
+}
+
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
 
     // Set up logging
-    let level = if cli.verbose { Level::DEBUG } else { Level::INFO };
-    let _subscriber = FmtSubscriber::builder()
-        .with_max_level(level)
+    let rust_log = std::env::var(EnvFilter::DEFAULT_ENV).ok();
+    let filter = build_log_filter(cli.log_filter.as_deref(), cli.verbose, rust_log.as_deref());
+    FmtSubscriber::builder()
+        .with_env_filter(filter)
         .with_target(false)
         .without_time()
+        .with_writer(core::progress::LogWriter)
         .init();
 
+    if cli.offline {
+        std::env::set_var(core::offline::OFFLINE_ENV, "1");
+    }
+
     let result = match cli.command {
-        Commands::Init { path, lang, model } => {
+        Commands::Init { path, lang, model, editor } => {
             let project_root = path.unwrap_or_else(|| std::env::current_dir().unwrap());
-            init_project(&project_root, lang, model)
+            init_project(&project_root, lang, model, editor)
+        }
+
+        Commands::Reset { jobs, status, tag } => {
+            let project_root = std::env::current_dir().unwrap();
+            let selection = JobSelection { selectors: jobs, status, tag };
+            crate::commands::reset::reset_jobs(&project_root, &selection).map(|_| ())
+        }
+
+        Commands::Mv { old, new } => {
+            let project_root = std::env::current_dir().unwrap();
+            rename_job(&project_root, &old, &new)
         }
 
-        Commands::Reset { job, status } => {
+        Commands::ScanTodos { dry_run } => {
             let project_root = std::env::current_dir().unwrap();
-            crate::commands::reset::reset_jobs(&project_root, &job, status.as_deref())
+            scan_todo_jobs(&project_root, dry_run)
         }
 
-        Commands::Cancel { job } => {
+        #[cfg(feature = "bundle")]
+        Commands::Export { selectors, output } => {
             let project_root = std::env::current_dir().unwrap();
-            cancel_jobs(&project_root, &job)
+            export_jobs(&project_root, &selectors, &output)
         }
 
-        Commands::Retry { job } => {
+        #[cfg(feature = "bundle")]
+        Commands::Import { bundle, overwrite, force } => {
             let project_root = std::env::current_dir().unwrap();
-            retry_job(&project_root, &job).await
+            import_jobs(&project_root, &bundle, overwrite, force)
+        }
+
+        Commands::Cancel { jobs, status, tag } => {
+            let project_root = std::env::current_dir().unwrap();
+            cancel_jobs(&project_root, &JobSelection { selectors: jobs, status, tag }).map(|_| ())
+        }
+
+        Commands::Retry { jobs, status, tag } => {
+            let project_root = std::env::current_dir().unwrap();
+            retry_jobs(&project_root, &JobSelection { selectors: jobs, status, tag }).await
+        }
+
+        Commands::Freeze { jobs, passed, tag, remove } => {
+            let project_root = std::env::current_dir().unwrap();
+            let selection = JobSelection { selectors: jobs, status: passed.then(|| "pass".to_string()), tag };
+            if remove {
+                unfreeze_jobs(&project_root, &selection).map(|_| ())
+            } else if selection.is_empty() {
+                show_frozen(&project_root)
+            } else {
+                freeze_jobs(&project_root, &selection).map(|_| ())
+            }
         }
 
         Commands::Run {
@@ -254,6 +767,7 @@ async fn main() {
             resume,
             job_timeout,
             reset,
+            profile,
             model,
             url,
             timeout,
@@ -262,6 +776,18 @@ async fn main() {
             batch,
             max_concurrent,
             rerun,
+            force,
+            plan_only,
+            continue_job,
+            max_jobs,
+            max_minutes,
+            max_tokens,
+            mock,
+            record,
+            replay,
+            stream_to,
+            all_projects,
+            ci,
         } => {
             let project_root = std::env::current_dir().unwrap();
             let options = RunOptions {
@@ -269,6 +795,7 @@ async fn main() {
                 dry_run,
                 resume,
                 reset,
+                profile,
                 model,
                 url,
                 timeout,
@@ -278,18 +805,50 @@ async fn main() {
                 batch,
                 max_concurrent,
                 rerun,
+                force,
+                plan_only,
+                continue_job,
+                max_jobs,
+                max_minutes,
+                max_tokens,
+                mock,
+                record,
+                replay,
+                stream_to,
+                ci,
             };
-            run_jobs(&project_root, options).await
+            if all_projects {
+                run_all_projects(&project_root, options).await.map(|_| ())
+            } else {
+                run_jobs(&project_root, options).await
+            }
+        }
+
+        Commands::Apply { job, dry_run } => {
+            let project_root = std::env::current_dir().unwrap();
+            apply_staged(&project_root, job.as_deref(), dry_run)
+        }
+
+        Commands::Experiment { prompts, jobs, kind, model, url, timeout, mock } => {
+            let project_root = std::env::current_dir().unwrap();
+            let options = ExperimentOptions { prompts, jobs, kind, model, url, timeout, mock };
+            experiment(&project_root, options).await
+        }
+
+        Commands::Bench { models, job, runs, url, timeout, mock } => {
+            let project_root = std::env::current_dir().unwrap();
+            let options = BenchOptions { models, jobs: job, runs, url, timeout, mock };
+            bench(&project_root, options).await
         }
 
-        Commands::Status { verbose } => {
+        Commands::Status { verbose, by_failure, since, cache } => {
             let project_root = std::env::current_dir().unwrap();
-            show_status(&project_root, verbose)
+            show_status(&project_root, verbose, by_failure, since.as_deref(), cache)
         }
 
-        Commands::Validate => {
+        Commands::Validate { lint } => {
             let project_root = std::env::current_dir().unwrap();
-            match validate_jobs(&project_root) {
+            match validate_jobs(&project_root, lint) {
                 Ok(result) => {
                     print_validation_result(&result);
                     if result.valid {
@@ -307,14 +866,22 @@ async fn main() {
             preview_job(&project_root, &job)
         }
 
+        Commands::Prompt { job, phase, copy } => {
+            let project_root = std::env::current_dir().unwrap();
+            print_prompt(&project_root, &job, phase, copy).await
+        }
+
         Commands::Lint { job } => {
             let project_root = std::env::current_dir().unwrap();
             lint_jobs(&project_root, job.as_deref())
         }
 
-        Commands::Fix { job } => {
+        Commands::Fix { job, files, from_build: _, workdir } => {
             let project_root = std::env::current_dir().unwrap();
-            fix_job(&project_root, &job).await
+            match job {
+                Some(job) => fix_job(&project_root, &job).await,
+                None => fix_build_errors(&project_root, &files, workdir.as_deref()).await,
+            }
         }
 
         Commands::FixAll => {
@@ -324,27 +891,82 @@ async fn main() {
 
         Commands::NewJob {
             name,
+            interactive,
             template,
+            project_template,
+            set,
+            lang,
             target_files,
             output_dir,
             output_file,
             context_files,
         } => {
             let project_root = std::env::current_dir().unwrap();
-            create_new_job(
-                &project_root,
-                &name,
-                template,
-                target_files,
-                &output_dir,
-                output_file,
-                context_files,
-            )
+            if interactive {
+                create_new_job_interactive(&project_root, name, lang)
+            } else if let Some(project_template) = project_template {
+                create_new_job_from_template(
+                    &project_root,
+                    &name.unwrap_or_default(),
+                    &project_template,
+                    lang,
+                    target_files,
+                    output_dir,
+                    output_file,
+                    context_files,
+                    &set,
+                )
+            } else {
+                create_new_job(
+                    &project_root,
+                    &name.unwrap_or_default(),
+                    template,
+                    lang,
+                    target_files,
+                    output_dir,
+                    output_file,
+                    context_files,
+                )
+            }
+        }
+
+        Commands::Enqueue { selectors } => {
+            let project_root = std::env::current_dir().unwrap();
+            enqueue_jobs(&project_root, &selectors)
+        }
+
+        Commands::Worker { once, poll, requeue_after, name, profile, model, url } => {
+            let project_root = std::env::current_dir().unwrap();
+            let options = WorkerOptions { once, poll_seconds: poll, requeue_after, name, profile, model, url };
+            run_worker(&project_root, options).await
         }
 
-        Commands::Archive { days, dry_run } => {
+        Commands::Warmup { profile, model, url } => {
             let project_root = std::env::current_dir().unwrap();
-            match archive_jobs(&project_root, days, dry_run) {
+            warm_up_model(&project_root, profile.as_deref(), model, url).await
+        }
+
+        Commands::Oneshot { instructions, message, output, context, profile, model } => {
+            let project_root = std::env::current_dir().unwrap();
+            match read_instructions(instructions.as_deref(), message) {
+                Ok(instructions) => {
+                    let options = OneshotOptions { output, context, instructions, profile, model };
+                    run_oneshot_job(&project_root, options).await
+                }
+                Err(e) => Err(e),
+            }
+        }
+
+        Commands::Archive { jobs, passed, days, dry_run } => {
+            let project_root = std::env::current_dir().unwrap();
+            let selection = if !jobs.is_empty() {
+                ArchiveSelection::Jobs(jobs)
+            } else if passed {
+                ArchiveSelection::Passed
+            } else {
+                ArchiveSelection::OlderThan(days)
+            };
+            match archive_jobs(&project_root, &selection, dry_run) {
                 Ok(result) => {
                     if dry_run {
                         println!("\nDry run: would archive {} job(s)", result.archived_count);
@@ -376,6 +998,21 @@ async fn main() {
             }
         }
 
+        #[cfg(feature = "server")]
+        Commands::Serve { port, host, allow_job_commands } => {
+            let project_root = std::env::current_dir().unwrap();
+            serve(&project_root, &host, port, allow_job_commands).await
+        }
+
+        #[cfg(feature = "schema")]
+        Commands::Schema { action } => match action {
+            SchemaAction::Dump { kind, out } => dump_schema(kind, out.as_deref()),
+        },
+
+        Commands::Templates { action } => match action {
+            TemplatesAction::List => list_templates(&std::env::current_dir().unwrap()),
+        },
+
         Commands::Readme => {
             const README: &str = include_str!("../README.md");
             println!("{}", README);
@@ -388,3 +1025,18 @@ async fn main() {
         std::process::exit(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_filter_precedence() {
+        let filter = |log_filter, verbose, rust_log| build_log_filter(log_filter, verbose, rust_log).to_string();
+        assert_eq!(filter(None, false, None), "info");
+        assert_eq!(filter(None, false, Some("warn")), "warn");
+        assert_eq!(filter(None, true, Some("warn")), "debug");
+        assert_eq!(filter(Some("worksplit=trace"), true, Some("warn")), "worksplit=trace");
+        assert_eq!(filter(Some("=bad=filter="), false, Some("warn")), "warn");
+    }
+}
@@ -1,26 +1,25 @@
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 use super::Language;
 
 /// Project-level configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
 pub struct ProjectConfig {
     /// Programming language for this project
     #[serde(default)]
     pub language: Language,
 }
 
-impl Default for ProjectConfig {
-    fn default() -> Self {
-        Self {
-            language: Language::default(),
-        }
-    }
-}
-
 /// Configuration loaded from worksplit.toml
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
 pub struct Config {
     #[serde(default)]
     pub project: ProjectConfig,
@@ -36,24 +35,44 @@ pub struct Config {
     pub archive: ArchiveConfig,
     #[serde(default)]
     pub cleanup: CleanupConfig,
-}
-
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            project: ProjectConfig::default(),
-            ollama: OllamaConfig::default(),
-            limits: LimitsConfig::default(),
-            behavior: BehaviorConfig::default(),
-            build: BuildConfig::default(),
-            archive: ArchiveConfig::default(),
-            cleanup: CleanupConfig::default(),
-        }
-    }
+    #[serde(default)]
+    pub verification: VerificationConfig,
+    #[serde(default)]
+    pub external_verify: ExternalVerifyConfig,
+    #[serde(default)]
+    pub context_urls: UrlContextConfig,
+    #[serde(default)]
+    pub safety: SafetyConfig,
+    #[serde(default)]
+    pub notify: NotifyConfig,
+    #[serde(default)]
+    pub status_webhook: StatusWebhookConfig,
+    #[serde(default)]
+    pub retrieval: RetrievalConfig,
+    #[serde(default)]
+    pub job_defaults: JobDefaultsConfig,
+    /// Post-processing of generated files per extension
+    /// (`[postprocess.<ext>]`, `[postprocess."*"]` for any other extension)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub postprocess: BTreeMap<String, PostprocessRule>,
+    /// Compile check of each generated file per extension, run with
+    /// `verify_build` (`[checkers]`, e.g. `py = "py_compile"`): a preset
+    /// (`rustc`, `tsc`, `solc`, `py_compile`) or a command with `{path}`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub checkers: BTreeMap<String, String>,
+    /// Monorepo packages with their own build commands (`[workspaces.<name>]`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub workspaces: BTreeMap<String, WorkspaceConfig>,
+    /// Named sets of overrides (`[profile.<name>]`), selected with
+    /// `worksplit run --profile <name>`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profile: BTreeMap<String, ProfileConfig>,
 }
 
 /// Ollama API configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
 pub struct OllamaConfig {
     /// Ollama API URL
     #[serde(default = "default_ollama_url")]
@@ -64,6 +83,35 @@ pub struct OllamaConfig {
     /// Timeout in seconds for API requests
     #[serde(default = "default_timeout")]
     pub timeout_seconds: u64,
+    /// Give up on a generation after this many seconds without a token
+    /// (0 waits for `timeout_seconds`); raise it for large models on CPU
+    #[serde(default = "default_stall_timeout")]
+    pub stall_timeout_seconds: u64,
+    /// Seconds between progress lines while tokens arrive (when output isn't
+    /// streamed to the terminal)
+    #[serde(default = "default_progress_interval")]
+    pub progress_interval_seconds: u64,
+    /// Log a heartbeat after this many seconds without a token, and again at
+    /// this interval until tokens arrive (0 disables)
+    #[serde(default = "default_heartbeat")]
+    pub heartbeat_seconds: u64,
+    /// Load the model with a one-token prompt before the first job of a
+    /// run, and log how long loading took
+    #[serde(default = "default_warmup")]
+    pub warmup: bool,
+    /// Retries of individual requests that fail (not of failed verification)
+    #[serde(default)]
+    pub retry: RequestRetryConfig,
+    /// Cleanup of responses before they're parsed
+    #[serde(default)]
+    pub sanitize: SanitizeConfig,
+    /// Endpoint for models without an entry in `models`
+    #[serde(default)]
+    pub api: ModelApi,
+    /// Settings for models whose name starts with the key (the longest
+    /// matching key wins)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub models: BTreeMap<String, ModelConfig>,
 }
 
 impl Default for OllamaConfig {
@@ -72,10 +120,221 @@ impl Default for OllamaConfig {
             url: default_ollama_url(),
             model: default_model(),
             timeout_seconds: default_timeout(),
+            stall_timeout_seconds: default_stall_timeout(),
+            progress_interval_seconds: default_progress_interval(),
+            heartbeat_seconds: default_heartbeat(),
+            warmup: default_warmup(),
+            retry: RequestRetryConfig::default(),
+            sanitize: SanitizeConfig::default(),
+            api: ModelApi::default(),
+            models: BTreeMap::new(),
         }
     }
 }
 
+impl OllamaConfig {
+    /// Settings of `model` under `[ollama.models]`, if any
+    pub fn model_config(&self, model: &str) -> Option<&ModelConfig> {
+        longest_prefix_match(&self.models, model)
+    }
+
+    /// Endpoint requests to `model` go to
+    pub fn api_for(&self, model: &str) -> ModelApi {
+        self.model_config(model).and_then(|m| m.api).unwrap_or(self.api)
+    }
+}
+
+/// The entry whose key is the longest prefix of `name`
+fn longest_prefix_match<'a, T>(entries: &'a BTreeMap<String, T>, name: &str) -> Option<&'a T> {
+    entries.iter()
+        .filter(|(prefix, _)| name.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, entry)| entry)
+}
+
+/// Ollama endpoint a model is prompted through
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ModelApi {
+    /// `/api/chat` with the message history
+    #[default]
+    Chat,
+    /// `/api/generate` with a single prompt; some coder and completion
+    /// models follow instructions better this way
+    Generate,
+}
+
+/// Per-model settings (`[ollama.models.<name prefix>]`)
+///
+/// ```toml
+/// [ollama.models."deepseek-coder"]
+/// api = "generate"
+/// raw = true
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct ModelConfig {
+    /// Endpoint instead of `[ollama] api`
+    #[serde(default)]
+    pub api: Option<ModelApi>,
+    /// With `api = "generate"`: send the prompt as is, without the model's
+    /// prompt template (the system prompt is prepended to it)
+    #[serde(default)]
+    pub raw: bool,
+    /// With `api = "generate"`: prompt template replacing the model's own
+    /// (Ollama template syntax)
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+/// Retries of a model request that failed (`[ollama.retry]`)
+///
+/// ```toml
+/// [ollama.retry]
+/// max_attempts = 4
+/// backoff_ms = 2000
+/// jitter = 0.5
+/// retry_on = ["thinking_timeout", "connection", "server_error"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct RequestRetryConfig {
+    /// Attempts per request, the first one included (1 never retries)
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubles with every further retry
+    #[serde(default = "default_backoff_ms")]
+    pub backoff_ms: u64,
+    /// Random spread of each wait, as a fraction of it (0.25 waits 75% to
+    /// 125% of the backoff), so parallel clients don't retry in lockstep
+    #[serde(default = "default_jitter")]
+    pub jitter: f64,
+    /// Failures that are retried; anything else fails the request at once
+    #[serde(default = "default_retry_on")]
+    pub retry_on: Vec<RetryClass>,
+}
+
+impl Default for RequestRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_max_attempts(),
+            backoff_ms: default_backoff_ms(),
+            jitter: default_jitter(),
+            retry_on: default_retry_on(),
+        }
+    }
+}
+
+fn default_max_attempts() -> u32 {
+    2
+}
+
+fn default_backoff_ms() -> u64 {
+    1000
+}
+
+fn default_jitter() -> f64 {
+    0.25
+}
+
+fn default_retry_on() -> Vec<RetryClass> {
+    vec![RetryClass::ThinkingTimeout]
+}
+
+/// Kind of request failure, for `[ollama.retry] retry_on`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RetryClass {
+    /// The model reasoned without producing output for too long
+    ThinkingTimeout,
+    /// The request timed out, or no token arrived for `stall_timeout_seconds`
+    Timeout,
+    /// Ollama couldn't be reached
+    Connection,
+    /// Ollama answered with a 5xx status
+    ServerError,
+    /// The response stream broke off
+    Stream,
+}
+
+/// Cleanup of model responses before code or verdicts are extracted from
+/// them (`[ollama.sanitize]`)
+///
+/// ```toml
+/// [ollama.sanitize]
+/// thinking_tags = ["think", "reasoning"]
+///
+/// [ollama.sanitize.models."qwen2.5-coder"]
+/// strip_preamble = false
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct SanitizeConfig {
+    /// Remove reasoning blocks (`<think>...</think>`) the response starts with
+    #[serde(default = "default_strip_thinking")]
+    pub strip_thinking: bool,
+    /// Tags of reasoning blocks
+    #[serde(default = "default_thinking_tags")]
+    pub thinking_tags: Vec<String>,
+    /// Remove leading lines introducing the answer ("Sure! Here is the code:")
+    #[serde(default = "default_strip_preamble")]
+    pub strip_preamble: bool,
+    /// Overrides for models whose name starts with the key (the longest
+    /// matching key wins)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub models: BTreeMap<String, SanitizeOverride>,
+}
+
+impl Default for SanitizeConfig {
+    fn default() -> Self {
+        Self {
+            strip_thinking: default_strip_thinking(),
+            thinking_tags: default_thinking_tags(),
+            strip_preamble: default_strip_preamble(),
+            models: BTreeMap::new(),
+        }
+    }
+}
+
+impl SanitizeConfig {
+    /// Whether to strip reasoning blocks and preambles from `model`'s responses
+    pub fn for_model(&self, model: &str) -> (bool, bool) {
+        let matching = longest_prefix_match(&self.models, model);
+        (
+            matching.and_then(|o| o.strip_thinking).unwrap_or(self.strip_thinking),
+            matching.and_then(|o| o.strip_preamble).unwrap_or(self.strip_preamble),
+        )
+    }
+}
+
+/// Per-model settings of `[ollama.sanitize]`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct SanitizeOverride {
+    #[serde(default)]
+    pub strip_thinking: Option<bool>,
+    #[serde(default)]
+    pub strip_preamble: Option<bool>,
+}
+
+fn default_strip_thinking() -> bool {
+    true
+}
+
+fn default_thinking_tags() -> Vec<String> {
+    vec!["think".to_string(), "thinking".to_string(), "reasoning".to_string()]
+}
+
+fn default_strip_preamble() -> bool {
+    true
+}
+
 fn default_ollama_url() -> String {
     "http://localhost:11434".to_string()
 }
@@ -88,30 +347,87 @@ fn default_timeout() -> u64 {
     300
 }
 
+fn default_stall_timeout() -> u64 {
+    120
+}
+
+fn default_progress_interval() -> u64 {
+    10
+}
+
+fn default_heartbeat() -> u64 {
+    30
+}
+
+fn default_warmup() -> bool {
+    true
+}
+
 /// Limits configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
 pub struct LimitsConfig {
     /// Maximum lines of code in output
     #[serde(default = "default_max_output_lines")]
     pub max_output_lines: usize,
+    /// What happens when a created file is longer than `max_output_lines`
+    #[serde(default)]
+    pub oversized_outputs: OversizedOutputPolicy,
     /// Maximum lines of code per context file
     #[serde(default = "default_max_context_lines")]
     pub max_context_lines: usize,
     /// Maximum number of context files
     #[serde(default = "default_max_context_files")]
     pub max_context_files: usize,
+    /// Maximum target-file lines sent in one edit prompt; larger target
+    /// sets (directories, globs) are split across several prompts
+    #[serde(default = "default_max_edit_chunk_lines")]
+    pub max_edit_chunk_lines: usize,
+    /// Module generations run at once by `split_strategy: planned`
+    #[serde(default = "default_max_split_concurrency")]
+    pub max_split_concurrency: usize,
+    /// Bytes of file content kept in memory between prompts; the least
+    /// recently used files are dropped beyond it (0 = unbounded)
+    #[serde(default = "default_file_cache_max_bytes")]
+    pub file_cache_max_bytes: usize,
+    /// Model context window in estimated tokens (4 characters each). Creation
+    /// prompts over 90% of it fail; verification and retry prompts have
+    /// their context trimmed to fit.
+    #[serde(default = "default_context_window_tokens")]
+    pub context_window_tokens: usize,
 }
 
 impl Default for LimitsConfig {
     fn default() -> Self {
         Self {
             max_output_lines: default_max_output_lines(),
+            oversized_outputs: OversizedOutputPolicy::default(),
             max_context_lines: default_max_context_lines(),
             max_context_files: default_max_context_files(),
+            max_edit_chunk_lines: default_max_edit_chunk_lines(),
+            max_split_concurrency: default_max_split_concurrency(),
+            file_cache_max_bytes: default_file_cache_max_bytes(),
+            context_window_tokens: default_context_window_tokens(),
         }
     }
 }
 
+/// Handling of a created file over `[limits] max_output_lines`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum OversizedOutputPolicy {
+    /// Once the job passes, write a split-mode job (`<job>_split`) for the
+    /// file to jobs/ and run it
+    #[default]
+    Split,
+    /// Fail the job, suggesting how to split it
+    Fail,
+    /// Log a warning and keep the file
+    Warn,
+}
+
 fn default_max_output_lines() -> usize {
     900
 }
@@ -124,36 +440,238 @@ fn default_max_context_files() -> usize {
     2
 }
 
+fn default_max_edit_chunk_lines() -> usize {
+    1500
+}
+
+fn default_max_split_concurrency() -> usize {
+    4
+}
+
+fn default_file_cache_max_bytes() -> usize {
+    64 * 1024 * 1024
+}
+
+fn default_context_window_tokens() -> usize {
+    32000
+}
+
 /// Behavior configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
 pub struct BehaviorConfig {
     /// Show streaming output in terminal
     #[serde(default = "default_stream_output")]
     pub stream_output: bool,
+    /// Show progress bars for `worksplit run` on a terminal when output isn't
+    /// streamed: jobs processed with an ETA, and files done in multi-file jobs
+    #[serde(default = "default_progress_bars")]
+    pub progress_bars: bool,
+    /// Directory (relative to the project root) receiving a copy of each
+    /// job's token stream as `<job>.log`, streamed to the console or not
+    #[serde(default)]
+    pub stream_to: Option<PathBuf>,
     /// Create output directories if missing
     #[serde(default = "default_create_output_dirs")]
     pub create_output_dirs: bool,
+    /// What to change on each retry after failed verification
+    #[serde(default)]
+    pub retry_ladder: RetryLadderConfig,
+    /// Retry replace mode jobs as a follow-up chat turn: the model sees the
+    /// original prompt and its own answer, then the verification feedback.
+    /// Off sends every retry as a fresh single prompt
+    #[serde(default = "default_retry_conversation")]
+    pub retry_conversation: bool,
+    /// Token budget of a retry conversation; older attempts are dropped to
+    /// stay within it, and a fresh prompt is used if even the latest doesn't fit
+    #[serde(default = "default_retry_conversation_max_tokens")]
+    pub retry_conversation_max_tokens: usize,
+    /// Verify each job this many times and take the majority verdict (a tie
+    /// fails); voting stops as soon as the majority is certain
+    #[serde(default = "default_verify_votes")]
+    pub verify_votes: usize,
+    /// Models that take turns casting the votes; empty uses `[ollama] model`
+    #[serde(default)]
+    pub verify_vote_models: Vec<String>,
+    /// Order of context files in prompts
+    #[serde(default)]
+    pub context_order: ContextOrder,
+    /// Replace a context file that doesn't exist with the project file of
+    /// the same name closest to its path, with a warning
+    #[serde(default)]
+    pub substitute_missing_context: bool,
+    /// Add the files modified earlier in the run to each job's context (up
+    /// to `max_context_files`); jobs can override it with `implicit_context`
+    #[serde(default = "default_implicit_context")]
+    pub implicit_context: bool,
+    /// Where job status and run history are kept. `sqlite` (needs the
+    /// "sqlite" feature) moves them into `jobs/_jobstatus.db` on the next run
+    #[serde(default)]
+    pub status_backend: StatusBackend,
+    /// What to do when an edit's FIND text matches several places, and only
+    /// after whitespace normalization
+    #[serde(default)]
+    pub fuzzy_match: FuzzyMatchPolicy,
+    /// Offer `FIND_REGEX:` edit blocks to the model; when off, the edit
+    /// prompt doesn't mention them and their text is matched literally
+    #[serde(default)]
+    pub regex_edits: bool,
 }
 
 impl Default for BehaviorConfig {
     fn default() -> Self {
         Self {
             stream_output: default_stream_output(),
+            progress_bars: default_progress_bars(),
+            stream_to: None,
             create_output_dirs: default_create_output_dirs(),
+            retry_ladder: RetryLadderConfig::default(),
+            retry_conversation: default_retry_conversation(),
+            retry_conversation_max_tokens: default_retry_conversation_max_tokens(),
+            verify_votes: default_verify_votes(),
+            verify_vote_models: Vec::new(),
+            context_order: ContextOrder::default(),
+            substitute_missing_context: false,
+            implicit_context: default_implicit_context(),
+            status_backend: StatusBackend::default(),
+            fuzzy_match: FuzzyMatchPolicy::default(),
+            regex_edits: false,
         }
     }
 }
 
+/// Storage for job status and run history
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum StatusBackend {
+    /// `jobs/_jobstatus.json` and `jobs/_history.jsonl`
+    #[default]
+    Json,
+    /// `jobs/_jobstatus.db`, written one changed entry at a time
+    Sqlite,
+}
+
+/// Handling of edits whose FIND text matches several places, and only after
+/// whitespace normalization (a single fuzzy match is always applied)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum FuzzyMatchPolicy {
+    /// Apply the edit at the first match in the file
+    #[default]
+    First,
+    /// Fail the edit, naming the lines of every match
+    Fail,
+    /// Apply the edit at every match
+    All,
+    /// List the matches with surrounding lines and let the user pick one, all
+    /// or none; fails like `fail` when not run from a terminal
+    Ask,
+}
+
+/// Order of a job's context in prompts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ContextOrder {
+    /// The job's own context files, then files modified earlier in the run,
+    /// then context URLs, then retrieved snippets (best match first)
+    #[default]
+    Listed,
+    /// Reverse: retrieved snippets (weakest match first), context URLs,
+    /// files modified earlier in the run, and the job's own context files
+    /// last, right before the instructions
+    MostRelevantLast,
+}
+
+/// Escalation ladder for retries after failed verification
+///
+/// ```toml
+/// [behavior.retry_ladder]
+/// steps = [
+///     { temperature = 0.2 },
+///     { model = "qwen2.5-coder:32b" },
+/// ]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct RetryLadderConfig {
+    /// One retry per step, in order, until verification passes; empty means
+    /// a single retry with the normal model and settings
+    #[serde(default)]
+    pub steps: Vec<RetryStep>,
+}
+
+impl RetryLadderConfig {
+    /// The retries to attempt, in order
+    pub fn retries(&self) -> Vec<RetryStep> {
+        if self.steps.is_empty() {
+            vec![RetryStep::default()]
+        } else {
+            self.steps.clone()
+        }
+    }
+}
+
+/// Settings for one retry; unset fields keep the normal settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct RetryStep {
+    /// Model to retry with instead of `[ollama] model`
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Sampling temperature for the retry
+    #[serde(default)]
+    pub temperature: Option<f32>,
+}
+
+impl std::fmt::Display for RetryStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.model, self.temperature) {
+            (None, None) => write!(f, "same settings"),
+            (Some(model), None) => write!(f, "model {}", model),
+            (None, Some(temperature)) => write!(f, "temperature {}", temperature),
+            (Some(model), Some(temperature)) => write!(f, "model {}, temperature {}", model, temperature),
+        }
+    }
+}
+
+fn default_retry_conversation() -> bool {
+    true
+}
+
+fn default_retry_conversation_max_tokens() -> usize {
+    32000
+}
+
+fn default_verify_votes() -> usize {
+    1
+}
+
 fn default_stream_output() -> bool {
     true
 }
 
+fn default_progress_bars() -> bool {
+    true
+}
+
 fn default_create_output_dirs() -> bool {
     true
 }
 
+fn default_implicit_context() -> bool {
+    true
+}
+
 /// Build and test verification configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
 pub struct BuildConfig {
     /// Command to verify code compiles (optional)
     pub build_command: Option<String>,
@@ -205,8 +723,71 @@ fn default_auto_fix_attempts() -> u8 {
     2
 }
 
+/// A package of a monorepo. Jobs whose `workdir` lies inside `path` run
+/// these commands in that directory; unset commands fall back to `[build]`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct WorkspaceConfig {
+    /// Package directory relative to the project root
+    pub path: PathBuf,
+    pub build_command: Option<String>,
+    pub test_command: Option<String>,
+    pub lint_command: Option<String>,
+}
+
+/// What to do with generated files of one extension before they're written
+///
+/// ```toml
+/// [postprocess.rs]
+/// commands = ["rustfmt --edition 2021"]
+///
+/// [postprocess.ts]
+/// commands = ["npx prettier --stdin-filepath {path}"]
+/// trim_trailing_whitespace = true
+/// line_endings = "lf"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct PostprocessRule {
+    /// Shell commands run in order from the project root; each reads the
+    /// content on stdin and prints the new content. `{path}` is replaced by
+    /// the file's path. A failing command is skipped with a warning
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Strip spaces and tabs at the end of every line
+    #[serde(default)]
+    pub trim_trailing_whitespace: bool,
+    #[serde(default)]
+    pub line_endings: LineEndings,
+}
+
+/// Line endings of post-processed files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum LineEndings {
+    /// Leave them as generated
+    #[default]
+    Keep,
+    Lf,
+    Crlf,
+}
+
+/// Commands for a job and the project-relative directory they run in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildTarget {
+    pub dir: PathBuf,
+    pub build_command: Option<String>,
+    pub test_command: Option<String>,
+    pub lint_command: Option<String>,
+}
+
 /// Archive configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
 pub struct ArchiveConfig {
     /// Whether auto-archive is enabled
     #[serde(default = "default_archive_enabled")]
@@ -235,6 +816,8 @@ fn default_archive_days() -> u32 {
 
 /// Cleanup configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
 pub struct CleanupConfig {
     /// Whether auto-cleanup is enabled
     #[serde(default = "default_cleanup_enabled")]
@@ -261,17 +844,449 @@ fn default_cleanup_days() -> u32 {
     30
 }
 
+/// How the verifier judges generated output (`[verification]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct VerificationConfig {
+    /// `verdict` (PASS/FAIL, default) or `rubric` (scores per axis)
+    #[serde(default)]
+    pub mode: VerificationMode,
+    /// Average rubric score (1-5) a job needs to pass
+    #[serde(default = "default_pass_threshold")]
+    pub pass_threshold: f32,
+    /// Feedback categories that never trigger a retry; a failed job whose
+    /// feedback is all ignored passes with warnings
+    #[serde(default)]
+    pub ignore_feedback: Vec<FeedbackCategory>,
+    /// Regexes matched against each feedback issue; matching issues are
+    /// ignored like `ignore_feedback` categories
+    #[serde(default)]
+    pub ignore_feedback_patterns: Vec<String>,
+    /// Ignore complaints about missing tests on jobs that don't generate
+    /// tests (no `test_file` or `test_scope`)
+    #[serde(default = "default_tests_out_of_scope")]
+    pub tests_out_of_scope: bool,
+    /// Verify each file of a multi-file output on its own once the output
+    /// has more lines than this (0 = always one prompt)
+    #[serde(default = "default_per_file_over_lines")]
+    pub per_file_over_lines: usize,
+}
+
+impl Default for VerificationConfig {
+    fn default() -> Self {
+        Self {
+            mode: VerificationMode::default(),
+            pass_threshold: default_pass_threshold(),
+            ignore_feedback: Vec::new(),
+            ignore_feedback_patterns: Vec::new(),
+            tests_out_of_scope: true,
+            per_file_over_lines: default_per_file_over_lines(),
+        }
+    }
+}
+
+impl VerificationConfig {
+    /// The passing threshold, when jobs are scored on the rubric
+    pub fn rubric_threshold(&self) -> Option<f32> {
+        (self.mode == VerificationMode::Rubric).then_some(self.pass_threshold)
+    }
+}
+
+fn default_pass_threshold() -> f32 {
+    3.5
+}
+
+fn default_tests_out_of_scope() -> bool {
+    true
+}
+
+fn default_per_file_over_lines() -> usize {
+    600
+}
+
+/// What a verification complaint is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackCategory {
+    /// A requested function, method or type isn't there or is a stub
+    MissingFunction,
+    /// Wrong parameters, return type or name
+    WrongSignature,
+    /// Tests are missing or incomplete
+    MissingTests,
+    /// Doc comments and other documentation
+    Documentation,
+    /// Naming, formatting and other style nitpicks
+    Style,
+    /// Anything else, such as incorrect logic
+    Other,
+}
+
+/// What the verifier answers with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationMode {
+    /// PASS, PASS_WITH_WARNINGS, FAIL_SOFT or FAIL_HARD
+    #[default]
+    Verdict,
+    /// Scores from 1 to 5 for correctness, completeness, style and tests;
+    /// the job passes when their average reaches `pass_threshold`
+    Rubric,
+}
+
+/// External verification service configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct ExternalVerifyConfig {
+    /// Endpoint that receives generated files (disabled when unset)
+    pub url: Option<String>,
+    /// Seconds to wait for the service's verdict
+    #[serde(default = "default_external_verify_timeout")]
+    pub timeout_seconds: u64,
+    /// Fail the job if the service is unreachable or times out (default: false)
+    #[serde(default)]
+    pub required: bool,
+    /// Environment variable holding a bearer token for the service
+    pub token_env: Option<String>,
+}
+
+impl Default for ExternalVerifyConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            timeout_seconds: default_external_verify_timeout(),
+            required: false,
+            token_env: None,
+        }
+    }
+}
+
+fn default_external_verify_timeout() -> u64 {
+    120
+}
+
+/// Notifications when a run finishes or a job fails
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct NotifyConfig {
+    /// Show a desktop notification (notify-send on Linux, osascript on macOS)
+    #[serde(default)]
+    pub desktop: bool,
+    /// Webhook receiving a JSON POST; the payload has `text` (Slack) and
+    /// `content` (Discord) fields plus the structured run summary
+    pub webhook_url: Option<String>,
+    /// Seconds to wait for the webhook before giving up
+    #[serde(default = "default_notify_timeout")]
+    pub timeout_seconds: u64,
+    /// Notify when `worksplit run` finishes, with the run summary
+    #[serde(default = "default_notify_on_finish")]
+    pub on_finish: bool,
+    /// Notify as soon as a job fails
+    #[serde(default = "default_notify_on_failure")]
+    pub on_failure: bool,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            desktop: false,
+            webhook_url: None,
+            timeout_seconds: default_notify_timeout(),
+            on_finish: default_notify_on_finish(),
+            on_failure: default_notify_on_failure(),
+        }
+    }
+}
+
+impl NotifyConfig {
+    /// Whether any notification channel is configured
+    pub fn is_enabled(&self) -> bool {
+        self.desktop || self.webhook_url.is_some()
+    }
+}
+
+fn default_notify_timeout() -> u64 {
+    10
+}
+
+fn default_notify_on_finish() -> bool {
+    true
+}
+
+fn default_notify_on_failure() -> bool {
+    true
+}
+
+/// A JSON POST on every job status transition during a run, for dashboards
+/// and chat bots that follow progress live
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct StatusWebhookConfig {
+    /// Endpoint receiving the transitions (disabled when unset)
+    pub url: Option<String>,
+    /// Environment variable holding a secret; each request is then signed
+    /// with HMAC-SHA256 in the `X-WorkSplit-Signature-256` header
+    pub secret_env: Option<String>,
+    /// Seconds to wait for the endpoint before giving up on a transition
+    #[serde(default = "default_status_webhook_timeout")]
+    pub timeout_seconds: u64,
+}
+
+impl Default for StatusWebhookConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            secret_env: None,
+            timeout_seconds: default_status_webhook_timeout(),
+        }
+    }
+}
+
+fn default_status_webhook_timeout() -> u64 {
+    10
+}
+
+/// Embeddings-based context retrieval (`[retrieval]`)
+///
+/// Project files are split into chunks and embedded with Ollama; each job
+/// gets the chunks most similar to its instructions as extra context.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct RetrievalConfig {
+    /// Retrieve context for every job (jobs can opt in or out with
+    /// `context_retrieval`)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Ollama embedding model
+    #[serde(default = "default_embedding_model")]
+    pub model: String,
+    /// Number of snippets added to each job
+    #[serde(default = "default_retrieval_top_k")]
+    pub top_k: usize,
+    /// Lines per indexed chunk
+    #[serde(default = "default_chunk_lines")]
+    pub chunk_lines: usize,
+    /// Snippets less similar than this (cosine similarity, -1 to 1) are
+    /// never added
+    #[serde(default = "default_min_similarity")]
+    pub min_similarity: f32,
+}
+
+impl Default for RetrievalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model: default_embedding_model(),
+            top_k: default_retrieval_top_k(),
+            chunk_lines: default_chunk_lines(),
+            min_similarity: default_min_similarity(),
+        }
+    }
+}
+
+fn default_embedding_model() -> String {
+    "nomic-embed-text".to_string()
+}
+
+fn default_retrieval_top_k() -> usize {
+    4
+}
+
+fn default_chunk_lines() -> usize {
+    40
+}
+
+fn default_min_similarity() -> f32 {
+    0.3
+}
+
+/// Frontmatter defaults for every job (`[job_defaults]`)
+///
+/// A job that sets a field itself keeps its own value; `context_files` are
+/// added to the job's own. Paths are read as if written in the job file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct JobDefaultsConfig {
+    /// Output directory for jobs that don't set `output_dir`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<PathBuf>,
+    /// `verify` for jobs that don't set it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify: Option<bool>,
+    /// `sequential` for jobs that don't set it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequential: Option<bool>,
+    /// Context files every job includes (e.g. `src/lib.rs`), ahead of the
+    /// job's own; they count toward `max_context_files`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub context_files: Vec<PathBuf>,
+}
+
+/// Settings for `context_urls` in job frontmatter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct UrlContextConfig {
+    /// Maximum lines kept per URL (longer pages are truncated)
+    #[serde(default = "default_url_max_lines")]
+    pub max_lines: usize,
+    /// Convert HTML pages to plain text before adding them to the prompt
+    #[serde(default = "default_html_to_text")]
+    pub html_to_text: bool,
+    /// Re-fetch cached URLs older than this many hours
+    #[serde(default = "default_url_cache_hours")]
+    pub cache_hours: u64,
+    /// Timeout in seconds for each fetch
+    #[serde(default = "default_url_timeout")]
+    pub timeout_seconds: u64,
+}
+
+impl Default for UrlContextConfig {
+    fn default() -> Self {
+        Self {
+            max_lines: default_url_max_lines(),
+            html_to_text: default_html_to_text(),
+            cache_hours: default_url_cache_hours(),
+            timeout_seconds: default_url_timeout(),
+        }
+    }
+}
+
+fn default_url_max_lines() -> usize {
+    500
+}
+
+fn default_html_to_text() -> bool {
+    true
+}
+
+fn default_url_cache_hours() -> u64 {
+    24
+}
+
+fn default_url_timeout() -> u64 {
+    30
+}
+
+/// Safety configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct SafetyConfig {
+    /// Never write to the project tree: outputs go to jobs/_staging/
+    /// and are copied over later with `worksplit apply`
+    #[serde(default)]
+    pub read_only: bool,
+    /// Refuse to contact anything but localhost (Ollama, external
+    /// verification, context URLs); fails fast before any request is made
+    #[serde(default)]
+    pub offline: bool,
+    /// Directories (relative to the project root) that generated files may
+    /// be written to, e.g. `["src/", "tests/"]`; empty allows the whole project
+    #[serde(default)]
+    pub allowed_output_dirs: Vec<PathBuf>,
+    /// Files the model may never write or edit, as globs relative to the
+    /// project root, e.g. `["Cargo.toml", ".github/**", "migrations/**"]`
+    #[serde(default)]
+    pub protected_paths: Vec<String>,
+    /// What to do when a job is about to overwrite a file another passed
+    /// job generated; `run --force` always overwrites
+    #[serde(default)]
+    pub owned_outputs: OwnedOutputPolicy,
+    /// What to do when job instructions or generated files look like an
+    /// injected request: paths outside the project, shell commands that
+    /// fetch and run code, changes to `jobs/_*` files
+    #[serde(default)]
+    pub injection_check: InjectionPolicy,
+}
+
+/// Handling of a job overwriting another passed job's output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum OwnedOutputPolicy {
+    /// Log a warning and overwrite
+    #[default]
+    Warn,
+    /// Fail the job unless the run has `--force`
+    Refuse,
+}
+
+/// Handling of suspected prompt injection (`[safety] injection_check`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum InjectionPolicy {
+    /// Don't check
+    Off,
+    /// Log a warning and go on; `worksplit validate` lists the findings
+    #[default]
+    Flag,
+    /// Fail the job before it is sent to the model, or before a
+    /// suspicious file is written
+    Refuse,
+}
+
+/// Overrides applied by a named profile; unset fields keep the settings
+/// from the rest of the file
+///
+/// ```toml
+/// [profile.fast]
+/// model = "qwen2.5-coder:7b"
+/// timeout_seconds = 120
+/// verify_tests = false
+///
+/// [profile.quality]
+/// model = "qwen2.5-coder:32b"
+/// timeout_seconds = 1800
+/// retry_ladder = [{ temperature = 0.2 }, { model = "qwen3:32b" }]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct ProfileConfig {
+    /// Model instead of `[ollama] model`
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Request timeout instead of `[ollama] timeout_seconds`
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
+    /// Retry steps instead of `[behavior.retry_ladder] steps`
+    #[serde(default)]
+    pub retry_ladder: Option<Vec<RetryStep>>,
+    /// Instead of `[build] verify_build`
+    #[serde(default)]
+    pub verify_build: Option<bool>,
+    /// Instead of `[build] verify_tests`
+    #[serde(default)]
+    pub verify_tests: Option<bool>,
+    /// Instead of `[build] auto_fix`
+    #[serde(default)]
+    pub auto_fix: Option<bool>,
+    /// Instead of `[build] auto_fix_attempts`
+    #[serde(default)]
+    pub auto_fix_attempts: Option<u8>,
+}
+
 impl Config {
     /// Load config from a TOML file
-    pub fn load_from_file(path: &PathBuf) -> Result<Self, ConfigError> {
+    pub fn load_from_file(path: &Path) -> Result<Self, ConfigError> {
         let contents = std::fs::read_to_string(path)
-            .map_err(|e| ConfigError::ReadError(path.clone(), e))?;
+            .map_err(|e| ConfigError::ReadError(path.to_path_buf(), e))?;
         toml::from_str(&contents)
-            .map_err(|e| ConfigError::ParseError(path.clone(), e))
+            .map_err(|e| ConfigError::ParseError(path.to_path_buf(), e))
     }
 
     /// Try to load config from worksplit.toml in the given directory
-    pub fn load_from_dir(dir: &PathBuf) -> Result<Self, ConfigError> {
+    pub fn load_from_dir(dir: &Path) -> Result<Self, ConfigError> {
         let config_path = dir.join("worksplit.toml");
         if config_path.exists() {
             Self::load_from_file(&config_path)
@@ -280,6 +1295,75 @@ impl Config {
         }
     }
 
+    /// The workspace containing `workdir`; the most specific one if nested
+    pub fn workspace_for(&self, workdir: &Path) -> Option<(&str, &WorkspaceConfig)> {
+        self.workspaces
+            .iter()
+            .filter(|(_, workspace)| workdir.starts_with(&workspace.path))
+            .max_by_key(|(_, workspace)| workspace.path.components().count())
+            .map(|(name, workspace)| (name.as_str(), workspace))
+    }
+
+    /// Build, test and lint commands for a job with the given `workdir`:
+    /// its workspace's (in the workspace directory), or `[build]`'s at the
+    /// project root
+    pub fn build_target(&self, workdir: Option<&Path>) -> BuildTarget {
+        let global = &self.build;
+        match workdir.and_then(|dir| self.workspace_for(dir)) {
+            Some((_, workspace)) => BuildTarget {
+                dir: workspace.path.clone(),
+                build_command: workspace.build_command.clone().or_else(|| global.build_command.clone()),
+                test_command: workspace.test_command.clone().or_else(|| global.test_command.clone()),
+                lint_command: workspace.lint_command.clone().or_else(|| global.lint_command.clone()),
+            },
+            None => BuildTarget {
+                dir: PathBuf::new(),
+                build_command: global.build_command.clone(),
+                test_command: global.test_command.clone(),
+                lint_command: global.lint_command.clone(),
+            },
+        }
+    }
+
+    /// Whether any lint command is configured, globally or for a workspace
+    pub fn has_lint_command(&self) -> bool {
+        self.build.lint_command.is_some() || self.workspaces.values().any(|w| w.lint_command.is_some())
+    }
+
+    /// Apply the overrides of the `[profile.<name>]` section
+    pub fn with_profile(mut self, name: &str) -> Result<Self, ConfigError> {
+        let profile = self.profile.get(name).cloned().ok_or_else(|| ConfigError::UnknownProfile {
+            name: name.to_string(),
+            available: if self.profile.is_empty() {
+                "none defined".to_string()
+            } else {
+                self.profile.keys().cloned().collect::<Vec<_>>().join(", ")
+            },
+        })?;
+        if let Some(model) = profile.model {
+            self.ollama.model = model;
+        }
+        if let Some(timeout) = profile.timeout_seconds {
+            self.ollama.timeout_seconds = timeout;
+        }
+        if let Some(steps) = profile.retry_ladder {
+            self.behavior.retry_ladder.steps = steps;
+        }
+        if let Some(verify_build) = profile.verify_build {
+            self.build.verify_build = verify_build;
+        }
+        if let Some(verify_tests) = profile.verify_tests {
+            self.build.verify_tests = verify_tests;
+        }
+        if let Some(auto_fix) = profile.auto_fix {
+            self.build.auto_fix = auto_fix;
+        }
+        if let Some(attempts) = profile.auto_fix_attempts {
+            self.build.auto_fix_attempts = attempts;
+        }
+        Ok(self)
+    }
+
     /// Merge CLI overrides into the config
     pub fn with_overrides(
         mut self,
@@ -310,6 +1394,8 @@ pub enum ConfigError {
     ReadError(PathBuf, std::io::Error),
     #[error("Failed to parse config file {0}: {1}")]
     ParseError(PathBuf, toml::de::Error),
+    #[error("Unknown profile '{name}' (profiles in worksplit.toml: {available})")]
+    UnknownProfile { name: String, available: String },
 }
 
 #[cfg(test)]
@@ -420,6 +1506,50 @@ days = 60
         assert_eq!(config.cleanup.days, 60);
     }
 
+    #[test]
+    fn test_parse_toml_with_external_verify() {
+        let config = Config::default();
+        assert!(config.external_verify.url.is_none());
+        assert_eq!(config.external_verify.timeout_seconds, 120);
+
+        let toml_str = r#"
+[external_verify]
+url = "https://review.example.com/verify"
+required = true
+token_env = "REVIEW_TOKEN"
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.external_verify.url.as_deref(), Some("https://review.example.com/verify"));
+        assert_eq!(config.external_verify.timeout_seconds, 120); // default
+        assert!(config.external_verify.required);
+        assert_eq!(config.external_verify.token_env.as_deref(), Some("REVIEW_TOKEN"));
+    }
+
+    #[test]
+    fn test_parse_toml_with_context_urls() {
+        let config = Config::default();
+        assert_eq!(config.context_urls.max_lines, 500);
+        assert!(config.context_urls.html_to_text);
+
+        let toml_str = r#"
+[context_urls]
+max_lines = 200
+html_to_text = false
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.context_urls.max_lines, 200);
+        assert!(!config.context_urls.html_to_text);
+        assert_eq!(config.context_urls.cache_hours, 24); // default
+    }
+
+    #[test]
+    fn test_parse_toml_with_safety() {
+        assert!(!Config::default().safety.read_only);
+
+        let config: Config = toml::from_str("[safety]\nread_only = true\n").unwrap();
+        assert!(config.safety.read_only);
+    }
+
     #[test]
     fn test_default_auto_fix_config() {
         let config = Config::default();
@@ -438,4 +1568,70 @@ auto_fix_attempts = 3
         assert!(!config.build.auto_fix);
         assert_eq!(config.build.auto_fix_attempts, 3);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_workspace_build_targets() {
+        let toml_str = r#"
+[build]
+build_command = "make"
+lint_command = "make lint"
+
+[workspaces.api]
+path = "packages/api"
+build_command = "cargo build"
+
+[workspaces.web]
+path = "packages/web"
+build_command = "npm run build"
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+
+        let api = config.build_target(Some(Path::new("packages/api/src")));
+        assert_eq!(api.dir, PathBuf::from("packages/api"));
+        assert_eq!(api.build_command.as_deref(), Some("cargo build"));
+        assert_eq!(api.lint_command.as_deref(), Some("make lint"));
+
+        assert_eq!(config.workspace_for(Path::new("packages/web")).unwrap().0, "web");
+        assert!(config.workspace_for(Path::new("packages/apis")).is_none());
+
+        let root = config.build_target(None);
+        assert_eq!(root.dir, PathBuf::new());
+        assert_eq!(root.build_command.as_deref(), Some("make"));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let toml_str = r#"
+[ollama]
+model = "qwen3:14b"
+timeout_seconds = 300
+
+[build]
+verify_tests = true
+
+[profile.fast]
+model = "qwen2.5-coder:7b"
+timeout_seconds = 60
+verify_tests = false
+
+[profile.quality]
+retry_ladder = [{ temperature = 0.2 }, { model = "qwen3:32b" }]
+auto_fix_attempts = 4
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+
+        let fast = config.clone().with_profile("fast").unwrap();
+        assert_eq!(fast.ollama.model, "qwen2.5-coder:7b");
+        assert_eq!(fast.ollama.timeout_seconds, 60);
+        assert!(!fast.build.verify_tests);
+        assert_eq!(fast.behavior.retry_ladder.retries(), vec![RetryStep::default()]);
+
+        let quality = config.clone().with_profile("quality").unwrap();
+        assert_eq!(quality.ollama.model, "qwen3:14b");
+        assert_eq!(quality.build.auto_fix_attempts, 4);
+        assert_eq!(quality.behavior.retry_ladder.retries().len(), 2);
+
+        let err = config.with_profile("overnight").unwrap_err().to_string();
+        assert!(err.contains("'overnight'") && err.contains("fast, quality"), "{}", err);
+    }
+}
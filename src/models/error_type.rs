@@ -1,6 +1,10 @@
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::error::{OllamaError, WorkSplitError};
+
 /// Type of error that triggered auto-fix
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ErrorType {
@@ -56,6 +60,98 @@ impl fmt::Display for ErrorType {
     }
 }
 
+/// Why a job failed, persisted in the status file so failures across many
+/// jobs can be triaged (`worksplit status --by-failure`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// The model timed out or got stuck thinking
+    OllamaTimeout,
+    /// Ollama was unreachable or returned an error
+    OllamaError,
+    /// The response contained no code or edit blocks
+    ExtractionEmpty,
+    /// The output didn't meet the job's `expect` assertions or
+    /// `[limits] max_output_lines`
+    ExpectationFailed,
+    /// Verification failed hard
+    VerificationHard,
+    /// Verification failed soft (after the retry)
+    VerificationSoft,
+    /// The build command failed (after auto-fix attempts)
+    BuildError,
+    /// The job's `test_scope` tests failed (after auto-fix attempts)
+    TestFailure,
+    /// Edit FIND blocks didn't match the target files
+    EditNoMatch,
+    /// The prompt exceeded the token budget or the model's context window
+    TokenBudget,
+    /// Anything else (I/O, configuration, cancelled, ...)
+    Other,
+}
+
+impl FailureKind {
+    pub const ALL: [FailureKind; 11] = [
+        FailureKind::OllamaTimeout,
+        FailureKind::OllamaError,
+        FailureKind::ExtractionEmpty,
+        FailureKind::ExpectationFailed,
+        FailureKind::VerificationHard,
+        FailureKind::VerificationSoft,
+        FailureKind::BuildError,
+        FailureKind::TestFailure,
+        FailureKind::EditNoMatch,
+        FailureKind::TokenBudget,
+        FailureKind::Other,
+    ];
+
+    /// Name as stored in `_jobstatus.json`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FailureKind::OllamaTimeout => "ollama_timeout",
+            FailureKind::OllamaError => "ollama_error",
+            FailureKind::ExtractionEmpty => "extraction_empty",
+            FailureKind::ExpectationFailed => "expectation_failed",
+            FailureKind::VerificationHard => "verification_hard",
+            FailureKind::VerificationSoft => "verification_soft",
+            FailureKind::BuildError => "build_error",
+            FailureKind::TestFailure => "test_failure",
+            FailureKind::EditNoMatch => "edit_no_match",
+            FailureKind::TokenBudget => "token_budget",
+            FailureKind::Other => "other",
+        }
+    }
+
+    /// Classify an error that aborted a job
+    pub fn from_error(error: &WorkSplitError) -> Self {
+        match error {
+            WorkSplitError::Ollama(
+                OllamaError::Timeout(_) | OllamaError::StallTimeout(_) | OllamaError::ThinkingTimeout { .. },
+            ) => {
+                FailureKind::OllamaTimeout
+            }
+            WorkSplitError::Ollama(OllamaError::ContextOverflow(_)) => FailureKind::TokenBudget,
+            WorkSplitError::Ollama(_) => FailureKind::OllamaError,
+            WorkSplitError::EmptyExtraction(_) => FailureKind::ExtractionEmpty,
+            WorkSplitError::ExpectationFailed(_) | WorkSplitError::OutputTooLarge(_) => FailureKind::ExpectationFailed,
+            WorkSplitError::BuildFailed { .. } => FailureKind::BuildError,
+            WorkSplitError::TestsFailed { .. } => FailureKind::TestFailure,
+            WorkSplitError::EditFailed(_) | WorkSplitError::EditFailedWithSuggestions { .. } => {
+                FailureKind::EditNoMatch
+            }
+            WorkSplitError::TokenBudgetExceeded { .. } => FailureKind::TokenBudget,
+            _ => FailureKind::Other,
+        }
+    }
+}
+
+impl fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -80,4 +176,43 @@ mod tests {
         assert_eq!(format!("{}", ErrorType::Test), "Test");
         assert_eq!(format!("{}", ErrorType::Lint), "Lint");
     }
+
+    #[test]
+    fn test_failure_kind_from_error() {
+        let classify = FailureKind::from_error;
+        assert_eq!(classify(&WorkSplitError::Ollama(OllamaError::Timeout(300))), FailureKind::OllamaTimeout);
+        assert_eq!(classify(&WorkSplitError::Ollama(OllamaError::StallTimeout(120))), FailureKind::OllamaTimeout);
+        assert_eq!(
+            classify(&WorkSplitError::Ollama(OllamaError::ConnectionRefused("down".into()))),
+            FailureKind::OllamaError
+        );
+        assert_eq!(
+            classify(&WorkSplitError::Ollama(OllamaError::ContextOverflow("too long".into()))),
+            FailureKind::TokenBudget
+        );
+        assert_eq!(classify(&WorkSplitError::EmptyExtraction("none".into())), FailureKind::ExtractionEmpty);
+        assert_eq!(classify(&WorkSplitError::ExpectationFailed("stub".into())), FailureKind::ExpectationFailed);
+        assert_eq!(classify(&WorkSplitError::OutputTooLarge("stub".into())), FailureKind::ExpectationFailed);
+        assert_eq!(
+            classify(&WorkSplitError::BuildFailed { command: "cargo build".into(), output: String::new() }),
+            FailureKind::BuildError
+        );
+        assert_eq!(
+            classify(&WorkSplitError::TestsFailed { command: "cargo test api".into(), output: String::new() }),
+            FailureKind::TestFailure
+        );
+        assert_eq!(classify(&WorkSplitError::EditFailed("no match".into())), FailureKind::EditNoMatch);
+        assert_eq!(
+            classify(&WorkSplitError::TokenBudgetExceeded { estimated: 40000, max: 32000 }),
+            FailureKind::TokenBudget
+        );
+        assert_eq!(classify(&WorkSplitError::JobError("x".into())), FailureKind::Other);
+    }
+
+    #[test]
+    fn test_failure_kind_serializes_snake_case() {
+        for kind in FailureKind::ALL {
+            assert_eq!(serde_json::to_string(&kind).unwrap(), format!("\"{}\"", kind.as_str()));
+        }
+    }
 }
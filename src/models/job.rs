@@ -1,5 +1,10 @@
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use crate::core::output_paths::normalize_relative;
 
 /// Job template type for new-job command
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
@@ -17,8 +22,9 @@ pub enum JobTemplate {
 }
 
 /// Output mode: "replace" (default) generates full files, "edit" applies surgical changes,
-/// "split" breaks a large file into smaller modules
+/// "split" breaks a large file into smaller modules, "report" writes a markdown document
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum OutputMode {
     #[default]
@@ -29,18 +35,177 @@ pub enum OutputMode {
     ReplacePattern,
     /// Update struct literals in test fixtures
     UpdateFixtures,
+    /// Write the model's markdown answer (design doc, review summary) to the
+    /// output path; verification checks content instead of code
+    Report,
+}
+
+/// How split mode generates its output files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SplitStrategy {
+    /// One file after another, each seeing the files already generated
+    #[default]
+    Sequential,
+    /// Plan module boundaries and interfaces first, then generate all modules
+    /// concurrently against the plan; mod.rs/index files are stitched last
+    Planned,
+}
+
+/// How edit mode asks the model for changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum EditStrategy {
+    /// FILE/FIND/REPLACE blocks against the target files
+    #[default]
+    FindReplace,
+    /// Fill-in-the-middle: the model gets the code before and after the
+    /// change site and writes what goes between (one target file; needs a
+    /// model whose template supports a suffix)
+    Fim,
+}
+
+/// How demanding verification is (`verification` in job frontmatter)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationLevel {
+    /// Warnings fail the job, so they are retried like soft failures
+    Strict,
+    #[default]
+    Normal,
+    /// Soft failures pass with a warning; only hard failures fail the job
+    Lenient,
+}
+
+impl VerificationLevel {
+    /// Suffix of the optional `_systemprompt_verify_<level>.md` override
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerificationLevel::Strict => "strict",
+            VerificationLevel::Normal => "normal",
+            VerificationLevel::Lenient => "lenient",
+        }
+    }
+}
+
+/// How to shrink context files that exceed `max_context_lines`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum TruncationStrategy {
+    /// Keep the first lines of the file
+    Head,
+    /// Keep declarations and signatures, strip function bodies
+    SignaturesOnly,
+    /// Keep the top-level sections that mention terms from the instructions
+    RelevantSections,
+}
+
+/// Assertions on the generated output, checked after extraction and before
+/// verification so stub output fails fast without a verification call
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(default)]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
+pub struct OutputExpectations {
+    /// Minimum number of lines across all generated files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_lines: Option<usize>,
+    /// Maximum number of lines across all generated files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_lines: Option<usize>,
+    /// Strings that must appear in at least one generated file
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub must_contain: Vec<String>,
+    /// Strings that must not appear in any generated file
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub must_not_contain: Vec<String>,
+}
+
+impl OutputExpectations {
+    /// Check generated files, returning one reason per unmet expectation
+    pub fn check(&self, files: &[(PathBuf, String)]) -> Vec<String> {
+        let mut reasons = Vec::new();
+        let lines: usize = files.iter().map(|(_, content)| content.lines().count()).sum();
+        if let Some(min) = self.min_lines {
+            if lines < min {
+                reasons.push(format!("output has {} lines, expected at least {}", lines, min));
+            }
+        }
+        if let Some(max) = self.max_lines {
+            if lines > max {
+                reasons.push(format!("output has {} lines, expected at most {}", lines, max));
+            }
+        }
+        for needle in &self.must_contain {
+            if !files.iter().any(|(_, content)| content.contains(needle.as_str())) {
+                reasons.push(format!("output does not contain '{}'", needle));
+            }
+        }
+        for needle in &self.must_not_contain {
+            for (path, content) in files {
+                if content.contains(needle.as_str()) {
+                    reasons.push(format!("{} contains '{}'", path.display(), needle));
+                }
+            }
+        }
+        reasons
+    }
 }
 
 /// Metadata parsed from job file YAML frontmatter
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(feature = "schema", schemars(deny_unknown_fields))]
 pub struct JobMetadata {
     /// Context files to include (max 2, each < 1000 LOC)
     #[serde(default)]
     pub context_files: Vec<PathBuf>,
+    /// Symbols (functions, types, exports) whose defining files are added
+    /// as context, looked up in the project symbol index
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_symbols: Option<Vec<String>>,
     /// Optional list of job IDs this job depends on
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub depends_on: Option<Vec<String>>,
-    /// Output directory relative to project root
+    /// Labels for selecting groups of jobs (`--jobs tag:<name>`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Work in progress: the job is listed in status but never run
+    #[serde(default)]
+    pub draft: bool,
+    /// HTTP(S) resources to include as context (docs pages, raw files)
+    /// Fetched once and cached under jobs/_context_cache/
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_urls: Option<Vec<String>>,
+    /// Truncate oversized context files instead of rejecting them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_truncation: Option<TruncationStrategy>,
+    /// Run without context files that don't exist (with a warning) instead
+    /// of failing the job
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_missing_context: bool,
+    /// Include the current content of output files that already exist as
+    /// context, so regeneration keeps manual fixes (defaults to true in
+    /// replace mode)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_existing_output: Option<bool>,
+    /// Add the project snippets most similar to the instructions as context;
+    /// overrides `[retrieval] enabled` for this job
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_retrieval: Option<bool>,
+    /// Add the files modified earlier in the run as context; overrides
+    /// `[behavior] implicit_context` for this job
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implicit_context: Option<bool>,
+    /// Package directory (e.g. `packages/api`) that context, output and
+    /// target paths are relative to; builds use its `[workspaces]` entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workdir: Option<PathBuf>,
+    /// Output directory relative to project root (or `workdir`)
     pub output_dir: PathBuf,
     /// Output filename (used when output_files is not specified)
     pub output_file: String,
@@ -58,16 +223,65 @@ pub struct JobMetadata {
     /// Output mode: "replace" (default) generates full files, "edit" applies surgical changes
     #[serde(default)]
     pub mode: OutputMode,
-    /// Target files for edit mode (files to apply edits to)
+    /// Target files for edit mode (files to apply edits to); entries may be
+    /// directories (`src/handlers/`) or glob patterns (`src/**/*.rs`)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub target_files: Option<Vec<PathBuf>>,
     /// Target file for split mode (the large file to split into modules)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub target_file: Option<PathBuf>,
+    /// Split mode strategy: "sequential" (default) or "planned"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split_strategy: Option<SplitStrategy>,
+    /// Edit mode strategy: "find_replace" (default) or "fim"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edit_strategy: Option<EditStrategy>,
+    /// Lines the fim strategy replaces, e.g. "42-57" or "42" (1-based,
+    /// inclusive); without it, the line marked `worksplit:fill` is replaced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill_range: Option<String>,
+    /// Declare generated files in their directory's mod.rs / index.ts /
+    /// __init__.py (no LLM call)
+    #[serde(default)]
+    pub generate_module_index: bool,
+    /// Assertions on the generated output (line counts, required and
+    /// forbidden strings), checked before verification
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expect: Option<OutputExpectations>,
     /// Whether to run verification phase (defaults to true)
     /// Set to false for simple/trusted jobs to skip verification and save an Ollama call
     #[serde(default = "default_verify")]
     pub verify: bool,
+    /// How demanding verification is: "strict", "normal" (default) or "lenient"
+    #[serde(default)]
+    pub verification: VerificationLevel,
+    /// Shell command running the tests this job affects (e.g. `cargo test
+    /// user_service`), run in the job's build directory after generation.
+    /// Failures are auto-fixed like build failures; it runs whether or not
+    /// `[build] verify_build` is on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_scope: Option<String>,
+    /// File holding the job's instructions (e.g. a spec shared by several
+    /// jobs, `specs/auth.md`), read when the job is parsed; an inline body
+    /// is appended after its content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions_file: Option<PathBuf>,
+    /// Environment variables for this job's build, test, lint, checker and
+    /// post-processing commands (e.g. `DATABASE_URL: "sqlite::memory:"`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Whether to append the project conventions (`jobs/_conventions.md`)
+    /// to this job's prompts (defaults to true)
+    #[serde(default = "default_include_conventions")]
+    pub include_conventions: bool,
+    /// Generate with `jobs/_systemprompt_<name>.md` instead of the mode's
+    /// default system prompt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// Generate this many candidate outputs, verify each and keep the best
+    /// (replace mode); the others are saved under `jobs/_candidates/<job>/`
+    #[serde(default = "default_candidates")]
+    pub candidates: usize,
     /// Struct name for update_fixtures mode
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub struct_name: Option<String>,
@@ -80,6 +294,14 @@ fn default_verify() -> bool {
     true
 }
 
+fn default_include_conventions() -> bool {
+    true
+}
+
+fn default_candidates() -> usize {
+    1
+}
+
 impl JobMetadata {
     /// Validate the metadata against configuration limits
     pub fn validate(&self, max_context_files: usize) -> Result<(), JobValidationError> {
@@ -92,11 +314,52 @@ impl JobMetadata {
         if self.output_file.is_empty() {
             return Err(JobValidationError::EmptyOutputFile);
         }
+        if let Some(ref workdir) = self.workdir {
+            let escapes = workdir.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+            if workdir.as_os_str().is_empty() || escapes {
+                return Err(JobValidationError::InvalidWorkdir(workdir.clone()));
+            }
+        }
+        if let Some(ref name) = self.system_prompt {
+            let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if !valid {
+                return Err(JobValidationError::InvalidSystemPrompt(name.clone()));
+            }
+        }
         if let Some(test_file) = &self.test_file {
             if test_file.is_empty() {
                 return Err(JobValidationError::EmptyTestFile);
             }
         }
+        if let Some(ref path) = self.instructions_file {
+            if path.as_os_str().is_empty() {
+                return Err(JobValidationError::EmptyInstructionsFile);
+            }
+            let in_project = match self.workdir {
+                Some(ref workdir) => from_workdir(workdir, path),
+                None => path.clone(),
+            };
+            if normalize_relative(&in_project).is_none() {
+                return Err(JobValidationError::InstructionsFileOutsideProject(path.clone()));
+            }
+        }
+        if let Some(name) = self.env.keys().find(|name| name.is_empty() || name.contains(['=', '\0'])) {
+            return Err(JobValidationError::InvalidEnvName(name.clone()));
+        }
+        if self.candidates == 0 {
+            return Err(JobValidationError::ZeroCandidates);
+        }
+        // Candidates are whole files, ranked by their verification
+        if self.candidates > 1 && (self.mode != OutputMode::Replace || self.is_sequential() || !self.verify) {
+            return Err(JobValidationError::UnsupportedCandidates);
+        }
+        if let Some(ref urls) = self.context_urls {
+            for url in urls {
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    return Err(JobValidationError::InvalidContextUrl(url.clone()));
+                }
+            }
+        }
         // Validate sequential mode configuration
         if let Some(ref files) = self.output_files {
             if files.is_empty() {
@@ -145,11 +408,39 @@ impl JobMetadata {
                 return Err(JobValidationError::SplitModeWithSequential);
             }
         }
-        // Validate replace_pattern mode configuration
-        if self.mode == OutputMode::ReplacePattern {
-            if self.target_files.is_none() {
-                return Err(JobValidationError::ReplacePatternMissingTargetFiles);
+        if let Some(OutputExpectations { min_lines: Some(min), max_lines: Some(max), .. }) = self.expect {
+            if min > max {
+                return Err(JobValidationError::InvalidExpectations { min, max });
+            }
+        }
+        if self.split_strategy.is_some() && self.mode != OutputMode::Split {
+            return Err(JobValidationError::SplitStrategyWithoutSplit);
+        }
+        if self.edit_strategy.is_some() && self.mode != OutputMode::Edit {
+            return Err(JobValidationError::EditStrategyWithoutEdit);
+        }
+        if self.is_fim_edit() {
+            // The model fills one gap in one file
+            if self.target_files.as_ref().is_some_and(|files| files.len() > 1) {
+                return Err(JobValidationError::FimMultipleTargets);
             }
+        } else if self.fill_range.is_some() {
+            return Err(JobValidationError::FillRangeWithoutFim);
+        }
+        if let Some(ref range) = self.fill_range {
+            if self.fill_lines().is_none() {
+                return Err(JobValidationError::InvalidFillRange(range.clone()));
+            }
+        }
+        // Reports are a single markdown document, not code
+        if self.mode == OutputMode::Report
+            && (self.test_file.is_some() || self.output_files.is_some() || self.generate_module_index)
+        {
+            return Err(JobValidationError::ReportModeWithCodeOptions);
+        }
+        // Validate replace_pattern mode configuration
+        if self.mode == OutputMode::ReplacePattern && self.target_files.is_none() {
+            return Err(JobValidationError::ReplacePatternMissingTargetFiles);
         }
         // Validate update_fixtures mode configuration
         if self.mode == OutputMode::UpdateFixtures {
@@ -166,6 +457,19 @@ impl JobMetadata {
         Ok(())
     }
 
+    /// Make paths given relative to `workdir` relative to the project root.
+    /// Paths starting with `/` already are (shared code outside the package).
+    pub fn apply_workdir(&mut self) {
+        let Some(workdir) = self.workdir.clone() else { return };
+        let resolve = |path: &mut PathBuf| *path = from_workdir(&workdir, path);
+        self.context_files.iter_mut().for_each(resolve);
+        resolve(&mut self.output_dir);
+        self.output_files.iter_mut().flatten().for_each(resolve);
+        self.target_files.iter_mut().flatten().for_each(resolve);
+        self.target_file.iter_mut().for_each(resolve);
+        self.instructions_file.iter_mut().for_each(resolve);
+    }
+
     /// Get the full output path
     pub fn output_path(&self) -> PathBuf {
         self.output_dir.join(&self.output_file)
@@ -198,6 +502,11 @@ impl JobMetadata {
         }
     }
 
+    /// Check if this split job plans interfaces first and generates modules concurrently
+    pub fn is_planned_split(&self) -> bool {
+        self.is_split_mode() && self.split_strategy == Some(SplitStrategy::Planned)
+    }
+
     /// Check if this job uses edit mode
     pub fn is_edit_mode(&self) -> bool {
         self.mode == OutputMode::Edit
@@ -208,6 +517,30 @@ impl JobMetadata {
         self.mode == OutputMode::Split
     }
 
+    /// Check if this job writes a markdown report instead of code
+    pub fn is_report_mode(&self) -> bool {
+        self.mode == OutputMode::Report
+    }
+
+    /// Whether existing output files are sent as context
+    pub fn includes_existing_output(&self) -> bool {
+        self.include_existing_output.unwrap_or(self.mode == OutputMode::Replace)
+    }
+
+    /// Check if this edit mode job fills in the middle instead of FIND/REPLACE
+    pub fn is_fim_edit(&self) -> bool {
+        self.is_edit_mode() && self.edit_strategy == Some(EditStrategy::Fim)
+    }
+
+    /// The 1-based, inclusive lines of `fill_range` (`"42-57"` or `"42"`);
+    /// None if unset or malformed
+    pub fn fill_lines(&self) -> Option<(usize, usize)> {
+        let range = self.fill_range.as_deref()?.trim();
+        let (start, end) = range.split_once('-').unwrap_or((range, range));
+        let (start, end) = (start.trim().parse().ok()?, end.trim().parse().ok()?);
+        (start >= 1 && end >= start).then_some((start, end))
+    }
+
     /// Get target files for edit mode
     /// Returns target_files if set, otherwise returns output_path as single-item vec
     pub fn get_target_files(&self) -> Vec<PathBuf> {
@@ -269,18 +602,43 @@ impl Job {
     }
 }
 
+/// A path given relative to `workdir`, made relative to the project root.
+/// Paths starting with `/` already are.
+fn from_workdir(workdir: &Path, path: &Path) -> PathBuf {
+    match path.strip_prefix("/") {
+        Ok(from_root) => from_root.to_path_buf(),
+        Err(_) => workdir.join(path),
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum JobValidationError {
     #[error("Too many context files: {count} (max: {max})")]
     TooManyContextFiles { count: usize, max: usize },
     #[error("Output file cannot be empty")]
     EmptyOutputFile,
+    #[error("workdir must be a relative path inside the project: {0}")]
+    InvalidWorkdir(PathBuf),
     #[error("Context file not found: {0}")]
     ContextFileNotFound(PathBuf),
     #[error("Context file too large: {path} has {lines} lines (max: {max})")]
     ContextFileTooLarge { path: PathBuf, lines: usize, max: usize },
+    #[error("system_prompt must be a name of letters, digits, '-' and '_' (for jobs/_systemprompt_<name>.md): {0}")]
+    InvalidSystemPrompt(String),
     #[error("Test file name cannot be empty")]
     EmptyTestFile,
+    #[error("instructions_file cannot be empty")]
+    EmptyInstructionsFile,
+    #[error("instructions_file must be a path inside the project: {0}")]
+    InstructionsFileOutsideProject(PathBuf),
+    #[error("env variable names must be non-empty and contain no '=': {0:?}")]
+    InvalidEnvName(String),
+    #[error("candidates must be at least 1")]
+    ZeroCandidates,
+    #[error("candidates above 1 require a verified replace mode job without sequential")]
+    UnsupportedCandidates,
+    #[error("context_urls entry must be an http(s) URL: {0}")]
+    InvalidContextUrl(String),
     #[error("output_files list cannot be empty")]
     EmptyOutputFiles,
     #[error("output_files contains an empty path")]
@@ -299,6 +657,20 @@ pub enum JobValidationError {
     SplitMissingOutputFiles,
     #[error("split mode cannot be combined with sequential mode")]
     SplitModeWithSequential,
+    #[error("expect.min_lines ({min}) is greater than expect.max_lines ({max})")]
+    InvalidExpectations { min: usize, max: usize },
+    #[error("split_strategy requires mode: split")]
+    SplitStrategyWithoutSplit,
+    #[error("edit_strategy requires mode: edit")]
+    EditStrategyWithoutEdit,
+    #[error("edit_strategy: fim edits a single target file")]
+    FimMultipleTargets,
+    #[error("fill_range requires edit_strategy: fim")]
+    FillRangeWithoutFim,
+    #[error("fill_range must be a line or an inclusive line range like \"42-57\": {0}")]
+    InvalidFillRange(String),
+    #[error("report mode cannot be combined with test_file, output_files or generate_module_index")]
+    ReportModeWithCodeOptions,
     #[error("replace_pattern mode requires target_files")]
     ReplacePatternMissingTargetFiles,
     #[error("update_fixtures mode requires target_files")]
@@ -313,10 +685,31 @@ pub enum JobValidationError {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_job_metadata_validate_context_urls() {
+        let yaml = r#"
+output_dir: src/
+output_file: client.rs
+context_urls:
+  - https://docs.example.com/api
+"#;
+        let mut metadata: JobMetadata = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(metadata.context_urls.as_ref().unwrap().len(), 1);
+        assert!(metadata.validate(2).is_ok());
+
+        metadata.context_urls = Some(vec!["docs/api.md".to_string()]);
+        assert!(matches!(
+            metadata.validate(2),
+            Err(JobValidationError::InvalidContextUrl(_))
+        ));
+    }
+
     #[test]
     fn test_job_metadata_validate() {
         let metadata = JobMetadata {
             context_files: vec![PathBuf::from("a.rs"), PathBuf::from("b.rs")],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "output.rs".to_string(),
             test_file: None,
@@ -326,18 +719,87 @@ mod tests {
             target_files: None,
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(metadata.validate(2).is_ok());
         assert!(metadata.validate(1).is_err());
     }
 
+    #[test]
+    fn test_apply_workdir() {
+        let yaml = "workdir: packages/api\ncontext_files: [src/types.rs, /packages/shared/lib.rs]\n\
+                    output_dir: src/\noutput_file: handler.rs\n";
+        let mut metadata: JobMetadata = serde_yaml::from_str(yaml).unwrap();
+        assert!(metadata.validate(2).is_ok());
+        metadata.apply_workdir();
+        assert_eq!(
+            metadata.context_files,
+            vec![PathBuf::from("packages/api/src/types.rs"), PathBuf::from("packages/shared/lib.rs")]
+        );
+        assert_eq!(metadata.output_path(), PathBuf::from("packages/api/src/handler.rs"));
+
+        metadata.workdir = Some(PathBuf::from("../elsewhere"));
+        assert!(matches!(metadata.validate(2), Err(JobValidationError::InvalidWorkdir(_))));
+    }
+
+    #[test]
+    fn test_system_prompt_name() {
+        let yaml = "system_prompt: migrations\noutput_dir: src/\noutput_file: a.rs\n";
+        let mut metadata: JobMetadata = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(metadata.system_prompt.as_deref(), Some("migrations"));
+        assert!(metadata.validate(2).is_ok());
+
+        for name in ["", "../create", "db/migrations"] {
+            metadata.system_prompt = Some(name.to_string());
+            assert!(matches!(metadata.validate(2), Err(JobValidationError::InvalidSystemPrompt(_))), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_candidates() {
+        let yaml = "candidates: 3\noutput_dir: src/\noutput_file: a.rs\n";
+        let mut metadata: JobMetadata = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(metadata.candidates, 3);
+        assert!(metadata.validate(2).is_ok());
+
+        metadata.verify = false;
+        assert!(matches!(metadata.validate(2), Err(JobValidationError::UnsupportedCandidates)));
+        metadata.verify = true;
+        metadata.mode = OutputMode::Edit;
+        assert!(matches!(metadata.validate(2), Err(JobValidationError::UnsupportedCandidates)));
+        metadata.mode = OutputMode::Replace;
+        metadata.candidates = 0;
+        assert!(matches!(metadata.validate(2), Err(JobValidationError::ZeroCandidates)));
+    }
+
     #[test]
     fn test_job_metadata_empty_output_file() {
         let metadata = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "".to_string(),
             test_file: None,
@@ -347,9 +809,29 @@ mod tests {
             target_files: None,
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(matches!(
             metadata.validate(2),
@@ -361,6 +843,8 @@ mod tests {
     fn test_output_path() {
         let metadata = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/services"),
             output_file: "user_service.rs".to_string(),
             test_file: None,
@@ -370,9 +854,29 @@ mod tests {
             target_files: None,
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert_eq!(
             metadata.output_path(),
@@ -384,6 +888,8 @@ mod tests {
     fn test_job_metadata_tdd_enabled() {
         let metadata_with_test = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "service.rs".to_string(),
             test_file: Some("service_test.rs".to_string()),
@@ -393,14 +899,36 @@ mod tests {
             target_files: None,
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(metadata_with_test.is_tdd_enabled());
 
         let metadata_without_test = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "service.rs".to_string(),
             test_file: None,
@@ -410,9 +938,29 @@ mod tests {
             target_files: None,
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(!metadata_without_test.is_tdd_enabled());
     }
@@ -421,6 +969,8 @@ mod tests {
     fn test_job_metadata_test_path() {
         let metadata_with_test = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/services"),
             output_file: "user_service.rs".to_string(),
             test_file: Some("user_service_test.rs".to_string()),
@@ -430,9 +980,29 @@ mod tests {
             target_files: None,
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert_eq!(
             metadata_with_test.test_path(),
@@ -441,6 +1011,8 @@ mod tests {
 
         let metadata_without_test = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/services"),
             output_file: "user_service.rs".to_string(),
             test_file: None,
@@ -450,9 +1022,29 @@ mod tests {
             target_files: None,
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert_eq!(metadata_without_test.test_path(), None);
     }
@@ -461,6 +1053,8 @@ mod tests {
     fn test_job_metadata_validate_empty_test_file() {
         let metadata = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "service.rs".to_string(),
             test_file: Some("".to_string()),
@@ -470,9 +1064,29 @@ mod tests {
             target_files: None,
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(matches!(
             metadata.validate(2),
@@ -480,6 +1094,20 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_job_env() {
+        let metadata: JobMetadata = serde_yaml::from_str(
+            "output_dir: src/\noutput_file: db.rs\nenv:\n  DATABASE_URL: \"sqlite::memory:\"\n",
+        ).unwrap();
+        assert_eq!(metadata.env["DATABASE_URL"], "sqlite::memory:");
+        assert!(metadata.validate(2).is_ok());
+
+        let metadata: JobMetadata = serde_yaml::from_str(
+            "output_dir: src/\noutput_file: db.rs\nenv:\n  \"A=B\": x\n",
+        ).unwrap();
+        assert!(matches!(metadata.validate(2), Err(JobValidationError::InvalidEnvName(_))));
+    }
+
     #[test]
     fn test_job_metadata_default_no_test_file() {
         let metadata: JobMetadata = serde_yaml::from_str(
@@ -562,6 +1190,8 @@ sequential: true
     fn test_job_metadata_get_output_files_fallback() {
         let metadata = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/services"),
             output_file: "user_service.rs".to_string(),
             test_file: None,
@@ -571,9 +1201,29 @@ sequential: true
             target_files: None,
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         let output_files = metadata.get_output_files();
         assert_eq!(output_files.len(), 1);
@@ -584,6 +1234,8 @@ sequential: true
     fn test_job_metadata_validate_empty_output_files() {
         let metadata = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "service.rs".to_string(),
             test_file: None,
@@ -593,9 +1245,29 @@ sequential: true
             target_files: None,
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(matches!(
             metadata.validate(2),
@@ -607,6 +1279,8 @@ sequential: true
     fn test_job_metadata_validate_empty_path_in_output_files() {
         let metadata = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "service.rs".to_string(),
             test_file: None,
@@ -616,9 +1290,29 @@ sequential: true
             target_files: None,
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(matches!(
             metadata.validate(2),
@@ -630,6 +1324,8 @@ sequential: true
     fn test_job_metadata_is_edit_mode() {
         let metadata_replace = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "service.rs".to_string(),
             test_file: None,
@@ -639,14 +1335,36 @@ sequential: true
             target_files: None,
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(!metadata_replace.is_edit_mode());
 
         let metadata_edit = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "service.rs".to_string(),
             test_file: None,
@@ -656,9 +1374,29 @@ sequential: true
             target_files: None,
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(metadata_edit.is_edit_mode());
     }
@@ -667,6 +1405,8 @@ sequential: true
     fn test_job_metadata_get_target_files() {
         let metadata_with_targets = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "service.rs".to_string(),
             test_file: None,
@@ -679,9 +1419,29 @@ sequential: true
             ]),
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         let target_files = metadata_with_targets.get_target_files();
         assert_eq!(target_files.len(), 2);
@@ -690,6 +1450,8 @@ sequential: true
 
         let metadata_without_targets = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/services"),
             output_file: "user_service.rs".to_string(),
             test_file: None,
@@ -699,9 +1461,29 @@ sequential: true
             target_files: None,
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         let target_files = metadata_without_targets.get_target_files();
         assert_eq!(target_files.len(), 1);
@@ -712,6 +1494,8 @@ sequential: true
     fn test_job_metadata_validate_empty_target_files() {
         let metadata = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "service.rs".to_string(),
             test_file: None,
@@ -721,9 +1505,29 @@ sequential: true
             target_files: Some(vec![]),
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(matches!(
             metadata.validate(2),
@@ -735,6 +1539,8 @@ sequential: true
     fn test_job_metadata_validate_empty_path_in_target_files() {
         let metadata = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "service.rs".to_string(),
             test_file: None,
@@ -744,9 +1550,29 @@ sequential: true
             target_files: Some(vec![PathBuf::from("src/main.rs"), PathBuf::from("")]),
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(matches!(
             metadata.validate(2),
@@ -758,6 +1584,8 @@ sequential: true
     fn test_job_metadata_validate_edit_mode_with_sequential() {
         let metadata = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "service.rs".to_string(),
             test_file: None,
@@ -767,9 +1595,29 @@ sequential: true
             target_files: Some(vec![PathBuf::from("src/main.rs")]),
             target_file: None,
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(matches!(
             metadata.validate(2),
@@ -825,6 +1673,8 @@ output_file: service.rs
     fn test_job_metadata_is_split_mode() {
         let metadata_split = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "runner.rs".to_string(),
             test_file: None,
@@ -834,9 +1684,29 @@ output_file: service.rs
             target_files: None,
             target_file: Some(PathBuf::from("src/runner.rs")),
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(metadata_split.is_split_mode());
         assert!(!metadata_split.is_edit_mode());
@@ -847,6 +1717,8 @@ output_file: service.rs
         // Valid split mode
         let valid_metadata = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "runner.rs".to_string(),
             test_file: None,
@@ -856,9 +1728,29 @@ output_file: service.rs
             target_files: None,
             target_file: Some(PathBuf::from("src/core/runner.rs")),
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(valid_metadata.validate(2).is_ok());
     }
@@ -867,6 +1759,8 @@ output_file: service.rs
     fn test_job_metadata_split_mode_missing_target_file() {
         let metadata = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "runner.rs".to_string(),
             test_file: None,
@@ -876,9 +1770,29 @@ output_file: service.rs
             target_files: None,
             target_file: None, // Missing!
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(matches!(
             metadata.validate(2),
@@ -890,6 +1804,8 @@ output_file: service.rs
     fn test_job_metadata_split_mode_missing_output_files() {
         let metadata = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "runner.rs".to_string(),
             test_file: None,
@@ -899,9 +1815,29 @@ output_file: service.rs
             target_files: None,
             target_file: Some(PathBuf::from("src/core/runner.rs")),
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(matches!(
             metadata.validate(2),
@@ -913,6 +1849,8 @@ output_file: service.rs
     fn test_job_metadata_split_mode_with_sequential() {
         let metadata = JobMetadata {
             context_files: vec![],
+            context_symbols: None,
+            workdir: None,
             output_dir: PathBuf::from("src/"),
             output_file: "runner.rs".to_string(),
             test_file: None,
@@ -922,9 +1860,29 @@ output_file: service.rs
             target_files: None,
             target_file: Some(PathBuf::from("src/core/runner.rs")),
             verify: true,
+            verification: VerificationLevel::Normal,
+            include_conventions: true,
+            system_prompt: None,
+            candidates: 1,
             struct_name: None,
             new_field: None,
             depends_on: None,
+            tags: Vec::new(),
+            draft: false,
+            context_urls: None,
+            context_truncation: None,
+            allow_missing_context: false,
+            include_existing_output: None,
+            test_scope: None,
+            instructions_file: None,
+            env: Default::default(),
+            context_retrieval: None,
+            implicit_context: None,
+            split_strategy: None,
+            edit_strategy: None,
+            fill_range: None,
+            generate_module_index: false,
+            expect: None,
         };
         assert!(matches!(
             metadata.validate(2),
@@ -951,4 +1909,86 @@ output_file: runner.rs
         assert_eq!(metadata.target_file, Some(PathBuf::from("src/core/runner.rs")));
         assert_eq!(metadata.output_files.as_ref().unwrap().len(), 3);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_job_metadata_report_mode_validation() {
+        let mut metadata: JobMetadata = serde_yaml::from_str(
+            "mode: report\noutput_dir: docs/\noutput_file: review.md\n",
+        )
+        .unwrap();
+        assert!(metadata.is_report_mode());
+        assert!(metadata.validate(2).is_ok());
+
+        metadata.test_file = Some("review_test.rs".to_string());
+        assert!(matches!(
+            metadata.validate(2),
+            Err(JobValidationError::ReportModeWithCodeOptions)
+        ));
+    }
+
+    #[test]
+    fn test_job_metadata_fim_edit_validation() {
+        let mut metadata: JobMetadata = serde_yaml::from_str(
+            "mode: edit\nedit_strategy: fim\nfill_range: 42-57\ntarget_files: [src/lib.rs]\noutput_dir: src/\noutput_file: lib.rs\n",
+        )
+        .unwrap();
+        assert!(metadata.is_fim_edit());
+        assert_eq!(metadata.fill_lines(), Some((42, 57)));
+        assert!(metadata.validate(2).is_ok());
+
+        metadata.fill_range = Some("42".to_string());
+        assert_eq!(metadata.fill_lines(), Some((42, 42)));
+        metadata.fill_range = Some("57-42".to_string());
+        assert!(matches!(metadata.validate(2), Err(JobValidationError::InvalidFillRange(_))));
+
+        metadata.fill_range = None;
+        metadata.target_files = Some(vec![PathBuf::from("src/a.rs"), PathBuf::from("src/b.rs")]);
+        assert!(matches!(metadata.validate(2), Err(JobValidationError::FimMultipleTargets)));
+
+        metadata.edit_strategy = None;
+        metadata.fill_range = Some("3".to_string());
+        assert!(matches!(metadata.validate(2), Err(JobValidationError::FillRangeWithoutFim)));
+        metadata.mode = OutputMode::Replace;
+        metadata.edit_strategy = Some(EditStrategy::FindReplace);
+        assert!(matches!(metadata.validate(2), Err(JobValidationError::EditStrategyWithoutEdit)));
+    }
+
+    #[test]
+    fn test_job_metadata_expectations() {
+        let mut metadata: JobMetadata = serde_yaml::from_str(
+            r##"
+output_dir: src/
+output_file: user_service.rs
+expect:
+  min_lines: 5
+  must_contain: ["impl UserService", "#[cfg(test)]"]
+  must_not_contain: ["todo!"]
+"##,
+        )
+        .unwrap();
+        assert!(metadata.validate(2).is_ok());
+        let expect = metadata.expect.clone().unwrap();
+
+        let stub = vec![(PathBuf::from("src/user_service.rs"), "impl UserService {\n    fn new() { todo!() }\n}\n".to_string())];
+        assert_eq!(
+            expect.check(&stub),
+            vec![
+                "output has 3 lines, expected at least 5".to_string(),
+                "output does not contain '#[cfg(test)]'".to_string(),
+                "src/user_service.rs contains 'todo!'".to_string(),
+            ]
+        );
+
+        let full = vec![
+            (PathBuf::from("src/user_service.rs"), "pub struct UserService;\n\nimpl UserService {\n}\n".to_string()),
+            (PathBuf::from("src/tests.rs"), "#[cfg(test)]\nmod tests {}\n".to_string()),
+        ];
+        assert!(expect.check(&full).is_empty());
+
+        metadata.expect = Some(OutputExpectations { min_lines: Some(10), max_lines: Some(5), ..Default::default() });
+        assert!(matches!(
+            metadata.validate(2),
+            Err(JobValidationError::InvalidExpectations { min: 10, max: 5 })
+        ));
+    }
+}
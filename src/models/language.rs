@@ -1,19 +1,26 @@
 //! Language enumeration for multi-language project support
 
 use clap::ValueEnum;
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// Supported programming languages for WorkSplit projects
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum Language {
     /// Rust programming language
+    #[default]
     Rust,
     /// Solidity smart contracts (Foundry)
     Solidity,
     /// TypeScript programming language
     Typescript,
+    /// C# (.NET)
+    #[value(name = "csharp")]
+    CSharp,
 }
 
 impl Language {
@@ -23,6 +30,7 @@ impl Language {
             Language::Rust => "Rust",
             Language::Solidity => "Solidity (Foundry)",
             Language::Typescript => "TypeScript",
+            Language::CSharp => "C# (.NET)",
         }
     }
 
@@ -32,19 +40,67 @@ impl Language {
             Language::Rust => "rs",
             Language::Solidity => "sol",
             Language::Typescript => "ts",
+            Language::CSharp => "cs",
+        }
+    }
+
+    /// Returns the tag of code fences in this language
+    pub fn fence_tag(&self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::Solidity => "solidity",
+            Language::Typescript => "typescript",
+            Language::CSharp => "csharp",
+        }
+    }
+
+    /// Returns the conventional directory for new source files (Foundry and
+    /// .NET projects keep sources in `src/` too)
+    pub fn default_output_dir(&self) -> &'static str {
+        match self {
+            Language::Rust | Language::Solidity | Language::Typescript | Language::CSharp => "src/",
+        }
+    }
+
+    /// Returns the conventional file name for a module called `stem`:
+    /// snake_case as given for Rust and TypeScript, PascalCase for Solidity
+    /// and C#, where files are named after the contract or type they hold
+    pub fn source_file_name(&self, stem: &str) -> String {
+        match self {
+            Language::Rust | Language::Typescript => format!("{}.{}", stem, self.file_extension()),
+            Language::Solidity | Language::CSharp => format!("{}.{}", pascal_case(stem), self.file_extension()),
+        }
+    }
+
+    /// Returns the conventional test file name for `output_file`:
+    /// `foo_test.rs`, `Foo.t.sol`, `foo.test.ts` or `FooTests.cs`
+    pub fn test_file_name(&self, output_file: &str) -> String {
+        let (stem, extension) = output_file.rsplit_once('.').unwrap_or((output_file, self.file_extension()));
+        match self {
+            Language::Rust => format!("{}_test.{}", stem, extension),
+            Language::Solidity => format!("{}.t.{}", stem, extension),
+            Language::Typescript => format!("{}.test.{}", stem, extension),
+            Language::CSharp => format!("{}Tests.{}", stem, extension),
         }
     }
 
     /// Returns all available languages
     pub fn all() -> &'static [Language] {
-        &[Language::Rust, Language::Solidity, Language::Typescript]
+        &[Language::Rust, Language::Solidity, Language::Typescript, Language::CSharp]
     }
 }
 
-impl Default for Language {
-    fn default() -> Self {
-        Language::Rust
-    }
+/// `user_service` or `user-service` -> `UserService`
+fn pascal_case(name: &str) -> String {
+    name.split(['_', '-'])
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
 }
 
 impl fmt::Display for Language {
@@ -62,6 +118,7 @@ mod tests {
         assert_eq!(Language::Rust.display_name(), "Rust");
         assert_eq!(Language::Solidity.display_name(), "Solidity (Foundry)");
         assert_eq!(Language::Typescript.display_name(), "TypeScript");
+        assert_eq!(Language::CSharp.display_name(), "C# (.NET)");
     }
 
     #[test]
@@ -69,6 +126,25 @@ mod tests {
         assert_eq!(Language::Rust.file_extension(), "rs");
         assert_eq!(Language::Solidity.file_extension(), "sol");
         assert_eq!(Language::Typescript.file_extension(), "ts");
+        assert_eq!(Language::CSharp.file_extension(), "cs");
+    }
+
+    #[test]
+    fn test_language_file_naming() {
+        assert_eq!(Language::Rust.source_file_name("user_service"), "user_service.rs");
+        assert_eq!(Language::Typescript.source_file_name("login"), "login.ts");
+        assert_eq!(Language::Solidity.source_file_name("token_vault"), "TokenVault.sol");
+        assert_eq!(Language::CSharp.source_file_name("order-service"), "OrderService.cs");
+
+        assert_eq!(Language::Rust.test_file_name("calculator.rs"), "calculator_test.rs");
+        assert_eq!(Language::Solidity.test_file_name("Vault.sol"), "Vault.t.sol");
+        assert_eq!(Language::Typescript.test_file_name("Button.tsx"), "Button.test.tsx");
+        assert_eq!(Language::CSharp.test_file_name("Calculator.cs"), "CalculatorTests.cs");
+        assert_eq!(Language::CSharp.test_file_name("Calculator"), "CalculatorTests.cs");
+
+        for language in Language::all() {
+            assert_eq!(language.default_output_dir(), "src/");
+        }
     }
 
     #[test]
@@ -89,6 +165,10 @@ mod tests {
         let ts = Language::Typescript;
         let json = serde_json::to_string(&ts).unwrap();
         assert_eq!(json, "\"typescript\"");
+
+        let cs = Language::CSharp;
+        let json = serde_json::to_string(&cs).unwrap();
+        assert_eq!(json, "\"csharp\"");
     }
 
     #[test]
@@ -101,5 +181,8 @@ mod tests {
 
         let ts: Language = serde_json::from_str("\"typescript\"").unwrap();
         assert_eq!(ts, Language::Typescript);
+
+        let cs: Language = serde_json::from_str("\"csharp\"").unwrap();
+        assert_eq!(cs, Language::CSharp);
     }
 }
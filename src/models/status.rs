@@ -1,8 +1,14 @@
 use chrono::{DateTime, Utc};
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use super::FailureKind;
 
 /// Status of a job in the processing pipeline
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum JobStatus {
     /// Job file exists but hasn't been started
@@ -75,6 +81,7 @@ impl JobStatus {
 
 /// State for partially completed edit jobs
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct PartialEditState {
     /// Edits that were successfully applied
     pub successful_edits: Vec<SuccessfulEdit>,
@@ -106,6 +113,10 @@ impl PartialEditState {
             find_preview: find_preview.into(),
             reason: String::new(),
             suggested_line: None,
+            find: String::new(),
+            replace: String::new(),
+            regex: false,
+            item: None,
         });
     }
 
@@ -123,6 +134,7 @@ impl Default for PartialEditState {
 
 /// Successful edit record
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct SuccessfulEdit {
     pub file_path: String,
     pub find_preview: String,
@@ -130,15 +142,29 @@ pub struct SuccessfulEdit {
 
 /// Failed edit record
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct FailedEdit {
     pub file_path: String,
     pub find_preview: String,
     pub reason: String,
     pub suggested_line: Option<usize>,
+    /// Full FIND text (used by `run --continue`)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub find: String,
+    /// Full REPLACE text (used by `run --continue`)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub replace: String,
+    /// The FIND text is a `FIND_REGEX:` pattern
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub regex: bool,
+    /// `ITEM:` operation (`REPLACE` or `APPEND`); the FIND text is the item path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item: Option<String>,
 }
 
 /// Entry in the job status file
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct JobStatusEntry {
     /// Job identifier
     pub id: String,
@@ -158,6 +184,19 @@ pub struct JobStatusEntry {
     /// Jobs with ran=true are skipped by default on subsequent runs
     #[serde(default)]
     pub ran: bool,
+    /// Why the job failed (or is partial), for triage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<FailureKind>,
+    /// The job file is marked `draft: true`; drafts are never scheduled
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub draft: bool,
+    /// Scores from the latest rubric verification
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rubric: Option<RubricScores>,
+    /// Files this job generated last, relative to the project (or staging)
+    /// root; the job owns them until another job writes them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<PathBuf>,
 }
 
 impl JobStatusEntry {
@@ -172,6 +211,10 @@ impl JobStatusEntry {
             error: None,
             partial_state: None,
             ran: false,
+            failure: None,
+            draft: false,
+            rubric: None,
+            outputs: Vec::new(),
         }
     }
 
@@ -194,6 +237,9 @@ impl JobStatusEntry {
         if status != JobStatus::Fail {
             self.error = None;
         }
+        if status != JobStatus::Fail && status != JobStatus::Partial {
+            self.failure = None;
+        }
     }
 
     /// Set the status to failed with an error message
@@ -216,6 +262,85 @@ impl JobStatusEntry {
     }
 }
 
+/// Verifier scores from 1 (poor) to 5 (excellent) per rubric axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct RubricScores {
+    /// Does what the instructions ask, without bugs
+    pub correctness: u8,
+    /// Covers every requirement
+    pub completeness: u8,
+    /// Idiomatic and consistent with the context
+    pub style: u8,
+    /// Quality of the tests; absent when the job has none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tests: Option<u8>,
+}
+
+impl RubricScores {
+    /// Scored axes with their names
+    pub fn axes(&self) -> Vec<(&'static str, u8)> {
+        let mut axes = vec![
+            ("correctness", self.correctness),
+            ("completeness", self.completeness),
+            ("style", self.style),
+        ];
+        axes.extend(self.tests.map(|score| ("tests", score)));
+        axes
+    }
+
+    /// Mean of the scored axes
+    pub fn average(&self) -> f32 {
+        let axes = self.axes();
+        axes.iter().map(|(_, score)| f32::from(*score)).sum::<f32>() / axes.len() as f32
+    }
+
+    /// Per-axis median of several verifiers' scores (the upper median for an
+    /// even count); `tests` is the median of the votes that scored it
+    pub fn median(votes: &[RubricScores]) -> Option<RubricScores> {
+        fn median(mut scores: Vec<u8>) -> Option<u8> {
+            scores.sort_unstable();
+            scores.get(scores.len() / 2).copied()
+        }
+        Some(RubricScores {
+            correctness: median(votes.iter().map(|v| v.correctness).collect())?,
+            completeness: median(votes.iter().map(|v| v.completeness).collect())?,
+            style: median(votes.iter().map(|v| v.style).collect())?,
+            tests: median(votes.iter().filter_map(|v| v.tests).collect()),
+        })
+    }
+}
+
+impl std::fmt::Display for RubricScores {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let axes: Vec<String> = self.axes().iter().map(|(name, score)| format!("{} {}", name, score)).collect();
+        write!(f, "{:.1}/5 ({})", self.average(), axes.join(", "))
+    }
+}
+
+/// Current `_jobstatus.json` format version. Version 1 files are a bare
+/// array of entries; older formats are migrated when loaded.
+pub const STATUS_FILE_VERSION: u32 = 2;
+
+/// Contents of `jobs/_jobstatus.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct StatusFile {
+    /// Format version, used to migrate files written by older releases
+    pub version: u32,
+    /// Job entries, sorted by ID
+    pub jobs: Vec<JobStatusEntry>,
+}
+
+impl Default for StatusFile {
+    fn default() -> Self {
+        Self {
+            version: STATUS_FILE_VERSION,
+            jobs: Vec::new(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -284,6 +409,21 @@ mod tests {
         assert_eq!(JobStatus::Fail.next_status(false), None);
     }
 
+    #[test]
+    fn test_rubric_scores_median() {
+        let votes = [
+            RubricScores { correctness: 5, completeness: 2, style: 4, tests: None },
+            RubricScores { correctness: 3, completeness: 4, style: 4, tests: Some(2) },
+            RubricScores { correctness: 4, completeness: 5, style: 1, tests: Some(5) },
+        ];
+        assert_eq!(
+            RubricScores::median(&votes),
+            Some(RubricScores { correctness: 4, completeness: 4, style: 4, tests: Some(5) })
+        );
+        assert_eq!(RubricScores::median(&votes[..1]), Some(votes[0]));
+        assert_eq!(RubricScores::median(&[]), None);
+    }
+
     #[test]
     fn test_job_status_entry_new() {
         let entry = JobStatusEntry::new("test_job".to_string());
@@ -385,6 +525,10 @@ mod tests {
                 find_preview: "pub fn".to_string(),
                 reason: "Pattern not found".to_string(),
                 suggested_line: Some(10),
+                find: String::new(),
+                replace: String::new(),
+                regex: false,
+                item: None,
             }],
         };
         let json = serde_json::to_string(&state).unwrap();
@@ -418,6 +562,10 @@ mod tests {
             find_preview: "pub fn".to_string(),
             reason: "Pattern not found".to_string(),
             suggested_line: Some(10),
+            find: "pub fn old()".to_string(),
+            replace: "pub fn new()".to_string(),
+            regex: false,
+            item: None,
         };
         let json = serde_json::to_string(&edit).unwrap();
         assert!(json.contains("\"file_path\""));
@@ -430,5 +578,13 @@ mod tests {
         assert_eq!(parsed.find_preview, "pub fn");
         assert_eq!(parsed.reason, "Pattern not found");
         assert_eq!(parsed.suggested_line, Some(10));
+        assert_eq!(parsed.find, "pub fn old()");
+        assert_eq!(parsed.replace, "pub fn new()");
+
+        // Records written before find/replace were stored still load
+        let legacy: FailedEdit = serde_json::from_str(
+            r#"{"file_path":"a.rs","find_preview":"x","reason":"","suggested_line":null}"#,
+        ).unwrap();
+        assert!(legacy.find.is_empty());
     }
 }
\ No newline at end of file
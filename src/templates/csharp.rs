@@ -0,0 +1,22 @@
+//! C#-specific templates for WorkSplit
+//!
+//! Templates are loaded from external files in the `templates/csharp/` directory.
+
+use super::Templates;
+
+/// Get C#-specific templates (.NET)
+pub fn templates() -> Templates {
+    Templates {
+        create_prompt: include_str!("../../templates/csharp/systemprompt_create.md"),
+        verify_prompt: include_str!("../../templates/csharp/systemprompt_verify.md"),
+        edit_prompt: include_str!("../../templates/csharp/systemprompt_edit.md"),
+        verify_edit_prompt: include_str!("../../templates/csharp/systemprompt_verify_edit.md"),
+        split_prompt: include_str!("../../templates/csharp/systemprompt_split.md"),
+        test_prompt: include_str!("../../templates/csharp/systemprompt_test.md"),
+        fix_prompt: include_str!("../../templates/csharp/systemprompt_fix.md"),
+        manager_instruction: include_str!("../../templates/csharp/manager_instruction.md"),
+        config: include_str!("../../templates/csharp/config.toml"),
+        example_job: include_str!("../../templates/csharp/example_job.md"),
+        tdd_example_job: include_str!("../../templates/csharp/example_tdd_job.md"),
+    }
+}
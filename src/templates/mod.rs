@@ -6,6 +6,7 @@
 //! Templates are stored as external markdown files in the `templates/` directory
 //! and embedded at compile time using `include_str!`.
 
+pub mod csharp;
 pub mod rust;
 pub mod solidity;
 pub mod typescript;
@@ -44,6 +45,7 @@ pub fn get_templates(language: Language) -> Templates {
         Language::Rust => rust::templates(),
         Language::Solidity => solidity::templates(),
         Language::Typescript => typescript::templates(),
+        Language::CSharp => csharp::templates(),
     }
 }
 
@@ -83,4 +85,18 @@ mod tests {
         assert!(templates.split_prompt.contains("index.ts"));
         assert!(templates.config.contains("npm"));
     }
+
+    #[test]
+    fn test_get_csharp_templates() {
+        let templates = get_templates(Language::CSharp);
+        assert!(templates.create_prompt.contains("C#"));
+        assert!(templates.verify_prompt.contains("PASS"));
+        assert!(templates.edit_prompt.contains("FIND"));
+        assert!(templates.verify_edit_prompt.contains("PASS"));
+        assert!(templates.split_prompt.contains("partial class"));
+        assert!(templates.test_prompt.contains("xUnit"));
+        assert!(templates.config.contains("dotnet build"));
+        assert!(templates.config.contains("dotnet test"));
+        assert!(templates.example_job.contains("Greeter.cs"));
+    }
 }
@@ -1,7 +1,11 @@
 //! Common test utilities
 
+use axum::body::Body;
+use axum::http::{StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tempfile::TempDir;
 
 /// Create a test project with jobs folder and required files
@@ -34,7 +38,7 @@ pub fn create_test_project() -> (TempDir, PathBuf) {
 
 /// Create a test job file
 pub fn create_test_job(
-    project_root: &PathBuf,
+    project_root: &Path,
     job_id: &str,
     output_dir: &str,
     output_file: &str,
@@ -59,7 +63,7 @@ output_file: {}
 
 /// Create a test job with context files
 pub fn create_test_job_with_context(
-    project_root: &PathBuf,
+    project_root: &Path,
     job_id: &str,
     context_files: &[&str],
     output_dir: &str,
@@ -92,10 +96,223 @@ output_file: {}
 }
 
 /// Create a context file
-pub fn create_context_file(project_root: &PathBuf, path: &str, content: &str) {
+pub fn create_context_file(project_root: &Path, path: &str, content: &str) {
     let full_path = project_root.join(path);
     if let Some(parent) = full_path.parent() {
         fs::create_dir_all(parent).expect("Failed to create context file parent dir");
     }
     fs::write(full_path, content).expect("Failed to write context file");
 }
+
+/// Start a mock Ollama server that answers `/api/chat` with `responses` in
+/// order (the last one repeats). Returns the base URL.
+pub async fn start_mock_ollama(responses: Vec<&str>) -> String {
+    start_recording_mock_ollama(responses).await.0
+}
+
+/// Like `start_mock_ollama`, but also records the user prompt of every
+/// `/api/chat` request
+pub async fn start_recording_mock_ollama(responses: Vec<&str>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let responses = owned(responses);
+    let prompts = Arc::new(Mutex::new(Vec::new()));
+    let recorded = prompts.clone();
+    let url = serve_mock_ollama(move |path, request| {
+        (path == "/api/chat").then(|| {
+            let mut prompts = prompts.lock().unwrap();
+            prompts.push(chat_messages(request).pop().map(|(_, content)| content).unwrap_or_default());
+            chat_reply(nth(&responses, prompts.len() - 1))
+        })
+    })
+    .await;
+    (url, recorded)
+}
+
+/// Like `start_mock_ollama`, but also records the full message list, as
+/// `(role, content)` pairs, of every `/api/chat` request
+pub async fn start_transcript_mock_ollama(responses: Vec<&str>) -> (String, Arc<Mutex<Vec<Vec<(String, String)>>>>) {
+    let responses = owned(responses);
+    let transcripts = Arc::new(Mutex::new(Vec::new()));
+    let recorded = transcripts.clone();
+    let url = serve_mock_ollama(move |path, request| {
+        (path == "/api/chat").then(|| {
+            let mut transcripts = transcripts.lock().unwrap();
+            transcripts.push(chat_messages(request));
+            chat_reply(nth(&responses, transcripts.len() - 1))
+        })
+    })
+    .await;
+    (url, recorded)
+}
+
+/// Start a mock Ollama server answering `/api/generate`; returns its URL and
+/// the body of every request
+pub async fn start_generate_mock_ollama(responses: Vec<&str>) -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let responses = owned(responses);
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+    let url = serve_mock_ollama(move |path, request| {
+        (path == "/api/generate").then(|| {
+            let mut requests = requests.lock().unwrap();
+            requests.push(request.clone());
+            let content = nth(&responses, requests.len() - 1);
+            format!("{}\n", serde_json::json!({ "response": content, "done": true })).into_response()
+        })
+    })
+    .await;
+    (url, recorded)
+}
+
+/// Start a mock Ollama server whose chat responses send one token and then
+/// hang, as a model stuck on a slow machine does
+pub async fn start_stalling_mock_ollama() -> String {
+    use futures::stream::{self, StreamExt};
+
+    serve_mock_ollama(|path, _| {
+        (path == "/api/chat").then(|| {
+            let token = serde_json::json!({ "message": { "role": "assistant", "content": "fn" }, "done": false });
+            let first = stream::once(async move { Ok::<_, std::io::Error>(format!("{}\n", token)) });
+            Body::from_stream(first.chain(stream::pending())).into_response()
+        })
+    })
+    .await
+}
+
+/// Start a mock Ollama server that answers the first `failures` chat
+/// requests with HTTP 503, then `responses` in order (the last one repeats)
+pub async fn start_flaky_mock_ollama(failures: usize, responses: Vec<&str>) -> String {
+    let responses = owned(responses);
+    let calls = Arc::new(Mutex::new(0usize));
+    serve_mock_ollama(move |path, _| {
+        (path == "/api/chat").then(|| {
+            let mut n = calls.lock().unwrap();
+            *n += 1;
+            if *n <= failures {
+                return (StatusCode::SERVICE_UNAVAILABLE, "model loading").into_response();
+            }
+            chat_reply(nth(&responses, *n - failures - 1))
+        })
+    })
+    .await
+}
+
+/// Start a mock Ollama server whose context window holds `max_chars`
+/// characters: longer chat requests get Ollama's context length error, the
+/// others `responses` in order (the last one repeats). Returns the URL and
+/// the user prompt of every request that fit.
+pub async fn start_context_limited_mock_ollama(max_chars: usize, responses: Vec<&str>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let responses = owned(responses);
+    let prompts = Arc::new(Mutex::new(Vec::new()));
+    let recorded = prompts.clone();
+    let url = serve_mock_ollama(move |path, request| {
+        (path == "/api/chat").then(|| {
+            let mut messages = chat_messages(request);
+            if messages.iter().map(|(_, content)| content.len()).sum::<usize>() > max_chars {
+                let error = serde_json::json!({ "error": "the input length exceeds the context length" });
+                return (StatusCode::INTERNAL_SERVER_ERROR, error.to_string()).into_response();
+            }
+            let mut prompts = prompts.lock().unwrap();
+            prompts.push(messages.pop().map(|(_, content)| content).unwrap_or_default());
+            chat_reply(nth(&responses, prompts.len() - 1))
+        })
+    })
+    .await;
+    (url, recorded)
+}
+
+/// Start a server that records the JSON bodies POSTed to `/hook`
+#[cfg(feature = "notify")]
+pub async fn start_webhook_receiver() -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+    let bodies = Arc::new(Mutex::new(Vec::new()));
+    let recorded = bodies.clone();
+    let url = serve_mock_ollama(move |path, request| {
+        (path == "/hook").then(|| {
+            bodies.lock().unwrap().push(request.clone());
+            "ok".into_response()
+        })
+    })
+    .await;
+    (format!("{}/hook", url), recorded)
+}
+
+/// Serve `handler` on a local port and return the base URL. The handler
+/// gets the path and JSON body of every request and answers it, or returns
+/// `None` to leave it to the defaults: an empty model list on `/api/tags`,
+/// [`mock_embedding`] on `/api/embeddings`, and 404 anywhere else.
+async fn serve_mock_ollama(
+    handler: impl Fn(&str, &serde_json::Value) -> Option<Response> + Clone + Send + Sync + 'static,
+) -> String {
+    let app = axum::Router::new().fallback(move |uri: Uri, body: String| {
+        let handler = handler.clone();
+        async move {
+            let request: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+            if let Some(response) = handler(uri.path(), &request) {
+                return response;
+            }
+            match uri.path() {
+                "/api/tags" => r#"{"models":[]}"#.into_response(),
+                "/api/embeddings" => {
+                    let embedding = mock_embedding(request["prompt"].as_str().unwrap_or_default());
+                    serde_json::json!({ "embedding": embedding }).to_string().into_response()
+                }
+                _ => StatusCode::NOT_FOUND.into_response(),
+            }
+        }
+    });
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+fn owned(responses: Vec<&str>) -> Vec<String> {
+    responses.into_iter().map(String::from).collect()
+}
+
+/// The `n`th of `responses`, the last one repeating
+fn nth(responses: &[String], n: usize) -> &str {
+    &responses[n.min(responses.len() - 1)]
+}
+
+/// The messages of a chat request, as `(role, content)` pairs
+fn chat_messages(request: &serde_json::Value) -> Vec<(String, String)> {
+    request["messages"]
+        .as_array()
+        .map(|messages| {
+            messages
+                .iter()
+                .map(|m| {
+                    let field = |name: &str| m[name].as_str().unwrap_or_default().to_string();
+                    (field("role"), field("content"))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// A complete `/api/chat` answer
+fn chat_reply(content: &str) -> Response {
+    let line = serde_json::json!({ "message": { "role": "assistant", "content": content }, "done": true });
+    format!("{}\n", line).into_response()
+}
+
+/// Bag-of-words embedding for the mock server: every word of four or more
+/// letters is hashed into one of 64 buckets, so texts sharing words are similar
+fn mock_embedding(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0f32; 64];
+    for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| w.len() >= 4) {
+        let hash = word.to_lowercase().bytes().fold(7u32, |h, b| h.wrapping_mul(31).wrapping_add(b as u32));
+        vector[(hash % 64) as usize] += 1.0;
+    }
+    vector
+}
+
+/// Write a worksplit.toml pointing at the given Ollama URL, with warm-up
+/// off so mock servers only see job prompts
+pub fn write_config(project_root: &Path, ollama_url: &str, extra: &str) {
+    let config = format!(
+        "[ollama]\nurl = \"{}\"\nwarmup = false\n\n[behavior]\nstream_output = false\n\n{}\n",
+        ollama_url, extra
+    );
+    fs::write(project_root.join("worksplit.toml"), config).expect("Failed to write config");
+}